@@ -0,0 +1,39 @@
+//! Разбор произвольных байтов не должен приводить к панике. Успешно
+//! разобранный кадр после кодирования разбирается в тот же кадр.
+
+#![no_main]
+
+use bytes::BytesMut;
+use libfuzzer_sys::fuzz_target;
+use mini_redis::Frame;
+use std::io::Cursor;
+
+fuzz_target!(|data: &[u8]| {
+    let mut buf = Cursor::new(data);
+    let checked = Frame::check(&mut buf).is_ok();
+
+    // `parse` должен обрабатывать и непроверенные данные
+    buf.set_position(0);
+    let frame = match Frame::parse(&mut buf) {
+        Ok(frame) => frame,
+        Err(_) => return,
+    };
+
+    if !checked {
+        return;
+    }
+
+    let mut encoded = BytesMut::new();
+    frame.encode(&mut encoded);
+
+    let mut buf = Cursor::new(&encoded[..]);
+    Frame::check(&mut buf).expect("закодированный кадр не прошел проверку");
+    assert_eq!(buf.position() as usize, encoded.len());
+
+    buf.set_position(0);
+    let reparsed = Frame::parse(&mut buf).expect("закодированный кадр не разобран");
+
+    let mut reencoded = BytesMut::new();
+    reparsed.encode(&mut reencoded);
+    assert_eq!(encoded, reencoded);
+});
@@ -0,0 +1,30 @@
+//! Любой кадр после кодирования проверяется и разбирается в тот же кадр,
+//! а любой обрезанный префикс закодированного кадра считается неполным.
+
+#![no_main]
+
+use bytes::BytesMut;
+use libfuzzer_sys::fuzz_target;
+use mini_redis::frame::{Error, Frame};
+use std::io::Cursor;
+
+fuzz_target!(|frame: Frame| {
+    let mut encoded = BytesMut::new();
+    frame.encode(&mut encoded);
+
+    let mut buf = Cursor::new(&encoded[..]);
+    Frame::check(&mut buf).expect("закодированный кадр не прошел проверку");
+    assert_eq!(buf.position() as usize, encoded.len());
+
+    buf.set_position(0);
+    let parsed = Frame::parse(&mut buf).expect("закодированный кадр не разобран");
+
+    let mut reencoded = BytesMut::new();
+    parsed.encode(&mut reencoded);
+    assert_eq!(encoded, reencoded);
+
+    for len in 0..encoded.len() {
+        let mut buf = Cursor::new(&encoded[..len]);
+        assert!(matches!(Frame::check(&mut buf), Err(Error::Incomplete)));
+    }
+});
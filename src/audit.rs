@@ -0,0 +1,250 @@
+//! Журнал аудита изменяющих команд.
+//!
+//! Журнал аудита предназначен для ответа на вопрос "кто и когда изменил
+//! ключ". Каждая успешно выполненная изменяющая команда записывается в файл
+//! отдельной строкой:
+//!
+//! ```text
+//! 1760598000.123 127.0.0.1:52144 SET "hello"
+//! ```
+//!
+//! Строка содержит время выполнения команды (секунды с начала эпохи `Unix`
+//! с миллисекундами), адрес клиента (`-` для соединений без адреса, например,
+//! созданных `server::connect_in_memory`), название команды и ключ. Ключ
+//! экранируется, поэтому пробелы и переводы строк в ключах не нарушают формат.
+//! Значения не записываются.
+//!
+//! При превышении максимального размера файл ротируется: `audit.log`
+//! переименовывается в `audit.log.1`, `audit.log.1` - в `audit.log.2` и т.д.
+//! Самый старый файл удаляется.
+//!
+//! Журнал аудита не является механизмом сохранения данных и не используется
+//! для их восстановления.
+
+use crate::glob;
+
+use std::collections::HashSet;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::error;
+
+/// Максимальный размер файла журнала по умолчанию (10 Мб).
+const DEFAULT_MAX_SIZE: u64 = 10 * 1024 * 1024;
+
+/// Количество хранимых ротированных файлов по умолчанию.
+const DEFAULT_MAX_FILES: usize = 5;
+
+/// Журнал аудита.
+///
+/// Создается с помощью `AuditLog::open` и передается серверу через
+/// `server::Builder::audit_log`. Клоны `AuditLog` пишут в один файл.
+///
+/// Запись выполняется синхронно в задаче соединения, поэтому порядок строк
+/// совпадает с порядком выполнения команд одного соединения.
+///
+/// # Примеры
+///
+/// ```no_run
+/// use mini_redis::audit::AuditLog;
+/// use mini_redis::server::Server;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let audit = AuditLog::open("audit.log")
+///         .unwrap()
+///         .max_size(1024 * 1024)
+///         .keys("user:*");
+///
+///     let server = Server::builder()
+///         .audit_log(audit)
+///         .bind("127.0.0.1:6379")
+///         .await
+///         .unwrap();
+///
+///     server.join().await.unwrap();
+/// }
+/// ```
+#[derive(Clone)]
+pub struct AuditLog {
+    /// Открытый файл журнала.
+    file: Arc<Mutex<LogFile>>,
+
+    /// Записываемые команды в нижнем регистре. Если `None`, записываются
+    /// все изменяющие команды.
+    commands: Option<HashSet<String>>,
+
+    /// Шаблон записываемых ключей в стиле glob.
+    keys: Option<String>,
+}
+
+/// Файл журнала с текущим размером.
+struct LogFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_size: u64,
+    max_files: usize,
+}
+
+impl AuditLog {
+    /// Открывает журнал аудита в файле `path`.
+    ///
+    /// Если файл существует, записи добавляются в его конец.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<AuditLog> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+
+        Ok(AuditLog {
+            file: Arc::new(Mutex::new(LogFile {
+                path,
+                file,
+                size,
+                max_size: DEFAULT_MAX_SIZE,
+                max_files: DEFAULT_MAX_FILES,
+            })),
+            commands: None,
+            keys: None,
+        })
+    }
+
+    /// Устанавливает максимальный размер файла в байтах, при превышении
+    /// которого выполняется ротация.
+    ///
+    /// # Паники
+    ///
+    /// Паникует, если `max` равен `0`.
+    pub fn max_size(self, max: u64) -> AuditLog {
+        assert!(
+            max > 0,
+            "Максимальный размер журнала должен быть больше нуля"
+        );
+        self.file.lock().unwrap().max_size = max;
+        self
+    }
+
+    /// Устанавливает количество хранимых ротированных файлов. Если `max`
+    /// равен `0`, при ротации файл журнала очищается.
+    pub fn max_files(self, max: usize) -> AuditLog {
+        self.file.lock().unwrap().max_files = max;
+        self
+    }
+
+    /// Ограничивает журнал перечисленными командами. Названия команд
+    /// не чувствительны к регистру.
+    pub fn commands<I, S>(mut self, commands: I) -> AuditLog
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let commands = commands
+            .into_iter()
+            .map(|name| name.as_ref().to_lowercase())
+            .collect();
+        self.commands = Some(commands);
+        self
+    }
+
+    /// Ограничивает журнал ключами, соответствующими шаблону в стиле glob
+    /// (как в команде `KEYS`).
+    pub fn keys(mut self, pattern: impl ToString) -> AuditLog {
+        self.keys = Some(pattern.to_string());
+        self
+    }
+
+    /// Записывает выполнение команды `command` с ключом `key` клиентом `peer`.
+    ///
+    /// Ошибки записи не прерывают выполнение команды и только логируются.
+    pub(crate) fn record(&self, peer: Option<SocketAddr>, command: &str, key: &str) {
+        if let Some(commands) = &self.commands {
+            if !commands.contains(command) {
+                return;
+            }
+        }
+
+        if let Some(pattern) = &self.keys {
+            if !glob::matches(pattern.as_bytes(), key.as_bytes()) {
+                return;
+            }
+        }
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let peer = match peer {
+            Some(peer) => peer.to_string(),
+            None => "-".to_string(),
+        };
+        let line = format!(
+            "{}.{:03} {} {} {:?}\n",
+            timestamp.as_secs(),
+            timestamp.subsec_millis(),
+            peer,
+            command.to_uppercase(),
+            key
+        );
+
+        if let Err(err) = self.file.lock().unwrap().write(line.as_bytes()) {
+            error!(cause = %err, "Ошибка записи журнала аудита");
+        }
+    }
+}
+
+impl LogFile {
+    /// Записывает строку, выполняя ротацию при необходимости.
+    fn write(&mut self, line: &[u8]) -> io::Result<()> {
+        if self.size > 0 && self.size + line.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+
+        self.file.write_all(line)?;
+        self.size += line.len() as u64;
+
+        Ok(())
+    }
+
+    /// Сдвигает ротированные файлы и начинает новый файл журнала.
+    fn rotate(&mut self) -> io::Result<()> {
+        if self.max_files > 0 {
+            // Самый старый файл перезаписывается следующим
+            for n in (1..self.max_files).rev() {
+                let from = self.rotated_path(n);
+                if from.exists() {
+                    fs::rename(&from, self.rotated_path(n + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.rotated_path(1))?;
+        }
+
+        self.file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&self.path)?;
+        self.size = 0;
+
+        Ok(())
+    }
+
+    /// Возвращает путь ротированного файла с номером `n`.
+    fn rotated_path(&self, n: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", n));
+        path.into()
+    }
+}
+
+impl fmt::Debug for AuditLog {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("AuditLog")
+            .field("path", &self.file.lock().unwrap().path)
+            .field("commands", &self.commands)
+            .field("keys", &self.keys)
+            .finish()
+    }
+}
@@ -0,0 +1,236 @@
+//! Нагрузочное тестирование `mini-redis`.
+//!
+//! Открывает несколько соединений с сервером и конкурентно отправляет
+//! смесь команд `SET` и `GET`, после чего печатает пропускную способность
+//! и перцентили задержки. Полезно для оценки изменений, влияющих
+//! на производительность сервера.
+//!
+//! Команда для запуска:
+//!
+//!     cargo run --release --bin mini-redis-bench -- -c 50 -n 100000 -P 16
+
+use mini_redis::{Connection, Frame, DEFAULT_PORT};
+
+use bytes::Bytes;
+use clap::Parser;
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+
+#[derive(Parser, Debug)]
+#[clap(
+    name = "mini-redis-bench",
+    version,
+    author,
+    about = "Нагрузочное тестирование сервера Redis"
+)]
+struct Cli {
+    #[clap(name = "hostname", long, default_value = "127.0.0.1")]
+    host: String,
+
+    #[clap(long, default_value_t = DEFAULT_PORT)]
+    port: u16,
+
+    /// Количество одновременных соединений.
+    #[clap(short = 'c', long, default_value_t = 50)]
+    connections: usize,
+
+    /// Общее количество запросов.
+    #[clap(short = 'n', long, default_value_t = 100_000)]
+    requests: usize,
+
+    /// Размер значения `SET` в байтах.
+    #[clap(short = 'd', long, default_value_t = 3)]
+    data_size: usize,
+
+    /// Количество запросов, отправляемых до чтения ответов (глубина конвейера).
+    #[clap(short = 'P', long, default_value_t = 1)]
+    pipeline: usize,
+
+    /// Доля команд `SET` в процентах. Остальные команды - `GET`.
+    #[clap(long, default_value_t = 50, value_parser = clap::value_parser!(u8).range(0..=100))]
+    set_ratio: u8,
+
+    /// Количество различных ключей.
+    #[clap(short = 'r', long, default_value_t = 10_000)]
+    keyspace: u64,
+}
+
+/// Результаты работы одного соединения.
+#[derive(Default)]
+struct Report {
+    /// Задержка каждого выполненного запроса.
+    latencies: Vec<Duration>,
+
+    /// Количество ответов-ошибок.
+    errors: usize,
+}
+
+#[tokio::main]
+async fn main() -> mini_redis::Result<()> {
+    let cli = Cli::parse();
+
+    if cli.connections == 0 || cli.pipeline == 0 {
+        return Err("Количество соединений и глубина конвейера должны быть больше нуля".into());
+    }
+
+    let addr = format!("{}:{}", cli.host, cli.port);
+    let value = Bytes::from(vec![b'x'; cli.data_size]);
+
+    // Устанавливаем все соединения до начала измерений
+    let mut connections = Vec::with_capacity(cli.connections);
+    for _ in 0..cli.connections {
+        let socket = TcpStream::connect(&addr).await?;
+
+        // `Connection` сбрасывает буфер после каждого кадра. При конвейерной отправке
+        // алгоритм Нейгла задерживает маленькие сегменты, что искажает измерения
+        socket.set_nodelay(true)?;
+
+        connections.push(Connection::new(socket));
+    }
+
+    let start = Instant::now();
+
+    // Распределяем запросы между соединениями. Каждое соединение
+    // обрабатывается отдельной задачей
+    let mut tasks = Vec::with_capacity(cli.connections);
+    for (i, connection) in connections.into_iter().enumerate() {
+        let requests =
+            cli.requests / cli.connections + usize::from(i < cli.requests % cli.connections);
+        let worker = Worker {
+            connection,
+            requests,
+            pipeline: cli.pipeline,
+            set_ratio: cli.set_ratio,
+            keyspace: cli.keyspace.max(1),
+            value: value.clone(),
+            // У каждой задачи своя последовательность ключей
+            rng: 0x9E37_79B9_7F4A_7C15 ^ (i as u64 + 1),
+        };
+
+        tasks.push(tokio::spawn(worker.run()));
+    }
+
+    let mut report = Report::default();
+    for task in tasks {
+        let task_report = task.await??;
+        report.latencies.extend(task_report.latencies);
+        report.errors += task_report.errors;
+    }
+
+    let elapsed = start.elapsed();
+    print_report(&cli, &mut report, elapsed);
+
+    Ok(())
+}
+
+/// Состояние задачи, отправляющей запросы через одно соединение.
+struct Worker {
+    connection: Connection,
+    requests: usize,
+    pipeline: usize,
+    set_ratio: u8,
+    keyspace: u64,
+    value: Bytes,
+
+    /// Состояние генератора псевдослучайных чисел (xorshift).
+    /// Криптографическая стойкость здесь не нужна.
+    rng: u64,
+}
+
+impl Worker {
+    async fn run(mut self) -> mini_redis::Result<Report> {
+        let mut report = Report {
+            latencies: Vec::with_capacity(self.requests),
+            errors: 0,
+        };
+
+        let mut remaining = self.requests;
+
+        while remaining > 0 {
+            let batch = remaining.min(self.pipeline);
+            let start = Instant::now();
+
+            // Отправляем пакет запросов, не дожидаясь ответов
+            for _ in 0..batch {
+                let frame = self.next_frame();
+                self.connection.write_frame(&frame).await?;
+            }
+
+            // Читаем ответы. Задержка запроса - время от отправки пакета
+            // до получения ответа на этот запрос
+            for _ in 0..batch {
+                match self.connection.read_frame().await? {
+                    Some(Frame::Error(_)) => report.errors += 1,
+                    Some(_) => {}
+                    None => return Err("Соединение сброшено сервером.".into()),
+                }
+
+                report.latencies.push(start.elapsed());
+            }
+
+            remaining -= batch;
+        }
+
+        Ok(report)
+    }
+
+    /// Создает кадр следующего запроса
+    fn next_frame(&mut self) -> Frame {
+        let rand = self.next_rand();
+        let key = Bytes::from(format!("key:{}", rand % self.keyspace));
+
+        if (rand >> 32) % 100 < u64::from(self.set_ratio) {
+            Frame::Array(vec![
+                Frame::Bulk(Bytes::from_static(b"set")),
+                Frame::Bulk(key),
+                Frame::Bulk(self.value.clone()),
+            ])
+        } else {
+            Frame::Array(vec![
+                Frame::Bulk(Bytes::from_static(b"get")),
+                Frame::Bulk(key),
+            ])
+        }
+    }
+
+    fn next_rand(&mut self) -> u64 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        self.rng
+    }
+}
+
+/// Печатает итоги тестирования
+fn print_report(cli: &Cli, report: &mut Report, elapsed: Duration) {
+    let total = report.latencies.len();
+    report.latencies.sort_unstable();
+
+    println!(
+        "SET/GET ({}% SET): {} запросов, {} соединений, конвейер {}, значение {} байт",
+        cli.set_ratio, total, cli.connections, cli.pipeline, cli.data_size
+    );
+    println!("Время: {:.2} с", elapsed.as_secs_f64());
+    println!(
+        "Пропускная способность: {:.0} запросов/с",
+        total as f64 / elapsed.as_secs_f64()
+    );
+    println!("Ошибки: {}", report.errors);
+
+    if total == 0 {
+        return;
+    }
+
+    let percentile = |p: f64| {
+        let index = ((total as f64 * p).ceil() as usize).clamp(1, total) - 1;
+        report.latencies[index].as_secs_f64() * 1000.0
+    };
+
+    println!(
+        "Задержка (мс): p50 = {:.3}, p95 = {:.3}, p99 = {:.3}, max = {:.3}",
+        percentile(0.50),
+        percentile(0.95),
+        percentile(0.99),
+        percentile(1.0)
+    );
+}
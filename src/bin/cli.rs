@@ -1,11 +1,33 @@
-use mini_redis::{clients::Client, DEFAULT_PORT};
+use mini_redis::clients::{Client, Subscriber};
+use mini_redis::{Frame, DEFAULT_PORT};
 
-use bytes::Bytes;
+use bytes::{Buf, Bytes, BytesMut};
 use clap::{Parser, Subcommand};
+use mini_redis::cmd::{self, CommandInfo};
+#[cfg(feature = "json")]
+use mini_redis::export;
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+use std::borrow::Cow;
 use std::convert::Infallible;
+#[cfg(feature = "json")]
+use std::fs::File;
+use std::io::{BufRead, Cursor};
+#[cfg(feature = "json")]
+use std::io::{BufReader, BufWriter};
 use std::num::ParseIntError;
+use std::path::PathBuf;
 use std::str;
 use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::OwnedReadHalf;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
 
 #[derive(Parser, Debug)]
 #[clap(
@@ -15,17 +37,51 @@ use std::time::Duration;
     about = "Выполнение команд Redis"
 )]
 struct Cli {
+    /// Команда для выполнения. Если команда не указана, запускается
+    /// интерактивный режим.
     #[clap(subcommand)]
-    command: Command,
+    command: Option<Command>,
 
     #[clap(name = "hostname", long, default_value = "127.0.0.1")]
     host: String,
 
     #[clap(long, default_value_t = DEFAULT_PORT)]
     port: u16,
+
+    /// URL сервера вида `redis://[[user]:password@]host[:port]`.
+    /// Значения из URL имеют приоритет над `--hostname`, `--port` и `--pass`.
+    #[clap(short = 'u', long = "uri", value_parser = parse_url)]
+    url: Option<Endpoint>,
+
+    /// Пароль для аутентификации с помощью команды `AUTH`.
+    #[clap(short = 'a', long)]
+    pass: Option<String>,
+
+    /// Количество повторений команды. Отрицательное значение означает
+    /// повторение до прерывания.
+    #[clap(short = 'r', long, default_value_t = 1, allow_negative_numbers = true)]
+    repeat: i64,
+
+    /// Интервал между повторениями команды в секундах (например, `0.5`).
+    #[clap(short = 'i', long, value_parser = duration_from_secs_str)]
+    interval: Option<Duration>,
+
+    /// Читает команды из стандартного ввода и отправляет их конвейером.
+    /// Команды указываются по одной на строку или в формате RESP.
+    #[clap(long)]
+    pipe: bool,
+}
+
+/// Параметры подключения, извлеченные из URL
+#[derive(Debug, Clone)]
+struct Endpoint {
+    host: String,
+    port: u16,
+    user: Option<String>,
+    password: Option<String>,
 }
 
-#[derive(Subcommand, Debug)]
+#[derive(Subcommand, Debug, Clone)]
 enum Command {
     Ping {
         /// Сообщение для пинга.
@@ -50,6 +106,21 @@ enum Command {
         #[clap(value_parser = duration_from_ms_str)]
         expires: Option<Duration>,
     },
+    /// Печатает ключи, соответствующие шаблону, по одному на строку.
+    Keys {
+        /// Шаблон в стиле glob (`*`, `?`, `[...]`).
+        pattern: String,
+    },
+    /// Постранично перебирает ключи и печатает их по одному на строку.
+    Scan {
+        /// Шаблон в стиле glob для фильтрации ключей.
+        #[clap(long = "match")]
+        pattern: Option<String>,
+
+        /// Количество ключей, рассматриваемых сервером за один запрос.
+        #[clap(long)]
+        count: Option<u64>,
+    },
     /// Издатель для отправки сообщения в определенный канал.
     Publish {
         /// Название канала.
@@ -64,6 +135,34 @@ enum Command {
         /// Канал или каналы для подписки.
         channels: Vec<String>,
     },
+    /// Экспортирует все ключи в файл. Время жизни ключей не сохраняется.
+    #[cfg(feature = "json")]
+    Export {
+        /// Формат файла.
+        #[clap(long, value_enum, default_value_t = Format::Json)]
+        format: Format,
+
+        /// Файл для записи. `-` означает стандартный вывод.
+        file: PathBuf,
+    },
+    /// Импортирует ключи из файла, созданного командой `export`.
+    #[cfg(feature = "json")]
+    Import {
+        /// Формат файла.
+        #[clap(long, value_enum, default_value_t = Format::Json)]
+        format: Format,
+
+        /// Файл для чтения. `-` означает стандартный ввод.
+        file: PathBuf,
+    },
+}
+
+/// Формат файла экспорта
+#[cfg(feature = "json")]
+#[derive(clap::ValueEnum, Debug, Clone, Copy)]
+enum Format {
+    /// JSON Lines: по одному объекту JSON на ключ (см. `mini_redis::export`).
+    Json,
 }
 
 /// Входная точка CLI.
@@ -81,14 +180,117 @@ async fn main() -> mini_redis::Result<()> {
     // Разбираем аргументы командной строки
     let cli = Cli::parse();
 
-    // Получаем адрес для подключения
-    let addr = format!("{}:{}", cli.host, cli.port);
+    // Получаем параметры подключения
+    let endpoint = cli.endpoint();
+    let addr = format!("{}:{}", endpoint.host, endpoint.port);
+
+    // Массовая загрузка данных из стандартного ввода
+    if cli.pipe {
+        if cli.command.is_some() {
+            return Err("Режим `--pipe` не может использоваться вместе с командой".into());
+        }
+
+        return pipe(&addr, &endpoint).await;
+    }
 
     // Устанавливаем соединение
-    let mut client = Client::connect(&addr).await?;
+    let mut client = connect(&addr, &endpoint).await?;
 
-    // Обрабатываем команду
-    match cli.command {
+    // Если команда не указана, запускаем интерактивный режим
+    let command = match cli.command {
+        Some(command) => command,
+        None => return repl(client, &addr).await,
+    };
+
+    // Подписка не завершается, поэтому повторять ее не имеет смысла
+    if let Command::Subscribe { channels } = command {
+        if channels.is_empty() {
+            return Err("Должны быть предоставлены каналы".into());
+        }
+        let subscriber = client.subscribe(channels).await?;
+
+        return subscribe_mode(subscriber).await;
+    }
+
+    // Выполняем команду `repeat` раз с интервалом `interval`
+    let mut executed = 0;
+    while cli.repeat < 0 || executed < cli.repeat {
+        if executed > 0 {
+            if let Some(interval) = cli.interval {
+                tokio::time::sleep(interval).await;
+            }
+        }
+
+        run_command(&mut client, command.clone()).await?;
+        executed += 1;
+    }
+
+    Ok(())
+}
+
+impl Cli {
+    /// Объединяет параметры подключения из URL и отдельных флагов
+    fn endpoint(&self) -> Endpoint {
+        match &self.url {
+            Some(url) => Endpoint {
+                password: url.password.clone().or_else(|| self.pass.clone()),
+                ..url.clone()
+            },
+            None => Endpoint {
+                host: self.host.clone(),
+                port: self.port,
+                user: None,
+                password: self.pass.clone(),
+            },
+        }
+    }
+}
+
+/// Устанавливает соединение и, если указан пароль, выполняет аутентификацию.
+///
+/// Возвращает ошибку, если сервер отклонил `AUTH` (в том числе если он не
+/// поддерживает эту команду)
+async fn connect(addr: &str, endpoint: &Endpoint) -> mini_redis::Result<Client> {
+    let mut client = Client::connect(addr).await?;
+
+    if let Some(args) = auth_args(endpoint) {
+        match client.send_frame(command_frame(args)).await {
+            Ok(reply) => check_auth_reply(reply)?,
+            Err(err) => return Err(auth_error(err)),
+        }
+    }
+
+    Ok(client)
+}
+
+/// Проверяет ответ на команду `AUTH`
+fn check_auth_reply(reply: Frame) -> mini_redis::Result<()> {
+    match reply {
+        Frame::Simple(ref status) if status == "OK" => Ok(()),
+        Frame::Error(msg) => Err(auth_error(msg)),
+        reply => Err(auth_error(format!("неожиданный ответ {}", reply))),
+    }
+}
+
+/// Возвращает ошибку аутентификации с сообщением `msg`
+fn auth_error(msg: impl std::fmt::Display) -> mini_redis::Error {
+    format!("Ошибка аутентификации: {}", msg).into()
+}
+
+/// Возвращает аргументы команды `AUTH [user] password`, если указан пароль
+fn auth_args(endpoint: &Endpoint) -> Option<Vec<String>> {
+    let password = endpoint.password.clone()?;
+
+    let mut args = vec!["auth".to_string()];
+    args.extend(endpoint.user.clone());
+    args.push(password);
+
+    Some(args)
+}
+
+/// Выполняет команду и печатает ответ
+async fn run_command(client: &mut Client, command: Command) -> mini_redis::Result<()> {
+    match command {
         Command::Ping { msg } => {
             let value = client.ping(msg).await?;
             if let Ok(string) = str::from_utf8(&value) {
@@ -124,34 +326,665 @@ async fn main() -> mini_redis::Result<()> {
             client.set_expires(&key, value, expires).await?;
             println!("OK");
         }
+        Command::Keys { pattern } => {
+            for key in client.keys(&pattern).await? {
+                println!("{}", key);
+            }
+        }
+        Command::Scan { pattern, count } => {
+            // Следуем за курсором, пока сервер не вернет `0`
+            let mut cursor = 0;
+            loop {
+                let (next, keys) = client.scan_page(cursor, pattern.as_deref(), count).await?;
+
+                for key in keys {
+                    println!("{}", key);
+                }
+
+                if next == 0 {
+                    break;
+                }
+                cursor = next;
+            }
+        }
         Command::Publish { channel, message } => {
             client.publish(&channel, message).await?;
             println!("Publish OK");
         }
-        Command::Subscribe { channels } => {
-            if channels.is_empty() {
-                return Err("Должны быть предоставлены каналы".into());
+        Command::Subscribe { .. } => unreachable!("подписка обрабатывается отдельно"),
+        #[cfg(feature = "json")]
+        Command::Export {
+            format: Format::Json,
+            file,
+        } => {
+            let exported = if file.as_os_str() == "-" {
+                export::export_client(client, std::io::stdout().lock()).await?
+            } else {
+                let file = BufWriter::new(File::create(file)?);
+                export::export_client(client, file).await?
+            };
+
+            // Количество печатается в `stderr`, чтобы не смешиваться с
+            // данными, выводимыми в `stdout`
+            eprintln!("Экспортировано ключей: {}", exported);
+        }
+        #[cfg(feature = "json")]
+        Command::Import {
+            format: Format::Json,
+            file,
+        } => {
+            let imported = if file.as_os_str() == "-" {
+                export::import_client(client, std::io::stdin().lock()).await?
+            } else {
+                let file = BufReader::new(File::open(file)?);
+                export::import_client(client, file).await?
+            };
+
+            println!("Импортировано ключей: {}", imported);
+        }
+    }
+
+    Ok(())
+}
+
+/// Интерактивный режим.
+///
+/// Читает команды из терминала, отправляет их серверу и печатает ответы,
+/// подобно `redis-cli`. Поддерживаются редактирование строки, история команд,
+/// автодополнение названий команд (Tab) и подсказки с аргументами команды.
+/// `help [команда]` печатает описание команд.
+/// Выход выполняется с помощью `quit`, `exit`, Ctrl-C или Ctrl-D.
+async fn repl(mut client: Client, addr: &str) -> mini_redis::Result<()> {
+    let mut editor = Editor::<CliHelper, DefaultHistory>::new()?;
+    editor.set_helper(Some(CliHelper));
+    let prompt = format!("{}> ", addr);
+
+    loop {
+        // `readline` блокирует поток. Это допустимо, поскольку, пока пользователь
+        // вводит команду, среде выполнения нечего делать
+        let line = match editor.readline(&prompt) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted | ReadlineError::Eof) => return Ok(()),
+            Err(err) => return Err(err.into()),
+        };
+
+        let args = match split_args(&line) {
+            Ok(args) => args,
+            Err(err) => {
+                println!("(error) {}", err);
+                continue;
+            }
+        };
+
+        if args.is_empty() {
+            continue;
+        }
+
+        editor.add_history_entry(line.as_str())?;
+
+        match args[0].to_lowercase().as_str() {
+            "quit" | "exit" => return Ok(()),
+            "help" => {
+                print_help(args.get(1).map(String::as_str));
+                continue;
+            }
+            // После подписки соединение переходит в режим pub/sub, поэтому
+            // интерактивный режим сменяется режимом подписки
+            "subscribe" => {
+                let subscriber = client.subscribe(args[1..].to_vec()).await?;
+                return subscribe_mode(subscriber).await;
+            }
+            _ => {}
+        }
+
+        match client.send_frame(command_frame(args)).await {
+            Ok(response) => println!("{}", format_frame(&response, 0)),
+            Err(err) => println!("(error) {}", err),
+        }
+    }
+}
+
+/// Печатает описание команды `name` или список всех команд
+fn print_help(name: Option<&str>) {
+    match name {
+        Some(name) => match cmd::lookup(name) {
+            Some(info) => {
+                println!("\n  {} {}", info.name.to_uppercase(), info.usage);
+                println!("  {}\n", info.summary);
             }
-            let mut subscriber = client.subscribe(channels).await?;
+            None => println!("(error) неизвестная команда '{}'", name),
+        },
+        None => {
+            for info in cmd::COMMAND_TABLE {
+                println!("  {:<12} {}", info.name.to_uppercase(), info.summary);
+            }
+            println!("\nhelp <команда> печатает аргументы команды, quit завершает работу");
+        }
+    }
+}
+
+/// Помощник редактора строки интерактивного режима.
+///
+/// Дополняет названия команд и подсказывает их аргументы на основе
+/// `cmd::COMMAND_TABLE`
+struct CliHelper;
+
+/// Команды интерактивного режима, которые не отправляются серверу
+const REPL_COMMANDS: &[&str] = &["exit", "help", "quit"];
+
+impl CliHelper {
+    /// Возвращает описание команды, если строка состоит только из ее названия
+    /// (возможно, с пробелами в конце)
+    fn command_info(line: &str) -> Option<&'static CommandInfo> {
+        let name = line.trim_end();
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return None;
+        }
+
+        cmd::lookup(name)
+    }
+}
+
+impl Completer for CliHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        // Дополняется только название команды - первое слово строки
+        let prefix = &line[..pos];
+        if prefix.contains(char::is_whitespace) {
+            return Ok((pos, vec![]));
+        }
+
+        // Регистр дополнения соответствует регистру введенного префикса
+        let upper = prefix.chars().any(|c| c.is_ascii_uppercase());
+
+        let candidates = cmd::COMMAND_TABLE
+            .iter()
+            .map(|info| info.name)
+            .chain(REPL_COMMANDS.iter().copied())
+            .filter(|name| name.starts_with(&prefix.to_lowercase()))
+            .map(|name| {
+                if upper {
+                    name.to_uppercase()
+                } else {
+                    name.to_string()
+                }
+            })
+            .collect();
+
+        Ok((0, candidates))
+    }
+}
+
+impl Hinter for CliHelper {
+    type Hint = String;
+
+    fn hint(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> Option<String> {
+        // Подсказка отображается после введенного названия команды
+        if pos < line.len() {
+            return None;
+        }
+
+        let info = CliHelper::command_info(line)?;
+        let separator = if line.ends_with(' ') { "" } else { " " };
+
+        Some(format!("{}{}", separator, info.usage))
+    }
+}
+
+impl Highlighter for CliHelper {
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        // Подсказка выводится бледным цветом
+        Cow::Owned(format!("\x1b[2m{}\x1b[0m", hint))
+    }
+}
+
+impl Validator for CliHelper {}
+
+impl Helper for CliHelper {}
 
-            // Ждем сообщения в канале
-            while let Some(msg) = subscriber.next_message().await? {
-                println!(
+/// Режим подписки.
+///
+/// Печатает сообщения, полученные подписчиком, и одновременно читает команды
+/// из стандартного ввода: `subscribe <канал>...` и `unsubscribe [канал...]`
+/// изменяют подписку, а `quit` завершает работу
+async fn subscribe_mode(mut subscriber: Subscriber) -> mini_redis::Result<()> {
+    println!(
+        "Подписка на {}. Команды: subscribe <канал>..., unsubscribe [канал...], quit",
+        subscriber.get_subscribed().join(", ")
+    );
+
+    let mut lines = stdin_lines();
+
+    loop {
+        tokio::select! {
+            msg = subscriber.next_message() => match msg? {
+                Some(msg) => println!(
                     "Из канала {} получено сообщение {:?}",
                     msg.channel, msg.content
-                );
+                ),
+                // Сервер закрыл соединение
+                None => return Ok(()),
+            },
+            line = lines.recv() => {
+                // Стандартный ввод закрыт, продолжаем только печатать сообщения
+                let line = match line {
+                    Some(line) => line,
+                    None => return print_messages(subscriber).await,
+                };
+
+                let args = match split_args(&line) {
+                    Ok(args) => args,
+                    Err(err) => {
+                        println!("(error) {}", err);
+                        continue;
+                    }
+                };
+
+                let (command, channels) = match args.split_first() {
+                    Some((command, channels)) => (command.to_lowercase(), channels),
+                    None => continue,
+                };
+
+                match command.as_str() {
+                    "quit" | "exit" => return Ok(()),
+                    "subscribe" if channels.is_empty() => {
+                        println!("(error) должны быть предоставлены каналы")
+                    }
+                    "subscribe" => subscriber.subscribe(channels).await?,
+                    "unsubscribe" => {
+                        // Отписка от канала, на который нет подписки,
+                        // приводит к ошибке клиента
+                        if let Some(channel) = channels
+                            .iter()
+                            .find(|channel| !subscriber.get_subscribed().contains(channel))
+                        {
+                            println!("(error) нет подписки на канал {}", channel);
+                            continue;
+                        }
+
+                        subscriber.unsubscribe(channels).await?;
+                    }
+                    _ => {
+                        println!("(error) в режиме подписки доступны только subscribe, unsubscribe и quit");
+                        continue;
+                    }
+                }
+
+                println!("Подписка на: {}", subscriber.get_subscribed().join(", "));
             }
         }
     }
+}
+
+/// Печатает сообщения, полученные подписчиком
+async fn print_messages(mut subscriber: Subscriber) -> mini_redis::Result<()> {
+    while let Some(msg) = subscriber.next_message().await? {
+        println!(
+            "Из канала {} получено сообщение {:?}",
+            msg.channel, msg.content
+        );
+    }
 
     Ok(())
 }
 
+/// Читает строки стандартного ввода в отдельном потоке.
+///
+/// `tokio::io::stdin` выполняет блокирующее чтение, которое невозможно
+/// отменить, поэтому среда выполнения не может завершиться, пока ожидает
+/// ввода. Отдельный поток не мешает завершению процесса
+fn stdin_lines() -> mpsc::UnboundedReceiver<String> {
+    let (tx, rx) = mpsc::unbounded_channel();
+
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lock().lines() {
+            let Ok(line) = line else { return };
+
+            if tx.send(line).is_err() {
+                return;
+            }
+        }
+    });
+
+    rx
+}
+
+/// Режим массовой загрузки, аналог `redis-cli --pipe`.
+///
+/// Читает команды из стандартного ввода и отправляет их серверу, не дожидаясь
+/// ответов. Ответы читаются одновременно с отправкой, поэтому большой объем
+/// входных данных не приводит к взаимной блокировке при заполнении буферов
+/// сокета. По завершении печатается количество ответов и ошибок.
+///
+/// Если указан пароль, команда `AUTH` выполняется до отправки остальных
+/// команд: при ее ошибке команды не отправляются.
+async fn pipe(addr: &str, endpoint: &Endpoint) -> mini_redis::Result<()> {
+    let mut input = vec![];
+    tokio::io::stdin().read_to_end(&mut input).await?;
+
+    let (payload, expected) = encode_pipe_input(&input)?;
+
+    let socket = TcpStream::connect(addr).await?;
+    let (mut rd, mut wr) = socket.into_split();
+    let mut buffer = BytesMut::with_capacity(4 * 1024);
+
+    if let Some(args) = auth_args(endpoint) {
+        wr.write_all(&encode_command(&args)).await?;
+        check_auth_reply(read_reply(&mut rd, &mut buffer).await?)?;
+    }
+
+    let write = async move {
+        wr.write_all(&payload).await?;
+        // Сообщаем серверу, что команд больше не будет
+        wr.shutdown().await?;
+        Ok::<_, mini_redis::Error>(())
+    };
+
+    let read = async move {
+        let mut replies = 0;
+        let mut errors = 0;
+
+        while replies < expected {
+            if let Frame::Error(msg) = read_reply(&mut rd, &mut buffer).await? {
+                errors += 1;
+                eprintln!("(error) {}", msg);
+            }
+            replies += 1;
+        }
+
+        Ok::<_, mini_redis::Error>((replies, errors))
+    };
+
+    let ((), (replies, errors)) = tokio::try_join!(write, read)?;
+    println!("Ответов: {}, ошибок: {}", replies, errors);
+
+    Ok(())
+}
+
+/// Преобразует входные данные режима `--pipe` в байты для отправки.
+///
+/// Если данные начинаются с `*`, они считаются уже закодированными в RESP
+/// и отправляются как есть. Иначе каждая непустая строка разбивается на
+/// аргументы с помощью `split_args` и кодируется как массив bulk-строк.
+///
+/// Возвращает данные для отправки и количество команд в них
+fn encode_pipe_input(input: &[u8]) -> mini_redis::Result<(Vec<u8>, usize)> {
+    if input.first() == Some(&b'*') {
+        // Считаем команды, проверяя целостность каждого кадра
+        let mut buf = Cursor::new(input);
+        let mut count = 0;
+
+        while buf.has_remaining() {
+            Frame::check(&mut buf)?;
+            count += 1;
+        }
+
+        return Ok((input.to_vec(), count));
+    }
+
+    let input = str::from_utf8(input)?;
+    let mut payload = vec![];
+    let mut count = 0;
+
+    for (n, line) in input.lines().enumerate() {
+        let args = split_args(line).map_err(|err| format!("строка {}: {}", n + 1, err))?;
+
+        if args.is_empty() {
+            continue;
+        }
+
+        payload.append(&mut encode_command(&args));
+        count += 1;
+    }
+
+    Ok((payload, count))
+}
+
+/// Создает кадр команды из ее аргументов
+fn command_frame(args: Vec<String>) -> Frame {
+    Frame::Array(
+        args.into_iter()
+            .map(|arg| Frame::Bulk(Bytes::from(arg)))
+            .collect(),
+    )
+}
+
+/// Кодирует аргументы команды как массив bulk-строк RESP
+fn encode_command(args: &[String]) -> Vec<u8> {
+    let mut payload = format!("*{}\r\n", args.len()).into_bytes();
+
+    for arg in args {
+        payload.extend_from_slice(format!("${}\r\n", arg.len()).as_bytes());
+        payload.extend_from_slice(arg.as_bytes());
+        payload.extend_from_slice(b"\r\n");
+    }
+
+    payload
+}
+
+/// Читает следующий ответ сервера в режиме `--pipe`, дополняя буфер данными
+/// из `rd`
+async fn read_reply(rd: &mut OwnedReadHalf, buffer: &mut BytesMut) -> mini_redis::Result<Frame> {
+    loop {
+        if let Some(frame) = parse_reply(buffer)? {
+            return Ok(frame);
+        }
+
+        if 0 == rd.read_buf(buffer).await? {
+            return Err("Соединение закрыто сервером до получения всех ответов".into());
+        }
+    }
+}
+
+/// Пытается разобрать кадр ответа из буфера.
+/// Возвращает `Ok(None)`, если данных для полного кадра недостаточно
+fn parse_reply(buffer: &mut BytesMut) -> mini_redis::Result<Option<Frame>> {
+    let mut buf = Cursor::new(&buffer[..]);
+
+    match Frame::check(&mut buf) {
+        Ok(_) => {
+            let len = buf.position() as usize;
+            buf.set_position(0);
+
+            let frame = Frame::parse(&mut buf)?;
+            buffer.advance(len);
+
+            Ok(Some(frame))
+        }
+        Err(mini_redis::frame::Error::Incomplete) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Разбивает строку на аргументы команды.
+///
+/// Аргументы разделяются пробелами. Аргументы, содержащие пробелы, могут быть
+/// заключены в двойные или одинарные кавычки. Внутри двойных кавычек
+/// поддерживаются экранированные последовательности `\"`, `\\`, `\n`, `\r` и `\t`.
+fn split_args(line: &str) -> Result<Vec<String>, &'static str> {
+    let mut args = vec![];
+    let mut chars = line.chars().peekable();
+
+    loop {
+        // Пропускаем пробелы между аргументами
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+
+        let first = match chars.peek() {
+            Some(&c) => c,
+            None => return Ok(args),
+        };
+
+        let mut arg = String::new();
+
+        if first == '"' || first == '\'' {
+            chars.next();
+
+            loop {
+                match chars.next() {
+                    Some(c) if c == first => break,
+                    Some('\\') if first == '"' => match chars.next() {
+                        Some('n') => arg.push('\n'),
+                        Some('r') => arg.push('\r'),
+                        Some('t') => arg.push('\t'),
+                        Some(c) => arg.push(c),
+                        None => return Err("незакрытые кавычки"),
+                    },
+                    Some(c) => arg.push(c),
+                    None => return Err("незакрытые кавычки"),
+                }
+            }
+
+            // После закрывающей кавычки должен следовать пробел или конец строки
+            if chars.peek().is_some_and(|c| !c.is_whitespace()) {
+                return Err("после закрывающей кавычки ожидается пробел");
+            }
+        } else {
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+
+                arg.push(c);
+                chars.next();
+            }
+        }
+
+        args.push(arg);
+    }
+}
+
+/// Форматирует кадр ответа для печати в стиле `redis-cli`.
+///
+/// `indent` - отступ вложенного массива.
+fn format_frame(frame: &Frame, indent: usize) -> String {
+    match frame {
+        Frame::Simple(value) => value.to_string(),
+        Frame::Error(msg) => format!("(error) {}", msg),
+        Frame::Integer(num) => format!("(integer) {}", num),
+        Frame::Bulk(value) => match str::from_utf8(value) {
+            Ok(string) => format!("{:?}", string),
+            Err(_) => format!("{:?}", value),
+        },
+        Frame::Null => "(nil)".to_string(),
+        Frame::Array(parts) if parts.is_empty() => "(empty array)".to_string(),
+        Frame::Array(parts) => {
+            // Ширина номера элемента, чтобы вложенные элементы были выровнены
+            let width = parts.len().to_string().len();
+
+            parts
+                .iter()
+                .enumerate()
+                .map(|(i, part)| {
+                    let prefix = format!("{:>width$}) ", i + 1, width = width);
+                    let nested = format_frame(part, indent + prefix.len());
+
+                    if i == 0 {
+                        format!("{}{}", prefix, nested)
+                    } else {
+                        format!("{}{}{}", " ".repeat(indent), prefix, nested)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+    }
+}
+
+/// Разбирает URL вида `redis://[[user]:password@]host[:port][/db]`.
+///
+/// Схемы `rediss://` (TLS) и `unix://` (сокет Unix) отклоняются, поскольку
+/// клиент поддерживает только TCP
+fn parse_url(src: &str) -> Result<Endpoint, String> {
+    let (scheme, rest) = src
+        .split_once("://")
+        .ok_or("ожидается URL вида redis://[[user]:password@]host[:port]")?;
+
+    match scheme {
+        "redis" => {}
+        "rediss" | "unix" => {
+            return Err(format!(
+                "схема URL `{}` не поддерживается клиентом `mini-redis`",
+                scheme
+            ))
+        }
+        _ => return Err(format!("неизвестная схема URL `{}`", scheme)),
+    }
+
+    // Учетные данные отделяются от адреса последним символом `@`
+    let (credentials, rest) = match rest.rsplit_once('@') {
+        Some((credentials, rest)) => (Some(credentials), rest),
+        None => (None, rest),
+    };
+
+    let (user, password) = match credentials {
+        Some(credentials) => match credentials.split_once(':') {
+            Some((user, password)) => (
+                Some(user.to_string()).filter(|user| !user.is_empty()),
+                Some(password.to_string()),
+            ),
+            None => (None, Some(credentials.to_string())),
+        },
+        None => (None, None),
+    };
+
+    let mut endpoint = Endpoint {
+        host: "127.0.0.1".to_string(),
+        port: DEFAULT_PORT,
+        user,
+        password,
+    };
+
+    // `mini-redis` не поддерживает несколько баз данных
+    let (address, db) = rest.split_once('/').unwrap_or((rest, ""));
+    if !db.is_empty() && db != "0" {
+        return Err("поддерживается только база данных 0".to_string());
+    }
+
+    // Адрес IPv6 заключается в квадратные скобки: `[::1]:6379`
+    let (host, port) = match address.strip_prefix('[') {
+        Some(address) => {
+            let (host, port) = address
+                .split_once(']')
+                .ok_or("незакрытая скобка в адресе")?;
+            (host, port.strip_prefix(':'))
+        }
+        None => match address.split_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (address, None),
+        },
+    };
+
+    if !host.is_empty() {
+        endpoint.host = host.to_string();
+    }
+
+    if let Some(port) = port {
+        endpoint.port = port
+            .parse()
+            .map_err(|_| format!("невалидный порт `{}`", port))?;
+    }
+
+    Ok(endpoint)
+}
+
 fn duration_from_ms_str(src: &str) -> Result<Duration, ParseIntError> {
     let ms = src.parse::<u64>()?;
     Ok(Duration::from_millis(ms))
 }
 
+fn duration_from_secs_str(src: &str) -> Result<Duration, String> {
+    let secs = src.parse::<f64>().map_err(|err| err.to_string())?;
+    Duration::try_from_secs_f64(secs).map_err(|err| err.to_string())
+}
+
 fn bytes_from_str(src: &str) -> Result<Bytes, Infallible> {
     Ok(Bytes::from(src.to_string()))
 }
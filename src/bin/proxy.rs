@@ -0,0 +1,141 @@
+//! Прокси `mini-redis` для записи и воспроизведения сессий и шардирования.
+//!
+//! Этот файл представляет собой входную точку прокси.
+//! Здесь выполняется разбор командной строки и передача аргументов в
+//! `mini_redis::proxy`.
+//!
+//! Запись сессии клиентов сервера на порту 6379:
+//!
+//!     mini-redis-proxy record --port 6380 --upstream 127.0.0.1:6379 -o session.resp
+//!
+//! Воспроизведение записанной сессии:
+//!
+//!     mini-redis-proxy replay -i session.resp --port 6379
+//!
+//! Распределение ключей между двумя серверами:
+//!
+//!     mini-redis-proxy shard --port 6380 -b 127.0.0.1:6379 -b 127.0.0.1:6381
+
+use mini_redis::{proxy, DEFAULT_PORT};
+
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+use tokio::fs::File;
+use tokio::net::{lookup_host, TcpListener};
+use tokio::signal;
+
+#[derive(Parser, Debug)]
+#[clap(
+    name = "mini-redis-proxy",
+    version,
+    author,
+    about = "Запись и воспроизведение сессий Redis, шардирование ключей"
+)]
+struct Cli {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Проксирует соединения на сервер и записывает все кадры в файл.
+    Record {
+        /// Порт, на котором прокси принимает соединения.
+        #[clap(long, default_value_t = 6380)]
+        port: u16,
+
+        /// Адрес сервера.
+        #[clap(long, default_value_t = format!("127.0.0.1:{}", DEFAULT_PORT))]
+        upstream: String,
+
+        /// Файл для записи.
+        #[clap(short = 'o', long)]
+        output: PathBuf,
+    },
+    /// Воспроизводит записанную сессию на сервере.
+    Replay {
+        #[clap(name = "hostname", long, default_value = "127.0.0.1")]
+        host: String,
+
+        #[clap(long, default_value_t = DEFAULT_PORT)]
+        port: u16,
+
+        /// Файл с записью.
+        #[clap(short = 'i', long)]
+        input: PathBuf,
+
+        /// Отправлять запросы без задержек, не соблюдая записанные интервалы.
+        #[clap(long)]
+        no_timing: bool,
+    },
+    /// Распределяет ключи между серверами с помощью согласованного хеширования.
+    Shard {
+        /// Порт, на котором прокси принимает соединения.
+        #[clap(long, default_value_t = 6380)]
+        port: u16,
+
+        /// Адрес сервера. Указывается для каждого сервера.
+        #[clap(short = 'b', long = "backend", required = true)]
+        backends: Vec<String>,
+    },
+}
+
+#[tokio::main]
+async fn main() -> mini_redis::Result<()> {
+    // См. https://docs.rs/tracing
+    tracing_subscriber::fmt::try_init()?;
+
+    match Cli::parse().command {
+        Command::Record {
+            port,
+            upstream,
+            output,
+        } => {
+            let upstream = lookup_host(&upstream)
+                .await?
+                .next()
+                .ok_or("Не удалось определить адрес сервера")?;
+            let listener = TcpListener::bind(&format!("127.0.0.1:{}", port)).await?;
+            let out = File::create(&output).await?;
+
+            proxy::record(listener, upstream, out, signal::ctrl_c()).await?;
+        }
+        Command::Replay {
+            host,
+            port,
+            input,
+            no_timing,
+        } => {
+            let records = proxy::read_records(File::open(&input).await?).await?;
+            let report = proxy::replay((host.as_str(), port), records, !no_timing).await?;
+
+            println!(
+                "соединений: {}, запросов: {}, совпало ответов: {}, не совпало: {}, время: {:.2?}",
+                report.connections,
+                report.requests,
+                report.matched,
+                report.mismatched,
+                report.elapsed
+            );
+
+            if report.mismatched > 0 {
+                return Err("Ответы сервера отличаются от записанных".into());
+            }
+        }
+        Command::Shard { port, backends } => {
+            let mut addrs = vec![];
+            for backend in &backends {
+                let addr = lookup_host(backend)
+                    .await?
+                    .next()
+                    .ok_or("Не удалось определить адрес сервера")?;
+                addrs.push(addr);
+            }
+            let listener = TcpListener::bind(&format!("127.0.0.1:{}", port)).await?;
+
+            proxy::shard(listener, addrs, signal::ctrl_c()).await?;
+        }
+    }
+
+    Ok(())
+}
@@ -6,36 +6,390 @@
 //!
 //! Для разбора командной строки используется крейт `clap`.
 
-use mini_redis::{server, DEFAULT_PORT};
+use mini_redis::audit::AuditLog;
+use mini_redis::log_filter::LogFilter;
+use mini_redis::server::{self, Accept};
+use mini_redis::{DbDropGuard, DEFAULT_PORT};
 
+use clap::builder::BoolishValueParser;
+use clap::ArgAction;
 use clap::Parser;
+use std::fs::{self, File, OpenOptions};
+use std::net::IpAddr;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::net::TcpListener;
+use tokio::runtime::Runtime;
 use tokio::signal;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
 
-#[tokio::main]
-pub async fn main() -> mini_redis::Result<()> {
-    set_up_logging()?;
+#[cfg(unix)]
+use daemonize::Daemonize;
 
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+use mini_redis::uring::UringListener;
+#[cfg(feature = "websocket")]
+use mini_redis::websocket::WsListener;
+
+#[cfg(feature = "otel")]
+// Для установки `XrayPropagator` и закрытия экспортеров
+use opentelemetry::global;
+#[cfg(feature = "otel")]
+use opentelemetry::sdk::{metrics::MeterProvider, trace as sdktrace};
+#[cfg(feature = "otel")]
+use opentelemetry_aws::trace::{XrayIdGenerator, XrayPropagator};
+#[cfg(feature = "otel")]
+use std::sync::OnceLock;
+#[cfg(feature = "otel")]
+// Трейты `Ext` позволяют `Registry` принимать типы `OpenTelemetry`
+// (например, `OpenTelemetryLayer`)
+use tracing_subscriber::{fmt, layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter};
+
+/// С флагом `jemalloc` сервер использует аллокатор `jemalloc`. Статистика
+/// аллокатора возвращается командой `MEMORY STATS`.
+#[cfg(feature = "jemalloc")]
+#[global_allocator]
+static GLOBAL: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
+
+pub fn main() -> mini_redis::Result<()> {
     let cli = Cli::parse();
+
+    // Адрес проверяется до отделения процесса, чтобы ошибка была видна
+    // в терминале
+    check_protected_mode(&cli)?;
+
+    // Процесс отделяется до запуска среды выполнения `Tokio`: после `fork`
+    // в дочернем процессе остается только вызывающий поток
+    if cli.daemonize {
+        daemonize(&cli)?;
+    } else if let Some(path) = &cli.pidfile {
+        fs::write(path, format!("{}\n", process::id()))?;
+    }
+
+    let res = Runtime::new()?.block_on(run(&cli));
+
+    if let Some(path) = &cli.pidfile {
+        let _ = fs::remove_file(path);
+    }
+
+    res
+}
+
+/// Запускает сервер и ждет его завершения по сигналу `SIGINT`.
+async fn run(cli: &Cli) -> mini_redis::Result<()> {
+    let log_filter = set_up_logging(cli.logfile.as_deref())?;
+
     let port = cli.port.unwrap_or(DEFAULT_PORT);
 
+    // БД создается здесь для настройки очистки истекших ключей. Фоновая
+    // задача очистки закрывается при уничтожении `db_holder`
+    let db_holder = DbDropGuard::new();
+    let db = db_holder.db();
+    if let Some(size) = cli.active_expire_batch {
+        db.set_purge_batch_size(size.get());
+    }
+    if let Some(ms) = cli.active_expire_min_interval {
+        db.set_purge_min_interval(Duration::from_millis(ms));
+    }
+    if cli.prefix_index {
+        db.set_prefix_index(true);
+    }
+
+    #[cfg(feature = "http")]
+    if let Some(port) = cli.http_port {
+        let listener = TcpListener::bind((cli.bind, port)).await?;
+        let db = db.clone();
+
+        tokio::spawn(async move {
+            if let Err(err) = mini_redis::http::run(listener, db, signal::ctrl_c()).await {
+                tracing::error!(cause = %err, "Ошибка HTTP-шлюза");
+            }
+        });
+    }
+
+    let mut builder = server::Builder::new().db(db).log_filter(log_filter);
+    if let Some(path) = &cli.audit_log {
+        builder = builder.audit_log(AuditLog::open(path)?);
+    }
+
+    if let Some(ms) = cli.command_timeout {
+        builder = builder.command_timeout(Duration::from_millis(ms));
+    }
+
+    builder = builder.strict_framing(cli.strict_framing);
+
     // Привязываем обработчик TCP
-    let listener = TcpListener::bind(&format!("127.0.0.1:{}", port)).await?;
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    if cli.io_uring {
+        let listener = UringListener::bind((cli.bind, port)).await?;
+        serve(builder, listener, cli).await?;
+        shut_down_telemetry();
+        return Ok(());
+    }
+
+    let listener = TcpListener::bind((cli.bind, port)).await?;
+    serve(builder, listener, cli).await?;
+
+    shut_down_telemetry();
+
+    Ok(())
+}
 
-    server::run(listener, signal::ctrl_c()).await;
+/// Запускает сервер на `listener` и, если указан порт WebSocket, на
+/// обработчике WebSocket, и ждет их завершения по сигналу `SIGINT`.
+async fn serve(
+    builder: server::Builder,
+    listener: impl Accept,
+    cli: &Cli,
+) -> mini_redis::Result<()> {
+    match websocket_listener(cli).await? {
+        // Серверы используют общую БД, но ограничение количества соединений
+        // применяется к каждому отдельно
+        Some(ws) => {
+            tokio::join!(
+                builder.clone().run(listener, signal::ctrl_c()),
+                builder.run(ws, signal::ctrl_c()),
+            );
+        }
+        None => builder.run(listener, signal::ctrl_c()).await,
+    }
 
     Ok(())
 }
 
+/// Привязывает обработчик соединений WebSocket, если указан его порт.
+#[cfg(feature = "websocket")]
+async fn websocket_listener(cli: &Cli) -> mini_redis::Result<Option<WsListener>> {
+    match cli.websocket_port {
+        Some(port) => Ok(Some(WsListener::bind((cli.bind, port)).await?)),
+        None => Ok(None),
+    }
+}
+
+#[cfg(not(feature = "websocket"))]
+async fn websocket_listener(_cli: &Cli) -> mini_redis::Result<Option<TcpListener>> {
+    Ok(None)
+}
+
 #[derive(Parser, Debug)]
 #[clap(name = "mini-redis-server", version, author, about = "A Redis server")]
 struct Cli {
     #[clap(long)]
     port: Option<u16>,
+
+    /// Адрес, на котором сервер принимает соединения, например, `0.0.0.0`.
+    #[clap(long, default_value = "127.0.0.1")]
+    bind: IpAddr,
+
+    /// Защищенный режим: сервер принимает соединения только на локальном
+    /// адресе (`yes` или `no`).
+    #[clap(
+        long,
+        default_value = "yes",
+        action = ArgAction::Set,
+        value_parser = BoolishValueParser::new()
+    )]
+    protected_mode: bool,
+
+    /// Файл журнала аудита изменяющих команд.
+    #[clap(long)]
+    audit_log: Option<PathBuf>,
+
+    /// Максимальное время выполнения одной команды в миллисекундах.
+    #[clap(long, value_parser = clap::value_parser!(u64).range(1..))]
+    command_timeout: Option<u64>,
+
+    /// Максимальное количество истекших ключей, удаляемых за один проход
+    /// фоновой очистки.
+    #[clap(long)]
+    active_expire_batch: Option<NonZeroUsize>,
+
+    /// Минимальный интервал между проходами фоновой очистки в миллисекундах.
+    #[clap(long)]
+    active_expire_min_interval: Option<u64>,
+
+    /// Поддерживать индекс префиксов ключей для `KEYS` и `SCAN` по префиксу.
+    #[clap(long)]
+    prefix_index: bool,
+
+    /// Строго проверять формат кадров, получаемых от клиентов.
+    #[clap(long)]
+    strict_framing: bool,
+
+    /// Принимать соединения через `io_uring` (только Linux).
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    #[clap(long)]
+    io_uring: bool,
+
+    /// Порт HTTP-шлюза.
+    #[cfg(feature = "http")]
+    #[clap(long)]
+    http_port: Option<u16>,
+
+    /// Порт, на котором сервер принимает соединения WebSocket.
+    #[cfg(feature = "websocket")]
+    #[clap(long)]
+    websocket_port: Option<u16>,
+
+    /// Запустить сервер в фоновом режиме (только Unix).
+    #[clap(long)]
+    daemonize: bool,
+
+    /// Файл, в который записывается идентификатор процесса сервера.
+    #[clap(long)]
+    pidfile: Option<PathBuf>,
+
+    /// Файл логов. По умолчанию логи выводятся в стандартный вывод.
+    #[clap(long)]
+    logfile: Option<PathBuf>,
+}
+
+/// Проверяет, что адрес `--bind` разрешен в защищенном режиме.
+///
+/// `mini-redis` не поддерживает аутентификацию, поэтому по умолчанию сервер
+/// недоступен с других хостов. Для привязки к внешнему адресу защищенный
+/// режим должен быть отключен явно.
+fn check_protected_mode(cli: &Cli) -> mini_redis::Result<()> {
+    if cli.protected_mode && !cli.bind.is_loopback() {
+        return Err(format!(
+            "Адрес `{}` недоступен в защищенном режиме. Для приема соединений с других хостов используйте `--protected-mode no`",
+            cli.bind
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Отделяет процесс сервера от терминала.
+///
+/// Родительский процесс завершается, а сервер продолжает работу в дочернем
+/// процессе. Идентификатор дочернего процесса записывается в файл `--pidfile`.
+#[cfg(unix)]
+fn daemonize(cli: &Cli) -> mini_redis::Result<()> {
+    // По умолчанию демон переходит в корневую директорию. Текущая директория
+    // сохраняется, чтобы относительные пути в аргументах оставались верными
+    let mut daemon = Daemonize::new().working_directory(std::env::current_dir()?);
+
+    if let Some(path) = &cli.pidfile {
+        daemon = daemon.pid_file(path);
+    }
+
+    // Сообщения о панике выводятся в stderr, поэтому также записываются
+    // в файл логов
+    if let Some(path) = &cli.logfile {
+        daemon = daemon.stderr(open_logfile(path)?);
+    }
+
+    daemon.start()?;
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn daemonize(_cli: &Cli) -> mini_redis::Result<()> {
+    Err("`--daemonize` поддерживается только в Unix".into())
+}
+
+/// Открывает файл логов для добавления записей.
+fn open_logfile(path: &Path) -> std::io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// Возвращает получателя логов: файл `logfile` или стандартный вывод.
+fn log_writer(logfile: Option<&Path>) -> mini_redis::Result<BoxMakeWriter> {
+    let writer = match logfile {
+        Some(path) => BoxMakeWriter::new(Arc::new(open_logfile(path)?)),
+        None => BoxMakeWriter::new(std::io::stdout),
+    };
+
+    Ok(writer)
 }
 
 #[cfg(not(feature = "otel"))]
-fn set_up_logging() -> mini_redis::Result<()> {
+fn set_up_logging(logfile: Option<&Path>) -> mini_redis::Result<LogFilter> {
     // См. https://docs.rs/tracing
-    tracing_subscriber::fmt::try_init()
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .with_writer(log_writer(logfile)?)
+        // Управляющие последовательности цветов не нужны в файле
+        .with_ansi(logfile.is_none())
+        // Фильтр изменяется командой `CONFIG SET loglevel`
+        .with_filter_reloading();
+    let filter = LogFilter::new(builder.reload_handle());
+
+    builder.try_init()?;
+
+    Ok(filter)
+}
+
+#[cfg(not(feature = "otel"))]
+fn shut_down_telemetry() {}
+
+/// `MeterProvider`, созданный в `set_up_logging`. Сохраняется для отправки
+/// накопленных метрик при завершении сервера.
+#[cfg(feature = "otel")]
+static METER_PROVIDER: OnceLock<MeterProvider> = OnceLock::new();
+
+#[cfg(feature = "otel")]
+fn set_up_logging(logfile: Option<&Path>) -> mini_redis::Result<LogFilter> {
+    // Устанавливаем глобальный пропагатор X-Ray. Он необходим для передачи
+    // заголовка `x-amzn-trace-id` между сервисами в рамках одной трассировки.
+    // См. https://github.com/open-telemetry/opentelemetry-rust/blob/main/examples/aws-xray/src/server.rs
+    global::set_text_map_propagator(XrayPropagator::default());
+
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(opentelemetry_otlp::new_exporter().tonic())
+        .with_trace_config(
+            sdktrace::config()
+                .with_sampler(sdktrace::Sampler::AlwaysOn)
+                // Идентификаторы трассировок в формате, совместимом с X-Ray
+                .with_id_generator(XrayIdGenerator::default()),
+        )
+        .install_simple()?;
+
+    // Метрики периодически отправляются в тот же коллектор OTLP.
+    // `build` также устанавливает глобальный `MeterProvider`, который
+    // используется сервером для записи метрик
+    let meter_provider = opentelemetry_otlp::new_pipeline()
+        .metrics(opentelemetry::runtime::Tokio)
+        .with_exporter(opentelemetry_otlp::new_exporter().tonic())
+        .build()?;
+    let _ = METER_PROVIDER.set(meter_provider);
+
+    // Создаем слой трассировки с настроенным трассировщиком
+    let opentelemetry = tracing_opentelemetry::layer().with_tracer(tracer);
+
+    // Разбираем настройки `EnvFilter` из переменной среды `RUST_LOG`.
+    // Фильтр изменяется командой `CONFIG SET loglevel`
+    let (filter, handle) = reload::Layer::new(EnvFilter::from_default_env());
+
+    // Используем `Registry` (или любой другой подписчик, реализующий `LookupSpan`)
+    tracing_subscriber::registry()
+        .with(opentelemetry)
+        .with(filter)
+        .with(
+            fmt::Layer::default()
+                .with_writer(log_writer(logfile)?)
+                .with_ansi(logfile.is_none()),
+        )
+        .try_init()?;
+
+    Ok(LogFilter::new(handle))
+}
+
+/// Отправляет накопленные трассировки и метрики.
+#[cfg(feature = "otel")]
+fn shut_down_telemetry() {
+    if let Some(meter_provider) = METER_PROVIDER.get() {
+        if let Err(err) = meter_provider.shutdown() {
+            tracing::warn!(cause = %err, "Провал отправки метрик");
+        }
+    }
+
+    global::shutdown_tracer_provider();
 }
@@ -0,0 +1,95 @@
+//! Внедрение сбоев (fault injection) для тестирования клиентов.
+//!
+//! Доступно с флагом `chaos`. Сбои настраиваются командой `DEBUG CHAOS` или
+//! методами `Chaos`, полученного из `Db::chaos`, и действуют на все
+//! соединения сервера, использующие эту БД:
+//!
+//! * задержка - перед выполнением каждой команды сервер ждет случайное время
+//!   от нуля до заданного максимума;
+//! * разрыв соединения - с заданной вероятностью сервер закрывает соединение
+//!   вместо выполнения команды;
+//! * отставание подписчика - с заданной вероятностью сообщение не
+//!   доставляется подписчику, как если бы он отстал (`Lagged`) от канала.
+//!
+//! Команды `DEBUG` сбоям не подвергаются, поэтому сбои всегда можно
+//! отключить командой `DEBUG CHAOS OFF`.
+
+use std::convert::TryFrom;
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use tokio::time::Duration;
+
+/// Настройки внедряемых сбоев. По умолчанию сбои отключены.
+#[derive(Debug, Default)]
+pub struct Chaos {
+    /// Максимальная задержка выполнения команды в миллисекундах.
+    max_latency: AtomicU64,
+
+    /// Вероятность разрыва соединения в процентах.
+    drop_percent: AtomicU8,
+
+    /// Вероятность потери сообщения подписчиком в процентах.
+    lag_percent: AtomicU8,
+}
+
+impl Chaos {
+    /// Устанавливает максимальную задержку выполнения команды. Нулевая
+    /// задержка отключает сбой.
+    pub fn set_max_latency(&self, max: Duration) {
+        let millis = u64::try_from(max.as_millis()).unwrap_or(u64::MAX);
+        self.max_latency.store(millis, Ordering::Relaxed);
+    }
+
+    /// Устанавливает вероятность разрыва соединения перед выполнением
+    /// команды в процентах.
+    ///
+    /// # Паники
+    ///
+    /// Паникует, если `percent` больше `100`.
+    pub fn set_drop_percent(&self, percent: u8) {
+        assert!(percent <= 100, "Вероятность не может превышать 100%");
+        self.drop_percent.store(percent, Ordering::Relaxed);
+    }
+
+    /// Устанавливает вероятность потери сообщения подписчиком в процентах.
+    ///
+    /// # Паники
+    ///
+    /// Паникует, если `percent` больше `100`.
+    pub fn set_lag_percent(&self, percent: u8) {
+        assert!(percent <= 100, "Вероятность не может превышать 100%");
+        self.lag_percent.store(percent, Ordering::Relaxed);
+    }
+
+    /// Отключает все сбои.
+    pub fn reset(&self) {
+        self.set_max_latency(Duration::ZERO);
+        self.set_drop_percent(0);
+        self.set_lag_percent(0);
+    }
+
+    /// Возвращает задержку выполнения очередной команды.
+    pub(crate) fn latency(&self) -> Option<Duration> {
+        match self.max_latency.load(Ordering::Relaxed) {
+            0 => None,
+            max => Some(Duration::from_millis(
+                (crate::server::random_fraction() * (max + 1) as f64) as u64,
+            )),
+        }
+    }
+
+    /// Возвращает `true`, если соединение должно быть разорвано.
+    pub(crate) fn drop_connection(&self) -> bool {
+        happens(self.drop_percent.load(Ordering::Relaxed))
+    }
+
+    /// Возвращает `true`, если сообщение не должно быть доставлено
+    /// подписчику.
+    pub(crate) fn lag(&self) -> bool {
+        happens(self.lag_percent.load(Ordering::Relaxed))
+    }
+}
+
+/// Возвращает `true` с вероятностью `percent` процентов.
+fn happens(percent: u8) -> bool {
+    percent > 0 && crate::server::random_fraction() * 100.0 < f64::from(percent)
+}
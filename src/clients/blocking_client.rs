@@ -3,11 +3,16 @@
 //! Предоставляет блокирующее подключение и методы для обработки поддерживаемых команд.
 
 use bytes::Bytes;
+use std::future::{self, Future};
+use std::io::{Error, ErrorKind};
+use std::pin::pin;
+use std::task::Poll;
 use std::time::Duration;
 use tokio::net::ToSocketAddrs;
 use tokio::runtime::Runtime;
 
 pub use crate::clients::Message;
+use crate::Frame;
 
 /// Соединение, установленное с сервером `Redis`.
 ///
@@ -23,6 +28,10 @@ pub struct BlockingClient {
     /// Среда `current_thread` для выполнения операций с помощью
     /// асинхронного `Client` блокирующим способом.
     rt: Runtime,
+
+    /// Максимальное время выполнения одной операции. `None` означает
+    /// отсутствие ограничения.
+    timeout: Option<Duration>,
 }
 
 /// Клиент в режиме pub/sub (издатель/подписчик).
@@ -37,16 +46,9 @@ pub struct BlockingSubscriber {
     /// Среда `current_thread` для выполнения операций с помощью
     /// асинхронного `Subscriber` блокирующим способом.
     rt: Runtime,
-}
-
-/// Итератор, возвращаемый `Subscriber::into_iter()`.
-struct SubscriberIterator {
-    /// Асинхронный `Subscriber`.
-    inner: crate::clients::Subscriber,
 
-    /// Среда `current_thread` для выполнения операций с помощью
-    /// асинхронного `Subscriber` блокирующим способом.
-    rt: Runtime,
+    /// Максимальное время выполнения команд подписки и отписки.
+    timeout: Option<Duration>,
 }
 
 impl BlockingClient {
@@ -70,13 +72,78 @@ impl BlockingClient {
     /// }
     /// ```
     pub fn connect<T: ToSocketAddrs>(addr: T) -> crate::Result<BlockingClient> {
+        BlockingClient::connect_timeout(addr, None)
+    }
+
+    /// Устанавливает соединение с сервером `Redis`, находящимся по `addr`,
+    /// ограничивая время выполнения операций `timeout`.
+    ///
+    /// Ограничение применяется как к установке соединения, так и ко всем последующим
+    /// операциям клиента. Его можно изменить с помощью `set_timeout`.
+    ///
+    /// # Примеры
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::BlockingClient;
+    /// use std::time::Duration;
+    ///
+    /// fn main() {
+    ///     let timeout = Some(Duration::from_secs(1));
+    ///     let mut client = BlockingClient::connect_timeout("localhost:6379", timeout).unwrap();
+    ///
+    ///     let val = client.get("foo").unwrap();
+    ///     println!("Получено = {:?}", val);
+    /// }
+    /// ```
+    pub fn connect_timeout<T: ToSocketAddrs>(
+        addr: T,
+        timeout: Option<Duration>,
+    ) -> crate::Result<BlockingClient> {
         let rt = tokio::runtime::Builder::new_current_thread()
             .enable_all()
             .build()?;
 
-        let inner = rt.block_on(crate::clients::Client::connect(addr))?;
+        let inner = block_on(&rt, timeout, crate::clients::Client::connect(addr))?;
+
+        Ok(BlockingClient { inner, rt, timeout })
+    }
+
+    /// Устанавливает максимальное время выполнения одной операции.
+    ///
+    /// Если сервер не отвечает в течение `timeout`, операция возвращает ошибку
+    /// с видом `ErrorKind::TimedOut`. Ответ на прерванную команду может прийти позже
+    /// и быть прочитан следующей операцией, поэтому после такой ошибки клиент
+    /// следует уничтожить и установить новое соединение.
+    ///
+    /// `None` снимает ограничение.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
 
-        Ok(BlockingClient { inner, rt })
+    /// Возвращает максимальное время выполнения одной операции.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// "Пингует" сервер.
+    ///
+    /// При отсутствии аргументов, возвращается "PONG",
+    /// иначе, возвращается копия аргументов в виде группы (bulk).
+    ///
+    /// # Примеры
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::BlockingClient;
+    ///
+    /// fn main() {
+    ///     let mut client = BlockingClient::connect("localhost:6379").unwrap();
+    ///
+    ///     let pong = client.ping(None).unwrap();
+    ///     assert_eq!(b"PONG", &pong[..]);
+    /// }
+    /// ```
+    pub fn ping(&mut self, msg: Option<Bytes>) -> crate::Result<Bytes> {
+        block_on(&self.rt, self.timeout, self.inner.ping(msg))
     }
 
     /// Извлекает значение по ключу.
@@ -96,7 +163,7 @@ impl BlockingClient {
     /// }
     /// ```
     pub fn get(&mut self, key: &str) -> crate::Result<Option<Bytes>> {
-        self.rt.block_on(self.inner.get(key))
+        block_on(&self.rt, self.timeout, self.inner.get(key))
     }
 
     /// Устанавливает переданное `value` для `key`.
@@ -122,7 +189,7 @@ impl BlockingClient {
     /// }
     /// ```
     pub fn set(&mut self, key: &str, value: Bytes) -> crate::Result<()> {
-        self.rt.block_on(self.inner.set(key, value))
+        block_on(&self.rt, self.timeout, self.inner.set(key, value))
     }
 
     /// Устанавливает переданное `value` для `key`. Значение истекает после `expiration`.
@@ -167,8 +234,11 @@ impl BlockingClient {
         value: Bytes,
         expiration: Duration,
     ) -> crate::Result<()> {
-        self.rt
-            .block_on(self.inner.set_expires(key, value, expiration))
+        block_on(
+            &self.rt,
+            self.timeout,
+            self.inner.set_expires(key, value, expiration),
+        )
     }
 
     /// Отправляет  `message` в определенный `channel`.
@@ -190,7 +260,38 @@ impl BlockingClient {
     /// }
     /// ```
     pub fn publish(&mut self, channel: &str, message: Bytes) -> crate::Result<u64> {
-        self.rt.block_on(self.inner.publish(channel, message))
+        block_on(&self.rt, self.timeout, self.inner.publish(channel, message))
+    }
+
+    /// Отправляет серверу произвольный кадр и возвращает кадр ответа.
+    ///
+    /// Аналогично `Client::send_frame`. Позволяет выполнять команды, для которых
+    /// у `BlockingClient` нет отдельного метода.
+    ///
+    /// # Примеры
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::BlockingClient;
+    /// use mini_redis::Frame;
+    ///
+    /// fn main() {
+    ///     let mut client = BlockingClient::connect("localhost:6379").unwrap();
+    ///
+    ///     let frame = Frame::Array(vec![
+    ///         Frame::Bulk("get".into()),
+    ///         Frame::Bulk("foo".into()),
+    ///     ]);
+    ///     let response = client.send_frame(frame).unwrap();
+    ///     println!("Получено = {}", response);
+    /// }
+    /// ```
+    pub fn send_frame(&mut self, frame: Frame) -> crate::Result<Frame> {
+        block_on(&self.rt, self.timeout, self.inner.send_frame(frame))
+    }
+
+    /// Завершает сессию и закрывает соединение. См. `Client::close`.
+    pub fn close(self) -> crate::Result<()> {
+        block_on(&self.rt, self.timeout, self.inner.close())
     }
 
     /// Подписывает клиента на определенные каналы.
@@ -201,10 +302,11 @@ impl BlockingClient {
     /// Значение `BlockingSubscriber` используется для получения сообщений, а также
     /// для управления списком каналов, на которые подписан клиент.
     pub fn subscribe(self, channels: Vec<String>) -> crate::Result<BlockingSubscriber> {
-        let subscriber = self.rt.block_on(self.inner.subscribe(channels))?;
+        let subscriber = block_on(&self.rt, self.timeout, self.inner.subscribe(channels))?;
         Ok(BlockingSubscriber {
             inner: subscriber,
             rt: self.rt,
+            timeout: self.timeout,
         })
     }
 }
@@ -223,30 +325,80 @@ impl BlockingSubscriber {
         self.rt.block_on(self.inner.next_message())
     }
 
-    /// Преобразует подписчика в `Iterator`, возвращающий (yielding) новые сообщения,
-    /// опубликованные в подписанных каналах.
-    pub fn into_iter(self) -> impl Iterator<Item = crate::Result<Message>> {
-        SubscriberIterator {
-            inner: self.inner,
-            rt: self.rt,
-        }
-    }
-
     /// Выполняет подписку на указанные каналы.
+    ///
+    /// Время выполнения ограничивается значением, унаследованным от `BlockingClient`.
     pub fn subscribe(&mut self, channels: &[String]) -> crate::Result<()> {
-        self.rt.block_on(self.inner.subscribe(channels))
+        block_on(&self.rt, self.timeout, self.inner.subscribe(channels))
     }
 
     /// Выполняет отписку от указанных каналов.
+    ///
+    /// Время выполнения ограничивается значением, унаследованным от `BlockingClient`.
     pub fn unsubscribe(&mut self, channels: &[String]) -> crate::Result<()> {
-        self.rt.block_on(self.inner.unsubscribe(channels))
+        block_on(&self.rt, self.timeout, self.inner.unsubscribe(channels))
+    }
+
+    /// Завершает сессию и закрывает соединение. См. `Client::close`.
+    ///
+    /// Время выполнения ограничивается значением, унаследованным от `BlockingClient`.
+    pub fn close(mut self) -> crate::Result<()> {
+        // После `QUIT` список подписок пуст, поэтому `drop` не отправляет
+        // `UNSUBSCRIBE`
+        block_on(&self.rt, self.timeout, self.inner.quit())
     }
 }
 
-impl Iterator for SubscriberIterator {
+/// `BlockingSubscriber` является итератором новых сообщений, опубликованных
+/// в подписанных каналах. Итерация завершается при прекращении подписки.
+impl Iterator for BlockingSubscriber {
     type Item = crate::Result<Message>;
 
     fn next(&mut self) -> Option<crate::Result<Message>> {
         self.rt.block_on(self.inner.next_message()).transpose()
     }
 }
+
+impl Drop for BlockingSubscriber {
+    /// Отправляет серверу `UNSUBSCRIBE` для всех каналов.
+    ///
+    /// Это делается по принципу "best effort" и не блокирует поток: запись
+    /// выполняется за один опрос, ответ сервера не ожидается. Если сокет не
+    /// готов к записи или соединение разорвано, отписка пропускается. В этом
+    /// случае сервер удалит подписки при обнаружении закрытия соединения.
+    fn drop(&mut self) {
+        if self.inner.get_subscribed().is_empty() {
+            return;
+        }
+
+        // Опрос выполняется внутри среды, поскольку сокет зарегистрирован в
+        // ее драйвере ввода-вывода
+        let mut unsubscribe = pin!(self.inner.send_unsubscribe_all());
+        let _ = self.rt.block_on(future::poll_fn(|cx| {
+            Poll::Ready(unsubscribe.as_mut().poll(cx))
+        }));
+    }
+}
+
+/// Выполняет `future` в среде `rt`, ожидая результата не дольше `timeout`.
+///
+/// Поскольку среда `current_thread` выполняет задачи только внутри `block_on`,
+/// зависший сервер блокирует поток вызывающей стороны. Ограничение времени
+/// позволяет вернуть управление даже в этом случае.
+fn block_on<T>(
+    rt: &Runtime,
+    timeout: Option<Duration>,
+    future: impl Future<Output = crate::Result<T>>,
+) -> crate::Result<T> {
+    match timeout {
+        Some(timeout) => rt.block_on(async {
+            match tokio::time::timeout(timeout, future).await {
+                Ok(res) => res,
+                Err(_) => {
+                    Err(Error::new(ErrorKind::TimedOut, "Время ожидания ответа истекло.").into())
+                }
+            }
+        }),
+        None => rt.block_on(future),
+    }
+}
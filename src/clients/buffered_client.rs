@@ -1,49 +1,170 @@
+use crate::clients::client::{get_reply, ping_reply, publish_reply, set_reply};
 use crate::clients::Client;
-use crate::Result;
+use crate::cmd::{Get, Ping, Publish, Set};
+use crate::{frame, Frame, Result};
 
 use bytes::Bytes;
+use std::fmt;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tokio::sync::mpsc::{channel, Receiver, Sender};
 use tokio::sync::oneshot;
 
 // Перечисление, используемое для передачи команды из обработчика `BufferedClient`
 #[derive(Debug)]
 enum Command {
+    Ping(Option<Bytes>),
     Get(String),
-    Set(String, Bytes),
+    Set(String, Bytes, Option<Duration>),
+    Publish(String, Bytes),
+}
+
+impl Command {
+    /// Преобразует команду в кадр для отправки серверу
+    fn into_frame(self) -> Frame {
+        match self {
+            Command::Ping(msg) => Ping::new(msg).into_frame(),
+            Command::Get(key) => Get::new(key).into_frame(),
+            Command::Set(key, value, expire) => Set::new(key, value, expire).into_frame(),
+            Command::Publish(channel, message) => Publish::new(channel, message).into_frame(),
+        }
+    }
 }
 
 // Тип сообщения, передаваемый через канал в задачу соединения.
-//
-// `Command` - это команда для передачи в соединение.
-//
-// `oneshot::Sender` - тип канала, отправляющий единичное значение. Используется
-// здесь для отправки ответа, полученного из соединения, вызывающей стороне
-type Message = (Command, oneshot::Sender<Result<Option<Bytes>>>);
+#[derive(Debug)]
+enum Message {
+    // `Command` - это команда для передачи в соединение.
+    //
+    // `oneshot::Sender` - тип канала, отправляющий единичное значение. Используется
+    // здесь для отправки ответа, полученного из соединения, вызывающей стороне
+    Request(Command, oneshot::Sender<Result<Frame>>),
+
+    // Запрос на закрытие. После выполнения всех команд, находящихся в канале,
+    // внутренний `Client` возвращается через `oneshot`
+    Close(oneshot::Sender<Client>),
+}
+
+/// Ошибка, возвращаемая `BufferedClient`, когда фоновая задача соединения
+/// завершилась из-за потери соединения с сервером (например, при перезапуске сервера).
+///
+/// После получения этой ошибки обработчик и все его клоны считаются
+/// сломанными (см. `BufferedClient::is_broken`). Для продолжения работы
+/// необходимо установить новое соединение и передать его в
+/// `BufferedClient::reconnect` любого из клонов.
+#[derive(Debug)]
+pub struct ConnectionLost;
 
 /// Получает команды через канал и передает их клиенту.
-/// Ответ возвращается вызывающей стороне через `oneshot`
-async fn run(mut client: Client, mut rx: Receiver<Message>) {
+/// Ответ возвращается вызывающей стороне через `oneshot`.
+///
+/// При потере соединения устанавливается флаг `broken`, и задача завершается.
+/// Команды, оставшиеся в канале, уничтожаются, а вызывающие стороны получают `ConnectionLost`
+async fn run(mut client: Client, mut rx: Receiver<Message>, broken: Arc<AtomicBool>) {
     // Извлекаем сообщения из канала в цикле. `None`
     // является индикатором того, что все обработчики `BufferedClient` уничтожены и
     // сообщений в канале больше не будет
-    while let Some((cmd, tx)) = rx.recv().await {
-        // Команда передается в соединение
-        let response = match cmd {
-            Command::Get(key) => client.get(&key).await,
-            Command::Set(key, value) => client.set(&key, value).await.map(|_| None),
-        };
-
-        // Возвращаем ответ вызывающей стороне.
-        //
-        // Провал отправки сообщения свидетельствует о том, что половина `rx` уничтожена.
-        // Это нормальное событие среды выполнения
+    while let Some(message) = rx.recv().await {
+        match message {
+            Message::Request(cmd, tx) => {
+                if !execute(&mut client, cmd, tx).await {
+                    broken.store(true, Ordering::SeqCst);
+                    return;
+                }
+            }
+            Message::Close(tx) => {
+                // Закрываем канал. Новые команды больше не принимаются, но
+                // сообщения, уже находящиеся в канале, по-прежнему могут быть получены
+                rx.close();
+
+                // Выполняем все команды, отправленные до закрытия канала.
+                // Повторные запросы на закрытие отбрасываются: `Client`
+                // возвращается только первой вызывающей стороне
+                while let Some(message) = rx.recv().await {
+                    if let Message::Request(cmd, tx) = message {
+                        if !execute(&mut client, cmd, tx).await {
+                            broken.store(true, Ordering::SeqCst);
+                            return;
+                        }
+                    }
+                }
+
+                // Возвращаем `Client` вызывающей стороне. Задача завершается
+                let _ = tx.send(client);
+                return;
+            }
+        }
+    }
+}
+
+/// Выполняет команду и возвращает ответ вызывающей стороне.
+///
+/// Возвращает `false`, если соединение с сервером потеряно
+async fn execute(client: &mut Client, cmd: Command, tx: oneshot::Sender<Result<Frame>>) -> bool {
+    // Команда передается в соединение. Ответ разбирается вызывающей стороной
+    let response = client.send_frame(cmd.into_frame()).await;
+
+    // Ошибки ввода-вывода и ошибки разбора кадров означают, что соединение
+    // больше не может использоваться. Ошибки, возвращенные сервером (`-ERR ...`),
+    // относятся только к текущей команде
+    let lost = match &response {
+        Err(err) => err.is::<io::Error>() || err.is::<frame::Error>(),
+        Ok(_) => false,
+    };
+
+    // Возвращаем ответ вызывающей стороне.
+    //
+    // Провал отправки сообщения свидетельствует о том, что половина `rx` уничтожена.
+    // Это нормальное событие среды выполнения
+    if lost {
+        let _ = tx.send(Err(ConnectionLost.into()));
+    } else {
         let _ = tx.send(response);
     }
+
+    !lost
 }
 
 #[derive(Clone)]
 pub struct BufferedClient {
+    /// Текущая фоновая задача. Является общей для всех клонов обработчика,
+    /// поэтому `reconnect` обновляет их все
+    task: Arc<Mutex<Task>>,
+}
+
+/// Обработчик фоновой задачи соединения.
+#[derive(Clone)]
+struct Task {
     tx: Sender<Message>,
+
+    /// Устанавливается фоновой задачей при потере соединения
+    broken: Arc<AtomicBool>,
+}
+
+impl Task {
+    /// Выделяет фоновую задачу для обработки запросов соединения `client`
+    fn spawn(client: Client) -> Task {
+        // Устанавливаем лимит сообщений в 32. В реальном приложении
+        // размер буфера должен быть настраиваемым
+        let (tx, rx) = channel(32);
+        let broken = Arc::new(AtomicBool::new(false));
+
+        let task_broken = broken.clone();
+        tokio::spawn(async move { run(client, rx, task_broken).await });
+
+        Task { tx, broken }
+    }
+
+    /// Возвращает ошибку для задачи, которая завершена
+    fn closed_error(&self) -> crate::Error {
+        if self.broken.load(Ordering::SeqCst) {
+            ConnectionLost.into()
+        } else {
+            "`BufferedClient` закрыт.".into()
+        }
+    }
 }
 
 impl BufferedClient {
@@ -64,15 +185,33 @@ impl BufferedClient {
     /// Возвращаемый обработчик `BufferedClient` может быть клонирован перед передачей
     /// нового обработчика в отдельные задачи.
     pub fn buffer(client: Client) -> BufferedClient {
-        // Устанавливаем лимит сообщений в 32. В реальном приложении
-        // размер буфера должен быть настраиваемым
-        let (tx, rx) = channel(32);
+        BufferedClient {
+            task: Arc::new(Mutex::new(Task::spawn(client))),
+        }
+    }
+
+    /// Возвращает `true`, если фоновая задача завершилась из-за потери соединения.
+    ///
+    /// Все команды сломанного обработчика возвращают `ConnectionLost`
+    pub fn is_broken(&self) -> bool {
+        self.task().broken.load(Ordering::SeqCst)
+    }
 
-        // Выделяем задачу для обработки запросов соединения
-        tokio::spawn(async move { run(client, rx).await });
+    /// Запускает новую фоновую задачу поверх свежего соединения `client`.
+    ///
+    /// Задача является общей для всех клонов обработчика, поэтому
+    /// переподключаются и клоны, созданные до вызова. Команды, уже
+    /// отправленные в старую задачу, завершаются ошибкой `ConnectionLost`
+    pub fn reconnect(&mut self, client: Client) {
+        *self.task.lock().unwrap() = Task::spawn(client);
+    }
 
-        // Возвращаем обработчик `BufferedClient`
-        BufferedClient { tx }
+    /// "Пингует" сервер.
+    ///
+    /// Аналогично `Client::ping`, но запросы помещаются в буфер,
+    /// пока соответствующее соединение не сможет отправить запрос
+    pub async fn ping(&mut self, msg: Option<Bytes>) -> Result<Bytes> {
+        ping_reply(self.request(Command::Ping(msg)).await?)
     }
 
     /// Извлекает значение по ключу.
@@ -83,17 +222,7 @@ impl BufferedClient {
         // Инициализируем новую команду `Get` для отправки через канал
         let get = Command::Get(key.into());
 
-        // Инициализируем новый `oneshot` для получения ответа из соединения
-        let (tx, rx) = oneshot::channel();
-
-        // Отправляем запрос
-        self.tx.send((get, tx)).await?;
-
-        // Ждем ответ
-        match rx.await {
-            Ok(res) => res,
-            Err(err) => Err(err.into()),
-        }
+        get_reply(self.request(get).await?)
     }
 
     /// Устанавливает `value` для `key`.
@@ -102,18 +231,89 @@ impl BufferedClient {
     /// пока соответствующее соединение не сможет отправить запрос
     pub async fn set(&mut self, key: &str, value: Bytes) -> Result<()> {
         // Инициализируем новую команду `Set` для отправки через канал
-        let set = Command::Set(key.into(), value);
+        let set = Command::Set(key.into(), value, None);
 
-        // Инициализируем новый `oneshot` для получения ответа из соединения
+        set_reply(self.request(set).await?)
+    }
+
+    /// Устанавливает `value` для `key`. Значение истекает после `expiration`.
+    ///
+    /// Аналогично `Client::set_expires`, но запросы помещаются в буфер,
+    /// пока соответствующее соединение не сможет отправить запрос
+    pub async fn set_expires(
+        &mut self,
+        key: &str,
+        value: Bytes,
+        expiration: Duration,
+    ) -> Result<()> {
+        let set = Command::Set(key.into(), value, Some(expiration));
+
+        set_reply(self.request(set).await?)
+    }
+
+    /// Отправляет `message` в `channel`.
+    ///
+    /// Аналогично `Client::publish`, но запросы помещаются в буфер,
+    /// пока соответствующее соединение не сможет отправить запрос
+    pub async fn publish(&mut self, channel: &str, message: Bytes) -> Result<u64> {
+        let publish = Command::Publish(channel.into(), message);
+
+        publish_reply(self.request(publish).await?)
+    }
+
+    /// Закрывает буфер запросов и возвращает внутренний `Client`.
+    ///
+    /// После вызова этого метода новые команды не принимаются: вызовы методов
+    /// на клонах `BufferedClient` возвращают ошибку. Команды, отправленные
+    /// до закрытия, выполняются, после чего фоновая задача завершается, а
+    /// `Client` возвращается вызывающей стороне.
+    ///
+    /// Если буфер уже закрыт другим клоном, возвращается ошибка
+    pub async fn close(self) -> Result<Client> {
+        let task = self.task();
+
+        // Инициализируем новый `oneshot` для получения `Client` из задачи соединения
         let (tx, rx) = oneshot::channel();
 
-        // Отправляем запрос
-        self.tx.send((set, tx)).await?;
+        // Отправляем запрос на закрытие. Он помещается в очередь после всех
+        // ранее отправленных команд
+        if task.tx.send(Message::Close(tx)).await.is_err() {
+            return Err(task.closed_error());
+        }
+
+        // Ждем завершения задачи
+        rx.await.map_err(|_| task.closed_error())
+    }
+
+    /// Отправляет команду в задачу соединения и ждет ответа
+    async fn request(&mut self, cmd: Command) -> Result<Frame> {
+        // Блокировка не удерживается во время ожидания: задача копируется
+        let task = self.task();
 
-        // Ждем ответ
-        match rx.await {
-            Ok(res) => res.map(|_| ()),
-            Err(err) => Err(err.into()),
+        // Инициализируем новый `oneshot` для получения ответа из соединения
+        let (tx, rx) = oneshot::channel();
+
+        // Отправляем запрос. Провал означает, что задача соединения завершена
+        if task.tx.send(Message::Request(cmd, tx)).await.is_err() {
+            return Err(task.closed_error());
         }
+
+        // Ждем ответ. Задача соединения отвечает на каждую полученную команду,
+        // поэтому уничтожение `oneshot::Sender` означает, что задача завершилась
+        // до обработки команды
+        rx.await.map_err(|_| task.closed_error())?
+    }
+
+    /// Возвращает текущую фоновую задачу
+    fn task(&self) -> Task {
+        self.task.lock().unwrap().clone()
     }
 }
+
+impl fmt::Display for ConnectionLost {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "Соединение с сервером потеряно.".fmt(fmt)
+    }
+}
+
+impl std::error::Error for ConnectionLost {}
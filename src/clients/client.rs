@@ -2,61 +2,1468 @@
 //!
 //! Предоставляет асинхронное подключение и методы для обработки поддерживаемых команд.
 
-use crate::cmd::{Get, Ping, Publish, Set, Subscribe, Unsubscribe};
+use crate::clients::ServerError;
+use crate::cmd::{
+    Decr, DecrBy, Dump, Exists, Get, Hdel, Hello, Hexists, Hget, Hgetall, HincrBy, Hkeys, Hlen,
+    Hmget, Hrandfield, Hscan, Hset, HsetNx, Hvals, Incr, IncrBy, IncrByFloat, Keys, Mget, Mset,
+    MsetNx, Object, Persist, Pexpire, Ping, Pttl, Publish, Quit, Restore, Scan, Set, SetCondition,
+    Subscribe, Unsubscribe, Zadd, Zcount, ZincrBy, Zrange, ZrangeByLex, ZrangeByScore, Zrank, Zrem,
+    Zscore,
+};
 use crate::{Connection, Frame};
 
 use async_stream::try_stream;
 use bytes::Bytes;
+use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::io::{Error, ErrorKind};
+use std::ops::Bound;
 use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::net::{TcpStream, ToSocketAddrs};
+use tokio::time;
 use tokio_stream::Stream;
 use tracing::{debug, instrument};
 
+/// Максимальное время ожидания ответа на `PING` в `Client::check`.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(1);
+
 /// Соединение, установленное с сервером `Redis`.
 ///
 /// Поддерживаемый одним `TcpStream`, `Client` предоставляет базовую функциональность
 /// сетевого клиента (нет длинного опроса (polling), повторов и др.). Соединения устанавливаются
-/// с помощью функции `connect`.
+/// с помощью функции `connect`. Функция `new` создает клиента поверх произвольного транспорта.
 ///
 /// Запросы обрабатываются с помощью разных методов `Client`.
+#[derive(Debug)]
 pub struct Client {
     /// Соединение TCP, декорированное кодировщиком/декодером протокола `Redis`,
     /// реализованного с помощью буферного `TcpStream`.
     ///
-    /// Когда `Listener` получает входящее соединение, `TcpStream`
-    /// передается в `Connection::new()`, инициализирующий соответствующие буферы.
-    /// `Connection` позволяет обработчику оперировать на уровне "кадра",
-    /// инкапсулируя детали разбора протокола на уровне байтов.
-    connection: Connection,
-}
+    /// Когда `Listener` получает входящее соединение, `TcpStream`
+    /// передается в `Connection::new()`, инициализирующий соответствующие буферы.
+    /// `Connection` позволяет обработчику оперировать на уровне "кадра",
+    /// инкапсулируя детали разбора протокола на уровне байтов.
+    connection: Connection,
+}
+
+/// Клиент в режиме pub/sub (издатель/подписчик).
+///
+/// После подписки на канал, клиенты могут выполнять только команды, связанные с pub/sub.
+/// Тип `Client` становится типом `Subscriber` для предотвращения вызова команд,
+/// не связанных с pub/sub.
+pub struct Subscriber {
+    /// Подписанный клиент.
+    client: Client,
+
+    /// Набор каналов, на которые подписан `Subscriber`.
+    subscribed_channels: Vec<String>,
+}
+
+/// Сообщение, полученное в подписанном канале.
+#[derive(Debug, Clone)]
+pub struct Message {
+    pub channel: String,
+    pub content: Bytes,
+}
+
+impl Client {
+    /// Устанавливает соединение с сервером `Redis`, находящимся по `addr`.
+    ///
+    /// `addr` - любой тип, который может быть асинхронно преобразован в
+    /// `SocketAddr`. Это включает `SocketAddr` и строки. Трейт `ToSocketAddrs`
+    /// предоставляется `Tokio`, а `std`.
+    ///
+    /// # Примеры
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = match Client::connect("localhost:6379").await {
+    ///         Ok(client) => client,
+    ///         Err(_) => panic!("Невозможно установить соединение!"),
+    ///     };
+    /// # drop(client);
+    /// }
+    /// ```
+    ///
+    pub async fn connect<T: ToSocketAddrs>(addr: T) -> crate::Result<Client> {
+        // Аргумент `addr` передается прямо в `TcpStream::connect()`. Выполняется
+        // асинхронный поиск DNS и попытка установить соединение TCP.
+        // Ошибка, возникшая на этом этапе, поднимается (bubble up) к вызывающей стороне.
+        let socket = TcpStream::connect(addr).await?;
+
+        // Инициализируем состояние подключения. Это выделяет буферы чтения/записи для
+        // разбора кадра протокола `Redis`.
+        Ok(Client::new(socket))
+    }
+
+    /// Создает клиента поверх уже установленного соединения `io`.
+    ///
+    /// `io` может быть любым транспортом, реализующим `AsyncRead` и `AsyncWrite`:
+    /// `TcpStream`, сокетом Unix или половиной `tokio::io::duplex`. Для
+    /// подключения к серверу в текущем процессе см. `server::connect_in_memory`.
+    pub fn new<T>(io: T) -> Client
+    where
+        T: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+    {
+        // Инициализируем состояние подключения. Это выделяет буферы чтения/записи для
+        // разбора кадра протокола `Redis`.
+        let connection = Connection::new(io);
+
+        Client { connection }
+    }
+
+    /// "Пингует" сервер.
+    ///
+    /// При отсутствии аргументов, возвращается "PONG",
+    /// иначе, возвращается копия аргументов в виде группы (bulk).
+    ///
+    /// Эта команда часто используется для тестирования того, что
+    /// соединение открыто, а также для измерения задержки.
+    ///
+    /// # Примеры
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let pong = client.ping(None).await.unwrap();
+    ///     assert_eq!(b"PONG", &pong[..]);
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn ping(&mut self, msg: Option<Bytes>) -> crate::Result<Bytes> {
+        let frame = Ping::new(msg).into_frame();
+        debug!(request = ?frame);
+        self.connection.write_frame(&frame).await?;
+
+        ping_reply(self.read_response().await?)
+    }
+
+    /// Проверяет без обращения к серверу, что соединение не закрыто.
+    ///
+    /// Возвращает `false`, если сервер закрыл соединение (например, при
+    /// перезапуске) или отправил данные, которые клиент не запрашивал. Такое
+    /// соединение не может использоваться для команд. Соединение, оборванное
+    /// без уведомления (например, при отключении сети), не обнаруживается:
+    /// для этого используется `check`.
+    pub fn is_connected(&mut self) -> bool {
+        self.connection.is_idle()
+    }
+
+    /// Проверяет работоспособность соединения командой `PING`.
+    ///
+    /// Возвращает `Err`, если соединение закрыто или сервер не ответил за
+    /// `CHECK_TIMEOUT`. После ошибки клиент не должен использоваться, поскольку
+    /// ответ сервера может быть получен позже.
+    ///
+    /// # Примеры
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     if client.check().await.is_err() {
+    ///         client = Client::connect("localhost:6379").await.unwrap();
+    ///     }
+    /// #   drop(client);
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn check(&mut self) -> crate::Result<()> {
+        if !self.is_connected() {
+            return Err(Error::new(ErrorKind::NotConnected, "Соединение закрыто.").into());
+        }
+
+        match time::timeout(CHECK_TIMEOUT, self.ping(None)).await {
+            Ok(Ok(pong)) if &pong[..] == b"PONG" => Ok(()),
+            Ok(Ok(pong)) => Err(Frame::Bulk(pong).to_error()),
+            Ok(Err(err)) => Err(err),
+            Err(_) => Err(Error::new(ErrorKind::TimedOut, "Сервер не ответил на `PING`.").into()),
+        }
+    }
+
+    /// Выполняет рукопожатие с сервером и возвращает информацию о сервере.
+    ///
+    /// Запрашивается протокол RESP2 - единственный протокол, поддерживаемый
+    /// клиентом. Опционально выполняется аутентификация с помощью `auth`
+    /// (имя пользователя и пароль) и устанавливается имя клиента `setname`.
+    ///
+    /// Возвращаются пары "ключ-значение" ответа, например, `server`,
+    /// `version`, `proto` и `id`.
+    ///
+    /// # Примеры
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    /// use mini_redis::Frame;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let info = client.hello(None, Some("worker-1")).await.unwrap();
+    ///     if let Some(Frame::Integer(id)) = info.get("id") {
+    ///         println!("Идентификатор соединения = {}", id);
+    ///     }
+    /// }
+    /// ```
+    #[instrument(skip(self, auth))]
+    pub async fn hello(
+        &mut self,
+        auth: Option<(&str, &str)>,
+        setname: Option<&str>,
+    ) -> crate::Result<HashMap<String, Frame>> {
+        let auth = auth.map(|(username, password)| (username.to_string(), password.to_string()));
+        let frame = Hello::new(Some(2), auth, setname.map(str::to_string)).into_frame();
+
+        // Кадр содержит пароль, поэтому в лог записывается только название команды
+        debug!(request = "hello");
+
+        self.connection.write_frame(&frame).await?;
+
+        hello_reply(self.read_response().await?)
+    }
+
+    /// Извлекает значение по ключу.
+    ///
+    /// При отсутствии значения, возвращается `None`.
+    ///
+    /// # Примеры
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let val = client.get("foo").await.unwrap();
+    ///     println!("{:?}", val);
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn get(&mut self, key: &str) -> crate::Result<Option<Bytes>> {
+        // Создаем команду `Get` для `key` и преобразуем ее в кадр.
+        let frame = Get::new(key).into_frame();
+
+        debug!(request = ?frame);
+
+        // Это записывает полный кадр в
+        // сокет, ожидая при необходимости
+        self.connection.write_frame(&frame).await?;
+
+        // Ждем ответа сервера
+        get_reply(self.read_response().await?)
+    }
+
+    /// Извлекает значения нескольких ключей за один запрос.
+    ///
+    /// Значения возвращаются в порядке `keys`, отсутствующим ключам
+    /// соответствует `None`.
+    ///
+    /// # Примеры
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let values = client.mget(&["foo", "bar"]).await.unwrap();
+    ///     println!("{:?}", values);
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn mget(&mut self, keys: &[&str]) -> crate::Result<Vec<Option<Bytes>>> {
+        let frame = Mget::new(keys).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Array(values) if values.len() == keys.len() => {
+                values.into_iter().map(get_reply).collect()
+            }
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// Атомарно устанавливает значения нескольких ключей за один запрос.
+    ///
+    /// Как и `set`, удаляет время жизни ключей.
+    ///
+    /// # Примеры
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client
+    ///         .mset(&[("foo", "bar".into()), ("baz", "qux".into())])
+    ///         .await
+    ///         .unwrap();
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn mset(&mut self, pairs: &[(&str, Bytes)]) -> crate::Result<()> {
+        let frame = Mset::new(pairs).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        set_reply(self.read_response().await?)
+    }
+
+    /// Атомарно устанавливает значения нескольких ключей, только если ни
+    /// один из них не существует.
+    ///
+    /// Возвращает `true`, если значения установлены. Если хотя бы один ключ
+    /// существует, ни одно значение не изменяется.
+    ///
+    /// # Примеры
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let created = client
+    ///         .mset_nx(&[("lock:a", "1".into()), ("lock:b", "1".into())])
+    ///         .await
+    ///         .unwrap();
+    ///     println!("Блокировки получены = {}", created);
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn mset_nx(&mut self, pairs: &[(&str, Bytes)]) -> crate::Result<bool> {
+        let frame = MsetNx::new(pairs).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        Ok(integer_reply(self.read_response().await?)? == 1)
+    }
+
+    /// Возвращает количество существующих ключей из `keys`.
+    ///
+    /// Ключ, указанный несколько раз, учитывается каждый раз.
+    ///
+    /// # Примеры
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let count = client.exists(&["foo", "bar"]).await.unwrap();
+    ///     println!("Существует = {}", count);
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn exists(&mut self, keys: &[&str]) -> crate::Result<u64> {
+        let frame = Exists::new(keys).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        integer_reply(self.read_response().await?)
+    }
+
+    /// Устанавливает переданное `value` для `key`.
+    ///
+    /// `value` ассоциируется с `key`, пока не будет перезаписано следующим
+    /// вызовом `set` или не будет удалено.
+    ///
+    /// Предыдущее значение перезаписывается (при наличии). Предыдущее время жизни
+    /// ключа отбрасывается (discard) при успехе операции `SET`.
+    ///
+    /// # Примеры
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.set("foo", "bar".into()).await.unwrap();
+    ///
+    ///     let val = client.get("foo").await.unwrap().unwrap();
+    ///     assert_eq!(val, "bar");
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn set(&mut self, key: &str, value: Bytes) -> crate::Result<()> {
+        // Создаем команду `Set` и передаем ее в `set_cmd()`. Для установки значения
+        // с временем жизни (expiration) используется отдельный метод. Общая часть обеих
+        // функций реализуется `set_cmd`.
+        self.set_cmd(Set::new(key, value, None)).await
+    }
+
+    /// Устанавливает переданное `value` для `key`. Значение истекает после `expiration`.
+    ///
+    /// `value` ассоциируется с `key`, пока оно не:
+    /// - истечет
+    /// - будет перезаписано следующим вызовом `set`
+    /// - будет удалено
+    ///
+    /// Предыдущее значение перезаписывается (при наличии). Предыдущее время жизни
+    /// ключа отбрасывается (discard) при успехе операции `SET`.
+    ///
+    /// # Примеры
+    ///
+    /// Пример может работать не всегда, поскольку он полагается на
+    /// относительную синхронизацию клиента и сервера по времени.
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    /// use tokio::time;
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let ttl = Duration::from_millis(500);
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.set_expires("foo", "bar".into(), ttl).await.unwrap();
+    ///
+    ///     let val = client.get("foo").await.unwrap().unwrap();
+    ///     assert_eq!(val, "bar");
+    ///
+    ///     // Ждем окончания времени жизни
+    ///     time::sleep(ttl).await;
+    ///
+    ///     let val = client.get("foo").await.unwrap();
+    ///     assert!(val.is_some());
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn set_expires(
+        &mut self,
+        key: &str,
+        value: Bytes,
+        expiration: Duration,
+    ) -> crate::Result<()> {
+        self.set_cmd(Set::new(key, value, Some(expiration))).await
+    }
+
+    /// Устанавливает `value` для `key`, только если ключа нет (`SET NX`).
+    ///
+    /// Возвращает `true`, если значение установлено. Это позволяет
+    /// использовать ключ как простую блокировку.
+    ///
+    /// # Примеры
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     if client.set_nx("lock", "owner".into()).await.unwrap() {
+    ///         println!("Блокировка получена");
+    ///     }
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn set_nx(&mut self, key: &str, value: Bytes) -> crate::Result<bool> {
+        let cmd = Set::new(key, value, None).with_condition(SetCondition::Nx);
+        self.set_conditional_cmd(cmd).await
+    }
+
+    /// Устанавливает `value` для `key`, только если ключ существует
+    /// (`SET XX`).
+    ///
+    /// Возвращает `true`, если значение установлено.
+    ///
+    /// # Примеры
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let updated = client.set_xx("foo", "bar".into()).await.unwrap();
+    ///     println!("Значение обновлено = {}", updated);
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn set_xx(&mut self, key: &str, value: Bytes) -> crate::Result<bool> {
+        let cmd = Set::new(key, value, None).with_condition(SetCondition::Xx);
+        self.set_conditional_cmd(cmd).await
+    }
+
+    /// Устанавливает `value` для `key` и возвращает предыдущее значение
+    /// (`SET GET`).
+    ///
+    /// При отсутствии предыдущего значения возвращается `None`.
+    ///
+    /// # Примеры
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let prev = client.set_get("foo", "bar".into()).await.unwrap();
+    ///     println!("Предыдущее значение = {:?}", prev);
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn set_get(&mut self, key: &str, value: Bytes) -> crate::Result<Option<Bytes>> {
+        let frame = Set::new(key, value, None).with_get().into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        get_reply(self.read_response().await?)
+    }
+
+    /// Логика `SET` с условием, используемая методами `set_nx` и `set_xx`.
+    async fn set_conditional_cmd(&mut self, cmd: Set) -> crate::Result<bool> {
+        let frame = cmd.into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        // Если условие не выполнено, сервер отвечает `nil`
+        match self.read_response().await? {
+            Frame::Null => Ok(false),
+            frame => set_reply(frame).map(|()| true),
+        }
+    }
+
+    /// Основная логика `SET`, используемая методами `set` и `set_expires.
+    async fn set_cmd(&mut self, cmd: Set) -> crate::Result<()> {
+        // Преобразуем команду `Set` в кадр
+        let frame = cmd.into_frame();
+
+        debug!(request = ?frame);
+
+        // Это записывает полный кадр в
+        // сокет, ожидая при необходимости
+        self.connection.write_frame(&frame).await?;
+
+        // Ждем ответа сервера
+        set_reply(self.read_response().await?)
+    }
+
+    /// Устанавливает время жизни существующего ключа, заменяя предыдущее.
+    ///
+    /// Время жизни передается в миллисекундах (`PEXPIRE`). Ключ с нулевым
+    /// временем жизни удаляется. Возвращает `false`, если ключа нет.
+    ///
+    /// # Примеры
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.set("foo", "bar".into()).await.unwrap();
+    ///
+    ///     let set = client.expire("foo", Duration::from_secs(60)).await.unwrap();
+    ///     assert!(set);
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn expire(&mut self, key: &str, expiration: Duration) -> crate::Result<bool> {
+        let ms = i64::try_from(expiration.as_millis()).unwrap_or(i64::MAX);
+        let frame = Pexpire::new(key, ms).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        Ok(integer_reply(self.read_response().await?)? == 1)
+    }
+
+    /// Атомарно увеличивает целое число, хранящееся по ключу, на единицу.
+    ///
+    /// Отсутствующий ключ считается равным `0`. Возвращает новое значение
+    /// или ошибку, если значение не является целым числом.
+    ///
+    /// # Примеры
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let visits = client.incr("visits").await.unwrap();
+    ///     println!("Посещений = {}", visits);
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn incr(&mut self, key: &str) -> crate::Result<i64> {
+        let frame = Incr::new(key).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        signed_integer_reply(self.read_response().await?)
+    }
+
+    /// Атомарно уменьшает целое число, хранящееся по ключу, на единицу.
+    ///
+    /// Работает так же, как `incr`.
+    ///
+    /// # Примеры
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let left = client.decr("tickets").await.unwrap();
+    ///     println!("Осталось билетов = {}", left);
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn decr(&mut self, key: &str) -> crate::Result<i64> {
+        let frame = Decr::new(key).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        signed_integer_reply(self.read_response().await?)
+    }
+
+    /// Атомарно увеличивает целое число, хранящееся по ключу, на
+    /// `increment`.
+    ///
+    /// Работает так же, как `incr`. Возвращает ошибку, если результат не
+    /// помещается в `i64`.
+    ///
+    /// # Примеры
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let total = client.incr_by("bytes", 1024).await.unwrap();
+    ///     println!("Всего байтов = {}", total);
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn incr_by(&mut self, key: &str, increment: i64) -> crate::Result<i64> {
+        let frame = IncrBy::new(key, increment).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        signed_integer_reply(self.read_response().await?)
+    }
+
+    /// Атомарно уменьшает целое число, хранящееся по ключу, на
+    /// `decrement`.
+    ///
+    /// Работает так же, как `incr_by`.
+    ///
+    /// # Примеры
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let balance = client.decr_by("balance", 100).await.unwrap();
+    ///     println!("Баланс = {}", balance);
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn decr_by(&mut self, key: &str, decrement: i64) -> crate::Result<i64> {
+        let frame = DecrBy::new(key, decrement).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        signed_integer_reply(self.read_response().await?)
+    }
+
+    /// Атомарно увеличивает число с плавающей точкой, хранящееся по ключу,
+    /// на `increment`.
+    ///
+    /// Отсутствующий ключ считается равным `0`. Возвращает новое значение
+    /// или ошибку, если значение не является числом или результат не
+    /// является конечным числом.
+    ///
+    /// # Примеры
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let price = client.incr_by_float("price", 0.5).await.unwrap();
+    ///     println!("Цена = {}", price);
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn incr_by_float(&mut self, key: &str, increment: f64) -> crate::Result<f64> {
+        let frame = IncrByFloat::new(key, increment).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Bulk(value) => Ok(std::str::from_utf8(&value)?.parse()?),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// Удаляет время жизни ключа.
+    ///
+    /// Возвращает `true`, если время жизни было ограничено и удалено.
+    ///
+    /// # Примеры
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let removed = client.persist("foo").await.unwrap();
+    ///     println!("Время жизни удалено = {}", removed);
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn persist(&mut self, key: &str) -> crate::Result<bool> {
+        let frame = Persist::new(key).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        Ok(integer_reply(self.read_response().await?)? == 1)
+    }
+
+    /// Возвращает оставшееся время жизни ключа.
+    ///
+    /// Время жизни запрашивается в миллисекундах (`PTTL`). Возвращает `None`,
+    /// если ключа нет, и `Some(None)`, если время жизни ключа не ограничено.
+    ///
+    /// # Примеры
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let ttl = client.ttl("foo").await.unwrap();
+    ///     println!("Время жизни = {:?}", ttl);
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn ttl(&mut self, key: &str) -> crate::Result<Option<Option<Duration>>> {
+        let frame = Pttl::new(key).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(-2) => Ok(None),
+            Frame::Integer(-1) => Ok(Some(None)),
+            Frame::Integer(ms) if ms >= 0 => Ok(Some(Some(Duration::from_millis(ms as u64)))),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// Возвращает сериализованное значение ключа (`DUMP`).
+    ///
+    /// Возвращает `None`, если ключа нет. Значение восстанавливается методом
+    /// `restore`, в том числе на другом сервере.
+    ///
+    /// # Примеры
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut source = Client::connect("localhost:6379").await.unwrap();
+    ///     let mut target = Client::connect("localhost:6380").await.unwrap();
+    ///
+    ///     if let Some(payload) = source.dump("foo").await.unwrap() {
+    ///         target.restore("foo", None, payload, false).await.unwrap();
+    ///     }
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn dump(&mut self, key: &str) -> crate::Result<Option<Bytes>> {
+        let frame = Dump::new(key).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        get_reply(self.read_response().await?)
+    }
+
+    /// Создает ключ из значения, сериализованного `dump` (`RESTORE`).
+    ///
+    /// `ttl` задает время жизни ключа. Существующий ключ заменяется, только
+    /// если `replace` равен `true`, иначе возвращается ошибка `BUSYKEY`.
+    ///
+    /// # Примеры
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let payload = client.dump("foo").await.unwrap().unwrap();
+    ///     client
+    ///         .restore("copy", Some(Duration::from_secs(60)), payload, true)
+    ///         .await
+    ///         .unwrap();
+    /// }
+    /// ```
+    #[instrument(skip(self, payload))]
+    pub async fn restore(
+        &mut self,
+        key: &str,
+        ttl: Option<Duration>,
+        payload: Bytes,
+        replace: bool,
+    ) -> crate::Result<()> {
+        let frame = Restore::new(key, ttl, payload)
+            .with_replace(replace)
+            .into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        set_reply(self.read_response().await?)
+    }
+
+    /// Устанавливает значение поля хеша (`HSET`).
+    ///
+    /// Возвращает `true`, если поле добавлено, и `false`, если значение
+    /// существующего поля заменено.
+    ///
+    /// # Примеры
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.hset("user", "name", "Alice".into()).await.unwrap();
+    ///     let name = client.hget("user", "name").await.unwrap();
+    ///     println!("Получено = {:?}", name);
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn hset(&mut self, key: &str, field: &str, value: Bytes) -> crate::Result<bool> {
+        let frame = Hset::new(key, &[(field, value)]).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        Ok(integer_reply(self.read_response().await?)? == 1)
+    }
+
+    /// Возвращает значение поля хеша (`HGET`).
+    ///
+    /// Возвращает `None`, если хеша или поля нет.
+    #[instrument(skip(self))]
+    pub async fn hget(&mut self, key: &str, field: &str) -> crate::Result<Option<Bytes>> {
+        let frame = Hget::new(key, field).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        get_reply(self.read_response().await?)
+    }
+
+    /// Возвращает все поля и значения хеша (`HGETALL`).
+    ///
+    /// Если хеша нет, возвращается пустая карта.
+    #[instrument(skip(self))]
+    pub async fn hgetall(&mut self, key: &str) -> crate::Result<HashMap<String, Bytes>> {
+        let frame = Hgetall::new(key).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        let parts = match self.read_response().await? {
+            Frame::Array(parts) if parts.len() % 2 == 0 => parts,
+            frame => return Err(frame.to_error()),
+        };
+
+        let mut fields = HashMap::new();
+        let mut parts = parts.into_iter();
+
+        while let (Some(field), Some(value)) = (parts.next(), parts.next()) {
+            match value {
+                Frame::Bulk(value) => fields.insert(key_from_frame(field)?, value),
+                frame => return Err(frame.to_error()),
+            };
+        }
+
+        Ok(fields)
+    }
+
+    /// Удаляет поля хеша (`HDEL`) и возвращает количество удаленных полей.
+    #[instrument(skip(self))]
+    pub async fn hdel(&mut self, key: &str, fields: &[&str]) -> crate::Result<u64> {
+        let frame = Hdel::new(key, fields).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        integer_reply(self.read_response().await?)
+    }
+
+    /// Увеличивает целое число в поле хеша на `increment` (`HINCRBY`) и
+    /// возвращает новое значение.
+    ///
+    /// # Примеры
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let visits = client.hincr_by("stats", "visits", 1).await.unwrap();
+    ///     println!("Посещений = {}", visits);
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn hincr_by(&mut self, key: &str, field: &str, increment: i64) -> crate::Result<i64> {
+        let frame = HincrBy::new(key, field, increment).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        signed_integer_reply(self.read_response().await?)
+    }
+
+    /// Проверяет наличие поля в хеше (`HEXISTS`).
+    #[instrument(skip(self))]
+    pub async fn hexists(&mut self, key: &str, field: &str) -> crate::Result<bool> {
+        let frame = Hexists::new(key, field).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        Ok(integer_reply(self.read_response().await?)? == 1)
+    }
+
+    /// Возвращает поля хеша в порядке возрастания (`HKEYS`).
+    #[instrument(skip(self))]
+    pub async fn hkeys(&mut self, key: &str) -> crate::Result<Vec<String>> {
+        let frame = Hkeys::new(key).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Array(fields) => fields.into_iter().map(key_from_frame).collect(),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// Возвращает значения хеша в порядке возрастания их полей (`HVALS`).
+    #[instrument(skip(self))]
+    pub async fn hvals(&mut self, key: &str) -> crate::Result<Vec<Bytes>> {
+        let frame = Hvals::new(key).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Array(values) => values
+                .into_iter()
+                .map(|value| match value {
+                    Frame::Bulk(value) => Ok(value),
+                    frame => Err(frame.to_error()),
+                })
+                .collect(),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// Возвращает количество полей хеша (`HLEN`).
+    #[instrument(skip(self))]
+    pub async fn hlen(&mut self, key: &str) -> crate::Result<u64> {
+        let frame = Hlen::new(key).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        integer_reply(self.read_response().await?)
+    }
+
+    /// Возвращает значения нескольких полей хеша (`HMGET`).
+    ///
+    /// Значения возвращаются в порядке `fields`, отсутствующим полям
+    /// соответствует `None`.
+    #[instrument(skip(self))]
+    pub async fn hmget(&mut self, key: &str, fields: &[&str]) -> crate::Result<Vec<Option<Bytes>>> {
+        let frame = Hmget::new(key, fields).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Array(values) if values.len() == fields.len() => {
+                values.into_iter().map(get_reply).collect()
+            }
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// Устанавливает значение поля хеша, только если поля нет (`HSETNX`).
+    ///
+    /// Возвращает `true`, если поле добавлено.
+    #[instrument(skip(self))]
+    pub async fn hset_nx(&mut self, key: &str, field: &str, value: Bytes) -> crate::Result<bool> {
+        let frame = HsetNx::new(key, field, value).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        Ok(integer_reply(self.read_response().await?)? == 1)
+    }
+
+    /// Возвращает случайные поля хеша (`HRANDFIELD key count`).
+    ///
+    /// При положительном `count` поля различны, при отрицательном - могут
+    /// повторяться.
+    ///
+    /// # Примеры
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let fields = client.hrandfield("user", 2).await.unwrap();
+    ///     println!("Поля = {:?}", fields);
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn hrandfield(&mut self, key: &str, count: i64) -> crate::Result<Vec<String>> {
+        let frame = Hrandfield::new(key, Some(count)).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Array(fields) => fields.into_iter().map(key_from_frame).collect(),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// Возвращает случайные поля хеша вместе с их значениями
+    /// (`HRANDFIELD key count WITHVALUES`). Работает так же, как
+    /// `hrandfield`.
+    #[instrument(skip(self))]
+    pub async fn hrandfield_with_values(
+        &mut self,
+        key: &str,
+        count: i64,
+    ) -> crate::Result<Vec<(String, Bytes)>> {
+        let frame = Hrandfield::new(key, Some(count))
+            .with_values(true)
+            .into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        let parts = match self.read_response().await? {
+            Frame::Array(parts) if parts.len() % 2 == 0 => parts,
+            frame => return Err(frame.to_error()),
+        };
+
+        let mut fields = Vec::with_capacity(parts.len() / 2);
+        let mut parts = parts.into_iter();
+
+        while let (Some(field), Some(value)) = (parts.next(), parts.next()) {
+            match value {
+                Frame::Bulk(value) => fields.push((key_from_frame(field)?, value)),
+                frame => return Err(frame.to_error()),
+            }
+        }
+
+        Ok(fields)
+    }
+
+    /// Запрашивает одну порцию полей хеша (`HSCAN`).
+    ///
+    /// Работает так же, как `scan_page`: возвращает следующий курсор и
+    /// порцию полей со значениями. Перебор завершен, когда курсор равен `0`.
+    #[instrument(skip(self))]
+    pub async fn hscan_page(
+        &mut self,
+        key: &str,
+        cursor: u64,
+        pattern: Option<&str>,
+        count: Option<u64>,
+    ) -> crate::Result<(u64, Vec<(String, Bytes)>)> {
+        let frame = Hscan::new(key, cursor, pattern.map(str::to_string), count).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        // Ответ - массив из курсора и массива чередующихся полей и значений
+        let response = self.read_response().await?;
+        let (next, parts) = match response {
+            Frame::Array(ref parts) => match parts.as_slice() {
+                [Frame::Bulk(next), Frame::Array(parts)] if parts.len() % 2 == 0 => {
+                    let next = atoi::atoi::<u64>(next).ok_or_else(|| response.to_error())?;
+                    (next, parts.clone())
+                }
+                _ => return Err(response.to_error()),
+            },
+            frame => return Err(frame.to_error()),
+        };
+
+        let mut fields = Vec::with_capacity(parts.len() / 2);
+        let mut parts = parts.into_iter();
+
+        while let (Some(field), Some(value)) = (parts.next(), parts.next()) {
+            match value {
+                Frame::Bulk(value) => fields.push((key_from_frame(field)?, value)),
+                frame => return Err(frame.to_error()),
+            }
+        }
+
+        Ok((next, fields))
+    }
+
+    /// Добавляет элементы в сортированное множество или обновляет их оценки
+    /// (`ZADD`).
+    ///
+    /// Возвращает количество добавленных элементов.
+    ///
+    /// # Примеры
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client
+    ///         .zadd("leaderboard", &[(100.0, "alice"), (85.5, "bob")])
+    ///         .await
+    ///         .unwrap();
+    ///
+    ///     let top = client.zrange("leaderboard", 0, 9, true).await.unwrap();
+    ///     println!("Лидеры = {:?}", top);
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn zadd(&mut self, key: &str, members: &[(f64, &str)]) -> crate::Result<u64> {
+        let frame = Zadd::new(key, members).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        integer_reply(self.read_response().await?)
+    }
+
+    /// Возвращает оценку элемента сортированного множества (`ZSCORE`).
+    ///
+    /// Возвращает `None`, если множества или элемента нет.
+    #[instrument(skip(self))]
+    pub async fn zscore(&mut self, key: &str, member: &str) -> crate::Result<Option<f64>> {
+        let frame = Zscore::new(key, member).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Null => Ok(None),
+            frame => Ok(Some(score_from_frame(frame)?)),
+        }
+    }
+
+    /// Удаляет элементы сортированного множества (`ZREM`) и возвращает
+    /// количество удаленных элементов.
+    #[instrument(skip(self))]
+    pub async fn zrem(&mut self, key: &str, members: &[&str]) -> crate::Result<u64> {
+        let frame = Zrem::new(key, members).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        integer_reply(self.read_response().await?)
+    }
+
+    /// Возвращает элементы сортированного множества с позициями от `start` до
+    /// `stop` включительно (`ZRANGE`).
+    ///
+    /// Позиции отсчитываются от наименьшей оценки, а если `rev` равен
+    /// `true`, то от наибольшей.
+    #[instrument(skip(self))]
+    pub async fn zrange(
+        &mut self,
+        key: &str,
+        start: i64,
+        stop: i64,
+        rev: bool,
+    ) -> crate::Result<Vec<String>> {
+        let frame = Zrange::new(key, start, stop).with_rev(rev).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Array(members) => members.into_iter().map(key_from_frame).collect(),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// Возвращает элементы сортированного множества вместе с их оценками
+    /// (`ZRANGE ... WITHSCORES`). Работает так же, как `zrange`.
+    #[instrument(skip(self))]
+    pub async fn zrange_with_scores(
+        &mut self,
+        key: &str,
+        start: i64,
+        stop: i64,
+        rev: bool,
+    ) -> crate::Result<Vec<(String, f64)>> {
+        let frame = Zrange::new(key, start, stop)
+            .with_rev(rev)
+            .with_scores(true)
+            .into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        scores_reply(self.read_response().await?)
+    }
+
+    /// Возвращает элементы сортированного множества, оценки которых
+    /// находятся между `min` и `max`, в порядке возрастания оценок
+    /// (`ZRANGEBYSCORE`).
+    ///
+    /// # Примеры
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    /// use std::ops::Bound;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     // Игроки, набравшие больше 90 очков
+    ///     let players = client
+    ///         .zrange_by_score("leaderboard", Bound::Excluded(90.0), Bound::Unbounded)
+    ///         .await
+    ///         .unwrap();
+    ///     println!("Игроки = {:?}", players);
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn zrange_by_score(
+        &mut self,
+        key: &str,
+        min: Bound<f64>,
+        max: Bound<f64>,
+    ) -> crate::Result<Vec<String>> {
+        let frame = ZrangeByScore::new(key, min, max).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Array(members) => members.into_iter().map(key_from_frame).collect(),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// Возвращает элементы сортированного множества, находящиеся между `min`
+    /// и `max` в лексикографическом порядке (`ZRANGEBYLEX`).
+    ///
+    /// Как и в Redis, предполагается, что оценки всех элементов одинаковы.
+    ///
+    /// # Примеры
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    /// use std::ops::Bound;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     // Автодополнение: слова, начинающиеся с "ca"
+    ///     let words = client
+    ///         .zrange_by_lex("words", Bound::Included("ca"), Bound::Excluded("cb"))
+    ///         .await
+    ///         .unwrap();
+    ///     println!("Слова = {:?}", words);
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn zrange_by_lex(
+        &mut self,
+        key: &str,
+        min: Bound<&str>,
+        max: Bound<&str>,
+    ) -> crate::Result<Vec<String>> {
+        let frame = ZrangeByLex::new(key, min, max).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Array(members) => members.into_iter().map(key_from_frame).collect(),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// Возвращает количество элементов сортированного множества, оценки
+    /// которых находятся между `min` и `max` (`ZCOUNT`).
+    #[instrument(skip(self))]
+    pub async fn zcount(
+        &mut self,
+        key: &str,
+        min: Bound<f64>,
+        max: Bound<f64>,
+    ) -> crate::Result<u64> {
+        let frame = Zcount::new(key, min, max).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        integer_reply(self.read_response().await?)
+    }
+
+    /// Увеличивает оценку элемента сортированного множества на `increment`
+    /// (`ZINCRBY`) и возвращает новую оценку.
+    #[instrument(skip(self))]
+    pub async fn zincr_by(
+        &mut self,
+        key: &str,
+        increment: f64,
+        member: &str,
+    ) -> crate::Result<f64> {
+        let frame = ZincrBy::new(key, increment, member).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        score_from_frame(self.read_response().await?)
+    }
+
+    /// Возвращает позицию элемента сортированного множества в порядке
+    /// возрастания оценок (`ZRANK`).
+    ///
+    /// Возвращает `None`, если множества или элемента нет.
+    #[instrument(skip(self))]
+    pub async fn zrank(&mut self, key: &str, member: &str) -> crate::Result<Option<u64>> {
+        let frame = Zrank::new(key, member).into_frame();
 
-/// Клиент в режиме pub/sub (издатель/подписчик).
-///
-/// После подписки на канал, клиенты могут выполнять только команды, связанные с pub/sub.
-/// Тип `Client` становится типом `Subscriber` для предотвращения вызова команд,
-/// не связанных с pub/sub.
-pub struct Subscriber {
-    /// Подписанный клиент.
-    client: Client,
+        debug!(request = ?frame);
 
-    /// Набор каналов, на которые подписан `Subscriber`.
-    subscribed_channels: Vec<String>,
-}
+        self.connection.write_frame(&frame).await?;
 
-/// Сообщение, полученное в подписанном канале.
-#[derive(Debug, Clone)]
-pub struct Message {
-    pub channel: String,
-    pub content: Bytes,
-}
+        match self.read_response().await? {
+            Frame::Null => Ok(None),
+            frame => Ok(Some(integer_reply(frame)?)),
+        }
+    }
 
-impl Client {
-    /// Устанавливает соединение с сервером `Redis`, находящимся по `addr`.
+    /// Отправляет  `message` в определенный `channel`.
     ///
-    /// `addr` - любой тип, который может быть асинхронно преобразован в
-    /// `SocketAddr`. Это включает `SocketAddr` и строки. Трейт `ToSocketAddrs`
-    /// предоставляется `Tokio`, а `std`.
+    /// Возвращает количество подписчиков канала.
+    /// Не гарантируется, что все эти подписчики получат сообщение, поскольку
+    /// они могут отключиться в любой момент.
     ///
     /// # Примеры
     ///
@@ -65,34 +1472,29 @@ impl Client {
     ///
     /// #[tokio::main]
     /// async fn main() {
-    ///     let client = match Client::connect("localhost:6379").await {
-    ///         Ok(client) => client,
-    ///         Err(_) => panic!("Невозможно установить соединение!"),
-    ///     };
-    /// # drop(client);
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let val = client.publish("foo", "bar".into()).await.unwrap();
+    ///     println!("Получено = {:?}", val);
     /// }
     /// ```
-    ///
-    pub async fn connect<T: ToSocketAddrs>(addr: T) -> crate::Result<Client> {
-        // Аргумент `addr` передается прямо в `TcpStream::connect()`. Выполняется
-        // асинхронный поиск DNS и попытка установить соединение TCP.
-        // Ошибка, возникшая на этом этапе, поднимается (bubble up) к вызывающей стороне.
-        let socket = TcpStream::connect(addr).await?;
+    #[instrument(skip(self))]
+    pub async fn publish(&mut self, channel: &str, message: Bytes) -> crate::Result<u64> {
+        // Преобразуем команду `Publish` в кадр
+        let frame = Publish::new(channel, message).into_frame();
 
-        // Инициализируем состояние подключения. Это выделяет буферы чтения/записи для
-        // разбора кадра протокола `Redis`.
-        let connection = Connection::new(socket);
+        debug!(request = ?frame);
 
-        Ok(Client { connection })
+        // Записываем кадр в сокет
+        self.connection.write_frame(&frame).await?;
+
+        // Читаем ответ
+        publish_reply(self.read_response().await?)
     }
 
-    /// "Пингует" сервер.
-    ///
-    /// При отсутствии аргументов, возвращается "PONG",
-    /// иначе, возвращается копия аргументов в виде группы (bulk).
+    /// Возвращает кодировку значения `key` (`int`, `embstr` или `raw`).
     ///
-    /// Эта команда часто используется для тестирования того, что
-    /// соединение открыто, а также для измерения задержки.
+    /// При отсутствии ключа возвращается `None`.
     ///
     /// # Примеры
     ///
@@ -103,26 +1505,29 @@ impl Client {
     /// async fn main() {
     ///     let mut client = Client::connect("localhost:6379").await.unwrap();
     ///
-    ///     let pong = client.ping(None).await.unwrap();
-    ///     assert_eq!(b"PONG", &pong[..]);
+    ///     client.set("counter", "42".into()).await.unwrap();
+    ///
+    ///     let encoding = client.object_encoding("counter").await.unwrap();
+    ///     assert_eq!(encoding.as_deref(), Some("int"));
     /// }
     /// ```
     #[instrument(skip(self))]
-    pub async fn ping(&mut self, msg: Option<Bytes>) -> crate::Result<Bytes> {
-        let frame = Ping::new(msg).into_frame();
+    pub async fn object_encoding(&mut self, key: &str) -> crate::Result<Option<String>> {
+        let frame = Object::encoding(key).into_frame();
+
         debug!(request = ?frame);
+
         self.connection.write_frame(&frame).await?;
 
-        match self.read_response().await? {
-            Frame::Simple(value) => Ok(value.into()),
-            Frame::Bulk(value) => Ok(value),
-            frame => Err(frame.to_error()),
+        match get_reply(self.read_response().await?)? {
+            Some(encoding) => Ok(Some(String::from_utf8(encoding.to_vec())?)),
+            None => Ok(None),
         }
     }
 
-    /// Извлекает значение по ключу.
+    /// Возвращает все ключи, соответствующие шаблону `pattern`.
     ///
-    /// При отсутствии значения, возвращается `None`.
+    /// Шаблон может содержать `*`, `?`, классы символов `[...]` и экранирование `\`.
     ///
     /// # Примеры
     ///
@@ -133,40 +1538,33 @@ impl Client {
     /// async fn main() {
     ///     let mut client = Client::connect("localhost:6379").await.unwrap();
     ///
-    ///     let val = client.get("foo").await.unwrap();
-    ///     println!("{:?}", val);
+    ///     let keys = client.keys("user:*").await.unwrap();
+    ///     println!("Получено = {:?}", keys);
     /// }
     /// ```
     #[instrument(skip(self))]
-    pub async fn get(&mut self, key: &str) -> crate::Result<Option<Bytes>> {
-        // Создаем команду `Get` для `key` и преобразуем ее в кадр.
-        let frame = Get::new(key).into_frame();
+    pub async fn keys(&mut self, pattern: &str) -> crate::Result<Vec<String>> {
+        let frame = Keys::new(pattern).into_frame();
 
         debug!(request = ?frame);
 
-        // Это записывает полный кадр в
-        // сокет, ожидая при необходимости
         self.connection.write_frame(&frame).await?;
 
-        // Ждем ответа сервера.
-        //
-        // Принимаются кадры `Simple` и `Bulk`. `Null` представляет
-        // отсутствующий ключ - возвращается `None`
         match self.read_response().await? {
-            Frame::Simple(value) => Ok(Some(value.into())),
-            Frame::Bulk(value) => Ok(Some(value)),
-            Frame::Null => Ok(None),
+            Frame::Array(keys) => keys.into_iter().map(key_from_frame).collect(),
             frame => Err(frame.to_error()),
         }
     }
 
-    /// Устанавливает переданное `value` для `key`.
+    /// Возвращает очередную порцию ключей, начиная с курсора `cursor`.
     ///
-    /// `value` ассоциируется с `key`, пока не будет перезаписано следующим
-    /// вызовом `set` или не будет удалено.
+    /// Перебор начинается с курсора `0`. Возвращается курсор для следующего
+    /// вызова и порция ключей, соответствующих `pattern` (при наличии).
+    /// Курсор `0` означает, что перебор завершен. Порция может быть пустой,
+    /// даже если перебор не завершен. Ключ, присутствующий в течение всего
+    /// перебора, возвращается ровно один раз.
     ///
-    /// Предыдущее значение перезаписывается (при наличии). Предыдущее время жизни
-    /// ключа отбрасывается (discard) при успехе операции `SET`.
+    /// `count` - количество ключей, рассматриваемых сервером за один вызов.
     ///
     /// # Примеры
     ///
@@ -177,91 +1575,146 @@ impl Client {
     /// async fn main() {
     ///     let mut client = Client::connect("localhost:6379").await.unwrap();
     ///
-    ///     client.set("foo", "bar".into()).await.unwrap();
+    ///     let mut cursor = 0;
+    ///     loop {
+    ///         let (next, keys) = client.scan_page(cursor, Some("user:*"), None).await.unwrap();
+    ///         println!("Получено = {:?}", keys);
     ///
-    ///     let val = client.get("foo").await.unwrap().unwrap();
-    ///     assert_eq!(val, "bar");
+    ///         if next == 0 {
+    ///             break;
+    ///         }
+    ///         cursor = next;
+    ///     }
     /// }
     /// ```
     #[instrument(skip(self))]
-    pub async fn set(&mut self, key: &str, value: Bytes) -> crate::Result<()> {
-        // Создаем команду `Set` и передаем ее в `set_cmd()`. Для установки значения
-        // с временем жизни (expiration) используется отдельный метод. Общая часть обеих
-        // функций реализуется `set_cmd`.
-        self.set_cmd(Set::new(key, value, None)).await
+    pub async fn scan_page(
+        &mut self,
+        cursor: u64,
+        pattern: Option<&str>,
+        count: Option<u64>,
+    ) -> crate::Result<(u64, Vec<String>)> {
+        let frame = Scan::new(cursor, pattern.map(str::to_string), count).into_frame();
+
+        debug!(request = ?frame);
+
+        self.connection.write_frame(&frame).await?;
+
+        // Ответ - массив из курсора и массива ключей
+        let response = self.read_response().await?;
+        match response {
+            Frame::Array(ref parts) => match parts.as_slice() {
+                [Frame::Bulk(next), Frame::Array(keys)] => {
+                    let next = atoi::atoi::<u64>(next).ok_or_else(|| response.to_error())?;
+                    let keys = keys
+                        .iter()
+                        .cloned()
+                        .map(key_from_frame)
+                        .collect::<crate::Result<_>>()?;
+
+                    Ok((next, keys))
+                }
+                _ => Err(response.to_error()),
+            },
+            frame => Err(frame.to_error()),
+        }
     }
 
-    /// Устанавливает переданное `value` для `key`. Значение истекает после `expiration`.
+    /// Перебирает все ключи, соответствующие `pattern` (при наличии).
     ///
-    /// `value` ассоциируется с `key`, пока оно не:
-    /// - истечет
-    /// - будет перезаписано следующим вызовом `set`
-    /// - будет удалено
+    /// Возвращает поток ключей, который запрашивает порции ключей с помощью
+    /// `scan_page` по мере необходимости, следуя за курсором сервера до
+    /// завершения перебора. Ошибка завершает поток.
     ///
-    /// Предыдущее значение перезаписывается (при наличии). Предыдущее время жизни
-    /// ключа отбрасывается (discard) при успехе операции `SET`.
+    /// Поток заимствует клиента, поэтому во время перебора клиент не может
+    /// использоваться для других команд.
     ///
     /// # Примеры
     ///
-    /// Пример может работать не всегда, поскольку он полагается на
-    /// относительную синхронизацию клиента и сервера по времени.
-    ///
     /// ```no_run
     /// use mini_redis::clients::Client;
-    /// use tokio::time;
-    /// use std::time::Duration;
+    /// use tokio_stream::StreamExt;
     ///
     /// #[tokio::main]
     /// async fn main() {
-    ///     let ttl = Duration::from_millis(500);
     ///     let mut client = Client::connect("localhost:6379").await.unwrap();
     ///
-    ///     client.set_expires("foo", "bar".into(), ttl).await.unwrap();
+    ///     let keys = client.scan(Some("user:*"));
+    ///     tokio::pin!(keys);
     ///
-    ///     let val = client.get("foo").await.unwrap().unwrap();
-    ///     assert_eq!(val, "bar");
+    ///     while let Some(key) = keys.next().await {
+    ///         println!("Получено = {}", key.unwrap());
+    ///     }
+    /// }
+    /// ```
+    pub fn scan<'a>(
+        &'a mut self,
+        pattern: Option<&'a str>,
+    ) -> impl Stream<Item = crate::Result<String>> + 'a {
+        try_stream! {
+            let mut cursor = 0;
+
+            loop {
+                let (next, keys) = self.scan_page(cursor, pattern, None).await?;
+
+                for key in keys {
+                    yield key;
+                }
+
+                // Курсор `0` означает, что перебор завершен
+                if next == 0 {
+                    break;
+                }
+                cursor = next;
+            }
+        }
+    }
+
+    /// Отправляет серверу произвольный кадр и возвращает кадр ответа.
     ///
-    ///     // Ждем окончания времени жизни
-    ///     time::sleep(ttl).await;
+    /// Позволяет выполнять команды, для которых у `Client` нет отдельного метода.
+    /// Кадр `Error`, полученный от сервера, преобразуется в `Err`.
     ///
-    ///     let val = client.get("foo").await.unwrap();
-    ///     assert!(val.is_some());
+    /// Команды, меняющие режим соединения (например, `SUBSCRIBE`),
+    /// должны выполняться с помощью соответствующих методов.
+    ///
+    /// # Примеры
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    /// use mini_redis::Frame;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let frame = Frame::Array(vec![
+    ///         Frame::Bulk("get".into()),
+    ///         Frame::Bulk("foo".into()),
+    ///     ]);
+    ///     let response = client.send_frame(frame).await.unwrap();
+    ///     println!("Получено = {}", response);
     /// }
     /// ```
     #[instrument(skip(self))]
-    pub async fn set_expires(
-        &mut self,
-        key: &str,
-        value: Bytes,
-        expiration: Duration,
-    ) -> crate::Result<()> {
-        self.set_cmd(Set::new(key, value, Some(expiration))).await
-    }
-
-    /// Основная логика `SET`, используемая методами `set` и `set_expires.
-    async fn set_cmd(&mut self, cmd: Set) -> crate::Result<()> {
-        // Преобразуем команду `Set` в кадр
-        let frame = cmd.into_frame();
-
+    pub async fn send_frame(&mut self, frame: Frame) -> crate::Result<Frame> {
         debug!(request = ?frame);
 
-        // Это записывает полный кадр в
-        // сокет, ожидая при необходимости
+        // Записываем кадр в сокет
         self.connection.write_frame(&frame).await?;
 
-        // Ждем ответа сервера. При успехе сервер отвечает
-        // простым `OK`. Любой другой ответ означает ошибку
-        match self.read_response().await? {
-            Frame::Simple(response) if response == "OK" => Ok(()),
-            frame => Err(frame.to_error()),
-        }
+        // Читаем ответ
+        self.read_response().await
     }
 
-    /// Отправляет  `message` в определенный `channel`.
+    /// Завершает сессию и закрывает соединение.
     ///
-    /// Возвращает количество подписчиков канала.
-    /// Не гарантируется, что все эти подписчики получат сообщение, поскольку
-    /// они могут отключиться в любой момент.
+    /// Серверу отправляется команда `QUIT`. После получения ответа соединение
+    /// закрывается для записи, поэтому сервер получает признак конца потока,
+    /// а не разрыв соединения посреди протокола.
+    ///
+    /// Если сервер не поддерживает `QUIT` и отвечает ошибкой, соединение
+    /// все равно закрывается.
     ///
     /// # Примеры
     ///
@@ -271,26 +1724,40 @@ impl Client {
     /// #[tokio::main]
     /// async fn main() {
     ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///     client.set("foo", "bar".into()).await.unwrap();
     ///
-    ///     let val = client.publish("foo", "bar".into()).await.unwrap();
-    ///     println!("Получено = {:?}", val);
+    ///     client.close().await.unwrap();
     /// }
     /// ```
     #[instrument(skip(self))]
-    pub async fn publish(&mut self, channel: &str, message: Bytes) -> crate::Result<u64> {
-        // Преобразуем команду `Publish` в кадр
-        let frame = Publish::new(channel, message).into_frame();
+    pub async fn close(mut self) -> crate::Result<()> {
+        self.quit().await
+    }
+
+    /// Отправляет `QUIT`, ждет ответа и закрывает соединение для записи.
+    pub(crate) async fn quit(&mut self) -> crate::Result<()> {
+        let frame = Quit::new().into_frame();
 
         debug!(request = ?frame);
 
-        // Записываем кадр в сокет
         self.connection.write_frame(&frame).await?;
 
-        // Читаем ответ
-        match self.read_response().await? {
-            Frame::Integer(response) => Ok(response),
-            frame => Err(frame.to_error()),
+        loop {
+            match self.read_response().await {
+                // Подписчик может получить сообщения, опубликованные
+                // до обработки `QUIT`. Они пропускаются
+                Ok(Frame::Array(ref parts))
+                    if parts.first().is_some_and(|kind| *kind == "message") => {}
+                Ok(_) => break,
+                // Сервер не поддерживает `QUIT`, но соединение рабочее
+                Err(err) if err.downcast_ref::<ServerError>().is_some() => break,
+                Err(err) => return Err(err),
+            }
         }
+
+        self.connection.shutdown().await?;
+
+        Ok(())
     }
 
     /// Подписывает клиента на определенные каналы.
@@ -342,7 +1809,7 @@ impl Client {
                     // где `channel` - это название канала, а
                     // `num-subscribed` - количество подписчиков этого канала
                     [subscribe, schannel, ..]
-                        if *subscribe == "subscribe" && *schannel == channel => {}
+                        if *subscribe == "subscribe" && *schannel == channel.as_str() => {}
                     _ => return Err(response.to_error()),
                 },
                 frame => return Err(frame.to_error()),
@@ -361,8 +1828,8 @@ impl Client {
         debug!(?response);
 
         match response {
-            // Кадры `Error` преобразуются в `Err`
-            Some(Frame::Error(msg)) => Err(msg.into()),
+            // Кадры `Error` преобразуются в `Err` с категорией ошибки
+            Some(Frame::Error(msg)) => Err(ServerError::parse(msg).into()),
             Some(frame) => Ok(frame),
             None => {
                 // `None` - индикатор того, что сервер закрыл
@@ -398,6 +1865,8 @@ impl Subscriber {
                         })),
                         _ => Err(mframe.to_error()),
                     },
+                    // Например, уведомление о закрытии сервера
+                    Frame::Error(msg) => Err(ServerError::parse(msg).into()),
                     frame => Err(frame.to_error()),
                 }
             }
@@ -436,6 +1905,37 @@ impl Subscriber {
         Ok(())
     }
 
+    /// Отправляет команду отписки от всех каналов, не дожидаясь ответа сервера.
+    ///
+    /// Используется при уничтожении подписчика, когда ответы уже
+    /// никто не прочитает
+    pub(crate) async fn send_unsubscribe_all(&mut self) -> crate::Result<()> {
+        let frame = Unsubscribe::new(&[]).into_frame();
+
+        debug!(request = ?frame);
+
+        // Записываем кадр в сокет
+        self.client.connection.write_frame(&frame).await?;
+
+        Ok(())
+    }
+
+    /// Завершает сессию и закрывает соединение.
+    ///
+    /// Аналогичен `Client::close`. Сообщения, полученные до ответа сервера,
+    /// отбрасываются.
+    #[instrument(skip(self))]
+    pub async fn close(mut self) -> crate::Result<()> {
+        self.quit().await
+    }
+
+    /// Отправляет `QUIT` и закрывает соединение. После вызова подписчик
+    /// считается отписанным от всех каналов.
+    pub(crate) async fn quit(&mut self) -> crate::Result<()> {
+        self.subscribed_channels.clear();
+        self.client.quit().await
+    }
+
     /// Выполняет отписку от указанных каналов
     #[instrument(skip(self))]
     pub async fn unsubscribe(&mut self, channels: &[String]) -> crate::Result<()> {
@@ -485,3 +1985,111 @@ impl Subscriber {
         Ok(())
     }
 }
+
+/// Преобразует кадр ответа `KEYS` или `SCAN` в название ключа
+fn key_from_frame(frame: Frame) -> crate::Result<String> {
+    match frame {
+        Frame::Bulk(key) => String::from_utf8(key.to_vec()).map_err(|err| err.into()),
+        frame => Err(frame.to_error()),
+    }
+}
+
+/// Разбирает оценку элемента сортированного множества.
+fn score_from_frame(frame: Frame) -> crate::Result<f64> {
+    match frame {
+        Frame::Bulk(score) => Ok(std::str::from_utf8(&score)?.parse()?),
+        frame => Err(frame.to_error()),
+    }
+}
+
+/// Разбирает массив чередующихся элементов сортированного множества и их
+/// оценок, например, ответ на `ZRANGE ... WITHSCORES`.
+fn scores_reply(frame: Frame) -> crate::Result<Vec<(String, f64)>> {
+    let parts = match frame {
+        Frame::Array(parts) if parts.len() % 2 == 0 => parts,
+        frame => return Err(frame.to_error()),
+    };
+
+    let mut members = Vec::with_capacity(parts.len() / 2);
+    let mut parts = parts.into_iter();
+
+    while let (Some(member), Some(score)) = (parts.next(), parts.next()) {
+        members.push((key_from_frame(member)?, score_from_frame(score)?));
+    }
+
+    Ok(members)
+}
+
+/// Разбирает ответ на `HELLO`: массив чередующихся ключей и значений.
+fn hello_reply(frame: Frame) -> crate::Result<HashMap<String, Frame>> {
+    let parts = match frame {
+        Frame::Array(parts) if parts.len() % 2 == 0 => parts,
+        frame => return Err(frame.to_error()),
+    };
+
+    let mut info = HashMap::new();
+    let mut parts = parts.into_iter();
+
+    while let (Some(key), Some(value)) = (parts.next(), parts.next()) {
+        info.insert(key_from_frame(key)?, value);
+    }
+
+    Ok(info)
+}
+
+/// Разбирает ответ на `PING`.
+pub(crate) fn ping_reply(frame: Frame) -> crate::Result<Bytes> {
+    match frame {
+        Frame::Simple(value) => Ok(value.into()),
+        Frame::Bulk(value) => Ok(value),
+        frame => Err(frame.to_error()),
+    }
+}
+
+/// Разбирает ответ на `GET`.
+///
+/// Принимаются кадры `Simple` и `Bulk`. `Null` представляет
+/// отсутствующий ключ - возвращается `None`
+pub(crate) fn get_reply(frame: Frame) -> crate::Result<Option<Bytes>> {
+    match frame {
+        Frame::Simple(value) => Ok(Some(value.into())),
+        Frame::Bulk(value) => Ok(Some(value)),
+        Frame::Null => Ok(None),
+        frame => Err(frame.to_error()),
+    }
+}
+
+/// Разбирает ответ на `SET`.
+///
+/// При успехе сервер отвечает простым `OK`. Любой другой ответ означает ошибку
+pub(crate) fn set_reply(frame: Frame) -> crate::Result<()> {
+    match frame {
+        Frame::Simple(response) if response == "OK" => Ok(()),
+        frame => Err(frame.to_error()),
+    }
+}
+
+/// Разбирает ответ на `PUBLISH`: количество подписчиков канала.
+pub(crate) fn publish_reply(frame: Frame) -> crate::Result<u64> {
+    match frame {
+        Frame::Integer(response) if response >= 0 => Ok(response as u64),
+        frame => Err(frame.to_error()),
+    }
+}
+
+/// Разбирает целочисленный ответ, например, на `EXISTS`.
+pub(crate) fn integer_reply(frame: Frame) -> crate::Result<u64> {
+    match frame {
+        Frame::Integer(response) if response >= 0 => Ok(response as u64),
+        frame => Err(frame.to_error()),
+    }
+}
+
+/// Разбирает целочисленный ответ, который может быть отрицательным,
+/// например, на `INCR`.
+fn signed_integer_reply(frame: Frame) -> crate::Result<i64> {
+    match frame {
+        Frame::Integer(response) => Ok(response),
+        frame => Err(frame.to_error()),
+    }
+}
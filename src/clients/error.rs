@@ -0,0 +1,121 @@
+use std::fmt;
+
+/// Ошибка, полученная от сервера в ответ на команду.
+///
+/// Сервер сообщает об ошибке кадром `Error`, который начинается с префикса,
+/// определяющего категорию ошибки, например:
+///
+/// ```text
+/// -WRONGTYPE Operation against a key holding the wrong kind of value
+/// -MOVED 3999 127.0.0.1:6381
+/// ```
+///
+/// Клиенты возвращают `ServerError` в составе `crate::Error`. Для проверки
+/// категории ошибки используется `downcast_ref`.
+///
+/// # Примеры
+///
+/// ```no_run
+/// use mini_redis::clients::{Client, ErrorKind, ServerError};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let mut client = Client::connect("localhost:6379").await.unwrap();
+///
+///     if let Err(err) = client.get("foo").await {
+///         match err.downcast_ref::<ServerError>().map(ServerError::kind) {
+///             Some(ErrorKind::WrongType) => println!("`foo` не является строкой"),
+///             Some(ErrorKind::Moved { addr, .. }) => println!("ключ находится на {}", addr),
+///             _ => println!("ошибка: {}", err),
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerError {
+    kind: ErrorKind,
+    message: String,
+}
+
+/// Категория ошибки сервера.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// Операция применяется к ключу, содержащему значение другого типа
+    /// (`WRONGTYPE`).
+    WrongType,
+
+    /// Команда требует аутентификации (`NOAUTH`).
+    NoAuth,
+
+    /// Сервер закрывается (`SHUTDOWN`). Сервер отправляет эту ошибку всем
+    /// клиентам перед закрытием соединений, поэтому клиент может
+    /// переподключиться, не считая закрытие сетевой ошибкой.
+    Shutdown,
+
+    /// Ключ обслуживается другим узлом кластера (`MOVED`).
+    Moved {
+        /// Слот ключа.
+        slot: u16,
+
+        /// Адрес узла, обслуживающего слот.
+        addr: String,
+    },
+
+    /// Остальные ошибки, включая общие ошибки с префиксом `ERR`. Префикс
+    /// возвращается методом `ServerError::prefix`.
+    Unknown,
+}
+
+impl ServerError {
+    /// Разбирает сообщение кадра `Error`.
+    pub fn parse(message: impl ToString) -> ServerError {
+        let message = message.to_string();
+        let mut parts = message.split_whitespace();
+
+        let kind = match parts.next() {
+            Some("WRONGTYPE") => ErrorKind::WrongType,
+            Some("NOAUTH") => ErrorKind::NoAuth,
+            Some("SHUTDOWN") => ErrorKind::Shutdown,
+            Some("MOVED") => {
+                let slot = parts.next().and_then(|slot| slot.parse().ok());
+                let addr = parts.next();
+
+                match (slot, addr) {
+                    (Some(slot), Some(addr)) => ErrorKind::Moved {
+                        slot,
+                        addr: addr.to_string(),
+                    },
+                    // Невалидное перенаправление не может быть выполнено
+                    _ => ErrorKind::Unknown,
+                }
+            }
+            _ => ErrorKind::Unknown,
+        };
+
+        ServerError { kind, message }
+    }
+
+    /// Возвращает категорию ошибки.
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    /// Возвращает префикс ошибки, например, `ERR` или `WRONGTYPE`.
+    pub fn prefix(&self) -> &str {
+        self.message.split_whitespace().next().unwrap_or("")
+    }
+
+    /// Возвращает полное сообщение об ошибке.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for ServerError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.message.fmt(fmt)
+    }
+}
+
+impl std::error::Error for ServerError {}
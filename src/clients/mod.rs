@@ -1,8 +1,17 @@
-mod client;
+pub(crate) mod client;
 pub use client::{Client, Message, Subscriber};
 
 mod blocking_client;
 pub use blocking_client::BlockingClient;
 
 mod buffered_client;
-pub use buffered_client::BufferedClient;
+pub use buffered_client::{BufferedClient, ConnectionLost};
+
+mod error;
+pub use error::{ErrorKind, ServerError};
+
+mod sharded_client;
+pub use sharded_client::ShardedClient;
+
+mod redis_client;
+pub use redis_client::{BlockingRedisClient, RedisClient};
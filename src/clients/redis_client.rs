@@ -0,0 +1,166 @@
+//! Общие интерфейсы клиентов `Redis`.
+//!
+//! Код приложения, зависящий от `RedisClient` (или `BlockingRedisClient`), а не
+//! от конкретного клиента, может работать с `Client`, `BufferedClient` и
+//! `BlockingClient`, а в тестах - с `test_util::MockClient`, которому не
+//! требуется сервер.
+
+use crate::clients::{BlockingClient, BufferedClient, Client};
+
+use bytes::Bytes;
+use std::future::Future;
+use std::time::Duration;
+
+/// Асинхронный клиент `Redis`.
+///
+/// Реализуется `Client`, `BufferedClient` и `test_util::MockClient`.
+/// Семантика методов совпадает с одноименными методами `Client`.
+///
+/// # Примеры
+///
+/// ```no_run
+/// use mini_redis::clients::{Client, RedisClient};
+///
+/// // Функция не зависит от конкретного клиента
+/// async fn visit<C: RedisClient>(client: &mut C, page: &str) -> mini_redis::Result<()> {
+///     client.set(page, "visited".into()).await
+/// }
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let mut client = Client::connect("localhost:6379").await.unwrap();
+///     visit(&mut client, "/index").await.unwrap();
+/// }
+/// ```
+pub trait RedisClient {
+    /// "Пингует" сервер. См. `Client::ping`.
+    fn ping(&mut self, msg: Option<Bytes>) -> impl Future<Output = crate::Result<Bytes>> + Send;
+
+    /// Извлекает значение по ключу. См. `Client::get`.
+    fn get(&mut self, key: &str) -> impl Future<Output = crate::Result<Option<Bytes>>> + Send;
+
+    /// Устанавливает `value` для `key`. См. `Client::set`.
+    fn set(&mut self, key: &str, value: Bytes) -> impl Future<Output = crate::Result<()>> + Send;
+
+    /// Устанавливает `value` для `key` со временем жизни `expiration`.
+    /// См. `Client::set_expires`.
+    fn set_expires(
+        &mut self,
+        key: &str,
+        value: Bytes,
+        expiration: Duration,
+    ) -> impl Future<Output = crate::Result<()>> + Send;
+
+    /// Отправляет `message` в `channel`. См. `Client::publish`.
+    fn publish(
+        &mut self,
+        channel: &str,
+        message: Bytes,
+    ) -> impl Future<Output = crate::Result<u64>> + Send;
+}
+
+/// Блокирующий клиент `Redis`.
+///
+/// Реализуется `BlockingClient` и `test_util::MockClient`. Семантика методов
+/// совпадает с одноименными методами `Client`.
+pub trait BlockingRedisClient {
+    /// "Пингует" сервер. См. `Client::ping`.
+    fn ping(&mut self, msg: Option<Bytes>) -> crate::Result<Bytes>;
+
+    /// Извлекает значение по ключу. См. `Client::get`.
+    fn get(&mut self, key: &str) -> crate::Result<Option<Bytes>>;
+
+    /// Устанавливает `value` для `key`. См. `Client::set`.
+    fn set(&mut self, key: &str, value: Bytes) -> crate::Result<()>;
+
+    /// Устанавливает `value` для `key` со временем жизни `expiration`.
+    /// См. `Client::set_expires`.
+    fn set_expires(&mut self, key: &str, value: Bytes, expiration: Duration) -> crate::Result<()>;
+
+    /// Отправляет `message` в `channel`. См. `Client::publish`.
+    fn publish(&mut self, channel: &str, message: Bytes) -> crate::Result<u64>;
+}
+
+impl RedisClient for Client {
+    fn ping(&mut self, msg: Option<Bytes>) -> impl Future<Output = crate::Result<Bytes>> + Send {
+        Client::ping(self, msg)
+    }
+
+    fn get(&mut self, key: &str) -> impl Future<Output = crate::Result<Option<Bytes>>> + Send {
+        Client::get(self, key)
+    }
+
+    fn set(&mut self, key: &str, value: Bytes) -> impl Future<Output = crate::Result<()>> + Send {
+        Client::set(self, key, value)
+    }
+
+    fn set_expires(
+        &mut self,
+        key: &str,
+        value: Bytes,
+        expiration: Duration,
+    ) -> impl Future<Output = crate::Result<()>> + Send {
+        Client::set_expires(self, key, value, expiration)
+    }
+
+    fn publish(
+        &mut self,
+        channel: &str,
+        message: Bytes,
+    ) -> impl Future<Output = crate::Result<u64>> + Send {
+        Client::publish(self, channel, message)
+    }
+}
+
+impl RedisClient for BufferedClient {
+    fn ping(&mut self, msg: Option<Bytes>) -> impl Future<Output = crate::Result<Bytes>> + Send {
+        BufferedClient::ping(self, msg)
+    }
+
+    fn get(&mut self, key: &str) -> impl Future<Output = crate::Result<Option<Bytes>>> + Send {
+        BufferedClient::get(self, key)
+    }
+
+    fn set(&mut self, key: &str, value: Bytes) -> impl Future<Output = crate::Result<()>> + Send {
+        BufferedClient::set(self, key, value)
+    }
+
+    fn set_expires(
+        &mut self,
+        key: &str,
+        value: Bytes,
+        expiration: Duration,
+    ) -> impl Future<Output = crate::Result<()>> + Send {
+        BufferedClient::set_expires(self, key, value, expiration)
+    }
+
+    fn publish(
+        &mut self,
+        channel: &str,
+        message: Bytes,
+    ) -> impl Future<Output = crate::Result<u64>> + Send {
+        BufferedClient::publish(self, channel, message)
+    }
+}
+
+impl BlockingRedisClient for BlockingClient {
+    fn ping(&mut self, msg: Option<Bytes>) -> crate::Result<Bytes> {
+        BlockingClient::ping(self, msg)
+    }
+
+    fn get(&mut self, key: &str) -> crate::Result<Option<Bytes>> {
+        BlockingClient::get(self, key)
+    }
+
+    fn set(&mut self, key: &str, value: Bytes) -> crate::Result<()> {
+        BlockingClient::set(self, key, value)
+    }
+
+    fn set_expires(&mut self, key: &str, value: Bytes, expiration: Duration) -> crate::Result<()> {
+        BlockingClient::set_expires(self, key, value, expiration)
+    }
+
+    fn publish(&mut self, channel: &str, message: Bytes) -> crate::Result<u64> {
+        BlockingClient::publish(self, channel, message)
+    }
+}
@@ -0,0 +1,223 @@
+use crate::clients::{Client, ServerError};
+use crate::ring::Ring;
+
+use bytes::Bytes;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinSet;
+
+/// Максимальное количество простаивающих соединений с одним сервером.
+const MAX_IDLE_CONNECTIONS: usize = 8;
+
+/// Клиент, распределяющий ключи между несколькими серверами.
+///
+/// Сервер каждого ключа определяется согласованным хешированием по
+/// статическому списку адресов, поэтому поддержка кластерного протокола
+/// серверами не требуется. Распределение ключей совпадает с распределением
+/// прокси `proxy::shard` с теми же серверами.
+///
+/// Соединения с каждым сервером объединяются в пул: соединение
+/// устанавливается при первом обращении к серверу и возвращается в пул после
+/// выполнения команды. Методы принимают `&self`, а клоны `ShardedClient`
+/// используют общие пулы, поэтому клиент может использоваться конкурентно из
+/// нескольких задач.
+///
+/// # Примеры
+///
+/// ```no_run
+/// use mini_redis::clients::ShardedClient;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let client = ShardedClient::new(vec![
+///         "127.0.0.1:6379".parse().unwrap(),
+///         "127.0.0.1:6380".parse().unwrap(),
+///     ]);
+///
+///     client.set("foo", "bar".into()).await.unwrap();
+///     client.set("baz", "qux".into()).await.unwrap();
+///
+///     let values = client.mget(&["foo", "baz", "missing"]).await.unwrap();
+///     assert_eq!(values, vec![Some("bar".into()), Some("qux".into()), None]);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ShardedClient {
+    /// Кольцо согласованного хеширования.
+    ring: Arc<Ring>,
+
+    /// Серверы в порядке, в котором они были переданы.
+    shards: Arc<[Shard]>,
+}
+
+/// Сервер и пул соединений с ним.
+#[derive(Debug)]
+struct Shard {
+    /// Адрес сервера.
+    addr: SocketAddr,
+
+    /// Простаивающие соединения.
+    idle: Mutex<Vec<Client>>,
+}
+
+impl ShardedClient {
+    /// Создает клиента для серверов `addrs`.
+    ///
+    /// Соединения устанавливаются при первом обращении к серверу, поэтому
+    /// недоступность сервера обнаруживается при выполнении команды.
+    ///
+    /// # Паники
+    ///
+    /// Паникует, если `addrs` пуст.
+    pub fn new(addrs: Vec<SocketAddr>) -> ShardedClient {
+        assert!(!addrs.is_empty(), "Не указан ни один сервер");
+
+        let ring = Arc::new(Ring::new(&addrs));
+        let shards = addrs
+            .into_iter()
+            .map(|addr| Shard {
+                addr,
+                idle: Mutex::new(vec![]),
+            })
+            .collect();
+
+        ShardedClient { ring, shards }
+    }
+
+    /// Возвращает адрес сервера, которому принадлежит `key`.
+    pub fn addr(&self, key: &str) -> SocketAddr {
+        self.shard(key).addr
+    }
+
+    /// Извлекает значение по ключу. См. `Client::get`.
+    pub async fn get(&self, key: &str) -> crate::Result<Option<Bytes>> {
+        let shard = self.shard(key);
+
+        let mut client = shard.checkout().await?;
+        let res = client.get(key).await;
+        shard.checkin(client, &res);
+
+        res
+    }
+
+    /// Устанавливает `value` для `key`. См. `Client::set`.
+    pub async fn set(&self, key: &str, value: Bytes) -> crate::Result<()> {
+        let shard = self.shard(key);
+
+        let mut client = shard.checkout().await?;
+        let res = client.set(key, value).await;
+        shard.checkin(client, &res);
+
+        res
+    }
+
+    /// Устанавливает `value` для `key` со временем жизни `expiration`.
+    /// См. `Client::set_expires`.
+    pub async fn set_expires(
+        &self,
+        key: &str,
+        value: Bytes,
+        expiration: Duration,
+    ) -> crate::Result<()> {
+        let shard = self.shard(key);
+
+        let mut client = shard.checkout().await?;
+        let res = client.set_expires(key, value, expiration).await;
+        shard.checkin(client, &res);
+
+        res
+    }
+
+    /// Извлекает значения нескольких ключей.
+    ///
+    /// Ключи группируются по серверам, и запросы к разным серверам
+    /// выполняются конкурентно. Значения возвращаются в порядке `keys`,
+    /// отсутствующим ключам соответствует `None`. Если запрос к одному из
+    /// серверов завершился ошибкой, возвращается `Err`.
+    pub async fn mget(&self, keys: &[&str]) -> crate::Result<Vec<Option<Bytes>>> {
+        // Позиции ключей в `keys` для каждого сервера
+        let mut positions = vec![vec![]; self.shards.len()];
+        for (pos, key) in keys.iter().enumerate() {
+            positions[self.ring.get(key.as_bytes())].push(pos);
+        }
+
+        let mut requests = JoinSet::new();
+
+        for (idx, positions) in positions.into_iter().enumerate() {
+            if positions.is_empty() {
+                continue;
+            }
+
+            let shards = self.shards.clone();
+            let keys: Vec<String> = positions.iter().map(|&pos| keys[pos].to_string()).collect();
+
+            requests.spawn(async move {
+                let shard = &shards[idx];
+                let mut client = shard.checkout().await?;
+
+                // Ключи одного сервера запрашиваются одной командой `MGET`
+                let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+                let res = client.mget(&keys).await;
+                shard.checkin(client, &res);
+
+                res.map(|values| (positions, values))
+            });
+        }
+
+        let mut values = vec![None; keys.len()];
+        while let Some(res) = requests.join_next().await {
+            let (positions, shard_values) = res??;
+
+            for (pos, value) in positions.into_iter().zip(shard_values) {
+                values[pos] = value;
+            }
+        }
+
+        Ok(values)
+    }
+
+    /// Возвращает сервер, которому принадлежит `key`.
+    fn shard(&self, key: &str) -> &Shard {
+        &self.shards[self.ring.get(key.as_bytes())]
+    }
+}
+
+impl Shard {
+    /// Извлекает соединение из пула или устанавливает новое.
+    ///
+    /// Соединения, закрытые сервером за время простоя (например, при его
+    /// перезапуске), отбрасываются.
+    async fn checkout(&self) -> crate::Result<Client> {
+        loop {
+            let idle = self.idle.lock().unwrap().pop();
+
+            match idle {
+                Some(mut client) => {
+                    if client.is_connected() {
+                        return Ok(client);
+                    }
+                }
+                None => return Client::connect(self.addr).await,
+            }
+        }
+    }
+
+    /// Возвращает соединение в пул после выполнения команды с результатом
+    /// `res`.
+    ///
+    /// После ошибки сервера соединение остается пригодным, после остальных
+    /// ошибок (например, разрыва соединения) оно закрывается.
+    fn checkin<T>(&self, client: Client, res: &crate::Result<T>) {
+        if let Err(err) = res {
+            if err.downcast_ref::<ServerError>().is_none() {
+                return;
+            }
+        }
+
+        let mut idle = self.idle.lock().unwrap();
+        if idle.len() < MAX_IDLE_CONNECTIONS {
+            idle.push(client);
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use crate::cmd::Parse;
+use crate::{Connection, Frame, Session};
+
+use bytes::Bytes;
+use tracing::{debug, info, instrument};
+
+/// Чтение и изменение параметров сервера во время работы.
+///
+/// Поддерживается параметр `loglevel` - директивы фильтра логов в формате
+/// `RUST_LOG`, например, `debug` или `mini_redis=trace,info`. Параметр
+/// изменяется, если сервер запущен с фильтром (см. `Builder::log_filter`).
+#[derive(Debug)]
+pub struct Config {
+    /// Подкоманда
+    subcommand: Subcommand,
+}
+
+/// Подкоманда `CONFIG`.
+#[derive(Debug)]
+enum Subcommand {
+    /// Возвращает значение параметра
+    Get(String),
+
+    /// Устанавливает значение параметра
+    Set(String, String),
+}
+
+impl Config {
+    /// Разбирает экземпляр `Config` из полученного кадра.
+    ///
+    /// Строка `CONFIG` уже потреблена.
+    ///
+    /// # Возвращаемые значения
+    ///
+    /// Возвращает значение `Config` при успехе. Если кадр испорчен или
+    /// подкоманда не поддерживается, возвращается `Err`.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий `CONFIG`, подкоманду и ее аргументы:
+    ///
+    /// ```text
+    /// CONFIG GET parameter
+    /// CONFIG SET parameter value
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Config> {
+        let subcommand = parse.next_string()?;
+
+        let subcommand = match &subcommand.to_uppercase()[..] {
+            "GET" => Subcommand::Get(parse.next_string()?),
+            "SET" => Subcommand::Set(parse.next_string()?, parse.next_string()?),
+            _ => {
+                return Err(format!("`CONFIG` не поддерживает подкоманду `{}`.", subcommand).into())
+            }
+        };
+
+        Ok(Config { subcommand })
+    }
+
+    /// Применяет команду `Config`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды
+    #[instrument(skip(self, dst, session))]
+    pub(crate) async fn apply(
+        self,
+        dst: &mut Connection,
+        session: &mut Session,
+    ) -> crate::Result<()> {
+        let response = match self.subcommand {
+            Subcommand::Get(parameter) => {
+                let mut response = Frame::array();
+
+                // Как и `Redis`, для неизвестного параметра возвращаем
+                // пустой массив
+                if parameter.eq_ignore_ascii_case("loglevel") {
+                    if let Some(filter) = session.log_filter() {
+                        response.push_bulk(Bytes::from("loglevel"));
+                        response.push_bulk(Bytes::from(filter.directives()));
+                    }
+                }
+
+                response
+            }
+            Subcommand::Set(parameter, value) if parameter.eq_ignore_ascii_case("loglevel") => {
+                match session.log_filter() {
+                    Some(filter) => match filter.set(&value) {
+                        Ok(()) => {
+                            info!(directives = %value, "Уровень логирования изменен");
+                            Frame::Simple("OK".to_string())
+                        }
+                        Err(_) => Frame::Error(format!(
+                            "ERR Invalid argument '{}' for CONFIG SET 'loglevel'",
+                            value
+                        )),
+                    },
+                    None => Frame::Error(
+                        "ERR CONFIG SET 'loglevel' is not supported by this server".to_string(),
+                    ),
+                }
+            }
+            Subcommand::Set(parameter, _) => Frame::Error(format!(
+                "ERR Unknown option or number of arguments for CONFIG SET - '{}'",
+                parameter
+            )),
+        };
+
+        debug!(?response);
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
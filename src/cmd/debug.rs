@@ -0,0 +1,148 @@
+use crate::cmd::Parse;
+use crate::{Connection, Db, Frame};
+
+#[cfg(feature = "chaos")]
+use std::time::Duration;
+use tracing::{debug, instrument};
+
+/// Отладочные команды сервера.
+///
+/// Поддерживается подкоманда `SET-ACTIVE-EXPIRE`, включающая и отключающая
+/// удаление истекших ключей фоновой задачей (см. `Db::set_active_expire`).
+///
+/// С флагом `chaos` также поддерживается подкоманда `CHAOS`, настраивающая
+/// внедряемые сбои (см. `crate::chaos`):
+///
+/// * `LATENCY ms` - максимальная задержка выполнения команды;
+/// * `DROP percent` - вероятность разрыва соединения;
+/// * `LAG percent` - вероятность потери сообщения подписчиком;
+/// * `OFF` - отключение всех сбоев.
+#[derive(Debug)]
+pub struct Debug {
+    /// Подкоманда
+    subcommand: Subcommand,
+}
+
+/// Подкоманда `DEBUG`.
+#[derive(Debug)]
+enum Subcommand {
+    /// Включает (`true`) или отключает (`false`) фоновую очистку истекших ключей
+    SetActiveExpire(bool),
+
+    /// Настраивает внедряемые сбои
+    #[cfg(feature = "chaos")]
+    Chaos(Fault),
+}
+
+/// Сбой, настраиваемый подкомандой `DEBUG CHAOS`.
+#[cfg(feature = "chaos")]
+#[derive(Debug)]
+enum Fault {
+    /// Максимальная задержка выполнения команды
+    Latency(Duration),
+
+    /// Вероятность разрыва соединения в процентах
+    Drop(u8),
+
+    /// Вероятность потери сообщения подписчиком в процентах
+    Lag(u8),
+
+    /// Отключение всех сбоев
+    Off,
+}
+
+impl Debug {
+    /// Создает команду `DEBUG SET-ACTIVE-EXPIRE`.
+    pub fn set_active_expire(enabled: bool) -> Debug {
+        Debug {
+            subcommand: Subcommand::SetActiveExpire(enabled),
+        }
+    }
+
+    /// Разбирает экземпляр `Debug` из полученного кадра.
+    ///
+    /// Строка `DEBUG` уже потреблена.
+    ///
+    /// # Возвращаемые значения
+    ///
+    /// Возвращает значение `Debug` при успехе. Если кадр испорчен или
+    /// подкоманда не поддерживается, возвращается `Err`.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий `DEBUG`, подкоманду и ее аргументы:
+    ///
+    /// ```text
+    /// DEBUG SET-ACTIVE-EXPIRE 0|1
+    /// DEBUG CHAOS LATENCY ms | DROP percent | LAG percent | OFF
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Debug> {
+        let subcommand = parse.next_string()?;
+
+        match &subcommand.to_uppercase()[..] {
+            "SET-ACTIVE-EXPIRE" => match parse.next_int()? {
+                0 => Ok(Debug::set_active_expire(false)),
+                1 => Ok(Debug::set_active_expire(true)),
+                value => Err(format!(
+                    "`DEBUG SET-ACTIVE-EXPIRE` ожидает `0` или `1`, получено `{}`.",
+                    value
+                )
+                .into()),
+            },
+            #[cfg(feature = "chaos")]
+            "CHAOS" => Ok(Debug {
+                subcommand: Subcommand::Chaos(parse_fault(parse)?),
+            }),
+            _ => Err(format!("`DEBUG` не поддерживает подкоманду `{}`.", subcommand).into()),
+        }
+    }
+
+    /// Применяет команду `Debug` к БД.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        match self.subcommand {
+            Subcommand::SetActiveExpire(enabled) => db.set_active_expire(enabled),
+            #[cfg(feature = "chaos")]
+            Subcommand::Chaos(fault) => match fault {
+                Fault::Latency(max) => db.chaos().set_max_latency(max),
+                Fault::Drop(percent) => db.chaos().set_drop_percent(percent),
+                Fault::Lag(percent) => db.chaos().set_lag_percent(percent),
+                Fault::Off => db.chaos().reset(),
+            },
+        }
+
+        let response = Frame::Simple("OK".to_string());
+        debug!(?response);
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// Разбирает аргументы подкоманды `DEBUG CHAOS`.
+#[cfg(feature = "chaos")]
+fn parse_fault(parse: &mut Parse) -> crate::Result<Fault> {
+    let fault = parse.next_string()?;
+
+    let percent = |parse: &mut Parse| -> crate::Result<u8> {
+        match parse.next_int()? {
+            percent @ 0..=100 => Ok(percent as u8),
+            value => Err(format!(
+                "`DEBUG CHAOS {}` ожидает значение от `0` до `100`, получено `{}`.",
+                fault, value
+            )
+            .into()),
+        }
+    };
+
+    match &fault.to_uppercase()[..] {
+        "LATENCY" => Ok(Fault::Latency(Duration::from_millis(parse.next_int()?))),
+        "DROP" => Ok(Fault::Drop(percent(parse)?)),
+        "LAG" => Ok(Fault::Lag(percent(parse)?)),
+        "OFF" => Ok(Fault::Off),
+        _ => Err(format!("`DEBUG CHAOS` не поддерживает сбой `{}`.", fault).into()),
+    }
+}
@@ -0,0 +1,255 @@
+use crate::cmd::expire;
+use crate::parse::ParseError;
+use crate::{Connection, Db, Frame, Parse};
+
+use bytes::Bytes;
+use std::convert::TryFrom;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{debug, instrument};
+
+/// Возвращает сериализованное значение ключа.
+///
+/// Формат описан в модуле `serialize`: значение содержит версию формата и
+/// контрольную сумму и восстанавливается командой `RESTORE`. Время жизни не
+/// сериализуется. При отсутствии ключа возвращается `nil`
+#[derive(Debug)]
+pub struct Dump {
+    /// Ключ
+    key: String,
+}
+
+/// Создает ключ из значения, сериализованного командой `DUMP`.
+///
+/// Время жизни задается в миллисекундах, `0` означает отсутствие времени
+/// жизни. С настройкой `ABSTTL` время жизни является моментом истечения в
+/// миллисекундах с начала эпохи Unix. Существующий ключ заменяется только с
+/// настройкой `REPLACE`
+#[derive(Debug)]
+pub struct Restore {
+    /// Ключ
+    key: String,
+
+    /// Время жизни или момент истечения в миллисекундах
+    ttl: i64,
+
+    /// Сериализованное значение
+    payload: Bytes,
+
+    /// Заменять ли существующий ключ
+    replace: bool,
+
+    /// Является ли `ttl` моментом истечения
+    absttl: bool,
+}
+
+impl Dump {
+    /// Создает новую команду `Dump`, запрашивающую сериализованное значение
+    /// `key`
+    pub fn new(key: impl ToString) -> Dump {
+        Dump {
+            key: key.to_string(),
+        }
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `Dump` из полученного кадра.
+    ///
+    /// Строка `DUMP` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 2 сущности:
+    ///
+    /// ```text
+    /// DUMP key
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Dump> {
+        let key = parse.next_string()?;
+
+        Ok(Dump { key })
+    }
+
+    /// Применяет команду `Dump` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = match db.dump(&self.key) {
+            Some(payload) => Frame::Bulk(payload),
+            None => Frame::Null,
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `Dump`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("dump".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        frame
+    }
+}
+
+impl Restore {
+    /// Создает новую команду `Restore`, создающую `key` из `payload` с
+    /// временем жизни `ttl`
+    pub fn new(key: impl ToString, ttl: Option<Duration>, payload: Bytes) -> Restore {
+        // Время жизни меньше миллисекунды округляется вверх, чтобы ключ не
+        // стал бессрочным
+        let ttl = ttl.map_or(0, |ttl| {
+            i64::try_from(ttl.as_millis()).unwrap_or(i64::MAX).max(1)
+        });
+
+        Restore {
+            key: key.to_string(),
+            ttl,
+            payload,
+            replace: false,
+            absttl: false,
+        }
+    }
+
+    /// Заменять существующий ключ (`REPLACE`)
+    pub fn with_replace(mut self, replace: bool) -> Restore {
+        self.replace = replace;
+        self
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `Restore` из полученного кадра.
+    ///
+    /// Строка `RESTORE` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий как минимум 4 сущности:
+    ///
+    /// ```text
+    /// RESTORE key ttl payload [REPLACE] [ABSTTL]
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Restore> {
+        use ParseError::EndOfStream;
+
+        let key = parse.next_string()?;
+        let ttl = parse.next_signed_int()?;
+        let payload = parse.next_bytes()?;
+
+        let mut restore = Restore {
+            key,
+            ttl,
+            payload,
+            replace: false,
+            absttl: false,
+        };
+
+        loop {
+            let option = match parse.next_string() {
+                Ok(option) => option.to_uppercase(),
+                Err(EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            };
+
+            match &option[..] {
+                "REPLACE" => restore.replace = true,
+                "ABSTTL" => restore.absttl = true,
+                _ => {
+                    return Err(format!(
+                        "Ошибка протокола; недопустимая настройка `RESTORE`: {}",
+                        option
+                    )
+                    .into())
+                }
+            }
+        }
+
+        Ok(restore)
+    }
+
+    /// Применяет команду `Restore` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды.
+    ///
+    /// Возвращает `true`, если команда изменила данные
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<bool> {
+        let (response, written) = match self.expires_at() {
+            Ok(expires_at) => match db.restore(self.key, &self.payload, expires_at, self.replace) {
+                Ok(()) => (Frame::Simple("OK".to_string()), true),
+                Err(err) => (Frame::Error(err.to_string()), false),
+            },
+            Err(response) => (response, false),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(written)
+    }
+
+    /// Возвращает момент истечения ключа или ответ с ошибкой
+    fn expires_at(&self) -> Result<Option<SystemTime>, Frame> {
+        let ttl = match u64::try_from(self.ttl) {
+            Ok(0) => return Ok(None),
+            Ok(ttl) => Duration::from_millis(ttl),
+            Err(_) => {
+                return Err(Frame::Error(
+                    "ERR Invalid TTL value, must be >= 0".to_string(),
+                ))
+            }
+        };
+
+        let since_epoch = if self.absttl {
+            Some(ttl)
+        } else {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .checked_add(ttl)
+        };
+
+        match since_epoch.and_then(expire::deadline) {
+            Some(when) => Ok(Some(when)),
+            None => Err(Frame::Error(
+                "ERR invalid expire time in 'restore' command".to_string(),
+            )),
+        }
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `Restore`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("restore".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        frame.push_int(self.ttl);
+        frame.push_bulk(self.payload);
+        if self.replace {
+            frame.push_bulk(Bytes::from("replace".as_bytes()));
+        }
+        if self.absttl {
+            frame.push_bulk(Bytes::from("absttl".as_bytes()));
+        }
+        frame
+    }
+}
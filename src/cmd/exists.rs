@@ -0,0 +1,82 @@
+use crate::{Connection, Db, Frame, Parse, ParseError};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// Возвращает количество существующих ключей.
+///
+/// Ключ, указанный несколько раз, учитывается каждый раз. Истекшие, но еще
+/// не удаленные ключи не учитываются
+#[derive(Debug)]
+pub struct Exists {
+    /// Проверяемые ключи
+    keys: Vec<String>,
+}
+
+impl Exists {
+    /// Создает новую команду `Exists`, проверяющую `keys`
+    pub fn new(keys: &[impl ToString]) -> Exists {
+        Exists {
+            keys: keys.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    /// Возвращает ключи
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    /// Разбирает экземпляр `Exists` из полученного кадра.
+    ///
+    /// Строка `EXISTS` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 2 и более сущности:
+    ///
+    /// ```text
+    /// EXISTS key [key ...]
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Exists> {
+        // Должен быть указан хотя бы один ключ
+        let mut keys = vec![parse.next_string()?];
+
+        loop {
+            match parse.next_string() {
+                Ok(key) => keys.push(key),
+                Err(ParseError::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(Exists { keys })
+    }
+
+    /// Применяет команду `Exists` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = Frame::Integer(db.exists(&self.keys) as i64);
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `Exists`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("exists".as_bytes()));
+        for key in self.keys {
+            frame.push_bulk(Bytes::from(key.into_bytes()));
+        }
+        frame
+    }
+}
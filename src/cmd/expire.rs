@@ -0,0 +1,339 @@
+use crate::{Connection, Db, Frame, Parse};
+
+use bytes::Bytes;
+use std::convert::TryFrom;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::{debug, instrument};
+
+/// Устанавливает время жизни существующего ключа в секундах.
+///
+/// Предыдущее время жизни заменяется. Ключ с неположительным временем жизни
+/// удаляется. Возвращается `1`, если время жизни установлено, и `0`, если
+/// ключа нет
+#[derive(Debug)]
+pub struct Expire {
+    /// Ключ
+    key: String,
+
+    /// Время жизни в секундах
+    seconds: i64,
+}
+
+/// Устанавливает время жизни существующего ключа в миллисекундах.
+///
+/// Работает так же, как `Expire`
+#[derive(Debug)]
+pub struct Pexpire {
+    /// Ключ
+    key: String,
+
+    /// Время жизни в миллисекундах
+    milliseconds: i64,
+}
+
+/// Устанавливает момент истечения существующего ключа в виде Unix-времени
+/// в секундах.
+///
+/// Предыдущее время жизни заменяется. Ключ с моментом истечения в прошлом
+/// удаляется. Возвращается `1`, если время жизни установлено, и `0`, если
+/// ключа нет
+#[derive(Debug)]
+pub struct ExpireAt {
+    /// Ключ
+    key: String,
+
+    /// Момент истечения в секундах с начала эпохи Unix
+    timestamp: i64,
+}
+
+/// Устанавливает момент истечения существующего ключа в виде Unix-времени
+/// в миллисекундах.
+///
+/// Работает так же, как `ExpireAt`
+#[derive(Debug)]
+pub struct PexpireAt {
+    /// Ключ
+    key: String,
+
+    /// Момент истечения в миллисекундах с начала эпохи Unix
+    timestamp: i64,
+}
+
+impl Expire {
+    /// Создает новую команду `Expire`, устанавливающую время жизни `key`
+    /// в `seconds` секунд
+    pub fn new(key: impl ToString, seconds: i64) -> Expire {
+        Expire {
+            key: key.to_string(),
+            seconds,
+        }
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `Expire` из полученного кадра.
+    ///
+    /// Строка `EXPIRE` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 3 сущности:
+    ///
+    /// ```text
+    /// EXPIRE key seconds
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Expire> {
+        let key = parse.next_string()?;
+        let seconds = parse.next_signed_int()?;
+
+        Ok(Expire { key, seconds })
+    }
+
+    /// Применяет команду `Expire` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды.
+    ///
+    /// Возвращает `true`, если команда изменила данные
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<bool> {
+        let expire = u64::try_from(self.seconds).map(Duration::from_secs);
+        let response = apply_expire(db, &self.key, expire, "expire");
+
+        let written = response == Frame::Integer(1);
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(written)
+    }
+}
+
+impl Pexpire {
+    /// Создает новую команду `Pexpire`, устанавливающую время жизни `key`
+    /// в `milliseconds` миллисекунд
+    pub fn new(key: impl ToString, milliseconds: i64) -> Pexpire {
+        Pexpire {
+            key: key.to_string(),
+            milliseconds,
+        }
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `Pexpire` из полученного кадра.
+    ///
+    /// Строка `PEXPIRE` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 3 сущности:
+    ///
+    /// ```text
+    /// PEXPIRE key milliseconds
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Pexpire> {
+        let key = parse.next_string()?;
+        let milliseconds = parse.next_signed_int()?;
+
+        Ok(Pexpire { key, milliseconds })
+    }
+
+    /// Применяет команду `Pexpire` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды.
+    ///
+    /// Возвращает `true`, если команда изменила данные
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<bool> {
+        let expire = u64::try_from(self.milliseconds).map(Duration::from_millis);
+        let response = apply_expire(db, &self.key, expire, "pexpire");
+
+        let written = response == Frame::Integer(1);
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(written)
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `Pexpire`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("pexpire".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        frame.push_bulk(Bytes::from(self.milliseconds.to_string()));
+        frame
+    }
+}
+
+impl ExpireAt {
+    /// Создает новую команду `ExpireAt`, устанавливающую момент истечения
+    /// `key` в `timestamp` секунд с начала эпохи Unix
+    pub fn new(key: impl ToString, timestamp: i64) -> ExpireAt {
+        ExpireAt {
+            key: key.to_string(),
+            timestamp,
+        }
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `ExpireAt` из полученного кадра.
+    ///
+    /// Строка `EXPIREAT` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 3 сущности:
+    ///
+    /// ```text
+    /// EXPIREAT key unix-time-seconds
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<ExpireAt> {
+        let key = parse.next_string()?;
+        let timestamp = parse.next_signed_int()?;
+
+        Ok(ExpireAt { key, timestamp })
+    }
+
+    /// Применяет команду `ExpireAt` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды.
+    ///
+    /// Возвращает `true`, если команда изменила данные
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<bool> {
+        let since_epoch = u64::try_from(self.timestamp).map(Duration::from_secs);
+        let response = apply_expire_at(db, &self.key, since_epoch, "expireat");
+
+        let written = response == Frame::Integer(1);
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(written)
+    }
+}
+
+impl PexpireAt {
+    /// Создает новую команду `PexpireAt`, устанавливающую момент истечения
+    /// `key` в `timestamp` миллисекунд с начала эпохи Unix
+    pub fn new(key: impl ToString, timestamp: i64) -> PexpireAt {
+        PexpireAt {
+            key: key.to_string(),
+            timestamp,
+        }
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `PexpireAt` из полученного кадра.
+    ///
+    /// Строка `PEXPIREAT` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 3 сущности:
+    ///
+    /// ```text
+    /// PEXPIREAT key unix-time-milliseconds
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<PexpireAt> {
+        let key = parse.next_string()?;
+        let timestamp = parse.next_signed_int()?;
+
+        Ok(PexpireAt { key, timestamp })
+    }
+
+    /// Применяет команду `PexpireAt` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды.
+    ///
+    /// Возвращает `true`, если команда изменила данные
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<bool> {
+        let since_epoch = u64::try_from(self.timestamp).map(Duration::from_millis);
+        let response = apply_expire_at(db, &self.key, since_epoch, "pexpireat");
+
+        let written = response == Frame::Integer(1);
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(written)
+    }
+}
+
+/// Устанавливает время жизни ключа и возвращает ответ команды `cmd`.
+///
+/// Отрицательное время жизни (`Err`) удаляет ключ так же, как нулевое
+fn apply_expire<E>(db: &Db, key: &str, expire: Result<Duration, E>, cmd: &str) -> Frame {
+    let expire = expire.unwrap_or_default();
+
+    // Момент истечения должен быть представим как `Instant` и `SystemTime`,
+    // иначе `Db::expire` паникует
+    if Instant::now().checked_add(expire).is_none()
+        || SystemTime::now().checked_add(expire).is_none()
+    {
+        return invalid_expire_time(cmd);
+    }
+
+    Frame::Integer(i64::from(db.expire(key, expire)))
+}
+
+/// Устанавливает момент истечения ключа и возвращает ответ команды `cmd`.
+///
+/// Отрицательный момент (`Err`) находится в прошлом и удаляет ключ так же,
+/// как начало эпохи Unix
+fn apply_expire_at<E>(db: &Db, key: &str, since_epoch: Result<Duration, E>, cmd: &str) -> Frame {
+    match deadline(since_epoch.unwrap_or_default()) {
+        Some(when) => Frame::Integer(i64::from(db.expire_at(key, when))),
+        None => invalid_expire_time(cmd),
+    }
+}
+
+/// Возвращает момент истечения, отстоящий от начала эпохи Unix на
+/// `since_epoch`.
+///
+/// Возвращает `None`, если момент не может быть передан в `Db::expire_at`
+/// или `Db::restore` без паники
+pub(super) fn deadline(since_epoch: Duration) -> Option<SystemTime> {
+    // Как и в Redis, момент истечения в миллисекундах должен помещаться в
+    // `i64`
+    i64::try_from(since_epoch.as_millis()).ok()?;
+
+    // Момент истечения должен быть представим как `SystemTime` и `Instant`
+    let when = UNIX_EPOCH.checked_add(since_epoch)?;
+    let remaining = when.duration_since(SystemTime::now()).unwrap_or_default();
+    Instant::now().checked_add(remaining)?;
+
+    Some(when)
+}
+
+/// Возвращает ошибку недопустимого времени жизни в команде `cmd`
+fn invalid_expire_time(cmd: &str) -> Frame {
+    Frame::Error(format!("ERR invalid expire time in '{}' command", cmd))
+}
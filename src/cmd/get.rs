@@ -62,12 +62,13 @@ impl Get {
     #[instrument(skip(self, db, dst))]
     pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
         // Извлекаем значение из общего состояния БД
-        let response = if let Some(value) = db.get(&self.key) {
+        let response = match db.get_string(&self.key) {
             // Если значение имеется, оно возвращается клиенту в "групповом" формате
-            Frame::Bulk(value)
-        } else {
+            Ok(Some(value)) => Frame::Bulk(value),
             // При отсутствии значения возвращается `Null`
-            Frame::Null
+            Ok(None) => Frame::Null,
+            // Значение не является строкой
+            Err(err) => Frame::Error(err.to_string()),
         };
 
         debug!(?response);
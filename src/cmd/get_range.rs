@@ -0,0 +1,74 @@
+use crate::{Connection, Db, Frame, Parse};
+
+use tracing::{debug, instrument};
+
+/// Возвращает часть строки, хранящейся по ключу.
+///
+/// Границы `start` и `end` включаются в результат, отрицательные индексы
+/// отсчитываются от конца строки. При отсутствии ключа возвращается пустая
+/// строка. Команда также доступна под устаревшим названием `SUBSTR`
+#[derive(Debug)]
+pub struct GetRange {
+    /// Ключ
+    key: String,
+
+    /// Индекс первого байта
+    start: i64,
+
+    /// Индекс последнего байта
+    end: i64,
+}
+
+impl GetRange {
+    /// Создает новую команду `GetRange`, запрашивающую байты `key` от `start`
+    /// до `end` включительно
+    pub fn new(key: impl ToString, start: i64, end: i64) -> GetRange {
+        GetRange {
+            key: key.to_string(),
+            start,
+            end,
+        }
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `GetRange` из полученного кадра.
+    ///
+    /// Строка `GETRANGE` (или `SUBSTR`) уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 4 сущности:
+    ///
+    /// ```text
+    /// GETRANGE key start end
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<GetRange> {
+        let key = parse.next_string()?;
+        let start = parse.next_signed_int()?;
+        let end = parse.next_signed_int()?;
+
+        Ok(GetRange { key, start, end })
+    }
+
+    /// Применяет команду `GetRange` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = match db.get_range(&self.key, self.start, self.end) {
+            Ok(value) => Frame::Bulk(value),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+use crate::{Connection, Db, Frame, Parse, ParseError};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// Удаляет поля хеша.
+///
+/// Возвращается количество удаленных полей. Хеш без полей удаляется
+#[derive(Debug)]
+pub struct Hdel {
+    /// Ключ
+    key: String,
+
+    /// Удаляемые поля
+    fields: Vec<String>,
+}
+
+impl Hdel {
+    /// Создает новую команду `Hdel`, удаляющую поля `fields` хеша `key`
+    pub fn new(key: impl ToString, fields: &[impl ToString]) -> Hdel {
+        Hdel {
+            key: key.to_string(),
+            fields: fields.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `Hdel` из полученного кадра.
+    ///
+    /// Строка `HDEL` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 3 и более сущности:
+    ///
+    /// ```text
+    /// HDEL key field [field ...]
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Hdel> {
+        let key = parse.next_string()?;
+
+        // Должно быть указано хотя бы одно поле
+        let mut fields = vec![parse.next_string()?];
+
+        loop {
+            match parse.next_string() {
+                Ok(field) => fields.push(field),
+                Err(ParseError::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(Hdel { key, fields })
+    }
+
+    /// Применяет команду `Hdel` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды.
+    ///
+    /// Возвращает `true`, если команда изменила данные
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<bool> {
+        let res = db.hdel(&self.key, &self.fields);
+        let written = matches!(res, Ok(removed) if removed > 0);
+
+        let response = match res {
+            Ok(removed) => Frame::Integer(removed as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(written)
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `Hdel`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("hdel".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        for field in self.fields {
+            frame.push_bulk(Bytes::from(field.into_bytes()));
+        }
+        frame
+    }
+}
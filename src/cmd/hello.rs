@@ -0,0 +1,186 @@
+use crate::cmd::{Parse, ParseError};
+use crate::{Connection, Frame, Session};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// Версия протокола, используемая `mini-redis`.
+const PROTOCOL_VERSION: u64 = 2;
+
+/// Выполняет рукопожатие с сервером и возвращает информацию о сервере.
+///
+/// Ответ содержит пары "ключ-значение": название и версию сервера, версию
+/// протокола, идентификатор соединения и роль сервера.
+///
+/// # Настройки
+///
+/// * `protover` - версия протокола. `mini-redis` поддерживает только RESP2,
+///   поэтому запрос другой версии отклоняется с ошибкой `NOPROTO`.
+/// * AUTH `username` `password` - аутентификация. Поскольку `mini-redis` не
+///   поддерживает пароли, принимаются любые учетные данные.
+/// * SETNAME `clientname` - имя клиента. Записывается в поле `client_name`
+///   span'а соединения.
+#[derive(Debug, Default)]
+pub struct Hello {
+    /// Запрашиваемая версия протокола
+    protover: Option<u64>,
+
+    /// Имя пользователя и пароль
+    auth: Option<(String, String)>,
+
+    /// Имя клиента
+    setname: Option<String>,
+}
+
+impl Hello {
+    /// Создает новую команду `Hello`.
+    ///
+    /// Настройки `auth` и `setname` могут указываться только вместе с версией
+    /// протокола.
+    pub fn new(
+        protover: Option<u64>,
+        auth: Option<(String, String)>,
+        setname: Option<String>,
+    ) -> Hello {
+        Hello {
+            protover,
+            auth,
+            setname,
+        }
+    }
+
+    /// Разбирает экземпляр `Hello` из полученного кадра.
+    ///
+    /// Аргумент `Parse` предоставляет подобное курсору (cursor-like) API для чтения полей из
+    /// `Frame`. На этом этапе из сокета получен весь кадр.
+    ///
+    /// Строка `HELLO` уже потреблена.
+    ///
+    /// # Возвращаемые значения
+    ///
+    /// Возвращает значение `Hello` при успехе. Если кадр испорчен,
+    /// возвращается `Err`.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий `HELLO` и опциональные настройки:
+    ///
+    /// ```text
+    /// HELLO [protover [AUTH username password] [SETNAME clientname]]
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Hello> {
+        use ParseError::EndOfStream;
+
+        let mut hello = Hello::default();
+
+        // Настройки указываются только после версии протокола
+        match parse.next_int() {
+            Ok(protover) => hello.protover = Some(protover),
+            Err(EndOfStream) => return Ok(hello),
+            Err(err) => return Err(err.into()),
+        }
+
+        loop {
+            match parse.next_string() {
+                Ok(s) if s.to_uppercase() == "AUTH" => {
+                    let username = parse.next_string()?;
+                    let password = parse.next_string()?;
+                    hello.auth = Some((username, password));
+                }
+                Ok(s) if s.to_uppercase() == "SETNAME" => {
+                    hello.setname = Some(parse.next_string()?);
+                }
+                Ok(s) => return Err(format!("`HELLO` не поддерживает настройку `{}`.", s).into()),
+                Err(EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(hello)
+    }
+
+    /// Применяет команду `Hello` к состоянию соединения.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды
+    #[instrument(skip(self, dst, session))]
+    pub(crate) async fn apply(
+        self,
+        dst: &mut Connection,
+        session: &mut Session,
+    ) -> crate::Result<()> {
+        let response = match self.protover {
+            Some(protover) if protover != PROTOCOL_VERSION => {
+                Frame::Error("NOPROTO unsupported protocol version".to_string())
+            }
+            _ => match self.setname {
+                // Имена с пробелами нарушили бы формат вывода списка клиентов
+                Some(name) if name.chars().any(|c| c.is_whitespace() || c.is_control()) => {
+                    Frame::Error(
+                        "ERR Client names cannot contain spaces, newlines or special characters."
+                            .to_string(),
+                    )
+                }
+                setname => {
+                    if let Some(name) = setname {
+                        session.set_name(&name);
+                    }
+                    server_info(session)
+                }
+            },
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `Hello`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("hello".as_bytes()));
+        if let Some(protover) = self.protover {
+            frame.push_int(protover as i64);
+
+            if let Some((username, password)) = self.auth {
+                frame.push_bulk(Bytes::from("auth".as_bytes()));
+                frame.push_bulk(Bytes::from(username.into_bytes()));
+                frame.push_bulk(Bytes::from(password.into_bytes()));
+            }
+
+            if let Some(name) = self.setname {
+                frame.push_bulk(Bytes::from("setname".as_bytes()));
+                frame.push_bulk(Bytes::from(name.into_bytes()));
+            }
+        }
+        frame
+    }
+}
+
+/// Создает ответ с информацией о сервере. В RESP2 ответ представлен
+/// массивом чередующихся ключей и значений
+fn server_info(session: &Session) -> Frame {
+    let bulk = |value: &'static str| Frame::Bulk(Bytes::from_static(value.as_bytes()));
+
+    Frame::Array(vec![
+        bulk("server"),
+        bulk("mini-redis"),
+        bulk("version"),
+        bulk(env!("CARGO_PKG_VERSION")),
+        bulk("proto"),
+        Frame::Integer(PROTOCOL_VERSION as i64),
+        bulk("id"),
+        Frame::Integer(session.id() as i64),
+        bulk("mode"),
+        bulk("standalone"),
+        bulk("role"),
+        bulk("master"),
+        bulk("modules"),
+        Frame::array(),
+    ])
+}
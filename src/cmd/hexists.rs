@@ -0,0 +1,80 @@
+use crate::{Connection, Db, Frame, Parse};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// Проверяет наличие поля в хеше.
+///
+/// Возвращается `1`, если поле существует, и `0`, если хеша или поля нет
+#[derive(Debug)]
+pub struct Hexists {
+    /// Ключ
+    key: String,
+
+    /// Поле
+    field: String,
+}
+
+impl Hexists {
+    /// Создает новую команду `Hexists`, проверяющую наличие поля `field` в
+    /// хеше `key`
+    pub fn new(key: impl ToString, field: impl ToString) -> Hexists {
+        Hexists {
+            key: key.to_string(),
+            field: field.to_string(),
+        }
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `Hexists` из полученного кадра.
+    ///
+    /// Строка `HEXISTS` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 3 сущности:
+    ///
+    /// ```text
+    /// HEXISTS key field
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Hexists> {
+        let key = parse.next_string()?;
+        let field = parse.next_string()?;
+
+        Ok(Hexists { key, field })
+    }
+
+    /// Применяет команду `Hexists` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = match db.hexists(&self.key, &self.field) {
+            Ok(exists) => Frame::Integer(i64::from(exists)),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `Hexists`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("hexists".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        frame.push_bulk(Bytes::from(self.field.into_bytes()));
+        frame
+    }
+}
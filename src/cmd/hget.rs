@@ -0,0 +1,81 @@
+use crate::{Connection, Db, Frame, Parse};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// Извлекает значение поля хеша.
+///
+/// При отсутствии хеша или поля возвращается специальное значение `nil`.
+/// Ошибка возвращается, если значение не является хешем
+#[derive(Debug)]
+pub struct Hget {
+    /// Ключ
+    key: String,
+
+    /// Поле
+    field: String,
+}
+
+impl Hget {
+    /// Создает новую команду `Hget`, запрашивающую поле `field` хеша `key`
+    pub fn new(key: impl ToString, field: impl ToString) -> Hget {
+        Hget {
+            key: key.to_string(),
+            field: field.to_string(),
+        }
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `Hget` из полученного кадра.
+    ///
+    /// Строка `HGET` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 3 сущности:
+    ///
+    /// ```text
+    /// HGET key field
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Hget> {
+        let key = parse.next_string()?;
+        let field = parse.next_string()?;
+
+        Ok(Hget { key, field })
+    }
+
+    /// Применяет команду `Hget` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = match db.hget(&self.key, &self.field) {
+            Ok(Some(value)) => Frame::Bulk(value),
+            Ok(None) => Frame::Null,
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `Hget`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("hget".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        frame.push_bulk(Bytes::from(self.field.into_bytes()));
+        frame
+    }
+}
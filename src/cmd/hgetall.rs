@@ -0,0 +1,81 @@
+use crate::{Connection, Db, Frame, Parse};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// Возвращает все поля и значения хеша.
+///
+/// Ответ - массив, в котором за каждым полем следует его значение. При
+/// отсутствии хеша возвращается пустой массив
+#[derive(Debug)]
+pub struct Hgetall {
+    /// Ключ
+    key: String,
+}
+
+impl Hgetall {
+    /// Создает новую команду `Hgetall`, запрашивающую хеш `key`
+    pub fn new(key: impl ToString) -> Hgetall {
+        Hgetall {
+            key: key.to_string(),
+        }
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `Hgetall` из полученного кадра.
+    ///
+    /// Строка `HGETALL` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 2 сущности:
+    ///
+    /// ```text
+    /// HGETALL key
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Hgetall> {
+        let key = parse.next_string()?;
+
+        Ok(Hgetall { key })
+    }
+
+    /// Применяет команду `Hgetall` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = match db.hgetall(&self.key) {
+            Ok(fields) => {
+                let mut response = Frame::array();
+                for (field, value) in fields {
+                    response.push_bulk(Bytes::from(field.into_bytes()));
+                    response.push_bulk(value);
+                }
+                response
+            }
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `Hgetall`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("hgetall".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        frame
+    }
+}
@@ -0,0 +1,96 @@
+use crate::{Connection, Db, Frame, Parse};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// Увеличивает целое число, хранящееся в поле хеша, на заданную величину.
+///
+/// Отсутствующие хеш и поле создаются со значением `0`. Возвращается новое
+/// значение поля
+#[derive(Debug)]
+pub struct HincrBy {
+    /// Ключ
+    key: String,
+
+    /// Поле
+    field: String,
+
+    /// Величина увеличения
+    increment: i64,
+}
+
+impl HincrBy {
+    /// Создает новую команду `HincrBy`, увеличивающую поле `field` хеша `key`
+    /// на `increment`
+    pub fn new(key: impl ToString, field: impl ToString, increment: i64) -> HincrBy {
+        HincrBy {
+            key: key.to_string(),
+            field: field.to_string(),
+            increment,
+        }
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `HincrBy` из полученного кадра.
+    ///
+    /// Строка `HINCRBY` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 4 сущности:
+    ///
+    /// ```text
+    /// HINCRBY key field increment
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<HincrBy> {
+        let key = parse.next_string()?;
+        let field = parse.next_string()?;
+        let increment = parse.next_signed_int()?;
+
+        Ok(HincrBy {
+            key,
+            field,
+            increment,
+        })
+    }
+
+    /// Применяет команду `HincrBy` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды.
+    ///
+    /// Возвращает `true`, если команда изменила данные
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<bool> {
+        let res = db.hincr_by(&self.key, &self.field, self.increment);
+        let written = res.is_ok();
+
+        let response = match res {
+            Ok(value) => Frame::Integer(value),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(written)
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `HincrBy`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("hincrby".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        frame.push_bulk(Bytes::from(self.field.into_bytes()));
+        frame.push_int(self.increment);
+        frame
+    }
+}
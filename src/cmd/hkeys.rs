@@ -0,0 +1,149 @@
+use crate::{Connection, Db, Frame, Parse};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// Возвращает поля хеша в порядке возрастания.
+///
+/// При отсутствии хеша возвращается пустой массив
+#[derive(Debug)]
+pub struct Hkeys {
+    /// Ключ
+    key: String,
+}
+
+/// Возвращает значения хеша в порядке возрастания их полей.
+///
+/// При отсутствии хеша возвращается пустой массив
+#[derive(Debug)]
+pub struct Hvals {
+    /// Ключ
+    key: String,
+}
+
+impl Hkeys {
+    /// Создает новую команду `Hkeys`, запрашивающую поля хеша `key`
+    pub fn new(key: impl ToString) -> Hkeys {
+        Hkeys {
+            key: key.to_string(),
+        }
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `Hkeys` из полученного кадра.
+    ///
+    /// Строка `HKEYS` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 2 сущности:
+    ///
+    /// ```text
+    /// HKEYS key
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Hkeys> {
+        let key = parse.next_string()?;
+
+        Ok(Hkeys { key })
+    }
+
+    /// Применяет команду `Hkeys` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = hash_frame(db, &self.key, |field, _| Bytes::from(field.into_bytes()));
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `Hkeys`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("hkeys".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        frame
+    }
+}
+
+impl Hvals {
+    /// Создает новую команду `Hvals`, запрашивающую значения хеша `key`
+    pub fn new(key: impl ToString) -> Hvals {
+        Hvals {
+            key: key.to_string(),
+        }
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `Hvals` из полученного кадра.
+    ///
+    /// Строка `HVALS` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 2 сущности:
+    ///
+    /// ```text
+    /// HVALS key
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Hvals> {
+        let key = parse.next_string()?;
+
+        Ok(Hvals { key })
+    }
+
+    /// Применяет команду `Hvals` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = hash_frame(db, &self.key, |_, value| value);
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `Hvals`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("hvals".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        frame
+    }
+}
+
+/// Возвращает массив, содержащий для каждого поля хеша `key` результат `f`
+fn hash_frame(db: &Db, key: &str, f: impl Fn(String, Bytes) -> Bytes) -> Frame {
+    match db.hgetall(key) {
+        Ok(fields) => Frame::Array(
+            fields
+                .into_iter()
+                .map(|(field, value)| Frame::Bulk(f(field, value)))
+                .collect(),
+        ),
+        Err(err) => Frame::Error(err.to_string()),
+    }
+}
@@ -0,0 +1,74 @@
+use crate::{Connection, Db, Frame, Parse};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// Возвращает количество полей хеша.
+///
+/// При отсутствии хеша возвращается `0`
+#[derive(Debug)]
+pub struct Hlen {
+    /// Ключ
+    key: String,
+}
+
+impl Hlen {
+    /// Создает новую команду `Hlen`, запрашивающую количество полей хеша
+    /// `key`
+    pub fn new(key: impl ToString) -> Hlen {
+        Hlen {
+            key: key.to_string(),
+        }
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `Hlen` из полученного кадра.
+    ///
+    /// Строка `HLEN` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 2 сущности:
+    ///
+    /// ```text
+    /// HLEN key
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Hlen> {
+        let key = parse.next_string()?;
+
+        Ok(Hlen { key })
+    }
+
+    /// Применяет команду `Hlen` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = match db.hlen(&self.key) {
+            Ok(len) => Frame::Integer(len as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `Hlen`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("hlen".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        frame
+    }
+}
@@ -0,0 +1,97 @@
+use crate::{Connection, Db, Frame, Parse, ParseError};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// Извлекает значения нескольких полей хеша.
+///
+/// Возвращается массив значений в порядке полей. Отсутствующим полям
+/// соответствует специальное значение `nil`
+#[derive(Debug)]
+pub struct Hmget {
+    /// Ключ
+    key: String,
+
+    /// Поля
+    fields: Vec<String>,
+}
+
+impl Hmget {
+    /// Создает новую команду `Hmget`, запрашивающую поля `fields` хеша `key`
+    pub fn new(key: impl ToString, fields: &[impl ToString]) -> Hmget {
+        Hmget {
+            key: key.to_string(),
+            fields: fields.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `Hmget` из полученного кадра.
+    ///
+    /// Строка `HMGET` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 3 и более сущности:
+    ///
+    /// ```text
+    /// HMGET key field [field ...]
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Hmget> {
+        let key = parse.next_string()?;
+
+        // Должно быть указано хотя бы одно поле
+        let mut fields = vec![parse.next_string()?];
+
+        loop {
+            match parse.next_string() {
+                Ok(field) => fields.push(field),
+                Err(ParseError::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(Hmget { key, fields })
+    }
+
+    /// Применяет команду `Hmget` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = match db.hmget(&self.key, &self.fields) {
+            Ok(values) => Frame::Array(
+                values
+                    .into_iter()
+                    .map(|value| value.map_or(Frame::Null, Frame::Bulk))
+                    .collect(),
+            ),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `Hmget`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("hmget".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        for field in self.fields {
+            frame.push_bulk(Bytes::from(field.into_bytes()));
+        }
+        frame
+    }
+}
@@ -0,0 +1,145 @@
+use crate::parse::ParseError;
+use crate::{Connection, Db, Frame, Parse};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// Возвращает случайные поля хеша.
+///
+/// Без количества возвращается одно поле или `nil`, если хеша нет. С
+/// положительным количеством возвращается массив различных полей, с
+/// отрицательным - массив полей, которые могут повторяться. С настройкой
+/// `WITHVALUES` за каждым полем следует его значение
+#[derive(Debug)]
+pub struct Hrandfield {
+    /// Ключ
+    key: String,
+
+    /// Количество полей
+    count: Option<i64>,
+
+    /// Возвращать ли значения полей
+    with_values: bool,
+}
+
+impl Hrandfield {
+    /// Создает новую команду `Hrandfield`, запрашивающую `count` случайных
+    /// полей хеша `key` или одно поле, если `count` равен `None`
+    pub fn new(key: impl ToString, count: Option<i64>) -> Hrandfield {
+        Hrandfield {
+            key: key.to_string(),
+            count,
+            with_values: false,
+        }
+    }
+
+    /// Возвращать значения полей (`WITHVALUES`). Учитывается, только если
+    /// задано количество полей
+    pub fn with_values(mut self, with_values: bool) -> Hrandfield {
+        self.with_values = with_values;
+        self
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `Hrandfield` из полученного кадра.
+    ///
+    /// Строка `HRANDFIELD` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий от 2 до 4 сущностей:
+    ///
+    /// ```text
+    /// HRANDFIELD key [count [WITHVALUES]]
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Hrandfield> {
+        let key = parse.next_string()?;
+
+        let count = match parse.next_signed_int() {
+            Ok(count) => Some(count),
+            Err(ParseError::EndOfStream) => None,
+            Err(err) => return Err(err.into()),
+        };
+
+        let with_values = match parse.next_string() {
+            Ok(option) if count.is_some() && option.eq_ignore_ascii_case("withvalues") => true,
+            Ok(option) => {
+                return Err(format!(
+                    "Ошибка протокола; недопустимая настройка `HRANDFIELD`: {}",
+                    option
+                )
+                .into())
+            }
+            Err(ParseError::EndOfStream) => false,
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(Hrandfield {
+            key,
+            count,
+            with_values,
+        })
+    }
+
+    /// Применяет команду `Hrandfield` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = match self.count {
+            // Как и в Redis, количество ограничено, чтобы ответ с
+            // `WITHVALUES` не переполнял счетчик элементов массива
+            Some(count) if count.unsigned_abs() > i64::MAX as u64 / 2 => {
+                Frame::Error("ERR value is out of range".to_string())
+            }
+            Some(count) => match db.hrandfield(&self.key, count) {
+                Ok(fields) => {
+                    let mut response = Frame::array();
+                    for (field, value) in fields {
+                        response.push_bulk(Bytes::from(field.into_bytes()));
+                        if self.with_values {
+                            response.push_bulk(value);
+                        }
+                    }
+                    response
+                }
+                Err(err) => Frame::Error(err.to_string()),
+            },
+            None => match db.hrandfield(&self.key, 1) {
+                Ok(fields) => match fields.into_iter().next() {
+                    Some((field, _)) => Frame::Bulk(Bytes::from(field.into_bytes())),
+                    None => Frame::Null,
+                },
+                Err(err) => Frame::Error(err.to_string()),
+            },
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `Hrandfield`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("hrandfield".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        if let Some(count) = self.count {
+            frame.push_int(count);
+            if self.with_values {
+                frame.push_bulk(Bytes::from("withvalues".as_bytes()));
+            }
+        }
+        frame
+    }
+}
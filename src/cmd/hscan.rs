@@ -0,0 +1,116 @@
+use crate::cmd::scan::{self, DEFAULT_COUNT};
+use crate::{Connection, Db, Frame, Parse};
+
+use bytes::Bytes;
+use std::convert::TryFrom;
+use tracing::{debug, instrument};
+
+/// Постранично перебирает поля хеша.
+///
+/// Работает так же, как `SCAN`, но перебирает поля одного хеша и возвращает
+/// за каждым полем его значение. Если хеша нет, возвращается курсор `0` и
+/// пустая порция
+#[derive(Debug)]
+pub struct Hscan {
+    /// Ключ
+    key: String,
+
+    /// Позиция, с которой продолжается перебор
+    cursor: u64,
+
+    /// Опциональный шаблон для фильтрации полей
+    pattern: Option<String>,
+
+    /// Количество полей, рассматриваемых за один вызов
+    count: Option<u64>,
+}
+
+impl Hscan {
+    /// Создает новую команду `Hscan`
+    pub fn new(
+        key: impl ToString,
+        cursor: u64,
+        pattern: Option<String>,
+        count: Option<u64>,
+    ) -> Hscan {
+        Hscan {
+            key: key.to_string(),
+            cursor,
+            pattern,
+            count,
+        }
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `Hscan` из полученного кадра.
+    ///
+    /// Строка `HSCAN` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив, состоящий минимум из 3 сущностей:
+    ///
+    /// ```text
+    /// HSCAN key cursor [MATCH pattern] [COUNT count]
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Hscan> {
+        let key = parse.next_string()?;
+        let cursor = parse.next_int()?;
+        let (pattern, count) = scan::parse_options(parse, "HSCAN")?;
+
+        Ok(Hscan {
+            key,
+            cursor,
+            pattern,
+            count,
+        })
+    }
+
+    /// Применяет команду `Hscan` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let count = self.count.unwrap_or(DEFAULT_COUNT);
+        let count = usize::try_from(count).unwrap_or(usize::MAX);
+
+        let response = match db.hscan(&self.key, self.cursor, self.pattern.as_deref(), count) {
+            Ok((cursor, fields)) => {
+                // Ответ - массив из курсора (в виде строки) и массива
+                // чередующихся полей и значений
+                let mut page = Frame::array();
+                for (field, value) in fields {
+                    page.push_bulk(Bytes::from(field));
+                    page.push_bulk(value);
+                }
+
+                Frame::Array(vec![Frame::Bulk(Bytes::from(cursor.to_string())), page])
+            }
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `Hscan`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("hscan".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        frame.push_bulk(Bytes::from(self.cursor.to_string()));
+        scan::push_options(&mut frame, self.pattern, self.count);
+        frame
+    }
+}
@@ -0,0 +1,89 @@
+use crate::cmd::mset::{parse_pairs, to_owned_pairs};
+use crate::{Connection, Db, Frame, Parse};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// Устанавливает значения полей хеша.
+///
+/// Отсутствующий хеш создается. Возвращается количество добавленных полей,
+/// без учета полей, значения которых были заменены
+#[derive(Debug)]
+pub struct Hset {
+    /// Ключ
+    key: String,
+
+    /// Поля и их значения
+    fields: Vec<(String, Bytes)>,
+}
+
+impl Hset {
+    /// Создает новую команду `Hset`, устанавливающую поля `fields` хеша `key`
+    pub fn new(key: impl ToString, fields: &[(impl ToString, Bytes)]) -> Hset {
+        Hset {
+            key: key.to_string(),
+            fields: to_owned_pairs(fields),
+        }
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `Hset` из полученного кадра.
+    ///
+    /// Строка `HSET` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 4 и более сущности:
+    ///
+    /// ```text
+    /// HSET key field value [field value ...]
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Hset> {
+        let key = parse.next_string()?;
+        let fields = parse_pairs(parse)?;
+
+        Ok(Hset { key, fields })
+    }
+
+    /// Применяет команду `Hset` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды.
+    ///
+    /// Возвращает `true`, если команда изменила данные
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<bool> {
+        let res = db.hset(&self.key, self.fields);
+        let written = res.is_ok();
+
+        let response = match res {
+            Ok(added) => Frame::Integer(added as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(written)
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `Hset`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("hset".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        for (field, value) in self.fields {
+            frame.push_bulk(Bytes::from(field.into_bytes()));
+            frame.push_bulk(value);
+        }
+        frame
+    }
+}
@@ -0,0 +1,92 @@
+use crate::{Connection, Db, Frame, Parse};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// Устанавливает значение поля хеша, только если поля нет.
+///
+/// Отсутствующий хеш создается. Возвращается `1`, если поле добавлено, и `0`,
+/// если поле уже существует
+#[derive(Debug)]
+pub struct HsetNx {
+    /// Ключ
+    key: String,
+
+    /// Поле
+    field: String,
+
+    /// Значение
+    value: Bytes,
+}
+
+impl HsetNx {
+    /// Создает новую команду `HsetNx`, устанавливающую поле `field` хеша
+    /// `key` в `value`
+    pub fn new(key: impl ToString, field: impl ToString, value: Bytes) -> HsetNx {
+        HsetNx {
+            key: key.to_string(),
+            field: field.to_string(),
+            value,
+        }
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `HsetNx` из полученного кадра.
+    ///
+    /// Строка `HSETNX` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 4 сущности:
+    ///
+    /// ```text
+    /// HSETNX key field value
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<HsetNx> {
+        let key = parse.next_string()?;
+        let field = parse.next_string()?;
+        let value = parse.next_bytes()?;
+
+        Ok(HsetNx { key, field, value })
+    }
+
+    /// Применяет команду `HsetNx` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды.
+    ///
+    /// Возвращает `true`, если команда изменила данные
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<bool> {
+        let res = db.hset_nx(&self.key, self.field, self.value);
+        let written = matches!(res, Ok(true));
+
+        let response = match res {
+            Ok(added) => Frame::Integer(i64::from(added)),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(written)
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `HsetNx`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("hsetnx".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        frame.push_bulk(Bytes::from(self.field.into_bytes()));
+        frame.push_bulk(self.value);
+        frame
+    }
+}
@@ -0,0 +1,398 @@
+use crate::value::format_float;
+use crate::{Connection, Db, Frame, Parse};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// Атомарно увеличивает целое число, хранящееся по ключу, на единицу.
+///
+/// Отсутствующий ключ считается равным `0`. Возвращается новое значение или
+/// ошибка, если значение не является целым числом
+#[derive(Debug)]
+pub struct Incr {
+    /// Ключ
+    key: String,
+}
+
+/// Атомарно уменьшает целое число, хранящееся по ключу, на единицу.
+///
+/// Работает так же, как `Incr`
+#[derive(Debug)]
+pub struct Decr {
+    /// Ключ
+    key: String,
+}
+
+/// Атомарно увеличивает целое число, хранящееся по ключу, на `increment`.
+///
+/// Работает так же, как `Incr`. Возвращается ошибка, если результат не
+/// помещается в 64-битное целое со знаком
+#[derive(Debug)]
+pub struct IncrBy {
+    /// Ключ
+    key: String,
+
+    /// Приращение
+    increment: i64,
+}
+
+/// Атомарно уменьшает целое число, хранящееся по ключу, на `decrement`.
+///
+/// Работает так же, как `IncrBy`
+#[derive(Debug)]
+pub struct DecrBy {
+    /// Ключ
+    key: String,
+
+    /// Уменьшение
+    decrement: i64,
+}
+
+/// Атомарно увеличивает число с плавающей точкой, хранящееся по ключу, на
+/// `increment`.
+///
+/// Отсутствующий ключ считается равным `0`. Возвращается новое значение в
+/// виде строки без экспоненты и незначащих нулей или ошибка, если значение
+/// не является числом или результат не является конечным числом
+#[derive(Debug)]
+pub struct IncrByFloat {
+    /// Ключ
+    key: String,
+
+    /// Приращение
+    increment: f64,
+}
+
+impl Incr {
+    /// Создает новую команду `Incr`, увеличивающую значение `key`
+    pub fn new(key: impl ToString) -> Incr {
+        Incr {
+            key: key.to_string(),
+        }
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `Incr` из полученного кадра.
+    ///
+    /// Строка `INCR` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 2 сущности:
+    ///
+    /// ```text
+    /// INCR key
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Incr> {
+        let key = parse.next_string()?;
+
+        Ok(Incr { key })
+    }
+
+    /// Применяет команду `Incr` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды.
+    ///
+    /// Возвращает `true`, если команда изменила данные
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<bool> {
+        let res = db.incr_by(&self.key, 1);
+        let written = res.is_ok();
+        let response = counter_frame(res);
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(written)
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `Incr`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("incr".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        frame
+    }
+}
+
+impl Decr {
+    /// Создает новую команду `Decr`, уменьшающую значение `key`
+    pub fn new(key: impl ToString) -> Decr {
+        Decr {
+            key: key.to_string(),
+        }
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `Decr` из полученного кадра.
+    ///
+    /// Строка `DECR` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 2 сущности:
+    ///
+    /// ```text
+    /// DECR key
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Decr> {
+        let key = parse.next_string()?;
+
+        Ok(Decr { key })
+    }
+
+    /// Применяет команду `Decr` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды.
+    ///
+    /// Возвращает `true`, если команда изменила данные
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<bool> {
+        let res = db.incr_by(&self.key, -1);
+        let written = res.is_ok();
+        let response = counter_frame(res);
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(written)
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `Decr`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("decr".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        frame
+    }
+}
+
+impl IncrBy {
+    /// Создает новую команду `IncrBy`, увеличивающую значение `key` на
+    /// `increment`
+    pub fn new(key: impl ToString, increment: i64) -> IncrBy {
+        IncrBy {
+            key: key.to_string(),
+            increment,
+        }
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `IncrBy` из полученного кадра.
+    ///
+    /// Строка `INCRBY` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 3 сущности:
+    ///
+    /// ```text
+    /// INCRBY key increment
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<IncrBy> {
+        let key = parse.next_string()?;
+        let increment = parse.next_signed_int()?;
+
+        Ok(IncrBy { key, increment })
+    }
+
+    /// Применяет команду `IncrBy` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды.
+    ///
+    /// Возвращает `true`, если команда изменила данные
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<bool> {
+        let res = db.incr_by(&self.key, self.increment);
+        let written = res.is_ok();
+        let response = counter_frame(res);
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(written)
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `IncrBy`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("incrby".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        frame.push_bulk(Bytes::from(self.increment.to_string()));
+        frame
+    }
+}
+
+impl DecrBy {
+    /// Создает новую команду `DecrBy`, уменьшающую значение `key` на
+    /// `decrement`
+    pub fn new(key: impl ToString, decrement: i64) -> DecrBy {
+        DecrBy {
+            key: key.to_string(),
+            decrement,
+        }
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `DecrBy` из полученного кадра.
+    ///
+    /// Строка `DECRBY` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 3 сущности:
+    ///
+    /// ```text
+    /// DECRBY key decrement
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<DecrBy> {
+        let key = parse.next_string()?;
+        let decrement = parse.next_signed_int()?;
+
+        Ok(DecrBy { key, decrement })
+    }
+
+    /// Применяет команду `DecrBy` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды.
+    ///
+    /// Возвращает `true`, если команда изменила данные
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<bool> {
+        // Как и в Redis, `i64::MIN` не может быть изменен на
+        // противоположное число
+        let res = match self.decrement.checked_neg() {
+            Some(delta) => db.incr_by(&self.key, delta),
+            None => Err("ERR decrement would overflow".into()),
+        };
+        let written = res.is_ok();
+        let response = counter_frame(res);
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(written)
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `DecrBy`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("decrby".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        frame.push_bulk(Bytes::from(self.decrement.to_string()));
+        frame
+    }
+}
+
+impl IncrByFloat {
+    /// Создает новую команду `IncrByFloat`, увеличивающую значение `key` на
+    /// `increment`
+    pub fn new(key: impl ToString, increment: f64) -> IncrByFloat {
+        IncrByFloat {
+            key: key.to_string(),
+            increment,
+        }
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `IncrByFloat` из полученного кадра.
+    ///
+    /// Строка `INCRBYFLOAT` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 3 сущности:
+    ///
+    /// ```text
+    /// INCRBYFLOAT key increment
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<IncrByFloat> {
+        let key = parse.next_string()?;
+        let increment = parse.next_float()?;
+
+        Ok(IncrByFloat { key, increment })
+    }
+
+    /// Применяет команду `IncrByFloat` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды.
+    ///
+    /// Возвращает `true`, если команда изменила данные
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<bool> {
+        let res = db.incr_by_float(&self.key, self.increment);
+        let written = res.is_ok();
+
+        let response = match res {
+            Ok(value) => Frame::Bulk(Bytes::from(format_float(value))),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(written)
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `IncrByFloat`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("incrbyfloat".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        frame.push_bulk(Bytes::from(self.increment.to_string()));
+        frame
+    }
+}
+
+/// Создает ответ с новым значением счетчика или ошибкой
+fn counter_frame(res: crate::Result<i64>) -> Frame {
+    match res {
+        Ok(value) => Frame::Integer(value),
+        Err(err) => Frame::Error(err.to_string()),
+    }
+}
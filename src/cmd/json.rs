@@ -0,0 +1,188 @@
+use crate::cmd::{Parse, ParseError};
+use crate::{Connection, Db, Frame};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// Возвращает значение по пути в документе JSON.
+///
+/// Путь задается в формате JSON Pointer (см. `crate::json`), по умолчанию
+/// возвращается весь документ. Если ключ или путь не существует,
+/// возвращается `nil`.
+#[derive(Debug)]
+pub struct JsonGet {
+    /// Ключ документа
+    key: String,
+
+    /// Путь в документе
+    path: String,
+}
+
+/// Устанавливает значение по пути в документе JSON.
+///
+/// Отсутствующий документ создается, только если путь обозначает корень.
+/// Возвращается `OK` или `nil`, если путь не существует.
+#[derive(Debug)]
+pub struct JsonSet {
+    /// Ключ документа
+    key: String,
+
+    /// Путь в документе
+    path: String,
+
+    /// Текст устанавливаемого значения
+    value: Bytes,
+}
+
+/// Удаляет значение по пути в документе JSON.
+///
+/// По умолчанию удаляется весь документ. Возвращается количество удаленных
+/// значений.
+#[derive(Debug)]
+pub struct JsonDel {
+    /// Ключ документа
+    key: String,
+
+    /// Путь в документе
+    path: String,
+}
+
+impl JsonGet {
+    /// Разбирает экземпляр `JsonGet` из полученного кадра.
+    ///
+    /// Строка `JSON.GET` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// ```text
+    /// JSON.GET key [path]
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<JsonGet> {
+        let key = parse.next_string()?;
+        let path = next_path(parse)?;
+
+        Ok(JsonGet { key, path })
+    }
+
+    /// Применяет команду `JsonGet` к БД.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = match db.json_get(&self.key, &self.path) {
+            Ok(Some(value)) => Frame::Bulk(value),
+            Ok(None) => Frame::Null,
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+impl JsonSet {
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `JsonSet` из полученного кадра.
+    ///
+    /// Строка `JSON.SET` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// ```text
+    /// JSON.SET key path value
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<JsonSet> {
+        let key = parse.next_string()?;
+        let path = parse.next_string()?;
+        let value = parse.next_bytes()?;
+
+        Ok(JsonSet { key, path, value })
+    }
+
+    /// Применяет команду `JsonSet` к БД.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды.
+    ///
+    /// Возвращает `true`, если команда изменила данные
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<bool> {
+        let res = match serde_json::from_slice(&self.value) {
+            Ok(value) => db.json_set(&self.key, &self.path, value),
+            Err(err) => Err(format!("ERR invalid JSON: {}", err).into()),
+        };
+        let written = matches!(res, Ok(true));
+
+        let response = match res {
+            Ok(true) => Frame::Simple("OK".to_string()),
+            Ok(false) => Frame::Null,
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+        dst.write_frame(&response).await?;
+
+        Ok(written)
+    }
+}
+
+impl JsonDel {
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `JsonDel` из полученного кадра.
+    ///
+    /// Строка `JSON.DEL` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// ```text
+    /// JSON.DEL key [path]
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<JsonDel> {
+        let key = parse.next_string()?;
+        let path = next_path(parse)?;
+
+        Ok(JsonDel { key, path })
+    }
+
+    /// Применяет команду `JsonDel` к БД.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды.
+    ///
+    /// Возвращает `true`, если команда изменила данные
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<bool> {
+        let res = db.json_del(&self.key, &self.path);
+        let written = matches!(res, Ok(true));
+
+        let response = match res {
+            Ok(deleted) => Frame::Integer(i64::from(deleted)),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+        dst.write_frame(&response).await?;
+
+        Ok(written)
+    }
+}
+
+/// Читает опциональный путь. По умолчанию используется корень документа.
+fn next_path(parse: &mut Parse) -> crate::Result<String> {
+    match parse.next_string() {
+        Ok(path) => Ok(path),
+        Err(ParseError::EndOfStream) => Ok(String::new()),
+        Err(err) => Err(err.into()),
+    }
+}
@@ -0,0 +1,89 @@
+use crate::{Connection, Db, Frame, Parse};
+
+use bytes::Bytes;
+use tokio::time::Instant;
+use tracing::{debug, instrument};
+
+/// Возвращает все ключи, соответствующие шаблону.
+///
+/// Шаблон может содержать `*`, `?`, классы символов `[...]` и экранирование `\`.
+/// Команда перебирает все пространство ключей, поэтому для больших БД
+/// рекомендуется использовать `SCAN`
+#[derive(Debug)]
+pub struct Keys {
+    /// Шаблон для сопоставления
+    pattern: String,
+}
+
+impl Keys {
+    /// Создает новую команду `Keys` с шаблоном `pattern`
+    pub fn new(pattern: impl ToString) -> Keys {
+        Keys {
+            pattern: pattern.to_string(),
+        }
+    }
+
+    /// Возвращает шаблон
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Разбирает экземпляр `Keys` из полученного кадра.
+    ///
+    /// Строка `KEYS` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 2 сущности:
+    ///
+    /// ```text
+    /// KEYS pattern
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Keys> {
+        let pattern = parse.next_string()?;
+
+        Ok(Keys { pattern })
+    }
+
+    /// Применяет команду `Keys` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды. Если перебор ключей не завершился
+    /// до `deadline`, клиент получает ошибку
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(
+        self,
+        db: &Db,
+        dst: &mut Connection,
+        deadline: Option<Instant>,
+    ) -> crate::Result<()> {
+        // Ключи возвращаются в виде массива групп
+        let response = match db.keys(&self.pattern, deadline) {
+            Ok(keys) => {
+                let mut response = Frame::array();
+                for key in keys {
+                    response.push_bulk(Bytes::from(key));
+                }
+                response
+            }
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `Keys`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("keys".as_bytes()));
+        frame.push_bulk(Bytes::from(self.pattern.into_bytes()));
+        frame
+    }
+}
@@ -0,0 +1,112 @@
+use crate::cmd::Parse;
+use crate::{Connection, Db, Frame};
+
+use bytes::Bytes;
+use std::convert::TryFrom;
+use tracing::{debug, instrument};
+
+/// Отчеты об использовании памяти.
+///
+/// Поддерживаются подкоманды:
+///
+/// * `STATS` - статистика в виде массива пар "название - значение";
+/// * `DOCTOR` - текстовый отчет о проблемах использования памяти.
+///
+/// См. `crate::memory`.
+#[derive(Debug)]
+pub struct Memory {
+    /// Подкоманда
+    subcommand: Subcommand,
+}
+
+/// Подкоманда `MEMORY`.
+#[derive(Debug)]
+enum Subcommand {
+    /// Возвращает статистику
+    Stats,
+
+    /// Возвращает отчет о проблемах
+    Doctor,
+}
+
+impl Memory {
+    /// Разбирает экземпляр `Memory` из полученного кадра.
+    ///
+    /// Строка `MEMORY` уже потреблена.
+    ///
+    /// # Возвращаемые значения
+    ///
+    /// Возвращает значение `Memory` при успехе. Если кадр испорчен или
+    /// подкоманда не поддерживается, возвращается `Err`.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий `MEMORY` и подкоманду:
+    ///
+    /// ```text
+    /// MEMORY STATS | DOCTOR
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Memory> {
+        let subcommand = parse.next_string()?;
+
+        let subcommand = match &subcommand.to_uppercase()[..] {
+            "STATS" => Subcommand::Stats,
+            "DOCTOR" => Subcommand::Doctor,
+            _ => {
+                return Err(format!("`MEMORY` не поддерживает подкоманду `{}`.", subcommand).into())
+            }
+        };
+
+        Ok(Memory { subcommand })
+    }
+
+    /// Применяет команду `Memory` к БД.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let stats = db.memory_stats();
+
+        let response = match self.subcommand {
+            Subcommand::Stats => {
+                let mut fields = vec![
+                    ("keys.count", Some(stats.keys)),
+                    ("dataset.bytes", Some(stats.dataset_bytes)),
+                    ("overhead.total", Some(stats.overhead_bytes)),
+                    ("rss.bytes", stats.rss_bytes),
+                    ("peak.rss", stats.peak_rss_bytes),
+                ];
+
+                if let Some(allocator) = stats.allocator {
+                    fields.push(("allocator.allocated", Some(allocator.allocated)));
+                    fields.push(("allocator.active", Some(allocator.active)));
+                    fields.push(("allocator.resident", Some(allocator.resident)));
+                }
+
+                let mut response = Frame::array();
+                for (name, value) in fields {
+                    // Недоступные значения пропускаются
+                    if let Some(value) = value {
+                        response.push_bulk(Bytes::from(name));
+                        response.push_int(i64::try_from(value).unwrap_or(i64::MAX));
+                    }
+                }
+
+                // Коэффициент возвращается строкой, как в `Redis`
+                if let Some(fragmentation) = stats.fragmentation() {
+                    response.push_bulk(Bytes::from("fragmentation"));
+                    response.push_bulk(Bytes::from(format!("{:.2}", fragmentation)));
+                }
+
+                response
+            }
+            Subcommand::Doctor => Frame::Bulk(Bytes::from(stats.doctor())),
+        };
+
+        debug!(?response);
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
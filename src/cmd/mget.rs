@@ -0,0 +1,87 @@
+use crate::{Connection, Db, Frame, Parse, ParseError};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// Извлекает значения нескольких ключей.
+///
+/// Возвращается массив значений в порядке ключей. Отсутствующим ключам
+/// соответствует специальное значение `nil`
+#[derive(Debug)]
+pub struct Mget {
+    /// Ключи
+    keys: Vec<String>,
+}
+
+impl Mget {
+    /// Создает новую команду `Mget`, запрашивающую `keys`
+    pub fn new(keys: &[impl ToString]) -> Mget {
+        Mget {
+            keys: keys.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    /// Возвращает ключи
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    /// Разбирает экземпляр `Mget` из полученного кадра.
+    ///
+    /// Строка `MGET` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 2 и более сущности:
+    ///
+    /// ```text
+    /// MGET key [key ...]
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Mget> {
+        // Должен быть указан хотя бы один ключ
+        let mut keys = vec![parse.next_string()?];
+
+        loop {
+            match parse.next_string() {
+                Ok(key) => keys.push(key),
+                Err(ParseError::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(Mget { keys })
+    }
+
+    /// Применяет команду `Mget` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let values = db
+            .mget(&self.keys)
+            .into_iter()
+            .map(|value| value.map_or(Frame::Null, Frame::Bulk))
+            .collect();
+        let response = Frame::Array(values);
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `Mget`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("mget".as_bytes()));
+        for key in self.keys {
+            frame.push_bulk(Bytes::from(key.into_bytes()));
+        }
+        frame
+    }
+}
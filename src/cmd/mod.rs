@@ -1,33 +1,217 @@
+mod debug;
+pub use debug::Debug;
+
 mod get;
 pub use get::Get;
 
+mod hello;
+pub use hello::Hello;
+
+mod keys;
+pub use keys::Keys;
+
 mod publish;
 pub use publish::Publish;
 
+mod quit;
+pub use quit::Quit;
+
+mod scan;
+pub use scan::Scan;
+
 mod set;
-pub use set::Set;
+pub use set::{Set, SetCondition};
 
 mod subscribe;
 pub use subscribe::{Subscribe, Unsubscribe};
 
+mod config;
+pub use config::Config;
+
+mod object;
+pub use object::Object;
+
+mod memory;
+pub use memory::Memory;
+
+mod throttle;
+pub use throttle::Throttle;
+
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "json")]
+pub use json::{JsonDel, JsonGet, JsonSet};
+
+mod exists;
+pub use exists::Exists;
+
+mod expire;
+pub use expire::{Expire, ExpireAt, Pexpire, PexpireAt};
+
+mod ttl;
+pub use ttl::{ExpireTime, PexpireTime, Pttl, Ttl};
+
+mod persist;
+pub use persist::Persist;
+
+mod incr;
+pub use incr::{Decr, DecrBy, Incr, IncrBy, IncrByFloat};
+
+mod get_range;
+pub use get_range::GetRange;
+
+mod set_range;
+pub use set_range::SetRange;
+
+mod mget;
+pub use mget::Mget;
+
+mod mset;
+pub use mset::{Mset, MsetNx};
+
+mod dump;
+pub use dump::{Dump, Restore};
+
+mod hset;
+pub use hset::Hset;
+
+mod hget;
+pub use hget::Hget;
+
+mod hdel;
+pub use hdel::Hdel;
+
+mod hgetall;
+pub use hgetall::Hgetall;
+
+mod hincrby;
+pub use hincrby::HincrBy;
+
+mod hexists;
+pub use hexists::Hexists;
+
+mod hkeys;
+pub use hkeys::{Hkeys, Hvals};
+
+mod hlen;
+pub use hlen::Hlen;
+
+mod hmget;
+pub use hmget::Hmget;
+
+mod hsetnx;
+pub use hsetnx::HsetNx;
+
+mod hrandfield;
+pub use hrandfield::Hrandfield;
+
+mod hscan;
+pub use hscan::Hscan;
+
+mod zadd;
+pub use zadd::{Zadd, ZaddComparison, ZaddOptions};
+
+mod zscore;
+pub use zscore::Zscore;
+
+mod zrem;
+pub use zrem::Zrem;
+
+mod zrange;
+pub use zrange::Zrange;
+
+mod zrangebyscore;
+pub use zrangebyscore::ZrangeByScore;
+
+mod zcount;
+pub use zcount::Zcount;
+
+mod zincrby;
+pub use zincrby::ZincrBy;
+
+mod zrank;
+pub use zrank::Zrank;
+
+mod zrangebylex;
+pub use zrangebylex::ZrangeByLex;
+
 mod ping;
 pub use ping::Ping;
 
 mod unknown;
 pub use unknown::Unknown;
 
-use crate::{Connection, Db, Frame, Parse, ParseError, Shutdown};
+use crate::{Connection, Db, Frame, Parse, ParseError, Session, Shutdown};
 
 /// Перечисление поддерживаемых команд.
 ///
 /// Методы, вызываемые на `Command`, делегируются реализации команды
 #[derive(Debug)]
 pub enum Command {
+    Debug(Debug),
     Get(Get),
+    Hello(Hello),
+    Keys(Keys),
     Publish(Publish),
+    Quit(Quit),
+    Scan(Scan),
     Set(Set),
     Subscribe(Subscribe),
     Unsubscribe(Unsubscribe),
+    Config(Config),
+    Object(Object),
+    Memory(Memory),
+    Throttle(Throttle),
+    #[cfg(feature = "json")]
+    JsonGet(JsonGet),
+    #[cfg(feature = "json")]
+    JsonSet(JsonSet),
+    #[cfg(feature = "json")]
+    JsonDel(JsonDel),
+    Exists(Exists),
+    Expire(Expire),
+    Pexpire(Pexpire),
+    Ttl(Ttl),
+    Pttl(Pttl),
+    Persist(Persist),
+    ExpireAt(ExpireAt),
+    PexpireAt(PexpireAt),
+    ExpireTime(ExpireTime),
+    PexpireTime(PexpireTime),
+    Incr(Incr),
+    Decr(Decr),
+    IncrBy(IncrBy),
+    DecrBy(DecrBy),
+    IncrByFloat(IncrByFloat),
+    GetRange(GetRange),
+    SetRange(SetRange),
+    Mget(Mget),
+    Mset(Mset),
+    MsetNx(MsetNx),
+    Dump(Dump),
+    Restore(Restore),
+    Hset(Hset),
+    Hget(Hget),
+    Hdel(Hdel),
+    Hgetall(Hgetall),
+    HincrBy(HincrBy),
+    Hexists(Hexists),
+    Hkeys(Hkeys),
+    Hvals(Hvals),
+    Hlen(Hlen),
+    Hmget(Hmget),
+    HsetNx(HsetNx),
+    Hrandfield(Hrandfield),
+    Hscan(Hscan),
+    Zadd(Zadd),
+    Zscore(Zscore),
+    Zrem(Zrem),
+    Zrange(Zrange),
+    ZrangeByScore(ZrangeByScore),
+    Zcount(Zcount),
+    ZincrBy(ZincrBy),
+    Zrank(Zrank),
+    ZrangeByLex(ZrangeByLex),
     Ping(Ping),
     Unknown(Unknown),
 }
@@ -56,11 +240,70 @@ impl Command {
         // Сопоставляем название команды, делегируя ее дальнейший разбор реализации
         // соответствующей команды
         let command = match &command_name[..] {
+            "debug" => Command::Debug(Debug::parse_frames(&mut parse)?),
             "get" => Command::Get(Get::parse_frames(&mut parse)?),
+            "hello" => Command::Hello(Hello::parse_frames(&mut parse)?),
+            "keys" => Command::Keys(Keys::parse_frames(&mut parse)?),
             "publish" => Command::Publish(Publish::parse_frames(&mut parse)?),
+            "quit" => Command::Quit(Quit::parse_frames(&mut parse)?),
+            "scan" => Command::Scan(Scan::parse_frames(&mut parse)?),
             "set" => Command::Set(Set::parse_frames(&mut parse)?),
             "subscribe" => Command::Subscribe(Subscribe::parse_frames(&mut parse)?),
             "unsubscribe" => Command::Unsubscribe(Unsubscribe::parse_frames(&mut parse)?),
+            "config" => Command::Config(Config::parse_frames(&mut parse)?),
+            "object" => Command::Object(Object::parse_frames(&mut parse)?),
+            "memory" => Command::Memory(Memory::parse_frames(&mut parse)?),
+            "throttle" => Command::Throttle(Throttle::parse_frames(&mut parse)?),
+            #[cfg(feature = "json")]
+            "json.get" => Command::JsonGet(JsonGet::parse_frames(&mut parse)?),
+            #[cfg(feature = "json")]
+            "json.set" => Command::JsonSet(JsonSet::parse_frames(&mut parse)?),
+            #[cfg(feature = "json")]
+            "json.del" => Command::JsonDel(JsonDel::parse_frames(&mut parse)?),
+            "exists" => Command::Exists(Exists::parse_frames(&mut parse)?),
+            "expire" => Command::Expire(Expire::parse_frames(&mut parse)?),
+            "pexpire" => Command::Pexpire(Pexpire::parse_frames(&mut parse)?),
+            "ttl" => Command::Ttl(Ttl::parse_frames(&mut parse)?),
+            "pttl" => Command::Pttl(Pttl::parse_frames(&mut parse)?),
+            "persist" => Command::Persist(Persist::parse_frames(&mut parse)?),
+            "expireat" => Command::ExpireAt(ExpireAt::parse_frames(&mut parse)?),
+            "pexpireat" => Command::PexpireAt(PexpireAt::parse_frames(&mut parse)?),
+            "expiretime" => Command::ExpireTime(ExpireTime::parse_frames(&mut parse)?),
+            "pexpiretime" => Command::PexpireTime(PexpireTime::parse_frames(&mut parse)?),
+            "incr" => Command::Incr(Incr::parse_frames(&mut parse)?),
+            "decr" => Command::Decr(Decr::parse_frames(&mut parse)?),
+            "incrby" => Command::IncrBy(IncrBy::parse_frames(&mut parse)?),
+            "decrby" => Command::DecrBy(DecrBy::parse_frames(&mut parse)?),
+            "incrbyfloat" => Command::IncrByFloat(IncrByFloat::parse_frames(&mut parse)?),
+            "getrange" | "substr" => Command::GetRange(GetRange::parse_frames(&mut parse)?),
+            "setrange" => Command::SetRange(SetRange::parse_frames(&mut parse)?),
+            "mget" => Command::Mget(Mget::parse_frames(&mut parse)?),
+            "mset" => Command::Mset(Mset::parse_frames(&mut parse)?),
+            "msetnx" => Command::MsetNx(MsetNx::parse_frames(&mut parse)?),
+            "dump" => Command::Dump(Dump::parse_frames(&mut parse)?),
+            "restore" => Command::Restore(Restore::parse_frames(&mut parse)?),
+            "hset" => Command::Hset(Hset::parse_frames(&mut parse)?),
+            "hget" => Command::Hget(Hget::parse_frames(&mut parse)?),
+            "hdel" => Command::Hdel(Hdel::parse_frames(&mut parse)?),
+            "hgetall" => Command::Hgetall(Hgetall::parse_frames(&mut parse)?),
+            "hincrby" => Command::HincrBy(HincrBy::parse_frames(&mut parse)?),
+            "hexists" => Command::Hexists(Hexists::parse_frames(&mut parse)?),
+            "hkeys" => Command::Hkeys(Hkeys::parse_frames(&mut parse)?),
+            "hvals" => Command::Hvals(Hvals::parse_frames(&mut parse)?),
+            "hlen" => Command::Hlen(Hlen::parse_frames(&mut parse)?),
+            "hmget" => Command::Hmget(Hmget::parse_frames(&mut parse)?),
+            "hsetnx" => Command::HsetNx(HsetNx::parse_frames(&mut parse)?),
+            "hrandfield" => Command::Hrandfield(Hrandfield::parse_frames(&mut parse)?),
+            "hscan" => Command::Hscan(Hscan::parse_frames(&mut parse)?),
+            "zadd" => Command::Zadd(Zadd::parse_frames(&mut parse)?),
+            "zscore" => Command::Zscore(Zscore::parse_frames(&mut parse)?),
+            "zrem" => Command::Zrem(Zrem::parse_frames(&mut parse)?),
+            "zrange" => Command::Zrange(Zrange::parse_frames(&mut parse)?),
+            "zrangebyscore" => Command::ZrangeByScore(ZrangeByScore::parse_frames(&mut parse)?),
+            "zcount" => Command::Zcount(Zcount::parse_frames(&mut parse)?),
+            "zincrby" => Command::ZincrBy(ZincrBy::parse_frames(&mut parse)?),
+            "zrank" => Command::Zrank(Zrank::parse_frames(&mut parse)?),
+            "zrangebylex" => Command::ZrangeByLex(ZrangeByLex::parse_frames(&mut parse)?),
             "ping" => Command::Ping(Ping::parse_frames(&mut parse)?),
             _ => {
                 // Команда не распознана, возвращается `Unknown`.
@@ -84,38 +327,612 @@ impl Command {
     /// Применяет команду к определенному экземпляру `Db`.
     ///
     /// Ответ записывается в `dst`. Это вызывается сервером для
-    /// выполнения полученной команды
+    /// выполнения полученной команды.
+    ///
+    /// Возвращает `true`, если команда изменила ключи, перечисленные в
+    /// `write_keys`. Команда, завершившаяся ошибкой или не выполнившая запись
+    /// из-за условия (например, `SET NX`), возвращает `false`
     pub(crate) async fn apply(
         self,
         db: &Db,
         dst: &mut Connection,
         shutdown: &mut Shutdown,
-    ) -> crate::Result<()> {
+        session: &mut Session,
+    ) -> crate::Result<bool> {
         use Command::*;
 
         match self {
-            Get(cmd) => cmd.apply(db, dst).await,
-            Publish(cmd) => cmd.apply(db, dst).await,
+            Debug(cmd) => cmd.apply(db, dst).await.map(|()| false),
+            Get(cmd) => cmd.apply(db, dst).await.map(|()| false),
+            Hello(cmd) => cmd.apply(dst, session).await.map(|()| false),
+            Keys(cmd) => cmd.apply(db, dst, session.deadline()).await.map(|()| false),
+            Publish(cmd) => cmd.apply(db, dst).await.map(|()| false),
+            Quit(cmd) => cmd.apply(dst, session).await.map(|()| false),
+            Scan(cmd) => cmd.apply(db, dst, session.deadline()).await.map(|()| false),
             Set(cmd) => cmd.apply(db, dst).await,
-            Subscribe(cmd) => cmd.apply(db, dst, shutdown).await,
-            Ping(cmd) => cmd.apply(dst).await,
-            Unknown(cmd) => cmd.apply(dst).await,
+            Subscribe(cmd) => cmd.apply(db, dst, shutdown, session).await.map(|()| false),
+            Config(cmd) => cmd.apply(dst, session).await.map(|()| false),
+            Object(cmd) => cmd.apply(db, dst).await.map(|()| false),
+            Memory(cmd) => cmd.apply(db, dst).await.map(|()| false),
+            Throttle(cmd) => cmd.apply(db, dst).await,
+            #[cfg(feature = "json")]
+            JsonGet(cmd) => cmd.apply(db, dst).await.map(|()| false),
+            #[cfg(feature = "json")]
+            JsonSet(cmd) => cmd.apply(db, dst).await,
+            #[cfg(feature = "json")]
+            JsonDel(cmd) => cmd.apply(db, dst).await,
+            Exists(cmd) => cmd.apply(db, dst).await.map(|()| false),
+            Expire(cmd) => cmd.apply(db, dst).await,
+            Pexpire(cmd) => cmd.apply(db, dst).await,
+            Ttl(cmd) => cmd.apply(db, dst).await.map(|()| false),
+            Pttl(cmd) => cmd.apply(db, dst).await.map(|()| false),
+            Persist(cmd) => cmd.apply(db, dst).await,
+            ExpireAt(cmd) => cmd.apply(db, dst).await,
+            PexpireAt(cmd) => cmd.apply(db, dst).await,
+            ExpireTime(cmd) => cmd.apply(db, dst).await.map(|()| false),
+            PexpireTime(cmd) => cmd.apply(db, dst).await.map(|()| false),
+            Incr(cmd) => cmd.apply(db, dst).await,
+            Decr(cmd) => cmd.apply(db, dst).await,
+            IncrBy(cmd) => cmd.apply(db, dst).await,
+            DecrBy(cmd) => cmd.apply(db, dst).await,
+            IncrByFloat(cmd) => cmd.apply(db, dst).await,
+            GetRange(cmd) => cmd.apply(db, dst).await.map(|()| false),
+            SetRange(cmd) => cmd.apply(db, dst).await,
+            Mget(cmd) => cmd.apply(db, dst).await.map(|()| false),
+            Mset(cmd) => cmd.apply(db, dst).await,
+            MsetNx(cmd) => cmd.apply(db, dst).await,
+            Dump(cmd) => cmd.apply(db, dst).await.map(|()| false),
+            Restore(cmd) => cmd.apply(db, dst).await,
+            Hset(cmd) => cmd.apply(db, dst).await,
+            Hget(cmd) => cmd.apply(db, dst).await.map(|()| false),
+            Hdel(cmd) => cmd.apply(db, dst).await,
+            Hgetall(cmd) => cmd.apply(db, dst).await.map(|()| false),
+            HincrBy(cmd) => cmd.apply(db, dst).await,
+            Hexists(cmd) => cmd.apply(db, dst).await.map(|()| false),
+            Hkeys(cmd) => cmd.apply(db, dst).await.map(|()| false),
+            Hvals(cmd) => cmd.apply(db, dst).await.map(|()| false),
+            Hlen(cmd) => cmd.apply(db, dst).await.map(|()| false),
+            Hmget(cmd) => cmd.apply(db, dst).await.map(|()| false),
+            HsetNx(cmd) => cmd.apply(db, dst).await,
+            Hrandfield(cmd) => cmd.apply(db, dst).await.map(|()| false),
+            Hscan(cmd) => cmd.apply(db, dst).await.map(|()| false),
+            Zadd(cmd) => cmd.apply(db, dst).await,
+            Zscore(cmd) => cmd.apply(db, dst).await.map(|()| false),
+            Zrem(cmd) => cmd.apply(db, dst).await,
+            Zrange(cmd) => cmd.apply(db, dst).await.map(|()| false),
+            ZrangeByScore(cmd) => cmd.apply(db, dst).await.map(|()| false),
+            Zcount(cmd) => cmd.apply(db, dst).await.map(|()| false),
+            ZincrBy(cmd) => cmd.apply(db, dst).await,
+            Zrank(cmd) => cmd.apply(db, dst).await.map(|()| false),
+            ZrangeByLex(cmd) => cmd.apply(db, dst).await.map(|()| false),
+            Ping(cmd) => cmd.apply(dst).await.map(|()| false),
+            Unknown(cmd) => cmd.apply(dst).await.map(|()| false),
             // `Unsubscribe` не может применяться здесь. Она может приходить только
             // из контекста команды `Subscribe`
             Unsubscribe(_) => Err("`Unsubscribe` не поддерживается в этом контексте".into()),
         }
     }
 
+    /// Возвращает ключи, изменяемые командой.
+    ///
+    /// Для команд, не изменяющих данные, возвращается пустой вектор.
+    /// Используется журналом аудита
+    pub(crate) fn write_keys(&self) -> Vec<&str> {
+        match self {
+            Command::Set(cmd) => vec![cmd.key()],
+            Command::Throttle(cmd) => vec![cmd.key()],
+            Command::Expire(cmd) => vec![cmd.key()],
+            Command::Pexpire(cmd) => vec![cmd.key()],
+            Command::ExpireAt(cmd) => vec![cmd.key()],
+            Command::PexpireAt(cmd) => vec![cmd.key()],
+            Command::Persist(cmd) => vec![cmd.key()],
+            Command::Incr(cmd) => vec![cmd.key()],
+            Command::Decr(cmd) => vec![cmd.key()],
+            Command::IncrBy(cmd) => vec![cmd.key()],
+            Command::DecrBy(cmd) => vec![cmd.key()],
+            Command::IncrByFloat(cmd) => vec![cmd.key()],
+            Command::SetRange(cmd) => vec![cmd.key()],
+            Command::Mset(cmd) => cmd.keys().collect(),
+            Command::MsetNx(cmd) => cmd.keys().collect(),
+            Command::Restore(cmd) => vec![cmd.key()],
+            Command::Hset(cmd) => vec![cmd.key()],
+            Command::Hdel(cmd) => vec![cmd.key()],
+            Command::HincrBy(cmd) => vec![cmd.key()],
+            Command::HsetNx(cmd) => vec![cmd.key()],
+            Command::Zadd(cmd) => vec![cmd.key()],
+            Command::Zrem(cmd) => vec![cmd.key()],
+            Command::ZincrBy(cmd) => vec![cmd.key()],
+            #[cfg(feature = "json")]
+            Command::JsonSet(cmd) => vec![cmd.key()],
+            #[cfg(feature = "json")]
+            Command::JsonDel(cmd) => vec![cmd.key()],
+            _ => vec![],
+        }
+    }
+
     /// Возвращает название команды
     pub(crate) fn get_name(&self) -> &str {
         match self {
+            Command::Debug(_) => "debug",
             Command::Get(_) => "get",
+            Command::Hello(_) => "hello",
+            Command::Keys(_) => "keys",
             Command::Publish(_) => "pub",
+            Command::Quit(_) => "quit",
+            Command::Scan(_) => "scan",
             Command::Set(_) => "set",
             Command::Subscribe(_) => "subscribe",
             Command::Unsubscribe(_) => "unsubscribe",
+            Command::Config(_) => "config",
+            Command::Object(_) => "object",
+            Command::Memory(_) => "memory",
+            Command::Throttle(_) => "throttle",
+            #[cfg(feature = "json")]
+            Command::JsonGet(_) => "json.get",
+            #[cfg(feature = "json")]
+            Command::JsonSet(_) => "json.set",
+            #[cfg(feature = "json")]
+            Command::JsonDel(_) => "json.del",
+            Command::Exists(_) => "exists",
+            Command::Expire(_) => "expire",
+            Command::Pexpire(_) => "pexpire",
+            Command::Ttl(_) => "ttl",
+            Command::Pttl(_) => "pttl",
+            Command::Persist(_) => "persist",
+            Command::ExpireAt(_) => "expireat",
+            Command::PexpireAt(_) => "pexpireat",
+            Command::ExpireTime(_) => "expiretime",
+            Command::PexpireTime(_) => "pexpiretime",
+            Command::Incr(_) => "incr",
+            Command::Decr(_) => "decr",
+            Command::IncrBy(_) => "incrby",
+            Command::DecrBy(_) => "decrby",
+            Command::IncrByFloat(_) => "incrbyfloat",
+            Command::GetRange(_) => "getrange",
+            Command::SetRange(_) => "setrange",
+            Command::Mget(_) => "mget",
+            Command::Mset(_) => "mset",
+            Command::MsetNx(_) => "msetnx",
+            Command::Dump(_) => "dump",
+            Command::Restore(_) => "restore",
+            Command::Hset(_) => "hset",
+            Command::Hget(_) => "hget",
+            Command::Hdel(_) => "hdel",
+            Command::Hgetall(_) => "hgetall",
+            Command::HincrBy(_) => "hincrby",
+            Command::Hexists(_) => "hexists",
+            Command::Hkeys(_) => "hkeys",
+            Command::Hvals(_) => "hvals",
+            Command::Hlen(_) => "hlen",
+            Command::Hmget(_) => "hmget",
+            Command::HsetNx(_) => "hsetnx",
+            Command::Hrandfield(_) => "hrandfield",
+            Command::Hscan(_) => "hscan",
+            Command::Zadd(_) => "zadd",
+            Command::Zscore(_) => "zscore",
+            Command::Zrem(_) => "zrem",
+            Command::Zrange(_) => "zrange",
+            Command::ZrangeByScore(_) => "zrangebyscore",
+            Command::Zcount(_) => "zcount",
+            Command::ZincrBy(_) => "zincrby",
+            Command::Zrank(_) => "zrank",
+            Command::ZrangeByLex(_) => "zrangebylex",
             Command::Ping(_) => "ping",
             Command::Unknown(cmd) => cmd.get_name(),
         }
     }
 }
+
+/// Описание команды, поддерживаемой `mini-redis`.
+///
+/// Используется клиентами, например, для автодополнения и подсказок в
+/// интерактивном режиме CLI
+#[derive(Debug, Clone, Copy)]
+pub struct CommandInfo {
+    /// Название команды в нижнем регистре
+    pub name: &'static str,
+
+    /// Количество аргументов, включая название команды. Отрицательное
+    /// значение `-N` означает "не менее `N`", как в ответе `COMMAND` `Redis`
+    pub arity: i32,
+
+    /// Аргументы команды
+    pub usage: &'static str,
+
+    /// Краткое описание команды
+    pub summary: &'static str,
+}
+
+/// Таблица команд, поддерживаемых `mini-redis`, отсортированная по названию.
+///
+/// При добавлении новой команды ее описание должно добавляться в эту таблицу
+pub const COMMAND_TABLE: &[CommandInfo] = &[
+    CommandInfo {
+        name: "config",
+        arity: -3,
+        usage: "GET parameter | SET parameter value",
+        summary: "Читает и изменяет параметры сервера",
+    },
+    CommandInfo {
+        name: "debug",
+        arity: -2,
+        usage: "SET-ACTIVE-EXPIRE 0|1",
+        summary: "Отладочные команды сервера",
+    },
+    CommandInfo {
+        name: "decr",
+        arity: 2,
+        usage: "key",
+        summary: "Уменьшает целое число на единицу",
+    },
+    CommandInfo {
+        name: "decrby",
+        arity: 3,
+        usage: "key decrement",
+        summary: "Уменьшает целое число на заданную величину",
+    },
+    CommandInfo {
+        name: "dump",
+        arity: 2,
+        usage: "key",
+        summary: "Возвращает сериализованное значение ключа",
+    },
+    CommandInfo {
+        name: "exists",
+        arity: -2,
+        usage: "key [key ...]",
+        summary: "Возвращает количество существующих ключей",
+    },
+    CommandInfo {
+        name: "expire",
+        arity: 3,
+        usage: "key seconds",
+        summary: "Устанавливает время жизни ключа в секундах",
+    },
+    CommandInfo {
+        name: "expireat",
+        arity: 3,
+        usage: "key unix-time-seconds",
+        summary: "Устанавливает момент истечения ключа (Unix-время в секундах)",
+    },
+    CommandInfo {
+        name: "expiretime",
+        arity: 2,
+        usage: "key",
+        summary: "Возвращает момент истечения ключа (Unix-время в секундах)",
+    },
+    CommandInfo {
+        name: "get",
+        arity: 2,
+        usage: "key",
+        summary: "Возвращает значение по ключу",
+    },
+    CommandInfo {
+        name: "getrange",
+        arity: 4,
+        usage: "key start end",
+        summary: "Возвращает часть строки",
+    },
+    CommandInfo {
+        name: "hdel",
+        arity: -3,
+        usage: "key field [field ...]",
+        summary: "Удаляет поля хеша",
+    },
+    CommandInfo {
+        name: "hello",
+        arity: -1,
+        usage: "[protover [AUTH username password] [SETNAME clientname]]",
+        summary: "Выполняет рукопожатие и возвращает информацию о сервере",
+    },
+    CommandInfo {
+        name: "hexists",
+        arity: 3,
+        usage: "key field",
+        summary: "Проверяет наличие поля в хеше",
+    },
+    CommandInfo {
+        name: "hget",
+        arity: 3,
+        usage: "key field",
+        summary: "Возвращает значение поля хеша",
+    },
+    CommandInfo {
+        name: "hgetall",
+        arity: 2,
+        usage: "key",
+        summary: "Возвращает все поля и значения хеша",
+    },
+    CommandInfo {
+        name: "hincrby",
+        arity: 4,
+        usage: "key field increment",
+        summary: "Увеличивает целое число в поле хеша на заданную величину",
+    },
+    CommandInfo {
+        name: "hkeys",
+        arity: 2,
+        usage: "key",
+        summary: "Возвращает поля хеша",
+    },
+    CommandInfo {
+        name: "hlen",
+        arity: 2,
+        usage: "key",
+        summary: "Возвращает количество полей хеша",
+    },
+    CommandInfo {
+        name: "hmget",
+        arity: -3,
+        usage: "key field [field ...]",
+        summary: "Возвращает значения нескольких полей хеша",
+    },
+    CommandInfo {
+        name: "hrandfield",
+        arity: -2,
+        usage: "key [count [WITHVALUES]]",
+        summary: "Возвращает случайные поля хеша",
+    },
+    CommandInfo {
+        name: "hscan",
+        arity: -3,
+        usage: "key cursor [MATCH pattern] [COUNT count]",
+        summary: "Постранично перебирает поля хеша",
+    },
+    CommandInfo {
+        name: "hset",
+        arity: -4,
+        usage: "key field value [field value ...]",
+        summary: "Устанавливает значения полей хеша",
+    },
+    CommandInfo {
+        name: "hsetnx",
+        arity: 4,
+        usage: "key field value",
+        summary: "Устанавливает значение поля хеша, если поля нет",
+    },
+    CommandInfo {
+        name: "hvals",
+        arity: 2,
+        usage: "key",
+        summary: "Возвращает значения хеша",
+    },
+    CommandInfo {
+        name: "incr",
+        arity: 2,
+        usage: "key",
+        summary: "Увеличивает целое число на единицу",
+    },
+    CommandInfo {
+        name: "incrby",
+        arity: 3,
+        usage: "key increment",
+        summary: "Увеличивает целое число на заданную величину",
+    },
+    CommandInfo {
+        name: "incrbyfloat",
+        arity: 3,
+        usage: "key increment",
+        summary: "Увеличивает число с плавающей точкой на заданную величину",
+    },
+    #[cfg(feature = "json")]
+    CommandInfo {
+        name: "json.del",
+        arity: -2,
+        usage: "key [path]",
+        summary: "Удаляет значение по пути в документе JSON",
+    },
+    #[cfg(feature = "json")]
+    CommandInfo {
+        name: "json.get",
+        arity: -2,
+        usage: "key [path]",
+        summary: "Возвращает значение по пути в документе JSON",
+    },
+    #[cfg(feature = "json")]
+    CommandInfo {
+        name: "json.set",
+        arity: 4,
+        usage: "key path value",
+        summary: "Устанавливает значение по пути в документе JSON",
+    },
+    CommandInfo {
+        name: "keys",
+        arity: 2,
+        usage: "pattern",
+        summary: "Возвращает все ключи, соответствующие шаблону",
+    },
+    CommandInfo {
+        name: "memory",
+        arity: 2,
+        usage: "STATS | DOCTOR",
+        summary: "Возвращает статистику использования памяти",
+    },
+    CommandInfo {
+        name: "mget",
+        arity: -2,
+        usage: "key [key ...]",
+        summary: "Извлекает значения нескольких ключей",
+    },
+    CommandInfo {
+        name: "mset",
+        arity: -3,
+        usage: "key value [key value ...]",
+        summary: "Устанавливает значения нескольких ключей",
+    },
+    CommandInfo {
+        name: "msetnx",
+        arity: -3,
+        usage: "key value [key value ...]",
+        summary: "Устанавливает значения нескольких ключей, если ни один из них не существует",
+    },
+    CommandInfo {
+        name: "object",
+        arity: 3,
+        usage: "ENCODING key",
+        summary: "Возвращает кодировку значения",
+    },
+    CommandInfo {
+        name: "persist",
+        arity: 2,
+        usage: "key",
+        summary: "Удаляет время жизни ключа",
+    },
+    CommandInfo {
+        name: "pexpire",
+        arity: 3,
+        usage: "key milliseconds",
+        summary: "Устанавливает время жизни ключа в миллисекундах",
+    },
+    CommandInfo {
+        name: "pexpireat",
+        arity: 3,
+        usage: "key unix-time-milliseconds",
+        summary: "Устанавливает момент истечения ключа (Unix-время в миллисекундах)",
+    },
+    CommandInfo {
+        name: "pexpiretime",
+        arity: 2,
+        usage: "key",
+        summary: "Возвращает момент истечения ключа (Unix-время в миллисекундах)",
+    },
+    CommandInfo {
+        name: "ping",
+        arity: -1,
+        usage: "[message]",
+        summary: "Возвращает PONG или переданное сообщение",
+    },
+    CommandInfo {
+        name: "pttl",
+        arity: 2,
+        usage: "key",
+        summary: "Возвращает оставшееся время жизни ключа в миллисекундах",
+    },
+    CommandInfo {
+        name: "publish",
+        arity: 3,
+        usage: "channel message",
+        summary: "Публикует сообщение в канале",
+    },
+    CommandInfo {
+        name: "quit",
+        arity: 1,
+        usage: "",
+        summary: "Закрывает соединение",
+    },
+    CommandInfo {
+        name: "restore",
+        arity: -4,
+        usage: "key ttl payload [REPLACE] [ABSTTL]",
+        summary: "Создает ключ из значения, сериализованного командой DUMP",
+    },
+    CommandInfo {
+        name: "scan",
+        arity: -2,
+        usage: "cursor [MATCH pattern] [COUNT count]",
+        summary: "Постранично перебирает ключи",
+    },
+    CommandInfo {
+        name: "set",
+        arity: -3,
+        usage: "key value [EX seconds|PX milliseconds]",
+        summary: "Устанавливает значение по ключу",
+    },
+    CommandInfo {
+        name: "setrange",
+        arity: 4,
+        usage: "key offset value",
+        summary: "Перезаписывает часть строки",
+    },
+    CommandInfo {
+        name: "subscribe",
+        arity: -2,
+        usage: "channel [channel ...]",
+        summary: "Подписывает клиента на каналы",
+    },
+    CommandInfo {
+        name: "substr",
+        arity: 4,
+        usage: "key start end",
+        summary: "Возвращает часть строки (устаревшее название GETRANGE)",
+    },
+    CommandInfo {
+        name: "throttle",
+        arity: -5,
+        usage: "key max_burst count period [quantity]",
+        summary: "Проверяет ограничение частоты запросов",
+    },
+    CommandInfo {
+        name: "ttl",
+        arity: 2,
+        usage: "key",
+        summary: "Возвращает оставшееся время жизни ключа в секундах",
+    },
+    CommandInfo {
+        name: "unsubscribe",
+        arity: -1,
+        usage: "[channel [channel ...]]",
+        summary: "Отписывает клиента от каналов",
+    },
+    CommandInfo {
+        name: "zadd",
+        arity: -4,
+        usage: "key [NX|XX] [GT|LT] [CH] score member [score member ...]",
+        summary: "Добавляет элементы в сортированное множество",
+    },
+    CommandInfo {
+        name: "zcount",
+        arity: 4,
+        usage: "key min max",
+        summary: "Возвращает количество элементов сортированного множества в диапазоне оценок",
+    },
+    CommandInfo {
+        name: "zincrby",
+        arity: 4,
+        usage: "key increment member",
+        summary: "Увеличивает оценку элемента сортированного множества",
+    },
+    CommandInfo {
+        name: "zrange",
+        arity: -4,
+        usage: "key start stop [REV] [WITHSCORES]",
+        summary: "Возвращает элементы сортированного множества по позициям",
+    },
+    CommandInfo {
+        name: "zrangebylex",
+        arity: -4,
+        usage: "key min max [LIMIT offset count]",
+        summary: "Возвращает элементы сортированного множества в лексикографическом диапазоне",
+    },
+    CommandInfo {
+        name: "zrangebyscore",
+        arity: -4,
+        usage: "key min max [WITHSCORES] [LIMIT offset count]",
+        summary: "Возвращает элементы сортированного множества по оценкам",
+    },
+    CommandInfo {
+        name: "zrank",
+        arity: 3,
+        usage: "key member",
+        summary: "Возвращает позицию элемента сортированного множества",
+    },
+    CommandInfo {
+        name: "zrem",
+        arity: -3,
+        usage: "key member [member ...]",
+        summary: "Удаляет элементы сортированного множества",
+    },
+    CommandInfo {
+        name: "zscore",
+        arity: 3,
+        usage: "key member",
+        summary: "Возвращает оценку элемента сортированного множества",
+    },
+];
+
+/// Возвращает описание команды по названию без учета регистра
+pub fn lookup(name: &str) -> Option<&'static CommandInfo> {
+    COMMAND_TABLE
+        .iter()
+        .find(|info| info.name.eq_ignore_ascii_case(name))
+}
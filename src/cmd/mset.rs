@@ -0,0 +1,174 @@
+use crate::{Connection, Db, Frame, Parse, ParseError};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// Устанавливает значения нескольких ключей.
+///
+/// Значения устанавливаются атомарно: другие клиенты не видят часть новых
+/// значений. Как и `SET`, команда удаляет время жизни ключей
+#[derive(Debug)]
+pub struct Mset {
+    /// Пары ключей и значений
+    pairs: Vec<(String, Bytes)>,
+}
+
+/// Устанавливает значения нескольких ключей, только если ни один из них не
+/// существует.
+///
+/// Возвращается `1`, если значения установлены, и `0`, если хотя бы один ключ
+/// существует. В последнем случае ни одно значение не изменяется
+#[derive(Debug)]
+pub struct MsetNx {
+    /// Пары ключей и значений
+    pairs: Vec<(String, Bytes)>,
+}
+
+impl Mset {
+    /// Создает новую команду `Mset`, устанавливающую значения `pairs`
+    pub fn new(pairs: &[(impl ToString, Bytes)]) -> Mset {
+        Mset {
+            pairs: to_owned_pairs(pairs),
+        }
+    }
+
+    /// Возвращает ключи
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.pairs.iter().map(|(key, _)| key.as_str())
+    }
+
+    /// Разбирает экземпляр `Mset` из полученного кадра.
+    ///
+    /// Строка `MSET` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 3 и более сущности:
+    ///
+    /// ```text
+    /// MSET key value [key value ...]
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Mset> {
+        let pairs = parse_pairs(parse)?;
+
+        Ok(Mset { pairs })
+    }
+
+    /// Применяет команду `Mset` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды.
+    ///
+    /// Возвращает `true`, если команда изменила данные
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<bool> {
+        db.mset(self.pairs);
+
+        let response = Frame::Simple("OK".to_string());
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(true)
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `Mset`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        pairs_frame("mset", self.pairs)
+    }
+}
+
+impl MsetNx {
+    /// Создает новую команду `MsetNx`, устанавливающую значения `pairs`
+    pub fn new(pairs: &[(impl ToString, Bytes)]) -> MsetNx {
+        MsetNx {
+            pairs: to_owned_pairs(pairs),
+        }
+    }
+
+    /// Возвращает ключи
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.pairs.iter().map(|(key, _)| key.as_str())
+    }
+
+    /// Разбирает экземпляр `MsetNx` из полученного кадра.
+    ///
+    /// Строка `MSETNX` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 3 и более сущности:
+    ///
+    /// ```text
+    /// MSETNX key value [key value ...]
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<MsetNx> {
+        let pairs = parse_pairs(parse)?;
+
+        Ok(MsetNx { pairs })
+    }
+
+    /// Применяет команду `MsetNx` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды.
+    ///
+    /// Возвращает `true`, если команда изменила данные
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<bool> {
+        let written = db.mset_nx(self.pairs);
+        let response = Frame::Integer(i64::from(written));
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(written)
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `MsetNx`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        pairs_frame("msetnx", self.pairs)
+    }
+}
+
+/// Копирует пары ключей и значений. Значения `Bytes` не копируются
+pub(super) fn to_owned_pairs(pairs: &[(impl ToString, Bytes)]) -> Vec<(String, Bytes)> {
+    pairs
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.clone()))
+        .collect()
+}
+
+/// Разбирает пары ключей и значений. Должна быть указана хотя бы одна пара
+pub(super) fn parse_pairs(parse: &mut Parse) -> crate::Result<Vec<(String, Bytes)>> {
+    let mut pairs = vec![(parse.next_string()?, parse.next_bytes()?)];
+
+    loop {
+        match parse.next_string() {
+            Ok(key) => pairs.push((key, parse.next_bytes()?)),
+            Err(ParseError::EndOfStream) => break,
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    Ok(pairs)
+}
+
+/// Создает кадр команды `name` с парами ключей и значений
+fn pairs_frame(name: &str, pairs: Vec<(String, Bytes)>) -> Frame {
+    let mut frame = Frame::array();
+    frame.push_bulk(Bytes::from(name.to_string()));
+    for (key, value) in pairs {
+        frame.push_bulk(Bytes::from(key.into_bytes()));
+        frame.push_bulk(value);
+    }
+    frame
+}
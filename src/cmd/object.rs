@@ -0,0 +1,94 @@
+use crate::cmd::Parse;
+use crate::{Connection, Db, Frame};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// Возвращает сведения о внутреннем представлении значения.
+///
+/// Поддерживается подкоманда `ENCODING`, возвращающая кодировку значения:
+/// `int`, `embstr` или `raw` (см. `crate::value`). Для отсутствующего ключа
+/// возвращается `nil`.
+#[derive(Debug)]
+pub struct Object {
+    /// Подкоманда
+    subcommand: Subcommand,
+}
+
+/// Подкоманда `OBJECT`.
+#[derive(Debug)]
+enum Subcommand {
+    /// Возвращает кодировку значения ключа
+    Encoding(String),
+}
+
+impl Object {
+    /// Создает команду `OBJECT ENCODING`, запрашивающую кодировку `key`.
+    pub fn encoding(key: impl ToString) -> Object {
+        Object {
+            subcommand: Subcommand::Encoding(key.to_string()),
+        }
+    }
+
+    /// Разбирает экземпляр `Object` из полученного кадра.
+    ///
+    /// Строка `OBJECT` уже потреблена.
+    ///
+    /// # Возвращаемые значения
+    ///
+    /// Возвращает значение `Object` при успехе. Если кадр испорчен или
+    /// подкоманда не поддерживается, возвращается `Err`.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий `OBJECT`, подкоманду и ключ:
+    ///
+    /// ```text
+    /// OBJECT ENCODING key
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Object> {
+        let subcommand = parse.next_string()?;
+
+        match &subcommand.to_uppercase()[..] {
+            "ENCODING" => Ok(Object::encoding(parse.next_string()?)),
+            _ => Err(format!("`OBJECT` не поддерживает подкоманду `{}`.", subcommand).into()),
+        }
+    }
+
+    /// Применяет команду `Object` к БД.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = match self.subcommand {
+            Subcommand::Encoding(key) => match db.encoding(&key) {
+                Some(encoding) => Frame::Bulk(Bytes::from(encoding)),
+                None => Frame::Null,
+            },
+        };
+
+        debug!(?response);
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `Object`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("object".as_bytes()));
+
+        match self.subcommand {
+            Subcommand::Encoding(key) => {
+                frame.push_bulk(Bytes::from("encoding".as_bytes()));
+                frame.push_bulk(Bytes::from(key.into_bytes()));
+            }
+        }
+
+        frame
+    }
+}
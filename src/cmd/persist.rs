@@ -0,0 +1,74 @@
+use crate::{Connection, Db, Frame, Parse};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// Удаляет время жизни ключа.
+///
+/// Возвращается `1`, если время жизни удалено, и `0`, если ключа нет или его
+/// время жизни не ограничено
+#[derive(Debug)]
+pub struct Persist {
+    /// Ключ
+    key: String,
+}
+
+impl Persist {
+    /// Создает новую команду `Persist`, удаляющую время жизни `key`
+    pub fn new(key: impl ToString) -> Persist {
+        Persist {
+            key: key.to_string(),
+        }
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `Persist` из полученного кадра.
+    ///
+    /// Строка `PERSIST` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 2 сущности:
+    ///
+    /// ```text
+    /// PERSIST key
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Persist> {
+        let key = parse.next_string()?;
+
+        Ok(Persist { key })
+    }
+
+    /// Применяет команду `Persist` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды.
+    ///
+    /// Возвращает `true`, если команда изменила данные
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<bool> {
+        let persisted = db.persist(&self.key);
+        let response = Frame::Integer(i64::from(persisted));
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(persisted)
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `Persist`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("persist".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        frame
+    }
+}
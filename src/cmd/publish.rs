@@ -75,7 +75,7 @@ impl Publish {
         let num_subscribers = db.publish(&self.channel, self.message);
 
         // В ответ на запрос публикации возвращается количество подписчиков на канал
-        let response = Frame::Integer(num_subscribers as u64);
+        let response = Frame::Integer(num_subscribers as i64);
 
         // Возвращаем ответ клиенту
         dst.write_frame(&response).await?;
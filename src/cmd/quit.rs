@@ -0,0 +1,63 @@
+use crate::cmd::Parse;
+use crate::{Connection, Frame, Session};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// Просит сервер закрыть соединение.
+///
+/// Сервер отвечает `OK` и закрывает соединение после отправки ответа
+#[derive(Debug, Default)]
+pub struct Quit;
+
+impl Quit {
+    /// Создает новую команду `Quit`
+    pub fn new() -> Quit {
+        Quit
+    }
+
+    /// Разбирает экземпляр `Quit` из полученного кадра.
+    ///
+    /// Строка `QUIT` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий только `QUIT`:
+    ///
+    /// ```text
+    /// QUIT
+    /// ```
+    pub(crate) fn parse_frames(_parse: &mut Parse) -> crate::Result<Quit> {
+        // Лишние аргументы обнаруживаются вызовом `Parse::finish`
+        Ok(Quit)
+    }
+
+    /// Применяет команду `Quit`.
+    ///
+    /// Ответ записывается в `dst`. Соединение закрывается обработчиком
+    /// после выполнения команды
+    #[instrument(skip(self, dst, session))]
+    pub(crate) async fn apply(
+        self,
+        dst: &mut Connection,
+        session: &mut Session,
+    ) -> crate::Result<()> {
+        let response = Frame::Simple("OK".to_string());
+        debug!(?response);
+        dst.write_frame(&response).await?;
+
+        session.close();
+
+        Ok(())
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `Quit`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("quit".as_bytes()));
+        frame
+    }
+}
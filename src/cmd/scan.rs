@@ -0,0 +1,167 @@
+use crate::{Connection, Db, Frame, Parse, ParseError};
+
+use bytes::Bytes;
+use std::convert::TryFrom;
+use tokio::time::Instant;
+use tracing::{debug, instrument};
+
+/// Количество ключей, рассматриваемых за один вызов, если `COUNT` не указан
+pub(super) const DEFAULT_COUNT: u64 = 10;
+
+/// Постранично перебирает пространство ключей.
+///
+/// Каждый вызов возвращает курсор для следующего вызова и порцию ключей.
+/// Перебор начинается с курсора `0` и завершается, когда сервер возвращает
+/// курсор `0`. В отличие от `KEYS`, команда не блокирует сервер на время
+/// перебора всех ключей.
+///
+/// Ключ, присутствующий в течение всего перебора, возвращается ровно один
+/// раз, даже если во время перебора добавляются или удаляются другие ключи.
+/// Порядок ключей не определен
+#[derive(Debug)]
+pub struct Scan {
+    /// Позиция, с которой продолжается перебор
+    cursor: u64,
+
+    /// Опциональный шаблон для фильтрации ключей
+    pattern: Option<String>,
+
+    /// Количество ключей, рассматриваемых за один вызов
+    count: Option<u64>,
+}
+
+impl Scan {
+    /// Создает новую команду `Scan`
+    pub fn new(cursor: u64, pattern: Option<String>, count: Option<u64>) -> Scan {
+        Scan {
+            cursor,
+            pattern,
+            count,
+        }
+    }
+
+    /// Возвращает курсор
+    pub fn cursor(&self) -> u64 {
+        self.cursor
+    }
+
+    /// Возвращает шаблон
+    pub fn pattern(&self) -> Option<&str> {
+        self.pattern.as_deref()
+    }
+
+    /// Возвращает количество ключей, рассматриваемых за один вызов
+    pub fn count(&self) -> Option<u64> {
+        self.count
+    }
+
+    /// Разбирает экземпляр `Scan` из полученного кадра.
+    ///
+    /// Строка `SCAN` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив, состоящий минимум из 2 сущностей:
+    ///
+    /// ```text
+    /// SCAN cursor [MATCH pattern] [COUNT count]
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Scan> {
+        let cursor = parse.next_int()?;
+        let (pattern, count) = parse_options(parse, "SCAN")?;
+
+        Ok(Scan {
+            cursor,
+            pattern,
+            count,
+        })
+    }
+
+    /// Применяет команду `Scan` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды. Если перебор ключей не завершился
+    /// до `deadline`, клиент получает ошибку
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(
+        self,
+        db: &Db,
+        dst: &mut Connection,
+        deadline: Option<Instant>,
+    ) -> crate::Result<()> {
+        let count = self.count.unwrap_or(DEFAULT_COUNT);
+        let count = usize::try_from(count).unwrap_or(usize::MAX);
+
+        // Ответ - массив из курсора (в виде строки) и массива ключей
+        let response = match db.scan(self.cursor, self.pattern.as_deref(), count, deadline) {
+            Ok((cursor, keys)) => {
+                let mut page = Frame::array();
+                for key in keys {
+                    page.push_bulk(Bytes::from(key));
+                }
+
+                Frame::Array(vec![Frame::Bulk(Bytes::from(cursor.to_string())), page])
+            }
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `Scan`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("scan".as_bytes()));
+        frame.push_bulk(Bytes::from(self.cursor.to_string()));
+        push_options(&mut frame, self.pattern, self.count);
+        frame
+    }
+}
+
+/// Разбирает настройки `MATCH` и `COUNT` команды `cmd`
+pub(super) fn parse_options(
+    parse: &mut Parse,
+    cmd: &str,
+) -> crate::Result<(Option<String>, Option<u64>)> {
+    use ParseError::EndOfStream;
+
+    let mut pattern = None;
+    let mut count = None;
+
+    // Настройки могут следовать в любом порядке
+    loop {
+        match parse.next_string() {
+            Ok(s) if s.to_uppercase() == "MATCH" => {
+                pattern = Some(parse.next_string()?);
+            }
+            Ok(s) if s.to_uppercase() == "COUNT" => {
+                count = Some(parse.next_int()?);
+            }
+            Ok(s) => return Err(format!("`{}` не поддерживает настройку `{}`.", cmd, s).into()),
+            Err(EndOfStream) => break,
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    Ok((pattern, count))
+}
+
+/// Добавляет в кадр настройки `MATCH` и `COUNT`
+pub(super) fn push_options(frame: &mut Frame, pattern: Option<String>, count: Option<u64>) {
+    if let Some(pattern) = pattern {
+        frame.push_bulk(Bytes::from("match".as_bytes()));
+        frame.push_bulk(Bytes::from(pattern.into_bytes()));
+    }
+
+    if let Some(count) = count {
+        frame.push_bulk(Bytes::from("count".as_bytes()));
+        frame.push_bulk(Bytes::from(count.to_string()));
+    }
+}
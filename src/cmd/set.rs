@@ -16,6 +16,13 @@ use tracing::{debug, instrument};
 ///
 /// * EX `seconds` - время жизни в секундах.
 /// * PX `milliseconds` - время жизни в миллисекундах.
+/// * NX - значение устанавливается, только если ключа нет.
+/// * XX - значение устанавливается, только если ключ существует.
+/// * KEEPTTL - время жизни существующего ключа сохраняется.
+/// * GET - вместо `OK` возвращается предыдущее значение ключа.
+///
+/// Если условие `NX` или `XX` не выполнено, значение не изменяется и
+/// возвращается `nil` (или предыдущее значение с настройкой `GET`)
 #[derive(Debug)]
 pub struct Set {
     /// Ключ для поиска
@@ -26,6 +33,25 @@ pub struct Set {
 
     /// Время жизни ключа
     expire: Option<Duration>,
+
+    /// Условие установки значения
+    condition: Option<SetCondition>,
+
+    /// Сохранять время жизни существующего ключа
+    keep_ttl: bool,
+
+    /// Возвращать предыдущее значение
+    get: bool,
+}
+
+/// Условие установки значения командой `SET`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetCondition {
+    /// Значение устанавливается, только если ключа нет (`NX`).
+    Nx,
+
+    /// Значение устанавливается, только если ключ существует (`XX`).
+    Xx,
 }
 
 impl Set {
@@ -37,9 +63,31 @@ impl Set {
             key: key.to_string(),
             value,
             expire,
+            condition: None,
+            keep_ttl: false,
+            get: false,
         }
     }
 
+    /// Устанавливает условие установки значения
+    pub fn with_condition(mut self, condition: SetCondition) -> Set {
+        self.condition = Some(condition);
+        self
+    }
+
+    /// Сохраняет время жизни существующего ключа. Не используется вместе с
+    /// временем жизни, переданным в `new`
+    pub fn with_keep_ttl(mut self) -> Set {
+        self.keep_ttl = true;
+        self
+    }
+
+    /// Запрашивает предыдущее значение ключа
+    pub fn with_get(mut self) -> Set {
+        self.get = true;
+        self
+    }
+
     /// Возвращает ключ
     pub fn key(&self) -> &str {
         &self.key
@@ -55,6 +103,11 @@ impl Set {
         self.expire
     }
 
+    /// Возвращает условие установки значения
+    pub fn condition(&self) -> Option<SetCondition> {
+        self.condition
+    }
+
     /// Разбирает экземпляр `Set` из полученного кадра.
     ///
     /// Аргумент `Parse` предоставляет подобное курсору (cursor-like) API для чтения полей из
@@ -64,7 +117,7 @@ impl Set {
     ///
     /// # Возвращаемые значения
     ///
-    /// Возвращает значение `Get` при успехе. Если кадр испорчен,
+    /// Возвращает значение `Set` при успехе. Если кадр испорчен,
     /// возвращается `Err`.
     ///
     /// # Формат
@@ -72,7 +125,7 @@ impl Set {
     /// Ожидается массив, состоящий минимум из 3 сущностей:
     ///
     /// ```text
-    /// SET key value [EX seconds|PX milliseconds]
+    /// SET key value [NX|XX] [GET] [EX seconds|PX milliseconds|KEEPTTL]
     /// ```
     pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Set> {
         use ParseError::EndOfStream;
@@ -83,55 +136,93 @@ impl Set {
         // Читаем значение для установки. Это обязательное поле
         let value = parse.next_bytes()?;
 
-        // Время жизни является опциональным. Если отсутствует, то имеет значение
-        // `None`.
-        let mut expire = None;
+        let mut set = Set::new(key, value, None);
+
+        // Настройки являются опциональными и могут следовать в любом порядке
+        loop {
+            let option = match parse.next_string() {
+                Ok(option) => option.to_uppercase(),
+                // Ошибка `EndOfStream` является индикатором того, что для разбора не осталось данных.
+                // Это нормальная ситуация времени выполнения, означающая, что
+                // настроек `SET` больше нет
+                Err(EndOfStream) => break,
+                // Другие ошибки всплывают наверх, что приводит к прерыванию соединения
+                Err(err) => return Err(err.into()),
+            };
 
-        // Пытаемся разобрать следующую строку
-        match parse.next_string() {
-            Ok(s) if s.to_uppercase() == "EX" => {
+            // Время жизни указывается одной из настроек `EX`, `PX` и
+            // `KEEPTTL`, условие - одной из настроек `NX` и `XX`
+            let has_ttl = set.expire.is_some() || set.keep_ttl;
+
+            match &option[..] {
                 // Время жизни определено в секундах. Следующее значение -
                 // целое число
-                let secs = parse.next_int()?;
-                expire = Some(Duration::from_secs(secs));
-            }
-            Ok(s) if s.to_uppercase() == "PX" => {
+                "EX" if !has_ttl => set.expire = Some(Duration::from_secs(parse.next_int()?)),
                 // Время жизни определено в миллисекундах. Следующее значение -
                 // целое число
-                let ms = parse.next_int()?;
-                expire = Some(Duration::from_millis(ms));
+                "PX" if !has_ttl => set.expire = Some(Duration::from_millis(parse.next_int()?)),
+                "KEEPTTL" if !has_ttl => set.keep_ttl = true,
+                "NX" if set.condition.is_none() => set.condition = Some(SetCondition::Nx),
+                "XX" if set.condition.is_none() => set.condition = Some(SetCondition::Xx),
+                "GET" if !set.get => set.get = true,
+                // Ошибка, возникающая здесь, приводит к закрытию соединения.
+                // Другие соединения продолжают нормально функционировать
+                _ => {
+                    return Err(format!(
+                        "Ошибка протокола; недопустимая настройка `SET`: {}",
+                        option
+                    )
+                    .into())
+                }
             }
-            // `mini-redis` не поддерживает другие настройки `SET`
-            // Ошибка, возникающая здесь, приводит к закрытию соединения.
-            // Другие соединения продолжают нормально функционировать
-            Ok(_) => return Err("`SET` поддерживает только настройку `expiration`.".into()),
-            // Ошибка `EndOfStream` является индикатором того, что для разбора не осталось данных.
-            // Это нормальная ситуация времени выполнения, означающая, что
-            // настройки `SET` отсутствуют
-            Err(EndOfStream) => {}
-            // Другие ошибки всплывают наверх, что приводит к прерыванию соединения
-            Err(err) => return Err(err.into()),
         }
 
-        Ok(Set { key, value, expire })
+        Ok(set)
     }
 
     /// Применяет команду `Set` к определенному
     /// экземпляру `Db`.
     ///
     /// Ответ записывается в `dst`. Это вызывается сервером для
-    /// выполнения полученной команды
+    /// выполнения полученной команды.
+    ///
+    /// Возвращает `true`, если команда изменила данные
     #[instrument(skip(self, db, dst))]
-    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
-        // Установка значения в общее состояние БД
-        db.set(self.key, self.value, self.expire);
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<bool> {
+        let (response, written) = if self.condition.is_none() && !self.keep_ttl && !self.get {
+            // Установка значения в общее состояние БД
+            db.set(self.key, self.value, self.expire);
 
-        // Создание успешного ответа и его запись в `dst`
-        let response = Frame::Simple("OK".to_string());
+            // Создание успешного ответа
+            (Frame::Simple("OK".to_string()), true)
+        } else {
+            let res = db.set_with(
+                self.key,
+                self.value,
+                self.expire,
+                self.condition,
+                self.keep_ttl,
+                self.get,
+            );
+            let written = matches!(res, Ok((true, _)));
+
+            let response = match (self.get, res) {
+                (true, Ok((_, prev))) => prev.map_or(Frame::Null, Frame::Bulk),
+                (false, Ok((true, _))) => Frame::Simple("OK".to_string()),
+                // Условие не выполнено
+                (false, Ok((false, _))) => Frame::Null,
+                // Предыдущее значение с `GET` не является строкой
+                (_, Err(err)) => Frame::Error(err.to_string()),
+            };
+
+            (response, written)
+        };
+
+        // Запись ответа в `dst`
         debug!(?response);
         dst.write_frame(&response).await?;
 
-        Ok(())
+        Ok(written)
     }
 
     /// Преобразует команду в соответствующий `Frame`.
@@ -151,7 +242,18 @@ impl Set {
             // `src/bin/cli.rs` разбирает аргумент `expiration` как миллисекунды
             // в `duration_from_ms_str()`
             frame.push_bulk(Bytes::from("px".as_bytes()));
-            frame.push_int(ms.as_millis() as u64);
+            frame.push_int(ms.as_millis() as i64);
+        }
+        match self.condition {
+            Some(SetCondition::Nx) => frame.push_bulk(Bytes::from("nx".as_bytes())),
+            Some(SetCondition::Xx) => frame.push_bulk(Bytes::from("xx".as_bytes())),
+            None => {}
+        }
+        if self.keep_ttl {
+            frame.push_bulk(Bytes::from("keepttl".as_bytes()));
+        }
+        if self.get {
+            frame.push_bulk(Bytes::from("get".as_bytes()));
         }
         frame
     }
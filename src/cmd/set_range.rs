@@ -0,0 +1,85 @@
+use crate::{Connection, Db, Frame, Parse};
+
+use bytes::Bytes;
+use std::convert::TryFrom;
+use tracing::{debug, instrument};
+
+/// Перезаписывает часть строки, хранящейся по ключу, начиная с байта
+/// `offset`.
+///
+/// Строка, которая короче `offset`, дополняется нулевыми байтами.
+/// Возвращается новая длина строки
+#[derive(Debug)]
+pub struct SetRange {
+    /// Ключ
+    key: String,
+
+    /// Индекс первого перезаписываемого байта
+    offset: i64,
+
+    /// Записываемые байты
+    value: Bytes,
+}
+
+impl SetRange {
+    /// Создает новую команду `SetRange`, записывающую `value` в `key`,
+    /// начиная с байта `offset`
+    pub fn new(key: impl ToString, offset: i64, value: Bytes) -> SetRange {
+        SetRange {
+            key: key.to_string(),
+            offset,
+            value,
+        }
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `SetRange` из полученного кадра.
+    ///
+    /// Строка `SETRANGE` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 4 сущности:
+    ///
+    /// ```text
+    /// SETRANGE key offset value
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<SetRange> {
+        let key = parse.next_string()?;
+        let offset = parse.next_signed_int()?;
+        let value = parse.next_bytes()?;
+
+        Ok(SetRange { key, offset, value })
+    }
+
+    /// Применяет команду `SetRange` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды.
+    ///
+    /// Возвращает `true`, если команда изменила данные
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<bool> {
+        let res = match usize::try_from(self.offset) {
+            Ok(offset) => db.set_range(&self.key, offset, &self.value),
+            Err(_) => Err("ERR offset is out of range".into()),
+        };
+        // Пустое значение не изменяет строку
+        let written = res.is_ok() && !self.value.is_empty();
+
+        let response = match res {
+            Ok(len) => Frame::Integer(len as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(written)
+    }
+}
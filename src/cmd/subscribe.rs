@@ -1,7 +1,8 @@
 use crate::cmd::{Parse, ParseError, Unknown};
-use crate::{Command, Connection, Db, Frame, Shutdown};
+use crate::{Command, Connection, Db, Frame, Session, Shutdown};
 
 use bytes::Bytes;
+use std::collections::HashSet;
 use std::pin::Pin;
 use tokio::select;
 use tokio::sync::broadcast;
@@ -90,12 +91,17 @@ impl Subscribe {
     /// каналов для подписки. Дополнительные команды `subscribe` и `unsubscribe`
     /// могут быть получены от клиента, и список подписок обновляется соответствующим образом.
     ///
+    /// Клиент может быть подписан не более чем на `Session::max_subscriptions` каналов.
+    /// Команда подписки, превышающая лимит, отклоняется целиком: клиент
+    /// получает ошибку, а список подписок не меняется.
+    ///
     /// См. https://redis.io/topics/pubsub
     pub(crate) async fn apply(
         mut self,
         db: &Db,
         dst: &mut Connection,
         shutdown: &mut Shutdown,
+        session: &mut Session,
     ) -> crate::Result<()> {
         // Подписка на конкретный канал `sync::broadcast`. Сообщения передаются
         // всем клиентам, подписанным на канал.
@@ -107,12 +113,43 @@ impl Subscribe {
         // по мере их поступления.
         let mut subscriptions = StreamMap::new();
 
+        // Если начальная подписка превышает лимит, соединение не переходит
+        // в режим подписки
+        if exceeds_limit(&self.channels, &subscriptions, session.max_subscriptions()) {
+            dst.write_frame(&make_limit_error_frame()).await?;
+            return Ok(());
+        }
+
+        let res = self
+            .run(db, dst, shutdown, session, &mut subscriptions)
+            .await;
+
+        // Соединение выходит из режима подписки. Удаляем получателей и
+        // каналы, у которых не осталось подписчиков
+        let channels: Vec<String> = subscriptions.keys().cloned().collect();
+        drop(subscriptions);
+        for channel_name in channels {
+            db.release_channel(&channel_name);
+        }
+
+        res
+    }
+
+    /// Обрабатывает сообщения каналов и команды клиента в режиме подписки.
+    async fn run(
+        &mut self,
+        db: &Db,
+        dst: &mut Connection,
+        shutdown: &mut Shutdown,
+        session: &mut Session,
+        subscriptions: &mut StreamMap<String, Messages>,
+    ) -> crate::Result<()> {
         loop {
             // `self.channels` используется для отслеживания дополнительных каналов для подписки.
             // При получении новых команд `SUBSCRIBE` в процессе
             // выполнения `apply`, новые каналы помещаются в этот `vec`
             for channel_name in self.channels.drain(..) {
-                subscribe_to_channel(channel_name, &mut subscriptions, db, dst).await?;
+                subscribe_to_channel(channel_name, subscriptions, db, dst).await?;
             }
 
             // Ждем наступления одного из следующих событий:
@@ -135,9 +172,16 @@ impl Subscribe {
                     handle_command(
                         frame,
                         &mut self.channels,
-                        &mut subscriptions,
+                        subscriptions,
+                        db,
                         dst,
+                        session,
                     ).await?;
+
+                    // После `QUIT` соединение закрывается обработчиком
+                    if session.is_closed() {
+                        return Ok(());
+                    }
                 }
                 _ = shutdown.recv() => {
                     return Ok(());
@@ -167,11 +211,16 @@ async fn subscribe_to_channel(
     dst: &mut Connection,
 ) -> crate::Result<()> {
     let mut rx = db.subscribe(channel_name.clone());
+    #[cfg(feature = "chaos")]
+    let db = db.clone();
 
     // Подписываемся на канал
     let rx = Box::pin(async_stream::stream! {
         loop {
             match rx.recv().await {
+                // Внедренный сбой: сообщение теряется, как при отставании
+                #[cfg(feature = "chaos")]
+                Ok(_) if db.chaos().lag() => {}
                 Ok(msg) => yield msg,
                 // Если мы зависли (lagged) при потреблении сообщений, просто продолжаем
                 Err(broadcast::error::RecvError::Lagged(_)) => {}
@@ -191,7 +240,7 @@ async fn subscribe_to_channel(
 }
 
 /// Обрабатывает команду, полученную во время выполнения `Subscribe::apply`.
-/// В этом контексте разрешены только команды подписки и отписки.
+/// В этом контексте разрешены только команды подписки, отписки и `QUIT`.
 ///
 /// Любые новые подписки добавляются в `subscribe_to` вместо модификации
 /// `subscriptions`
@@ -199,16 +248,27 @@ async fn handle_command(
     frame: Frame,
     subscribe_to: &mut Vec<String>,
     subscriptions: &mut StreamMap<String, Messages>,
+    db: &Db,
     dst: &mut Connection,
+    session: &mut Session,
 ) -> crate::Result<()> {
     // От клиента была получена команда.
     //
-    // В этом контексте разрешены только команды `SUBSCRIBE` и `UNSUBSCRIBE`
+    // В этом контексте разрешены только команды `SUBSCRIBE`, `UNSUBSCRIBE` и `QUIT`
     match Command::from_frame(frame)? {
         Command::Subscribe(subscribe) => {
+            if exceeds_limit(
+                &subscribe.channels,
+                subscriptions,
+                session.max_subscriptions(),
+            ) {
+                dst.write_frame(&make_limit_error_frame()).await?;
+                return Ok(());
+            }
+
             // Метод `apply` выполнит подписку на каналы,
             // добавленные в этот вектор
-            subscribe_to.extend(subscribe.channels.into_iter());
+            subscribe_to.extend(subscribe.channels);
         }
         Command::Unsubscribe(mut unsubscribe) => {
             // Если каналы не указаны, выполняется отписка от всех каналов.
@@ -223,11 +283,13 @@ async fn handle_command(
 
             for channel_name in unsubscribe.channels {
                 subscriptions.remove(&channel_name);
+                db.release_channel(&channel_name);
 
                 let response = make_unsubscribe_frame(channel_name, subscriptions.len());
                 dst.write_frame(&response).await?;
             }
         }
+        Command::Quit(quit) => quit.apply(dst, session).await?,
         command => {
             let cmd = Unknown::new(command.get_name());
             cmd.apply(dst).await?;
@@ -236,6 +298,26 @@ async fn handle_command(
     Ok(())
 }
 
+/// Проверяет, превысит ли подписка на `channels` лимит подписок.
+/// Каналы, на которые клиент уже подписан, и повторы не учитываются
+fn exceeds_limit(
+    channels: &[String],
+    subscriptions: &StreamMap<String, Messages>,
+    max_subscriptions: usize,
+) -> bool {
+    let new_channels: HashSet<&String> = channels
+        .iter()
+        .filter(|channel| !subscriptions.contains_key(*channel))
+        .collect();
+
+    subscriptions.len() + new_channels.len() > max_subscriptions
+}
+
+/// Создает ответ на запрос подписки, превышающий лимит подписок
+fn make_limit_error_frame() -> Frame {
+    Frame::Error("ERR max number of subscriptions reached".to_string())
+}
+
 /// Создает ответ на запрос подписки.
 ///
 /// Все эти функции принимают `channel_name` как `String`, а не
@@ -246,7 +328,7 @@ fn make_subscribe_frame(channel_name: String, num_subs: usize) -> Frame {
     let mut response = Frame::array();
     response.push_bulk(Bytes::from_static(b"subscribe"));
     response.push_bulk(Bytes::from(channel_name));
-    response.push_int(num_subs as u64);
+    response.push_int(num_subs as i64);
     response
 }
 
@@ -255,7 +337,7 @@ fn make_unsubscribe_frame(channel_name: String, num_subs: usize) -> Frame {
     let mut response = Frame::array();
     response.push_bulk(Bytes::from_static(b"unsubscribe"));
     response.push_bulk(Bytes::from(channel_name));
-    response.push_int(num_subs as u64);
+    response.push_int(num_subs as i64);
     response
 }
 
@@ -0,0 +1,136 @@
+use crate::cmd::{Parse, ParseError};
+use crate::{Connection, Db, Frame};
+
+use std::convert::TryFrom;
+use std::time::Duration;
+use tracing::{debug, instrument};
+
+/// Проверяет ограничение частоты запросов по ключу.
+///
+/// Ограничение допускает `count` запросов за `period` секунд и всплески до
+/// `max_burst` запросов сверх этого. Опциональный аргумент `quantity`
+/// (по умолчанию `1`) задает количество запросов, которые учитываются
+/// одной командой. См. `crate::throttle`.
+///
+/// Возвращается массив целых чисел:
+///
+/// * `0`, если запрос разрешен, или `1`, если он отклонен;
+/// * максимальное количество запросов без ожидания (`max_burst + 1`);
+/// * оставшееся количество запросов без ожидания;
+/// * количество секунд до повторной попытки (`0`, если запрос разрешен);
+/// * количество секунд до возвращения ограничения в исходное состояние.
+///
+/// Время округляется до целых секунд в большую сторону.
+#[derive(Debug)]
+pub struct Throttle {
+    /// Ключ ограничения
+    key: String,
+
+    /// Максимальный всплеск
+    max_burst: u64,
+
+    /// Количество запросов за период
+    count: u64,
+
+    /// Период в секундах
+    period: u64,
+
+    /// Количество учитываемых запросов
+    quantity: u64,
+}
+
+impl Throttle {
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `Throttle` из полученного кадра.
+    ///
+    /// Строка `THROTTLE` уже потреблена.
+    ///
+    /// # Возвращаемые значения
+    ///
+    /// Возвращает значение `Throttle` при успехе. Если кадр испорчен,
+    /// возвращается `Err`.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий минимум 5 сущностей:
+    ///
+    /// ```text
+    /// THROTTLE key max_burst count period [quantity]
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Throttle> {
+        use ParseError::EndOfStream;
+
+        let key = parse.next_string()?;
+        let max_burst = parse.next_int()?;
+        let count = parse.next_int()?;
+        let period = parse.next_int()?;
+
+        let quantity = match parse.next_int() {
+            Ok(quantity) => quantity,
+            Err(EndOfStream) => 1,
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(Throttle {
+            key,
+            max_burst,
+            count,
+            period,
+            quantity,
+        })
+    }
+
+    /// Применяет команду `Throttle` к БД.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды.
+    ///
+    /// Возвращает `true`, если команда изменила данные
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<bool> {
+        let res = db.throttle(
+            &self.key,
+            self.max_burst,
+            self.count,
+            Duration::from_secs(self.period),
+            self.quantity,
+        );
+        // Состояние сохраняется, только если запрос разрешен
+        let written = matches!(&res, Ok(res) if res.allowed);
+
+        let response = match res {
+            Ok(res) if res.allowed || res.retry_after.is_some() => {
+                let retry_after = res.retry_after.unwrap_or_default();
+
+                let mut frame = Frame::array();
+                frame.push_int(i64::from(!res.allowed));
+                frame.push_int(saturating_int(res.limit));
+                frame.push_int(saturating_int(res.remaining));
+                frame.push_int(saturating_int(ceil_secs(retry_after)));
+                frame.push_int(saturating_int(ceil_secs(res.reset_after)));
+                frame
+            }
+            Ok(_) => Frame::Error("ERR quantity exceeds the maximum burst".to_string()),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+        dst.write_frame(&response).await?;
+
+        Ok(written)
+    }
+}
+
+/// Преобразует значение в целое число кадра, ограничивая его сверху.
+fn saturating_int(value: u64) -> i64 {
+    i64::try_from(value).unwrap_or(i64::MAX)
+}
+
+/// Возвращает количество секунд, округленное в большую сторону.
+fn ceil_secs(duration: Duration) -> u64 {
+    duration.as_secs() + u64::from(duration.subsec_nanos() > 0)
+}
@@ -0,0 +1,270 @@
+use crate::{Connection, Db, Frame, Parse};
+
+use bytes::Bytes;
+use std::convert::TryFrom;
+use std::time::{Duration, UNIX_EPOCH};
+use tracing::{debug, instrument};
+
+/// Возвращает оставшееся время жизни ключа в секундах.
+///
+/// Возвращается `-1`, если время жизни ключа не ограничено, и `-2`, если
+/// ключа нет. Время округляется до ближайшей секунды
+#[derive(Debug)]
+pub struct Ttl {
+    /// Ключ
+    key: String,
+}
+
+/// Возвращает оставшееся время жизни ключа в миллисекундах.
+///
+/// Работает так же, как `Ttl`
+#[derive(Debug)]
+pub struct Pttl {
+    /// Ключ
+    key: String,
+}
+
+/// Возвращает момент истечения времени жизни ключа в виде Unix-времени в
+/// секундах.
+///
+/// Возвращается `-1`, если время жизни ключа не ограничено, и `-2`, если
+/// ключа нет
+#[derive(Debug)]
+pub struct ExpireTime {
+    /// Ключ
+    key: String,
+}
+
+/// Возвращает момент истечения времени жизни ключа в виде Unix-времени в
+/// миллисекундах.
+///
+/// Работает так же, как `ExpireTime`
+#[derive(Debug)]
+pub struct PexpireTime {
+    /// Ключ
+    key: String,
+}
+
+impl Ttl {
+    /// Создает новую команду `Ttl`, запрашивающую время жизни `key`
+    pub fn new(key: impl ToString) -> Ttl {
+        Ttl {
+            key: key.to_string(),
+        }
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `Ttl` из полученного кадра.
+    ///
+    /// Строка `TTL` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 2 сущности:
+    ///
+    /// ```text
+    /// TTL key
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Ttl> {
+        let key = parse.next_string()?;
+
+        Ok(Ttl { key })
+    }
+
+    /// Применяет команду `Ttl` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = ttl_frame(db, &self.key, |ttl| (ttl.as_millis() + 500) / 1000);
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+impl Pttl {
+    /// Создает новую команду `Pttl`, запрашивающую время жизни `key`
+    pub fn new(key: impl ToString) -> Pttl {
+        Pttl {
+            key: key.to_string(),
+        }
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `Pttl` из полученного кадра.
+    ///
+    /// Строка `PTTL` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 2 сущности:
+    ///
+    /// ```text
+    /// PTTL key
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Pttl> {
+        let key = parse.next_string()?;
+
+        Ok(Pttl { key })
+    }
+
+    /// Применяет команду `Pttl` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = ttl_frame(db, &self.key, |ttl| ttl.as_millis());
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `Pttl`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("pttl".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        frame
+    }
+}
+
+impl ExpireTime {
+    /// Создает новую команду `ExpireTime`, запрашивающую момент истечения
+    /// `key`
+    pub fn new(key: impl ToString) -> ExpireTime {
+        ExpireTime {
+            key: key.to_string(),
+        }
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `ExpireTime` из полученного кадра.
+    ///
+    /// Строка `EXPIRETIME` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 2 сущности:
+    ///
+    /// ```text
+    /// EXPIRETIME key
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<ExpireTime> {
+        let key = parse.next_string()?;
+
+        Ok(ExpireTime { key })
+    }
+
+    /// Применяет команду `ExpireTime` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = expire_time_frame(db, &self.key, |time| (time.as_millis() + 500) / 1000);
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+impl PexpireTime {
+    /// Создает новую команду `PexpireTime`, запрашивающую момент истечения
+    /// `key`
+    pub fn new(key: impl ToString) -> PexpireTime {
+        PexpireTime {
+            key: key.to_string(),
+        }
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `PexpireTime` из полученного кадра.
+    ///
+    /// Строка `PEXPIRETIME` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 2 сущности:
+    ///
+    /// ```text
+    /// PEXPIRETIME key
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<PexpireTime> {
+        let key = parse.next_string()?;
+
+        Ok(PexpireTime { key })
+    }
+
+    /// Применяет команду `PexpireTime` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = expire_time_frame(db, &self.key, |time| time.as_millis());
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// Создает ответ с оставшимся временем жизни ключа, переведенным в нужные
+/// единицы функцией `units`
+fn ttl_frame(db: &Db, key: &str, units: impl Fn(Duration) -> u128) -> Frame {
+    match db.ttl(key) {
+        Some(Some(ttl)) => Frame::Integer(i64::try_from(units(ttl)).unwrap_or(i64::MAX)),
+        // Время жизни не ограничено
+        Some(None) => Frame::Integer(-1),
+        // Ключа нет
+        None => Frame::Integer(-2),
+    }
+}
+
+/// Создает ответ с моментом истечения времени жизни ключа, переведенным в
+/// нужные единицы функцией `units`. Момент отсчитывается от начала эпохи Unix
+fn expire_time_frame(db: &Db, key: &str, units: impl Fn(Duration) -> u128) -> Frame {
+    match db.expire_time(key) {
+        Some(Some(when)) => {
+            let since_epoch = when.duration_since(UNIX_EPOCH).unwrap_or_default();
+            Frame::Integer(i64::try_from(units(since_epoch)).unwrap_or(i64::MAX))
+        }
+        // Время жизни не ограничено
+        Some(None) => Frame::Integer(-1),
+        // Ключа нет
+        None => Frame::Integer(-2),
+    }
+}
@@ -0,0 +1,207 @@
+use crate::cmd::SetCondition;
+use crate::parse::ParseError;
+use crate::{Connection, Db, Frame, Parse};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// Ошибка, возвращаемая при несовместимых настройках сравнения
+const INCOMPATIBLE: &str = "ERR GT, LT, and/or NX options at the same time are not compatible";
+
+/// Добавляет элементы в сортированное множество или обновляет их оценки.
+///
+/// Отсутствующее множество создается. Возвращается количество добавленных
+/// элементов, а с настройкой `CH` - количество добавленных и измененных
+/// элементов
+#[derive(Debug)]
+pub struct Zadd {
+    /// Ключ
+    key: String,
+
+    /// Оценки и элементы
+    members: Vec<(f64, String)>,
+
+    /// Настройки
+    options: ZaddOptions,
+}
+
+/// Настройки команды `ZADD`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ZaddOptions {
+    /// Условие на наличие элемента: только добавлять новые элементы (`NX`)
+    /// или только обновлять существующие (`XX`).
+    pub condition: Option<SetCondition>,
+
+    /// Обновлять оценку, только если новая оценка больше (`GT`) или меньше
+    /// (`LT`) текущей. Новые элементы добавляются независимо от этой
+    /// настройки.
+    pub comparison: Option<ZaddComparison>,
+
+    /// Учитывать в ответе элементы, оценка которых изменилась (`CH`).
+    pub changed: bool,
+}
+
+/// Сравнение новой и текущей оценок командой `ZADD`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZaddComparison {
+    /// Новая оценка должна быть больше текущей (`GT`).
+    Gt,
+
+    /// Новая оценка должна быть меньше текущей (`LT`).
+    Lt,
+}
+
+impl Zadd {
+    /// Создает новую команду `Zadd`, добавляющую `members` с их оценками в
+    /// сортированное множество `key`
+    pub fn new(key: impl ToString, members: &[(f64, impl ToString)]) -> Zadd {
+        Zadd {
+            key: key.to_string(),
+            members: members
+                .iter()
+                .map(|(score, member)| (*score, member.to_string()))
+                .collect(),
+            options: ZaddOptions::default(),
+        }
+    }
+
+    /// Задает настройки команды
+    pub fn with_options(mut self, options: ZaddOptions) -> Zadd {
+        self.options = options;
+        self
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `Zadd` из полученного кадра.
+    ///
+    /// Строка `ZADD` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 4 и более сущности:
+    ///
+    /// ```text
+    /// ZADD key [NX|XX] [GT|LT] [CH] score member [score member ...]
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Zadd> {
+        let key = parse.next_string()?;
+
+        let mut options = ZaddOptions::default();
+
+        // Настройки предшествуют оценкам. Первая строка, не являющаяся
+        // настройкой, - оценка первого элемента
+        let score = loop {
+            let token = parse.next_string()?;
+
+            let (condition, comparison) = match &token.to_uppercase()[..] {
+                "NX" => (Some(SetCondition::Nx), None),
+                "XX" => (Some(SetCondition::Xx), None),
+                "GT" => (None, Some(ZaddComparison::Gt)),
+                "LT" => (None, Some(ZaddComparison::Lt)),
+                "CH" => {
+                    options.changed = true;
+                    continue;
+                }
+                _ => break parse_score(&token)?,
+            };
+
+            if condition.is_some() && options.condition.is_some_and(|c| Some(c) != condition) {
+                return Err("ERR XX and NX options at the same time are not compatible".into());
+            }
+            if comparison.is_some() && options.comparison.is_some_and(|c| Some(c) != comparison) {
+                return Err(INCOMPATIBLE.into());
+            }
+            options.condition = condition.or(options.condition);
+            options.comparison = comparison.or(options.comparison);
+        };
+
+        if options.condition == Some(SetCondition::Nx) && options.comparison.is_some() {
+            return Err(INCOMPATIBLE.into());
+        }
+
+        let mut members = vec![(score, parse.next_string()?)];
+
+        loop {
+            let score = match parse.next_string() {
+                Ok(score) => parse_score(&score)?,
+                Err(ParseError::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            };
+            members.push((score, parse.next_string()?));
+        }
+
+        Ok(Zadd {
+            key,
+            members,
+            options,
+        })
+    }
+
+    /// Применяет команду `Zadd` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды.
+    ///
+    /// Возвращает `true`, если команда изменила данные
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<bool> {
+        let res = db.zadd_changes(&self.key, self.members, self.options);
+        let written = matches!(res, Ok((added, changed)) if added + changed > 0);
+
+        let response = match res {
+            Ok((added, changed)) if self.options.changed => {
+                Frame::Integer((added + changed) as i64)
+            }
+            Ok((added, _)) => Frame::Integer(added as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(written)
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `Zadd`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("zadd".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+
+        match self.options.condition {
+            Some(SetCondition::Nx) => frame.push_bulk(Bytes::from("nx".as_bytes())),
+            Some(SetCondition::Xx) => frame.push_bulk(Bytes::from("xx".as_bytes())),
+            None => {}
+        }
+        match self.options.comparison {
+            Some(ZaddComparison::Gt) => frame.push_bulk(Bytes::from("gt".as_bytes())),
+            Some(ZaddComparison::Lt) => frame.push_bulk(Bytes::from("lt".as_bytes())),
+            None => {}
+        }
+        if self.options.changed {
+            frame.push_bulk(Bytes::from("ch".as_bytes()));
+        }
+
+        for (score, member) in self.members {
+            frame.push_bulk(Bytes::from(score.to_string()));
+            frame.push_bulk(Bytes::from(member.into_bytes()));
+        }
+        frame
+    }
+}
+
+/// Разбирает оценку элемента. Значение `NaN` считается невалидным
+pub(super) fn parse_score(score: &str) -> crate::Result<f64> {
+    match score.parse::<f64>() {
+        Ok(score) if !score.is_nan() => Ok(score),
+        _ => Err("Ошибка протокола; невалидное число с плавающей точкой".into()),
+    }
+}
@@ -0,0 +1,88 @@
+use crate::cmd::zrange::{parse_score_range, score_bound_to_string};
+use crate::{Connection, Db, Frame, Parse};
+
+use bytes::Bytes;
+use std::ops::Bound;
+use tracing::{debug, instrument};
+
+/// Возвращает количество элементов сортированного множества, оценки которых
+/// находятся между `min` и `max`.
+///
+/// Границы задаются так же, как в `ZRANGEBYSCORE`
+#[derive(Debug)]
+pub struct Zcount {
+    /// Ключ
+    key: String,
+
+    /// Нижняя граница оценок
+    min: Bound<f64>,
+
+    /// Верхняя граница оценок
+    max: Bound<f64>,
+}
+
+impl Zcount {
+    /// Создает новую команду `Zcount`, запрашивающую количество элементов
+    /// сортированного множества `key` с оценками между `min` и `max`
+    pub fn new(key: impl ToString, min: Bound<f64>, max: Bound<f64>) -> Zcount {
+        Zcount {
+            key: key.to_string(),
+            min,
+            max,
+        }
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `Zcount` из полученного кадра.
+    ///
+    /// Строка `ZCOUNT` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 4 сущности:
+    ///
+    /// ```text
+    /// ZCOUNT key min max
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Zcount> {
+        let key = parse.next_string()?;
+        let (min, max) = parse_score_range(&parse.next_string()?, &parse.next_string()?)?;
+
+        Ok(Zcount { key, min, max })
+    }
+
+    /// Применяет команду `Zcount` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = match db.zcount(&self.key, self.min, self.max) {
+            Ok(count) => Frame::Integer(count as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `Zcount`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("zcount".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        frame.push_bulk(Bytes::from(score_bound_to_string(self.min, "-inf")));
+        frame.push_bulk(Bytes::from(score_bound_to_string(self.max, "+inf")));
+        frame
+    }
+}
@@ -0,0 +1,98 @@
+use crate::value::format_float;
+use crate::{Connection, Db, Frame, Parse};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// Увеличивает оценку элемента сортированного множества на заданную
+/// величину.
+///
+/// Отсутствующие множество и элемент создаются с оценкой `0`. Возвращается
+/// новая оценка элемента
+#[derive(Debug)]
+pub struct ZincrBy {
+    /// Ключ
+    key: String,
+
+    /// Величина увеличения
+    increment: f64,
+
+    /// Элемент
+    member: String,
+}
+
+impl ZincrBy {
+    /// Создает новую команду `ZincrBy`, увеличивающую оценку элемента
+    /// `member` сортированного множества `key` на `increment`
+    pub fn new(key: impl ToString, increment: f64, member: impl ToString) -> ZincrBy {
+        ZincrBy {
+            key: key.to_string(),
+            increment,
+            member: member.to_string(),
+        }
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `ZincrBy` из полученного кадра.
+    ///
+    /// Строка `ZINCRBY` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 4 сущности:
+    ///
+    /// ```text
+    /// ZINCRBY key increment member
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<ZincrBy> {
+        let key = parse.next_string()?;
+        let increment = parse.next_float()?;
+        let member = parse.next_string()?;
+
+        Ok(ZincrBy {
+            key,
+            increment,
+            member,
+        })
+    }
+
+    /// Применяет команду `ZincrBy` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды.
+    ///
+    /// Возвращает `true`, если команда изменила данные
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<bool> {
+        let res = db.zincr_by(&self.key, &self.member, self.increment);
+        let written = res.is_ok();
+
+        let response = match res {
+            Ok(score) => Frame::Bulk(Bytes::from(format_float(score))),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(written)
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `ZincrBy`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("zincrby".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        frame.push_bulk(Bytes::from(self.increment.to_string()));
+        frame.push_bulk(Bytes::from(self.member.into_bytes()));
+        frame
+    }
+}
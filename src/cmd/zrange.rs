@@ -0,0 +1,253 @@
+use crate::parse::ParseError;
+use crate::value::format_float;
+use crate::{Connection, Db, Frame, Parse};
+
+use bytes::Bytes;
+use std::convert::TryFrom;
+use std::fmt::Display;
+use std::ops::Bound;
+use tracing::{debug, instrument};
+
+/// Возвращает элементы сортированного множества с позициями от `start` до
+/// `stop` включительно.
+///
+/// Позиции отсчитываются от элемента с наименьшей оценкой, а с настройкой
+/// `REV` - с наибольшей. Отрицательные позиции отсчитываются от конца. С
+/// настройкой `WITHSCORES` за каждым элементом следует его оценка
+#[derive(Debug)]
+pub struct Zrange {
+    /// Ключ
+    key: String,
+
+    /// Начальная позиция
+    start: i64,
+
+    /// Конечная позиция
+    stop: i64,
+
+    /// Отсчитывать ли позиции от наибольшей оценки
+    rev: bool,
+
+    /// Возвращать ли оценки элементов
+    with_scores: bool,
+}
+
+impl Zrange {
+    /// Создает новую команду `Zrange`, запрашивающую элементы сортированного
+    /// множества `key` с позициями от `start` до `stop`
+    pub fn new(key: impl ToString, start: i64, stop: i64) -> Zrange {
+        Zrange {
+            key: key.to_string(),
+            start,
+            stop,
+            rev: false,
+            with_scores: false,
+        }
+    }
+
+    /// Отсчитывать позиции от наибольшей оценки (`REV`)
+    pub fn with_rev(mut self, rev: bool) -> Zrange {
+        self.rev = rev;
+        self
+    }
+
+    /// Возвращать оценки элементов (`WITHSCORES`)
+    pub fn with_scores(mut self, with_scores: bool) -> Zrange {
+        self.with_scores = with_scores;
+        self
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `Zrange` из полученного кадра.
+    ///
+    /// Строка `ZRANGE` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий от 4 до 6 сущностей:
+    ///
+    /// ```text
+    /// ZRANGE key start stop [REV] [WITHSCORES]
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Zrange> {
+        let key = parse.next_string()?;
+        let start = parse.next_signed_int()?;
+        let stop = parse.next_signed_int()?;
+
+        let mut zrange = Zrange::new(key, start, stop);
+
+        loop {
+            let option = match parse.next_string() {
+                Ok(option) => option.to_uppercase(),
+                Err(ParseError::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            };
+
+            match &option[..] {
+                "REV" => zrange.rev = true,
+                "WITHSCORES" => zrange.with_scores = true,
+                _ => {
+                    return Err(format!(
+                        "Ошибка протокола; недопустимая настройка `ZRANGE`: {}",
+                        option
+                    )
+                    .into())
+                }
+            }
+        }
+
+        Ok(zrange)
+    }
+
+    /// Применяет команду `Zrange` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = match db.zrange(&self.key, self.start, self.stop, self.rev) {
+            Ok(members) => members_frame(members, self.with_scores),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `Zrange`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("zrange".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        frame.push_int(self.start);
+        frame.push_int(self.stop);
+        if self.rev {
+            frame.push_bulk(Bytes::from("rev".as_bytes()));
+        }
+        if self.with_scores {
+            frame.push_bulk(Bytes::from("withscores".as_bytes()));
+        }
+        frame
+    }
+}
+
+/// Создает ответ из элементов сортированного множества и, если
+/// `with_scores` равен `true`, их оценок
+pub(super) fn members_frame(members: Vec<(String, f64)>, with_scores: bool) -> Frame {
+    let mut frame = Frame::array();
+    for (member, score) in members {
+        frame.push_bulk(Bytes::from(member.into_bytes()));
+        if with_scores {
+            frame.push_bulk(Bytes::from(format_float(score)));
+        }
+    }
+    frame
+}
+
+/// Разбирает диапазон оценок `min`..`max`.
+///
+/// Граница - число, включаемое в диапазон, или число с префиксом `(`, не
+/// включаемое в диапазон. `-inf` и `+inf` обозначают неограниченный
+/// диапазон
+pub(super) fn parse_score_range(min: &str, max: &str) -> crate::Result<(Bound<f64>, Bound<f64>)> {
+    let parse_score = |bound| {
+        parse_bound(bound, "", |score| {
+            score.parse().ok().filter(|score: &f64| !score.is_nan())
+        })
+        .ok_or("ERR min or max is not a float")
+    };
+
+    Ok((parse_score(min)?, parse_score(max)?))
+}
+
+/// Разбирает лексикографический диапазон `min`..`max`.
+///
+/// Граница - строка с префиксом `[`, включаемая в диапазон, или с префиксом
+/// `(`, не включаемая в диапазон. `-` и `+` обозначают наименьшую и
+/// наибольшую строки
+pub(super) fn parse_lex_range(
+    min: &str,
+    max: &str,
+) -> crate::Result<(Bound<String>, Bound<String>)> {
+    let parse_member = |bound| {
+        parse_bound(bound, "[", |member| Some(member.to_string()))
+            .ok_or("ERR min or max not valid string range item")
+    };
+
+    let min = match min {
+        "-" => Bound::Unbounded,
+        // Диапазон, начинающийся с наибольшей строки, пуст. Пустой диапазон
+        // задается верхней границей, не включающей наименьшую строку
+        "+" => return Ok((Bound::Unbounded, Bound::Excluded(String::new()))),
+        min => parse_member(min)?,
+    };
+
+    let max = match max {
+        "+" => Bound::Unbounded,
+        "-" => Bound::Excluded(String::new()),
+        max => parse_member(max)?,
+    };
+
+    Ok((min, max))
+}
+
+/// Преобразует границу диапазона оценок в строку, которую разбирает
+/// `parse_score_range`. Неограниченная граница записывается как `unbounded`
+pub(super) fn score_bound_to_string(bound: Bound<f64>, unbounded: &str) -> String {
+    bound_to_string(bound, "", unbounded)
+}
+
+/// Преобразует границу лексикографического диапазона в строку, которую
+/// разбирает `parse_lex_range`. Неограниченная граница записывается как
+/// `unbounded`
+pub(super) fn lex_bound_to_string(bound: Bound<&str>, unbounded: &str) -> String {
+    bound_to_string(bound, "[", unbounded)
+}
+
+/// Разбирает границу диапазона: значение с префиксом `(` не включается в
+/// диапазон, значение с префиксом `inclusive` - включается. Возвращает
+/// `None`, если граница невалидна
+fn parse_bound<T>(
+    bound: &str,
+    inclusive: &str,
+    parse_value: impl FnOnce(&str) -> Option<T>,
+) -> Option<Bound<T>> {
+    match bound.strip_prefix('(') {
+        Some(value) => parse_value(value).map(Bound::Excluded),
+        None => bound
+            .strip_prefix(inclusive)
+            .and_then(parse_value)
+            .map(Bound::Included),
+    }
+}
+
+/// Преобразует границу диапазона в строку, которую разбирает `parse_bound`
+fn bound_to_string<T: Display>(bound: Bound<T>, inclusive: &str, unbounded: &str) -> String {
+    match bound {
+        Bound::Included(value) => format!("{}{}", inclusive, value),
+        Bound::Excluded(value) => format!("({}", value),
+        Bound::Unbounded => unbounded.to_string(),
+    }
+}
+
+/// Возвращает смещение и количество элементов для `Db` по настройке
+/// `LIMIT offset count` или `None`, если ответ пуст.
+///
+/// Как и в Redis, отрицательное смещение дает пустой ответ, а отрицательное
+/// количество снимает ограничение
+pub(super) fn limit_range(limit: Option<(i64, i64)>) -> Option<(usize, Option<usize>)> {
+    match limit {
+        Some((offset, count)) => Some((usize::try_from(offset).ok()?, usize::try_from(count).ok())),
+        None => Some((0, None)),
+    }
+}
@@ -0,0 +1,140 @@
+use crate::cmd::zrange::{lex_bound_to_string, limit_range, members_frame, parse_lex_range};
+use crate::parse::ParseError;
+use crate::{Connection, Db, Frame, Parse};
+
+use bytes::Bytes;
+use std::ops::Bound;
+use tracing::{debug, instrument};
+
+/// Возвращает элементы сортированного множества, находящиеся между `min` и
+/// `max` в лексикографическом порядке.
+///
+/// Как и в Redis, предполагается, что оценки всех элементов одинаковы.
+/// Граница с префиксом `[` включается в диапазон, с префиксом `(` - не
+/// включается, `-` и `+` обозначают наименьшую и наибольшую строки.
+/// Настройка `LIMIT` работает так же, как в `ZRANGEBYSCORE`
+#[derive(Debug)]
+pub struct ZrangeByLex {
+    /// Ключ
+    key: String,
+
+    /// Нижняя граница
+    min: Bound<String>,
+
+    /// Верхняя граница
+    max: Bound<String>,
+
+    /// Количество пропускаемых элементов и максимальное количество
+    /// возвращаемых элементов
+    limit: Option<(i64, i64)>,
+}
+
+impl ZrangeByLex {
+    /// Создает новую команду `ZrangeByLex`, запрашивающую элементы
+    /// сортированного множества `key` между `min` и `max`
+    pub fn new(key: impl ToString, min: Bound<&str>, max: Bound<&str>) -> ZrangeByLex {
+        ZrangeByLex {
+            key: key.to_string(),
+            min: min.map(str::to_string),
+            max: max.map(str::to_string),
+            limit: None,
+        }
+    }
+
+    /// Пропустить первые `offset` элементов и вернуть не больше `count`
+    /// элементов (`LIMIT`)
+    pub fn with_limit(mut self, offset: i64, count: i64) -> ZrangeByLex {
+        self.limit = Some((offset, count));
+        self
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `ZrangeByLex` из полученного кадра.
+    ///
+    /// Строка `ZRANGEBYLEX` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 4 или 7 сущностей:
+    ///
+    /// ```text
+    /// ZRANGEBYLEX key min max [LIMIT offset count]
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<ZrangeByLex> {
+        let key = parse.next_string()?;
+        let (min, max) = parse_lex_range(&parse.next_string()?, &parse.next_string()?)?;
+
+        let limit = match parse.next_string() {
+            Ok(option) if option.eq_ignore_ascii_case("limit") => {
+                let offset = parse.next_signed_int()?;
+                let count = parse.next_signed_int()?;
+                Some((offset, count))
+            }
+            Ok(option) => {
+                return Err(format!(
+                    "Ошибка протокола; недопустимая настройка `ZRANGEBYLEX`: {}",
+                    option
+                )
+                .into())
+            }
+            Err(ParseError::EndOfStream) => None,
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(ZrangeByLex {
+            key,
+            min,
+            max,
+            limit,
+        })
+    }
+
+    /// Применяет команду `ZrangeByLex` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let min = self.min.as_ref().map(String::as_str);
+        let max = self.max.as_ref().map(String::as_str);
+
+        let response = match limit_range(self.limit) {
+            Some((offset, count)) => match db.zrange_by_lex(&self.key, min, max, offset, count) {
+                Ok(members) => members_frame(members, false),
+                Err(err) => Frame::Error(err.to_string()),
+            },
+            None => Frame::array(),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `ZrangeByLex`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let min = lex_bound_to_string(self.min.as_ref().map(String::as_str), "-");
+        let max = lex_bound_to_string(self.max.as_ref().map(String::as_str), "+");
+
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("zrangebylex".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        frame.push_bulk(Bytes::from(min.into_bytes()));
+        frame.push_bulk(Bytes::from(max.into_bytes()));
+        if let Some((offset, count)) = self.limit {
+            frame.push_bulk(Bytes::from("limit".as_bytes()));
+            frame.push_int(offset);
+            frame.push_int(count);
+        }
+        frame
+    }
+}
@@ -0,0 +1,154 @@
+use crate::cmd::zrange::{limit_range, members_frame, parse_score_range, score_bound_to_string};
+use crate::parse::ParseError;
+use crate::{Connection, Db, Frame, Parse};
+
+use bytes::Bytes;
+use std::ops::Bound;
+use tracing::{debug, instrument};
+
+/// Возвращает элементы сортированного множества, оценки которых находятся
+/// между `min` и `max`, в порядке возрастания оценок.
+///
+/// Граница с префиксом `(` не включается в диапазон, `-inf` и `+inf`
+/// обозначают неограниченный диапазон. С настройкой `LIMIT` пропускаются
+/// первые `offset` элементов и возвращается не больше `count` элементов
+/// (все, если `count` отрицательный). С настройкой `WITHSCORES` за каждым
+/// элементом следует его оценка
+#[derive(Debug)]
+pub struct ZrangeByScore {
+    /// Ключ
+    key: String,
+
+    /// Нижняя граница оценок
+    min: Bound<f64>,
+
+    /// Верхняя граница оценок
+    max: Bound<f64>,
+
+    /// Возвращать ли оценки элементов
+    with_scores: bool,
+
+    /// Количество пропускаемых элементов и максимальное количество
+    /// возвращаемых элементов
+    limit: Option<(i64, i64)>,
+}
+
+impl ZrangeByScore {
+    /// Создает новую команду `ZrangeByScore`, запрашивающую элементы
+    /// сортированного множества `key` с оценками между `min` и `max`
+    pub fn new(key: impl ToString, min: Bound<f64>, max: Bound<f64>) -> ZrangeByScore {
+        ZrangeByScore {
+            key: key.to_string(),
+            min,
+            max,
+            with_scores: false,
+            limit: None,
+        }
+    }
+
+    /// Возвращать оценки элементов (`WITHSCORES`)
+    pub fn with_scores(mut self, with_scores: bool) -> ZrangeByScore {
+        self.with_scores = with_scores;
+        self
+    }
+
+    /// Пропустить первые `offset` элементов и вернуть не больше `count`
+    /// элементов (`LIMIT`)
+    pub fn with_limit(mut self, offset: i64, count: i64) -> ZrangeByScore {
+        self.limit = Some((offset, count));
+        self
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `ZrangeByScore` из полученного кадра.
+    ///
+    /// Строка `ZRANGEBYSCORE` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий от 4 до 8 сущностей:
+    ///
+    /// ```text
+    /// ZRANGEBYSCORE key min max [WITHSCORES] [LIMIT offset count]
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<ZrangeByScore> {
+        let key = parse.next_string()?;
+        let (min, max) = parse_score_range(&parse.next_string()?, &parse.next_string()?)?;
+
+        let mut zrange = ZrangeByScore::new(key, min, max);
+
+        loop {
+            let option = match parse.next_string() {
+                Ok(option) => option.to_uppercase(),
+                Err(ParseError::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            };
+
+            match &option[..] {
+                "WITHSCORES" => zrange.with_scores = true,
+                "LIMIT" => {
+                    let offset = parse.next_signed_int()?;
+                    let count = parse.next_signed_int()?;
+                    zrange.limit = Some((offset, count));
+                }
+                _ => {
+                    return Err(format!(
+                        "Ошибка протокола; недопустимая настройка `ZRANGEBYSCORE`: {}",
+                        option
+                    )
+                    .into())
+                }
+            }
+        }
+
+        Ok(zrange)
+    }
+
+    /// Применяет команду `ZrangeByScore` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = match limit_range(self.limit) {
+            Some((offset, count)) => {
+                match db.zrange_by_score(&self.key, self.min, self.max, offset, count) {
+                    Ok(members) => members_frame(members, self.with_scores),
+                    Err(err) => Frame::Error(err.to_string()),
+                }
+            }
+            None => Frame::array(),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `ZrangeByScore`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("zrangebyscore".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        frame.push_bulk(Bytes::from(score_bound_to_string(self.min, "-inf")));
+        frame.push_bulk(Bytes::from(score_bound_to_string(self.max, "+inf")));
+        if self.with_scores {
+            frame.push_bulk(Bytes::from("withscores".as_bytes()));
+        }
+        if let Some((offset, count)) = self.limit {
+            frame.push_bulk(Bytes::from("limit".as_bytes()));
+            frame.push_int(offset);
+            frame.push_int(count);
+        }
+        frame
+    }
+}
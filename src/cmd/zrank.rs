@@ -0,0 +1,84 @@
+use crate::{Connection, Db, Frame, Parse};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// Возвращает позицию элемента сортированного множества в порядке
+/// возрастания оценок.
+///
+/// Позиции отсчитываются от `0`. При отсутствии множества или элемента
+/// возвращается специальное значение `nil`. Ошибка возвращается, если
+/// значение не является сортированным множеством
+#[derive(Debug)]
+pub struct Zrank {
+    /// Ключ
+    key: String,
+
+    /// Элемент
+    member: String,
+}
+
+impl Zrank {
+    /// Создает новую команду `Zrank`, запрашивающую позицию элемента `member`
+    /// сортированного множества `key`
+    pub fn new(key: impl ToString, member: impl ToString) -> Zrank {
+        Zrank {
+            key: key.to_string(),
+            member: member.to_string(),
+        }
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `Zrank` из полученного кадра.
+    ///
+    /// Строка `ZRANK` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 3 сущности:
+    ///
+    /// ```text
+    /// ZRANK key member
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Zrank> {
+        let key = parse.next_string()?;
+        let member = parse.next_string()?;
+
+        Ok(Zrank { key, member })
+    }
+
+    /// Применяет команду `Zrank` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = match db.zrank(&self.key, &self.member) {
+            Ok(Some(rank)) => Frame::Integer(rank as i64),
+            Ok(None) => Frame::Null,
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `Zrank`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("zrank".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        frame.push_bulk(Bytes::from(self.member.into_bytes()));
+        frame
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{Connection, Db, Frame, Parse, ParseError};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// Удаляет элементы сортированного множества.
+///
+/// Возвращается количество удаленных элементов. Множество без элементов
+/// удаляется
+#[derive(Debug)]
+pub struct Zrem {
+    /// Ключ
+    key: String,
+
+    /// Удаляемые элементы
+    members: Vec<String>,
+}
+
+impl Zrem {
+    /// Создает новую команду `Zrem`, удаляющую элементы `members`
+    /// сортированного множества `key`
+    pub fn new(key: impl ToString, members: &[impl ToString]) -> Zrem {
+        Zrem {
+            key: key.to_string(),
+            members: members.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `Zrem` из полученного кадра.
+    ///
+    /// Строка `ZREM` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 3 и более сущности:
+    ///
+    /// ```text
+    /// ZREM key member [member ...]
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Zrem> {
+        let key = parse.next_string()?;
+
+        // Должен быть указан хотя бы один элемент
+        let mut members = vec![parse.next_string()?];
+
+        loop {
+            match parse.next_string() {
+                Ok(member) => members.push(member),
+                Err(ParseError::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(Zrem { key, members })
+    }
+
+    /// Применяет команду `Zrem` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды.
+    ///
+    /// Возвращает `true`, если команда изменила данные
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<bool> {
+        let res = db.zrem(&self.key, &self.members);
+        let written = matches!(res, Ok(removed) if removed > 0);
+
+        let response = match res {
+            Ok(removed) => Frame::Integer(removed as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(written)
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `Zrem`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("zrem".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        for member in self.members {
+            frame.push_bulk(Bytes::from(member.into_bytes()));
+        }
+        frame
+    }
+}
@@ -0,0 +1,84 @@
+use crate::value::format_float;
+use crate::{Connection, Db, Frame, Parse};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// Возвращает оценку элемента сортированного множества.
+///
+/// При отсутствии множества или элемента возвращается специальное значение
+/// `nil`. Ошибка возвращается, если значение не является сортированным
+/// множеством
+#[derive(Debug)]
+pub struct Zscore {
+    /// Ключ
+    key: String,
+
+    /// Элемент
+    member: String,
+}
+
+impl Zscore {
+    /// Создает новую команду `Zscore`, запрашивающую оценку элемента `member`
+    /// сортированного множества `key`
+    pub fn new(key: impl ToString, member: impl ToString) -> Zscore {
+        Zscore {
+            key: key.to_string(),
+            member: member.to_string(),
+        }
+    }
+
+    /// Возвращает ключ
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Разбирает экземпляр `Zscore` из полученного кадра.
+    ///
+    /// Строка `ZSCORE` уже потреблена.
+    ///
+    /// # Формат
+    ///
+    /// Ожидается массив кадров, содержащий 3 сущности:
+    ///
+    /// ```text
+    /// ZSCORE key member
+    /// ```
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Zscore> {
+        let key = parse.next_string()?;
+        let member = parse.next_string()?;
+
+        Ok(Zscore { key, member })
+    }
+
+    /// Применяет команду `Zscore` к определенному экземпляру `Db`.
+    ///
+    /// Ответ записывается в `dst`. Это вызывается сервером для
+    /// выполнения полученной команды
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = match db.zscore(&self.key, &self.member) {
+            Ok(Some(score)) => Frame::Bulk(Bytes::from(format_float(score))),
+            Ok(None) => Frame::Null,
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// Преобразует команду в соответствующий `Frame`.
+    ///
+    /// Это вызывается клиентом при кодировке команды `Zscore`
+    /// для отправки на сервер
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("zscore".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        frame.push_bulk(Bytes::from(self.member.into_bytes()));
+        frame
+    }
+}
@@ -0,0 +1,116 @@
+//! Кодек кадров для [`tokio_util::codec`].
+//!
+//! `FrameCodec` позволяет обернуть любой транспорт в
+//! `Framed<T, FrameCodec>` и получить `Stream` входящих кадров и `Sink`
+//! исходящих кадров. Это удобно для прокси, анализаторов трафика и
+//! собственных серверов, которым нужен протокол `mini-redis`, но не нужен
+//! `Connection`.
+//!
+//! # Примеры
+//!
+//! ```no_run
+//! use futures_util::SinkExt;
+//! use mini_redis::codec::FrameCodec;
+//! use mini_redis::Frame;
+//! use tokio::net::TcpStream;
+//! use tokio_stream::StreamExt;
+//! use tokio_util::codec::Framed;
+//!
+//! #[tokio::main]
+//! async fn main() -> mini_redis::Result<()> {
+//!     let socket = TcpStream::connect("127.0.0.1:6379").await?;
+//!     let mut framed = Framed::new(socket, FrameCodec::new());
+//!
+//!     framed
+//!         .send(Frame::Array(vec![Frame::Bulk("ping".into())]))
+//!         .await?;
+//!     println!("{:?}", framed.next().await);
+//!
+//!     Ok(())
+//! }
+//! ```
+//!
+//! [`tokio_util::codec`]: https://docs.rs/tokio-util/*/tokio_util/codec/index.html
+
+use crate::frame::{self, Frame};
+
+use bytes::{Buf, BytesMut};
+use std::io::Cursor;
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Кодек, разбирающий и кодирующий значения `Frame`.
+///
+/// Разбор выполняется так же, как в `Connection`: по умолчанию допускаются
+/// отклонения от формата, а строгий режим (`FrameCodec::strict`) проверяет
+/// кадры с помощью `Frame::check_strict`.
+#[derive(Debug, Clone, Default)]
+pub struct FrameCodec {
+    /// Строгая проверка формата входящих кадров.
+    strict: bool,
+}
+
+impl FrameCodec {
+    /// Создает кодек с нестрогой проверкой формата.
+    pub fn new() -> FrameCodec {
+        FrameCodec::default()
+    }
+
+    /// Создает кодек со строгой проверкой формата входящих кадров.
+    pub fn strict() -> FrameCodec {
+        FrameCodec { strict: true }
+    }
+}
+
+impl Decoder for FrameCodec {
+    type Item = Frame;
+    type Error = crate::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> crate::Result<Option<Frame>> {
+        let mut buf = Cursor::new(&src[..]);
+
+        // Сначала проверяем, что буфер содержит полный кадр, затем
+        // разбираем его
+        let check = if self.strict {
+            Frame::check_strict(&mut buf)
+        } else {
+            Frame::check(&mut buf)
+        };
+
+        match check {
+            Ok(()) => {
+                let len = buf.position() as usize;
+                buf.set_position(0);
+
+                let frame = if self.strict {
+                    Frame::parse_strict(&mut buf)?
+                } else {
+                    Frame::parse(&mut buf)?
+                };
+
+                src.advance(len);
+                Ok(Some(frame))
+            }
+            // Данных недостаточно, `Framed` прочитает больше
+            Err(frame::Error::Incomplete) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+impl Encoder<Frame> for FrameCodec {
+    type Error = crate::Error;
+
+    fn encode(&mut self, frame: Frame, dst: &mut BytesMut) -> crate::Result<()> {
+        frame.encode(dst);
+        Ok(())
+    }
+}
+
+impl Encoder<&Frame> for FrameCodec {
+    type Error = crate::Error;
+
+    fn encode(&mut self, frame: &Frame, dst: &mut BytesMut) -> crate::Result<()> {
+        frame.encode(dst);
+        Ok(())
+    }
+}
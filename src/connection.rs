@@ -1,15 +1,17 @@
 use crate::frame::{self, Frame};
 
 use bytes::{Buf, BytesMut};
+use std::fmt;
 use std::io::{self, Cursor};
-use tokio::io::{AsyncReadExt, AsyncWriteExt, BufWriter};
-use tokio::net::TcpStream;
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufWriter, ReadBuf};
 
 /// Отправляет и получает значения `Frame` от сервера.
 ///
 /// При реализации сетевых протоколов, сообщение протокола (protocol message)
 /// часто состоит из нескольких меньших сообщений - кадров (frames). Цель
-/// `Connection` - читать и записывать кадры в `TcpStream`.
+/// `Connection` - читать и записывать кадры в транспорт (обычно `TcpStream`).
 ///
 /// Для чтения кадров `Connection` использует внутренний буфер, который заполняется
 /// до тех пор, пока в нем не окажется достаточно байтов для создания полного кадра. Как только это произошло,
@@ -17,31 +19,57 @@ use tokio::net::TcpStream;
 ///
 /// При отправке кадров, кадр сначала кодируется в буфер для записи.
 /// Содержимое буфера для записи затем записывается в сокет.
-#[derive(Debug)]
+///
+/// Транспорт не обязан быть сокетом TCP: `Connection` работает поверх любого
+/// типа, реализующего `AsyncRead` и `AsyncWrite`, например, `tokio::io::DuplexStream`
+/// для обмена кадрами внутри одного процесса.
 pub struct Connection {
-    // `TcpStream` декорируется с помощью `BufWriter`, который предоставляет буфер
+    // Транспорт декорируется с помощью `BufWriter`, который предоставляет буфер
     // для записи. Реализация `BufWriter`, предоставляемая Tokio,
     // достаточна для наших нужд.
-    stream: BufWriter<TcpStream>,
+    //
+    // Тип транспорта стирается, чтобы `Connection` (а вместе с ним `Client`
+    // и обработчик соединения сервера) не становился обобщенным типом.
+    stream: BufWriter<Box<dyn Io>>,
 
     // Буфер для чтения кадров.
     buffer: BytesMut,
+
+    // Строгая проверка формата входящих кадров.
+    strict: bool,
 }
 
+/// Транспорт соединения: `TcpStream`, `DuplexStream` и др.
+trait Io: AsyncRead + AsyncWrite + Send + Unpin {}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin> Io for T {}
+
 impl Connection {
     /// Создает новый `Connection`, поддерживаемый `socket`.
     /// Инициализируются буферы для чтения и записи
-    pub fn new(socket: TcpStream) -> Connection {
+    pub fn new<T>(socket: T) -> Connection
+    where
+        T: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+    {
         Connection {
-            stream: BufWriter::new(socket),
+            stream: BufWriter::new(Box::new(socket)),
             // Дефолтный 4 КБ буфер для чтения. Для целей `mini-redis`
             // этого достаточно. Размер буфера в реальных приложениях
             // будет зависеть от их нужд. Высока вероятность, что
             // буфер большего размера будет работать лучше.
             buffer: BytesMut::with_capacity(4 * 1024),
+            strict: false,
         }
     }
 
+    /// Включает строгую проверку формата входящих кадров.
+    ///
+    /// Кадры проверяются с помощью `Frame::check_strict` вместо
+    /// `Frame::check`. По умолчанию проверка не строгая.
+    pub fn set_strict_framing(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
     /// Читает значение `Frame` из потока.
     ///
     /// Функция ждет достаточного количества данных для разбора кадра.
@@ -50,7 +78,7 @@ impl Connection {
     ///
     /// # Возвращаемые значения
     ///
-    /// При успехе возвращается полученный кадр. Если `TcpStream`
+    /// При успехе возвращается полученный кадр. Если транспорт
     /// закрыт способом, который не нарушает целостность кадра, возвращается
     /// `None`. Иначе, возвращается ошибка.
     pub async fn read_frame(&mut self) -> crate::Result<Option<Frame>> {
@@ -74,7 +102,11 @@ impl Connection {
                 if self.buffer.is_empty() {
                     return Ok(None);
                 } else {
-                    return Err("connection reset by peer".into());
+                    return Err(io::Error::new(
+                        io::ErrorKind::ConnectionReset,
+                        "connection reset by peer",
+                    )
+                    .into());
                 }
             }
         }
@@ -96,7 +128,13 @@ impl Connection {
         // кадра. Этот шаг обычно гораздо быстрее, чем полный разбор
         // кадра, и позволяет пропустить выделение структур данных
         // для хранения данных до получения всего кадра.
-        match Frame::check(&mut buf) {
+        let check = if self.strict {
+            Frame::check_strict(&mut buf)
+        } else {
+            Frame::check(&mut buf)
+        };
+
+        match check {
             Ok(_) => {
                 // Функция `check` перемещает (advance) курсор в
                 // конец кадра. Поскольку позиция курсора устанавливается в ноль
@@ -114,7 +152,11 @@ impl Connection {
                 // Если кодированное представление кадра является невалидным,
                 // возвращается ошибка. Это должно приводить к закрытию текущего соединения,
                 // но не должно влиять на других подключенных клиентов.
-                let frame = Frame::parse(&mut buf)?;
+                let frame = if self.strict {
+                    Frame::parse_strict(&mut buf)?
+                } else {
+                    Frame::parse(&mut buf)?
+                };
 
                 // Отбрасываем (discard) разобранные данные из буфера для чтения.
                 //
@@ -141,41 +183,65 @@ impl Connection {
         }
     }
 
+    /// Проверяет без ожидания, что соединение пригодно для отправки запроса.
+    ///
+    /// Соединение, ожидающее запроса, не должно получать данных. Конец
+    /// потока, ошибка чтения или неожиданные данные (например, ошибка
+    /// `SHUTDOWN`, отправленная сервером перед закрытием соединения) означают,
+    /// что соединение непригодно. Полученные данные сохраняются в буфере для
+    /// чтения.
+    pub(crate) fn is_idle(&mut self) -> bool {
+        if !self.buffer.is_empty() {
+            return false;
+        }
+
+        // Транспорт опрашивается один раз. Пробуждение не требуется,
+        // поэтому используется пустой `Waker`
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut data = [0; 64];
+        let mut buf = ReadBuf::new(&mut data);
+
+        match Pin::new(&mut self.stream).poll_read(&mut cx, &mut buf) {
+            Poll::Pending => true,
+            Poll::Ready(Ok(())) => {
+                self.buffer.extend_from_slice(buf.filled());
+                false
+            }
+            Poll::Ready(Err(_)) => false,
+        }
+    }
+
     /// Записывает значение `Frame` в поток.
     ///
     /// Значение `Frame` записывается в сокет с помощью различных функций
     /// `write_*`, предоставляемых `AsyncWrite`. Вызывать эти функции прямо на
-    /// `TcpStream` не рекомендуется, поскольку это приведет к большому количеству
+    /// сокете не рекомендуется, поскольку это приведет к большому количеству
     /// системных вызовов (syscalls). Эти функции лучше вызывать на буферизованном
     /// потоке для записи. Данные записываются в буфер. При заполнении
     /// буфера, данные передаются (flush) сокету.
     pub async fn write_frame(&mut self, frame: &Frame) -> io::Result<()> {
-        // Массивы кодируются путем кодирования каждого элемента. Другие типы кадров
-        // считаются литералами. `mini-redis` не умеет рекурсивно кодировать
-        // вложенные структуры.
-        match frame {
-            Frame::Array(val) => {
-                // Кодируем префикс типа кадра. Для массива таким префиксом является `*`.
-                self.stream.write_u8(b'*').await?;
-
-                // Кодируем длину массива.
-                self.write_decimal(val.len() as u64).await?;
-
-                // Перебираем и кодируем каждый элемент массива.
-                for entry in &**val {
-                    self.write_value(entry).await?;
-                }
-            }
-            // Типом кадра является литерал. Кодируем значение.
-            _ => self.write_value(frame).await?,
-        }
+        // Кодируем кадр в буфер для записи.
+        self.write_value(frame).await?;
 
         // Закодированный кадр должен быть записан в сокет.
         // Вызов `flush` записывает содержимое буфера в сокет.
         self.stream.flush().await
     }
 
-    /// Записывает литерал кадра в поток.
+    /// Передает оставшиеся данные буфера для записи и закрывает поток
+    /// для записи.
+    ///
+    /// Другая сторона получает признак конца потока после всех отправленных
+    /// кадров. Чтение из соединения по-прежнему возможно.
+    pub async fn shutdown(&mut self) -> io::Result<()> {
+        self.stream.shutdown().await
+    }
+
+    /// Записывает кадр в поток.
+    ///
+    /// Массивы кодируются путем рекурсивного кодирования каждого элемента.
+    /// Рекурсивный вызов асинхронной функции требует размещения ее
+    /// `Future` в куче, поэтому вызов оборачивается в `Box::pin`.
     async fn write_value(&mut self, frame: &Frame) -> io::Result<()> {
         match frame {
             Frame::Simple(val) => {
@@ -199,21 +265,29 @@ impl Connection {
                 let len = val.len();
 
                 self.stream.write_u8(b'$').await?;
-                self.write_decimal(len as u64).await?;
+                self.write_decimal(len as i64).await?;
                 self.stream.write_all(val).await?;
                 self.stream.write_all(b"\r\n").await?;
             }
-            // Кодирование `Array` из значения не может быть выполнено рекурсивно.
-            // В настоящее время асинхронные функции не поддерживают рекурсию.
-            // `mini-redis` не поддерживает кодирование вложенных массивов.
-            Frame::Array(_val) => unreachable!(),
+            Frame::Array(val) => {
+                // Кодируем префикс типа кадра. Для массива таким префиксом является `*`.
+                self.stream.write_u8(b'*').await?;
+
+                // Кодируем длину массива.
+                self.write_decimal(val.len() as i64).await?;
+
+                // Перебираем и кодируем каждый элемент массива.
+                for entry in val {
+                    Box::pin(self.write_value(entry)).await?;
+                }
+            }
         }
 
         Ok(())
     }
 
     /// Записывает десятичный кадр в поток.
-    async fn write_decimal(&mut self, val: u64) -> io::Result<()> {
+    async fn write_decimal(&mut self, val: i64) -> io::Result<()> {
         use std::io::Write;
 
         // Преобразуем значение в строку.
@@ -228,3 +302,12 @@ impl Connection {
         Ok(())
     }
 }
+
+impl fmt::Debug for Connection {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Connection")
+            .field("buffer", &self.buffer)
+            .field("strict", &self.strict)
+            .finish_non_exhaustive()
+    }
+}
@@ -1,16 +1,33 @@
 use tokio::sync::{broadcast, Notify};
 use tokio::time::{self, Duration, Instant};
 
-use bytes::Bytes;
-use std::collections::{BTreeSet, HashMap};
+use crate::cmd::{SetCondition, ZaddComparison, ZaddOptions};
+#[cfg(feature = "json")]
+use crate::json;
+use crate::memory::MemoryStats;
+use crate::snapshot::{Snapshot, SnapshotEntry, SnapshotValue};
+use crate::throttle::{self, Throttle};
+use crate::value::{format_float, parse_int, Value};
+use crate::zset::SortedSet;
+use crate::{glob, ring, serialize};
+
+use bytes::{Bytes, BytesMut};
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::mem;
+use std::ops::Bound;
 use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 use tracing::debug;
 
 /// Обертка над экземпляром `Db`. Это необходимо для упорядоченной очистки
 /// `Db` путем указания фоновой задаче очистки (purge task) закрыться при
 /// уничтожении (drop) структуры.
+///
+/// Экземпляры `Db` создаются только через `DbDropGuard`. Для передачи
+/// `Db` в `server::serve_connection` или `server::connect_in_memory`
+/// используется метод `db`.
 #[derive(Debug)]
-pub(crate) struct DbDropGuard {
+pub struct DbDropGuard {
     /// Экземпляр `Db`, который будет закрыт, когда эта структура будет уничтожена.
     db: Db,
 }
@@ -23,12 +40,16 @@ pub(crate) struct DbDropGuard {
 /// Экземпляр `Db` - это обработчик общего состояния. Клонирование `Db` является поверхностным и
 /// приводит лишь к атомарному увеличению счетчика.
 ///
+/// `Db` может использоваться без сервера как встроенный (in-process) кэш
+/// с временем жизни значений и pub/sub. Экземпляр создается с помощью
+/// `DbDropGuard::new` и извлекается методом `DbDropGuard::db`.
+///
 /// При создании значения `Db` порождается (spawn) фоновая задача. Эта задача
 /// используется для уничтожения (expire) значений после истечения определенного времени. Задача
 /// запускается до тех пор, пока все экземпляры `Db` не будут уничтожены, после чего задача
 /// прерывается (terminates).
 #[derive(Debug, Clone)]
-pub(crate) struct Db {
+pub struct Db {
     /// Обработчик общего состояния. Фоновая задача также будет иметь
     /// `Arc<Shared>`.
     shared: Arc<Shared>,
@@ -52,6 +73,10 @@ struct Shared {
     /// Уведомляет фоновую задачу, обрабатывающую истечение времени жизни сущности.
     /// Фоновая задача ждет уведомления, затем проверяет время жизни значений или наличие сигнала о закрытии.
     background_task: Notify,
+
+    /// Настройки внедряемых сбоев.
+    #[cfg(feature = "chaos")]
+    chaos: crate::chaos::Chaos,
 }
 
 #[derive(Debug)]
@@ -75,37 +100,216 @@ struct State {
     /// используется `String`, а не `Instant`.
     expirations: BTreeSet<(Instant, String)>,
 
+    /// Упорядоченный индекс ключей для запросов по префиксу (см.
+    /// `Db::set_prefix_index`). `None`, если индекс отключен.
+    ///
+    /// Ключи с общим префиксом расположены в индексе подряд, поэтому `KEYS`
+    /// и `SCAN` с шаблоном, начинающимся с префикса без специальных
+    /// символов, перебирают только ключи с этим префиксом.
+    prefix_index: Option<BTreeSet<String>>,
+
+    /// Ключи в порядке перебора `SCAN`: по хешу ключа, а при совпадении
+    /// хешей - по самому ключу. Позволяет начинать перебор с курсора, не
+    /// рассматривая остальные ключи.
+    scan_index: BTreeSet<(u64, String)>,
+
+    /// Задачи, ожидающие изменения ключей блокирующими операциями.
+    waiters: Waiters,
+
     /// `true`, когда экземпляр `Db` закрыт. Это происходит, когда все
     /// значения `Db` уничтожены. Установка этого поля в значение `true`
     /// указывает фоновым задачам закрыться.
     shutdown: bool,
+
+    /// `true`, если фоновая задача удаляет истекшие ключи. Если очистка
+    /// отключена, истекшие ключи остаются в памяти, но не возвращаются
+    /// при чтении.
+    active_expire: bool,
+
+    /// Максимальное количество ключей, удаляемых за один проход очистки.
+    /// Ограничение не позволяет фоновой задаче надолго блокировать мьютекс.
+    purge_batch_size: usize,
+
+    /// Минимальный интервал между проходами очистки.
+    purge_min_interval: Duration,
 }
 
+/// Максимальное количество ключей, удаляемых за один проход очистки, если
+/// оно не настроено с помощью `Db::set_purge_batch_size`.
+const PURGE_BATCH_SIZE: usize = 1000;
+
+/// Ошибка, возвращаемая при выполнении команды над значением другого типа.
+pub(crate) const WRONGTYPE: &str =
+    "WRONGTYPE Operation against a key holding the wrong kind of value";
+
+/// Ошибка, возвращаемая при изменении счетчика, значение которого не является
+/// целым числом.
+const NOT_INTEGER: &str = "ERR value is not an integer or out of range";
+
+/// Ошибка, возвращаемая при переполнении счетчика.
+const OVERFLOW: &str = "ERR increment or decrement would overflow";
+
+/// Максимальная длина строки, которую можно получить с помощью `SETRANGE`,
+/// как `proto-max-bulk-len` в Redis.
+const STRING_MAX_LEN: usize = 512 * 1024 * 1024;
+
+/// Ошибка, возвращаемая при превышении `STRING_MAX_LEN`.
+const STRING_TOO_LONG: &str = "ERR string exceeds maximum allowed size (proto-max-bulk-len)";
+
+/// Ошибка, возвращаемая при изменении числа с плавающей точкой, значение
+/// которого не является числом.
+const NOT_FLOAT: &str = "ERR value is not a valid float";
+
+/// Ошибка, возвращаемая, если новое значение числа с плавающей точкой не
+/// является конечным.
+const NOT_FINITE: &str = "ERR increment would produce NaN or Infinity";
+
+/// Ошибка, возвращаемая при изменении поля хеша, значение которого не
+/// является целым числом.
+const HASH_NOT_INTEGER: &str = "ERR hash value is not an integer";
+
+/// Максимальное количество случайных элементов с повторениями, возвращаемых
+/// за один вызов (отрицательное количество в `HRANDFIELD`).
+const RANDOM_COUNT_MAX: u64 = 1 << 20;
+
+/// Ошибка, возвращаемая, если новая оценка элемента сортированного
+/// множества не является числом (при сложении бесконечностей разного знака).
+const SCORE_NAN: &str = "ERR resulting score is not a number (NaN)";
+
+/// Ошибка, возвращаемая, если перебор ключей не завершился до истечения
+/// времени выполнения команды.
+const TIMED_OUT: &str = "ERR command timed out";
+
+/// Количество ключей, перебираемых между проверками времени выполнения
+/// команды.
+const DEADLINE_CHECK_INTERVAL: usize = 256;
+
 /// Сущность хранилища ключ-значение.
 #[derive(Debug)]
 struct Entry {
     /// Хранящиеся данные.
-    data: Bytes,
+    data: Value,
+
+    /// Момент истечения времени жизни сущности, после которого она
+    /// удаляется из БД.
+    expires_at: Option<Expiration>,
+}
+
+/// Момент истечения времени жизни сущности.
+///
+/// Сущности истекают по монотонным часам (`Instant`), которые не зависят от
+/// изменения системного времени. Исходный момент по системным часам
+/// хранится отдельно, чтобы `EXPIRETIME` возвращал именно его.
+#[derive(Debug, Clone, Copy)]
+struct Expiration {
+    /// Момент истечения по монотонным часам.
+    instant: Instant,
+
+    /// Момент истечения по системным часам.
+    system: SystemTime,
+}
+
+impl Entry {
+    /// Возвращает `true`, если время жизни сущности истекло к моменту `now`.
+    fn is_expired(&self, now: Instant) -> bool {
+        self.expires_at.is_some_and(|when| when.instant <= now)
+    }
+}
+
+impl Expiration {
+    /// Возвращает момент истечения через `duration` от текущего момента.
+    ///
+    /// # Паника
+    ///
+    /// Паникует, если момент не может быть представлен `Instant` или
+    /// `SystemTime`.
+    fn after(duration: Duration) -> Expiration {
+        Expiration {
+            instant: Instant::now() + duration,
+            system: SystemTime::now() + duration,
+        }
+    }
+
+    /// Возвращает момент истечения по системным часам `when`, переведенный в
+    /// `Instant` относительно текущего времени.
+    ///
+    /// Возвращает `None`, если момент уже наступил.
+    ///
+    /// # Паника
+    ///
+    /// Паникует, если момент не может быть представлен `Instant`.
+    fn at(when: SystemTime) -> Option<Expiration> {
+        let remaining = when.duration_since(SystemTime::now()).ok()?;
+
+        if remaining.is_zero() {
+            return None;
+        }
+
+        Some(Expiration {
+            instant: Instant::now() + remaining,
+            system: when,
+        })
+    }
+}
 
-    /// Момент (instant) истечения времени жизни сущности, после которого
-    /// она удаляется из БД.
-    expires_at: Option<Instant>,
+/// Реестр задач, ожидающих изменения ключей.
+///
+/// Это общая основа блокирующих операций: задача, не получившая результата,
+/// регистрируется в очередях ожидания своих ключей и засыпает, а операция,
+/// изменяющая ключ, пробуждает первую задачу очереди. Задачи пробуждаются по
+/// одной в порядке регистрации, поэтому дольше всех ожидающая задача первой
+/// получает результат. Задача, получившая результат, передает пробуждение
+/// следующей, которая проверяет, осталось ли что-то и для нее.
+///
+/// Реестр хранится в `State`, поэтому регистрация выполняется под той же
+/// блокировкой, что и проверка результата, и изменение ключа между ними не
+/// может быть пропущено. Пробуждение сохраняется `Notify` до начала
+/// ожидания, поэтому задачу можно пробуждать под блокировкой.
+#[derive(Debug, Default)]
+struct Waiters {
+    /// Очереди ожидающих задач по ключам.
+    keys: HashMap<String, VecDeque<Arc<Notify>>>,
+}
+
+/// Отменяет регистрацию задачи в реестре при завершении ожидания, в том
+/// числе при отмене future.
+struct WaitGuard<'a> {
+    /// БД, в реестре которой зарегистрирована задача.
+    db: &'a Db,
+
+    /// Ключи, изменения которых ожидает задача.
+    keys: &'a [&'a str],
+
+    /// Уведомление задачи.
+    waiter: Arc<Notify>,
+
+    /// `true`, если задача была зарегистрирована.
+    registered: bool,
 }
 
 impl DbDropGuard {
     /// Создает новый `DbDropGuard`, оборачивающий экземпляр `Db`.
     /// Когда он уничтожается, задача очистки `Db` закрывается.
-    pub(crate) fn new() -> DbDropGuard {
+    ///
+    /// Должен вызываться в контексте среды выполнения `Tokio`, поскольку
+    /// выделяет фоновую задачу.
+    pub fn new() -> DbDropGuard {
         DbDropGuard { db: Db::new() }
     }
 
     /// Возвращает общую БД. Внутри это `Arc`,
     /// поэтому его клонирование лишь увеличивает количество ссылок.
-    pub(crate) fn db(&self) -> Db {
+    pub fn db(&self) -> Db {
         self.db.clone()
     }
 }
 
+impl Default for DbDropGuard {
+    fn default() -> DbDropGuard {
+        DbDropGuard::new()
+    }
+}
+
 impl Drop for DbDropGuard {
     fn drop(&mut self) {
         // Указывает экземпляру `Db` закрыть задачу, очищающую истекшие ключи.
@@ -122,9 +326,17 @@ impl Db {
                 entries: HashMap::new(),
                 pub_sub: HashMap::new(),
                 expirations: BTreeSet::new(),
+                prefix_index: None,
+                scan_index: BTreeSet::new(),
+                waiters: Waiters::default(),
                 shutdown: false,
+                active_expire: true,
+                purge_batch_size: PURGE_BATCH_SIZE,
+                purge_min_interval: Duration::ZERO,
             }),
             background_task: Notify::new(),
+            #[cfg(feature = "chaos")]
+            chaos: crate::chaos::Chaos::default(),
         });
 
         // Запускает фоновую задачу.
@@ -136,67 +348,166 @@ impl Db {
     /// Возвращает значение по ключу.
     ///
     /// При отсутствии значения возвращается `None`. Это может произойти,
-    /// если значение не присваивалось или истекло.
-    pub(crate) fn get(&self, key: &str) -> Option<Bytes> {
+    /// если значение не присваивалось или истекло. Истекшее значение не
+    /// возвращается, даже если оно еще не удалено фоновой задачей. Значение,
+    /// не являющееся строкой (хеш), также не возвращается.
+    ///
+    /// # Примеры
+    ///
+    /// ```
+    /// use mini_redis::DbDropGuard;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let guard = DbDropGuard::new();
+    ///     let db = guard.db();
+    ///
+    ///     db.set("foo".to_string(), "bar".into(), None);
+    ///     assert_eq!(db.get("foo").unwrap(), "bar");
+    ///     assert!(db.get("baz").is_none());
+    /// }
+    /// ```
+    pub fn get(&self, key: &str) -> Option<Bytes> {
         // Выполняем блокировку (acquire the lock), получаем сущность и клонируем значение.
         //
-        // Поскольку данные хранятся с помощью `Bytes`, клонирование является
-        // поверхностным. Данные не копируются.
+        // Длинные строки хранятся с помощью `Bytes`, поэтому их клонирование
+        // является поверхностным. Копируются только короткие строки и числа.
+        let state = self.shared.state.lock().unwrap();
+        state
+            .entries
+            .get(key)
+            .filter(|entry| !entry.is_expired(Instant::now()))
+            .and_then(|entry| entry.data.to_bytes())
+    }
+
+    /// Возвращает строковое значение по ключу.
+    ///
+    /// Работает так же, как `get`, но возвращает `Err`, если значение не
+    /// является строкой.
+    pub(crate) fn get_string(&self, key: &str) -> crate::Result<Option<Bytes>> {
+        let state = self.shared.state.lock().unwrap();
+
+        match state
+            .entries
+            .get(key)
+            .filter(|entry| !entry.is_expired(Instant::now()))
+        {
+            Some(entry) => Ok(Some(entry.data.to_bytes().ok_or(WRONGTYPE)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Возвращает значения нескольких ключей в порядке `keys`.
+    ///
+    /// Значения читаются под одной блокировкой, поэтому результат
+    /// согласован. Отсутствующим ключам и значениям, не являющимся строками,
+    /// соответствует `None`.
+    ///
+    /// # Примеры
+    ///
+    /// ```
+    /// use mini_redis::DbDropGuard;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let guard = DbDropGuard::new();
+    ///     let db = guard.db();
+    ///
+    ///     db.set("foo".to_string(), "bar".into(), None);
+    ///
+    ///     assert_eq!(db.mget(&["foo", "baz"]), vec![Some("bar".into()), None]);
+    /// }
+    /// ```
+    pub fn mget<K: AsRef<str>>(&self, keys: &[K]) -> Vec<Option<Bytes>> {
         let state = self.shared.state.lock().unwrap();
-        state.entries.get(key).map(|entry| entry.data.clone())
+        let now = Instant::now();
+
+        keys.iter()
+            .map(|key| {
+                state
+                    .entries
+                    .get(key.as_ref())
+                    .filter(|entry| !entry.is_expired(now))
+                    .and_then(|entry| entry.data.to_bytes())
+            })
+            .collect()
+    }
+
+    /// Возвращает часть значения от байта `start` до байта `end` включительно.
+    ///
+    /// Отрицательные индексы отсчитываются от конца значения: `-1` - последний
+    /// байт. Индексы за пределами значения ограничиваются его границами.
+    /// Если значения нет или диапазон пуст, возвращается пустая строка. Часть
+    /// длинной строки не копируется. Возвращает `Err`, если значение не
+    /// является строкой.
+    ///
+    /// # Примеры
+    ///
+    /// ```
+    /// use mini_redis::DbDropGuard;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let guard = DbDropGuard::new();
+    ///     let db = guard.db();
+    ///
+    ///     db.set("foo".to_string(), "This is a string".into(), None);
+    ///
+    ///     assert_eq!(db.get_range("foo", 0, 3).unwrap(), "This");
+    ///     assert_eq!(db.get_range("foo", -3, -1).unwrap(), "ing");
+    ///     assert_eq!(db.get_range("foo", 10, 100).unwrap(), "string");
+    /// }
+    /// ```
+    pub fn get_range(&self, key: &str, start: i64, end: i64) -> crate::Result<Bytes> {
+        let value = match self.get_string(key)? {
+            Some(value) => value,
+            None => return Ok(Bytes::new()),
+        };
+
+        let len = value.len() as i64;
+
+        let start = if start < 0 { len + start } else { start }.max(0);
+        let end = if end < 0 { len + end } else { end }.min(len - 1);
+
+        if start > end {
+            return Ok(Bytes::new());
+        }
+
+        Ok(value.slice(start as usize..=end as usize))
     }
 
     /// Устанавливает значение по ключу и, опционально, время его жизни.
     ///
     /// Если значение уже установлено, оно удаляется.
-    pub(crate) fn set(&self, key: String, value: Bytes, expire: Option<Duration>) {
+    ///
+    /// # Примеры
+    ///
+    /// ```
+    /// use mini_redis::DbDropGuard;
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let guard = DbDropGuard::new();
+    ///     let db = guard.db();
+    ///
+    ///     // Значение удаляется через минуту
+    ///     db.set("session".to_string(), "data".into(), Some(Duration::from_secs(60)));
+    /// }
+    /// ```
+    pub fn set(&self, key: String, value: Bytes, expire: Option<Duration>) {
+        self.set_value(key, Value::from_bytes(value), expire);
+    }
+
+    /// Устанавливает значение любого типа по ключу. Работает так же, как
+    /// `set`. Используется при импорте снимка.
+    pub(crate) fn set_value(&self, key: String, data: Value, expire: Option<Duration>) {
         let mut state = self.shared.state.lock().unwrap();
 
-        // Если этот `set` становится следующим истекающим ключом, фоновая задача
-        // должна узнать об этом для обновления своего состояния.
-        //
-        // Должна ли задача быть уведомлена, вычисляется в теле этого метода.
-        let mut notify = false;
-
-        let expires_at = expire.map(|duration| {
-            // `Instant`, когда истекает время жизни ключа.
-            let when = Instant::now() + duration;
-
-            // "Воркер" задачи уведомляется, только если добавленное время жизни
-            // является следующим истекающим ключом. В этом случае воркер
-            // должен быть "разбужен" для обновления своего состояния.
-            notify = state
-                .next_expiration()
-                .map(|expiration| expiration > when)
-                .unwrap_or(true);
-
-            when
-        });
+        let expires_at = expire.map(Expiration::after);
 
         // Добавляем новую сущность в `HashMap`.
-        let prev = state.entries.insert(
-            key.clone(),
-            Entry {
-                data: value,
-                expires_at,
-            },
-        );
-
-        // Если по ключу имеется значение и у него есть время жизни. Соответствующая сущность в карте
-        // `expirations` также должна быть удалена. Это предотвращает утечку данных.
-        if let Some(prev) = prev {
-            if let Some(when) = prev.expires_at {
-                // Удаляем время жизни.
-                state.expirations.remove(&(when, key.clone()));
-            }
-        }
-
-        // Отслеживаем время жизни. Добавление сущности перед удалением может привести к багу,
-        // когда текущий `(when, key)` будет равен предыдущему `(when, key)`.
-        // Удаление перед добавлением решает эту проблему.
-        if let Some(when) = expires_at {
-            state.expirations.insert((when, key));
-        }
+        let notify = state.insert(key, Entry { data, expires_at });
 
         // Освобождаем (release) мьютекс перед уведомлением фоновой задачи. Это позволяет
         // предотвратить ситуацию, когда фоновая задача не может блокировать мьютекс, поскольку он удерживается этой функцией.
@@ -209,108 +520,2103 @@ impl Db {
         }
     }
 
-    /// Возвращает `Receiver` для запрошенного канала.
+    /// Устанавливает значение по ключу с настройками команды `SET`.
     ///
-    /// Этот `Receiver` используется для получения значений, отправленных с помощью команды `PUBLISH`.
-    pub(crate) fn subscribe(&self, key: String) -> broadcast::Receiver<Bytes> {
-        use std::collections::hash_map::Entry;
-
-        // Блокируем мьютекс.
+    /// Значение устанавливается, только если выполнено условие `condition`.
+    /// Если `keep_ttl` равен `true`, а `expire` - `None`, время жизни
+    /// существующего значения сохраняется. Проверка условия и установка
+    /// выполняются атомарно.
+    ///
+    /// Возвращает признак установки значения и предыдущее значение.
+    /// Предыдущее значение, не являющееся строкой (например, хеш), не
+    /// возвращается. Если при этом `get` равен `true`, как в `SET ... GET`,
+    /// возвращается `Err`, а значение не изменяется.
+    ///
+    /// # Примеры
+    ///
+    /// ```
+    /// use mini_redis::cmd::SetCondition;
+    /// use mini_redis::DbDropGuard;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let guard = DbDropGuard::new();
+    ///     let db = guard.db();
+    ///
+    ///     let nx = Some(SetCondition::Nx);
+    ///
+    ///     assert_eq!(
+    ///         db.set_with("foo".to_string(), "bar".into(), None, nx, false, false).unwrap(),
+    ///         (true, None)
+    ///     );
+    ///     assert_eq!(
+    ///         db.set_with("foo".to_string(), "baz".into(), None, nx, false, true).unwrap(),
+    ///         (false, Some("bar".into()))
+    ///     );
+    /// }
+    /// ```
+    pub fn set_with(
+        &self,
+        key: String,
+        value: Bytes,
+        expire: Option<Duration>,
+        condition: Option<SetCondition>,
+        keep_ttl: bool,
+        get: bool,
+    ) -> crate::Result<(bool, Option<Bytes>)> {
         let mut state = self.shared.state.lock().unwrap();
 
-        // Если для запрошенного канала нет сущности, создаем новый
-        // широковещательный (broadcast) канал и связываем его с ключом. Если канал существует,
-        // возвращаем соответствующего получателя.
-        match state.pub_sub.entry(key) {
-            Entry::Occupied(e) => e.get().subscribe(),
-            Entry::Vacant(e) => {
-                // Широковещательный канал отсутствует, создаем его.
-                //
-                // Канал создается с емкостью `1024` сообщений.
-                // Сообщение хранится в канале до тех пор, пока все подписчики
-                // его не увидят. Это означает, что наличие "медленного" подписчика может привести к
-                // бесконечно долгому хранению сообщения.
-                //
-                // При заполнении емкости канала, публикация будет приводить к
-                // уничтожению старых сообщений. Это решает проблему блокировки
-                // всей системы медленными потребителями.
-                let (tx, rx) = broadcast::channel(1024);
-                e.insert(tx);
-                rx
+        let (exists, prev, prev_expires_at) = match state
+            .entries
+            .get(&key)
+            .filter(|entry| !entry.is_expired(Instant::now()))
+        {
+            Some(entry) => {
+                let prev = entry.data.to_bytes();
+                if get && prev.is_none() {
+                    return Err(WRONGTYPE.into());
+                }
+                (true, prev, entry.expires_at)
             }
+            None => (false, None, None),
+        };
+
+        let allowed = match condition {
+            Some(SetCondition::Nx) => !exists,
+            Some(SetCondition::Xx) => exists,
+            None => true,
+        };
+        if !allowed {
+            return Ok((false, prev));
         }
-    }
 
-    /// Публикует сообщение в канале. Возвращает количество подписчиков,
-    /// "слушающих" канал.
-    pub(crate) fn publish(&self, key: &str, value: Bytes) -> usize {
-        let state = self.shared.state.lock().unwrap();
+        let expires_at = match expire {
+            Some(expire) => Some(Expiration::after(expire)),
+            None if keep_ttl => prev_expires_at,
+            None => None,
+        };
 
-        state
-            .pub_sub
-            .get(key)
-            // При успешной отправке сообщения в широковещательный канал, возвращается
-            // количество подписчиков. Ошибка указывает на отсутствие
-            // получателей. В этом случае должен возвращаться `0`.
-            .map(|tx| tx.send(value).unwrap_or(0))
-            // Если по ключу канала нет сущности, значит нет и
-            // подписчиков. В этом случае возвращается `0`.
-            .unwrap_or(0)
+        let notify = state.insert(
+            key,
+            Entry {
+                data: Value::from_bytes(value),
+                expires_at,
+            },
+        );
+
+        drop(state);
+
+        if notify {
+            self.shared.background_task.notify_one();
+        }
+
+        Ok((true, prev))
     }
 
-    /// Указывает фоновой задаче очистки закрыться. Это вызывается
-    /// реализацией `Drop` `DbShutdown`
-    fn shutdown_purge_task(&self) {
-        // Фоновая задача должна получить сигнал о закрытии. Это делается путем
-        // установки `State::shutdown` в значение `true`.
+    /// Устанавливает значения нескольких ключей под одной блокировкой.
+    ///
+    /// Как и `set`, заменяет предыдущие значения и удаляет их время жизни.
+    /// Если ключ указан несколько раз, сохраняется последнее значение.
+    ///
+    /// # Примеры
+    ///
+    /// ```
+    /// use mini_redis::DbDropGuard;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let guard = DbDropGuard::new();
+    ///     let db = guard.db();
+    ///
+    ///     db.mset(vec![
+    ///         ("foo".to_string(), "bar".into()),
+    ///         ("baz".to_string(), "qux".into()),
+    ///     ]);
+    ///
+    ///     assert_eq!(db.get("baz").unwrap(), "qux");
+    /// }
+    /// ```
+    pub fn mset(&self, pairs: Vec<(String, Bytes)>) {
         let mut state = self.shared.state.lock().unwrap();
-        state.shutdown = true;
 
-        // Снимаем блокировку перед уведомлением фоновой задачи. Это позволяет
-        // предотвратить ситуацию, когда фоновая задача не может блокировать мьютекс.
-        drop(state);
-        self.shared.background_task.notify_one();
+        for (key, value) in pairs {
+            state.insert(
+                key,
+                Entry {
+                    data: Value::from_bytes(value),
+                    expires_at: None,
+                },
+            );
+        }
     }
-}
 
-impl Shared {
-    /// Очищает все истекшие ключи и возвращает `Instant`, когда истечет
-    /// следующий ключ. Фоновая задача "спит" до этого момента.
-    fn purge_expired_keys(&self) -> Option<Instant> {
-        let mut state = self.state.lock().unwrap();
+    /// Устанавливает значения нескольких ключей, только если ни один из них
+    /// не существует.
+    ///
+    /// Проверка и установка выполняются под одной блокировкой: значения
+    /// устанавливаются либо все, либо ни одно. Возвращает `true`, если
+    /// значения установлены.
+    ///
+    /// # Примеры
+    ///
+    /// ```
+    /// use mini_redis::DbDropGuard;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let guard = DbDropGuard::new();
+    ///     let db = guard.db();
+    ///
+    ///     db.set("foo".to_string(), "bar".into(), None);
+    ///
+    ///     assert!(!db.mset_nx(vec![
+    ///         ("foo".to_string(), "new".into()),
+    ///         ("baz".to_string(), "qux".into()),
+    ///     ]));
+    ///     assert!(db.get("baz").is_none());
+    /// }
+    /// ```
+    pub fn mset_nx(&self, pairs: Vec<(String, Bytes)>) -> bool {
+        let mut state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
 
-        if state.shutdown {
-            // БД закрывается. Все обработчики общего состояния
-            // уничтожены. Фоновая задача должна завершиться.
-            return None;
+        let exists = pairs.iter().any(|(key, _)| {
+            state
+                .entries
+                .get(key)
+                .is_some_and(|entry| !entry.is_expired(now))
+        });
+        if exists {
+            return false;
         }
 
-        // Это нужно для того, чтобы сделать "счастливым" контроллера заимствований (borrow checker). Если коротко,
-        // `lock()` возвращает `MutexGuard`, а не `&mut State`. Контроллер заимствований
-        // не может видеть "сквозь" защитника мьютекса и определить, что
-        // мутабельный доступ к `state.expirations` и `state.entries` является безопасным,
-        // поэтому мы получаем "настоящую" мутабельную ссылку на `State` за пределами цикла.
-        let state = &mut *state;
+        for (key, value) in pairs {
+            state.insert(
+                key,
+                Entry {
+                    data: Value::from_bytes(value),
+                    expires_at: None,
+                },
+            );
+        }
 
-        // Находим все ключи, истекшие до настоящего времени.
-        let now = Instant::now();
+        true
+    }
 
-        while let Some(&(when, ref key)) = state.expirations.iter().next() {
-            if when > now {
-                // Выполняем очистку. `when` - это момент, когда истекает
-                // следующий ключ. Воркер задачи ждет этого момента.
-                return Some(when);
+    /// Удаляет значение по ключу.
+    ///
+    /// Возвращает `true`, если значение существовало и не истекло.
+    ///
+    /// # Примеры
+    ///
+    /// ```
+    /// use mini_redis::DbDropGuard;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let guard = DbDropGuard::new();
+    ///     let db = guard.db();
+    ///
+    ///     db.set("foo".to_string(), "bar".into(), None);
+    ///     assert!(db.del("foo"));
+    ///     assert!(!db.del("foo"));
+    /// }
+    /// ```
+    pub fn del(&self, key: &str) -> bool {
+        let mut state = self.shared.state.lock().unwrap();
+
+        state
+            .remove(key)
+            .is_some_and(|prev| !prev.is_expired(Instant::now()))
+    }
+
+    /// Атомарно увеличивает целое число, хранящееся по ключу, на `delta` и
+    /// возвращает новое значение.
+    ///
+    /// Отсутствующее значение считается равным `0`, время жизни существующего
+    /// значения сохраняется. Возвращает `Err`, если значение не является
+    /// целым числом или результат не помещается в `i64`.
+    ///
+    /// # Примеры
+    ///
+    /// ```
+    /// use mini_redis::DbDropGuard;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let guard = DbDropGuard::new();
+    ///     let db = guard.db();
+    ///
+    ///     assert_eq!(db.incr_by("counter", 1).unwrap(), 1);
+    ///     assert_eq!(db.incr_by("counter", -3).unwrap(), -2);
+    ///     assert_eq!(db.get("counter").unwrap(), "-2");
+    /// }
+    /// ```
+    pub fn incr_by(&self, key: &str, delta: i64) -> crate::Result<i64> {
+        let mut state = self.shared.state.lock().unwrap();
+
+        let entry = state
+            .entries
+            .get_mut(key)
+            .filter(|entry| !entry.is_expired(Instant::now()));
+
+        match entry {
+            // Строки, являющиеся целыми числами, всегда хранятся в кодировке
+            // `int`, поэтому остальные кодировки числами не являются
+            Some(Entry {
+                data: Value::Int(n),
+                ..
+            }) => {
+                *n = n.checked_add(delta).ok_or(OVERFLOW)?;
+                Ok(*n)
             }
+            #[cfg(feature = "json")]
+            Some(Entry {
+                data: Value::Json(_),
+                ..
+            }) => Err(WRONGTYPE.into()),
+            Some(Entry {
+                data: Value::Hash(_) | Value::SortedSet(_) | Value::Throttle(_),
+                ..
+            }) => Err(WRONGTYPE.into()),
+            Some(_) => Err(NOT_INTEGER.into()),
+            None => {
+                state.insert(
+                    key.to_string(),
+                    Entry {
+                        data: Value::Int(delta),
+                        expires_at: None,
+                    },
+                );
+                Ok(delta)
+            }
+        }
+    }
 
-            // Ключ истек, удаляем его.
-            state.entries.remove(key);
-            state.expirations.remove(&(when, key.clone()));
+    /// Атомарно увеличивает число с плавающей точкой, хранящееся по ключу,
+    /// на `delta` и возвращает новое значение.
+    ///
+    /// Работает так же, как `incr_by`. Новое значение хранится в виде строки
+    /// без экспоненты и незначащих нулей. Возвращает `Err`, если значение не
+    /// является числом или результат не является конечным числом.
+    ///
+    /// # Примеры
+    ///
+    /// ```
+    /// use mini_redis::DbDropGuard;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let guard = DbDropGuard::new();
+    ///     let db = guard.db();
+    ///
+    ///     db.set("price".to_string(), "10.50".into(), None);
+    ///
+    ///     assert_eq!(db.incr_by_float("price", 0.1).unwrap(), 10.6);
+    ///     assert_eq!(db.get("price").unwrap(), "10.6");
+    /// }
+    /// ```
+    pub fn incr_by_float(&self, key: &str, delta: f64) -> crate::Result<f64> {
+        let mut state = self.shared.state.lock().unwrap();
+
+        let entry = state
+            .entries
+            .get_mut(key)
+            .filter(|entry| !entry.is_expired(Instant::now()));
+
+        let current = match &entry {
+            #[cfg(feature = "json")]
+            Some(Entry {
+                data: Value::Json(_),
+                ..
+            }) => return Err(WRONGTYPE.into()),
+            Some(Entry {
+                data: Value::Hash(_) | Value::SortedSet(_) | Value::Throttle(_),
+                ..
+            }) => return Err(WRONGTYPE.into()),
+            Some(entry) => entry.data.to_float().ok_or(NOT_FLOAT)?,
+            None => 0.0,
+        };
+
+        let value = current + delta;
+        if !value.is_finite() {
+            return Err(NOT_FINITE.into());
         }
 
-        None
+        let data = Value::from_bytes(Bytes::from(format_float(value)));
+
+        match entry {
+            Some(entry) => entry.data = data,
+            None => {
+                state.insert(
+                    key.to_string(),
+                    Entry {
+                        data,
+                        expires_at: None,
+                    },
+                );
+            }
+        }
+
+        Ok(value)
     }
 
-    /// Возвращает `true`, если БД закрыта.
+    /// Перезаписывает часть строки, хранящейся по ключу, начиная с байта
+    /// `offset`, и возвращает новую длину строки.
+    ///
+    /// Если строка короче `offset`, она дополняется нулевыми байтами.
+    /// Отсутствующее значение считается пустой строкой, но пустой `value` его
+    /// не создает. Время жизни существующего значения сохраняется.
+    /// Возвращает `Err`, если длина строки превысит 512 МБ.
+    ///
+    /// # Примеры
+    ///
+    /// ```
+    /// use mini_redis::DbDropGuard;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let guard = DbDropGuard::new();
+    ///     let db = guard.db();
+    ///
+    ///     db.set("foo".to_string(), "Hello World".into(), None);
+    ///
+    ///     assert_eq!(db.set_range("foo", 6, b"Redis").unwrap(), 11);
+    ///     assert_eq!(db.get("foo").unwrap(), "Hello Redis");
+    ///
+    ///     assert_eq!(db.set_range("bar", 2, b"!").unwrap(), 3);
+    ///     assert_eq!(db.get("bar").unwrap(), "\0\0!");
+    /// }
+    /// ```
+    pub fn set_range(&self, key: &str, offset: usize, value: &[u8]) -> crate::Result<usize> {
+        let mut state = self.shared.state.lock().unwrap();
+
+        let entry = state
+            .entries
+            .get_mut(key)
+            .filter(|entry| !entry.is_expired(Instant::now()));
+
+        let current = match &entry {
+            #[cfg(feature = "json")]
+            Some(Entry {
+                data: Value::Json(_),
+                ..
+            }) => return Err(WRONGTYPE.into()),
+            Some(entry) => entry.data.to_bytes().ok_or(WRONGTYPE)?,
+            None => Bytes::new(),
+        };
+
+        if value.is_empty() {
+            return Ok(current.len());
+        }
+
+        let end = offset
+            .checked_add(value.len())
+            .filter(|&end| end <= STRING_MAX_LEN)
+            .ok_or(STRING_TOO_LONG)?;
+
+        let mut data = BytesMut::from(&current[..]);
+        if data.len() < end {
+            data.resize(end, 0);
+        }
+        data[offset..end].copy_from_slice(value);
+
+        let len = data.len();
+        let data = Value::from_bytes(data.freeze());
+
+        match entry {
+            Some(entry) => entry.data = data,
+            None => {
+                state.insert(
+                    key.to_string(),
+                    Entry {
+                        data,
+                        expires_at: None,
+                    },
+                );
+            }
+        }
+
+        Ok(len)
+    }
+
+    /// Возвращает количество существующих ключей из `keys`.
+    ///
+    /// Ключ, указанный несколько раз, учитывается каждый раз. Истекшие, но еще
+    /// не удаленные фоновой задачей ключи не учитываются.
+    ///
+    /// # Примеры
+    ///
+    /// ```
+    /// use mini_redis::DbDropGuard;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let guard = DbDropGuard::new();
+    ///     let db = guard.db();
+    ///
+    ///     db.set("foo".to_string(), "bar".into(), None);
+    ///
+    ///     assert_eq!(db.exists(&["foo", "baz", "foo"]), 2);
+    /// }
+    /// ```
+    pub fn exists<K: AsRef<str>>(&self, keys: &[K]) -> usize {
+        let state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+
+        keys.iter()
+            .filter(|key| {
+                state
+                    .entries
+                    .get(key.as_ref())
+                    .is_some_and(|entry| !entry.is_expired(now))
+            })
+            .count()
+    }
+
+    /// Устанавливает время жизни существующего значения, заменяя
+    /// предыдущее.
+    ///
+    /// Значение с нулевым временем жизни удаляется. Возвращает `true`, если
+    /// значение существовало и не истекло.
+    ///
+    /// # Паника
+    ///
+    /// Паникует, если момент истечения не может быть представлен `Instant`.
+    ///
+    /// # Примеры
+    ///
+    /// ```
+    /// use mini_redis::DbDropGuard;
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let guard = DbDropGuard::new();
+    ///     let db = guard.db();
+    ///
+    ///     db.set("foo".to_string(), "bar".into(), None);
+    ///
+    ///     assert!(db.expire("foo", Duration::from_secs(60)));
+    ///     assert!(!db.expire("baz", Duration::from_secs(60)));
+    ///     assert!(db.ttl("foo").unwrap().is_some());
+    /// }
+    /// ```
+    pub fn expire(&self, key: &str, expire: Duration) -> bool {
+        if expire.is_zero() {
+            return self.del(key);
+        }
+
+        let mut state = self.shared.state.lock().unwrap();
+
+        let notify = match state.set_expiration(key, Some(Expiration::after(expire))) {
+            Some(notify) => notify,
+            None => return false,
+        };
+
+        // Как и в `set`, фоновая задача уведомляется после освобождения
+        // мьютекса
+        drop(state);
+
+        if notify {
+            self.shared.background_task.notify_one();
+        }
+
+        true
+    }
+
+    /// Устанавливает момент истечения существующего значения по системным
+    /// часам, заменяя предыдущее время жизни.
+    ///
+    /// Момент переводится в `Instant` относительно текущего времени, поэтому
+    /// последующие изменения системных часов на истечение не влияют. Значение
+    /// с моментом истечения в прошлом удаляется. Возвращает `true`, если
+    /// значение существовало и не истекло.
+    ///
+    /// # Паника
+    ///
+    /// Паникует, если момент истечения не может быть представлен `Instant`.
+    ///
+    /// # Примеры
+    ///
+    /// ```
+    /// use mini_redis::DbDropGuard;
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let guard = DbDropGuard::new();
+    ///     let db = guard.db();
+    ///
+    ///     db.set("foo".to_string(), "bar".into(), None);
+    ///
+    ///     let when = SystemTime::now() + Duration::from_secs(60);
+    ///     assert!(db.expire_at("foo", when));
+    ///     assert!(db.ttl("foo").unwrap().is_some());
+    /// }
+    /// ```
+    pub fn expire_at(&self, key: &str, when: SystemTime) -> bool {
+        match Expiration::at(when) {
+            Some(when) => {
+                let mut state = self.shared.state.lock().unwrap();
+
+                let notify = match state.set_expiration(key, Some(when)) {
+                    Some(notify) => notify,
+                    None => return false,
+                };
+
+                drop(state);
+
+                if notify {
+                    self.shared.background_task.notify_one();
+                }
+
+                true
+            }
+            // Момент истечения уже наступил
+            None => self.del(key),
+        }
+    }
+
+    /// Удаляет время жизни значения.
+    ///
+    /// Возвращает `true`, если время жизни было ограничено и удалено.
+    ///
+    /// # Примеры
+    ///
+    /// ```
+    /// use mini_redis::DbDropGuard;
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let guard = DbDropGuard::new();
+    ///     let db = guard.db();
+    ///
+    ///     db.set("foo".to_string(), "bar".into(), Some(Duration::from_secs(60)));
+    ///
+    ///     assert!(db.persist("foo"));
+    ///     assert!(!db.persist("foo"));
+    ///     assert_eq!(db.ttl("foo"), Some(None));
+    /// }
+    /// ```
+    pub fn persist(&self, key: &str) -> bool {
+        let mut state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+
+        let when = match state
+            .entries
+            .get_mut(key)
+            .filter(|entry| !entry.is_expired(now))
+            .and_then(|entry| entry.expires_at.take())
+        {
+            Some(when) => when,
+            None => return false,
+        };
+
+        // Фоновая задача не уведомляется: если это время жизни было
+        // ближайшим, она проснется раньше и ничего не удалит
+        state.expirations.remove(&(when.instant, key.to_string()));
+
+        true
+    }
+
+    /// Возвращает оставшееся время жизни значения.
+    ///
+    /// Возвращает `None`, если значения нет, и `Some(None)`, если время
+    /// жизни значения не ограничено.
+    ///
+    /// # Примеры
+    ///
+    /// ```
+    /// use mini_redis::DbDropGuard;
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let guard = DbDropGuard::new();
+    ///     let db = guard.db();
+    ///
+    ///     db.set("foo".to_string(), "bar".into(), Some(Duration::from_secs(60)));
+    ///     db.set("baz".to_string(), "qux".into(), None);
+    ///
+    ///     assert!(db.ttl("foo").unwrap().unwrap() <= Duration::from_secs(60));
+    ///     assert_eq!(db.ttl("baz"), Some(None));
+    ///     assert_eq!(db.ttl("nope"), None);
+    /// }
+    /// ```
+    pub fn ttl(&self, key: &str) -> Option<Option<Duration>> {
+        let state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+
+        state
+            .entries
+            .get(key)
+            .filter(|entry| !entry.is_expired(now))
+            .map(|entry| {
+                entry
+                    .expires_at
+                    .map(|when| when.instant.saturating_duration_since(now))
+            })
+    }
+
+    /// Возвращает момент истечения времени жизни значения по системным
+    /// часам.
+    ///
+    /// Для значений, время жизни которых установлено методом `expire_at`,
+    /// возвращается исходный момент. Возвращает `None`, если значения нет, и
+    /// `Some(None)`, если время жизни значения не ограничено.
+    ///
+    /// # Примеры
+    ///
+    /// ```
+    /// use mini_redis::DbDropGuard;
+    /// use std::time::{Duration, SystemTime, UNIX_EPOCH};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let guard = DbDropGuard::new();
+    ///     let db = guard.db();
+    ///
+    ///     let when = UNIX_EPOCH + Duration::from_secs(4_000_000_000);
+    ///     db.set("foo".to_string(), "bar".into(), None);
+    ///     db.expire_at("foo", when);
+    ///
+    ///     assert_eq!(db.expire_time("foo"), Some(Some(when)));
+    ///     assert_eq!(db.expire_time("nope"), None);
+    /// }
+    /// ```
+    pub fn expire_time(&self, key: &str) -> Option<Option<SystemTime>> {
+        let state = self.shared.state.lock().unwrap();
+
+        state
+            .entries
+            .get(key)
+            .filter(|entry| !entry.is_expired(Instant::now()))
+            .map(|entry| entry.expires_at.map(|when| when.system))
+    }
+
+    /// Возвращает сериализованное значение ключа. Формат описан в модуле
+    /// `serialize` и совпадает с ответом команды `DUMP`.
+    ///
+    /// Время жизни не сериализуется. Возвращает `None`, если значения нет.
+    ///
+    /// # Примеры
+    ///
+    /// ```
+    /// use mini_redis::DbDropGuard;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let guard = DbDropGuard::new();
+    ///     let db = guard.db();
+    ///
+    ///     db.set("foo".to_string(), "bar".into(), None);
+    ///     let payload = db.dump("foo").unwrap();
+    ///
+    ///     db.restore("copy".to_string(), &payload, None, false).unwrap();
+    ///     assert_eq!(db.get("copy").unwrap(), "bar");
+    /// }
+    /// ```
+    pub fn dump(&self, key: &str) -> Option<Bytes> {
+        let state = self.shared.state.lock().unwrap();
+
+        state
+            .entries
+            .get(key)
+            .filter(|entry| !entry.is_expired(Instant::now()))
+            .map(|entry| serialize::dump(&entry.data))
+    }
+
+    /// Создает значение по ключу из результата `dump`.
+    ///
+    /// `expires_at` задает момент истечения по системным часам; значение с
+    /// моментом истечения в прошлом не создается. Существующее значение
+    /// заменяется, только если `replace` равен `true`.
+    ///
+    /// Возвращает `Err`, если ключ существует, а `replace` равен `false`, или
+    /// если данные повреждены.
+    ///
+    /// # Паника
+    ///
+    /// Паникует, если момент истечения не может быть представлен `Instant`.
+    pub fn restore(
+        &self,
+        key: String,
+        payload: &[u8],
+        expires_at: Option<SystemTime>,
+        replace: bool,
+    ) -> crate::Result<()> {
+        // Данные проверяются до блокировки
+        let data = serialize::restore(payload)?;
+
+        let mut state = self.shared.state.lock().unwrap();
+
+        let exists = state
+            .entries
+            .get(&key)
+            .is_some_and(|entry| !entry.is_expired(Instant::now()));
+        if exists && !replace {
+            return Err("BUSYKEY Target key name already exists.".into());
+        }
+
+        let expires_at = match expires_at.map(Expiration::at) {
+            Some(Some(when)) => Some(when),
+            // Момент истечения уже наступил: значение не создается, а
+            // заменяемое значение удаляется
+            Some(None) => {
+                state.remove(&key);
+                return Ok(());
+            }
+            None => None,
+        };
+
+        let notify = state.insert(key, Entry { data, expires_at });
+
+        drop(state);
+
+        if notify {
+            self.shared.background_task.notify_one();
+        }
+
+        Ok(())
+    }
+
+    /// Проверяет ограничение частоты запросов по ключу и, если запрос
+    /// разрешен, учитывает его.
+    ///
+    /// Ограничение допускает `count` запросов за период `period` и всплески
+    /// до `max_burst` запросов сверх этого, запрос учитывается как `quantity`
+    /// запросов. Проверка и обновление состояния выполняются атомарно. См.
+    /// `crate::throttle`.
+    ///
+    /// Возвращает `Err`, если `count` или `period` равен `0` или если по
+    /// ключу хранится значение, не являющееся состоянием ограничения (в том
+    /// числе целое число, записанное командой `SET`).
+    ///
+    /// # Примеры
+    ///
+    /// ```
+    /// use mini_redis::DbDropGuard;
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let guard = DbDropGuard::new();
+    ///     let db = guard.db();
+    ///
+    ///     // Не более 10 запросов в минуту без всплесков
+    ///     let res = db.throttle("user:1", 0, 10, Duration::from_secs(60), 1).unwrap();
+    ///     assert!(res.allowed);
+    ///
+    ///     let res = db.throttle("user:1", 0, 10, Duration::from_secs(60), 1).unwrap();
+    ///     assert!(!res.allowed);
+    ///     assert!(res.retry_after.unwrap() <= Duration::from_secs(6));
+    /// }
+    /// ```
+    pub fn throttle(
+        &self,
+        key: &str,
+        max_burst: u64,
+        count: u64,
+        period: Duration,
+        quantity: u64,
+    ) -> crate::Result<Throttle> {
+        if count == 0 || period.is_zero() {
+            return Err("ERR count and period must be positive".into());
+        }
+
+        let mut state = self.shared.state.lock().unwrap();
+
+        let tat = match state.entries.get(key) {
+            Some(entry) if entry.is_expired(Instant::now()) => None,
+            Some(Entry {
+                data: Value::Throttle(tat),
+                ..
+            }) => Some(*tat),
+            Some(_) => return Err(WRONGTYPE.into()),
+            None => None,
+        };
+
+        let (res, tat) = throttle::gcra(tat, throttle::now(), max_burst, count, period, quantity);
+
+        // Состояние хранится до возвращения ограничения в исходное состояние
+        let notify = match tat {
+            Some(tat) => state.insert(
+                key.to_string(),
+                Entry {
+                    data: Value::Throttle(tat),
+                    expires_at: Some(Expiration::after(res.reset_after)),
+                },
+            ),
+            None => false,
+        };
+
+        drop(state);
+
+        if notify {
+            self.shared.background_task.notify_one();
+        }
+
+        Ok(res)
+    }
+
+    /// Возвращает текст значения по пути `path` в документе JSON (см.
+    /// `crate::json`).
+    ///
+    /// Возвращает `None`, если ключ или путь не существует, и `Err`, если
+    /// значение не является документом JSON.
+    #[cfg(feature = "json")]
+    pub(crate) fn json_get(&self, key: &str, path: &str) -> crate::Result<Option<Bytes>> {
+        let state = self.shared.state.lock().unwrap();
+
+        let entry = state
+            .entries
+            .get(key)
+            .filter(|entry| !entry.is_expired(Instant::now()));
+
+        match entry {
+            Some(Entry {
+                data: Value::Json(doc),
+                ..
+            }) => Ok(json::get(doc, path).map(|value| Bytes::from(value.to_string()))),
+            Some(_) => Err(WRONGTYPE.into()),
+            None => Ok(None),
+        }
+    }
+
+    /// Устанавливает `value` по пути `path` в документе JSON (см.
+    /// `crate::json`). Время жизни ключа сохраняется.
+    ///
+    /// Отсутствующий ключ создается, только если `path` обозначает корень.
+    /// Возвращает `false`, если путь не существует, и `Err`, если значение
+    /// не является документом JSON или ключ отсутствует, а путь не является
+    /// корнем.
+    #[cfg(feature = "json")]
+    pub(crate) fn json_set(
+        &self,
+        key: &str,
+        path: &str,
+        value: serde_json::Value,
+    ) -> crate::Result<bool> {
+        let mut state = self.shared.state.lock().unwrap();
+
+        let entry = state
+            .entries
+            .get_mut(key)
+            .filter(|entry| !entry.is_expired(Instant::now()));
+
+        match entry {
+            Some(Entry {
+                data: Value::Json(doc),
+                ..
+            }) => Ok(json::set(doc, path, value)),
+            Some(_) => Err(WRONGTYPE.into()),
+            None if json::is_root(path) => {
+                state.insert(
+                    key.to_string(),
+                    Entry {
+                        data: Value::Json(Box::new(value)),
+                        expires_at: None,
+                    },
+                );
+                Ok(true)
+            }
+            None => Err("ERR new objects must be created at the root".into()),
+        }
+    }
+
+    /// Удаляет значение по пути `path` в документе JSON (см.
+    /// `crate::json`). Удаление корня удаляет ключ.
+    ///
+    /// Возвращает `true`, если значение существовало, и `Err`, если значение
+    /// ключа не является документом JSON.
+    #[cfg(feature = "json")]
+    pub(crate) fn json_del(&self, key: &str, path: &str) -> crate::Result<bool> {
+        let mut state = self.shared.state.lock().unwrap();
+
+        let entry = state
+            .entries
+            .get_mut(key)
+            .filter(|entry| !entry.is_expired(Instant::now()));
+
+        match entry {
+            Some(Entry {
+                data: Value::Json(_),
+                ..
+            }) if json::is_root(path) => {
+                state.remove(key);
+                Ok(true)
+            }
+            Some(Entry {
+                data: Value::Json(doc),
+                ..
+            }) => Ok(json::delete(doc, path)),
+            Some(_) => Err(WRONGTYPE.into()),
+            None => Ok(false),
+        }
+    }
+
+    /// Устанавливает значения полей хеша, хранящегося по ключу, и возвращает
+    /// количество добавленных полей.
+    ///
+    /// Отсутствующий хеш создается, значения существующих полей заменяются.
+    /// Время жизни существующего хеша сохраняется. Возвращает `Err`, если
+    /// значение не является хешем.
+    ///
+    /// # Примеры
+    ///
+    /// ```
+    /// use mini_redis::DbDropGuard;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let guard = DbDropGuard::new();
+    ///     let db = guard.db();
+    ///
+    ///     let fields = vec![("name".to_string(), "Alice".into())];
+    ///     assert_eq!(db.hset("user", fields).unwrap(), 1);
+    ///     assert_eq!(db.hget("user", "name").unwrap().unwrap(), "Alice");
+    /// }
+    /// ```
+    pub fn hset(&self, key: &str, fields: Vec<(String, Bytes)>) -> crate::Result<usize> {
+        let mut state = self.shared.state.lock().unwrap();
+
+        let hash = match state.hash_mut(key)? {
+            Some(hash) => hash,
+            // Хеш без полей не создается
+            None if fields.is_empty() => return Ok(0),
+            None => {
+                state.insert(
+                    key.to_string(),
+                    Entry {
+                        data: Value::Hash(Box::default()),
+                        expires_at: None,
+                    },
+                );
+                state.hash_mut(key)?.unwrap()
+            }
+        };
+
+        let mut added = 0;
+        for (field, value) in fields {
+            if hash.insert(field, value).is_none() {
+                added += 1;
+            }
+        }
+
+        Ok(added)
+    }
+
+    /// Возвращает значение поля хеша, хранящегося по ключу.
+    ///
+    /// Возвращает `None`, если хеша или поля нет, и `Err`, если значение не
+    /// является хешем.
+    pub fn hget(&self, key: &str, field: &str) -> crate::Result<Option<Bytes>> {
+        let state = self.shared.state.lock().unwrap();
+
+        Ok(state.hash(key)?.and_then(|hash| hash.get(field)).cloned())
+    }
+
+    /// Удаляет поля хеша, хранящегося по ключу, и возвращает количество
+    /// удаленных полей.
+    ///
+    /// Хеш без полей удаляется. Возвращает `Err`, если значение не является
+    /// хешем.
+    pub fn hdel<F: AsRef<str>>(&self, key: &str, fields: &[F]) -> crate::Result<usize> {
+        let mut state = self.shared.state.lock().unwrap();
+
+        let hash = match state.hash_mut(key)? {
+            Some(hash) => hash,
+            None => return Ok(0),
+        };
+
+        let removed = fields
+            .iter()
+            .filter(|field| hash.remove(field.as_ref()).is_some())
+            .count();
+
+        if hash.is_empty() {
+            state.remove(key);
+        }
+
+        Ok(removed)
+    }
+
+    /// Возвращает все поля и значения хеша, хранящегося по ключу, в порядке
+    /// возрастания полей.
+    ///
+    /// Если хеша нет, возвращается пустой вектор. Возвращает `Err`, если
+    /// значение не является хешем.
+    pub fn hgetall(&self, key: &str) -> crate::Result<Vec<(String, Bytes)>> {
+        let state = self.shared.state.lock().unwrap();
+
+        let mut fields: Vec<(String, Bytes)> = match state.hash(key)? {
+            Some(hash) => hash
+                .iter()
+                .map(|(field, value)| (field.clone(), value.clone()))
+                .collect(),
+            None => return Ok(vec![]),
+        };
+
+        // Освобождаем мьютекс до сортировки
+        drop(state);
+
+        fields.sort_unstable();
+        Ok(fields)
+    }
+
+    /// Атомарно увеличивает целое число, хранящееся в поле хеша, на `delta`
+    /// и возвращает новое значение.
+    ///
+    /// Отсутствующие хеш и поле создаются со значением `0` перед
+    /// увеличением. Возвращает `Err`, если значение не является хешем, поле
+    /// не является целым числом или результат не помещается в `i64`.
+    ///
+    /// # Примеры
+    ///
+    /// ```
+    /// use mini_redis::DbDropGuard;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let guard = DbDropGuard::new();
+    ///     let db = guard.db();
+    ///
+    ///     assert_eq!(db.hincr_by("stats", "visits", 5).unwrap(), 5);
+    ///     assert_eq!(db.hincr_by("stats", "visits", -2).unwrap(), 3);
+    ///     assert_eq!(db.hget("stats", "visits").unwrap().unwrap(), "3");
+    /// }
+    /// ```
+    pub fn hincr_by(&self, key: &str, field: &str, delta: i64) -> crate::Result<i64> {
+        let mut state = self.shared.state.lock().unwrap();
+
+        let current = match state.hash(key)? {
+            Some(hash) => match hash.get(field) {
+                Some(value) => parse_int(value).ok_or(HASH_NOT_INTEGER)?,
+                None => 0,
+            },
+            None => 0,
+        };
+        let value = current.checked_add(delta).ok_or(OVERFLOW)?;
+
+        let value_bytes = Bytes::from(value.to_string());
+        match state.hash_mut(key)? {
+            Some(hash) => {
+                hash.insert(field.to_string(), value_bytes);
+            }
+            None => {
+                let hash = HashMap::from([(field.to_string(), value_bytes)]);
+                state.insert(
+                    key.to_string(),
+                    Entry {
+                        data: Value::Hash(Box::new(hash)),
+                        expires_at: None,
+                    },
+                );
+            }
+        }
+
+        Ok(value)
+    }
+
+    /// Проверяет наличие поля в хеше, хранящемся по ключу.
+    ///
+    /// Возвращает `Err`, если значение не является хешем.
+    pub fn hexists(&self, key: &str, field: &str) -> crate::Result<bool> {
+        let state = self.shared.state.lock().unwrap();
+
+        Ok(state
+            .hash(key)?
+            .is_some_and(|hash| hash.contains_key(field)))
+    }
+
+    /// Возвращает количество полей хеша, хранящегося по ключу.
+    ///
+    /// Если хеша нет, возвращается `0`. Возвращает `Err`, если значение не
+    /// является хешем.
+    pub fn hlen(&self, key: &str) -> crate::Result<usize> {
+        let state = self.shared.state.lock().unwrap();
+
+        Ok(state.hash(key)?.map_or(0, |hash| hash.len()))
+    }
+
+    /// Возвращает значения полей `fields` хеша, хранящегося по ключу, в
+    /// порядке `fields`.
+    ///
+    /// Отсутствующим полям соответствует `None`. Возвращает `Err`, если
+    /// значение не является хешем.
+    pub fn hmget<F: AsRef<str>>(
+        &self,
+        key: &str,
+        fields: &[F],
+    ) -> crate::Result<Vec<Option<Bytes>>> {
+        let state = self.shared.state.lock().unwrap();
+
+        let hash = state.hash(key)?;
+
+        Ok(fields
+            .iter()
+            .map(|field| hash.and_then(|hash| hash.get(field.as_ref())).cloned())
+            .collect())
+    }
+
+    /// Устанавливает значение поля хеша, только если поля нет.
+    ///
+    /// Отсутствующий хеш создается. Возвращает `true`, если поле добавлено,
+    /// и `Err`, если значение не является хешем.
+    pub fn hset_nx(&self, key: &str, field: String, value: Bytes) -> crate::Result<bool> {
+        let mut state = self.shared.state.lock().unwrap();
+
+        match state.hash_mut(key)? {
+            Some(hash) if hash.contains_key(&field) => Ok(false),
+            Some(hash) => {
+                hash.insert(field, value);
+                Ok(true)
+            }
+            None => {
+                state.insert(
+                    key.to_string(),
+                    Entry {
+                        data: Value::Hash(Box::new(HashMap::from([(field, value)]))),
+                        expires_at: None,
+                    },
+                );
+                Ok(true)
+            }
+        }
+    }
+
+    /// Возвращает случайные поля хеша, хранящегося по ключу, вместе с их
+    /// значениями.
+    ///
+    /// При положительном `count` возвращаются различные поля, не больше
+    /// `count`. При отрицательном `count` возвращается ровно `-count` полей,
+    /// которые могут повторяться. Если хеша нет, возвращается пустой вектор.
+    /// Возвращает `Err`, если значение не является хешем или отрицательный
+    /// `count` по модулю больше 2^20.
+    pub fn hrandfield(&self, key: &str, count: i64) -> crate::Result<Vec<(String, Bytes)>> {
+        // Размер ответа с повторениями определяется клиентом, а не размером
+        // хеша, поэтому он ограничивается до блокировки
+        if count < 0 && count.unsigned_abs() > RANDOM_COUNT_MAX {
+            return Err("ERR value is out of range".into());
+        }
+
+        let state = self.shared.state.lock().unwrap();
+
+        let mut fields: Vec<(&String, &Bytes)> = match state.hash(key)? {
+            Some(hash) => hash.iter().collect(),
+            None => return Ok(vec![]),
+        };
+
+        // Случайный индекс из диапазона `0..len`
+        let random_index =
+            |len: usize| ((crate::server::random_fraction() * len as f64) as usize).min(len - 1);
+
+        let sample: Vec<(&String, &Bytes)> = if count >= 0 {
+            // Частичное перемешивание Фишера-Йетса: первые `n` полей
+            // выбираются без повторений
+            let n = fields.len().min(count as usize);
+            for i in 0..n {
+                let j = i + random_index(fields.len() - i);
+                fields.swap(i, j);
+            }
+            fields.truncate(n);
+            fields
+        } else {
+            (0..count.unsigned_abs())
+                .map(|_| fields[random_index(fields.len())])
+                .collect()
+        };
+
+        Ok(sample
+            .into_iter()
+            .map(|(field, value)| (field.clone(), value.clone()))
+            .collect())
+    }
+
+    /// Добавляет элементы в сортированное множество, хранящееся по ключу, или
+    /// обновляет их оценки с учетом настроек `options`.
+    ///
+    /// Отсутствующее множество создается. Время жизни существующего
+    /// множества сохраняется. Возвращает количество добавленных элементов, а с
+    /// настройкой `changed` - количество добавленных и измененных элементов.
+    /// Возвращает `Err`, если значение не является сортированным множеством.
+    ///
+    /// # Примеры
+    ///
+    /// ```
+    /// use mini_redis::cmd::ZaddOptions;
+    /// use mini_redis::DbDropGuard;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let guard = DbDropGuard::new();
+    ///     let db = guard.db();
+    ///
+    ///     let members = vec![(10.0, "alice".to_string()), (20.0, "bob".to_string())];
+    ///     assert_eq!(db.zadd("scores", members, ZaddOptions::default()).unwrap(), 2);
+    ///     assert_eq!(db.zscore("scores", "bob").unwrap(), Some(20.0));
+    /// }
+    /// ```
+    pub fn zadd(
+        &self,
+        key: &str,
+        members: Vec<(f64, String)>,
+        options: ZaddOptions,
+    ) -> crate::Result<usize> {
+        let (added, changed) = self.zadd_changes(key, members, options)?;
+
+        if options.changed {
+            Ok(added + changed)
+        } else {
+            Ok(added)
+        }
+    }
+
+    /// Добавляет элементы в сортированное множество так же, как `zadd`, и
+    /// возвращает количество добавленных элементов и элементов с измененной
+    /// оценкой независимо от `options.changed`
+    pub(crate) fn zadd_changes(
+        &self,
+        key: &str,
+        members: Vec<(f64, String)>,
+        options: ZaddOptions,
+    ) -> crate::Result<(usize, usize)> {
+        let mut state = self.shared.state.lock().unwrap();
+
+        let zset = match state.zset_mut(key)? {
+            Some(zset) => zset,
+            // Пустое множество не создается
+            None if members.is_empty() || options.condition == Some(SetCondition::Xx) => {
+                return Ok((0, 0))
+            }
+            None => {
+                state.insert(
+                    key.to_string(),
+                    Entry {
+                        data: Value::SortedSet(Box::default()),
+                        expires_at: None,
+                    },
+                );
+                state.zset_mut(key)?.unwrap()
+            }
+        };
+
+        let mut added = 0;
+        let mut changed = 0;
+        for (score, member) in members {
+            match zset.score(&member) {
+                None if options.condition == Some(SetCondition::Xx) => {}
+                None => {
+                    zset.insert(member, score);
+                    added += 1;
+                }
+                Some(_) if options.condition == Some(SetCondition::Nx) => {}
+                Some(prev) => {
+                    let update = match options.comparison {
+                        Some(ZaddComparison::Gt) => score > prev,
+                        Some(ZaddComparison::Lt) => score < prev,
+                        None => score != prev,
+                    };
+
+                    if update {
+                        zset.insert(member, score);
+                        changed += 1;
+                    }
+                }
+            }
+        }
+
+        Ok((added, changed))
+    }
+
+    /// Возвращает оценку элемента сортированного множества, хранящегося по
+    /// ключу.
+    ///
+    /// Возвращает `None`, если множества или элемента нет, и `Err`, если
+    /// значение не является сортированным множеством.
+    pub fn zscore(&self, key: &str, member: &str) -> crate::Result<Option<f64>> {
+        let state = self.shared.state.lock().unwrap();
+
+        Ok(state.zset(key)?.and_then(|zset| zset.score(member)))
+    }
+
+    /// Удаляет элементы сортированного множества, хранящегося по ключу, и
+    /// возвращает количество удаленных элементов.
+    ///
+    /// Множество без элементов удаляется. Возвращает `Err`, если значение не
+    /// является сортированным множеством.
+    pub fn zrem<M: AsRef<str>>(&self, key: &str, members: &[M]) -> crate::Result<usize> {
+        let mut state = self.shared.state.lock().unwrap();
+
+        let zset = match state.zset_mut(key)? {
+            Some(zset) => zset,
+            None => return Ok(0),
+        };
+
+        let removed = members
+            .iter()
+            .filter(|member| zset.remove(member.as_ref()).is_some())
+            .count();
+
+        if zset.is_empty() {
+            state.remove(key);
+        }
+
+        Ok(removed)
+    }
+
+    /// Возвращает элементы сортированного множества, хранящегося по ключу, с
+    /// позициями от `start` до `stop` включительно вместе с их оценками.
+    ///
+    /// Позиции отсчитываются от элемента с наименьшей оценкой, а если `rev`
+    /// равен `true` - с наибольшей. Отрицательные позиции отсчитываются от
+    /// конца, как в `get_range`. Если множества нет, возвращается пустой
+    /// вектор. Возвращает `Err`, если значение не является сортированным
+    /// множеством.
+    pub fn zrange(
+        &self,
+        key: &str,
+        start: i64,
+        stop: i64,
+        rev: bool,
+    ) -> crate::Result<Vec<(String, f64)>> {
+        let state = self.shared.state.lock().unwrap();
+
+        let zset = match state.zset(key)? {
+            Some(zset) => zset,
+            None => return Ok(vec![]),
+        };
+
+        let len = zset.len() as i64;
+
+        let start = if start < 0 { len + start } else { start }.max(0);
+        let stop = if stop < 0 { len + stop } else { stop }.min(len - 1);
+
+        if start > stop {
+            return Ok(vec![]);
+        }
+
+        let members: Box<dyn Iterator<Item = (&str, f64)>> = if rev {
+            Box::new(zset.iter().rev())
+        } else {
+            Box::new(zset.iter())
+        };
+
+        Ok(members
+            .skip(start as usize)
+            .take((stop - start + 1) as usize)
+            .map(|(member, score)| (member.to_string(), score))
+            .collect())
+    }
+
+    /// Возвращает элементы сортированного множества, хранящегося по ключу,
+    /// оценки которых находятся между `min` и `max`, вместе с их оценками в
+    /// порядке возрастания.
+    ///
+    /// Первые `offset` подходящих элементов пропускаются, возвращается не
+    /// больше `count` элементов (все, если `count` равен `None`). Если
+    /// множества нет, возвращается пустой вектор. Возвращает `Err`, если
+    /// значение не является сортированным множеством.
+    ///
+    /// # Примеры
+    ///
+    /// ```
+    /// use mini_redis::cmd::ZaddOptions;
+    /// use mini_redis::DbDropGuard;
+    /// use std::ops::Bound;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let guard = DbDropGuard::new();
+    ///     let db = guard.db();
+    ///
+    ///     let members = vec![(1.0, "a".to_string()), (2.0, "b".to_string())];
+    ///     db.zadd("scores", members, ZaddOptions::default()).unwrap();
+    ///
+    ///     let range = db
+    ///         .zrange_by_score("scores", Bound::Excluded(1.0), Bound::Unbounded, 0, None)
+    ///         .unwrap();
+    ///     assert_eq!(range, vec![("b".to_string(), 2.0)]);
+    /// }
+    /// ```
+    pub fn zrange_by_score(
+        &self,
+        key: &str,
+        min: Bound<f64>,
+        max: Bound<f64>,
+        offset: usize,
+        count: Option<usize>,
+    ) -> crate::Result<Vec<(String, f64)>> {
+        let state = self.shared.state.lock().unwrap();
+
+        let zset = match state.zset(key)? {
+            Some(zset) => zset,
+            None => return Ok(vec![]),
+        };
+
+        Ok(zset
+            .range_by_score(min, max)
+            .skip(offset)
+            .take(count.unwrap_or(usize::MAX))
+            .map(|(member, score)| (member.to_string(), score))
+            .collect())
+    }
+
+    /// Возвращает элементы сортированного множества, хранящегося по ключу,
+    /// находящиеся между `min` и `max` в лексикографическом порядке, вместе с
+    /// их оценками.
+    ///
+    /// Как и в Redis, предполагается, что оценки всех элементов одинаковы.
+    /// `offset` и `count` учитываются так же, как в `zrange_by_score`. Если
+    /// множества нет, возвращается пустой вектор. Возвращает `Err`, если
+    /// значение не является сортированным множеством.
+    pub fn zrange_by_lex(
+        &self,
+        key: &str,
+        min: Bound<&str>,
+        max: Bound<&str>,
+        offset: usize,
+        count: Option<usize>,
+    ) -> crate::Result<Vec<(String, f64)>> {
+        let state = self.shared.state.lock().unwrap();
+
+        let zset = match state.zset(key)? {
+            Some(zset) => zset,
+            None => return Ok(vec![]),
+        };
+
+        Ok(zset
+            .range_by_lex(min, max)
+            .skip(offset)
+            .take(count.unwrap_or(usize::MAX))
+            .map(|(member, score)| (member.to_string(), score))
+            .collect())
+    }
+
+    /// Возвращает количество элементов сортированного множества, хранящегося
+    /// по ключу, оценки которых находятся между `min` и `max`.
+    ///
+    /// Возвращает `Err`, если значение не является сортированным множеством.
+    pub fn zcount(&self, key: &str, min: Bound<f64>, max: Bound<f64>) -> crate::Result<usize> {
+        let state = self.shared.state.lock().unwrap();
+
+        Ok(state
+            .zset(key)?
+            .map_or(0, |zset| zset.range_by_score(min, max).count()))
+    }
+
+    /// Атомарно увеличивает оценку элемента сортированного множества,
+    /// хранящегося по ключу, на `delta` и возвращает новую оценку.
+    ///
+    /// Отсутствующие множество и элемент создаются с оценкой `0` перед
+    /// увеличением. Возвращает `Err`, если значение не является
+    /// сортированным множеством или новая оценка не является числом.
+    pub fn zincr_by(&self, key: &str, member: &str, delta: f64) -> crate::Result<f64> {
+        let mut state = self.shared.state.lock().unwrap();
+
+        let current = match state.zset(key)? {
+            Some(zset) => zset.score(member).unwrap_or(0.0),
+            None => 0.0,
+        };
+
+        let score = current + delta;
+        if score.is_nan() {
+            return Err(SCORE_NAN.into());
+        }
+
+        match state.zset_mut(key)? {
+            Some(zset) => {
+                zset.insert(member.to_string(), score);
+            }
+            None => {
+                let mut zset = SortedSet::default();
+                zset.insert(member.to_string(), score);
+                state.insert(
+                    key.to_string(),
+                    Entry {
+                        data: Value::SortedSet(Box::new(zset)),
+                        expires_at: None,
+                    },
+                );
+            }
+        }
+
+        Ok(score)
+    }
+
+    /// Возвращает позицию элемента сортированного множества, хранящегося по
+    /// ключу, в порядке возрастания оценок.
+    ///
+    /// Возвращает `None`, если множества или элемента нет, и `Err`, если
+    /// значение не является сортированным множеством.
+    pub fn zrank(&self, key: &str, member: &str) -> crate::Result<Option<usize>> {
+        let state = self.shared.state.lock().unwrap();
+
+        Ok(state.zset(key)?.and_then(|zset| zset.rank(member)))
+    }
+
+    /// Возвращает снимок всех ключей с их значениями и оставшимся временем
+    /// жизни. См. `crate::snapshot`.
+    ///
+    /// Сущности копируются под блокировкой, поэтому снимок согласован:
+    /// изменения, выполненные после его создания, в него не попадают.
+    /// Длинные строки хранятся в `Bytes` и не копируются, а хеши и
+    /// сортированные множества копируются целиком.
+    ///
+    /// # Примеры
+    ///
+    /// ```
+    /// use mini_redis::DbDropGuard;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let guard = DbDropGuard::new();
+    ///     let db = guard.db();
+    ///
+    ///     db.set("foo".to_string(), "bar".into(), None);
+    ///     let snapshot = db.snapshot();
+    ///     db.set("baz".to_string(), "qux".into(), None);
+    ///
+    ///     let keys: Vec<String> = snapshot.map(|entry| entry.key).collect();
+    ///     assert_eq!(keys, vec!["foo"]);
+    /// }
+    /// ```
+    pub fn snapshot(&self) -> Snapshot {
+        let state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+
+        let entries = state
+            .entries
+            .iter()
+            .filter(|(_, entry)| !entry.is_expired(now))
+            .map(|(key, entry)| {
+                SnapshotEntry::new(
+                    key.clone(),
+                    SnapshotValue::new(&entry.data),
+                    entry
+                        .expires_at
+                        .map(|when| when.instant.saturating_duration_since(now)),
+                )
+            })
+            .collect();
+
+        Snapshot::new(entries)
+    }
+
+    /// Возвращает значение по ключу, ожидая его установки не дольше
+    /// `timeout`.
+    ///
+    /// Если значение уже установлено, оно возвращается сразу. Иначе задача
+    /// ожидает установки значения (`set` или командой `SET`). Задачи,
+    /// ожидающие один ключ, получают значение в порядке вызова. Возвращает
+    /// `None`, если значение не было установлено за время `timeout`. `None`
+    /// в качестве `timeout` означает ожидание без ограничения времени.
+    ///
+    /// # Примеры
+    ///
+    /// ```
+    /// use mini_redis::DbDropGuard;
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let guard = DbDropGuard::new();
+    ///     let db = guard.db();
+    ///
+    ///     let producer = db.clone();
+    ///     tokio::spawn(async move {
+    ///         producer.set("result".to_string(), "done".into(), None);
+    ///     });
+    ///
+    ///     let value = db.wait_for_key("result", Some(Duration::from_secs(1))).await;
+    ///     assert_eq!(value.unwrap(), "done");
+    /// }
+    /// ```
+    pub async fn wait_for_key(&self, key: &str, timeout: Option<Duration>) -> Option<Bytes> {
+        self.block_on(&[key], timeout, |state| {
+            state
+                .entries
+                .get(key)
+                .filter(|entry| !entry.is_expired(Instant::now()))
+                .and_then(|entry| entry.data.to_bytes())
+        })
+        .await
+    }
+
+    /// Выполняет блокирующую операцию над ключами `keys`.
+    ///
+    /// `op` вызывается под блокировкой и возвращает результат операции или
+    /// `None`, если результата пока нет. В этом случае задача
+    /// регистрируется в реестре ожидающих задач (см. `Waiters`) и ожидает
+    /// изменения одного из ключей, после чего `op` вызывается снова.
+    /// Возвращает `None`, если результат не получен за время `timeout`.
+    async fn block_on<T>(
+        &self,
+        keys: &[&str],
+        timeout: Option<Duration>,
+        mut op: impl FnMut(&mut State) -> Option<T>,
+    ) -> Option<T> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+        let waiter = Arc::new(Notify::new());
+        let mut guard = WaitGuard {
+            db: self,
+            keys,
+            waiter: waiter.clone(),
+            registered: false,
+        };
+
+        loop {
+            {
+                let mut state = self.shared.state.lock().unwrap();
+
+                if let Some(res) = op(&mut state) {
+                    return Some(res);
+                }
+
+                // Пробужденная задача, не получившая результата, возвращается
+                // в начало очереди, чтобы не потерять очередь
+                for key in keys {
+                    state.waiters.register(key, &waiter, guard.registered);
+                }
+                guard.registered = true;
+            }
+
+            match deadline {
+                Some(deadline) => {
+                    if time::timeout_at(deadline, waiter.notified()).await.is_err() {
+                        return None;
+                    }
+                }
+                None => waiter.notified().await,
+            }
+        }
+    }
+
+    /// Возвращает статистику использования памяти.
+    ///
+    /// Размер данных и накладные расходы вычисляются перебором всех ключей,
+    /// поэтому время выполнения пропорционально их количеству. См.
+    /// `crate::memory`.
+    pub fn memory_stats(&self) -> MemoryStats {
+        let state = self.shared.state.lock().unwrap();
+
+        let mut dataset = 0;
+        let mut expirations = 0;
+        for (key, entry) in &state.entries {
+            dataset += key.len() + entry.data.data_size();
+
+            // Индекс времен жизни хранит копию ключа
+            if entry.expires_at.is_some() {
+                expirations += mem::size_of::<(Instant, String)>() + key.len();
+            }
+        }
+
+        // Каждый элемент хеш-таблицы также занимает управляющий байт
+        let entries = state.entries.capacity() * (mem::size_of::<(String, Entry)>() + 1);
+        let pub_sub = state.pub_sub.capacity()
+            * (mem::size_of::<(String, broadcast::Sender<Bytes>)>() + 1)
+            + state.pub_sub.keys().map(String::len).sum::<usize>();
+
+        // Индексы префиксов и перебора хранят копии ключей
+        let prefix_index = state.prefix_index.as_ref().map_or(0, |index| {
+            index
+                .iter()
+                .map(|key| mem::size_of::<String>() + key.len())
+                .sum::<usize>()
+        });
+        let scan_index = state
+            .scan_index
+            .iter()
+            .map(|(_, key)| mem::size_of::<(u64, String)>() + key.len())
+            .sum::<usize>();
+
+        let keys = state.entries.len();
+
+        // Освобождаем мьютекс до чтения статистики процесса
+        drop(state);
+
+        MemoryStats::new(
+            keys as u64,
+            dataset as u64,
+            (entries + expirations + pub_sub + prefix_index + scan_index) as u64,
+        )
+    }
+
+    /// Возвращает кодировку значения по ключу (см. `crate::value`) или
+    /// `None`, если ключ отсутствует.
+    pub(crate) fn encoding(&self, key: &str) -> Option<&'static str> {
+        let state = self.shared.state.lock().unwrap();
+        state
+            .entries
+            .get(key)
+            .filter(|entry| !entry.is_expired(Instant::now()))
+            .map(|entry| entry.data.encoding())
+    }
+
+    /// Возвращает все ключи, соответствующие шаблону `pattern`.
+    ///
+    /// Ключи возвращаются в лексикографическом порядке. Перебор выполняется
+    /// под мьютексом, поэтому при наступлении `deadline` он прерывается и
+    /// возвращается `Err`.
+    pub(crate) fn keys(
+        &self,
+        pattern: &str,
+        deadline: Option<Instant>,
+    ) -> crate::Result<Vec<String>> {
+        let state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+
+        let mut keys = vec![];
+        for (i, (key, entry)) in state.candidates(Some(pattern)).enumerate() {
+            if i % DEADLINE_CHECK_INTERVAL == 0 && is_past(deadline) {
+                return Err(TIMED_OUT.into());
+            }
+
+            if !entry.is_expired(now) && glob::matches(pattern.as_bytes(), key.as_bytes()) {
+                keys.push(key.clone());
+            }
+        }
+
+        // Освобождаем мьютекс до сортировки
+        drop(state);
+
+        keys.sort_unstable();
+        Ok(keys)
+    }
+
+    /// Возвращает очередную порцию ключей, начиная с позиции `cursor`.
+    ///
+    /// Ключи перебираются в порядке их хешей, а курсор - это хеш следующего
+    /// ключа. Рассматривается не более `count` ключей (кроме ключей с
+    /// одинаковым хешем, которые не разделяются между порциями), из которых
+    /// возвращаются только соответствующие шаблону `pattern` (при наличии).
+    /// Поэтому порция может быть пустой, даже если перебор не завершен.
+    ///
+    /// Возвращает курсор для следующего вызова. `0` означает, что перебор завершен.
+    ///
+    /// Позиция ключа в порядке перебора зависит только от самого ключа,
+    /// поэтому добавление и удаление других ключей не сдвигает курсор: ключ,
+    /// присутствующий в течение всего перебора, возвращается ровно один раз.
+    /// Ключи, добавленные или удаленные во время перебора, могут быть как
+    /// возвращены, так и пропущены.
+    ///
+    /// Большой `count` может надолго занять мьютекс, поэтому при наступлении
+    /// `deadline` перебор прерывается и возвращается `Err`.
+    pub(crate) fn scan(
+        &self,
+        cursor: u64,
+        pattern: Option<&str>,
+        count: usize,
+        deadline: Option<Instant>,
+    ) -> crate::Result<(u64, Vec<String>)> {
+        let state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+
+        // Перебор начинается с первого ключа, хеш которого не меньше
+        // курсора, и рассматривает не более `count` ключей, поэтому порция
+        // занимает O(count + log N) независимо от размера БД
+        let mut keys = state.scan_index.range((cursor, String::new())..).peekable();
+        let count = count.max(1);
+        let mut examined = 0;
+        let mut page = vec![];
+
+        while let Some((hash, key)) = keys.peek() {
+            // Курсор указывает на хеш, поэтому ключи с одинаковым хешем
+            // возвращаются в одной порции
+            if examined >= count && page.last().map(|&(last, _, _)| last) != Some(*hash) {
+                break;
+            }
+            if examined % DEADLINE_CHECK_INTERVAL == 0 && is_past(deadline) {
+                return Err(TIMED_OUT.into());
+            }
+            examined += 1;
+
+            let expired = state
+                .entries
+                .get(key)
+                .is_none_or(|entry| entry.is_expired(now));
+            page.push((*hash, key, expired));
+            keys.next();
+        }
+
+        // Хеш следующего ключа больше хешей всех рассмотренных ключей,
+        // поэтому он не может быть равен `0`
+        let next = keys.next().map_or(0, |&(hash, _)| hash);
+
+        let page = page
+            .into_iter()
+            .filter(|&(_, key, expired)| {
+                !expired
+                    && pattern
+                        .is_none_or(|pattern| glob::matches(pattern.as_bytes(), key.as_bytes()))
+            })
+            .map(|(_, key, _)| key.clone())
+            .collect();
+
+        Ok((next, page))
+    }
+
+    /// Возвращает очередную порцию полей хеша, хранящегося по ключу, вместе
+    /// с их значениями, начиная с позиции `cursor`.
+    ///
+    /// Поля перебираются так же, как ключи в `scan`. Если хеша нет,
+    /// возвращается пустая порция и курсор `0`. Возвращает `Err`, если
+    /// значение не является хешем.
+    pub(crate) fn hscan(
+        &self,
+        key: &str,
+        cursor: u64,
+        pattern: Option<&str>,
+        count: usize,
+    ) -> crate::Result<(u64, Vec<(String, Bytes)>)> {
+        let state = self.shared.state.lock().unwrap();
+
+        let hash = match state.hash(key)? {
+            Some(hash) => hash,
+            None => return Ok((0, vec![])),
+        };
+
+        let fields = hash.iter().map(|entry| (entry.0.as_str(), entry));
+        let (next, page) = scan_page(fields, cursor, pattern, count);
+
+        Ok((
+            next,
+            page.into_iter()
+                .map(|(field, value)| (field.clone(), value.clone()))
+                .collect(),
+        ))
+    }
+
+    /// Возвращает `Receiver` для запрошенного канала.
+    ///
+    /// Этот `Receiver` используется для получения значений, отправленных с помощью команды `PUBLISH`
+    /// или метода `publish`.
+    ///
+    /// # Примеры
+    ///
+    /// ```
+    /// use mini_redis::DbDropGuard;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let guard = DbDropGuard::new();
+    ///     let db = guard.db();
+    ///
+    ///     let mut rx = db.subscribe("news".to_string());
+    ///     assert_eq!(db.publish("news", "hello".into()), 1);
+    ///     assert_eq!(rx.recv().await.unwrap(), "hello");
+    /// }
+    /// ```
+    pub fn subscribe(&self, key: String) -> broadcast::Receiver<Bytes> {
+        use std::collections::hash_map::Entry;
+
+        // Блокируем мьютекс.
+        let mut state = self.shared.state.lock().unwrap();
+
+        // Если для запрошенного канала нет сущности, создаем новый
+        // широковещательный (broadcast) канал и связываем его с ключом. Если канал существует,
+        // возвращаем соответствующего получателя.
+        match state.pub_sub.entry(key) {
+            Entry::Occupied(e) => e.get().subscribe(),
+            Entry::Vacant(e) => {
+                // Широковещательный канал отсутствует, создаем его.
+                //
+                // Канал создается с емкостью `1024` сообщений.
+                // Сообщение хранится в канале до тех пор, пока все подписчики
+                // его не увидят. Это означает, что наличие "медленного" подписчика может привести к
+                // бесконечно долгому хранению сообщения.
+                //
+                // При заполнении емкости канала, публикация будет приводить к
+                // уничтожению старых сообщений. Это решает проблему блокировки
+                // всей системы медленными потребителями.
+                let (tx, rx) = broadcast::channel(1024);
+                e.insert(tx);
+                rx
+            }
+        }
+    }
+
+    /// Публикует сообщение в канале. Возвращает количество подписчиков,
+    /// "слушающих" канал.
+    ///
+    /// Канал, у которого не осталось подписчиков, удаляется.
+    pub fn publish(&self, key: &str, value: Bytes) -> usize {
+        let mut state = self.shared.state.lock().unwrap();
+
+        // Если по ключу канала нет сущности, значит нет и
+        // подписчиков. В этом случае возвращается `0`.
+        let tx = match state.pub_sub.get(key) {
+            Some(tx) => tx,
+            None => return 0,
+        };
+
+        // При успешной отправке сообщения в широковещательный канал, возвращается
+        // количество подписчиков. Ошибка указывает на отсутствие
+        // получателей. В этом случае должен возвращаться `0`.
+        match tx.send(value) {
+            Ok(num) => num,
+            Err(_) => {
+                state.pub_sub.remove(key);
+                0
+            }
+        }
+    }
+
+    /// Удаляет канал, если у него не осталось подписчиков.
+    ///
+    /// Вызывается после уничтожения получателя, возвращенного `subscribe`,
+    /// чтобы каналы с меняющимися названиями не накапливались в БД.
+    pub(crate) fn release_channel(&self, key: &str) {
+        let mut state = self.shared.state.lock().unwrap();
+
+        if let Some(tx) = state.pub_sub.get(key) {
+            if tx.receiver_count() == 0 {
+                state.pub_sub.remove(key);
+            }
+        }
+    }
+
+    /// Возвращает количество каналов, на которые подписан хотя бы один
+    /// клиент.
+    ///
+    /// Каналы без подписчиков удаляются при отписке и публикации, поэтому
+    /// значение может включать каналы, подписчики которых только что
+    /// отключились.
+    pub fn num_channels(&self) -> usize {
+        self.shared.state.lock().unwrap().pub_sub.len()
+    }
+
+    /// Возвращает настройки внедряемых сбоев сервера, использующего эту БД.
+    #[cfg(feature = "chaos")]
+    pub fn chaos(&self) -> &crate::chaos::Chaos {
+        &self.shared.chaos
+    }
+
+    /// Включает или отключает удаление истекших ключей фоновой задачей.
+    ///
+    /// Пока очистка отключена, истекшие ключи остаются в памяти, но не
+    /// возвращаются при чтении. Это позволяет, например, "заморозить"
+    /// состояние БД в тестах. По умолчанию очистка включена.
+    pub fn set_active_expire(&self, enabled: bool) {
+        self.shared.state.lock().unwrap().active_expire = enabled;
+        self.shared.background_task.notify_one();
+    }
+
+    /// Устанавливает максимальное количество ключей, удаляемых за один проход
+    /// очистки. Оставшиеся истекшие ключи удаляются следующим проходом.
+    ///
+    /// # Паники
+    ///
+    /// Паникует, если `size` равен `0`.
+    pub fn set_purge_batch_size(&self, size: usize) {
+        assert!(size > 0, "Размер порции очистки должен быть больше нуля");
+        self.shared.state.lock().unwrap().purge_batch_size = size;
+        self.shared.background_task.notify_one();
+    }
+
+    /// Устанавливает минимальный интервал между проходами очистки.
+    ///
+    /// Больший интервал снижает нагрузку фоновой задачи, но истекшие ключи
+    /// дольше занимают память. По умолчанию интервал не ограничен.
+    pub fn set_purge_min_interval(&self, interval: Duration) {
+        self.shared.state.lock().unwrap().purge_min_interval = interval;
+        self.shared.background_task.notify_one();
+    }
+
+    /// Включает или отключает индекс префиксов ключей.
+    ///
+    /// Индекс хранит ключи в упорядоченном виде, поэтому `KEYS` с шаблоном,
+    /// начинающимся с префикса без специальных символов (например,
+    /// `user:*`), перебирает только ключи с этим префиксом, а не все ключи. Это ускоряет запросы по префиксу в больших БД ценой
+    /// дополнительной памяти для копий ключей и небольшого замедления
+    /// добавления и удаления ключей. При включении индекс строится по
+    /// существующим ключам. По умолчанию индекс отключен.
+    pub fn set_prefix_index(&self, enabled: bool) {
+        let mut state = self.shared.state.lock().unwrap();
+
+        state.prefix_index = if enabled {
+            Some(state.entries.keys().cloned().collect())
+        } else {
+            None
+        };
+    }
+
+    /// Указывает фоновой задаче очистки закрыться. Это вызывается
+    /// реализацией `Drop` `DbShutdown`
+    fn shutdown_purge_task(&self) {
+        // Фоновая задача должна получить сигнал о закрытии. Это делается путем
+        // установки `State::shutdown` в значение `true`.
+        let mut state = self.shared.state.lock().unwrap();
+        state.shutdown = true;
+
+        // Снимаем блокировку перед уведомлением фоновой задачи. Это позволяет
+        // предотвратить ситуацию, когда фоновая задача не может блокировать мьютекс.
+        drop(state);
+        self.shared.background_task.notify_one();
+    }
+}
+
+impl Shared {
+    /// Очищает истекшие ключи и возвращает `Instant`, когда должен быть
+    /// выполнен следующий проход очистки. Фоновая задача "спит" до этого момента.
+    ///
+    /// За один проход удаляется не более `purge_batch_size` ключей. Следующий
+    /// проход выполняется не раньше, чем через `purge_min_interval`.
+    fn purge_expired_keys(&self) -> Option<Instant> {
+        let mut state = self.state.lock().unwrap();
+
+        if state.shutdown || !state.active_expire {
+            // БД закрывается. Все обработчики общего состояния
+            // уничтожены. Фоновая задача должна завершиться.
+            //
+            // Если очистка отключена, задача ждет уведомления о ее включении.
+            return None;
+        }
+
+        // Это нужно для того, чтобы сделать "счастливым" контроллера заимствований (borrow checker). Если коротко,
+        // `lock()` возвращает `MutexGuard`, а не `&mut State`. Контроллер заимствований
+        // не может видеть "сквозь" защитника мьютекса и определить, что
+        // мутабельный доступ к `state.expirations` и `state.entries` является безопасным,
+        // поэтому мы получаем "настоящую" мутабельную ссылку на `State` за пределами цикла.
+        let state = &mut *state;
+
+        // Находим все ключи, истекшие до настоящего времени.
+        let now = Instant::now();
+        let next_pass = now + state.purge_min_interval;
+        let mut purged = 0;
+
+        while let Some(&(when, ref key)) = state.expirations.iter().next() {
+            if when > now {
+                // Выполняем очистку. `when` - это момент, когда истекает
+                // следующий ключ. Воркер задачи ждет этого момента.
+                return Some(when.max(next_pass));
+            }
+
+            if purged == state.purge_batch_size {
+                // Порция исчерпана. Оставшиеся истекшие ключи удаляются
+                // следующим проходом, а мьютекс тем временем освобождается
+                return Some(next_pass);
+            }
+
+            // Ключ истек, удаляем его.
+            state.entries.remove(key);
+            if let Some(index) = &mut state.prefix_index {
+                index.remove(key);
+            }
+            state
+                .scan_index
+                .remove(&(ring::hash(key.as_bytes()), key.clone()));
+            state.expirations.remove(&(when, key.clone()));
+            purged += 1;
+        }
+
+        None
+    }
+
+    /// Возвращает `true`, если БД закрыта.
     ///
     /// Флаг `shutdown` устанавливается в значение `true`, когда все значения `Db` уничтожаются,
     /// что означает недоступность общего состояния.
@@ -319,7 +2625,332 @@ impl Shared {
     }
 }
 
+impl Waiters {
+    /// Регистрирует задачу `waiter` в очереди ожидания `key`.
+    ///
+    /// Задача, уже находящаяся в очереди, сохраняет свою позицию. Иначе она
+    /// добавляется в начало очереди, если `front` равен `true`, или в конец.
+    fn register(&mut self, key: &str, waiter: &Arc<Notify>, front: bool) {
+        let queue = self.keys.entry(key.to_string()).or_default();
+
+        if queue.iter().any(|queued| Arc::ptr_eq(queued, waiter)) {
+            return;
+        }
+
+        if front {
+            queue.push_front(waiter.clone());
+        } else {
+            queue.push_back(waiter.clone());
+        }
+    }
+
+    /// Удаляет задачу `waiter` из очереди ожидания `key`.
+    ///
+    /// Возвращает `false`, если задачи не было в очереди, то есть она уже
+    /// была пробуждена.
+    fn unregister(&mut self, key: &str, waiter: &Arc<Notify>) -> bool {
+        let queue = match self.keys.get_mut(key) {
+            Some(queue) => queue,
+            None => return false,
+        };
+
+        let removed = match queue.iter().position(|queued| Arc::ptr_eq(queued, waiter)) {
+            Some(pos) => queue.remove(pos).is_some(),
+            None => false,
+        };
+
+        if queue.is_empty() {
+            self.keys.remove(key);
+        }
+
+        removed
+    }
+
+    /// Пробуждает первую задачу, ожидающую изменения `key`.
+    fn wake(&mut self, key: &str) {
+        if let Some(queue) = self.keys.get_mut(key) {
+            if let Some(waiter) = queue.pop_front() {
+                waiter.notify_one();
+            }
+
+            if queue.is_empty() {
+                self.keys.remove(key);
+            }
+        }
+    }
+}
+
+impl Drop for WaitGuard<'_> {
+    fn drop(&mut self) {
+        if !self.registered {
+            return;
+        }
+
+        let mut state = self.db.shared.state.lock().unwrap();
+
+        for key in self.keys {
+            // Задача, удаленная из очереди, была пробуждена. Пробуждение
+            // передается следующей задаче: ей может достаться оставшийся
+            // результат или результат, который эта задача не получила из-за
+            // отмены или истечения времени ожидания
+            if !state.waiters.unregister(key, &self.waiter) {
+                state.waiters.wake(key);
+            }
+        }
+    }
+}
+
+/// Возвращает очередную порцию элементов `items`, начиная с позиции
+/// `cursor`, и курсор для следующего вызова. Используется `Db::hscan`:
+/// в отличие от ключей, поля хешей не индексируются, поэтому каждая порция
+/// рассматривает все поля. Порядок перебора тот же, что и в `Db::scan`.
+///
+/// Каждый элемент сопровождается названием, по хешу которого определяется
+/// его позиция в порядке перебора и которое сравнивается с шаблоном
+/// `pattern`.
+fn scan_page<'a, T>(
+    items: impl Iterator<Item = (&'a str, T)>,
+    cursor: u64,
+    pattern: Option<&str>,
+    count: usize,
+) -> (u64, Vec<T>) {
+    // Элементы, еще не рассмотренные перебором
+    let mut items: Vec<(u64, &str, T)> = items
+        .map(|(name, item)| (ring::hash(name.as_bytes()), name, item))
+        .filter(|&(hash, _, _)| hash >= cursor)
+        .collect();
+
+    // Полная сортировка не нужна: достаточно найти хеш последнего элемента
+    // порции и отделить элементы с хешами не больше него. Курсор указывает
+    // на хеш, поэтому элементы с одинаковым хешем возвращаются в одной
+    // порции
+    let count = count.max(1);
+    let mut next = 0;
+    if items.len() > count {
+        let (_, &mut (last, _, _), _) = items.select_nth_unstable_by_key(count - 1, |item| item.0);
+
+        // Хеш следующего элемента больше хешей всех возвращенных элементов,
+        // поэтому он не может быть равен `0`
+        next = items
+            .iter()
+            .map(|&(hash, _, _)| hash)
+            .filter(|&hash| hash > last)
+            .min()
+            .unwrap_or(0);
+        items.retain(|&(hash, _, _)| hash <= last);
+    }
+    items.sort_unstable_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+
+    let page = items
+        .into_iter()
+        .filter(|(_, name, _)| {
+            pattern.is_none_or(|pattern| glob::matches(pattern.as_bytes(), name.as_bytes()))
+        })
+        .map(|(_, _, item)| item)
+        .collect();
+
+    (next, page)
+}
+
+/// Возвращает `true`, если момент `deadline` наступил.
+fn is_past(deadline: Option<Instant>) -> bool {
+    deadline.is_some_and(|deadline| Instant::now() >= deadline)
+}
+
 impl State {
+    /// Добавляет сущность по ключу, заменяя предыдущую, и обновляет индекс
+    /// времен жизни.
+    ///
+    /// Возвращает `true`, если время жизни сущности стало ближайшим. В этом
+    /// случае фоновая задача должна быть уведомлена после освобождения
+    /// мьютекса.
+    fn insert(&mut self, key: String, entry: Entry) -> bool {
+        let expires_at = entry.expires_at;
+
+        // "Воркер" задачи уведомляется, только если добавленное время жизни
+        // является следующим истекающим ключом. В этом случае воркер
+        // должен быть "разбужен" для обновления своего состояния.
+        let notify = expires_at.is_some_and(|when| {
+            self.next_expiration()
+                .map(|expiration| expiration > when.instant)
+                .unwrap_or(true)
+        });
+
+        let prev = self.entries.insert(key.clone(), entry);
+
+        if prev.is_none() {
+            if let Some(index) = &mut self.prefix_index {
+                index.insert(key.clone());
+            }
+            self.scan_index
+                .insert((ring::hash(key.as_bytes()), key.clone()));
+        }
+
+        // Если по ключу имеется значение и у него есть время жизни. Соответствующая сущность в карте
+        // `expirations` также должна быть удалена. Это предотвращает утечку данных.
+        if let Some(prev) = prev {
+            if let Some(when) = prev.expires_at {
+                // Удаляем время жизни.
+                self.expirations.remove(&(when.instant, key.clone()));
+            }
+        }
+
+        // Пробуждаем задачи, ожидающие изменения ключа
+        self.waiters.wake(&key);
+
+        // Отслеживаем время жизни. Добавление сущности перед удалением может привести к багу,
+        // когда текущий `(when, key)` будет равен предыдущему `(when, key)`.
+        // Удаление перед добавлением решает эту проблему.
+        if let Some(when) = expires_at {
+            self.expirations.insert((when.instant, key));
+        }
+
+        notify
+    }
+
+    /// Заменяет время жизни существующей сущности и обновляет индекс
+    /// времен жизни.
+    ///
+    /// Возвращает `None`, если сущности нет или она истекла, иначе - признак
+    /// необходимости уведомить фоновую задачу, как `insert`.
+    fn set_expiration(&mut self, key: &str, expires_at: Option<Expiration>) -> Option<bool> {
+        let now = Instant::now();
+        let entry = self
+            .entries
+            .get_mut(key)
+            .filter(|entry| !entry.is_expired(now))?;
+
+        if let Some(when) = mem::replace(&mut entry.expires_at, expires_at) {
+            self.expirations.remove(&(when.instant, key.to_string()));
+        }
+
+        let notify = expires_at.is_some_and(|when| {
+            self.next_expiration()
+                .map(|expiration| expiration > when.instant)
+                .unwrap_or(true)
+        });
+
+        if let Some(when) = expires_at {
+            self.expirations.insert((when.instant, key.to_string()));
+        }
+
+        Some(notify)
+    }
+
+    /// Возвращает хеш, хранящийся по ключу.
+    ///
+    /// Возвращает `None`, если сущности нет или она истекла, и `Err`, если
+    /// значение не является хешем.
+    fn hash(&self, key: &str) -> crate::Result<Option<&HashMap<String, Bytes>>> {
+        match self
+            .entries
+            .get(key)
+            .filter(|entry| !entry.is_expired(Instant::now()))
+        {
+            Some(Entry {
+                data: Value::Hash(hash),
+                ..
+            }) => Ok(Some(hash)),
+            Some(_) => Err(WRONGTYPE.into()),
+            None => Ok(None),
+        }
+    }
+
+    /// Возвращает изменяемую ссылку на хеш, хранящийся по ключу. Работает
+    /// так же, как `hash`.
+    fn hash_mut(&mut self, key: &str) -> crate::Result<Option<&mut HashMap<String, Bytes>>> {
+        match self
+            .entries
+            .get_mut(key)
+            .filter(|entry| !entry.is_expired(Instant::now()))
+        {
+            Some(Entry {
+                data: Value::Hash(hash),
+                ..
+            }) => Ok(Some(hash)),
+            Some(_) => Err(WRONGTYPE.into()),
+            None => Ok(None),
+        }
+    }
+
+    /// Возвращает сортированное множество, хранящееся по ключу. Работает так
+    /// же, как `hash`.
+    fn zset(&self, key: &str) -> crate::Result<Option<&SortedSet>> {
+        match self
+            .entries
+            .get(key)
+            .filter(|entry| !entry.is_expired(Instant::now()))
+        {
+            Some(Entry {
+                data: Value::SortedSet(zset),
+                ..
+            }) => Ok(Some(zset)),
+            Some(_) => Err(WRONGTYPE.into()),
+            None => Ok(None),
+        }
+    }
+
+    /// Возвращает изменяемую ссылку на сортированное множество, хранящееся по
+    /// ключу. Работает так же, как `hash`.
+    fn zset_mut(&mut self, key: &str) -> crate::Result<Option<&mut SortedSet>> {
+        match self
+            .entries
+            .get_mut(key)
+            .filter(|entry| !entry.is_expired(Instant::now()))
+        {
+            Some(Entry {
+                data: Value::SortedSet(zset),
+                ..
+            }) => Ok(Some(zset)),
+            Some(_) => Err(WRONGTYPE.into()),
+            None => Ok(None),
+        }
+    }
+
+    /// Удаляет сущность по ключу вместе с ее временем жизни.
+    fn remove(&mut self, key: &str) -> Option<Entry> {
+        let prev = self.entries.remove(key)?;
+
+        if let Some(index) = &mut self.prefix_index {
+            index.remove(key);
+        }
+        self.scan_index
+            .remove(&(ring::hash(key.as_bytes()), key.to_string()));
+
+        // Удаляем время жизни, чтобы фоновая задача не хранила
+        // ссылку на удаленный ключ
+        if let Some(when) = prev.expires_at {
+            self.expirations.remove(&(when.instant, key.to_string()));
+        }
+
+        Some(prev)
+    }
+
+    /// Возвращает ключи, которые могут соответствовать шаблону `pattern`,
+    /// вместе с их сущностями.
+    ///
+    /// Если индекс префиксов включен, а шаблон начинается с префикса без
+    /// специальных символов, перебираются только ключи с этим префиксом,
+    /// иначе - все ключи.
+    fn candidates<'a>(
+        &'a self,
+        pattern: Option<&str>,
+    ) -> Box<dyn Iterator<Item = (&'a String, &'a Entry)> + 'a> {
+        let prefix = pattern
+            .and_then(|pattern| glob::literal_prefix(pattern.as_bytes()))
+            .filter(|prefix| !prefix.is_empty());
+
+        match (&self.prefix_index, prefix) {
+            (Some(index), Some(prefix)) => Box::new(
+                index
+                    .range(prefix.clone()..)
+                    .take_while(move |key| key.starts_with(prefix.as_str()))
+                    .filter_map(move |key| self.entries.get_key_value(key)),
+            ),
+            _ => Box::new(self.entries.iter()),
+        }
+    }
+
     fn next_expiration(&self) -> Option<Instant> {
         self.expirations
             .iter()
@@ -0,0 +1,281 @@
+//! Экспорт и импорт пространства ключей.
+//!
+//! Ключи записываются в формате JSON Lines: по одному объекту на строку с
+//! полями `key`, `value` и, если время жизни ключа ограничено, `ttl_ms`:
+//!
+//! ```text
+//! {"key":"foo","value":"bar"}
+//! {"key":"session","value":"data","ttl_ms":59000}
+//! {"key":"binary","value":[0,159,146,150]}
+//! ```
+//!
+//! Значение, являющееся строкой UTF-8, записывается как строка JSON, иначе -
+//! как массив байтов. Время жизни отсчитывается от момента импорта.
+//!
+//! Хеши и сортированные множества отмечаются полем `type`. Значением хеша
+//! является объект с полями и их значениями, а значением сортированного
+//! множества - объект с элементами и их оценками. Бесконечные оценки
+//! записываются как строки `inf` и `-inf`:
+//!
+//! ```text
+//! {"key":"user","type":"hash","value":{"name":"Alice"}}
+//! {"key":"board","type":"zset","value":{"alice":1.5,"bob":"inf"}}
+//! ```
+//!
+//! Данные экспортируются из встроенной БД (`export_db`, по снимку
+//! `Db::snapshot`) или с сервера через клиента (`export_client`, с помощью
+//! `SCAN`, `DUMP` и `PTTL`).
+
+use crate::serialize;
+use crate::snapshot::{SnapshotEntry, SnapshotValue};
+use crate::{Client, Db};
+
+use bytes::Bytes;
+use serde_json::{Map, Number, Value as Json};
+use std::convert::TryFrom;
+use std::io::{BufRead, Write};
+use std::time::Duration;
+
+/// Записывает сущность в `dst` в виде строки JSON Lines.
+pub fn write_entry<W: Write>(dst: &mut W, entry: &SnapshotEntry) -> crate::Result<()> {
+    let mut record = Map::new();
+    record.insert("key".to_string(), Json::from(entry.key.as_str()));
+
+    let value = match &entry.value {
+        SnapshotValue::String(value) => bytes_to_json(value),
+        SnapshotValue::Hash(fields) => {
+            record.insert("type".to_string(), Json::from("hash"));
+
+            let fields = fields
+                .iter()
+                .map(|(field, value)| (field.clone(), bytes_to_json(value)))
+                .collect();
+            Json::Object(fields)
+        }
+        SnapshotValue::SortedSet(members) => {
+            record.insert("type".to_string(), Json::from("zset"));
+
+            let members = members
+                .iter()
+                .map(|(member, score)| (member.clone(), score_to_json(*score)))
+                .collect();
+            Json::Object(members)
+        }
+    };
+    record.insert("value".to_string(), value);
+
+    if let Some(ttl) = entry.ttl {
+        // Время жизни меньше миллисекунды округляется вверх, чтобы ключ
+        // не стал бессрочным
+        let ms = u64::try_from(ttl.as_millis()).unwrap_or(u64::MAX).max(1);
+        record.insert("ttl_ms".to_string(), Json::from(ms));
+    }
+
+    serde_json::to_writer(&mut *dst, &record)?;
+    dst.write_all(b"\n")?;
+
+    Ok(())
+}
+
+/// Разбирает сущность из строки JSON Lines.
+pub fn read_entry(line: &str) -> crate::Result<SnapshotEntry> {
+    let mut record = match serde_json::from_str(line)? {
+        Json::Object(record) => record,
+        _ => return Err("Ожидается объект JSON".into()),
+    };
+
+    let key = match record.remove("key") {
+        Some(Json::String(key)) => key,
+        _ => return Err("Поле `key` должно быть строкой".into()),
+    };
+
+    let value = match record.remove("type") {
+        None => SnapshotValue::String(bytes_from_json(record.remove("value"))?),
+        Some(Json::String(kind)) if kind == "hash" => {
+            let fields = match record.remove("value") {
+                Some(Json::Object(fields)) if !fields.is_empty() => fields,
+                _ => return Err("Значение хеша должно быть непустым объектом".into()),
+            };
+
+            let fields = fields
+                .into_iter()
+                .map(|(field, value)| Ok((field, bytes_from_json(Some(value))?)))
+                .collect::<crate::Result<_>>()?;
+            SnapshotValue::Hash(fields)
+        }
+        Some(Json::String(kind)) if kind == "zset" => {
+            let members = match record.remove("value") {
+                Some(Json::Object(members)) if !members.is_empty() => members,
+                _ => {
+                    return Err(
+                        "Значение сортированного множества должно быть непустым объектом".into(),
+                    )
+                }
+            };
+
+            let members = members
+                .into_iter()
+                .map(|(member, score)| Ok((member, score_from_json(&score)?)))
+                .collect::<crate::Result<_>>()?;
+            SnapshotValue::SortedSet(members)
+        }
+        _ => return Err("Поле `type` должно быть `hash` или `zset`".into()),
+    };
+
+    let ttl = match record.remove("ttl_ms") {
+        Some(ttl) => Some(Duration::from_millis(
+            ttl.as_u64()
+                .ok_or("Поле `ttl_ms` должно быть целым числом")?,
+        )),
+        None => None,
+    };
+
+    Ok(SnapshotEntry::new(key, value, ttl))
+}
+
+/// Экспортирует все ключи встроенной БД в `dst`.
+///
+/// Возвращает количество экспортированных ключей.
+pub fn export_db<W: Write>(db: &Db, mut dst: W) -> crate::Result<u64> {
+    let mut exported = 0;
+
+    for entry in db.snapshot() {
+        write_entry(&mut dst, &entry)?;
+        exported += 1;
+    }
+
+    dst.flush()?;
+    Ok(exported)
+}
+
+/// Импортирует ключи из `src` во встроенную БД. Существующие ключи
+/// перезаписываются.
+///
+/// Возвращает количество импортированных ключей.
+pub fn import_db<R: BufRead>(db: &Db, src: R) -> crate::Result<u64> {
+    let mut imported = 0;
+
+    for entry in entries(src) {
+        let entry = entry?;
+        db.set_value(entry.key, entry.value.into_value(), entry.ttl);
+        imported += 1;
+    }
+
+    Ok(imported)
+}
+
+/// Экспортирует все ключи сервера в `dst` с помощью `SCAN`, `DUMP` и `PTTL`.
+///
+/// Ключи, удаленные или истекшие во время экспорта, пропускаются. Возвращает
+/// количество экспортированных ключей.
+pub async fn export_client<W: Write>(client: &mut Client, mut dst: W) -> crate::Result<u64> {
+    let mut exported = 0;
+    let mut cursor = 0;
+
+    loop {
+        let (next, keys) = client.scan_page(cursor, None, None).await?;
+
+        for key in keys {
+            let value = match client.dump(&key).await? {
+                Some(payload) => SnapshotValue::new(&serialize::restore(&payload)?),
+                None => continue,
+            };
+
+            // Время жизни запрашивается после значения: если ключ истек
+            // между запросами, он пропускается
+            if let Some(ttl) = client.ttl(&key).await? {
+                write_entry(&mut dst, &SnapshotEntry::new(key, value, ttl))?;
+                exported += 1;
+            }
+        }
+
+        if next == 0 {
+            break;
+        }
+        cursor = next;
+    }
+
+    dst.flush()?;
+    Ok(exported)
+}
+
+/// Импортирует ключи из `src` на сервер с помощью `SET`, а хеши и
+/// сортированные множества - с помощью `RESTORE`. Существующие ключи
+/// перезаписываются.
+///
+/// Возвращает количество импортированных ключей.
+pub async fn import_client<R: BufRead>(client: &mut Client, src: R) -> crate::Result<u64> {
+    let mut imported = 0;
+
+    for entry in entries(src) {
+        let entry = entry?;
+
+        match (entry.value, entry.ttl) {
+            (SnapshotValue::String(value), Some(ttl)) => {
+                client.set_expires(&entry.key, value, ttl).await?
+            }
+            (SnapshotValue::String(value), None) => client.set(&entry.key, value).await?,
+            (value, ttl) => {
+                let payload = serialize::dump(&value.into_value());
+                client.restore(&entry.key, ttl, payload, true).await?
+            }
+        }
+        imported += 1;
+    }
+
+    Ok(imported)
+}
+
+/// Возвращает итератор по сущностям, записанным в `src`. Пустые строки
+/// пропускаются, ошибка содержит номер строки.
+fn entries<R: BufRead>(src: R) -> impl Iterator<Item = crate::Result<SnapshotEntry>> {
+    src.lines()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, Ok(line) if line.trim().is_empty()))
+        .map(|(n, line)| {
+            read_entry(&line?).map_err(|err| format!("Строка {}: {}", n + 1, err).into())
+        })
+}
+
+/// Преобразует строку в строку JSON, если она является строкой UTF-8, иначе -
+/// в массив байтов.
+fn bytes_to_json(value: &Bytes) -> Json {
+    match std::str::from_utf8(value) {
+        Ok(string) => Json::from(string),
+        Err(_) => Json::from(value.to_vec()),
+    }
+}
+
+/// Разбирает строку, записанную `bytes_to_json`.
+fn bytes_from_json(value: Option<Json>) -> crate::Result<Bytes> {
+    match value {
+        Some(Json::String(value)) => Ok(Bytes::from(value)),
+        Some(Json::Array(bytes)) => Ok(bytes
+            .iter()
+            .map(|byte| byte.as_u64().and_then(|byte| u8::try_from(byte).ok()))
+            .collect::<Option<Vec<u8>>>()
+            .ok_or("Поле `value` должно содержать байты")?
+            .into()),
+        _ => Err("Поле `value` должно быть строкой или массивом байтов".into()),
+    }
+}
+
+/// Преобразует оценку в число JSON. Бесконечные оценки в JSON
+/// непредставимы и записываются как строки.
+fn score_to_json(score: f64) -> Json {
+    match Number::from_f64(score) {
+        Some(score) => Json::Number(score),
+        None if score > 0.0 => Json::from("inf"),
+        None => Json::from("-inf"),
+    }
+}
+
+/// Разбирает оценку, записанную `score_to_json`.
+fn score_from_json(score: &Json) -> crate::Result<f64> {
+    match score {
+        Json::Number(score) => score.as_f64().ok_or_else(|| "Невалидная оценка".into()),
+        Json::String(score) if score == "inf" || score == "+inf" => Ok(f64::INFINITY),
+        Json::String(score) if score == "-inf" => Ok(f64::NEG_INFINITY),
+        _ => Err("Оценка должна быть числом, `inf` или `-inf`".into()),
+    }
+}
@@ -1,7 +1,7 @@
 //! Предоставляет тип, представляющий кадр протокола `Redis`, а также
 //! утилиты для разбора кадров из массива байтов.
 
-use bytes::{Buf, Bytes};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
 use std::convert::TryInto;
 use std::fmt;
 use std::io::Cursor;
@@ -9,16 +9,20 @@ use std::num::TryFromIntError;
 use std::string::FromUtf8Error;
 
 /// Кадр протокола `Redis`.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Frame {
     Simple(String),
     Error(String),
-    Integer(u64),
+    Integer(i64),
     Bulk(Bytes),
     Null,
     Array(Vec<Frame>),
 }
 
+/// Максимальная вложенность массивов при разборе кадра. Ограничение
+/// защищает от переполнения стека при разборе вредоносных данных.
+const MAX_DEPTH: usize = 64;
+
 #[derive(Debug)]
 pub enum Error {
     /// Недостаточно данных для разбора сообщения.
@@ -53,7 +57,7 @@ impl Frame {
     /// # Паника
     ///
     /// Паникует, если `self` не является массивом.
-    pub(crate) fn push_int(&mut self, value: u64) {
+    pub(crate) fn push_int(&mut self, value: i64) {
         match self {
             Frame::Array(vec) => {
                 vec.push(Frame::Integer(value));
@@ -62,38 +66,112 @@ impl Frame {
         }
     }
 
+    /// Кодирует кадр в `dst`.
+    ///
+    /// Результат может быть разобран с помощью `check` и `parse`.
+    ///
+    /// # Примеры
+    ///
+    /// ```
+    /// use bytes::BytesMut;
+    /// use mini_redis::Frame;
+    /// use std::io::Cursor;
+    ///
+    /// let frame = Frame::Array(vec![Frame::Bulk("get".into()), Frame::Integer(1)]);
+    ///
+    /// let mut buf = BytesMut::new();
+    /// frame.encode(&mut buf);
+    /// assert_eq!(&buf[..], b"*2\r\n$3\r\nget\r\n:1\r\n");
+    ///
+    /// let parsed = Frame::parse(&mut Cursor::new(&buf[..])).unwrap();
+    /// assert_eq!(parsed.to_string(), frame.to_string());
+    /// ```
+    pub fn encode(&self, dst: &mut BytesMut) {
+        match self {
+            Frame::Simple(val) => {
+                dst.put_u8(b'+');
+                dst.put_slice(val.as_bytes());
+                dst.put_slice(b"\r\n");
+            }
+            Frame::Error(val) => {
+                dst.put_u8(b'-');
+                dst.put_slice(val.as_bytes());
+                dst.put_slice(b"\r\n");
+            }
+            Frame::Integer(val) => {
+                dst.put_u8(b':');
+                dst.put_slice(format!("{}\r\n", val).as_bytes());
+            }
+            Frame::Null => {
+                dst.put_slice(b"$-1\r\n");
+            }
+            Frame::Bulk(val) => {
+                dst.put_u8(b'$');
+                dst.put_slice(format!("{}\r\n", val.len()).as_bytes());
+                dst.put_slice(val);
+                dst.put_slice(b"\r\n");
+            }
+            Frame::Array(val) => {
+                dst.put_u8(b'*');
+                dst.put_slice(format!("{}\r\n", val.len()).as_bytes());
+
+                for entry in val {
+                    entry.encode(dst);
+                }
+            }
+        }
+    }
+
     /// Проверяет, что из `src` может быть декодировано целое сообщение
     pub fn check(src: &mut Cursor<&[u8]>) -> Result<(), Error> {
+        Frame::check_nested(src, 0, false)
+    }
+
+    /// Аналогична `check`, но строго проверяет формат сообщения.
+    ///
+    /// В отличие от `check`, отклоняются линии с `\r` или `\n` без пары,
+    /// заголовки длины с лишними символами и объемные строки без
+    /// завершающих `\r\n`. Сообщение об ошибке указывает на невалидные данные.
+    pub fn check_strict(src: &mut Cursor<&[u8]>) -> Result<(), Error> {
+        Frame::check_nested(src, 0, true)
+    }
+
+    fn check_nested(src: &mut Cursor<&[u8]>, depth: usize, strict: bool) -> Result<(), Error> {
         match get_u8(src)? {
             b'+' => {
-                get_line(src)?;
+                get_line(src, strict)?;
                 Ok(())
             }
             b'-' => {
-                get_line(src)?;
+                get_line(src, strict)?;
                 Ok(())
             }
             b':' => {
-                let _ = get_decimal(src)?;
+                let _ = get_integer(src, strict)?;
                 Ok(())
             }
             b'$' => {
                 if b'-' == peek_u8(src)? {
-                    // Пропускаем '-1\r\n'.
-                    skip(src, 4)
+                    if strict {
+                        get_null(src, strict)
+                    } else {
+                        // Пропускаем '-1\r\n'.
+                        skip(src, 4)
+                    }
                 } else {
                     // Читаем объемную (bulk) строку.
-                    let len: usize = get_decimal(src)?.try_into()?;
+                    let len: usize = get_decimal(src, strict)?.try_into()?;
 
                     // Пропускаем это число + 2 (\r\n) байта.
-                    skip(src, len + 2)
+                    skip_bulk(src, len, strict)
                 }
             }
             b'*' => {
-                let len = get_decimal(src)?;
+                check_depth(depth)?;
+                let len = get_decimal(src, strict)?;
 
                 for _ in 0..len {
-                    Frame::check(src)?;
+                    Frame::check_nested(src, depth + 1, strict)?;
                 }
 
                 Ok(())
@@ -102,12 +180,25 @@ impl Frame {
         }
     }
 
-    /// Сообщение было проверено с помощью `check`.
+    /// Разбирает сообщение.
+    ///
+    /// Обычно сообщение предварительно проверяется с помощью `check`,
+    /// но невалидные данные также приводят к ошибке, а не к панике.
     pub fn parse(src: &mut Cursor<&[u8]>) -> Result<Frame, Error> {
+        Frame::parse_nested(src, 0, false)
+    }
+
+    /// Аналогична `parse`, но строго проверяет формат сообщения
+    /// (см. `check_strict`).
+    pub fn parse_strict(src: &mut Cursor<&[u8]>) -> Result<Frame, Error> {
+        Frame::parse_nested(src, 0, true)
+    }
+
+    fn parse_nested(src: &mut Cursor<&[u8]>, depth: usize, strict: bool) -> Result<Frame, Error> {
         match get_u8(src)? {
             b'+' => {
                 // Читаем линию и преобразуем ее в `Vec<u8>`.
-                let line = get_line(src)?.to_vec();
+                let line = get_line(src, strict)?.to_vec();
 
                 // Преобразуем `Vec<u8>` в `String`.
                 let string = String::from_utf8(line)?;
@@ -116,7 +207,7 @@ impl Frame {
             }
             b'-' => {
                 // Читаем линию и преобразуем ее в `Vec<u8>`.
-                let line = get_line(src)?.to_vec();
+                let line = get_line(src, strict)?.to_vec();
 
                 // Преобразуем `Vec<u8>` в `String`.
                 let string = String::from_utf8(line)?;
@@ -124,46 +215,45 @@ impl Frame {
                 Ok(Frame::Error(string))
             }
             b':' => {
-                let len = get_decimal(src)?;
-                Ok(Frame::Integer(len))
+                let val = get_integer(src, strict)?;
+                Ok(Frame::Integer(val))
             }
             b'$' => {
                 if b'-' == peek_u8(src)? {
-                    let line = get_line(src)?;
-
-                    if line != b"-1" {
-                        return Err("Ошибка протокола; невалидный формат кадра.".into());
-                    }
-
+                    get_null(src, strict)?;
                     Ok(Frame::Null)
                 } else {
                     // Читаем объемную строку.
-                    let len = get_decimal(src)?.try_into()?;
-                    let n = len + 2;
+                    let len = get_decimal(src, strict)?.try_into()?;
 
-                    if src.remaining() < n {
+                    if src.remaining() < len {
                         return Err(Error::Incomplete);
                     }
 
                     let data = Bytes::copy_from_slice(&src.chunk()[..len]);
 
                     // Пропускаем это число + 2 (\r\n) байта.
-                    skip(src, n)?;
+                    skip_bulk(src, len, strict)?;
 
                     Ok(Frame::Bulk(data))
                 }
             }
             b'*' => {
-                let len = get_decimal(src)?.try_into()?;
-                let mut out = Vec::with_capacity(len);
+                check_depth(depth)?;
+                let len: usize = get_decimal(src, strict)?.try_into()?;
+
+                // Каждый элемент занимает минимум 3 байта, поэтому длина
+                // из заголовка не может превышать размер оставшихся данных.
+                // Это ограничивает выделение памяти для невалидных кадров
+                let mut out = Vec::with_capacity(len.min(src.remaining()));
 
                 for _ in 0..len {
-                    out.push(Frame::parse(src)?);
+                    out.push(Frame::parse_nested(src, depth + 1, strict)?);
                 }
 
                 Ok(Frame::Array(out))
             }
-            _ => unimplemented!(),
+            actual => Err(format!("Ошибка протокола; невалидный тип кадра `{}`.", actual).into()),
         }
     }
 
@@ -212,6 +302,49 @@ impl fmt::Display for Frame {
     }
 }
 
+/// Генерирует валидные кадры: простые строки и ошибки не содержат `\r` и
+/// `\n`, вложенность массивов ограничена.
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Frame {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Frame> {
+        arbitrary_frame(u, 0)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+fn arbitrary_frame(u: &mut arbitrary::Unstructured<'_>, depth: usize) -> arbitrary::Result<Frame> {
+    use arbitrary::Arbitrary;
+
+    // Массивы генерируются только до максимальной вложенности
+    let kinds = if depth + 1 < MAX_DEPTH { 6 } else { 5 };
+
+    let frame = match u.choose_index(kinds)? {
+        0 => Frame::Simple(arbitrary_line(u)?),
+        1 => Frame::Error(arbitrary_line(u)?),
+        2 => Frame::Integer(i64::arbitrary(u)?),
+        3 => Frame::Bulk(Bytes::from(Vec::<u8>::arbitrary(u)?)),
+        4 => Frame::Null,
+        _ => {
+            let mut entries = vec![];
+            u.arbitrary_loop(None, Some(16), |u| {
+                entries.push(arbitrary_frame(u, depth + 1)?);
+                Ok(std::ops::ControlFlow::Continue(()))
+            })?;
+            Frame::Array(entries)
+        }
+    };
+
+    Ok(frame)
+}
+
+/// Строка для простого кадра не может содержать разделитель линий.
+#[cfg(feature = "arbitrary")]
+fn arbitrary_line(u: &mut arbitrary::Unstructured<'_>) -> arbitrary::Result<String> {
+    use arbitrary::Arbitrary;
+
+    Ok(String::arbitrary(u)?.replace(['\r', '\n'], ""))
+}
+
 fn peek_u8(src: &mut Cursor<&[u8]>) -> Result<u8, Error> {
     if !src.has_remaining() {
         return Err(Error::Incomplete);
@@ -237,20 +370,97 @@ fn skip(src: &mut Cursor<&[u8]>, n: usize) -> Result<(), Error> {
     Ok(())
 }
 
-fn get_decimal(src: &mut Cursor<&[u8]>) -> Result<u64, Error> {
+/// Читает число. В строгом режиме линия должна состоять только из цифр.
+fn get_decimal(src: &mut Cursor<&[u8]>, strict: bool) -> Result<u64, Error> {
     use atoi::atoi;
 
-    let line = get_line(src)?;
+    let line = get_line(src, strict)?;
+
+    if strict && (line.is_empty() || !line.iter().all(u8::is_ascii_digit)) {
+        return Err(format!(
+            "Ошибка протокола; невалидное число `{}`.",
+            String::from_utf8_lossy(line)
+        )
+        .into());
+    }
 
     atoi::<u64>(line).ok_or_else(|| "Ошибка протокола; невалидный формат кадра.".into())
 }
 
+/// Читает целое число со знаком кадра `Integer`.
+fn get_integer(src: &mut Cursor<&[u8]>, strict: bool) -> Result<i64, Error> {
+    use atoi::atoi;
+
+    let line = get_line(src, strict)?;
+    let digits = line.strip_prefix(b"-").unwrap_or(line);
+
+    if strict && (digits.is_empty() || !digits.iter().all(u8::is_ascii_digit)) {
+        return Err(format!(
+            "Ошибка протокола; невалидное число `{}`.",
+            String::from_utf8_lossy(line)
+        )
+        .into());
+    }
+
+    atoi::<i64>(line).ok_or_else(|| "Ошибка протокола; невалидный формат кадра.".into())
+}
+
+/// Читает `-1\r\n` пустой объемной строки (`$-1`).
+fn get_null(src: &mut Cursor<&[u8]>, strict: bool) -> Result<(), Error> {
+    let line = get_line(src, strict)?;
+
+    if line != b"-1" {
+        return Err("Ошибка протокола; невалидный формат кадра.".into());
+    }
+
+    Ok(())
+}
+
+/// Пропускает данные объемной строки длиной `len` вместе с завершающими
+/// `\r\n`. В строгом режиме проверяет, что данные завершаются `\r\n`.
+fn skip_bulk(src: &mut Cursor<&[u8]>, len: usize, strict: bool) -> Result<(), Error> {
+    let n = bulk_frame_len(len)?;
+
+    if src.remaining() < n {
+        return Err(Error::Incomplete);
+    }
+
+    if strict && &src.chunk()[len..n] != b"\r\n" {
+        return Err(format!(
+            "Ошибка протокола; объемная строка длиной {} не завершается `\\r\\n` (позиция {}).",
+            len,
+            src.position() as usize + len
+        )
+        .into());
+    }
+
+    skip(src, n)
+}
+
+/// Возвращает размер объемной строки длиной `len` вместе с завершающими
+/// `\r\n`.
+fn bulk_frame_len(len: usize) -> Result<usize, Error> {
+    len.checked_add(2)
+        .ok_or_else(|| "Ошибка протокола; невалидный формат кадра.".into())
+}
+
+fn check_depth(depth: usize) -> Result<(), Error> {
+    if depth >= MAX_DEPTH {
+        return Err("Ошибка протокола; превышена вложенность массивов.".into());
+    }
+
+    Ok(())
+}
+
 /// Ищет линию.
-fn get_line<'a>(src: &mut Cursor<&'a [u8]>) -> Result<&'a [u8], Error> {
+///
+/// В строгом режиме `\r` и `\n` могут встречаться только в завершающей
+/// паре `\r\n`.
+fn get_line<'a>(src: &mut Cursor<&'a [u8]>, strict: bool) -> Result<&'a [u8], Error> {
     // Сканируем байты.
     let start = src.position() as usize;
-    // Сканируем до предпоследнего байта.
-    let end = src.get_ref().len() - 1;
+    // Сканируем до предпоследнего байта. Пустой буфер не содержит линии.
+    let end = src.get_ref().len().saturating_sub(1);
 
     for i in start..end {
         if src.get_ref()[i] == b'\r' && src.get_ref()[i + 1] == b'\n' {
@@ -260,11 +470,30 @@ fn get_line<'a>(src: &mut Cursor<&'a [u8]>) -> Result<&'a [u8], Error> {
             // Возвращаем линию.
             return Ok(&src.get_ref()[start..i]);
         }
+
+        if strict {
+            check_line_byte(src.get_ref()[i], i)?;
+        }
+    }
+
+    // Последний байт может быть `\r` неполной пары, но не `\n`
+    if strict && end >= start && src.get_ref().get(end) == Some(&b'\n') {
+        check_line_byte(b'\n', end)?;
     }
 
     Err(Error::Incomplete)
 }
 
+/// Проверяет, что байт линии в позиции `pos` не является `\r` или `\n`
+/// без пары.
+fn check_line_byte(byte: u8, pos: usize) -> Result<(), Error> {
+    match byte {
+        b'\r' => Err(format!("Ошибка протокола; `\\r` без `\\n` (позиция {}).", pos).into()),
+        b'\n' => Err(format!("Ошибка протокола; `\\n` без `\\r` (позиция {}).", pos).into()),
+        _ => Ok(()),
+    }
+}
+
 impl From<String> for Error {
     fn from(src: String) -> Error {
         Error::Other(src.into())
@@ -0,0 +1,148 @@
+//! Сопоставление строк с шаблонами в стиле glob, используемыми командами
+//! `KEYS` и `SCAN`.
+//!
+//! Поддерживаются следующие конструкции:
+//!
+//! * `?` - любой символ
+//! * `*` - любая последовательность символов, включая пустую
+//! * `[abc]`, `[a-z]` - любой символ из набора или диапазона
+//! * `[^abc]` - любой символ, кроме перечисленных
+//! * `\x` - символ `x` без специального значения
+
+/// Возвращает строку, с которой начинаются все строки, соответствующие
+/// шаблону `pattern`: часть шаблона до первого специального символа без
+/// экранирования.
+///
+/// Возвращает `None`, если префикс не является строкой UTF-8.
+pub(crate) fn literal_prefix(pattern: &[u8]) -> Option<String> {
+    let mut prefix = vec![];
+    let mut i = 0;
+
+    while let Some(&c) = pattern.get(i) {
+        match c {
+            b'*' | b'?' | b'[' => break,
+            b'\\' => match pattern.get(i + 1) {
+                Some(&escaped) => {
+                    prefix.push(escaped);
+                    i += 2;
+                }
+                None => break,
+            },
+            _ => {
+                prefix.push(c);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8(prefix).ok()
+}
+
+/// Возвращает `true`, если `string` соответствует шаблону `pattern`.
+///
+/// Сопоставление выполняется над байтами, поэтому `?` соответствует одному
+/// байту, а не символу Unicode.
+pub(crate) fn matches(pattern: &[u8], string: &[u8]) -> bool {
+    // Позиции в шаблоне и строке
+    let (mut p, mut s) = (0, 0);
+
+    // Позиция последней `*` в шаблоне и позиция в строке, с которой
+    // выполнялось сопоставление после нее. При несовпадении `*` "поглощает"
+    // еще один байт строки, и сопоставление повторяется
+    let mut star: Option<(usize, usize)> = None;
+
+    while s < string.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                star = Some((p, s));
+                p += 1;
+                continue;
+            }
+            Some(b'?') => {
+                p += 1;
+                s += 1;
+                continue;
+            }
+            Some(b'[') => match match_class(&pattern[p..], string[s]) {
+                Some((true, len)) => {
+                    p += len;
+                    s += 1;
+                    continue;
+                }
+                Some((false, _)) => {}
+                // Незакрытый класс сопоставляется как обычный символ `[`
+                None if string[s] == b'[' => {
+                    p += 1;
+                    s += 1;
+                    continue;
+                }
+                None => {}
+            },
+            Some(b'\\') if p + 1 < pattern.len() && pattern[p + 1] == string[s] => {
+                p += 2;
+                s += 1;
+                continue;
+            }
+            Some(&c) if c == string[s] => {
+                p += 1;
+                s += 1;
+                continue;
+            }
+            _ => {}
+        }
+
+        // Символы не совпали. Возвращаемся к последней `*`, если она была
+        match star {
+            Some((star_p, star_s)) => {
+                star = Some((star_p, star_s + 1));
+                p = star_p + 1;
+                s = star_s + 1;
+            }
+            None => return false,
+        }
+    }
+
+    // Строка закончилась. Оставшаяся часть шаблона может состоять только из `*`
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+/// Сопоставляет байт с классом символов `[...]` в начале `pattern`.
+///
+/// Возвращает результат сопоставления и длину класса в шаблоне. Если класс
+/// не закрыт, возвращается `None`, и `[` считается обычным символом
+fn match_class(pattern: &[u8], c: u8) -> Option<(bool, usize)> {
+    let mut i = 1;
+
+    let negate = pattern.get(i) == Some(&b'^');
+    if negate {
+        i += 1;
+    }
+
+    let mut matched = false;
+
+    loop {
+        match *pattern.get(i)? {
+            b']' => break,
+            b'\\' => {
+                i += 1;
+                matched |= *pattern.get(i)? == c;
+            }
+            start if pattern.get(i + 1) == Some(&b'-') && pattern.get(i + 2) != Some(&b']') => {
+                let end = *pattern.get(i + 2)?;
+                let (low, high) = if start <= end {
+                    (start, end)
+                } else {
+                    (end, start)
+                };
+
+                matched |= low <= c && c <= high;
+                i += 2;
+            }
+            other => matched |= other == c,
+        }
+
+        i += 1;
+    }
+
+    Some((matched != negate, i + 1))
+}
@@ -0,0 +1,192 @@
+//! HTTP-шлюз к `Db`.
+//!
+//! Доступен с флагом `http`. Позволяет работать с хранилищем инструментам,
+//! не поддерживающим протокол `Redis`, например, `curl` или вебхукам:
+//!
+//! * `GET /keys/{key}` - возвращает значение ключа или `404 Not Found`;
+//! * `PUT /keys/{key}[?ttl=ms]` - устанавливает значение из тела запроса,
+//!   опционально со временем жизни в миллисекундах, и возвращает
+//!   `204 No Content`;
+//! * `DELETE /keys/{key}` - удаляет ключ и возвращает `204 No Content` или
+//!   `404 Not Found`, если ключа не было;
+//! * `POST /publish/{channel}` - публикует тело запроса в канал и возвращает
+//!   количество подписчиков, получивших сообщение.
+//!
+//! Ключи и каналы в пути могут быть закодированы процентами (`%2F`).
+//!
+//! Шлюз работает с той же `Db`, что и сервер, поэтому значения, записанные
+//! через HTTP, доступны клиентам `Redis` и наоборот.
+
+use crate::Db;
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full, LengthLimitError, Limited};
+use hyper::body::Incoming;
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use std::convert::Infallible;
+use std::future::Future;
+use tokio::net::TcpListener;
+use tokio::task::JoinSet;
+use tokio::time::Duration;
+use tracing::debug;
+
+/// Максимальный размер тела запроса.
+const MAX_BODY_SIZE: usize = 64 * 1024 * 1024;
+
+/// Ресурс, к которому обращается запрос.
+enum Route {
+    /// Значение ключа.
+    Key(String),
+
+    /// Канал для публикации сообщений.
+    Channel(String),
+}
+
+/// Принимает соединения HTTP из `listener` и выполняет запросы над `db`.
+///
+/// Работает до завершения `shutdown`. После этого активные соединения
+/// закрываются.
+pub async fn run(listener: TcpListener, db: Db, shutdown: impl Future) -> crate::Result<()> {
+    let mut connections = JoinSet::new();
+
+    tokio::pin!(shutdown);
+
+    loop {
+        let (socket, addr) = tokio::select! {
+            res = listener.accept() => res?,
+            // Удаляем результаты завершенных задач
+            Some(_) = connections.join_next(), if !connections.is_empty() => continue,
+            _ = &mut shutdown => break,
+        };
+
+        debug!(%addr, "Новое соединение HTTP");
+
+        let db = db.clone();
+        connections.spawn(async move {
+            let service = service_fn(move |req| handle(db.clone(), req));
+
+            if let Err(err) = http1::Builder::new()
+                .serve_connection(TokioIo::new(socket), service)
+                .await
+            {
+                debug!(%addr, cause = %err, "Ошибка соединения HTTP");
+            }
+        });
+    }
+
+    connections.shutdown().await;
+
+    Ok(())
+}
+
+/// Выполняет запрос.
+async fn handle(db: Db, req: Request<Incoming>) -> Result<Response<Full<Bytes>>, Infallible> {
+    let route = match route(req.uri().path()) {
+        Some(route) => route,
+        None => return Ok(status(StatusCode::NOT_FOUND)),
+    };
+
+    let response = match (route, req.method().clone()) {
+        (Route::Key(key), Method::GET) => match db.get(&key) {
+            Some(value) => Response::new(Full::new(value)),
+            None => status(StatusCode::NOT_FOUND),
+        },
+        (Route::Key(key), Method::PUT) => {
+            let ttl = match ttl(req.uri().query()) {
+                Ok(ttl) => ttl,
+                Err(()) => return Ok(status(StatusCode::BAD_REQUEST)),
+            };
+
+            match read_body(req).await {
+                Ok(value) => {
+                    db.set(key, value, ttl);
+                    status(StatusCode::NO_CONTENT)
+                }
+                Err(response) => response,
+            }
+        }
+        (Route::Key(key), Method::DELETE) if db.del(&key) => status(StatusCode::NO_CONTENT),
+        (Route::Key(_), Method::DELETE) => status(StatusCode::NOT_FOUND),
+        (Route::Channel(channel), Method::POST) => match read_body(req).await {
+            Ok(message) => {
+                let receivers = db.publish(&channel, message);
+                Response::new(Full::new(Bytes::from(receivers.to_string())))
+            }
+            Err(response) => response,
+        },
+        _ => status(StatusCode::METHOD_NOT_ALLOWED),
+    };
+
+    Ok(response)
+}
+
+/// Определяет ресурс по пути запроса.
+fn route(path: &str) -> Option<Route> {
+    if let Some(key) = path.strip_prefix("/keys/") {
+        return percent_decode(key).map(Route::Key);
+    }
+
+    if let Some(channel) = path.strip_prefix("/publish/") {
+        return percent_decode(channel).map(Route::Channel);
+    }
+
+    None
+}
+
+/// Разбирает время жизни из параметра `ttl` строки запроса.
+fn ttl(query: Option<&str>) -> Result<Option<Duration>, ()> {
+    let value = query
+        .into_iter()
+        .flat_map(|query| query.split('&'))
+        .find_map(|param| param.strip_prefix("ttl="));
+
+    match value {
+        Some(ms) => match ms.parse() {
+            Ok(ms) if ms > 0 => Ok(Some(Duration::from_millis(ms))),
+            _ => Err(()),
+        },
+        None => Ok(None),
+    }
+}
+
+/// Читает тело запроса, ограничивая его размер.
+async fn read_body(req: Request<Incoming>) -> Result<Bytes, Response<Full<Bytes>>> {
+    match Limited::new(req.into_body(), MAX_BODY_SIZE).collect().await {
+        Ok(body) => Ok(body.to_bytes()),
+        Err(err) if err.is::<LengthLimitError>() => Err(status(StatusCode::PAYLOAD_TOO_LARGE)),
+        Err(_) => Err(status(StatusCode::BAD_REQUEST)),
+    }
+}
+
+/// Создает ответ с пустым телом.
+fn status(status: StatusCode) -> Response<Full<Bytes>> {
+    let mut response = Response::new(Full::default());
+    *response.status_mut() = status;
+    response
+}
+
+/// Декодирует строку, закодированную процентами. Возвращает `None` для
+/// пустой строки и невалидной кодировки.
+fn percent_decode(s: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut iter = s.bytes();
+
+    while let Some(byte) = iter.next() {
+        if byte == b'%' {
+            let hex = [iter.next()?, iter.next()?];
+            let hex = std::str::from_utf8(&hex).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+
+    if bytes.is_empty() {
+        return None;
+    }
+
+    String::from_utf8(bytes).ok()
+}
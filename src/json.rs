@@ -0,0 +1,85 @@
+//! Пути в документах JSON.
+//!
+//! Путь задается в формате JSON Pointer (RFC 6901), например, `/user/tags/0`.
+//! Корень документа обозначается пустой строкой или `$`. Символы `/` и `~` в
+//! названиях полей экранируются как `~1` и `~0` соответственно.
+
+use serde_json::Value as Json;
+
+/// Возвращает `true`, если `path` обозначает корень документа.
+pub(crate) fn is_root(path: &str) -> bool {
+    path.is_empty() || path == "$"
+}
+
+/// Возвращает значение по пути `path` или `None`, если путь не существует.
+pub(crate) fn get<'a>(doc: &'a Json, path: &str) -> Option<&'a Json> {
+    if is_root(path) {
+        return Some(doc);
+    }
+
+    doc.pointer(path)
+}
+
+/// Устанавливает `value` по пути `path`.
+///
+/// Путь должен указывать на существующее значение, новое поле существующего
+/// объекта или новый элемент в конце существующего массива (индекс, равный
+/// длине массива, или `-`). Возвращает `false`, если путь не существует.
+pub(crate) fn set(doc: &mut Json, path: &str, value: Json) -> bool {
+    if is_root(path) {
+        *doc = value;
+        return true;
+    }
+
+    if let Some(target) = doc.pointer_mut(path) {
+        *target = value;
+        return true;
+    }
+
+    let (parent, token) = match split(path) {
+        Some(split) => split,
+        None => return false,
+    };
+
+    match doc.pointer_mut(parent) {
+        Some(Json::Object(map)) => {
+            map.insert(token, value);
+            true
+        }
+        Some(Json::Array(vec)) if token == "-" || token == vec.len().to_string() => {
+            vec.push(value);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Удаляет значение по пути `path`, не являющемуся корнем.
+///
+/// Возвращает `false`, если путь не существует.
+pub(crate) fn delete(doc: &mut Json, path: &str) -> bool {
+    let (parent, token) = match split(path) {
+        Some(split) => split,
+        None => return false,
+    };
+
+    match doc.pointer_mut(parent) {
+        Some(Json::Object(map)) => map.remove(&token).is_some(),
+        Some(Json::Array(vec)) => match token.parse::<usize>() {
+            // Индексы с ведущими нулями не допускаются
+            Ok(idx) if idx < vec.len() && token == idx.to_string() => {
+                vec.remove(idx);
+                true
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Разделяет путь на путь родителя и название последнего поля без
+/// экранирования.
+fn split(path: &str) -> Option<(&str, String)> {
+    let (parent, token) = path.rsplit_once('/')?;
+    Some((parent, token.replace("~1", "/").replace("~0", "~")))
+}
@@ -12,22 +12,37 @@
 //! Основными компонентами являются:
 //!
 //! * `server` - реализация сервера `Redis`. Включает одну функцию `run`,
-//! принимающую `TcpListener` и обрабатывающую подключения клиента `Redis`.
+//!   принимающую `TcpListener` и обрабатывающую подключения клиента `Redis`.
 //!
 //! * `clients/client` - реализация асинхронного клиента `Redis`. Показывает,
-//! как разрабатывать клиенты с помощью `Tokio`.
+//!   как разрабатывать клиенты с помощью `Tokio`.
 //!
 //! * `cmd` - реализации поддерживаемых команд `Redis`.
 //!
+//! * `Db` - хранилище "ключ-значение" с временем жизни значений и pub/sub.
+//!   Может использоваться без сервера как встроенный кэш.
+//!
 //! * `frame` - представляет кадр протокола `Redis`. Кадр используется как
-//! промежуточное представление между "командой" и ее байтовым представлением.
+//!   промежуточное представление между "командой" и ее байтовым представлением.
+//!
+//! * `proxy` - прокси для записи сессий клиентов и их воспроизведения.
+
+pub mod audit;
+
+#[cfg(feature = "chaos")]
+pub mod chaos;
 
 pub mod clients;
-pub use clients::{BlockingClient, BufferedClient, Client};
+pub use clients::{
+    BlockingClient, BlockingRedisClient, BufferedClient, Client, RedisClient, ShardedClient,
+};
 
 pub mod cmd;
 pub use cmd::Command;
 
+#[cfg(feature = "codec")]
+pub mod codec;
+
 mod connection;
 pub use connection::Connection;
 
@@ -35,17 +50,59 @@ pub mod frame;
 pub use frame::Frame;
 
 mod db;
-use db::Db;
-use db::DbDropGuard;
+pub use db::{Db, DbDropGuard};
+
+#[cfg(feature = "json")]
+pub mod export;
+
+mod glob;
+
+#[cfg(feature = "http")]
+pub mod http;
+
+#[cfg(feature = "json")]
+mod json;
+
+pub mod log_filter;
+
+pub mod memory;
+
+mod metrics;
 
 mod parse;
 use parse::{Parse, ParseError};
 
+pub mod proxy;
+
+mod ring;
+
+mod serialize;
+
 pub mod server;
 
+mod session;
+use session::Session;
+
 mod shutdown;
 use shutdown::Shutdown;
 
+pub mod snapshot;
+
+pub mod throttle;
+
+#[cfg(feature = "test-util")]
+pub mod test_util;
+
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+pub mod uring;
+
+mod value;
+
+#[cfg(feature = "websocket")]
+pub mod websocket;
+
+mod zset;
+
 /// Порт по умолчанию.
 pub const DEFAULT_PORT: u16 = 6379;
 
@@ -0,0 +1,99 @@
+//! Изменение уровня логирования во время работы сервера.
+//!
+//! `LogFilter` оборачивает обработчик перезагрузки (`reload::Handle`) фильтра
+//! `EnvFilter`. Фильтр передается серверу через `Builder::log_filter`, после
+//! чего уровень логирования изменяется командой `CONFIG SET loglevel`, например,
+//! для включения подробных логов без перезапуска сервера:
+//!
+//! ```text
+//! CONFIG SET loglevel "mini_redis=debug"
+//! ```
+//!
+//! # Примеры
+//!
+//! ```no_run
+//! use mini_redis::log_filter::LogFilter;
+//! use mini_redis::server::Builder;
+//! use tracing_subscriber::prelude::*;
+//! use tracing_subscriber::{fmt, reload, EnvFilter};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let (filter, handle) = reload::Layer::new(EnvFilter::new("info"));
+//!     tracing_subscriber::registry()
+//!         .with(filter)
+//!         .with(fmt::layer())
+//!         .init();
+//!
+//!     let server = Builder::new()
+//!         .log_filter(LogFilter::new(handle))
+//!         .bind("127.0.0.1:6379")
+//!         .await
+//!         .unwrap();
+//! #   drop(server);
+//! }
+//! ```
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use tracing_subscriber::{reload, EnvFilter};
+
+/// Функция, заменяющая фильтр подписчика.
+type Reload = dyn Fn(EnvFilter) -> Result<(), reload::Error> + Send + Sync;
+
+/// Фильтр логов, изменяемый во время работы сервера.
+///
+/// Клоны `LogFilter` управляют одним и тем же фильтром.
+#[derive(Clone)]
+pub struct LogFilter {
+    /// Заменяет фильтр подписчика.
+    reload: Arc<Reload>,
+
+    /// Директивы текущего фильтра.
+    directives: Arc<Mutex<String>>,
+}
+
+impl LogFilter {
+    /// Создает `LogFilter`, управляющий фильтром, установленным слоем
+    /// `reload::Layer`.
+    pub fn new<S: 'static>(handle: reload::Handle<EnvFilter, S>) -> LogFilter {
+        let directives = handle
+            .with_current(|filter| filter.to_string())
+            .unwrap_or_default();
+
+        LogFilter {
+            reload: Arc::new(move |filter| handle.reload(filter)),
+            directives: Arc::new(Mutex::new(directives)),
+        }
+    }
+
+    /// Заменяет фильтр фильтром, разобранным из `directives` в формате
+    /// `RUST_LOG`, например, `debug` или `mini_redis=trace,info`.
+    ///
+    /// Возвращает `Err`, если директивы невалидны или подписчик уже уничтожен.
+    /// В этом случае текущий фильтр не изменяется.
+    pub fn set(&self, directives: &str) -> crate::Result<()> {
+        let filter = EnvFilter::try_new(directives)?;
+
+        // Блокировка удерживается во время замены, чтобы директивы
+        // соответствовали установленному фильтру при конкурентных вызовах
+        let mut current = self.directives.lock().unwrap();
+        (self.reload)(filter)?;
+        *current = directives.to_string();
+
+        Ok(())
+    }
+
+    /// Возвращает директивы текущего фильтра.
+    pub fn directives(&self) -> String {
+        self.directives.lock().unwrap().clone()
+    }
+}
+
+impl fmt::Debug for LogFilter {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("LogFilter")
+            .field("directives", &self.directives())
+            .finish()
+    }
+}
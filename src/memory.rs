@@ -0,0 +1,198 @@
+//! Статистика использования памяти.
+//!
+//! Используется командами `MEMORY STATS` и `MEMORY DOCTOR`, а также доступна
+//! приложениям, встраивающим `Db`, через `Db::memory_stats`.
+//!
+//! Размер данных и накладные расходы вычисляются по содержимому `Db` и
+//! являются оценкой: фактический размер выделенной памяти зависит от
+//! аллокатора. Размер резидентной памяти процесса (RSS) и его пиковое значение
+//! читаются из `/proc/self/status` и доступны только в Linux.
+//!
+//! С флагом `jemalloc` сервер использует аллокатор `jemalloc`, а статистика
+//! дополняется данными аллокатора: объемом выделенной, активной и резидентной
+//! памяти. Данные аллокатора корректны, только если `jemalloc` установлен
+//! глобальным аллокатором (`#[global_allocator]`), как это сделано в
+//! `mini-redis-server`.
+
+use std::fmt::Write;
+
+/// Минимальный объем памяти процесса, при котором `MEMORY DOCTOR`
+/// анализирует фрагментацию. Для маленьких процессов доля памяти
+/// исполняемого файла и библиотек искажает коэффициент.
+const DOCTOR_MIN_RSS: u64 = 64 * 1024 * 1024;
+
+/// Снимок статистики использования памяти.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct MemoryStats {
+    /// Количество ключей.
+    pub keys: u64,
+
+    /// Размер ключей и значений в байтах.
+    pub dataset_bytes: u64,
+
+    /// Накладные расходы хранилища в байтах: хеш-таблицы ключей и каналов,
+    /// индекс времен жизни.
+    pub overhead_bytes: u64,
+
+    /// Размер резидентной памяти процесса в байтах.
+    pub rss_bytes: Option<u64>,
+
+    /// Пиковый размер резидентной памяти процесса в байтах.
+    pub peak_rss_bytes: Option<u64>,
+
+    /// Статистика аллокатора. Доступна с флагом `jemalloc`.
+    pub allocator: Option<AllocatorStats>,
+}
+
+/// Статистика аллокатора.
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub struct AllocatorStats {
+    /// Память, выделенная приложением, в байтах.
+    pub allocated: u64,
+
+    /// Память в активных страницах аллокатора в байтах.
+    pub active: u64,
+
+    /// Резидентная память аллокатора в байтах.
+    pub resident: u64,
+}
+
+impl MemoryStats {
+    /// Создает статистику по размеру данных хранилища, дополняя ее данными
+    /// процесса и аллокатора.
+    pub(crate) fn new(keys: u64, dataset_bytes: u64, overhead_bytes: u64) -> MemoryStats {
+        let (rss_bytes, peak_rss_bytes) = process_rss();
+
+        MemoryStats {
+            keys,
+            dataset_bytes,
+            overhead_bytes,
+            rss_bytes,
+            peak_rss_bytes,
+            allocator: allocator_stats(),
+        }
+    }
+
+    /// Возвращает коэффициент фрагментации: отношение резидентной памяти к
+    /// используемой.
+    ///
+    /// С данными аллокатора используемой памятью считается выделенная
+    /// аллокатором, иначе - размер данных с накладными расходами. Во втором
+    /// случае коэффициент также учитывает память, не связанную с хранилищем,
+    /// и является грубой оценкой.
+    pub fn fragmentation(&self) -> Option<f64> {
+        let (resident, used) = match self.allocator {
+            Some(allocator) => (allocator.resident, allocator.allocated),
+            None => (self.rss_bytes?, self.dataset_bytes + self.overhead_bytes),
+        };
+
+        if used == 0 {
+            return None;
+        }
+
+        Some(resident as f64 / used as f64)
+    }
+
+    /// Возвращает отчет о проблемах использования памяти в виде текста,
+    /// как его возвращает `MEMORY DOCTOR`.
+    pub fn doctor(&self) -> String {
+        if self.keys == 0 {
+            return "The instance is empty, there is nothing to analyze.".to_string();
+        }
+
+        let mut report = String::new();
+
+        if let (Some(rss), Some(fragmentation)) = (self.rss_bytes, self.fragmentation()) {
+            if rss >= DOCTOR_MIN_RSS && fragmentation > 1.4 {
+                let _ = writeln!(
+                    report,
+                    "* High fragmentation: resident memory is {:.2} times the used memory. \
+                     Restarting the server may reclaim it.",
+                    fragmentation
+                );
+            }
+        }
+
+        if self.overhead_bytes > self.dataset_bytes {
+            let _ = writeln!(
+                report,
+                "* High overhead: storage overhead ({} bytes) exceeds the dataset size \
+                 ({} bytes). Many small keys are stored.",
+                self.overhead_bytes, self.dataset_bytes
+            );
+        }
+
+        if let (Some(rss), Some(peak)) = (self.rss_bytes, self.peak_rss_bytes) {
+            if peak >= DOCTOR_MIN_RSS && peak > rss + rss / 2 {
+                let _ = writeln!(
+                    report,
+                    "* High peak: peak resident memory ({} bytes) is more than 150% of the \
+                     current resident memory ({} bytes).",
+                    peak, rss
+                );
+            }
+        }
+
+        if report.is_empty() {
+            report.push_str("No memory issues detected.");
+        } else {
+            report.insert_str(0, "Memory issues detected:\n");
+        }
+
+        report
+    }
+}
+
+/// Читает текущий и пиковый размер резидентной памяти процесса.
+#[cfg(target_os = "linux")]
+fn process_rss() -> (Option<u64>, Option<u64>) {
+    let status = match std::fs::read_to_string("/proc/self/status") {
+        Ok(status) => status,
+        Err(_) => return (None, None),
+    };
+
+    // Значения указываются в килобайтах, например, `VmRSS:   10240 kB`
+    let field = |name: &str| {
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix(name))
+            .and_then(|value| {
+                value
+                    .trim()
+                    .trim_end_matches("kB")
+                    .trim()
+                    .parse::<u64>()
+                    .ok()
+            })
+            .map(|kb| kb * 1024)
+    };
+
+    (field("VmRSS:"), field("VmHWM:"))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn process_rss() -> (Option<u64>, Option<u64>) {
+    (None, None)
+}
+
+/// Читает статистику `jemalloc`.
+#[cfg(feature = "jemalloc")]
+fn allocator_stats() -> Option<AllocatorStats> {
+    use tikv_jemalloc_ctl::{epoch, stats};
+
+    // Статистика кэшируется аллокатором и обновляется при смене эпохи
+    epoch::advance().ok()?;
+
+    Some(AllocatorStats {
+        allocated: stats::allocated::read().ok()? as u64,
+        active: stats::active::read().ok()? as u64,
+        resident: stats::resident::read().ok()? as u64,
+    })
+}
+
+#[cfg(not(feature = "jemalloc"))]
+fn allocator_stats() -> Option<AllocatorStats> {
+    None
+}
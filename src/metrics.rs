@@ -0,0 +1,203 @@
+//! Метрики сервера.
+//!
+//! С флагом `otel` метрики записываются с помощью глобального `MeterProvider`
+//! `OpenTelemetry`, который настраивается приложением (см. `src/bin/server.rs`).
+//!
+//! С флагом `metrics` метрики записываются через фасад [`metrics`], поэтому их
+//! получает любой экспортер (recorder), установленный приложением. Флаги
+//! могут использоваться вместе. Без флагов функции этого модуля ничего не
+//! делают.
+//!
+//! [`metrics`]: https://docs.rs/metrics
+//!
+//! Записываются следующие метрики:
+//!
+//! * `mini_redis.commands` - количество выполненных команд с атрибутами
+//!   `command` и `status` (`ok` или `error`).
+//! * `mini_redis.command.errors` - количество команд, завершившихся ошибкой,
+//!   с атрибутом `command`. Записывается только с флагом `metrics`, в
+//!   `OpenTelemetry` ошибки отличаются атрибутом `status`.
+//! * `mini_redis.command.duration` - время выполнения команды в секундах
+//!   с атрибутом `command`.
+//! * `mini_redis.connections.accepted` - количество принятых соединений.
+//! * `mini_redis.connections.active` - количество активных соединений.
+//! * `mini_redis.connections.queued` - количество соединений, ожидающих
+//!   обработки.
+
+use std::time::Duration;
+
+/// Записывает выполнение команды `name`.
+#[cfg_attr(
+    not(any(feature = "otel", feature = "metrics")),
+    allow(unused_variables)
+)]
+pub(crate) fn command_executed(name: &str, elapsed: Duration, ok: bool) {
+    #[cfg(feature = "otel")]
+    otel::command_executed(name, elapsed, ok);
+    #[cfg(feature = "metrics")]
+    facade::command_executed(name, elapsed, ok);
+}
+
+/// Записывает установку соединения.
+pub(crate) fn connection_opened() {
+    #[cfg(feature = "otel")]
+    otel::connection_opened();
+    #[cfg(feature = "metrics")]
+    facade::connection_opened();
+}
+
+/// Записывает закрытие соединения.
+pub(crate) fn connection_closed() {
+    #[cfg(feature = "otel")]
+    otel::connection_closed();
+    #[cfg(feature = "metrics")]
+    facade::connection_closed();
+}
+
+/// Записывает постановку соединения в очередь ожидания.
+pub(crate) fn connection_queued() {
+    #[cfg(feature = "otel")]
+    otel::connection_queued();
+    #[cfg(feature = "metrics")]
+    facade::connection_queued();
+}
+
+/// Записывает удаление соединения из очереди ожидания.
+pub(crate) fn connection_dequeued() {
+    #[cfg(feature = "otel")]
+    otel::connection_dequeued();
+    #[cfg(feature = "metrics")]
+    facade::connection_dequeued();
+}
+
+#[cfg(feature = "otel")]
+mod otel {
+    use super::Duration;
+
+    use opentelemetry::metrics::{Counter, Histogram, Unit, UpDownCounter};
+    use opentelemetry::{global, KeyValue};
+    use std::sync::OnceLock;
+
+    /// Инструменты для записи метрик.
+    struct Instruments {
+        commands: Counter<u64>,
+        command_duration: Histogram<f64>,
+        connections_accepted: Counter<u64>,
+        connections_active: UpDownCounter<i64>,
+        connections_queued: UpDownCounter<i64>,
+    }
+
+    /// Возвращает инструменты, создавая их при первом вызове.
+    ///
+    /// Инструменты привязываются к глобальному `MeterProvider`, поэтому
+    /// он должен быть настроен до запуска сервера.
+    fn instruments() -> &'static Instruments {
+        static INSTRUMENTS: OnceLock<Instruments> = OnceLock::new();
+
+        INSTRUMENTS.get_or_init(|| {
+            let meter = global::meter("mini-redis");
+
+            Instruments {
+                commands: meter
+                    .u64_counter("mini_redis.commands")
+                    .with_description("Количество выполненных команд")
+                    .init(),
+                command_duration: meter
+                    .f64_histogram("mini_redis.command.duration")
+                    .with_description("Время выполнения команды")
+                    .with_unit(Unit::new("s"))
+                    .init(),
+                connections_accepted: meter
+                    .u64_counter("mini_redis.connections.accepted")
+                    .with_description("Количество принятых соединений")
+                    .init(),
+                connections_active: meter
+                    .i64_up_down_counter("mini_redis.connections.active")
+                    .with_description("Количество активных соединений")
+                    .init(),
+                connections_queued: meter
+                    .i64_up_down_counter("mini_redis.connections.queued")
+                    .with_description("Количество соединений, ожидающих обработки")
+                    .init(),
+            }
+        })
+    }
+
+    /// Записывает выполнение команды `name`.
+    pub(crate) fn command_executed(name: &str, elapsed: Duration, ok: bool) {
+        let instruments = instruments();
+        let command = KeyValue::new("command", name.to_string());
+        let status = KeyValue::new("status", if ok { "ok" } else { "error" });
+
+        instruments.commands.add(1, &[command.clone(), status]);
+        instruments
+            .command_duration
+            .record(elapsed.as_secs_f64(), &[command]);
+    }
+
+    /// Записывает установку соединения.
+    pub(crate) fn connection_opened() {
+        let instruments = instruments();
+
+        instruments.connections_accepted.add(1, &[]);
+        instruments.connections_active.add(1, &[]);
+    }
+
+    /// Записывает закрытие соединения.
+    pub(crate) fn connection_closed() {
+        instruments().connections_active.add(-1, &[]);
+    }
+
+    /// Записывает постановку соединения в очередь ожидания.
+    pub(crate) fn connection_queued() {
+        instruments().connections_queued.add(1, &[]);
+    }
+
+    /// Записывает удаление соединения из очереди ожидания.
+    pub(crate) fn connection_dequeued() {
+        instruments().connections_queued.add(-1, &[]);
+    }
+}
+
+#[cfg(feature = "metrics")]
+mod facade {
+    use super::Duration;
+
+    // Абсолютный путь отличает крейт `metrics` от этого модуля
+    use ::metrics::{counter, gauge, histogram};
+
+    /// Записывает выполнение команды `name`.
+    pub(crate) fn command_executed(name: &str, elapsed: Duration, ok: bool) {
+        let command = name.to_string();
+        let status = if ok { "ok" } else { "error" };
+
+        counter!("mini_redis.commands", "command" => command.clone(), "status" => status)
+            .increment(1);
+        if !ok {
+            counter!("mini_redis.command.errors", "command" => command.clone()).increment(1);
+        }
+        histogram!("mini_redis.command.duration", "command" => command)
+            .record(elapsed.as_secs_f64());
+    }
+
+    /// Записывает установку соединения.
+    pub(crate) fn connection_opened() {
+        counter!("mini_redis.connections.accepted").increment(1);
+        gauge!("mini_redis.connections.active").increment(1.0);
+    }
+
+    /// Записывает закрытие соединения.
+    pub(crate) fn connection_closed() {
+        gauge!("mini_redis.connections.active").decrement(1.0);
+    }
+
+    /// Записывает постановку соединения в очередь ожидания.
+    pub(crate) fn connection_queued() {
+        gauge!("mini_redis.connections.queued").increment(1.0);
+    }
+
+    /// Записывает удаление соединения из очереди ожидания.
+    pub(crate) fn connection_dequeued() {
+        gauge!("mini_redis.connections.queued").decrement(1.0);
+    }
+}
@@ -1,6 +1,7 @@
 use crate::Frame;
 
 use bytes::Bytes;
+use std::convert::TryFrom;
 use std::{fmt, str, vec};
 
 /// Утилита для разбора команды.
@@ -105,7 +106,7 @@ impl Parse {
 
         match self.next()? {
             // Кадр `Integer` хранится в виде целого числа.
-            Frame::Integer(v) => Ok(v),
+            Frame::Integer(v) => u64::try_from(v).map_err(|_| MSG.into()),
             // Кадры `Simple` и `Bulk` должны быть разобраны как целые числа. Если разбор
             // проваливается, возвращается ошибка.
             Frame::Simple(data) => atoi::<u64>(data.as_bytes()).ok_or_else(|| MSG.into()),
@@ -118,6 +119,57 @@ impl Parse {
         }
     }
 
+    /// Возвращает следующий кадр как целое число со знаком.
+    ///
+    /// Разбирается так же, как `next_int`, но допускает отрицательные
+    /// значения.
+    pub(crate) fn next_signed_int(&mut self) -> Result<i64, ParseError> {
+        use atoi::atoi;
+
+        const MSG: &str = "Ошибка протокола; невалидное число";
+
+        match self.next()? {
+            Frame::Integer(v) => Ok(v),
+            Frame::Simple(data) => atoi::<i64>(data.as_bytes()).ok_or_else(|| MSG.into()),
+            Frame::Bulk(data) => atoi::<i64>(&data).ok_or_else(|| MSG.into()),
+            frame => Err(format!(
+                "Ошибка протокола; ожидается кадр `int`, получено {:?}",
+                frame
+            )
+            .into()),
+        }
+    }
+
+    /// Возвращает следующий кадр как число с плавающей точкой.
+    ///
+    /// Разбирается так же, как `next_signed_int`. Значение `NaN` считается
+    /// невалидным.
+    pub(crate) fn next_float(&mut self) -> Result<f64, ParseError> {
+        const MSG: &str = "Ошибка протокола; невалидное число с плавающей точкой";
+
+        let value = match self.next()? {
+            Frame::Integer(v) => v as f64,
+            Frame::Simple(data) => data.parse().map_err(|_| MSG)?,
+            Frame::Bulk(data) => str::from_utf8(&data)
+                .ok()
+                .and_then(|data| data.parse().ok())
+                .ok_or(MSG)?,
+            frame => {
+                return Err(format!(
+                    "Ошибка протокола; ожидается кадр `float`, получено {:?}",
+                    frame
+                )
+                .into())
+            }
+        };
+
+        if value.is_nan() {
+            return Err(MSG.into());
+        }
+
+        Ok(value)
+    }
+
     /// Проверяет отсутствие сущностей в массиве.
     pub(crate) fn finish(&mut self) -> Result<(), ParseError> {
         if self.parts.next().is_none() {
@@ -0,0 +1,614 @@
+//! Прокси протокола `Redis`: запись и воспроизведение сессий, шардирование.
+//!
+//! Функция `record` принимает соединения клиентов, прозрачно передает данные
+//! серверу и обратно, а каждый декодированный кадр записывает вместе с
+//! временем его получения. Функция `replay` воспроизводит записанную сессию
+//! на сервере и сравнивает ответы сервера с записанными.
+//!
+//! Запись полезна для воспроизведения сообщений об ошибках и для
+//! детерминированного нагрузочного тестирования.
+//!
+//! Функция `shard` распределяет ключи между несколькими серверами с помощью
+//! согласованного хеширования (consistent hashing): каждая команда
+//! передается серверу, которому принадлежит ее ключ. При добавлении или
+//! удалении сервера перемещается лишь небольшая доля ключей.
+//!
+//! # Формат записи
+//!
+//! Запись - это последовательность кадров `Redis`. Каждый кадр - массив из
+//! четырех элементов: время с начала записи в микросекундах (`Integer`),
+//! номер соединения (`Integer`), направление (`Simple`: `>` - от клиента к
+//! серверу, `<` - от сервера к клиенту) и сам кадр.
+
+use crate::ring::Ring;
+use crate::{Connection, Frame};
+
+use bytes::{Buf, Bytes, BytesMut};
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::future::Future;
+use std::io::Cursor;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufWriter};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{lookup_host, TcpListener, TcpStream, ToSocketAddrs};
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+use tokio::time::{self, Duration, Instant};
+use tracing::{debug, error, info, warn};
+
+/// Время ожидания ответа сервера при воспроизведении.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Команды, которые не могут быть выполнены на одном сервере: они
+/// обращаются ко всем ключам или не имеют ключа. Команды, ключ которых не
+/// является первым аргументом, также не поддерживаются.
+const UNSHARDED_COMMANDS: &[&str] = &[
+    "config",
+    "debug",
+    "hello",
+    "keys",
+    "memory",
+    "object",
+    "publish",
+    "scan",
+    "subscribe",
+    "unsubscribe",
+];
+
+/// Команды с несколькими ключами и шаг между ключами в списке аргументов
+/// (`MSET` чередует ключи и значения). Такие команды выполняются, только
+/// если все ключи находятся на одном сервере.
+const MULTI_KEY_COMMANDS: &[(&str, usize)] =
+    &[("exists", 1), ("mget", 1), ("mset", 2), ("msetnx", 2)];
+
+/// Направление передачи кадра.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// Запрос клиента.
+    ToServer,
+
+    /// Ответ или сообщение сервера.
+    ToClient,
+}
+
+/// Записанный кадр.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Record {
+    /// Время получения кадра с начала записи.
+    pub elapsed: Duration,
+
+    /// Номер соединения. Соединения нумеруются в порядке подключения,
+    /// начиная с нуля.
+    pub connection: u64,
+
+    /// Направление передачи кадра.
+    pub direction: Direction,
+
+    /// Кадр.
+    pub frame: Frame,
+}
+
+/// Итоги воспроизведения сессии.
+#[derive(Debug, Default)]
+pub struct ReplayReport {
+    /// Количество воспроизведенных соединений.
+    pub connections: usize,
+
+    /// Количество отправленных запросов.
+    pub requests: usize,
+
+    /// Количество ответов, совпавших с записанными.
+    pub matched: usize,
+
+    /// Количество ответов, отличающихся от записанных или не полученных.
+    pub mismatched: usize,
+
+    /// Длительность воспроизведения.
+    pub elapsed: Duration,
+}
+
+impl Record {
+    /// Преобразует запись в кадр для сохранения.
+    pub fn into_frame(self) -> Frame {
+        let direction = match self.direction {
+            Direction::ToServer => ">",
+            Direction::ToClient => "<",
+        };
+
+        Frame::Array(vec![
+            Frame::Integer(i64::try_from(self.elapsed.as_micros()).unwrap_or(i64::MAX)),
+            Frame::Integer(self.connection as i64),
+            Frame::Simple(direction.to_string()),
+            self.frame,
+        ])
+    }
+
+    /// Разбирает запись из сохраненного кадра.
+    pub fn from_frame(frame: Frame) -> crate::Result<Record> {
+        let invalid = || "Ошибка протокола; невалидный формат записи.";
+
+        let mut parts = match frame {
+            Frame::Array(parts) if parts.len() == 4 => parts.into_iter(),
+            frame => return Err(frame.to_error()),
+        };
+
+        let elapsed = match parts.next() {
+            Some(Frame::Integer(micros)) if micros >= 0 => Duration::from_micros(micros as u64),
+            _ => return Err(invalid().into()),
+        };
+
+        let connection = match parts.next() {
+            Some(Frame::Integer(connection)) if connection >= 0 => connection as u64,
+            _ => return Err(invalid().into()),
+        };
+
+        let direction = match parts.next() {
+            Some(Frame::Simple(direction)) if direction == ">" => Direction::ToServer,
+            Some(Frame::Simple(direction)) if direction == "<" => Direction::ToClient,
+            _ => return Err(invalid().into()),
+        };
+
+        let frame = parts.next().ok_or_else(invalid)?;
+
+        Ok(Record {
+            elapsed,
+            connection,
+            direction,
+            frame,
+        })
+    }
+}
+
+/// Принимает соединения из `listener` и проксирует их на сервер `upstream`,
+/// записывая все кадры в `out`.
+///
+/// Работает до завершения `shutdown`. После этого активные соединения
+/// закрываются, а оставшиеся записи сохраняются в `out`.
+pub async fn record<W>(
+    listener: TcpListener,
+    upstream: SocketAddr,
+    out: W,
+    shutdown: impl Future,
+) -> crate::Result<()>
+where
+    W: AsyncWrite + Send + Unpin + 'static,
+{
+    let (records_tx, records_rx) = mpsc::channel(1024);
+    let writer = tokio::spawn(write_records(out, records_rx));
+
+    let start = Instant::now();
+    let mut connections = JoinSet::new();
+    let mut next_id = 0;
+
+    tokio::pin!(shutdown);
+
+    loop {
+        let (client, addr) = tokio::select! {
+            res = listener.accept() => res?,
+            _ = &mut shutdown => break,
+        };
+
+        let id = next_id;
+        next_id += 1;
+        debug!(connection = id, %addr, "Новое соединение");
+
+        let records_tx = records_tx.clone();
+        connections.spawn(async move {
+            if let Err(err) = proxy_connection(client, upstream, id, start, records_tx).await {
+                warn!(connection = id, cause = %err, "Ошибка проксирования");
+            }
+        });
+    }
+
+    // Уничтожение задач соединений и исходного передатчика закрывает канал,
+    // после чего задача записи сохраняет оставшиеся записи и завершается
+    connections.shutdown().await;
+    drop(records_tx);
+
+    writer.await?
+}
+
+/// Передает данные между клиентом и сервером в обоих направлениях.
+async fn proxy_connection(
+    client: TcpStream,
+    upstream: SocketAddr,
+    id: u64,
+    start: Instant,
+    records: mpsc::Sender<Record>,
+) -> crate::Result<()> {
+    let server = TcpStream::connect(upstream).await?;
+
+    let (client_rd, client_wr) = client.into_split();
+    let (server_rd, server_wr) = server.into_split();
+
+    let to_server = forward(
+        client_rd,
+        server_wr,
+        Direction::ToServer,
+        id,
+        start,
+        records.clone(),
+    );
+    let to_client = forward(
+        server_rd,
+        client_wr,
+        Direction::ToClient,
+        id,
+        start,
+        records,
+    );
+
+    let (to_server, to_client) = tokio::join!(to_server, to_client);
+    to_server.and(to_client)
+}
+
+/// Передает данные из `src` в `dst` без изменений, записывая каждый
+/// полученный кадр.
+async fn forward(
+    mut src: OwnedReadHalf,
+    mut dst: OwnedWriteHalf,
+    direction: Direction,
+    connection: u64,
+    start: Instant,
+    records: mpsc::Sender<Record>,
+) -> crate::Result<()> {
+    let mut buffer = BytesMut::with_capacity(4 * 1024);
+
+    loop {
+        let n = src.read_buf(&mut buffer).await?;
+
+        if n == 0 {
+            // Передаем закрытие соединения другой стороне
+            dst.shutdown().await?;
+            return Ok(());
+        }
+
+        let elapsed = start.elapsed();
+
+        // Кадры записываются до передачи данных, поэтому запись ответа
+        // появляется раньше, чем клиент его получает
+        let mut buf = Cursor::new(&buffer[..]);
+        let mut parsed = 0;
+
+        loop {
+            match Frame::check(&mut buf) {
+                Ok(_) => {
+                    buf.set_position(parsed as u64);
+                    let frame = Frame::parse(&mut buf)?;
+                    parsed = buf.position() as usize;
+
+                    let record = Record {
+                        elapsed,
+                        connection,
+                        direction,
+                        frame,
+                    };
+
+                    // Задача записи завершается только при закрытии прокси
+                    let _ = records.send(record).await;
+                }
+                Err(crate::frame::Error::Incomplete) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        dst.write_all(&buffer[buffer.len() - n..]).await?;
+        buffer.advance(parsed);
+    }
+}
+
+/// Сохраняет записи из канала в `out`.
+async fn write_records<W>(out: W, mut records: mpsc::Receiver<Record>) -> crate::Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    let mut out = BufWriter::new(out);
+    let mut buf = BytesMut::new();
+
+    while let Some(record) = records.recv().await {
+        record.into_frame().encode(&mut buf);
+
+        // Сбрасываем буфер, только когда в канале нет готовых записей
+        while let Ok(record) = records.try_recv() {
+            record.into_frame().encode(&mut buf);
+        }
+
+        out.write_all(&buf).await?;
+        out.flush().await?;
+        buf.clear();
+    }
+
+    Ok(())
+}
+
+/// Читает все записи из `src`.
+pub async fn read_records<R>(mut src: R) -> crate::Result<Vec<Record>>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut data = vec![];
+    src.read_to_end(&mut data).await?;
+
+    let mut buf = Cursor::new(&data[..]);
+    let mut records = vec![];
+
+    while buf.has_remaining() {
+        let frame = Frame::parse(&mut buf)?;
+        records.push(Record::from_frame(frame)?);
+    }
+
+    Ok(records)
+}
+
+/// Воспроизводит записанную сессию на сервере `addr`.
+///
+/// Каждое записанное соединение воспроизводится в отдельном соединении.
+/// Запросы отправляются в записанном порядке, а каждый полученный ответ
+/// сравнивается с записанным. Если `timing` равен `true`, запросы
+/// отправляются с записанными интервалами, иначе - без задержек.
+pub async fn replay(
+    addr: impl ToSocketAddrs,
+    records: Vec<Record>,
+    timing: bool,
+) -> crate::Result<ReplayReport> {
+    let addr = lookup_host(addr)
+        .await?
+        .next()
+        .ok_or("Не удалось определить адрес сервера")?;
+
+    let mut sessions: BTreeMap<u64, Vec<Record>> = BTreeMap::new();
+    for record in records {
+        sessions.entry(record.connection).or_default().push(record);
+    }
+
+    let start = Instant::now();
+    let mut report = ReplayReport {
+        connections: sessions.len(),
+        ..ReplayReport::default()
+    };
+
+    let mut tasks = JoinSet::new();
+    for (id, records) in sessions {
+        tasks.spawn(replay_connection(addr, id, records, start, timing));
+    }
+
+    while let Some(res) = tasks.join_next().await {
+        let session = res??;
+
+        report.requests += session.requests;
+        report.matched += session.matched;
+        report.mismatched += session.mismatched;
+    }
+
+    report.elapsed = start.elapsed();
+    info!(?report, "Воспроизведение завершено");
+
+    Ok(report)
+}
+
+/// Воспроизводит одно записанное соединение.
+async fn replay_connection(
+    addr: SocketAddr,
+    id: u64,
+    records: Vec<Record>,
+    start: Instant,
+    timing: bool,
+) -> crate::Result<ReplayReport> {
+    let socket = TcpStream::connect(addr).await?;
+    let mut connection = Connection::new(socket);
+    let mut report = ReplayReport::default();
+
+    let mut records = records.into_iter();
+
+    while let Some(record) = records.next() {
+        match record.direction {
+            Direction::ToServer => {
+                if timing {
+                    time::sleep_until(start + record.elapsed).await;
+                }
+
+                connection.write_frame(&record.frame).await?;
+                report.requests += 1;
+            }
+            Direction::ToClient => {
+                match time::timeout(RESPONSE_TIMEOUT, connection.read_frame()).await {
+                    Ok(Ok(Some(frame))) if frame == record.frame => report.matched += 1,
+                    Ok(Ok(Some(frame))) => {
+                        warn!(
+                            connection = id,
+                            expected = %record.frame,
+                            actual = %frame,
+                            "Ответ отличается от записанного"
+                        );
+                        report.mismatched += 1;
+                    }
+                    res => {
+                        error!(connection = id, ?res, "Ответ сервера не получен");
+
+                        // Оставшиеся ответы также не будут получены
+                        let remaining = records
+                            .by_ref()
+                            .filter(|record| record.direction == Direction::ToClient)
+                            .count();
+                        report.mismatched += 1 + remaining;
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Принимает соединения из `listener` и распределяет команды клиентов между
+/// серверами `backends` по ключу.
+///
+/// Сервер команды определяется согласованным хешированием ее первого
+/// аргумента. Каждое соединение клиента открывает собственные соединения с
+/// серверами при первом обращении к ним, поэтому команды одного клиента
+/// выполняются на каждом сервере в порядке отправки.
+///
+/// `PING` и `QUIT` обрабатываются прокси. Команды без ключа и команды,
+/// обращающиеся ко всем ключам (`KEYS`, `SCAN`), а также публикация и
+/// подписка отклоняются с ошибкой.
+///
+/// Работает до завершения `shutdown`.
+pub async fn shard(
+    listener: TcpListener,
+    backends: Vec<SocketAddr>,
+    shutdown: impl Future,
+) -> crate::Result<()> {
+    if backends.is_empty() {
+        return Err("Не указан ни один сервер".into());
+    }
+
+    let ring = Arc::new(Ring::new(&backends));
+    let mut connections = JoinSet::new();
+
+    tokio::pin!(shutdown);
+
+    loop {
+        let (client, addr) = tokio::select! {
+            res = listener.accept() => res?,
+            _ = &mut shutdown => break,
+        };
+
+        debug!(%addr, "Новое соединение");
+
+        let ring = ring.clone();
+        let backends = backends.clone();
+        connections.spawn(async move {
+            if let Err(err) = shard_connection(client, &ring, &backends).await {
+                warn!(%addr, cause = %err, "Ошибка проксирования");
+            }
+        });
+    }
+
+    connections.shutdown().await;
+
+    Ok(())
+}
+
+/// Передает команды клиента серверам, которым принадлежат их ключи.
+async fn shard_connection(
+    client: TcpStream,
+    ring: &Ring,
+    backends: &[SocketAddr],
+) -> crate::Result<()> {
+    let mut client = Connection::new(client);
+    let mut upstreams: Vec<Option<Connection>> = backends.iter().map(|_| None).collect();
+
+    while let Some(frame) = client.read_frame().await? {
+        let (name, key) = match &frame {
+            Frame::Array(parts) => (
+                parts.first().and_then(frame_bytes),
+                parts.get(1).and_then(frame_bytes),
+            ),
+            _ => (None, None),
+        };
+
+        let name = match name {
+            Some(name) => String::from_utf8_lossy(name).to_lowercase(),
+            None => return Err(frame.to_error()),
+        };
+
+        let response = match (name.as_str(), key) {
+            ("ping", None) => Frame::Simple("PONG".to_string()),
+            ("ping", Some(msg)) => Frame::Bulk(Bytes::copy_from_slice(msg)),
+            ("quit", _) => {
+                client.write_frame(&Frame::Simple("OK".to_string())).await?;
+                client.shutdown().await?;
+                return Ok(());
+            }
+            (name, Some(key)) if !same_backend(&frame, ring, key, key_step(name)) => {
+                Frame::Error("CROSSSLOT Keys in request don't hash to the same backend".to_string())
+            }
+            (name, Some(key)) if !UNSHARDED_COMMANDS.contains(&name) => {
+                let idx = ring.get(key);
+                let addr = backends[idx];
+
+                match forward_frame(&mut upstreams[idx], addr, &frame).await {
+                    Ok(response) => response,
+                    Err(err) => {
+                        warn!(backend = %addr, cause = %err, "Сервер недоступен");
+
+                        // Соединение будет открыто заново при следующем
+                        // обращении к серверу
+                        upstreams[idx] = None;
+                        Frame::Error(format!("ERR backend {} is unavailable", addr))
+                    }
+                }
+            }
+            (name, _) => Frame::Error(format!(
+                "ERR '{}' command is not supported by the sharding proxy",
+                name
+            )),
+        };
+
+        client.write_frame(&response).await?;
+    }
+
+    Ok(())
+}
+
+/// Отправляет кадр серверу `addr` и возвращает его ответ, при необходимости
+/// открывая соединение.
+async fn forward_frame(
+    upstream: &mut Option<Connection>,
+    addr: SocketAddr,
+    frame: &Frame,
+) -> crate::Result<Frame> {
+    let connection = match upstream {
+        Some(connection) => connection,
+        None => upstream.insert(Connection::new(TcpStream::connect(addr).await?)),
+    };
+
+    connection.write_frame(frame).await?;
+
+    match time::timeout(RESPONSE_TIMEOUT, connection.read_frame()).await {
+        Ok(Ok(Some(response))) => Ok(response),
+        Ok(Ok(None)) => Err("Сервер закрыл соединение".into()),
+        Ok(Err(err)) => Err(err),
+        Err(_) => Err("Превышено время ожидания ответа сервера".into()),
+    }
+}
+
+/// Возвращает шаг между ключами команды `name` с несколькими ключами или
+/// `None`, если команда обращается к одному ключу.
+fn key_step(name: &str) -> Option<usize> {
+    MULTI_KEY_COMMANDS
+        .iter()
+        .find(|(command, _)| *command == name)
+        .map(|&(_, step)| step)
+}
+
+/// Проверяет, что все ключи команды `frame`, расположенные в списке
+/// аргументов с шагом `step`, находятся на том же сервере, что и ключ `key`.
+/// Команды с одним ключом (`step` равен `None`) проверку проходят.
+fn same_backend(frame: &Frame, ring: &Ring, key: &[u8], step: Option<usize>) -> bool {
+    let step = match step {
+        Some(step) => step,
+        None => return true,
+    };
+    let idx = ring.get(key);
+
+    match frame {
+        Frame::Array(parts) => parts[1..]
+            .iter()
+            .step_by(step)
+            .all(|part| frame_bytes(part).is_some_and(|key| ring.get(key) == idx)),
+        _ => false,
+    }
+}
+
+/// Возвращает содержимое строкового кадра.
+fn frame_bytes(frame: &Frame) -> Option<&[u8]> {
+    match frame {
+        Frame::Bulk(data) => Some(data),
+        Frame::Simple(data) => Some(data.as_bytes()),
+        _ => None,
+    }
+}
@@ -0,0 +1,72 @@
+//! Кольцо согласованного хеширования (consistent hashing).
+//!
+//! Используется прокси (`proxy::shard`) и `ShardedClient` для распределения
+//! ключей между серверами. При добавлении или удалении сервера перемещается
+//! лишь небольшая доля ключей.
+
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+
+/// Количество виртуальных узлов кольца на один сервер. Чем больше узлов,
+/// тем равномернее ключи распределяются между серверами.
+const VIRTUAL_NODES: usize = 160;
+
+/// Кольцо согласованного хеширования.
+///
+/// Каждый сервер представлен на кольце `VIRTUAL_NODES` точками. Ключ
+/// принадлежит серверу первой точки, следующей за хешем ключа.
+#[derive(Debug)]
+pub(crate) struct Ring {
+    /// Точки кольца и индексы соответствующих серверов.
+    nodes: BTreeMap<u64, usize>,
+}
+
+impl Ring {
+    /// Создает кольцо для серверов `backends`.
+    ///
+    /// Точки вычисляются по адресам серверов, поэтому распределение ключей
+    /// не зависит от порядка, в котором указаны серверы.
+    pub(crate) fn new(backends: &[SocketAddr]) -> Ring {
+        let mut nodes = BTreeMap::new();
+
+        for (idx, addr) in backends.iter().enumerate() {
+            for vnode in 0..VIRTUAL_NODES {
+                nodes.insert(hash(format!("{}-{}", addr, vnode).as_bytes()), idx);
+            }
+        }
+
+        Ring { nodes }
+    }
+
+    /// Возвращает индекс сервера, которому принадлежит `key`.
+    pub(crate) fn get(&self, key: &[u8]) -> usize {
+        let hash = hash(key);
+
+        self.nodes
+            .range(hash..)
+            .next()
+            .or_else(|| self.nodes.iter().next())
+            .map(|(_, &idx)| idx)
+            .expect("кольцо не может быть пустым")
+    }
+}
+
+/// Хеширует `data` алгоритмом FNV-1a.
+///
+/// В отличие от `DefaultHasher`, результат не зависит от версии `Rust` и
+/// процесса, поэтому разные экземпляры прокси и `ShardedClient`
+/// распределяют ключи одинаково. Также определяет порядок перебора ключей
+/// командой `SCAN`.
+pub(crate) fn hash(data: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for &byte in data {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+
+    // Хеши коротких строк, отличающихся последним символом, близки друг к
+    // другу, поэтому перемешиваем биты (финализатор splitmix64)
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    hash ^ (hash >> 31)
+}
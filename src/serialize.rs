@@ -0,0 +1,269 @@
+//! Формат сериализации значений для `DUMP` и `RESTORE`.
+//!
+//! Сериализованное значение (payload) имеет следующий вид:
+//!
+//! ```text
+//! +-----+--------+--------+----------------+
+//! | тип | данные | версия | контрольная    |
+//! | u8  |        | u16 LE | сумма u64 LE   |
+//! +-----+--------+--------+----------------+
+//! ```
+//!
+//! * тип `0` - строка, данные - байты строки;
+//! * тип `1` - целое число, данные - `i64` в порядке little-endian;
+//! * тип `2` - документ JSON, данные - текст документа в UTF-8;
+//! * тип `3` - хеш, данные - количество полей (`u32` LE), затем для каждого
+//!   поля в порядке возрастания его длина (`u32` LE) и байты, длина и байты
+//!   значения;
+//! * тип `4` - сортированное множество, данные - количество элементов (`u32`
+//!   LE), затем для каждого элемента в порядке возрастания оценки его длина
+//!   (`u32` LE), байты и оценка (`f64` LE).
+//!
+//! Контрольная сумма CRC-64 (вариант Jones, как в Redis) вычисляется по всем
+//! предыдущим байтам. Значение, сериализованное более новой версией формата,
+//! не восстанавливается. Номера типов не зависят от флагов сборки, но
+//! документ JSON восстанавливается только с флагом `json`.
+
+use crate::value::Value;
+use crate::zset::SortedSet;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+/// Текущая версия формата.
+const VERSION: u16 = 1;
+
+/// Тип строки.
+const TYPE_STRING: u8 = 0;
+
+/// Тип целого числа.
+const TYPE_INT: u8 = 1;
+
+/// Тип документа JSON.
+const TYPE_JSON: u8 = 2;
+
+/// Тип хеша.
+const TYPE_HASH: u8 = 3;
+
+/// Тип сортированного множества.
+const TYPE_ZSET: u8 = 4;
+
+/// Размер версии и контрольной суммы в конце сериализованного значения.
+const TRAILER_LEN: usize = 2 + 8;
+
+/// Отраженный многочлен CRC-64 Jones.
+const CRC64_POLY: u64 = 0x95ac_9329_ac4b_c9b5;
+
+/// Таблица CRC-64 для побайтового вычисления.
+static CRC64_TABLE: [u64; 256] = crc64_table();
+
+/// Ошибка, возвращаемая при несовпадении версии или контрольной суммы.
+const BAD_CHECKSUM: &str = "ERR DUMP payload version or checksum are wrong";
+
+/// Ошибка, возвращаемая при невалидных данных.
+const BAD_FORMAT: &str = "ERR Bad data format";
+
+/// Сериализует значение.
+pub(crate) fn dump(value: &Value) -> Bytes {
+    let mut dst = BytesMut::new();
+
+    match value {
+        Value::Int(n) => {
+            dst.put_u8(TYPE_INT);
+            dst.put_i64_le(*n);
+        }
+        #[cfg(feature = "json")]
+        Value::Json(doc) => {
+            dst.put_u8(TYPE_JSON);
+            dst.put_slice(doc.to_string().as_bytes());
+        }
+        Value::Hash(hash) => {
+            dst.put_u8(TYPE_HASH);
+            dst.put_u32_le(hash.len() as u32);
+
+            // Поля сортируются, чтобы одинаковые хеши давали одинаковые
+            // сериализованные значения
+            let mut fields: Vec<_> = hash.iter().collect();
+            fields.sort_unstable();
+
+            for (field, value) in fields {
+                put_chunk(&mut dst, field.as_bytes());
+                put_chunk(&mut dst, value);
+            }
+        }
+        Value::SortedSet(zset) => {
+            dst.put_u8(TYPE_ZSET);
+            dst.put_u32_le(zset.len() as u32);
+
+            for (member, score) in zset.iter() {
+                put_chunk(&mut dst, member.as_bytes());
+                dst.put_f64_le(score);
+            }
+        }
+        value => {
+            dst.put_u8(TYPE_STRING);
+            dst.put_slice(&value.to_bytes().unwrap_or_default());
+        }
+    }
+
+    dst.put_u16_le(VERSION);
+    let checksum = crc64(&dst);
+    dst.put_u64_le(checksum);
+
+    dst.freeze()
+}
+
+/// Восстанавливает значение, сериализованное функцией `dump`.
+///
+/// Возвращает `Err`, если контрольная сумма не совпадает, версия формата не
+/// поддерживается или данные невалидны.
+pub(crate) fn restore(payload: &[u8]) -> crate::Result<Value> {
+    if payload.len() < 1 + TRAILER_LEN {
+        return Err(BAD_CHECKSUM.into());
+    }
+
+    let (body, checksum) = payload.split_at(payload.len() - 8);
+    let checksum = u64::from_le_bytes(<[u8; 8]>::try_from(checksum)?);
+    if crc64(body) != checksum {
+        return Err(BAD_CHECKSUM.into());
+    }
+
+    let (body, version) = body.split_at(body.len() - 2);
+    let version = u16::from_le_bytes(<[u8; 2]>::try_from(version)?);
+    if version > VERSION {
+        return Err(BAD_CHECKSUM.into());
+    }
+
+    let data = &body[1..];
+    match body[0] {
+        TYPE_STRING => Ok(Value::from_bytes(Bytes::copy_from_slice(data))),
+        TYPE_INT => {
+            let n = <[u8; 8]>::try_from(data).map_err(|_| BAD_FORMAT)?;
+            Ok(Value::Int(i64::from_le_bytes(n)))
+        }
+        TYPE_JSON => restore_json(data),
+        TYPE_HASH => restore_hash(data).ok_or_else(|| BAD_FORMAT.into()),
+        TYPE_ZSET => restore_zset(data).ok_or_else(|| BAD_FORMAT.into()),
+        _ => Err(BAD_FORMAT.into()),
+    }
+}
+
+/// Восстанавливает хеш. Возвращает `None`, если данные невалидны.
+fn restore_hash(mut data: &[u8]) -> Option<Value> {
+    if data.remaining() < 4 {
+        return None;
+    }
+    let len = data.get_u32_le() as usize;
+
+    // Емкость ограничивается размером данных: каждое поле занимает не
+    // меньше 8 байтов
+    let mut hash = HashMap::with_capacity(len.min(data.len() / 8));
+    for _ in 0..len {
+        let field = String::from_utf8(get_chunk(&mut data)?.to_vec()).ok()?;
+        let value = Bytes::copy_from_slice(get_chunk(&mut data)?);
+        hash.insert(field, value);
+    }
+
+    if data.has_remaining() || hash.len() != len {
+        return None;
+    }
+
+    Some(Value::Hash(Box::new(hash)))
+}
+
+/// Восстанавливает сортированное множество. Возвращает `None`, если данные
+/// невалидны.
+fn restore_zset(mut data: &[u8]) -> Option<Value> {
+    if data.remaining() < 4 {
+        return None;
+    }
+    let len = data.get_u32_le() as usize;
+
+    let mut zset = SortedSet::default();
+    for _ in 0..len {
+        let member = String::from_utf8(get_chunk(&mut data)?.to_vec()).ok()?;
+        if data.remaining() < 8 {
+            return None;
+        }
+        let score = data.get_f64_le();
+
+        if score.is_nan() || zset.insert(member, score).is_some() {
+            return None;
+        }
+    }
+
+    if data.has_remaining() {
+        return None;
+    }
+
+    Some(Value::SortedSet(Box::new(zset)))
+}
+
+/// Записывает длину `chunk` и его байты.
+fn put_chunk(dst: &mut BytesMut, chunk: &[u8]) {
+    dst.put_u32_le(chunk.len() as u32);
+    dst.put_slice(chunk);
+}
+
+/// Читает байты, записанные `put_chunk`. Возвращает `None`, если данных
+/// недостаточно.
+fn get_chunk<'a>(data: &mut &'a [u8]) -> Option<&'a [u8]> {
+    if data.remaining() < 4 {
+        return None;
+    }
+    let len = data.get_u32_le() as usize;
+
+    if data.len() < len {
+        return None;
+    }
+    let (chunk, rest) = data.split_at(len);
+    *data = rest;
+
+    Some(chunk)
+}
+
+/// Восстанавливает документ JSON.
+#[cfg(feature = "json")]
+fn restore_json(data: &[u8]) -> crate::Result<Value> {
+    let doc = serde_json::from_slice(data).map_err(|_| BAD_FORMAT)?;
+    Ok(Value::Json(Box::new(doc)))
+}
+
+/// Документы JSON поддерживаются только с флагом `json`.
+#[cfg(not(feature = "json"))]
+fn restore_json(_data: &[u8]) -> crate::Result<Value> {
+    Err(BAD_FORMAT.into())
+}
+
+/// Вычисляет контрольную сумму CRC-64 Jones.
+fn crc64(data: &[u8]) -> u64 {
+    data.iter().fold(0, |crc, &byte| {
+        CRC64_TABLE[((crc ^ u64::from(byte)) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+/// Строит таблицу CRC-64 во время компиляции.
+const fn crc64_table() -> [u64; 256] {
+    let mut table = [0; 256];
+
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u64;
+
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ CRC64_POLY
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+
+        table[i] = crc;
+        i += 1;
+    }
+
+    table
+}
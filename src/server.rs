@@ -2,42 +2,127 @@
 //!
 //! Предоставляет асинхронную функцию `run`, регистрирующую входящие соединения и
 //! выделяющую (spawn) задачу на каждое из них.
+//!
+//! `Builder` позволяет настроить сервер и запустить его в фоновой задаче.
+//! Возвращаемый обработчик `Server` предоставляет адрес сервера и позволяет
+//! закрыть сервер и дождаться его завершения.
+//!
+//! Функция `serve_connection` обрабатывает одно соединение поверх произвольного
+//! транспорта, а `connect_in_memory` позволяет запустить клиента и сервер в
+//! одном процессе без TCP.
+//!
+//! Сервер принимает соединения из любого типа, реализующего `Accept`. С флагом
+//! `turmoil` сервер может работать в симулированной сети `turmoil`.
+//!
+//! Обработка принятого соединения реализована `ConnectionService` -
+//! `tower_service::Service`, который позволяет добавлять промежуточные слои
+//! уровня соединения и запускать сервер из собственного цикла приема
+//! соединений.
 
-use crate::{Command, Connection, Db, DbDropGuard, Shutdown};
+use crate::audit::AuditLog;
+use crate::clients::Client;
+use crate::log_filter::LogFilter;
+use crate::{metrics, Command, Connection, Db, DbDropGuard, Frame, Session, Shutdown};
 
+use std::convert::TryFrom;
 use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::{broadcast, mpsc, Semaphore};
-use tokio::time::{self, Duration};
-use tracing::{debug, error, info, instrument};
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpListener, ToSocketAddrs};
+use tokio::sync::{broadcast, mpsc, watch, OwnedSemaphorePermit, Semaphore};
+use tokio::task::JoinHandle;
+use tokio::time::{self, Duration, Instant};
+use tower_service::Service;
+use tracing::{debug, error, field, info, info_span, instrument, warn, Instrument};
 
 /// Состояние обработчика сервера. Создается в вызове `run`. Включает метод `run`,
-/// прослушивающий TCP и инициализирующий состояние каждого соединения.
+/// прослушивающий TCP и передающий каждое соединение `ConnectionService`.
 #[derive(Debug)]
-struct Listener {
+struct Listener<L> {
+    /// Обработчик TCP, передаваемый стороне, вызывающей `run`.
+    listener: L,
+
+    /// Стратегия повторных попыток при ошибках установки соединения.
+    accept_retry: AcceptRetry,
+
+    /// Обрабатывает принятые соединения.
+    service: ConnectionService,
+}
+
+/// Сервис обработки принятых соединений.
+///
+/// Реализует `tower_service::Service` для пары из транспорта и адреса
+/// клиента. Вызов сервиса возвращает футуру, которая обрабатывает соединение
+/// до его закрытия: ждет места с учетом ограничения количества соединений,
+/// читает команды и записывает ответы. Футура должна выполняться в отдельной
+/// задаче.
+///
+/// Сервер использует этот сервис для каждого принятого соединения. Сервис,
+/// созданный `Builder::into_service`, позволяет обрабатывать соединения,
+/// принятые собственным циклом, и добавлять промежуточные слои (middleware)
+/// уровня соединения: терминацию TLS, аутентификацию, ограничение частоты
+/// подключений и т.п.
+///
+/// # Примеры
+///
+/// ```no_run
+/// use mini_redis::server::Builder;
+/// use tokio::net::TcpListener;
+/// use tower_service::Service;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let mut service = Builder::new().max_connections(10).into_service();
+///     let listener = TcpListener::bind("127.0.0.1:6379").await.unwrap();
+///
+///     loop {
+///         let (socket, peer) = listener.accept().await.unwrap();
+///         tokio::spawn(service.call((socket, peer)));
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConnectionService {
     /// Общий обработчик БД.
     ///
     /// Содержит хранилище в форме "ключ-значение", а также широковещательные каналы для
     /// pub/sub (издатель/подписчик).
     ///
-    /// Обертка вокруг `Arc`. Внутренняя `Db` может быть
-    /// извлечена и передана в состояние каждого соединения (`Handler`).
-    db_holder: DbDropGuard,
+    /// Обертка вокруг `Arc`. Клон `Db` передается в состояние
+    /// каждого соединения (`Handler`).
+    db: Db,
 
-    /// Обработчик TCP, передаваемый стороне, вызывающей `run`.
-    listener: TcpListener,
+    /// Удерживает БД, созданную сервисом, если БД не передана в настройках.
+    /// Фоновая задача очистки закрывается при уничтожении последнего клона
+    /// сервиса.
+    _db_holder: Option<Arc<DbDropGuard>>,
 
     /// Максимальное количество подключений.
     ///
     /// `Semaphore` используется для ограничения максимального количества соединений.
-    /// Перед попыткой принять новое соединение, проверяется разрешение (permit) из
-    /// семафора. Если разрешения отсутствуют, обработчик переходит в состояние ожидания.
+    /// Перед обработкой принятого соединения, проверяется разрешение (permit) из
+    /// семафора. Если разрешения отсутствуют, соединение ожидает в `admission_queue`.
     ///
     /// После завершения обработки соединения, в семафор возвращается
     /// разрешение.
     limit_connections: Arc<Semaphore>,
 
+    /// Очередь соединений, ожидающих разрешения `limit_connections`.
+    ///
+    /// Каждое ожидающее соединение удерживает разрешение этого семафора.
+    /// Если разрешения отсутствуют, очередь заполнена и соединение сразу
+    /// отклоняется.
+    admission_queue: Arc<Semaphore>,
+
+    /// Максимальное время ожидания в очереди. Если `None`, соединение
+    /// ожидает, пока не освободится место.
+    admission_timeout: Option<Duration>,
+
     /// Передает сигнал о закрытии всем активным подключениям.
     ///
     /// Начальный триггер `shutdown` предоставляется стороной, вызывающей `run`.
@@ -61,6 +146,22 @@ struct Listener {
     /// к завершению `shutdown_complete_rx.recv()` с `None`. После этого
     /// выход из серверного процесса становится безопасным.
     shutdown_complete_tx: mpsc::Sender<()>,
+
+    /// Журнал аудита, передаваемый каждому соединению.
+    audit: Option<AuditLog>,
+
+    /// Максимальное количество каналов, на которые может быть подписано
+    /// одно соединение.
+    max_subscriptions: usize,
+
+    /// Максимальное время выполнения одной команды.
+    command_timeout: Option<Duration>,
+
+    /// Строгая проверка формата входящих кадров.
+    strict_framing: bool,
+
+    /// Фильтр логов, изменяемый командой `CONFIG SET loglevel`.
+    log_filter: Option<LogFilter>,
 }
 
 /// Обработчик соединения. Читает запросы из `connection` и применяет
@@ -94,16 +195,298 @@ struct Handler {
 
     /// Предназначено для внутреннего использования.
     _shutdown_complete: mpsc::Sender<()>,
+
+    /// Адрес клиента. Отсутствует для соединений, созданных
+    /// `serve_connection`.
+    peer: Option<SocketAddr>,
+
+    /// Журнал аудита изменяющих команд.
+    audit: Option<AuditLog>,
+
+    /// Состояние соединения, доступное командам.
+    session: Session,
+
+    /// Максимальное время выполнения одной команды. Если `None`, время
+    /// выполнения не ограничено.
+    command_timeout: Option<Duration>,
 }
 
-/// Максимальное количество соединений, которые будет принимать сервер.
+/// Максимальное количество соединений, которые будет принимать сервер,
+/// если лимит не настроен с помощью `Builder::max_connections`.
 ///
 /// При достижении этого лимита, сервер перестает принимать соединения,
 /// пока активное соединение не будет прервано.
-///
-/// В реальном приложении это значение будет настраиваемым.
 const MAX_CONNECTIONS: usize = 250;
 
+/// Максимальное количество соединений, ожидающих обработки, если лимит
+/// не настроен с помощью `Builder::max_queued_connections`.
+const MAX_QUEUED_CONNECTIONS: usize = 1024;
+
+/// Стратегия повторных попыток при ошибках установки соединения.
+///
+/// Ошибка установки соединения может быть переходной (transient), например,
+/// при достижении лимита сокетов операционной системы. Сервер повторяет
+/// попытку с экспоненциально увеличивающейся задержкой: после первого провала
+/// задача ждет `initial_delay`, каждая следующая задержка умножается на
+/// `multiplier`, но не превышает `max_delay`. После `max_retries` повторных
+/// попыток сервер закрывается.
+///
+/// По умолчанию первая задержка составляет 1 секунду, задержка удваивается
+/// до 64 секунд, а сервер закрывается после 7 повторных попыток.
+///
+/// # Примеры
+///
+/// ```
+/// use mini_redis::server::{AcceptRetry, Builder};
+/// use std::time::Duration;
+///
+/// // Повторять попытки бесконечно, но не реже, чем раз в 10 секунд
+/// let retry = AcceptRetry::new()
+///     .initial_delay(Duration::from_millis(100))
+///     .max_delay(Duration::from_secs(10))
+///     .retry_forever()
+///     .jitter(true);
+///
+/// let builder = Builder::new().accept_retry(retry);
+/// ```
+#[derive(Debug, Clone)]
+pub struct AcceptRetry {
+    /// Задержка после первого провала.
+    initial_delay: Duration,
+
+    /// Множитель задержки.
+    multiplier: u32,
+
+    /// Максимальная задержка.
+    max_delay: Duration,
+
+    /// Максимальное количество повторных попыток. Если `None`, попытки
+    /// повторяются бесконечно.
+    max_retries: Option<usize>,
+
+    /// `true`, если задержка выбирается случайно.
+    jitter: bool,
+}
+
+impl AcceptRetry {
+    /// Создает стратегию по умолчанию.
+    pub fn new() -> AcceptRetry {
+        AcceptRetry {
+            initial_delay: Duration::from_secs(1),
+            multiplier: 2,
+            max_delay: Duration::from_secs(64),
+            max_retries: Some(7),
+            jitter: false,
+        }
+    }
+
+    /// Устанавливает задержку после первого провала.
+    pub fn initial_delay(mut self, delay: Duration) -> AcceptRetry {
+        self.initial_delay = delay;
+        self
+    }
+
+    /// Устанавливает множитель задержки. Множитель `1` означает постоянную
+    /// задержку.
+    ///
+    /// # Паники
+    ///
+    /// Паникует, если `multiplier` равен `0`.
+    pub fn multiplier(mut self, multiplier: u32) -> AcceptRetry {
+        assert!(multiplier > 0, "Множитель задержки должен быть больше нуля");
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// Устанавливает максимальную задержку.
+    pub fn max_delay(mut self, delay: Duration) -> AcceptRetry {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Устанавливает максимальное количество повторных попыток, после которых
+    /// сервер закрывается.
+    pub fn max_retries(mut self, retries: usize) -> AcceptRetry {
+        self.max_retries = Some(retries);
+        self
+    }
+
+    /// Повторять попытки бесконечно, не закрывая сервер.
+    pub fn retry_forever(mut self) -> AcceptRetry {
+        self.max_retries = None;
+        self
+    }
+
+    /// Включает случайный выбор задержки от половины до полной расчетной
+    /// задержки. Это позволяет избежать одновременных повторных попыток
+    /// нескольких серверов.
+    pub fn jitter(mut self, jitter: bool) -> AcceptRetry {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Возвращает задержку перед повторной попыткой номер `retry`,
+    /// начиная с `0`.
+    fn delay(&self, retry: usize) -> Duration {
+        let factor = u32::try_from(retry)
+            .ok()
+            .and_then(|retry| self.multiplier.checked_pow(retry))
+            .unwrap_or(u32::MAX);
+
+        let delay = self
+            .initial_delay
+            .checked_mul(factor)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay);
+
+        if self.jitter {
+            delay / 2 + delay.mul_f64(random_fraction() / 2.0)
+        } else {
+            delay
+        }
+    }
+}
+
+impl Default for AcceptRetry {
+    fn default() -> AcceptRetry {
+        AcceptRetry::new()
+    }
+}
+
+/// Возвращает псевдослучайное число от `0` до `1`.
+///
+/// `RandomState` инициализируется случайными ключами, поэтому хеш пустого
+/// ввода является случайным. Для выбора задержки этого достаточно.
+pub(crate) fn random_fraction() -> f64 {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+
+    let random = RandomState::new().build_hasher().finish();
+    (random >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Максимальное время отправки клиенту уведомления о закрытии сервера.
+const SHUTDOWN_NOTIFY_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Источник входящих соединений сервера.
+///
+/// Реализуется для `tokio::net::TcpListener`, а с флагом `turmoil` - для
+/// `turmoil::net::TcpListener`. Позволяет запускать сервер поверх другого
+/// сетевого стека, например, симулированной сети в тестах.
+pub trait Accept: Send + 'static {
+    /// Транспорт принятого соединения.
+    type Io: AsyncRead + AsyncWrite + Send + Unpin + 'static;
+
+    /// Принимает входящее соединение.
+    fn accept(&mut self) -> impl Future<Output = io::Result<(Self::Io, SocketAddr)>> + Send;
+
+    /// Возвращает адрес, на котором принимаются соединения.
+    fn local_addr(&self) -> io::Result<SocketAddr>;
+}
+
+impl Accept for TcpListener {
+    type Io = tokio::net::TcpStream;
+
+    fn accept(&mut self) -> impl Future<Output = io::Result<(Self::Io, SocketAddr)>> + Send {
+        TcpListener::accept(self)
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        TcpListener::local_addr(self)
+    }
+}
+
+#[cfg(feature = "turmoil")]
+impl Accept for turmoil::net::TcpListener {
+    type Io = turmoil::net::TcpStream;
+
+    fn accept(&mut self) -> impl Future<Output = io::Result<(Self::Io, SocketAddr)>> + Send {
+        turmoil::net::TcpListener::accept(self)
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        turmoil::net::TcpListener::local_addr(self)
+    }
+}
+
+/// Настройки сервера.
+///
+/// Создается с помощью `Server::builder` или `Builder::new`. Методы `bind` и
+/// `serve` запускают сервер в фоновой задаче и возвращают обработчик `Server`.
+///
+/// # Примеры
+///
+/// ```
+/// use mini_redis::{server::Server, Client};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let server = Server::builder()
+///         .max_connections(10)
+///         .bind("127.0.0.1:0")
+///         .await
+///         .unwrap();
+///
+///     let mut client = Client::connect(server.local_addr()).await.unwrap();
+///     client.ping(None).await.unwrap();
+///
+///     server.shutdown();
+///     server.join().await.unwrap();
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Builder {
+    /// Максимальное количество одновременных соединений.
+    max_connections: usize,
+
+    /// Максимальное количество соединений, ожидающих обработки.
+    max_queued_connections: usize,
+
+    /// Максимальное время ожидания обработки соединения.
+    admission_timeout: Option<Duration>,
+
+    /// Стратегия повторных попыток при ошибках установки соединения.
+    accept_retry: AcceptRetry,
+
+    /// Максимальное количество подписок одного соединения.
+    max_subscriptions: usize,
+
+    /// БД, используемая сервером. Если `None`, сервер создает новую БД.
+    db: Option<Db>,
+
+    /// Журнал аудита изменяющих команд.
+    audit: Option<AuditLog>,
+
+    /// Максимальное время выполнения одной команды.
+    command_timeout: Option<Duration>,
+
+    /// Строгая проверка формата входящих кадров.
+    strict_framing: bool,
+
+    /// Фильтр логов, изменяемый командой `CONFIG SET loglevel`.
+    log_filter: Option<LogFilter>,
+}
+
+/// Обработчик сервера, запущенного в фоновой задаче.
+///
+/// Уничтожение `Server` приводит к закрытию сервера. Для ожидания
+/// завершения сервера используется `join`.
+#[derive(Debug)]
+pub struct Server {
+    /// Адрес, на котором сервер принимает соединения.
+    local_addr: SocketAddr,
+
+    /// Сигнал о закрытии сервера. Уничтожение передатчика также
+    /// воспринимается сервером как сигнал.
+    shutdown: watch::Sender<bool>,
+
+    /// Фоновая задача сервера.
+    handle: JoinHandle<()>,
+
+    /// БД, используемая сервером.
+    db: Db,
+}
+
 /// Запускает сервер `mini-redis`.
 ///
 /// Принимает соединения из переданного обработчика. Для каждого входящего
@@ -112,79 +495,480 @@ const MAX_CONNECTIONS: usize = 250;
 /// `shutdown`, после чего плавно закрывается.
 ///
 /// `tokio::signal::ctrl_c()` может быть использован в качестве аргумента `shutdown`. Регистрируется сигнал `SIGINT`.
-pub async fn run(listener: TcpListener, shutdown: impl Future) {
-    // После завершения переданного `shutdown`, мы должны отправить сообщение о
-    // закрытии всем активным соединениям. Для этой цели используется широковещательный
-    // канал. В приведенном ниже коде игнорируется приемник широковещательной пары.
-    // Для создания приемника может использоваться метод передатчика `subscribe`.
-    let (notify_shutdown, _) = broadcast::channel(1);
-    let (shutdown_complete_tx, mut shutdown_complete_rx) = mpsc::channel(1);
-
-    // Инициализируем состояние обработчика.
-    let mut server = Listener {
-        listener,
-        db_holder: DbDropGuard::new(),
-        limit_connections: Arc::new(Semaphore::new(MAX_CONNECTIONS)),
-        notify_shutdown,
-        shutdown_complete_tx,
-    };
+///
+/// Сервер запускается с настройками по умолчанию. Для настройки сервера и
+/// получения обработчика запущенного сервера см. `Builder`.
+pub async fn run(listener: impl Accept, shutdown: impl Future) {
+    Builder::new().run(listener, shutdown).await
+}
 
-    // Конкурентно запускает сервер и регистрирует сигнал `shutdown`.
-    // Задача сервера выполняется до получения ошибки, поэтому при нормальных
-    // обстоятельствах эта инструкция `select!` выполняется до получения сигнала
-    // `shutdown`.
-    //
-    // Инструкция `select!` написана в форме:
-    //
-    // ```
-    // <результат асинхронной операции> = <асинхронная операция> => <операция обработки результата>
-    // ```
-    //
-    // Все инструкции `<асинхронная операция>` выполняются параллельно. После завершения первой
-    // операции, выполняется ее `<операция обработки результата>`.
-    //
-    // Макрос `select!` - основной строительный блок асинхронного
-    // `Rust`. См.: https://docs.rs/tokio/*/tokio/macro.select.html
-    tokio::select! {
-        res = server.run() => {
-            // Если здесь получена ошибка, значит установка соединения обработчиком TCP
-            // провалилась несколько раз, сервер сдался и закрылся.
-            //
-            // Ошибки, возникающие при обработке отдельных соединений, не
-            // достигают этой точки.
-            if let Err(err) = res {
-                error!(cause = %err, "Провал установки соединения.");
-            }
+impl Builder {
+    /// Создает настройки сервера по умолчанию.
+    pub fn new() -> Builder {
+        Builder {
+            max_connections: MAX_CONNECTIONS,
+            max_queued_connections: MAX_QUEUED_CONNECTIONS,
+            admission_timeout: None,
+            accept_retry: AcceptRetry::new(),
+            max_subscriptions: usize::MAX,
+            db: None,
+            audit: None,
+            command_timeout: None,
+            strict_framing: false,
+            log_filter: None,
         }
-        _ = shutdown => {
-            // Был получен сигнал о закрытии.
-            info!("Закрытие...");
+    }
+
+    /// Устанавливает БД, используемую сервером.
+    ///
+    /// Позволяет нескольким серверам работать с общим состоянием, а также
+    /// обращаться к данным сервера напрямую. По умолчанию сервер создает
+    /// новую БД.
+    pub fn db(mut self, db: Db) -> Builder {
+        self.db = Some(db);
+        self
+    }
+
+    /// Устанавливает максимальное количество одновременных соединений.
+    ///
+    /// # Паники
+    ///
+    /// Паникует, если `max` равен `0`.
+    pub fn max_connections(mut self, max: usize) -> Builder {
+        assert!(
+            max > 0,
+            "Максимальное количество соединений должно быть больше нуля"
+        );
+        self.max_connections = max;
+        self
+    }
+
+    /// Устанавливает максимальное количество соединений, ожидающих
+    /// обработки.
+    ///
+    /// Соединения сверх `max_connections` принимаются и ожидают в очереди,
+    /// пока одно из активных соединений не будет закрыто. Если очередь
+    /// заполнена, новое соединение получает ошибку и закрывается.
+    ///
+    /// # Паники
+    ///
+    /// Паникует, если `max` равен `0`.
+    pub fn max_queued_connections(mut self, max: usize) -> Builder {
+        assert!(
+            max > 0,
+            "Максимальное количество ожидающих соединений должно быть больше нуля"
+        );
+        self.max_queued_connections = max;
+        self
+    }
+
+    /// Устанавливает максимальное время ожидания соединения в очереди.
+    ///
+    /// Соединение, не дождавшееся обработки за `timeout`, получает ошибку и
+    /// закрывается. По умолчанию время ожидания не ограничено.
+    ///
+    /// # Паники
+    ///
+    /// Паникует, если `timeout` равен нулю.
+    pub fn admission_timeout(mut self, timeout: Duration) -> Builder {
+        assert!(
+            !timeout.is_zero(),
+            "Время ожидания соединения должно быть больше нуля"
+        );
+        self.admission_timeout = Some(timeout);
+        self
+    }
+
+    /// Устанавливает стратегию повторных попыток при ошибках установки
+    /// соединения.
+    ///
+    /// См. `AcceptRetry`.
+    pub fn accept_retry(mut self, retry: AcceptRetry) -> Builder {
+        self.accept_retry = retry;
+        self
+    }
+
+    /// Включает запись изменяющих команд в журнал аудита.
+    ///
+    /// См. `audit::AuditLog`.
+    pub fn audit_log(mut self, audit: AuditLog) -> Builder {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Устанавливает максимальное количество каналов, на которые может быть
+    /// подписано одно соединение.
+    ///
+    /// Команда `SUBSCRIBE`, превышающая лимит, отклоняется с ошибкой. Это не
+    /// позволяет одному клиенту неограниченно увеличивать количество подписок.
+    /// По умолчанию количество подписок не ограничено.
+    ///
+    /// # Паники
+    ///
+    /// Паникует, если `max` равен `0`.
+    pub fn max_subscriptions(mut self, max: usize) -> Builder {
+        assert!(
+            max > 0,
+            "Максимальное количество подписок должно быть больше нуля"
+        );
+        self.max_subscriptions = max;
+        self
+    }
+
+    /// Устанавливает максимальное время выполнения одной команды.
+    ///
+    /// Команда, не завершившаяся за `timeout`, прерывается: клиент получает
+    /// ошибку, а соединение закрывается, поскольку часть ответа уже могла
+    /// быть отправлена. Время проверяется в точках ожидания, а также при
+    /// переборе ключей в `KEYS` и `SCAN`: такая команда завершается ошибкой,
+    /// не закрывая соединение. Остальная синхронная работа с БД не
+    /// прерывается. `SUBSCRIBE` выполняется до
+    /// отписки клиента и не ограничивается. По умолчанию время выполнения
+    /// не ограничено.
+    ///
+    /// # Паники
+    ///
+    /// Паникует, если `timeout` равен нулю.
+    pub fn command_timeout(mut self, timeout: Duration) -> Builder {
+        assert!(
+            !timeout.is_zero(),
+            "Время выполнения команды должно быть больше нуля"
+        );
+        self.command_timeout = Some(timeout);
+        self
+    }
+
+    /// Включает строгую проверку формата кадров, получаемых от клиентов.
+    ///
+    /// Кадр с `\r` или `\n` без пары, заголовком длины с лишними символами
+    /// или объемной строкой без завершающих `\r\n` приводит к закрытию
+    /// соединения. См. `Frame::check_strict`. По умолчанию проверка не строгая.
+    pub fn strict_framing(mut self, strict: bool) -> Builder {
+        self.strict_framing = strict;
+        self
+    }
+
+    /// Устанавливает фильтр логов, изменяемый командой `CONFIG SET loglevel`.
+    ///
+    /// Без фильтра команда возвращает ошибку. См. `crate::log_filter`.
+    pub fn log_filter(mut self, filter: LogFilter) -> Builder {
+        self.log_filter = Some(filter);
+        self
+    }
+
+    /// Привязывает обработчик TCP к `addr` и запускает сервер в фоновой задаче.
+    ///
+    /// Для выбора свободного порта может использоваться порт `0`. Фактический
+    /// адрес возвращается методом `Server::local_addr`.
+    pub async fn bind(self, addr: impl ToSocketAddrs) -> crate::Result<Server> {
+        let listener = TcpListener::bind(addr).await?;
+        self.serve(listener)
+    }
+
+    /// Запускает сервер в фоновой задаче, принимая соединения из `listener`.
+    ///
+    /// Должен вызываться в контексте среды выполнения `Tokio`.
+    pub fn serve(mut self, listener: impl Accept) -> crate::Result<Server> {
+        let local_addr = listener.local_addr()?;
+        let (shutdown, mut shutdown_rx) = watch::channel(false);
+
+        // БД создается до запуска задачи, чтобы она была доступна через
+        // `Server::db`. Фоновая задача очистки закрывается вместе с сервером
+        let db_holder = match self.db {
+            Some(_) => None,
+            None => {
+                let db_holder = DbDropGuard::new();
+                self.db = Some(db_holder.db());
+                Some(db_holder)
+            }
+        };
+        let db = self.db.clone().unwrap();
+
+        let handle = tokio::spawn(async move {
+            self.run(listener, async move {
+                // Ошибка означает, что обработчик `Server` уничтожен
+                let _ = shutdown_rx.changed().await;
+            })
+            .await;
+
+            drop(db_holder);
+        });
+
+        Ok(Server {
+            local_addr,
+            shutdown,
+            handle,
+            db,
+        })
+    }
+
+    /// Запускает сервер с этими настройками и ждет его завершения.
+    ///
+    /// Аналогична функции `run`.
+    pub async fn run(self, listener: impl Accept, shutdown: impl Future) {
+        let accept_retry = self.accept_retry.clone();
+        let (service, mut shutdown_complete_rx) = self.build_service();
+
+        // Инициализируем состояние обработчика.
+        let mut server = Listener {
+            listener,
+            accept_retry,
+            service,
+        };
+
+        // Конкурентно запускает сервер и регистрирует сигнал `shutdown`.
+        // Задача сервера выполняется до получения ошибки, поэтому при нормальных
+        // обстоятельствах эта инструкция `select!` выполняется до получения сигнала
+        // `shutdown`.
+        //
+        // Инструкция `select!` написана в форме:
+        //
+        // ```
+        // <результат асинхронной операции> = <асинхронная операция> => <операция обработки результата>
+        // ```
+        //
+        // Все инструкции `<асинхронная операция>` выполняются параллельно. После завершения первой
+        // операции, выполняется ее `<операция обработки результата>`.
+        //
+        // Макрос `select!` - основной строительный блок асинхронного
+        // `Rust`. См.: https://docs.rs/tokio/*/tokio/macro.select.html
+        tokio::select! {
+            res = server.run() => {
+                // Если здесь получена ошибка, значит установка соединения обработчиком TCP
+                // провалилась несколько раз, сервер сдался и закрылся.
+                //
+                // Ошибки, возникающие при обработке отдельных соединений, не
+                // достигают этой точки.
+                if let Err(err) = res {
+                    error!(cause = %err, "Провал установки соединения.");
+                }
+            }
+            _ = shutdown => {
+                // Был получен сигнал о закрытии.
+                info!("Закрытие...");
+            }
         }
+
+        // Уничтожаем сервис, удерживающий передатчики `notify_shutdown` и
+        // `shutdown_complete_tx`. Это важно, поскольку в противном случае
+        // `.await` ниже никогда не завершится.
+        //
+        // При уничтожении `notify_shutdown`, все подписанные задачи
+        // получают сигнал о закрытии.
+        drop(server);
+
+        // Ждем завершения обработки все активных соединений. Поскольку
+        // `Sender`, удерживаемый обработчиком, был уничтожен выше, оставшиеся
+        // экземпляры `Sender` удерживаются задачами обработчика соединения. При их уничтожении,
+        // канал `mpsc` закрывается и `recv()` возвращает `None`.
+        let _ = shutdown_complete_rx.recv().await;
+    }
+
+    /// Создает сервис обработки соединений с этими настройками.
+    ///
+    /// Сервис позволяет обрабатывать соединения, принятые собственным циклом
+    /// вместо `run`, и оборачивать обработку промежуточными слоями. Настройка
+    /// `accept_retry` не используется. См. `ConnectionService`.
+    ///
+    /// Если БД не передана в настройках, сервис создает новую БД. Ее фоновая
+    /// задача очистки закрывается при уничтожении последнего клона сервиса.
+    ///
+    /// Должен вызываться в контексте среды выполнения `Tokio`.
+    pub fn into_service(self) -> ConnectionService {
+        self.build_service().0
     }
 
-    // Извлекаем приемник `shutdown_complete` и явно уничтожаем
-    // передатчик `shutdown_transmitter`. Это важно, поскольку в противном случае
-    // `.await` ниже никогда не завершится.
-    let Listener {
-        shutdown_complete_tx,
-        notify_shutdown,
-        ..
-    } = server;
-
-    // При уничтожении `notify_shutdown`, все подписанные задачи
-    // получают сигнал о закрытии.
-    drop(notify_shutdown);
-    // Уничтожаем финального `Sender`, чтобы `Receiver` ниже мог завершиться.
-    drop(shutdown_complete_tx);
-
-    // Ждем завершения обработки все активных соединений. Поскольку
-    // `Sender`, удерживаемый обработчиком, был уничтожен выше, оставшиеся
-    // экземпляры `Sender` удерживаются задачами обработчика соединения. При их уничтожении,
-    // канал `mpsc` закрывается и `recv()` возвращает `None`.
-    let _ = shutdown_complete_rx.recv().await;
+    /// Создает сервис обработки соединений и приемник, который получает
+    /// `None` после завершения обработки всех соединений и уничтожения
+    /// сервиса.
+    fn build_service(self) -> (ConnectionService, mpsc::Receiver<()>) {
+        // После завершения переданного `shutdown`, мы должны отправить сообщение о
+        // закрытии всем активным соединениям. Для этой цели используется широковещательный
+        // канал. В приведенном ниже коде игнорируется приемник широковещательной пары.
+        // Для создания приемника может использоваться метод передатчика `subscribe`.
+        let (notify_shutdown, _) = broadcast::channel(1);
+        let (shutdown_complete_tx, shutdown_complete_rx) = mpsc::channel(1);
+
+        // Если БД не передана в настройках, создаем новую. Фоновая задача
+        // очистки этой БД закрывается при уничтожении `_db_holder`.
+        let (db, db_holder) = match self.db {
+            Some(db) => (db, None),
+            None => {
+                let db_holder = DbDropGuard::new();
+                (db_holder.db(), Some(Arc::new(db_holder)))
+            }
+        };
+
+        let service = ConnectionService {
+            db,
+            _db_holder: db_holder,
+            limit_connections: Arc::new(Semaphore::new(self.max_connections)),
+            admission_queue: Arc::new(Semaphore::new(self.max_queued_connections)),
+            admission_timeout: self.admission_timeout,
+            notify_shutdown,
+            shutdown_complete_tx,
+            audit: self.audit,
+            max_subscriptions: self.max_subscriptions,
+            command_timeout: self.command_timeout,
+            strict_framing: self.strict_framing,
+            log_filter: self.log_filter,
+        };
+
+        (service, shutdown_complete_rx)
+    }
 }
 
-impl Listener {
+impl Default for Builder {
+    fn default() -> Builder {
+        Builder::new()
+    }
+}
+
+impl Server {
+    /// Создает настройки сервера по умолчанию.
+    pub fn builder() -> Builder {
+        Builder::new()
+    }
+
+    /// Возвращает адрес, на котором сервер принимает соединения.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Возвращает БД, используемую сервером.
+    ///
+    /// Позволяет читать и изменять данные сервера напрямую, минуя сеть.
+    pub fn db(&self) -> &Db {
+        &self.db
+    }
+
+    /// Отправляет серверу сигнал о закрытии.
+    ///
+    /// Сервер перестает принимать соединения, а активные соединения плавно
+    /// закрываются. Для ожидания завершения используется `join`.
+    pub fn shutdown(&self) {
+        let _ = self.shutdown.send(true);
+    }
+
+    /// Ждет завершения сервера.
+    ///
+    /// Сервер завершается после вызова `shutdown` или при невосстановимой
+    /// ошибке установки соединения. Если `shutdown` не вызывался, ожидание
+    /// может длиться бесконечно.
+    pub async fn join(self) -> crate::Result<()> {
+        let Server {
+            shutdown, handle, ..
+        } = self;
+
+        let res = handle.await;
+
+        // Передатчик удерживается до завершения задачи, поскольку его
+        // уничтожение является сигналом о закрытии
+        drop(shutdown);
+
+        res.map_err(Into::into)
+    }
+}
+
+/// Счетчик для присвоения идентификаторов соединениям. Идентификаторы
+/// уникальны в пределах процесса, в том числе для нескольких серверов.
+static NEXT_CONNECTION_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Создает состояние нового соединения со span'ом соединения.
+///
+/// Span соединения является родительским для span'ов всех команд этого
+/// соединения, что позволяет сопоставлять логи конкурентных обработчиков.
+/// Поле `client_name` заполняется после установки имени клиента.
+fn new_session(
+    peer: Option<SocketAddr>,
+    max_subscriptions: usize,
+    log_filter: Option<LogFilter>,
+) -> Session {
+    let id = NEXT_CONNECTION_ID.fetch_add(1, Ordering::Relaxed);
+
+    let span = info_span!(
+        "connection",
+        id,
+        peer = peer.map(field::display),
+        client_name = field::Empty
+    );
+
+    Session::new(id, span, max_subscriptions, log_filter)
+}
+
+/// Размер буфера `tokio::io::duplex`, используемого `connect_in_memory`.
+const DUPLEX_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Обрабатывает одно соединение поверх транспорта `io`.
+///
+/// Команды читаются из `io` и применяются к `db` так же, как для соединений,
+/// принятых `run`. Функция завершается, когда клиент закрывает соединение или
+/// при возникновении ошибки.
+///
+/// Транспорт может быть любым типом, реализующим `AsyncRead` и `AsyncWrite`,
+/// например, половиной `tokio::io::duplex`.
+pub async fn serve_connection<T>(io: T, db: Db) -> crate::Result<()>
+where
+    T: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+{
+    // Соединение не участвует в плавном закрытии сервера, но `Handler`
+    // ожидает сигнала о закрытии. Передатчики удерживаются до завершения
+    // обработки: уничтожение `notify_shutdown` было бы воспринято как сигнал
+    let (notify_shutdown, _) = broadcast::channel(1);
+    let (shutdown_complete_tx, _shutdown_complete_rx) = mpsc::channel(1);
+
+    let mut handler = Handler {
+        db,
+        connection: Connection::new(io),
+        shutdown: Shutdown::new(notify_shutdown.subscribe()),
+        _shutdown_complete: shutdown_complete_tx,
+        peer: None,
+        audit: None,
+        session: new_session(None, usize::MAX, None),
+        command_timeout: None,
+    };
+
+    let span = handler.session.span().clone();
+    handler.run().instrument(span).await
+}
+
+/// Создает `Client`, подключенный к серверу в текущем процессе.
+///
+/// Клиент и обработчик соединения обмениваются данными через
+/// `tokio::io::duplex`, поэтому сеть и порты не используются. Это удобно для
+/// тестов и примеров. Обработчик соединения выполняется в отдельной задаче
+/// и завершается при уничтожении клиента.
+///
+/// Все клиенты, созданные для одной `db`, работают с общим состоянием.
+///
+/// # Примеры
+///
+/// ```
+/// use mini_redis::{server, DbDropGuard};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let db = DbDropGuard::new();
+///     let mut client = server::connect_in_memory(db.db());
+///
+///     client.set("foo", "bar".into()).await.unwrap();
+///     let value = client.get("foo").await.unwrap().unwrap();
+///     assert_eq!(value, "bar");
+/// }
+/// ```
+pub fn connect_in_memory(db: Db) -> Client {
+    let (client_io, server_io) = tokio::io::duplex(DUPLEX_BUFFER_SIZE);
+
+    tokio::spawn(async move {
+        if let Err(err) = serve_connection(server_io, db).await {
+            error!(cause = ?err, "Ошибка соединения.");
+        }
+    });
+
+    Client::new(client_io)
+}
+
+impl<L: Accept> Listener<L> {
     /// Запускает сервер.
     ///
     /// Регистрирует входящие соединения. Для каждого соединения выделяется
@@ -203,90 +987,244 @@ impl Listener {
         info!("Установка соединения...");
 
         loop {
-            // Ждем доступности разрешения (permit).
-            //
-            // `acquire_owned()` возвращает разрешение, привязанное к семафору.
-            // Когда разрешение уничтожается, оно автоматически возвращается
-            // в семафор.
-            //
-            // `acquire_owned()` возвращает `Err`, когда семафор закрывается.
-            // Мы никогда этого не делаем, так что `unwrap()` является безопасным.
-            let permit = self
-                .limit_connections
-                .clone()
-                .acquire_owned()
-                .await
-                .unwrap();
-
             // Принимаем новый сокет. Это включает обработку ошибок.
             // Метод `accept` обрабатывает ошибки самостоятельно, так что
             // возникшая здесь ошибка является невосстановимой (non-recoverable).
-            let socket = self.accept().await?;
-
-            // Создаем необходимое состояние обработчика соединения.
-            let mut handler = Handler {
-                // Получаем общий обработчик БД.
-                db: self.db_holder.db(),
-
-                // Инициализируем состояние соединения. Это выделяет буферы
-                // чтения/записи для разбора кадров протокола `Redis`.
-                connection: Connection::new(socket),
-
-                // Подписываемся на уведомления о закрытии.
-                shutdown: Shutdown::new(self.notify_shutdown.subscribe()),
-
-                // Уведомляем приемник об уничтожении всех клонов.
-                _shutdown_complete: self.shutdown_complete_tx.clone(),
-            };
+            let (socket, peer) = self.accept().await?;
 
             // Выделяем новую задачу для обработки соединения. Задачи `Tokio` похожи на
             // асинхронные зеленые потоки (green threads) и выполняются параллельно.
-            tokio::spawn(async move {
-                // Обрабатываем соединение. Если возникает ошибка, печатаем ее.
-                if let Err(err) = handler.run().await {
-                    error!(cause = ?err, "Ошибка соединения.");
-                }
-                // Перемещаем разрешение в задачу и уничтожаем ее после завершения.
-                // Это возвращает разрешение семафору.
-                drop(permit);
-            });
+            // Ошибки соединения логируются сервисом.
+            tokio::spawn(self.service.call((socket, peer)));
         }
     }
 
     /// Принимает входящее соединение.
     ///
+    /// Возвращает сокет и адрес клиента.
+    ///
     /// Ошибки обрабатываются путем новых попыток установить соединение. Используется
-    /// стратегия экспоненциальной задержки. После первого провала задача ждет 1 секунду.
-    /// После второго провала задача ждет 2 секунды. Каждый последующий провал удваивает
-    /// задержку. Если попытка проваливается в шестой раз после 64 секунд ожидания,
-    /// функция возвращает ошибку.
-    async fn accept(&mut self) -> crate::Result<TcpStream> {
-        let mut backoff = 1;
+    /// стратегия экспоненциальной задержки, настраиваемая с помощью `AcceptRetry`.
+    /// Если количество повторных попыток исчерпано, функция возвращает ошибку.
+    async fn accept(&mut self) -> crate::Result<(L::Io, SocketAddr)> {
+        let mut retry = 0;
 
         // Пытаемся установить соединение несколько раз.
         loop {
             // Выполняем операцию установки соединения. Если сокет принят,
             // возвращаем его. Иначе, сохраняем ошибку.
             match self.listener.accept().await {
-                Ok((socket, _)) => return Ok(socket),
+                Ok(accepted) => return Ok(accepted),
                 Err(err) => {
-                    if backoff > 64 {
+                    if self
+                        .accept_retry
+                        .max_retries
+                        .is_some_and(|max_retries| retry >= max_retries)
+                    {
                         // Возвращаем ошибку.
                         return Err(err.into());
                     }
+
+                    warn!(cause = %err, retry, "Провал установки соединения, повторная попытка");
                 }
             }
 
             // Ставим выполнение на паузу в течение задержки.
-            time::sleep(Duration::from_secs(backoff)).await;
+            time::sleep(self.accept_retry.delay(retry)).await;
 
-            // Удваиваем задержку.
-            backoff *= 2;
+            retry += 1;
         }
     }
 }
 
+impl ConnectionService {
+    /// Возвращает БД, используемую сервисом.
+    pub fn db(&self) -> &Db {
+        &self.db
+    }
+
+    /// Отправляет сигнал о закрытии активным соединениям.
+    ///
+    /// Соединения завершают выполняемую работу, получают ошибку `SHUTDOWN` и
+    /// закрываются. Соединения, переданные сервису после вызова, этот сигнал
+    /// не получают.
+    pub fn shutdown(&self) {
+        let _ = self.notify_shutdown.send(());
+    }
+}
+
+impl<T> Service<(T, SocketAddr)> for ConnectionService
+where
+    T: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+{
+    type Response = ();
+    type Error = crate::Error;
+    type Future = Pin<Box<dyn Future<Output = crate::Result<()>> + Send>>;
+
+    /// Сервис всегда готов: соединения сверх ограничения ожидают в очереди
+    /// внутри футуры, возвращаемой `call`.
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<crate::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, (io, peer): (T, SocketAddr)) -> Self::Future {
+        // Инициализируем состояние соединения. Это выделяет буферы
+        // чтения/записи для разбора кадров протокола `Redis`.
+        let mut connection = Connection::new(io);
+        connection.set_strict_framing(self.strict_framing);
+
+        // Создаем необходимое состояние обработчика соединения.
+        let mut handler = Handler {
+            // Получаем общий обработчик БД.
+            db: self.db.clone(),
+
+            connection,
+
+            // Подписываемся на уведомления о закрытии.
+            shutdown: Shutdown::new(self.notify_shutdown.subscribe()),
+
+            // Уведомляем приемник об уничтожении всех клонов.
+            _shutdown_complete: self.shutdown_complete_tx.clone(),
+
+            peer: Some(peer),
+            audit: self.audit.clone(),
+            session: new_session(Some(peer), self.max_subscriptions, self.log_filter.clone()),
+            command_timeout: self.command_timeout,
+        };
+
+        let span = handler.session.span().clone();
+        debug!(parent: &span, "Соединение установлено");
+
+        let limit_connections = self.limit_connections.clone();
+        let admission_queue = self.admission_queue.clone();
+        let admission_timeout = self.admission_timeout;
+
+        // Футура не удерживает сервис, поэтому его уничтожение является
+        // сигналом о закрытии. Все события, включая span'ы команд,
+        // записываются в span соединения.
+        let task = async move {
+            // Ждем разрешения (permit) на обработку соединения. Разрешение
+            // возвращается семафору при уничтожении.
+            let permit = match handler
+                .admit(limit_connections, admission_queue, admission_timeout)
+                .await
+            {
+                Ok(Some(permit)) => permit,
+                Ok(None) => return Ok(()),
+                Err(err) => {
+                    error!(cause = ?err, "Ошибка соединения.");
+                    return Err(err);
+                }
+            };
+            metrics::connection_opened();
+
+            // Обрабатываем соединение. Если возникает ошибка, печатаем ее.
+            let res = handler.run().await;
+            if let Err(err) = &res {
+                error!(cause = ?err, "Ошибка соединения.");
+            }
+            metrics::connection_closed();
+            debug!("Соединение закрыто");
+
+            // Перемещаем разрешение в задачу и уничтожаем ее после завершения.
+            // Это возвращает разрешение семафору.
+            drop(permit);
+
+            res
+        };
+
+        Box::pin(task.instrument(span))
+    }
+}
+
 impl Handler {
+    /// Ждет разрешения на обработку соединения.
+    ///
+    /// Если все разрешения `limit` заняты, соединение ожидает в очереди
+    /// `queue` не дольше `timeout`. Соединение, для которого нет места в
+    /// очереди или время ожидания которого истекло, получает ошибку и
+    /// закрывается. В этом случае, а также при закрытии сервера, возвращается
+    /// `None`.
+    async fn admit(
+        &mut self,
+        limit: Arc<Semaphore>,
+        queue: Arc<Semaphore>,
+        timeout: Option<Duration>,
+    ) -> crate::Result<Option<OwnedSemaphorePermit>> {
+        if let Ok(permit) = limit.clone().try_acquire_owned() {
+            return Ok(Some(permit));
+        }
+
+        // Место в очереди освобождается при уничтожении `_queued`
+        let _queued = match queue.try_acquire_owned() {
+            Ok(queued) => queued,
+            Err(_) => {
+                self.reject().await?;
+                return Ok(None);
+            }
+        };
+        metrics::connection_queued();
+
+        // Семафор справедлив (fair): разрешения выдаются в порядке запросов.
+        //
+        // `acquire_owned()` возвращает `Err`, когда семафор закрывается.
+        // Мы никогда этого не делаем, так что `unwrap()` является безопасным.
+        let acquire = async {
+            let permit = limit.acquire_owned();
+            match timeout {
+                Some(timeout) => time::timeout(timeout, permit).await.ok(),
+                None => Some(permit.await),
+            }
+            .map(Result::unwrap)
+        };
+
+        let permit = tokio::select! {
+            permit = acquire => permit,
+            _ = self.shutdown.recv() => {
+                metrics::connection_dequeued();
+                self.notify_shutdown().await;
+                return Ok(None);
+            }
+        };
+        metrics::connection_dequeued();
+
+        match permit {
+            Some(permit) => Ok(Some(permit)),
+            None => {
+                self.reject().await?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Сообщает клиенту, что соединение не может быть обработано, и
+    /// закрывает соединение.
+    async fn reject(&mut self) -> crate::Result<()> {
+        debug!("Соединение отклонено");
+
+        let response = Frame::Error("ERR max number of clients reached".to_string());
+        self.connection.write_frame(&response).await?;
+        self.connection.shutdown().await?;
+
+        Ok(())
+    }
+
+    /// Уведомляет клиента о закрытии сервера и закрывает соединение.
+    ///
+    /// Клиент получает ошибку `SHUTDOWN`, что позволяет отличить плановое
+    /// закрытие сервера от сетевой ошибки. Клиент мог закрыть соединение или
+    /// не читать ответы, поэтому ошибки записи игнорируются, а время записи
+    /// ограничено.
+    async fn notify_shutdown(&mut self) {
+        let response = Frame::Error("SHUTDOWN server is going away".to_string());
+
+        let notify = async {
+            self.connection.write_frame(&response).await?;
+            self.connection.shutdown().await
+        };
+        let _ = time::timeout(SHUTDOWN_NOTIFY_TIMEOUT, notify).await;
+    }
+
     /// Обрабатывает соединение.
     ///
     /// Кадры запроса читаются из сокета и обрабатываются. Ответы
@@ -297,7 +1235,8 @@ impl Handler {
     /// https://redis.io/topics/pipelining
     ///
     /// При получении сигнала о закрытии, соединение обрабатывается до
-    /// безопасного состояния, после чего прерывается.
+    /// безопасного состояния, клиент уведомляется о закрытии, после чего
+    /// соединение прерывается.
     #[instrument(skip(self))]
     async fn run(&mut self) -> crate::Result<()> {
         // Пока не получен сигнал о закрытии, пытаемся читать
@@ -307,9 +1246,9 @@ impl Handler {
             let maybe_frame = tokio::select! {
                 res = self.connection.read_frame() => res?,
                 _ = self.shutdown.recv() => {
-                    // Если получен сигнал о закрытии, возвращаемся из `run()`.
+                    // Если получен сигнал о закрытии, выходим из цикла.
                     // Это приводит к закрытию задачи.
-                    return Ok(());
+                    break;
                 }
             };
 
@@ -336,16 +1275,99 @@ impl Handler {
             // в виде пар "ключ-значение".
             debug!(?cmd);
 
+            // Названия неизвестных команд не используются в метриках,
+            // чтобы количество значений атрибута было ограниченным
+            let name = match &cmd {
+                Command::Unknown(_) => "unknown".to_string(),
+                cmd => cmd.get_name().to_string(),
+            };
+            // Ключи запоминаются до выполнения команды, которое ее поглощает
+            let write_keys: Vec<String> = match &self.audit {
+                Some(_) => cmd.write_keys().into_iter().map(String::from).collect(),
+                None => vec![],
+            };
+            // `SUBSCRIBE` выполняется до отписки клиента, поэтому время ее
+            // выполнения не ограничивается
+            let timeout = match &cmd {
+                Command::Subscribe(_) => None,
+                _ => self.command_timeout,
+            };
+
+            // Сбои не внедряются в команды `DEBUG`, иначе их нельзя было бы
+            // отключить
+            #[cfg(feature = "chaos")]
+            if !matches!(cmd, Command::Debug(_)) {
+                let chaos = self.db.chaos();
+
+                if chaos.drop_connection() {
+                    warn!(command = %name, "Соединение разорвано (chaos)");
+                    return Ok(());
+                }
+
+                if let Some(delay) = chaos.latency() {
+                    time::sleep(delay).await;
+                }
+            }
+
+            let start = Instant::now();
+            self.session
+                .set_deadline(timeout.map(|timeout| start + timeout));
+
             // Выполняем работу, необходимую для применения команды. Это может приводить к
             // мутированию состояния БД.
             //
             // Соединение передается в функцию `apply`, что позволяет
             // команде писать ответ прямо в соединение. В случае
             // pub/sub клиенту может быть отправлено несколько кадров.
-            cmd.apply(&self.db, &mut self.connection, &mut self.shutdown)
-                .await?;
+            let apply = cmd.apply(
+                &self.db,
+                &mut self.connection,
+                &mut self.shutdown,
+                &mut self.session,
+            );
+            let res = match timeout {
+                Some(timeout) => time::timeout(timeout, apply).await,
+                None => Ok(apply.await),
+            };
+
+            let res = match res {
+                Ok(res) => res,
+                Err(_) => {
+                    metrics::command_executed(&name, start.elapsed(), false);
+                    warn!(command = %name, "Превышено время выполнения команды");
+
+                    // Часть ответа уже могла быть записана в соединение, поэтому
+                    // после ошибки соединение закрывается. Клиент может не читать
+                    // ответы, поэтому запись ошибки также ограничена по времени
+                    let response = Frame::Error("ERR command timed out".to_string());
+                    let _ = time::timeout(timeout.unwrap(), self.connection.write_frame(&response))
+                        .await;
+                    return Ok(());
+                }
+            };
+
+            metrics::command_executed(&name, start.elapsed(), res.is_ok());
+
+            // В журнал попадают только команды, действительно изменившие
+            // данные
+            if let (true, Some(audit)) = (res?, &self.audit) {
+                for key in &write_keys {
+                    audit.record(self.peer, &name, key);
+                }
+            }
+
+            // Клиент запросил закрытие соединения командой `QUIT`. Ответ уже
+            // отправлен, поэтому соединение закрывается для записи: клиент
+            // получает конец потока, а не сброс соединения
+            if self.session.is_closed() {
+                self.connection.shutdown().await?;
+                return Ok(());
+            }
         }
 
+        // Цикл завершается только при получении сигнала о закрытии
+        self.notify_shutdown().await;
+
         Ok(())
     }
 }
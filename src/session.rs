@@ -0,0 +1,102 @@
+use crate::log_filter::LogFilter;
+
+use tokio::time::Instant;
+use tracing::Span;
+
+/// Состояние соединения, доступное командам.
+///
+/// Создается обработчиком соединения и передается в `Command::apply`.
+/// В отличие от `Db`, состояние не распределяется между соединениями.
+#[derive(Debug)]
+pub(crate) struct Session {
+    /// Идентификатор соединения. Уникален в пределах процесса.
+    id: u64,
+
+    /// Span соединения. Имя клиента записывается в поле `client_name`.
+    span: Span,
+
+    /// Максимальное количество каналов, на которые может быть подписано
+    /// соединение.
+    max_subscriptions: usize,
+
+    /// `true`, если клиент запросил закрытие соединения командой `QUIT`.
+    closed: bool,
+
+    /// Фильтр логов сервера. `None`, если изменение уровня логирования не
+    /// поддерживается.
+    log_filter: Option<LogFilter>,
+
+    /// Момент, до которого должна завершиться выполняемая команда. `None`,
+    /// если время выполнения не ограничено.
+    deadline: Option<Instant>,
+}
+
+impl Session {
+    /// Создает состояние соединения с идентификатором `id`.
+    pub(crate) fn new(
+        id: u64,
+        span: Span,
+        max_subscriptions: usize,
+        log_filter: Option<LogFilter>,
+    ) -> Session {
+        Session {
+            id,
+            span,
+            max_subscriptions,
+            closed: false,
+            log_filter,
+            deadline: None,
+        }
+    }
+
+    /// Возвращает идентификатор соединения.
+    pub(crate) fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Устанавливает имя клиента, установленное командой `HELLO ... SETNAME`.
+    ///
+    /// Имя записывается в поле `client_name` span'а соединения.
+    pub(crate) fn set_name(&mut self, name: &str) {
+        self.span.record("client_name", name);
+    }
+
+    /// Возвращает span соединения.
+    pub(crate) fn span(&self) -> &Span {
+        &self.span
+    }
+
+    /// Возвращает максимальное количество подписок соединения.
+    pub(crate) fn max_subscriptions(&self) -> usize {
+        self.max_subscriptions
+    }
+
+    /// Отмечает, что соединение должно быть закрыто после отправки ответа.
+    pub(crate) fn close(&mut self) {
+        self.closed = true;
+    }
+
+    /// Возвращает `true`, если соединение должно быть закрыто.
+    pub(crate) fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Возвращает фильтр логов сервера.
+    pub(crate) fn log_filter(&self) -> Option<&LogFilter> {
+        self.log_filter.as_ref()
+    }
+
+    /// Устанавливает момент, до которого должна завершиться следующая
+    /// команда.
+    pub(crate) fn set_deadline(&mut self, deadline: Option<Instant>) {
+        self.deadline = deadline;
+    }
+
+    /// Возвращает момент, до которого должна завершиться команда.
+    ///
+    /// Команды, перебирающие ключи под мьютексом БД, передают его в `Db`,
+    /// поскольку синхронную работу нельзя прервать извне.
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+}
@@ -0,0 +1,128 @@
+//! Снимок пространства ключей.
+//!
+//! Снимок создается методом `Db::snapshot` и содержит все ключи, их значения
+//! и оставшееся время жизни на момент создания. Последующие изменения БД на
+//! снимок не влияют. Используется для экспорта данных (см. `crate::export`).
+
+use crate::value::Value;
+use crate::zset::SortedSet;
+
+use bytes::Bytes;
+use std::time::Duration;
+use std::vec;
+
+/// Сущность снимка.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct SnapshotEntry {
+    /// Ключ.
+    pub key: String,
+
+    /// Значение.
+    pub value: SnapshotValue,
+
+    /// Оставшееся время жизни на момент создания снимка. `None`, если время
+    /// жизни не ограничено.
+    pub ttl: Option<Duration>,
+}
+
+/// Значение сущности снимка.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum SnapshotValue {
+    /// Строка (в том числе число или документ JSON в виде текста).
+    String(Bytes),
+
+    /// Хеш: поля и их значения в порядке возрастания полей.
+    Hash(Vec<(String, Bytes)>),
+
+    /// Сортированное множество: элементы и их оценки в порядке возрастания
+    /// оценок.
+    SortedSet(Vec<(String, f64)>),
+}
+
+/// Итератор по сущностям снимка.
+///
+/// Сущности возвращаются в произвольном порядке.
+#[derive(Debug)]
+pub struct Snapshot {
+    /// Сущности, еще не возвращенные итератором.
+    entries: vec::IntoIter<SnapshotEntry>,
+}
+
+impl SnapshotEntry {
+    /// Создает сущность снимка.
+    pub fn new(key: String, value: SnapshotValue, ttl: Option<Duration>) -> SnapshotEntry {
+        SnapshotEntry { key, value, ttl }
+    }
+}
+
+impl SnapshotValue {
+    /// Копирует значение из БД.
+    pub(crate) fn new(value: &Value) -> SnapshotValue {
+        match value {
+            Value::Hash(hash) => {
+                let mut fields: Vec<_> = hash
+                    .iter()
+                    .map(|(field, value)| (field.clone(), value.clone()))
+                    .collect();
+                fields.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+
+                SnapshotValue::Hash(fields)
+            }
+            Value::SortedSet(zset) => SnapshotValue::SortedSet(
+                zset.iter()
+                    .map(|(member, score)| (member.to_string(), score))
+                    .collect(),
+            ),
+            value => SnapshotValue::String(value.to_bytes().unwrap_or_default()),
+        }
+    }
+
+    /// Преобразует значение в значение БД.
+    ///
+    /// Оценки сортированного множества не должны быть `NaN`.
+    pub(crate) fn into_value(self) -> Value {
+        match self {
+            SnapshotValue::String(value) => Value::from_bytes(value),
+            SnapshotValue::Hash(fields) => Value::Hash(Box::new(fields.into_iter().collect())),
+            SnapshotValue::SortedSet(members) => {
+                let mut zset = SortedSet::default();
+                for (member, score) in members {
+                    zset.insert(member, score);
+                }
+
+                Value::SortedSet(Box::new(zset))
+            }
+        }
+    }
+}
+
+impl From<Bytes> for SnapshotValue {
+    fn from(value: Bytes) -> SnapshotValue {
+        SnapshotValue::String(value)
+    }
+}
+
+impl Snapshot {
+    /// Создает снимок из сущностей, скопированных из БД.
+    pub(crate) fn new(entries: Vec<SnapshotEntry>) -> Snapshot {
+        Snapshot {
+            entries: entries.into_iter(),
+        }
+    }
+}
+
+impl Iterator for Snapshot {
+    type Item = SnapshotEntry;
+
+    fn next(&mut self) -> Option<SnapshotEntry> {
+        self.entries.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Snapshot {}
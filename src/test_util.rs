@@ -0,0 +1,321 @@
+//! Утилиты для тестирования приложений, использующих `mini-redis`.
+//!
+//! `TestServer` запускает изолированный сервер, а `MockClient` позволяет
+//! тестировать код, зависящий от `RedisClient`, без сервера.
+//!
+//! Доступны с флагом `test-util`:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! mini-redis = { version = "0.4", features = ["test-util"] }
+//! ```
+
+use crate::clients::client::{get_reply, ping_reply, publish_reply, set_reply};
+use crate::clients::{BlockingRedisClient, RedisClient, ServerError};
+use crate::cmd::{Get, Ping, Publish, Set};
+use crate::server::{self, Server};
+use crate::{Client, Db, DbDropGuard, Frame};
+
+use bytes::Bytes;
+use std::collections::VecDeque;
+use std::future::{self, Future};
+use std::net::SocketAddr;
+use std::thread;
+use std::time::Duration;
+
+/// Изолированный сервер для тестов.
+///
+/// Сервер запускается на свободном порту (`TestServer::start`) или в текущем
+/// процессе без TCP (`TestServer::in_memory`). Каждый экземпляр имеет
+/// собственную БД, поэтому тесты не влияют друг на друга.
+///
+/// При уничтожении `TestServer` сервер закрывается.
+///
+/// # Примеры
+///
+/// ```
+/// use mini_redis::test_util::TestServer;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let server = TestServer::start().await;
+///     let mut client = server.client().await;
+///
+///     client.set("foo", "bar".into()).await.unwrap();
+///
+///     // Данные сервера доступны напрямую
+///     assert_eq!(server.db().get("foo").unwrap(), "bar");
+/// }
+/// ```
+#[derive(Debug)]
+pub struct TestServer {
+    /// Способ подключения клиентов к серверу.
+    transport: Transport,
+
+    /// БД сервера.
+    db: Db,
+}
+
+#[derive(Debug)]
+enum Transport {
+    /// Сервер TCP. Уничтожение обработчика закрывает сервер.
+    Tcp(Server),
+
+    /// Сервер в текущем процессе. Каждый клиент обслуживается отдельной
+    /// задачей, которая завершается при уничтожении клиента. `DbDropGuard`
+    /// закрывает фоновую задачу очистки БД.
+    InMemory { _db_holder: DbDropGuard },
+}
+
+impl TestServer {
+    /// Запускает сервер TCP на свободном порту `127.0.0.1`.
+    ///
+    /// # Паники
+    ///
+    /// Паникует, если сервер не удалось запустить.
+    pub async fn start() -> TestServer {
+        let server = Server::builder()
+            .bind("127.0.0.1:0")
+            .await
+            .expect("Провал запуска тестового сервера");
+        let db = server.db().clone();
+
+        TestServer {
+            transport: Transport::Tcp(server),
+            db,
+        }
+    }
+
+    /// Создает сервер в текущем процессе. Клиенты подключаются к нему
+    /// через `tokio::io::duplex`, сеть не используется.
+    ///
+    /// Должен вызываться в контексте среды выполнения `Tokio`.
+    pub fn in_memory() -> TestServer {
+        let db_holder = DbDropGuard::new();
+        let db = db_holder.db();
+
+        TestServer {
+            transport: Transport::InMemory {
+                _db_holder: db_holder,
+            },
+            db,
+        }
+    }
+
+    /// Возвращает адрес сервера TCP.
+    ///
+    /// # Паники
+    ///
+    /// Паникует, если сервер создан с помощью `in_memory`.
+    pub fn addr(&self) -> SocketAddr {
+        match &self.transport {
+            Transport::Tcp(server) => server.local_addr(),
+            Transport::InMemory { .. } => panic!("У сервера в текущем процессе нет адреса"),
+        }
+    }
+
+    /// Возвращает нового клиента, подключенного к серверу.
+    ///
+    /// # Паники
+    ///
+    /// Паникует, если подключиться к серверу не удалось.
+    pub async fn client(&self) -> Client {
+        match &self.transport {
+            Transport::Tcp(server) => Client::connect(server.local_addr())
+                .await
+                .expect("Провал подключения к тестовому серверу"),
+            Transport::InMemory { .. } => server::connect_in_memory(self.db.clone()),
+        }
+    }
+
+    /// Возвращает БД сервера для чтения и изменения данных напрямую.
+    pub fn db(&self) -> &Db {
+        &self.db
+    }
+}
+
+/// Клиент с заранее заданными ответами для модульных тестов кода,
+/// использующего `RedisClient` или `BlockingRedisClient`.
+///
+/// Ожидаемые команды и ответы на них задаются с помощью `expect`. Каждый
+/// вызов метода клиента сравнивает кадр запроса со следующей ожидаемой
+/// командой и возвращает заданный ответ. Кадр `Error` в ответе
+/// преобразуется в `Err`, как и в `Client`.
+///
+/// При уничтожении `MockClient` паникует, если не все ожидаемые команды
+/// были выполнены.
+///
+/// # Примеры
+///
+/// ```
+/// use mini_redis::clients::RedisClient;
+/// use mini_redis::test_util::MockClient;
+/// use mini_redis::Frame;
+///
+/// async fn greeting<C: RedisClient>(client: &mut C) -> String {
+///     match client.get("name").await.unwrap() {
+///         Some(name) => format!("Привет, {}!", String::from_utf8_lossy(&name)),
+///         None => "Привет!".to_string(),
+///     }
+/// }
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let mut client = MockClient::new();
+///     client
+///         .expect(["get", "name"], Frame::Bulk("Иван".into()))
+///         .expect(["get", "name"], Frame::Null);
+///
+///     assert_eq!(greeting(&mut client).await, "Привет, Иван!");
+///     assert_eq!(greeting(&mut client).await, "Привет!");
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct MockClient {
+    /// Ожидаемые запросы и ответы на них.
+    script: VecDeque<(Frame, Frame)>,
+}
+
+impl MockClient {
+    /// Создает клиента без ожидаемых команд.
+    pub fn new() -> MockClient {
+        MockClient::default()
+    }
+
+    /// Добавляет ожидаемую команду и ответ на нее.
+    ///
+    /// Команда задается аргументами в том виде, в котором ее отправляет
+    /// `Client`: название команды в нижнем регистре, затем аргументы.
+    /// Например, `set_expires` отправляет `["set", key, value, "px", ms]`.
+    pub fn expect<I, T>(&mut self, command: I, response: Frame) -> &mut MockClient
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<Bytes>,
+    {
+        let request = command
+            .into_iter()
+            .map(|arg| Frame::Bulk(arg.into()))
+            .collect();
+
+        self.expect_frame(Frame::Array(request), response)
+    }
+
+    /// Добавляет ожидаемый кадр запроса и ответ на него.
+    pub fn expect_frame(&mut self, request: Frame, response: Frame) -> &mut MockClient {
+        self.script.push_back((request, response));
+        self
+    }
+
+    /// Возвращает количество ожидаемых, но еще не выполненных команд.
+    pub fn remaining(&self) -> usize {
+        self.script.len()
+    }
+
+    /// Выполняет запрос `frame` и возвращает заданный ответ.
+    ///
+    /// Возвращает ошибку, если запрос не совпадает с ожидаемым или
+    /// ожидаемые команды закончились.
+    pub fn send_frame(&mut self, frame: Frame) -> crate::Result<Frame> {
+        let frame = normalize_request(frame);
+
+        let (request, response) = match self.script.pop_front() {
+            Some(expected) => expected,
+            None => return Err(format!("Неожиданная команда: {}", frame).into()),
+        };
+
+        if normalize_request(request.clone()) != frame {
+            return Err(format!("Ожидалась команда `{}`, получена `{}`", request, frame).into());
+        }
+
+        match response {
+            Frame::Error(msg) => Err(ServerError::parse(msg).into()),
+            frame => Ok(frame),
+        }
+    }
+}
+
+/// Представляет целочисленные аргументы команды как строки. Сервер
+/// принимает аргументы в обоих видах, поэтому они считаются одинаковыми.
+fn normalize_request(frame: Frame) -> Frame {
+    match frame {
+        Frame::Array(args) => Frame::Array(
+            args.into_iter()
+                .map(|arg| match arg {
+                    Frame::Integer(value) => Frame::Bulk(value.to_string().into()),
+                    arg => arg,
+                })
+                .collect(),
+        ),
+        frame => frame,
+    }
+}
+
+impl Drop for MockClient {
+    fn drop(&mut self) {
+        // Повторная паника во время раскрутки стека прерывает процесс
+        if !self.script.is_empty() && !thread::panicking() {
+            panic!(
+                "Не выполнены ожидаемые команды: {:?}",
+                self.script
+                    .iter()
+                    .map(|(request, _)| request.to_string())
+                    .collect::<Vec<_>>()
+            );
+        }
+    }
+}
+
+impl BlockingRedisClient for MockClient {
+    fn ping(&mut self, msg: Option<Bytes>) -> crate::Result<Bytes> {
+        ping_reply(self.send_frame(Ping::new(msg).into_frame())?)
+    }
+
+    fn get(&mut self, key: &str) -> crate::Result<Option<Bytes>> {
+        get_reply(self.send_frame(Get::new(key).into_frame())?)
+    }
+
+    fn set(&mut self, key: &str, value: Bytes) -> crate::Result<()> {
+        set_reply(self.send_frame(Set::new(key, value, None).into_frame())?)
+    }
+
+    fn set_expires(&mut self, key: &str, value: Bytes, expiration: Duration) -> crate::Result<()> {
+        set_reply(self.send_frame(Set::new(key, value, Some(expiration)).into_frame())?)
+    }
+
+    fn publish(&mut self, channel: &str, message: Bytes) -> crate::Result<u64> {
+        publish_reply(self.send_frame(Publish::new(channel, message).into_frame())?)
+    }
+}
+
+impl RedisClient for MockClient {
+    fn ping(&mut self, msg: Option<Bytes>) -> impl Future<Output = crate::Result<Bytes>> + Send {
+        future::ready(BlockingRedisClient::ping(self, msg))
+    }
+
+    fn get(&mut self, key: &str) -> impl Future<Output = crate::Result<Option<Bytes>>> + Send {
+        future::ready(BlockingRedisClient::get(self, key))
+    }
+
+    fn set(&mut self, key: &str, value: Bytes) -> impl Future<Output = crate::Result<()>> + Send {
+        future::ready(BlockingRedisClient::set(self, key, value))
+    }
+
+    fn set_expires(
+        &mut self,
+        key: &str,
+        value: Bytes,
+        expiration: Duration,
+    ) -> impl Future<Output = crate::Result<()>> + Send {
+        future::ready(BlockingRedisClient::set_expires(
+            self, key, value, expiration,
+        ))
+    }
+
+    fn publish(
+        &mut self,
+        channel: &str,
+        message: Bytes,
+    ) -> impl Future<Output = crate::Result<u64>> + Send {
+        future::ready(BlockingRedisClient::publish(self, channel, message))
+    }
+}
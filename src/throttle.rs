@@ -0,0 +1,121 @@
+//! Ограничение частоты запросов по алгоритму GCRA (generic cell rate
+//! algorithm).
+//!
+//! Используется командой `THROTTLE` и доступна приложениям, встраивающим
+//! `Db`, через `Db::throttle`.
+//!
+//! Ограничение задается максимальным всплеском `max_burst` и скоростью
+//! `count` запросов за период `period`. Запросы равномерно распределяются по
+//! периоду с интервалом `period / count`, а до `max_burst` запросов сверх
+//! этого могут выполняться без ожидания. Таким образом, подряд без ожидания
+//! выполняется до `max_burst + 1` запросов.
+//!
+//! Состояние ограничения - теоретическое время прибытия следующего запроса
+//! (TAT, theoretical arrival time) - хранится в значении ключа как число
+//! наносекунд с начала эпохи UNIX. Время жизни ключа совпадает с моментом,
+//! когда ограничение возвращается в исходное состояние, поэтому ключи
+//! неактивных ограничений удаляются автоматически.
+
+use std::convert::TryFrom;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Результат проверки ограничения.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Throttle {
+    /// `true`, если запрос разрешен.
+    pub allowed: bool,
+
+    /// Максимальное количество запросов, выполняемых подряд без ожидания
+    /// (`max_burst + 1`).
+    pub limit: u64,
+
+    /// Количество запросов, которые могут быть выполнены без ожидания.
+    pub remaining: u64,
+
+    /// Время, через которое запрос будет разрешен. `None`, если запрос
+    /// разрешен или не может быть разрешен никогда, поскольку его размер
+    /// превышает `limit`.
+    pub retry_after: Option<Duration>,
+
+    /// Время, через которое ограничение вернется в исходное состояние.
+    pub reset_after: Duration,
+}
+
+/// Проверяет запрос размера `quantity` при теоретическом времени прибытия
+/// `tat` в момент `now`.
+///
+/// Возвращает результат проверки и новое теоретическое время прибытия, если
+/// запрос разрешен. Время указывается в наносекундах с начала эпохи UNIX.
+pub(crate) fn gcra(
+    tat: Option<i64>,
+    now: i64,
+    max_burst: u64,
+    count: u64,
+    period: Duration,
+    quantity: u64,
+) -> (Throttle, Option<i64>) {
+    let limit = max_burst.saturating_add(1);
+
+    // Интервал между равномерно распределенными запросами и допустимое
+    // опережение расписания
+    let emission_interval = (period.as_nanos() / count as u128).max(1) as i128;
+    let tolerance = emission_interval.saturating_mul(limit as i128);
+    let increment = emission_interval.saturating_mul(quantity as i128);
+
+    let now = now as i128;
+    let tat = tat.map_or(now, |tat| (tat as i128).max(now));
+    let new_tat = tat.saturating_add(increment);
+
+    // Запрос разрешен, если с его учетом расписание опережает текущее время
+    // не более чем на `tolerance`
+    let diff = now - (new_tat - tolerance);
+
+    let (allowed, retry_after, ttl) = if diff < 0 {
+        let retry_after = if increment <= tolerance {
+            Some(nanos(-diff))
+        } else {
+            None
+        };
+        (false, retry_after, tat - now)
+    } else {
+        (true, None, new_tat - now)
+    };
+
+    let next = tolerance - ttl;
+    let remaining = if next > 0 {
+        (next / emission_interval) as u64
+    } else {
+        0
+    };
+
+    let throttle = Throttle {
+        allowed,
+        limit,
+        remaining,
+        retry_after,
+        reset_after: nanos(ttl),
+    };
+
+    let new_tat = if allowed {
+        Some(i64::try_from(new_tat).unwrap_or(i64::MAX))
+    } else {
+        None
+    };
+
+    (throttle, new_tat)
+}
+
+/// Возвращает текущее время в наносекундах с начала эпохи UNIX.
+pub(crate) fn now() -> i64 {
+    let elapsed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+
+    i64::try_from(elapsed.as_nanos()).unwrap_or(i64::MAX)
+}
+
+/// Преобразует неотрицательное количество наносекунд в `Duration`.
+fn nanos(nanos: i128) -> Duration {
+    Duration::from_nanos(u64::try_from(nanos.max(0)).unwrap_or(u64::MAX))
+}
@@ -0,0 +1,209 @@
+//! Прием соединений с помощью `io_uring` (только Linux).
+//!
+//! Доступен с флагом `io-uring` и предназначен для сравнения
+//! производительности сервера с вводом-выводом на основе завершений.
+//!
+//! `UringListener` запускает отдельный поток со средой выполнения
+//! `tokio-uring`, которая принимает соединения TCP, читает из сокетов и
+//! пишет в них через `io_uring`. Типы `tokio-uring` не реализуют `Send`,
+//! поэтому каждое соединение передается серверу как `DuplexStream`, а данные
+//! копируются между ним и сокетом в потоке `io_uring`. Благодаря этому
+//! `Connection`, обработчик соединений и команды остаются без изменений.
+//!
+//! # Примеры
+//!
+//! ```no_run
+//! use mini_redis::server::Server;
+//! use mini_redis::uring::UringListener;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let listener = UringListener::bind("127.0.0.1:6379").await.unwrap();
+//!     let server = Server::builder().serve(listener).unwrap();
+//!     server.join().await.unwrap();
+//! }
+//! ```
+
+use crate::server::Accept;
+
+use std::io;
+use std::net::{Shutdown, SocketAddr};
+use std::thread;
+use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
+use tokio::net::{lookup_host, ToSocketAddrs};
+use tokio::sync::{mpsc, oneshot};
+use tokio_uring::buf::IoBuf;
+use tokio_uring::net::{TcpListener, TcpStream};
+use tracing::debug;
+
+/// Размер буфера копирования между сокетом и `DuplexStream`.
+const BUFFER_SIZE: usize = 16 * 1024;
+
+/// Количество принятых соединений, ожидающих обработки сервером.
+const ACCEPT_BACKLOG: usize = 128;
+
+/// Источник соединений TCP, принимаемых через `io_uring`.
+///
+/// Поток `io_uring` завершается после уничтожения обработчика и закрытия
+/// всех принятых им соединений.
+#[derive(Debug)]
+pub struct UringListener {
+    /// Соединения, принятые потоком `io_uring`.
+    incoming: mpsc::Receiver<io::Result<(DuplexStream, SocketAddr)>>,
+
+    /// Адрес, на котором принимаются соединения.
+    local_addr: SocketAddr,
+}
+
+impl UringListener {
+    /// Запускает поток `io_uring` и привязывает обработчик к `addr`.
+    ///
+    /// Возвращает ошибку, если ядро не поддерживает `io_uring`.
+    pub async fn bind(addr: impl ToSocketAddrs) -> io::Result<UringListener> {
+        let addr = lookup_host(addr)
+            .await?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Адрес не найден"))?;
+
+        let (ready_tx, ready_rx) = oneshot::channel();
+        let (incoming_tx, incoming) = mpsc::channel(ACCEPT_BACKLOG);
+
+        thread::Builder::new()
+            .name("mini-redis-io-uring".to_string())
+            .spawn(move || {
+                let rt = match tokio_uring::Runtime::new(&tokio_uring::builder()) {
+                    Ok(rt) => rt,
+                    Err(err) => {
+                        let _ = ready_tx.send(Err(err));
+                        return;
+                    }
+                };
+
+                rt.block_on(async move {
+                    let listener = match TcpListener::bind(addr) {
+                        Ok(listener) => listener,
+                        Err(err) => {
+                            let _ = ready_tx.send(Err(err));
+                            return;
+                        }
+                    };
+
+                    if ready_tx.send(listener.local_addr()).is_ok() {
+                        run(listener, incoming_tx).await;
+                    }
+                });
+            })?;
+
+        let local_addr = ready_rx
+            .await
+            .map_err(|_| io::Error::other("Поток io_uring завершился"))??;
+
+        Ok(UringListener {
+            incoming,
+            local_addr,
+        })
+    }
+}
+
+impl Accept for UringListener {
+    type Io = DuplexStream;
+
+    async fn accept(&mut self) -> io::Result<(Self::Io, SocketAddr)> {
+        match self.incoming.recv().await {
+            Some(res) => res,
+            None => Err(io::Error::other("Поток io_uring завершился")),
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        Ok(self.local_addr)
+    }
+}
+
+/// Принимает соединения и передает их в `incoming`, пока `UringListener` не
+/// будет уничтожен, затем ждет закрытия принятых соединений.
+async fn run(
+    listener: TcpListener,
+    incoming: mpsc::Sender<io::Result<(DuplexStream, SocketAddr)>>,
+) {
+    // Каждая задача копирования удерживает клон отправителя. Получатель
+    // возвращает `None`, когда все задачи завершились
+    let (complete_tx, mut complete_rx) = mpsc::channel::<()>(1);
+
+    loop {
+        let res = tokio::select! {
+            res = listener.accept() => res,
+            _ = incoming.closed() => break,
+        };
+
+        let (socket, peer) = match res {
+            Ok(accepted) => accepted,
+            Err(err) => {
+                // Ошибку обрабатывает сервер: повторяет прием или
+                // завершается
+                if incoming.send(Err(err)).await.is_err() {
+                    break;
+                }
+                continue;
+            }
+        };
+
+        let (local, remote) = tokio::io::duplex(BUFFER_SIZE);
+        if incoming.send(Ok((remote, peer))).await.is_err() {
+            break;
+        }
+
+        let complete = complete_tx.clone();
+        tokio_uring::spawn(async move {
+            if let Err(err) = copy(socket, local).await {
+                debug!(%peer, cause = %err, "Ошибка соединения io_uring");
+            }
+            drop(complete);
+        });
+    }
+
+    drop(complete_tx);
+    let _ = complete_rx.recv().await;
+}
+
+/// Копирует данные между сокетом и `DuplexStream` в обоих направлениях.
+async fn copy(socket: TcpStream, io: DuplexStream) -> io::Result<()> {
+    let (mut reader, mut writer) = tokio::io::split(io);
+
+    // От клиента к серверу. Закрытие сокета клиентом закрывает поток записи,
+    // и `Connection` получает конец потока
+    let inbound = async {
+        let mut buf = vec![0; BUFFER_SIZE];
+        loop {
+            let (res, read_buf) = socket.read(buf).await;
+            buf = read_buf;
+
+            let n = res?;
+            if n == 0 {
+                break;
+            }
+            writer.write_all(&buf[..n]).await?;
+        }
+        writer.shutdown().await
+    };
+
+    // От сервера к клиенту. Закрытие соединения сервером закрывает сокет
+    // на запись
+    let outbound = async {
+        let mut buf = vec![0; BUFFER_SIZE];
+        loop {
+            let n = reader.read(&mut buf).await?;
+            if n == 0 {
+                break;
+            }
+
+            let (res, slice) = socket.write_all(buf.slice(..n)).await;
+            buf = slice.into_inner();
+            res?;
+        }
+        socket.shutdown(Shutdown::Write)
+    };
+
+    tokio::try_join!(inbound, outbound)?;
+    Ok(())
+}
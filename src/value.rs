@@ -0,0 +1,189 @@
+//! Представление значений в памяти.
+//!
+//! Как и `Redis`, `mini-redis` выбирает для строки компактное представление
+//! (кодировку) в зависимости от ее содержимого:
+//!
+//! * `int` - целое число, хранящееся как `i64`;
+//! * `embstr` - короткая строка, хранящаяся внутри сущности без отдельного
+//!   выделения памяти в куче;
+//! * `raw` - остальные строки, хранящиеся в `Bytes`.
+//!
+//! Кодировка не видна клиентам, кроме команды `OBJECT ENCODING`: значение
+//! всегда возвращается в том виде, в котором оно было записано.
+//!
+//! С флагом `json` значением также может быть документ JSON (кодировка
+//! `json`), изменяемый командами `JSON.*`. При чтении командой `GET` документ
+//! возвращается целиком в виде текста.
+//!
+//! Хеш (кодировка `hashtable`) изменяется командами `H*` и, в отличие от
+//! документа JSON, строкой не является: команды для строк возвращают для него
+//! ошибку `WRONGTYPE`. То же относится к сортированному множеству (кодировка
+//! `skiplist`), изменяемому командами `Z*`.
+//!
+//! Состояние ограничения частоты запросов (`THROTTLE`) хранится отдельным
+//! вариантом, чтобы команда не принимала за него целое число, записанное
+//! клиентом. Оно читается как целое число, но команды `INCR*` возвращают для
+//! него ошибку `WRONGTYPE`.
+
+use crate::zset::SortedSet;
+
+use bytes::Bytes;
+use std::collections::HashMap;
+use std::mem;
+
+/// Максимальная длина строки в кодировке `embstr`.
+const EMBSTR_MAX_LEN: usize = 23;
+
+/// Значение ключа.
+#[derive(Debug, Clone)]
+pub(crate) enum Value {
+    /// Целое число, десятичное представление которого совпадает с
+    /// записанной строкой.
+    Int(i64),
+
+    /// Короткая строка. Используются первые `len` байтов `data`.
+    Embstr { len: u8, data: [u8; EMBSTR_MAX_LEN] },
+
+    /// Строка, хранящаяся в куче.
+    Raw(Bytes),
+
+    /// Документ JSON. Хранится в куче, чтобы не увеличивать размер
+    /// остальных значений.
+    #[cfg(feature = "json")]
+    Json(Box<serde_json::Value>),
+
+    /// Хеш: поля и их значения. Хранится в куче по той же причине: сама
+    /// `HashMap` занимает больше места, чем остальные варианты.
+    #[allow(clippy::box_collection)]
+    Hash(Box<HashMap<String, Bytes>>),
+
+    /// Сортированное множество.
+    SortedSet(Box<SortedSet>),
+
+    /// Состояние ограничения частоты запросов: теоретическое время прибытия
+    /// следующего запроса (см. `crate::throttle`).
+    Throttle(i64),
+}
+
+impl Value {
+    /// Создает значение из строки, выбирая наиболее компактную кодировку.
+    pub(crate) fn from_bytes(bytes: Bytes) -> Value {
+        if let Some(n) = parse_int(&bytes) {
+            return Value::Int(n);
+        }
+
+        if bytes.len() <= EMBSTR_MAX_LEN {
+            let mut data = [0; EMBSTR_MAX_LEN];
+            data[..bytes.len()].copy_from_slice(&bytes);
+
+            return Value::Embstr {
+                len: bytes.len() as u8,
+                data,
+            };
+        }
+
+        Value::Raw(bytes)
+    }
+
+    /// Возвращает значение в виде строки или `None`, если значение не
+    /// является строкой (хеш или сортированное множество).
+    ///
+    /// Для кодировки `raw` данные не копируются.
+    pub(crate) fn to_bytes(&self) -> Option<Bytes> {
+        let bytes = match self {
+            Value::Int(n) | Value::Throttle(n) => Bytes::from(n.to_string()),
+            Value::Embstr { len, data } => Bytes::copy_from_slice(&data[..*len as usize]),
+            Value::Raw(bytes) => bytes.clone(),
+            #[cfg(feature = "json")]
+            Value::Json(doc) => Bytes::from(doc.to_string()),
+            Value::Hash(_) | Value::SortedSet(_) => return None,
+        };
+
+        Some(bytes)
+    }
+
+    /// Возвращает значение в виде конечного числа с плавающей точкой или
+    /// `None`, если строка не является таким числом.
+    pub(crate) fn to_float(&self) -> Option<f64> {
+        let value: f64 = match self {
+            Value::Int(n) => return Some(*n as f64),
+            Value::Embstr { len, data } => std::str::from_utf8(&data[..*len as usize])
+                .ok()?
+                .parse()
+                .ok()?,
+            Value::Raw(bytes) => std::str::from_utf8(bytes).ok()?.parse().ok()?,
+            #[cfg(feature = "json")]
+            Value::Json(_) => return None,
+            Value::Hash(_) | Value::SortedSet(_) | Value::Throttle(_) => return None,
+        };
+
+        Some(value).filter(|value| value.is_finite())
+    }
+
+    /// Возвращает размер данных значения в байтах.
+    ///
+    /// Размер документа JSON оценивается по длине его текста, размер хеша -
+    /// по суммарной длине полей и значений, размер сортированного множества -
+    /// по суммарной длине элементов и оценок.
+    pub(crate) fn data_size(&self) -> usize {
+        match self {
+            Value::Int(_) | Value::Throttle(_) => mem::size_of::<i64>(),
+            Value::Embstr { len, .. } => *len as usize,
+            Value::Raw(bytes) => bytes.len(),
+            #[cfg(feature = "json")]
+            Value::Json(doc) => doc.to_string().len(),
+            Value::Hash(hash) => hash
+                .iter()
+                .map(|(field, value)| field.len() + value.len())
+                .sum(),
+            Value::SortedSet(zset) => zset
+                .iter()
+                .map(|(member, _)| member.len() + mem::size_of::<f64>())
+                .sum(),
+        }
+    }
+
+    /// Возвращает название кодировки, как его возвращает `OBJECT ENCODING`.
+    pub(crate) fn encoding(&self) -> &'static str {
+        match self {
+            Value::Int(_) | Value::Throttle(_) => "int",
+
+            Value::Embstr { .. } => "embstr",
+            Value::Raw(_) => "raw",
+            #[cfg(feature = "json")]
+            Value::Json(_) => "json",
+            Value::Hash(_) => "hashtable",
+            Value::SortedSet(_) => "skiplist",
+        }
+    }
+}
+
+/// Форматирует число с плавающей точкой так же, как `INCRBYFLOAT` в Redis:
+/// без экспоненты и незначащих нулей, целые числа - без дробной части.
+pub(crate) fn format_float(value: f64) -> String {
+    // `Display` выводит кратчайшее точное представление без экспоненты.
+    // Отрицательный ноль выводится как `0`
+    if value == 0.0 {
+        return "0".to_string();
+    }
+
+    value.to_string()
+}
+
+/// Разбирает целое число, если его десятичное представление совпадает с
+/// `bytes`. Строки вроде `007`, `+1` и `-0` не считаются числами, поскольку
+/// при чтении они вернулись бы в другом виде.
+pub(crate) fn parse_int(bytes: &[u8]) -> Option<i64> {
+    // `i64::MIN` содержит 20 символов
+    if bytes.is_empty() || bytes.len() > 20 {
+        return None;
+    }
+
+    let n: i64 = std::str::from_utf8(bytes).ok()?.parse().ok()?;
+
+    if n.to_string().as_bytes() == bytes {
+        Some(n)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,194 @@
+//! Транспорт WebSocket для протокола `Redis`.
+//!
+//! Доступен с флагом `websocket`. Кадры протокола передаются в бинарных
+//! сообщениях WebSocket, что позволяет обращаться к серверу из браузера или
+//! клиента WASM.
+//!
+//! `WsStream` представляет соединение WebSocket как поток байтов
+//! (`AsyncRead` и `AsyncWrite`), поэтому поверх него работают `Connection`,
+//! обработчик соединений сервера и `Client`. Каждый сброс (flush) потока
+//! отправляет записанные данные одним сообщением. Сообщение может содержать
+//! несколько кадров, а кадр может быть разделен между сообщениями.
+//!
+//! # Примеры
+//!
+//! ```no_run
+//! use mini_redis::server::Server;
+//! use mini_redis::websocket::{self, WsListener};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let listener = WsListener::bind("127.0.0.1:6380").await.unwrap();
+//!     let server = Server::builder().serve(listener).unwrap();
+//!
+//!     let mut client = websocket::connect("ws://127.0.0.1:6380").await.unwrap();
+//!     client.ping(None).await.unwrap();
+//! #   drop(server);
+//! }
+//! ```
+
+use crate::server::Accept;
+use crate::Client;
+
+use bytes::{Buf, Bytes, BytesMut};
+use futures_util::{Sink, Stream};
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::time::{self, Duration};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+use tracing::debug;
+
+/// Максимальное время рукопожатия WebSocket.
+///
+/// Рукопожатие выполняется при приеме соединения, поэтому медленный клиент
+/// задерживает прием остальных соединений не дольше этого времени.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Соединение WebSocket, представленное как поток байтов.
+#[derive(Debug)]
+pub struct WsStream<S> {
+    /// Соединение WebSocket.
+    inner: WebSocketStream<S>,
+
+    /// Непрочитанная часть последнего полученного сообщения.
+    read_buf: Bytes,
+
+    /// Данные, которые будут отправлены при сбросе потока.
+    write_buf: BytesMut,
+}
+
+impl<S> WsStream<S> {
+    /// Создает поток поверх установленного соединения WebSocket.
+    pub fn new(inner: WebSocketStream<S>) -> WsStream<S> {
+        WsStream {
+            inner,
+            read_buf: Bytes::new(),
+            write_buf: BytesMut::new(),
+        }
+    }
+}
+
+impl<S> AsyncRead for WsStream<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        while self.read_buf.is_empty() {
+            match ready!(Pin::new(&mut self.inner).poll_next(cx)) {
+                Some(Ok(Message::Binary(data))) => self.read_buf = data,
+                // Текстовые сообщения также принимаются: некоторым клиентам
+                // проще отправлять команды текстом
+                Some(Ok(Message::Text(text))) => self.read_buf = Bytes::from(text),
+                // Конец потока
+                Some(Ok(Message::Close(_))) | None => return Poll::Ready(Ok(())),
+                // `Ping` и `Pong` обрабатываются `tungstenite`
+                Some(Ok(_)) => {}
+                Some(Err(err)) => return Poll::Ready(Err(io::Error::other(err))),
+            }
+        }
+
+        let n = buf.remaining().min(self.read_buf.len());
+        buf.put_slice(&self.read_buf[..n]);
+        self.read_buf.advance(n);
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<S> AsyncWrite for WsStream<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        data: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        // `Connection` сбрасывает поток после записи каждого кадра, поэтому
+        // размер буфера ограничен
+        self.write_buf.extend_from_slice(data);
+        Poll::Ready(Ok(data.len()))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if !self.write_buf.is_empty() {
+            ready!(Pin::new(&mut self.inner).poll_ready(cx)).map_err(io::Error::other)?;
+
+            let data = self.write_buf.split().freeze();
+            Pin::new(&mut self.inner)
+                .start_send(Message::Binary(data))
+                .map_err(io::Error::other)?;
+        }
+
+        Pin::new(&mut self.inner)
+            .poll_flush(cx)
+            .map_err(io::Error::other)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.as_mut().poll_flush(cx))?;
+
+        Pin::new(&mut self.inner)
+            .poll_close(cx)
+            .map_err(io::Error::other)
+    }
+}
+
+/// Обработчик соединений WebSocket.
+///
+/// Реализует `server::Accept`, поэтому сервер запускается поверх него так же,
+/// как поверх `TcpListener`, например, `Server::builder().serve(listener)`.
+#[derive(Debug)]
+pub struct WsListener {
+    listener: TcpListener,
+}
+
+impl WsListener {
+    /// Привязывает обработчик к `addr`.
+    pub async fn bind(addr: impl ToSocketAddrs) -> io::Result<WsListener> {
+        Ok(WsListener::new(TcpListener::bind(addr).await?))
+    }
+
+    /// Создает обработчик, принимающий соединения из `listener`.
+    pub fn new(listener: TcpListener) -> WsListener {
+        WsListener { listener }
+    }
+}
+
+impl Accept for WsListener {
+    type Io = WsStream<TcpStream>;
+
+    async fn accept(&mut self) -> io::Result<(Self::Io, SocketAddr)> {
+        loop {
+            let (socket, addr) = self.listener.accept().await?;
+
+            // Ошибка рукопожатия относится к одному клиенту, поэтому она не
+            // возвращается серверу, который считал бы ее ошибкой приема
+            match time::timeout(HANDSHAKE_TIMEOUT, tokio_tungstenite::accept_async(socket)).await {
+                Ok(Ok(ws)) => return Ok((WsStream::new(ws), addr)),
+                Ok(Err(err)) => debug!(%addr, cause = %err, "Ошибка рукопожатия WebSocket"),
+                Err(_) => debug!(%addr, "Превышено время рукопожатия WebSocket"),
+            }
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+}
+
+/// Устанавливает соединение WebSocket с сервером по адресу `url`, например,
+/// `ws://127.0.0.1:6380`, и возвращает клиента.
+pub async fn connect(url: &str) -> crate::Result<Client> {
+    let (ws, _) = tokio_tungstenite::connect_async(url).await?;
+    Ok(Client::new(WsStream::new(ws)))
+}
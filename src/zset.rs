@@ -0,0 +1,165 @@
+//! Сортированное множество.
+//!
+//! Элементы упорядочены по оценке, элементы с одинаковой оценкой - по
+//! возрастанию самих элементов. Порядок хранится в `BTreeSet`, а оценка
+//! элемента по его имени находится с помощью `HashMap`.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap};
+use std::ops::Bound;
+
+/// Сортированное множество: элементы и их оценки.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SortedSet {
+    /// Оценки элементов.
+    scores: HashMap<String, Score>,
+
+    /// Элементы, упорядоченные по оценке и имени.
+    index: BTreeSet<(Score, String)>,
+}
+
+/// Оценка элемента.
+///
+/// `NaN` не может быть оценкой, а `-0` заменяется на `0`, поэтому оценки
+/// полностью упорядочены.
+#[derive(Debug, Clone, Copy)]
+struct Score(f64);
+
+impl SortedSet {
+    /// Добавляет элемент или заменяет его оценку. Возвращает предыдущую
+    /// оценку.
+    pub(crate) fn insert(&mut self, member: String, score: f64) -> Option<f64> {
+        debug_assert!(!score.is_nan());
+
+        let score = Score::new(score);
+        let prev = self.scores.insert(member.clone(), score);
+
+        if let Some(prev) = prev {
+            self.index.remove(&(prev, member.clone()));
+        }
+        self.index.insert((score, member));
+
+        prev.map(|prev| prev.0)
+    }
+
+    /// Возвращает оценку элемента.
+    pub(crate) fn score(&self, member: &str) -> Option<f64> {
+        self.scores.get(member).map(|score| score.0)
+    }
+
+    /// Удаляет элемент. Возвращает его оценку.
+    pub(crate) fn remove(&mut self, member: &str) -> Option<f64> {
+        let (member, score) = self.scores.remove_entry(member)?;
+        self.index.remove(&(score, member));
+
+        Some(score.0)
+    }
+
+    /// Возвращает количество элементов.
+    pub(crate) fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    /// Проверяет отсутствие элементов.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    /// Возвращает позицию элемента в порядке возрастания оценок.
+    pub(crate) fn rank(&self, member: &str) -> Option<usize> {
+        let score = *self.scores.get(member)?;
+
+        Some(self.index.range(..(score, member.to_string())).count())
+    }
+
+    /// Возвращает итератор по элементам и их оценкам в порядке возрастания.
+    pub(crate) fn iter(&self) -> impl DoubleEndedIterator<Item = (&str, f64)> {
+        self.index
+            .iter()
+            .map(|(score, member)| (member.as_str(), score.0))
+    }
+
+    /// Возвращает итератор по элементам, оценки которых находятся между
+    /// `min` и `max`, в порядке возрастания.
+    pub(crate) fn range_by_score(
+        &self,
+        min: Bound<f64>,
+        max: Bound<f64>,
+    ) -> impl Iterator<Item = (&str, f64)> {
+        // Перебор начинается с первого элемента с оценкой `min`: пустая
+        // строка меньше любого элемента с той же оценкой
+        let start = match min {
+            Bound::Included(score) | Bound::Excluded(score) => {
+                Bound::Included((Score::new(score), String::new()))
+            }
+            Bound::Unbounded => Bound::Unbounded,
+        };
+
+        self.index
+            .range((start, Bound::Unbounded))
+            .map(|(score, member)| (member.as_str(), score.0))
+            .skip_while(move |(_, score)| !is_after(min.as_ref(), score))
+            .take_while(move |(_, score)| is_before(max.as_ref(), score))
+    }
+
+    /// Возвращает итератор по элементам, находящимся между `min` и `max` в
+    /// лексикографическом порядке.
+    ///
+    /// Как и в Redis, предполагается, что оценки всех элементов одинаковы.
+    /// Иначе возвращаются подходящие элементы из непрерывной части порядка
+    /// элементов по оценкам, начинающейся с первого подходящего элемента.
+    pub(crate) fn range_by_lex<'a>(
+        &'a self,
+        min: Bound<&'a str>,
+        max: Bound<&'a str>,
+    ) -> impl Iterator<Item = (&'a str, f64)> {
+        self.iter()
+            .skip_while(move |(member, _)| !is_after(min, *member))
+            .take_while(move |(member, _)| is_before(max, *member))
+    }
+}
+
+impl Score {
+    /// Создает оценку, заменяя `-0` на `0`.
+    fn new(value: f64) -> Score {
+        Score(value + 0.0)
+    }
+}
+
+impl PartialEq for Score {
+    fn eq(&self, other: &Score) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Score {}
+
+impl PartialOrd for Score {
+    fn partial_cmp(&self, other: &Score) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Score {
+    fn cmp(&self, other: &Score) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+/// Проверяет, что `value` не выходит за нижнюю границу `min`.
+fn is_after<T: PartialOrd + ?Sized>(min: Bound<&T>, value: &T) -> bool {
+    match min {
+        Bound::Included(min) => value >= min,
+        Bound::Excluded(min) => value > min,
+        Bound::Unbounded => true,
+    }
+}
+
+/// Проверяет, что `value` не выходит за верхнюю границу `max`.
+fn is_before<T: PartialOrd + ?Sized>(max: Bound<&T>, value: &T) -> bool {
+    match max {
+        Bound::Included(max) => value <= max,
+        Bound::Excluded(max) => value < max,
+        Bound::Unbounded => true,
+    }
+}
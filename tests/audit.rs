@@ -0,0 +1,151 @@
+use mini_redis::audit::AuditLog;
+use mini_redis::server::Server;
+use mini_redis::Client;
+
+use std::fs;
+use std::path::PathBuf;
+
+/// Возвращает путь к файлу журнала во временной директории, удаляя
+/// оставшиеся от предыдущих запусков файлы.
+fn log_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "mini-redis-audit-{}-{}.log",
+        name,
+        std::process::id()
+    ));
+
+    for n in 0..4 {
+        let mut rotated = path.clone().into_os_string();
+        if n > 0 {
+            rotated.push(format!(".{}", n));
+        }
+        let _ = fs::remove_file(rotated);
+    }
+
+    path
+}
+
+/// Читает строки журнала, отбрасывая время выполнения команды.
+fn read_entries(path: &PathBuf) -> Vec<String> {
+    fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(|line| line.split_once(' ').unwrap().1.to_string())
+        .collect()
+}
+
+/// Записываются только изменяющие команды с адресом клиента и ключом
+#[tokio::test]
+async fn records_mutating_commands() {
+    let path = log_path("mutating");
+    let audit = AuditLog::open(&path).unwrap();
+
+    let server = Server::builder()
+        .audit_log(audit)
+        .bind("127.0.0.1:0")
+        .await
+        .unwrap();
+
+    let mut client = Client::connect(server.local_addr()).await.unwrap();
+    client.set("hello", "world".into()).await.unwrap();
+    client.get("hello").await.unwrap();
+    client.set("with space", "value".into()).await.unwrap();
+
+    let entries = read_entries(&path);
+    assert_eq!(2, entries.len());
+
+    let peer = entries[0].split(' ').next().unwrap();
+    assert!(peer.starts_with("127.0.0.1:"));
+    assert_eq!(format!("{} SET \"hello\"", peer), entries[0]);
+    assert_eq!(format!("{} SET \"with space\"", peer), entries[1]);
+
+    fs::remove_file(&path).unwrap();
+}
+
+/// Фильтр по шаблону ключей
+#[tokio::test]
+async fn filters_by_key_pattern() {
+    let path = log_path("filter");
+    let audit = AuditLog::open(&path).unwrap().keys("user:*");
+
+    let server = Server::builder()
+        .audit_log(audit)
+        .bind("127.0.0.1:0")
+        .await
+        .unwrap();
+
+    let mut client = Client::connect(server.local_addr()).await.unwrap();
+    client.set("user:1", "alice".into()).await.unwrap();
+    client.set("session:1", "token".into()).await.unwrap();
+
+    let entries = read_entries(&path);
+    assert_eq!(1, entries.len());
+    assert!(entries[0].ends_with("SET \"user:1\""));
+
+    fs::remove_file(&path).unwrap();
+}
+
+/// При превышении размера файл ротируется, лишние файлы удаляются
+#[tokio::test]
+async fn rotates_files() {
+    let path = log_path("rotate");
+
+    // Каждая строка занимает больше 32 байт, поэтому файл ротируется
+    // перед каждой записью
+    let audit = AuditLog::open(&path).unwrap().max_size(32).max_files(2);
+
+    let server = Server::builder()
+        .audit_log(audit)
+        .bind("127.0.0.1:0")
+        .await
+        .unwrap();
+
+    let mut client = Client::connect(server.local_addr()).await.unwrap();
+    for key in ["a", "b", "c", "d"] {
+        client.set(key, "value".into()).await.unwrap();
+    }
+
+    let rotated = |n: usize| {
+        let mut rotated = path.clone().into_os_string();
+        rotated.push(format!(".{}", n));
+        PathBuf::from(rotated)
+    };
+
+    assert!(read_entries(&path)[0].ends_with("SET \"d\""));
+    assert!(read_entries(&rotated(1))[0].ends_with("SET \"c\""));
+    assert!(read_entries(&rotated(2))[0].ends_with("SET \"b\""));
+    assert!(!rotated(3).exists());
+
+    for path in [path.clone(), rotated(1), rotated(2)] {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+/// Команды, не изменившие данные из-за условия или ошибки, не записываются
+#[tokio::test]
+async fn skips_unapplied_commands() {
+    let path = log_path("unapplied");
+    let audit = AuditLog::open(&path).unwrap();
+
+    let server = Server::builder()
+        .audit_log(audit)
+        .bind("127.0.0.1:0")
+        .await
+        .unwrap();
+
+    let mut client = Client::connect(server.local_addr()).await.unwrap();
+    assert!(client.set_nx("hello", "world".into()).await.unwrap());
+    assert!(!client.set_nx("hello", "again".into()).await.unwrap());
+    assert!(!client.set_xx("missing", "value".into()).await.unwrap());
+    assert!(!client
+        .mset_nx(&[("hello", "1".into()), ("other", "2".into())])
+        .await
+        .unwrap());
+    assert!(client.incr("hello").await.is_err());
+
+    let entries = read_entries(&path);
+    assert_eq!(1, entries.len(), "{:?}", entries);
+    assert!(entries[0].ends_with("SET \"hello\""));
+
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,253 @@
+use mini_redis::test_util::TestServer;
+use mini_redis::Client;
+
+use std::fs;
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::process::{Output, Stdio};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::time::{self, Duration};
+
+/// Код завершения `clap` при ошибке разбора аргументов
+const USAGE_ERROR: i32 = 2;
+
+/// Защищенный режим не допускает привязку к внешнему адресу, а
+/// невалидные значения флагов отклоняются до запуска сервера
+#[tokio::test]
+async fn server_rejects_invalid_flags() {
+    let output = server(&["--bind", "0.0.0.0"]).await;
+    assert_eq!(Some(1), output.status.code());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("--protected-mode no"), "{}", stderr);
+
+    for args in [
+        &["--bind", "localhost"][..],
+        &["--protected-mode", "maybe"],
+        &["--command-timeout", "0"],
+        &["--active-expire-batch", "0"],
+        &["--port", "65536"],
+    ] {
+        let output = server(args).await;
+        assert_eq!(Some(USAGE_ERROR), output.status.code(), "{:?}", args);
+    }
+}
+
+/// Сервер, запущенный со всеми флагами, принимает соединения на внешнем
+/// адресе при отключенном защищенном режиме и записывает файлы `--pidfile`,
+/// `--logfile` и `--audit-log`
+#[tokio::test]
+async fn server_starts_with_flags() {
+    let dir = temp_dir("server");
+    let port = free_port();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_mini-redis-server"))
+        .args(["--bind", "0.0.0.0", "--protected-mode", "no"])
+        .args(["--port", &port.to_string()])
+        .args([
+            "--command-timeout",
+            "1000",
+            "--prefix-index",
+            "--strict-framing",
+        ])
+        .args([
+            "--active-expire-batch",
+            "10",
+            "--active-expire-min-interval",
+            "5",
+        ])
+        .arg("--pidfile")
+        .arg(dir.join("server.pid"))
+        .arg("--logfile")
+        .arg(dir.join("server.log"))
+        .arg("--audit-log")
+        .arg(dir.join("audit.log"))
+        .stdout(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .unwrap();
+
+    let mut client = connect(port).await;
+    client.set("hello", "world".into()).await.unwrap();
+    assert_eq!(Some("world".into()), client.get("hello").await.unwrap());
+
+    let pid = fs::read_to_string(dir.join("server.pid")).unwrap();
+    assert_eq!(child.id().unwrap().to_string(), pid.trim());
+    assert!(dir.join("server.log").exists());
+    let audit = fs::read_to_string(dir.join("audit.log")).unwrap();
+    assert!(audit.contains("hello"), "{}", audit);
+
+    child.kill().await.unwrap();
+    let _ = fs::remove_dir_all(dir);
+}
+
+/// С `--daemonize` родительский процесс завершается, а сервер продолжает
+/// работу в дочернем процессе, идентификатор которого записан в `--pidfile`
+#[cfg(unix)]
+#[tokio::test]
+async fn server_daemonize() {
+    let dir = temp_dir("daemon");
+    let port = free_port();
+    let pidfile = dir.join("server.pid");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_mini-redis-server"))
+        .args(["--daemonize", "--port", &port.to_string()])
+        .arg("--pidfile")
+        .arg(&pidfile)
+        .arg("--logfile")
+        .arg(dir.join("server.log"))
+        .output()
+        .await
+        .unwrap();
+    assert!(output.status.success());
+
+    let mut client = connect(port).await;
+    client.set("hello", "world".into()).await.unwrap();
+
+    let pid = fs::read_to_string(&pidfile).unwrap();
+    let status = Command::new("kill").arg(pid.trim()).status().await.unwrap();
+    assert!(status.success());
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+/// TLS и сокеты Unix не поддерживаются и отклоняются при разборе
+/// аргументов, а `--pipe` не используется вместе с командой
+#[tokio::test]
+async fn cli_rejects_invalid_flags() {
+    for args in [
+        &["--tls", "get", "hello"][..],
+        &["--cacert", "ca.pem", "get", "hello"],
+        &["--socket", "/tmp/redis.sock", "get", "hello"],
+        &["--uri", "rediss://localhost", "get", "hello"],
+        &["--uri", "unix:///tmp/redis.sock", "get", "hello"],
+        &["-i", "soon", "get", "hello"],
+        &["-r", "many", "get", "hello"],
+        &["--uri", "http://localhost", "get", "hello"],
+        &["set", "hello"],
+    ] {
+        let output = cli(args).await;
+        assert_eq!(Some(USAGE_ERROR), output.status.code(), "{:?}", args);
+    }
+
+    let output = cli(&["--pipe", "get", "hello"]).await;
+    assert_eq!(Some(1), output.status.code());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("--pipe"), "{}", stderr);
+}
+
+/// Ошибка `AUTH` завершает клиента с сообщением сервера, а в режиме
+/// `--pipe` команды после нее не отправляются
+#[tokio::test]
+async fn cli_fails_on_auth_error() {
+    let server = TestServer::start().await;
+    let port = server.addr().port().to_string();
+
+    let output = cli(&["--port", &port, "-a", "secret", "get", "hello"]).await;
+    assert_eq!(Some(1), output.status.code());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("ERR unknown command 'auth'"), "{}", stderr);
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_mini-redis-cli"))
+        .args(["--port", &port, "-a", "secret", "--pipe"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    stdin.write_all(b"set a 1\n").await.unwrap();
+    drop(stdin);
+
+    let output = child.wait_with_output().await.unwrap();
+    assert_eq!(Some(1), output.status.code());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("ERR unknown command 'auth'"), "{}", stderr);
+    assert!(output.stdout.is_empty());
+    assert_eq!(None, server.db().get("a"));
+}
+
+/// `-r` и `-i` повторяют команду, `--uri` задает адрес сервера, а `--pipe`
+/// отправляет команды из стандартного ввода
+#[tokio::test]
+async fn cli_flags() {
+    let server = TestServer::start().await;
+    let port = server.addr().port().to_string();
+    server.db().set("hello".to_string(), "world".into(), None);
+
+    let output = cli(&["--port", &port, "-r", "3", "-i", "0.01", "get", "hello"]).await;
+    assert!(output.status.success());
+    assert_eq!(
+        "\"world\"\n".repeat(3),
+        String::from_utf8(output.stdout).unwrap()
+    );
+
+    let uri = format!("redis://127.0.0.1:{}", port);
+    let output = cli(&["--uri", &uri, "get", "hello"]).await;
+    assert!(output.status.success());
+    assert_eq!("\"world\"\n", String::from_utf8(output.stdout).unwrap());
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_mini-redis-cli"))
+        .args(["--port", &port, "--pipe"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    stdin.write_all(b"set a 1\nset b 2\n").await.unwrap();
+    drop(stdin);
+    assert!(child.wait().await.unwrap().success());
+
+    assert_eq!(Some("1".into()), server.db().get("a"));
+    assert_eq!(Some("2".into()), server.db().get("b"));
+}
+
+/// Запускает сервер с аргументами `args` и ждет его завершения
+async fn server(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_mini-redis-server"))
+        .args(args)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .unwrap()
+}
+
+/// Запускает клиента с аргументами `args` и ждет его завершения
+async fn cli(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_mini-redis-cli"))
+        .args(args)
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .output()
+        .await
+        .unwrap()
+}
+
+/// Устанавливает соединение с сервером, ожидая его запуска
+async fn connect(port: u16) -> Client {
+    for _ in 0..100 {
+        if let Ok(client) = Client::connect(("127.0.0.1", port)).await {
+            return client;
+        }
+        time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("Сервер не запущен");
+}
+
+/// Возвращает свободный порт
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+/// Создает пустую временную директорию
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("mini-redis-bin-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+
+    dir
+}
@@ -0,0 +1,173 @@
+use mini_redis::{clients::BlockingClient, server, Frame};
+use std::io::{self, ErrorKind};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+/// Тест PING PONG без сообщения и с сообщением.
+#[test]
+fn ping_pong() {
+    let addr = start_server();
+    let mut client = BlockingClient::connect(addr).unwrap();
+
+    let pong = client.ping(None).unwrap();
+    assert_eq!(b"PONG", &pong[..]);
+
+    let pong = client.ping(Some("hello".into())).unwrap();
+    assert_eq!(b"hello", &pong[..]);
+}
+
+/// `publish` блокирующего клиента возвращает то же, что и асинхронный клиент:
+/// количество подписчиков канала
+#[test]
+fn publish_without_subscribers() {
+    let addr = start_server();
+    let mut client = BlockingClient::connect(addr).unwrap();
+
+    assert_eq!(0, client.publish("hello", "world".into()).unwrap());
+}
+
+/// Команда, отправленная в виде "сырого" кадра, выполняется сервером,
+/// а кадр `Error` преобразуется в `Err`
+#[test]
+fn send_raw_frame() {
+    let addr = start_server();
+    let mut client = BlockingClient::connect(addr).unwrap();
+
+    client.set("hello", "world".into()).unwrap();
+
+    let frame = Frame::Array(vec![Frame::Bulk("get".into()), Frame::Bulk("hello".into())]);
+    let response = client.send_frame(frame).unwrap();
+    assert_eq!(response, "world");
+
+    let frame = Frame::Array(vec![Frame::Bulk("foo".into())]);
+    assert!(client.send_frame(frame).is_err());
+}
+
+/// `close` блокирующих клиента и подписчика завершает сессию
+#[test]
+fn close_session() {
+    let addr = start_server();
+
+    let mut client = BlockingClient::connect(addr).unwrap();
+    client.set("hello", "world".into()).unwrap();
+    client.close().unwrap();
+
+    let subscriber = BlockingClient::connect(addr)
+        .unwrap()
+        .subscribe(vec!["hello".into()])
+        .unwrap();
+    subscriber.close().unwrap();
+}
+
+/// Подписчик используется как итератор сообщений
+#[test]
+fn subscriber_iterator() {
+    let addr = start_server();
+
+    let client = BlockingClient::connect(addr).unwrap();
+    let mut subscriber = client.subscribe(vec!["hello".into()]).unwrap();
+
+    let mut publisher = BlockingClient::connect(addr).unwrap();
+    assert_eq!(1, publisher.publish("hello", "world".into()).unwrap());
+
+    let message = subscriber.next().unwrap().unwrap();
+    assert_eq!("hello", &message.channel);
+    assert_eq!(b"world", &message.content[..]);
+}
+
+/// Уничтожение подписчика не ждет ответа сервера, а подписки удаляются
+/// командой `UNSUBSCRIBE`, а не закрытием соединения
+#[test]
+fn subscriber_drop() {
+    let addr = start_server();
+    let (proxy, upstream) = start_proxy(addr);
+
+    let client = BlockingClient::connect(proxy).unwrap();
+    let subscriber = client.subscribe(vec!["hello".into()]).unwrap();
+    drop(subscriber);
+
+    // Соединение с сервером остается открытым после закрытия соединения
+    // подписчика, поэтому подписка может быть удалена только командой
+    let upstream = upstream.recv().unwrap();
+
+    let mut publisher = BlockingClient::connect(addr).unwrap();
+    for _ in 0..100 {
+        if publisher.publish("hello", "world".into()).unwrap() == 0 {
+            drop(upstream);
+            return;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    panic!("Подписка не удалена");
+}
+
+/// Сервер принимает соединение, но никогда не отвечает.
+/// Операция клиента должна завершиться ошибкой `TimedOut`, а не блокировать поток
+#[test]
+fn operation_times_out() {
+    let addr = start_unresponsive_server();
+
+    let mut client = BlockingClient::connect(addr).unwrap();
+    client.set_timeout(Some(Duration::from_millis(100)));
+
+    let err = client.get("hello").unwrap_err();
+    let err = err.downcast_ref::<std::io::Error>().unwrap();
+    assert_eq!(ErrorKind::TimedOut, err.kind());
+}
+
+/// Запускает сервер, который принимает соединения, но ничего не отвечает
+fn start_unresponsive_server() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    thread::spawn(move || {
+        // Удерживаем сокеты открытыми, чтобы клиент не получил `EOF`
+        let _sockets: Vec<_> = listener.incoming().collect();
+    });
+
+    addr
+}
+
+/// Запускает посредника, передающего данные одного соединения на `addr`.
+///
+/// Когда клиент закрывает соединение, посредник передает все полученные от
+/// него данные серверу, но не закрывает соединение с сервером, а отправляет
+/// его в канал
+fn start_proxy(addr: SocketAddr) -> (SocketAddr, mpsc::Receiver<TcpStream>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let proxy = listener.local_addr().unwrap();
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let (mut client, _) = listener.accept().unwrap();
+        let mut upstream = TcpStream::connect(addr).unwrap();
+
+        let mut from_upstream = upstream.try_clone().unwrap();
+        let mut to_client = client.try_clone().unwrap();
+        thread::spawn(move || io::copy(&mut from_upstream, &mut to_client));
+
+        let _ = io::copy(&mut client, &mut upstream);
+        tx.send(upstream).unwrap();
+    });
+
+    (proxy, rx)
+}
+
+/// Запускает сервер в отдельном потоке с собственной средой выполнения
+fn start_server() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    listener.set_nonblocking(true).unwrap();
+
+    thread::spawn(move || {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async move {
+            let listener = tokio::net::TcpListener::from_std(listener).unwrap();
+            server::run(listener, std::future::pending::<()>()).await
+        });
+    });
+
+    addr
+}
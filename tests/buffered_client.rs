@@ -1,10 +1,6 @@
-use mini_redis::{
-    clients::{BufferedClient, Client},
-    server,
-};
-use std::net::SocketAddr;
+use mini_redis::clients::{BufferedClient, Client, ConnectionLost};
+use mini_redis::test_util::TestServer;
 use tokio::net::TcpListener;
-use tokio::task::JoinHandle;
 
 /// Базовый тест. Экземпляр сервера запускается в фоновой задаче.
 /// Затем создается экземпляр клиента, который используется для
@@ -12,9 +8,9 @@ use tokio::task::JoinHandle;
 /// Затем оценивается ответ.
 #[tokio::test]
 async fn pool_key_value_get_set() {
-    let (addr, _) = start_server().await;
+    let server = TestServer::start().await;
 
-    let client = Client::connect(addr).await.unwrap();
+    let client = server.client().await;
     let mut client = BufferedClient::buffer(client);
 
     client.set("hello", "world".into()).await.unwrap();
@@ -23,11 +19,62 @@ async fn pool_key_value_get_set() {
     assert_eq!(b"world", &value[..])
 }
 
-async fn start_server() -> (SocketAddr, JoinHandle<()>) {
+/// Команды, отправленные до вызова `close`, выполняются, после чего
+/// возвращается внутренний `Client`. Клоны буфера перестают принимать команды
+#[tokio::test]
+async fn pool_close_flushes_and_returns_client() {
+    let server = TestServer::start().await;
+
+    let client = server.client().await;
+    let client = BufferedClient::buffer(client);
+    let mut clone = client.clone();
+
+    clone.set("hello", "world".into()).await.unwrap();
+
+    let mut client = client.close().await.unwrap();
+
+    assert!(clone.get("hello").await.is_err());
+
+    let value = client.get("hello").await.unwrap().unwrap();
+    assert_eq!(b"world", &value[..])
+}
+
+/// Сервер закрывает соединение сразу после его установки.
+/// Вызывающая сторона должна получить `ConnectionLost`, а обработчик -
+/// стать сломанным вместе с клонами. После переподключения работают и
+/// обработчик, и его клоны
+#[tokio::test]
+async fn pool_reports_connection_lost() {
     let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
     let addr = listener.local_addr().unwrap();
 
-    let handle = tokio::spawn(async move { server::run(listener, tokio::signal::ctrl_c()).await });
+    tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        drop(socket);
+    });
+
+    let client = Client::connect(addr).await.unwrap();
+    let mut client = BufferedClient::buffer(client);
+    let mut clone = client.clone();
+
+    let err = client.get("hello").await.unwrap_err();
+    assert!(err.is::<ConnectionLost>());
+    assert!(client.is_broken());
+    assert!(clone.is_broken());
+
+    let err = client.get("hello").await.unwrap_err();
+    assert!(err.is::<ConnectionLost>());
+
+    let server = TestServer::start().await;
+    client.reconnect(server.client().await);
+    assert!(!client.is_broken());
+
+    client.set("hello", "world".into()).await.unwrap();
+    let value = client.get("hello").await.unwrap().unwrap();
+    assert_eq!(b"world", &value[..]);
 
-    (addr, handle)
+    // Клон, созданный до переподключения, использует новую задачу
+    assert!(!clone.is_broken());
+    let value = clone.get("hello").await.unwrap().unwrap();
+    assert_eq!(b"world", &value[..]);
 }
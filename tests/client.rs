@@ -1,14 +1,20 @@
-use mini_redis::{clients::Client, server};
-use std::net::SocketAddr;
+use mini_redis::clients::{Client, ErrorKind, ServerError};
+use mini_redis::test_util::TestServer;
+use mini_redis::Frame;
+
+use bytes::Bytes;
+use std::collections::HashMap;
+use std::ops::Bound;
 use tokio::net::TcpListener;
-use tokio::task::JoinHandle;
+use tokio::time::{self, Duration};
+use tokio_stream::StreamExt;
 
 /// Тест PING PONG без сообщения.
 /// Должен вернуть "PONG".
 #[tokio::test]
 async fn ping_pong_without_message() {
-    let (addr, _) = start_server().await;
-    let mut client = Client::connect(addr).await.unwrap();
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
 
     let pong = client.ping(None).await.unwrap();
     assert_eq!(b"PONG", &pong[..]);
@@ -18,8 +24,8 @@ async fn ping_pong_without_message() {
 /// Должен вернуть сообщение.
 #[tokio::test]
 async fn ping_pong_with_message() {
-    let (addr, _) = start_server().await;
-    let mut client = Client::connect(addr).await.unwrap();
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
 
     let pong = client.ping(Some("你好世界".into())).await.unwrap();
     assert_eq!("你好世界".as_bytes(), &pong[..]);
@@ -30,22 +36,295 @@ async fn ping_pong_with_message() {
 /// команды `set` и `get`. Затем оценивается ответ.
 #[tokio::test]
 async fn key_value_get_set() {
-    let (addr, _) = start_server().await;
+    let server = TestServer::start().await;
 
-    let mut client = Client::connect(addr).await.unwrap();
+    let mut client = server.client().await;
     client.set("hello", "world".into()).await.unwrap();
 
     let value = client.get("hello").await.unwrap().unwrap();
     assert_eq!(b"world", &value[..])
 }
 
+/// Тест `KEYS` с шаблонами в стиле glob
+#[tokio::test]
+async fn keys_matching_pattern() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    for key in ["user:1", "user:2", "user:10", "session:1", "[x]"] {
+        client.set(key, "value".into()).await.unwrap();
+    }
+
+    assert_eq!(
+        vec!["user:1", "user:10", "user:2"],
+        client.keys("user:*").await.unwrap()
+    );
+    assert_eq!(
+        vec!["user:1", "user:2"],
+        client.keys("user:?").await.unwrap()
+    );
+    assert_eq!(
+        vec!["session:1", "user:1"],
+        client.keys("*:[0-1]").await.unwrap()
+    );
+    assert_eq!(vec!["user:2"], client.keys("user:[^1]").await.unwrap());
+    assert_eq!(vec!["[x]"], client.keys("\\[x\\]").await.unwrap());
+    assert!(client.keys("nothing*").await.unwrap().is_empty());
+}
+
+/// С индексом префиксов `KEYS` и `SCAN` возвращают те же ключи, что и без
+/// него, в том числе после изменения ключей
+#[tokio::test]
+async fn keys_and_scan_with_prefix_index() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    for key in ["user:1", "user:2", "user:10", "users", "session:1", "[x]1"] {
+        client.set(key, "value".into()).await.unwrap();
+    }
+
+    // Индекс строится по существующим ключам
+    server.db().set_prefix_index(true);
+    client.set("user:3", "value".into()).await.unwrap();
+    assert!(server.db().del("user:2"));
+
+    assert_eq!(
+        vec!["user:1", "user:10", "user:3"],
+        client.keys("user:*").await.unwrap()
+    );
+    assert_eq!(
+        vec!["user:1", "user:3"],
+        client.keys("user:?").await.unwrap()
+    );
+    assert_eq!(vec!["[x]1"], client.keys("\\[x\\]*").await.unwrap());
+    assert_eq!(
+        vec!["session:1", "user:1"],
+        client.keys("*:1").await.unwrap()
+    );
+
+    let keys: Vec<String> = client
+        .scan(Some("user*"))
+        .collect::<Result<_, _>>()
+        .await
+        .unwrap();
+    let mut keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+    keys.sort_unstable();
+    assert_eq!(vec!["user:1", "user:10", "user:3", "users"], keys);
+
+    // Истекшие ключи не возвращаются и удаляются из индекса
+    client
+        .set_expires("user:4", "value".into(), Duration::from_millis(50))
+        .await
+        .unwrap();
+    assert_eq!(4, client.keys("user:*").await.unwrap().len());
+    time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(3, client.keys("user:*").await.unwrap().len());
+
+    server.db().set_prefix_index(false);
+    assert_eq!(3, client.keys("user:*").await.unwrap().len());
+}
+
+/// Перебор всех ключей с помощью `SCAN` возвращает каждый ключ ровно один раз
+#[tokio::test]
+async fn scan_all_keys() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    for i in 0..25 {
+        client
+            .set(&format!("key:{}", i), "value".into())
+            .await
+            .unwrap();
+    }
+    client.set("other", "value".into()).await.unwrap();
+
+    let mut cursor = 0;
+    let mut pages = 0;
+    let mut keys = vec![];
+    loop {
+        let (next, page) = client
+            .scan_page(cursor, Some("key:*"), Some(10))
+            .await
+            .unwrap();
+        // Порция рассматривает не больше `COUNT` ключей
+        assert!(page.len() <= 10);
+        keys.extend(page);
+        pages += 1;
+
+        if next == 0 {
+            break;
+        }
+        cursor = next;
+    }
+
+    keys.sort();
+    let mut expected: Vec<String> = (0..25).map(|i| format!("key:{}", i)).collect();
+    expected.sort();
+
+    assert_eq!(expected, keys);
+    assert_eq!(3, pages);
+}
+
+/// `is_connected` и `check` обнаруживают соединение, закрытое сервером
+#[tokio::test]
+async fn health_check() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    assert!(client.is_connected());
+    client.check().await.unwrap();
+    client.set("hello", "world".into()).await.unwrap();
+    assert!(client.is_connected());
+
+    // Сервер принимает соединение и сразу закрывает его
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let mut client = Client::connect(listener.local_addr().unwrap())
+        .await
+        .unwrap();
+    let (socket, _) = listener.accept().await.unwrap();
+    drop(socket);
+
+    // Ждем получения клиентом признака конца потока
+    let mut attempts = 0;
+    while client.is_connected() {
+        attempts += 1;
+        assert!(attempts < 100, "закрытие соединения не обнаружено");
+        time::sleep(Duration::from_millis(10)).await;
+    }
+
+    assert!(client.check().await.is_err());
+}
+
+/// `OBJECT ENCODING` возвращает кодировку, выбранную по значению, а само
+/// значение возвращается в записанном виде
+#[tokio::test]
+async fn object_encoding() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    let long = "x".repeat(100);
+    let cases = [
+        ("42", "int"),
+        ("-9223372036854775808", "int"),
+        ("9223372036854775808", "embstr"),
+        ("007", "embstr"),
+        ("-0", "embstr"),
+        ("+1", "embstr"),
+        ("", "embstr"),
+        ("hello", "embstr"),
+        (long.as_str(), "raw"),
+    ];
+
+    for (value, encoding) in cases {
+        client.set("key", value.to_string().into()).await.unwrap();
+
+        assert_eq!(
+            Some(encoding),
+            client.object_encoding("key").await.unwrap().as_deref(),
+            "{:?}",
+            value
+        );
+        assert_eq!(
+            value.as_bytes(),
+            &client.get("key").await.unwrap().unwrap()[..]
+        );
+    }
+
+    assert!(client.object_encoding("missing").await.unwrap().is_none());
+}
+
+/// Ключи, присутствующие в течение всего перебора, возвращаются ровно один
+/// раз, даже если между порциями добавляются и удаляются другие ключи
+#[tokio::test]
+async fn scan_with_concurrent_changes() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+    let db = server.db();
+
+    for i in 0..100 {
+        db.set(format!("stable:{}", i), "value".into(), None);
+    }
+    for i in 0..50 {
+        db.set(format!("removed:{}", i), "value".into(), None);
+    }
+
+    let mut cursor = 0;
+    let mut round = 0;
+    let mut keys = vec![];
+    loop {
+        let (next, page) = client.scan_page(cursor, None, Some(7)).await.unwrap();
+        keys.extend(page);
+
+        // Изменяем пространство ключей между порциями
+        for i in 0..20 {
+            db.set(format!("added:{}:{}", round, i), "value".into(), None);
+        }
+        for i in 0..5 {
+            db.del(&format!("removed:{}", round * 5 + i));
+        }
+        round += 1;
+        assert!(round < 100, "перебор не завершается");
+
+        if next == 0 {
+            break;
+        }
+        cursor = next;
+    }
+
+    let mut stable: Vec<String> = keys
+        .into_iter()
+        .filter(|key| key.starts_with("stable:"))
+        .collect();
+    stable.sort();
+
+    let mut expected: Vec<String> = (0..100).map(|i| format!("stable:{}", i)).collect();
+    expected.sort();
+
+    assert_eq!(expected, stable);
+}
+
+/// Поток `scan` следует за курсором и возвращает все ключи, в том числе
+/// при переборе из нескольких порций
+#[tokio::test]
+async fn scan_stream_all_keys() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    for i in 0..25 {
+        client
+            .set(&format!("key:{}", i), "value".into())
+            .await
+            .unwrap();
+    }
+    client.set("other", "value".into()).await.unwrap();
+
+    let mut keys: Vec<String> = client
+        .scan(Some("key:*"))
+        .collect::<mini_redis::Result<_>>()
+        .await
+        .unwrap();
+    keys.sort();
+
+    let mut expected: Vec<String> = (0..25).map(|i| format!("key:{}", i)).collect();
+    expected.sort();
+    assert_eq!(expected, keys);
+
+    let all: Vec<String> = client
+        .scan(None)
+        .collect::<mini_redis::Result<_>>()
+        .await
+        .unwrap();
+    assert_eq!(26, all.len());
+}
+
 /// Аналогичен предыдущему тесту, но тестируется
 /// подписка на один канал
 #[tokio::test]
 async fn receive_message_subscribed_channel() {
-    let (addr, _) = start_server().await;
+    let server = TestServer::start().await;
+    let addr = server.addr();
 
-    let client = Client::connect(addr).await.unwrap();
+    let client = server.client().await;
     let mut subscriber = client.subscribe(vec!["hello".into()]).await.unwrap();
 
     tokio::spawn(async move {
@@ -61,9 +340,10 @@ async fn receive_message_subscribed_channel() {
 /// Тестирование получения клиентом сообщений из нескольких подписанных каналов
 #[tokio::test]
 async fn receive_message_multiple_subscribed_channels() {
-    let (addr, _) = start_server().await;
+    let server = TestServer::start().await;
+    let addr = server.addr();
 
-    let client = Client::connect(addr).await.unwrap();
+    let client = server.client().await;
     let mut subscriber = client
         .subscribe(vec!["hello".into(), "world".into()])
         .await
@@ -88,13 +368,215 @@ async fn receive_message_multiple_subscribed_channels() {
     assert_eq!(b"howdy?", &message2.content[..])
 }
 
+/// `EXISTS` учитывает повторяющиеся ключи и не учитывает отсутствующие
+#[tokio::test]
+async fn exists_counts_keys() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    assert_eq!(0, client.exists(&["hello"]).await.unwrap());
+
+    client.set("hello", "world".into()).await.unwrap();
+    client.set("foo", "bar".into()).await.unwrap();
+
+    assert_eq!(1, client.exists(&["hello"]).await.unwrap());
+    assert_eq!(
+        3,
+        client
+            .exists(&["hello", "foo", "missing", "hello"])
+            .await
+            .unwrap()
+    );
+}
+
+/// `mget` возвращает значения в порядке ключей
+#[tokio::test]
+async fn mget_returns_values_in_order() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    client.set("hello", "world".into()).await.unwrap();
+    client.set("foo", "bar".into()).await.unwrap();
+
+    let values = client
+        .mget(&["foo", "missing", "hello", "foo"])
+        .await
+        .unwrap();
+    assert_eq!(
+        vec![
+            Some("bar".into()),
+            None,
+            Some("world".into()),
+            Some("bar".into())
+        ],
+        values
+    );
+}
+
+/// `set_nx`, `set_xx` и `set_get` используют настройки `SET`
+#[tokio::test]
+async fn set_nx_xx_and_get() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    assert!(!client.set_xx("hello", "world".into()).await.unwrap());
+    assert!(client.set_nx("hello", "world".into()).await.unwrap());
+    assert!(!client.set_nx("hello", "other".into()).await.unwrap());
+    assert!(client.set_xx("hello", "again".into()).await.unwrap());
+
+    assert_eq!(
+        Some("again".into()),
+        client.set_get("hello", "last".into()).await.unwrap()
+    );
+    assert_eq!(
+        None,
+        client.set_get("missing", "value".into()).await.unwrap()
+    );
+    assert_eq!(Some("last".into()), client.get("hello").await.unwrap());
+}
+
+/// `mset` и `mset_nx` устанавливают несколько значений за один запрос
+#[tokio::test]
+async fn mset_and_mset_nx() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    client
+        .mset(&[("hello", "world".into()), ("foo", "bar".into())])
+        .await
+        .unwrap();
+    assert_eq!(
+        vec![Some("world".into()), Some("bar".into())],
+        client.mget(&["hello", "foo"]).await.unwrap()
+    );
+
+    assert!(!client
+        .mset_nx(&[("new", "1".into()), ("foo", "2".into())])
+        .await
+        .unwrap());
+    assert_eq!(None, client.get("new").await.unwrap());
+
+    assert!(client
+        .mset_nx(&[("new", "1".into()), ("other", "2".into())])
+        .await
+        .unwrap());
+    assert_eq!(Some("2".into()), client.get("other").await.unwrap());
+}
+
+/// `expire` устанавливает время жизни существующего ключа
+#[tokio::test]
+async fn expire_sets_ttl() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    assert!(!client
+        .expire("hello", Duration::from_secs(60))
+        .await
+        .unwrap());
+
+    client.set("hello", "world".into()).await.unwrap();
+    assert_eq!(Some(None), server.db().ttl("hello"));
+
+    assert!(client
+        .expire("hello", Duration::from_secs(60))
+        .await
+        .unwrap());
+    let ttl = server.db().ttl("hello").unwrap().unwrap();
+    assert!(ttl > Duration::from_secs(50) && ttl <= Duration::from_secs(60));
+
+    // Нулевое время жизни удаляет ключ
+    assert!(client.expire("hello", Duration::ZERO).await.unwrap());
+    assert_eq!(None, client.get("hello").await.unwrap());
+}
+
+/// `persist` удаляет время жизни ключа
+#[tokio::test]
+async fn persist_removes_ttl() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    assert!(!client.persist("hello").await.unwrap());
+
+    client.set("hello", "world".into()).await.unwrap();
+    assert!(!client.persist("hello").await.unwrap());
+
+    client
+        .expire("hello", Duration::from_secs(60))
+        .await
+        .unwrap();
+    assert!(client.persist("hello").await.unwrap());
+    assert_eq!(Some(None), server.db().ttl("hello"));
+}
+
+/// `incr` и `decr` изменяют счетчик и возвращают ошибку для нечисловых
+/// значений
+#[tokio::test]
+async fn incr_and_decr() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    assert_eq!(1, client.incr("counter").await.unwrap());
+    assert_eq!(2, client.incr("counter").await.unwrap());
+    assert_eq!(1, client.decr("counter").await.unwrap());
+    assert_eq!(-1, client.decr("missing").await.unwrap());
+
+    client.set("hello", "world".into()).await.unwrap();
+    let err = client.incr("hello").await.unwrap_err();
+    assert_eq!(
+        "ERR value is not an integer or out of range",
+        err.to_string()
+    );
+
+    // Соединение остается рабочим
+    assert_eq!(Some("1".into()), client.get("counter").await.unwrap());
+}
+
+/// `incr_by` и `decr_by` изменяют счетчик на заданную величину и
+/// проверяют переполнение
+#[tokio::test]
+async fn incr_by_and_decr_by() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    assert_eq!(10, client.incr_by("counter", 10).await.unwrap());
+    assert_eq!(7, client.decr_by("counter", 3).await.unwrap());
+    assert_eq!(-3, client.incr_by("counter", -10).await.unwrap());
+
+    let err = client.incr_by("counter", i64::MIN).await.unwrap_err();
+    assert_eq!("ERR increment or decrement would overflow", err.to_string());
+
+    let err = client.decr_by("counter", i64::MIN).await.unwrap_err();
+    assert_eq!("ERR decrement would overflow", err.to_string());
+
+    assert_eq!(Some("-3".into()), client.get("counter").await.unwrap());
+}
+
+/// `ttl` возвращает оставшееся время жизни ключа
+#[tokio::test]
+async fn ttl_reports_remaining_time() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    assert_eq!(None, client.ttl("hello").await.unwrap());
+
+    client.set("hello", "world".into()).await.unwrap();
+    assert_eq!(Some(None), client.ttl("hello").await.unwrap());
+
+    client
+        .set_expires("hello", "world".into(), Duration::from_secs(60))
+        .await
+        .unwrap();
+    let ttl = client.ttl("hello").await.unwrap().unwrap().unwrap();
+    assert!(ttl > Duration::from_secs(50) && ttl <= Duration::from_secs(60));
+}
+
 /// Тестирование удаления клиентом списка подписанных каналов
 /// при отписке от всех каналов путем отправки пустого вектора
 #[tokio::test]
 async fn unsubscribes_from_channels() {
-    let (addr, _) = start_server().await;
+    let server = TestServer::start().await;
 
-    let client = Client::connect(addr).await.unwrap();
+    let client = server.client().await;
     let mut subscriber = client
         .subscribe(vec!["hello".into(), "world".into()])
         .await
@@ -102,13 +584,423 @@ async fn unsubscribes_from_channels() {
 
     subscriber.unsubscribe(&[]).await.unwrap();
     assert_eq!(subscriber.get_subscribed().len(), 0);
+
+    // Каналы без подписчиков удаляются из БД
+    assert_eq!(0, server.db().num_channels());
 }
 
-async fn start_server() -> (SocketAddr, JoinHandle<()>) {
-    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
-    let addr = listener.local_addr().unwrap();
+/// Каналы отключившегося подписчика удаляются из БД
+#[tokio::test]
+async fn disconnect_removes_unused_channels() {
+    let server = TestServer::start().await;
+
+    let client = server.client().await;
+    let subscriber = client
+        .subscribe(vec!["hello".into(), "world".into()])
+        .await
+        .unwrap();
+    assert_eq!(2, server.db().num_channels());
+
+    drop(subscriber);
+
+    // Сервер обнаруживает отключение асинхронно
+    for _ in 0..100 {
+        if server.db().num_channels() == 0 {
+            return;
+        }
+        time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("Каналы не удалены");
+}
+
+/// `HELLO` возвращает информацию о сервере и уникальный идентификатор
+/// соединения
+#[tokio::test]
+async fn hello_returns_server_info() {
+    let server = TestServer::start().await;
+    let mut first = server.client().await;
+    let mut second = server.client().await;
+
+    let info = first
+        .hello(Some(("default", "secret")), Some("worker-1"))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Bulk("mini-redis".into()), info["server"]);
+    assert_eq!(Frame::Integer(2), info["proto"]);
+    assert_eq!(Frame::Bulk("master".into()), info["role"]);
+
+    let other = second.hello(None, None).await.unwrap();
+    assert_ne!(info["id"], other["id"]);
+
+    // Повторное рукопожатие в том же соединении возвращает тот же идентификатор
+    assert_eq!(info["id"], first.hello(None, None).await.unwrap()["id"]);
+}
+
+/// Неподдерживаемая версия протокола и невалидное имя клиента отклоняются
+#[tokio::test]
+async fn hello_rejects_invalid_options() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    let frame = Frame::Array(vec![Frame::Bulk("hello".into()), Frame::Integer(3)]);
+    let err = client.send_frame(frame).await.unwrap_err();
+    assert_eq!(
+        "NOPROTO",
+        err.downcast_ref::<ServerError>().unwrap().prefix()
+    );
+
+    let err = client.hello(None, Some("bad name")).await.unwrap_err();
+    assert_eq!(
+        &ErrorKind::Unknown,
+        err.downcast_ref::<ServerError>().unwrap().kind()
+    );
+
+    // Соединение остается рабочим
+    client.ping(None).await.unwrap();
+}
+
+/// `close` завершает сессию, не затрагивая данные сервера
+#[tokio::test]
+async fn close_session() {
+    let server = TestServer::start().await;
+
+    let mut client = server.client().await;
+    client.set("hello", "world".into()).await.unwrap();
+    client.close().await.unwrap();
+
+    let mut client = server.client().await;
+    let value = client.get("hello").await.unwrap().unwrap();
+    assert_eq!(b"world", &value[..]);
+}
+
+/// Подписчик закрывает соединение, даже если получены непрочитанные сообщения
+#[tokio::test]
+async fn close_subscriber_with_pending_messages() {
+    let server = TestServer::start().await;
+
+    let subscriber = server
+        .client()
+        .await
+        .subscribe(vec!["hello".into()])
+        .await
+        .unwrap();
+
+    let mut publisher = server.client().await;
+    assert_eq!(1, publisher.publish("hello", "world".into()).await.unwrap());
+
+    subscriber.close().await.unwrap();
+}
+
+/// Перенос ключей между серверами с помощью `DUMP` и `RESTORE`
+#[tokio::test]
+async fn dump_and_restore_between_servers() {
+    let source = TestServer::start().await;
+    let mut client = source.client().await;
+
+    client.set("string", "value".into()).await.unwrap();
+    client.set("number", "42".into()).await.unwrap();
+    assert!(client.dump("missing").await.unwrap().is_none());
+
+    let target = TestServer::start().await;
+    let mut copy = target.client().await;
+
+    for key in ["string", "number"] {
+        let payload = client.dump(key).await.unwrap().unwrap();
+        copy.restore(key, Some(Duration::from_secs(60)), payload, false)
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(Some("value".into()), copy.get("string").await.unwrap());
+    assert_eq!(
+        Some("int"),
+        copy.object_encoding("number").await.unwrap().as_deref()
+    );
+    let ttl = copy.ttl("number").await.unwrap().unwrap().unwrap();
+    assert!(ttl > Duration::from_secs(50) && ttl <= Duration::from_secs(60));
 
-    let handle = tokio::spawn(async move { server::run(listener, tokio::signal::ctrl_c()).await });
+    // Существующий ключ заменяется только при `replace`
+    let payload = client.dump("string").await.unwrap().unwrap();
+    let err = copy
+        .restore("number", None, payload.clone(), false)
+        .await
+        .unwrap_err();
+    assert_eq!("BUSYKEY Target key name already exists.", err.to_string());
+
+    copy.restore("number", None, payload, true).await.unwrap();
+    assert_eq!(Some("value".into()), copy.get("number").await.unwrap());
+    assert_eq!(Some(None), copy.ttl("number").await.unwrap());
+}
+
+/// `HSET`, `HGET` и `HGETALL`
+#[tokio::test]
+async fn hash_commands() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    assert!(client.hset("user", "name", "Alice".into()).await.unwrap());
+    assert!(client.hset("user", "age", "30".into()).await.unwrap());
+    assert!(!client.hset("user", "age", "31".into()).await.unwrap());
+
+    assert_eq!(Some("31".into()), client.hget("user", "age").await.unwrap());
+    assert!(client.hget("user", "missing").await.unwrap().is_none());
+
+    let fields = client.hgetall("user").await.unwrap();
+    assert_eq!(2, fields.len());
+    assert_eq!("Alice", fields["name"]);
+    assert!(client.hgetall("missing").await.unwrap().is_empty());
+
+    // Команды для строк не работают с хешем, и наоборот
+    let err = client.get("user").await.unwrap_err();
+    assert_eq!(
+        "WRONGTYPE Operation against a key holding the wrong kind of value",
+        err.to_string()
+    );
+    client.set("string", "value".into()).await.unwrap();
+    assert!(client.hget("string", "field").await.is_err());
+    assert_eq!(
+        Some("hashtable"),
+        client.object_encoding("user").await.unwrap().as_deref()
+    );
+}
+
+/// `HINCRBY`, `HEXISTS`, `HKEYS`, `HVALS`, `HLEN`, `HMGET` и `HDEL`
+#[tokio::test]
+async fn hash_extras() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    assert_eq!(3, client.hincr_by("user", "visits", 3).await.unwrap());
+    assert_eq!(1, client.hincr_by("user", "visits", -2).await.unwrap());
+    client.hset("user", "name", "Alice".into()).await.unwrap();
+
+    assert!(client.hexists("user", "name").await.unwrap());
+    assert!(!client.hexists("user", "missing").await.unwrap());
+
+    assert_eq!(vec!["name", "visits"], client.hkeys("user").await.unwrap());
+    assert_eq!(vec!["Alice", "1"], client.hvals("user").await.unwrap());
+    assert_eq!(2, client.hlen("user").await.unwrap());
+    assert_eq!(
+        vec![Some("1".into()), None],
+        client.hmget("user", &["visits", "missing"]).await.unwrap()
+    );
+
+    let err = client.hincr_by("user", "name", 1).await.unwrap_err();
+    assert_eq!("ERR hash value is not an integer", err.to_string());
+
+    assert_eq!(2, client.hdel("user", &["name", "visits"]).await.unwrap());
+    assert_eq!(0, client.hlen("user").await.unwrap());
+    assert!(client.hkeys("user").await.unwrap().is_empty());
+}
+
+/// `HSETNX` и `HRANDFIELD` с `WITHVALUES`
+#[tokio::test]
+async fn hset_nx_and_hrandfield() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    assert!(client
+        .hset_nx("user", "name", "Alice".into())
+        .await
+        .unwrap());
+    assert!(!client.hset_nx("user", "name", "Bob".into()).await.unwrap());
+    assert!(client.hset_nx("user", "age", "30".into()).await.unwrap());
+
+    let mut fields = client.hrandfield("user", 10).await.unwrap();
+    fields.sort();
+    assert_eq!(vec!["age", "name"], fields);
+    assert_eq!(4, client.hrandfield("user", -4).await.unwrap().len());
+
+    let pairs = client.hrandfield_with_values("user", -3).await.unwrap();
+    assert_eq!(3, pairs.len());
+    for (field, value) in pairs {
+        assert_eq!(Some(value), client.hget("user", &field).await.unwrap());
+    }
+
+    assert!(client.hrandfield("missing", 3).await.unwrap().is_empty());
+}
+
+/// `HSCAN` перебирает все поля хеша ровно один раз
+#[tokio::test]
+async fn hscan_pages() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    let fields: Vec<_> = (0..100)
+        .map(|i| (format!("field:{}", i), Bytes::from(i.to_string())))
+        .collect();
+    server.db().hset("big", fields).unwrap();
+
+    let mut seen = HashMap::new();
+    let mut cursor = 0;
+    loop {
+        let (next, page) = client
+            .hscan_page("big", cursor, None, Some(7))
+            .await
+            .unwrap();
+        for (field, value) in page {
+            assert!(seen.insert(field, value).is_none());
+        }
+
+        if next == 0 {
+            break;
+        }
+        cursor = next;
+    }
+
+    assert_eq!(100, seen.len());
+    assert_eq!(Some(&Bytes::from("42")), seen.get("field:42"));
+
+    // Фильтрация по шаблону
+    let mut matched = vec![];
+    let mut cursor = 0;
+    loop {
+        let (next, page) = client
+            .hscan_page("big", cursor, Some("field:9*"), None)
+            .await
+            .unwrap();
+        matched.extend(page.into_iter().map(|(field, _)| field));
+
+        if next == 0 {
+            break;
+        }
+        cursor = next;
+    }
+
+    matched.sort();
+    assert_eq!(11, matched.len());
+    assert_eq!("field:9", matched[0]);
+
+    assert_eq!(
+        (0, vec![]),
+        client.hscan_page("missing", 0, None, None).await.unwrap()
+    );
+}
+
+/// Сортированное множество: `ZADD`, `ZSCORE`, `ZREM` и `ZRANGE`
+#[tokio::test]
+async fn sorted_set_commands() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    assert_eq!(
+        3,
+        client
+            .zadd("board", &[(100.0, "alice"), (85.5, "bob"), (92.0, "carl")])
+            .await
+            .unwrap()
+    );
+    assert_eq!(0, client.zadd("board", &[(90.0, "bob")]).await.unwrap());
+
+    assert_eq!(Some(90.0), client.zscore("board", "bob").await.unwrap());
+    assert!(client.zscore("board", "dan").await.unwrap().is_none());
+
+    assert_eq!(
+        vec!["alice", "carl"],
+        client.zrange("board", 0, 1, true).await.unwrap()
+    );
+    assert_eq!(
+        vec![("bob".to_string(), 90.0), ("carl".to_string(), 92.0)],
+        client
+            .zrange_with_scores("board", 0, -2, false)
+            .await
+            .unwrap()
+    );
+
+    // Бесконечные оценки
+    client
+        .zadd("board", &[(f64::NEG_INFINITY, "zed")])
+        .await
+        .unwrap();
+    assert_eq!(
+        Some(f64::NEG_INFINITY),
+        client.zscore("board", "zed").await.unwrap()
+    );
+
+    assert_eq!(
+        2,
+        client.zrem("board", &["zed", "bob", "dan"]).await.unwrap()
+    );
+    assert_eq!(
+        vec!["carl", "alice"],
+        client.zrange("board", 0, -1, false).await.unwrap()
+    );
+    assert!(client
+        .zrange("missing", 0, -1, false)
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        Some("skiplist"),
+        client.object_encoding("board").await.unwrap().as_deref()
+    );
+}
+
+/// `ZRANGEBYSCORE`, `ZCOUNT`, `ZINCRBY` и `ZRANK`
+#[tokio::test]
+async fn sorted_set_ranges() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    client
+        .zadd("board", &[(100.0, "alice"), (85.5, "bob"), (92.0, "carl")])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        vec!["carl", "alice"],
+        client
+            .zrange_by_score("board", Bound::Excluded(90.0), Bound::Unbounded)
+            .await
+            .unwrap()
+    );
+    assert_eq!(
+        vec!["bob"],
+        client
+            .zrange_by_score("board", Bound::Unbounded, Bound::Excluded(92.0))
+            .await
+            .unwrap()
+    );
+    assert_eq!(
+        2,
+        client
+            .zcount("board", Bound::Included(85.5), Bound::Included(92.0))
+            .await
+            .unwrap()
+    );
+
+    assert_eq!(Some(0), client.zrank("board", "bob").await.unwrap());
+    assert_eq!(106.0, client.zincr_by("board", 20.5, "bob").await.unwrap());
+    assert_eq!(Some(2), client.zrank("board", "bob").await.unwrap());
+    assert!(client.zrank("board", "dan").await.unwrap().is_none());
+}
+
+/// `ZRANGEBYLEX`
+#[tokio::test]
+async fn sorted_set_lex_ranges() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    client
+        .zadd(
+            "words",
+            &[(0.0, "apple"), (0.0, "car"), (0.0, "cart"), (0.0, "dog")],
+        )
+        .await
+        .unwrap();
 
-    (addr, handle)
+    assert_eq!(
+        vec!["car", "cart"],
+        client
+            .zrange_by_lex("words", Bound::Included("car"), Bound::Excluded("cas"))
+            .await
+            .unwrap()
+    );
+    assert_eq!(
+        vec!["apple", "car", "cart", "dog"],
+        client
+            .zrange_by_lex("words", Bound::Unbounded, Bound::Unbounded)
+            .await
+            .unwrap()
+    );
 }
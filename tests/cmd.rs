@@ -0,0 +1,39 @@
+use mini_redis::cmd::{self, Command};
+use mini_redis::Frame;
+
+/// Таблица команд отсортирована по названию, и поиск в ней не зависит от
+/// регистра
+#[test]
+fn command_table_sorted() {
+    for pair in cmd::COMMAND_TABLE.windows(2) {
+        assert!(
+            pair[0].name < pair[1].name,
+            "{} >= {}",
+            pair[0].name,
+            pair[1].name
+        );
+    }
+
+    assert_eq!("get", cmd::lookup("GET").unwrap().name);
+    assert!(cmd::lookup("missing").is_none());
+}
+
+/// Каждая команда из таблицы распознается сервером при текущем наборе флагов
+#[test]
+fn command_table_parses() {
+    for info in cmd::COMMAND_TABLE {
+        // Аргументы не обязательно валидны для команды, но не влияют на
+        // распознавание ее названия: ошибка разбора аргументов возможна
+        // только для известной команды
+        let args = info.arity.unsigned_abs().max(1) as usize - 1;
+        let mut frame = vec![Frame::Bulk(info.name.to_uppercase().into())];
+        frame.extend((0..args).map(|_| Frame::Bulk("1".into())));
+
+        let res = Command::from_frame(Frame::Array(frame));
+        assert!(
+            !matches!(res, Ok(Command::Unknown(_))),
+            "{} не распознана",
+            info.name
+        );
+    }
+}
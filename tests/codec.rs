@@ -0,0 +1,80 @@
+use mini_redis::codec::FrameCodec;
+use mini_redis::test_util::TestServer;
+use mini_redis::Frame;
+
+use bytes::BytesMut;
+use futures_util::SinkExt;
+use tokio::net::TcpStream;
+use tokio_stream::StreamExt;
+use tokio_util::codec::{Decoder, Encoder, Framed};
+
+/// Кадры, закодированные кодеком, разбираются им обратно, в том числе
+/// при получении по частям
+#[test]
+fn round_trip() {
+    let frame = Frame::Array(vec![
+        Frame::Bulk("set".into()),
+        Frame::Simple("OK".into()),
+        Frame::Error("ERR oops".into()),
+        Frame::Integer(42),
+        Frame::Null,
+    ]);
+
+    let mut codec = FrameCodec::new();
+    let mut encoded = BytesMut::new();
+    codec.encode(&frame, &mut encoded).unwrap();
+    codec.encode(Frame::Integer(1), &mut encoded).unwrap();
+
+    let mut src = BytesMut::new();
+    let mut frames = vec![];
+    for byte in encoded {
+        src.extend_from_slice(&[byte]);
+        if let Some(frame) = codec.decode(&mut src).unwrap() {
+            frames.push(frame);
+        }
+    }
+
+    assert_eq!(vec![frame, Frame::Integer(1)], frames);
+    assert!(src.is_empty());
+}
+
+/// Строгий режим отклоняет заголовки длины с лишними символами
+#[test]
+fn strict() {
+    let mut src = BytesMut::from(&b"$3abc\r\nfoo\r\n"[..]);
+    assert!(FrameCodec::strict().decode(&mut src.clone()).is_err());
+    assert!(FrameCodec::new().decode(&mut src).unwrap().is_some());
+}
+
+/// `Framed` с кодеком обменивается командами с сервером
+#[tokio::test]
+async fn framed_server() {
+    let server = TestServer::start().await;
+    let socket = TcpStream::connect(server.addr()).await.unwrap();
+    let mut framed = Framed::new(socket, FrameCodec::new());
+
+    framed
+        .send(Frame::Array(vec![
+            Frame::Bulk("set".into()),
+            Frame::Bulk("hello".into()),
+            Frame::Bulk("world".into()),
+        ]))
+        .await
+        .unwrap();
+    framed
+        .send(Frame::Array(vec![
+            Frame::Bulk("get".into()),
+            Frame::Bulk("hello".into()),
+        ]))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        Frame::Simple("OK".into()),
+        framed.next().await.unwrap().unwrap()
+    );
+    assert_eq!(
+        Frame::Bulk("world".into()),
+        framed.next().await.unwrap().unwrap()
+    );
+}
@@ -0,0 +1,1040 @@
+use mini_redis::cmd::{SetCondition, ZaddComparison, ZaddOptions};
+use mini_redis::snapshot::SnapshotValue;
+use mini_redis::DbDropGuard;
+use std::ops::Bound;
+use std::time::{Duration, SystemTime};
+
+/// Встроенное хранилище без сервера: `get`, `set` и `del`
+#[tokio::test]
+async fn get_set_del() {
+    let guard = DbDropGuard::new();
+    let db = guard.db();
+
+    assert!(db.get("hello").is_none());
+
+    db.set("hello".to_string(), "world".into(), None);
+    assert_eq!(b"world", &db.get("hello").unwrap()[..]);
+
+    // Клоны `Db` работают с общим состоянием
+    let clone = db.clone();
+    clone.set("hello".to_string(), "again".into(), None);
+    assert_eq!(b"again", &db.get("hello").unwrap()[..]);
+
+    assert!(db.del("hello"));
+    assert!(db.get("hello").is_none());
+    assert!(!db.del("hello"));
+}
+
+/// Значения с временем жизни удаляются фоновой задачей
+#[tokio::test(start_paused = true)]
+async fn expiration_and_ttl() {
+    let guard = DbDropGuard::new();
+    let db = guard.db();
+
+    db.set(
+        "hello".to_string(),
+        "world".into(),
+        Some(Duration::from_secs(1)),
+    );
+    db.set("forever".to_string(), "value".into(), None);
+
+    assert_eq!(Some(Some(Duration::from_secs(1))), db.ttl("hello"));
+    assert_eq!(Some(None), db.ttl("forever"));
+    assert_eq!(None, db.ttl("missing"));
+
+    tokio::time::sleep(Duration::from_millis(400)).await;
+    assert_eq!(Some(Some(Duration::from_millis(600))), db.ttl("hello"));
+
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert!(db.get("hello").is_none());
+    assert_eq!(None, db.ttl("hello"));
+    assert!(db.get("forever").is_some());
+}
+
+/// Удаленное значение не удаляется повторно по истечении старого времени жизни
+#[tokio::test(start_paused = true)]
+async fn del_clears_expiration() {
+    let guard = DbDropGuard::new();
+    let db = guard.db();
+
+    db.set(
+        "hello".to_string(),
+        "world".into(),
+        Some(Duration::from_secs(1)),
+    );
+    assert!(db.del("hello"));
+
+    db.set("hello".to_string(), "again".into(), None);
+
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert_eq!(b"again", &db.get("hello").unwrap()[..]);
+}
+
+/// Пока фоновая очистка отключена, истекшие значения не возвращаются при
+/// чтении. Очистка удаляет ключи порциями
+#[tokio::test(start_paused = true)]
+async fn active_expire_control() {
+    let guard = DbDropGuard::new();
+    let db = guard.db();
+    db.set_active_expire(false);
+    db.set_purge_batch_size(1);
+    db.set_purge_min_interval(Duration::from_millis(100));
+
+    for key in ["a", "b", "c"] {
+        db.set(
+            key.to_string(),
+            "value".into(),
+            Some(Duration::from_secs(1)),
+        );
+    }
+
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert!(db.get("a").is_none());
+    assert_eq!(None, db.ttl("b"));
+    assert_eq!(0, db.exists(&["a", "b"]));
+    assert!(!db.del("c"));
+
+    db.set_active_expire(true);
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert!(db.get("a").is_none());
+    assert!(db.get("b").is_none());
+}
+
+/// `expire` заменяет время жизни существующего значения, нулевое время
+/// жизни удаляет значение
+#[tokio::test(start_paused = true)]
+async fn expire() {
+    let guard = DbDropGuard::new();
+    let db = guard.db();
+
+    db.set("a".to_string(), "value".into(), None);
+    db.set(
+        "b".to_string(),
+        "value".into(),
+        Some(Duration::from_secs(1)),
+    );
+    db.set("c".to_string(), "value".into(), None);
+
+    assert!(db.expire("a", Duration::from_secs(1)));
+    assert!(db.expire("b", Duration::from_secs(10)));
+    assert!(db.expire("c", Duration::ZERO));
+    assert!(!db.expire("missing", Duration::from_secs(1)));
+    assert!(db.get("c").is_none());
+
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert!(db.get("a").is_none());
+    assert!(!db.expire("a", Duration::from_secs(1)));
+    assert_eq!(Some("value".into()), db.get("b"));
+}
+
+/// `expire_at` устанавливает момент истечения по системным часам, момент в
+/// прошлом удаляет значение
+#[tokio::test]
+async fn expire_at() {
+    let guard = DbDropGuard::new();
+    let db = guard.db();
+
+    db.set("a".to_string(), "value".into(), None);
+    db.set("b".to_string(), "value".into(), None);
+
+    assert!(db.expire_at("a", SystemTime::now() + Duration::from_secs(60)));
+    let ttl = db.ttl("a").unwrap().unwrap();
+    assert!(ttl > Duration::from_secs(50) && ttl <= Duration::from_secs(60));
+
+    assert!(db.expire_at("b", SystemTime::now() - Duration::from_secs(1)));
+    assert!(db.get("b").is_none());
+    assert!(!db.expire_at("missing", SystemTime::now() + Duration::from_secs(60)));
+}
+
+/// `expire_time` возвращает исходный момент истечения
+#[tokio::test]
+async fn expire_time() {
+    let guard = DbDropGuard::new();
+    let db = guard.db();
+
+    db.set("a".to_string(), "value".into(), None);
+    db.set(
+        "b".to_string(),
+        "value".into(),
+        Some(Duration::from_secs(60)),
+    );
+
+    assert_eq!(Some(None), db.expire_time("a"));
+    assert_eq!(None, db.expire_time("missing"));
+
+    let when = SystemTime::now() + Duration::from_secs(3600);
+    assert!(db.expire_at("a", when));
+    assert_eq!(Some(Some(when)), db.expire_time("a"));
+
+    let when = db.expire_time("b").unwrap().unwrap();
+    let remaining = when.duration_since(SystemTime::now()).unwrap();
+    assert!(remaining > Duration::from_secs(50) && remaining <= Duration::from_secs(60));
+}
+
+/// После `persist` значение не истекает
+#[tokio::test(start_paused = true)]
+async fn persist() {
+    let guard = DbDropGuard::new();
+    let db = guard.db();
+
+    db.set(
+        "a".to_string(),
+        "value".into(),
+        Some(Duration::from_secs(1)),
+    );
+    assert!(db.persist("a"));
+    assert!(!db.persist("missing"));
+
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert_eq!(Some("value".into()), db.get("a"));
+    assert_eq!(Some(None), db.ttl("a"));
+}
+
+/// `set_with` проверяет условие, сохраняет время жизни и возвращает
+/// предыдущее значение
+#[tokio::test]
+async fn set_with() {
+    let guard = DbDropGuard::new();
+    let db = guard.db();
+
+    let nx = Some(SetCondition::Nx);
+    let xx = Some(SetCondition::Xx);
+
+    assert_eq!(
+        (false, None),
+        db.set_with("a".to_string(), "1".into(), None, xx, false, true)
+            .unwrap()
+    );
+    assert!(db.get("a").is_none());
+
+    assert_eq!(
+        (true, None),
+        db.set_with("a".to_string(), "1".into(), None, nx, false, true)
+            .unwrap()
+    );
+    assert_eq!(
+        (false, Some("1".into())),
+        db.set_with("a".to_string(), "2".into(), None, nx, false, true)
+            .unwrap()
+    );
+    assert_eq!(Some("1".into()), db.get("a"));
+
+    db.expire("a", Duration::from_secs(60));
+    assert_eq!(
+        (true, Some("1".into())),
+        db.set_with("a".to_string(), "2".into(), None, xx, true, true)
+            .unwrap()
+    );
+    assert_eq!(Some("2".into()), db.get("a"));
+    assert!(db.ttl("a").unwrap().is_some());
+
+    // Без `keep_ttl` время жизни удаляется
+    db.set_with("a".to_string(), "3".into(), None, None, false, true)
+        .unwrap();
+    assert_eq!(Some(None), db.ttl("a"));
+
+    // С `get` предыдущее значение, не являющееся строкой, не заменяется
+    db.hset("hash", vec![("f".to_string(), "v".into())])
+        .unwrap();
+    let err = db
+        .set_with("hash".to_string(), "x".into(), None, None, false, true)
+        .unwrap_err();
+    assert!(err.to_string().starts_with("WRONGTYPE"));
+    assert_eq!(Some("v".into()), db.hget("hash", "f").unwrap());
+
+    // Без `get` значение заменяется, как в `SET`
+    assert_eq!(
+        (true, None),
+        db.set_with("hash".to_string(), "x".into(), None, None, false, false)
+            .unwrap()
+    );
+    assert_eq!(Some("x".into()), db.get("hash"));
+}
+
+/// `mset` заменяет значения и время жизни, `mset_nx` устанавливает значения,
+/// только если ни один ключ не существует
+#[tokio::test]
+async fn mset_and_mset_nx() {
+    let guard = DbDropGuard::new();
+    let db = guard.db();
+
+    db.set("a".to_string(), "old".into(), Some(Duration::from_secs(60)));
+    db.mset(vec![
+        ("a".to_string(), "1".into()),
+        ("b".to_string(), "2".into()),
+        ("b".to_string(), "3".into()),
+    ]);
+    assert_eq!(Some("1".into()), db.get("a"));
+    assert_eq!(Some("3".into()), db.get("b"));
+    assert_eq!(Some(None), db.ttl("a"));
+
+    assert!(!db.mset_nx(vec![
+        ("c".to_string(), "1".into()),
+        ("a".to_string(), "2".into()),
+    ]));
+    assert!(db.get("c").is_none());
+    assert_eq!(Some("1".into()), db.get("a"));
+
+    assert!(db.mset_nx(vec![
+        ("c".to_string(), "1".into()),
+        ("d".to_string(), "2".into()),
+    ]));
+    assert_eq!(Some("2".into()), db.get("d"));
+}
+
+/// `get_range` поддерживает отрицательные индексы и ограничивает диапазон
+/// границами значения
+#[tokio::test]
+async fn get_range() {
+    let guard = DbDropGuard::new();
+    let db = guard.db();
+
+    db.set("a".to_string(), "This is a string".into(), None);
+    db.set("n".to_string(), "12345".into(), None);
+
+    assert_eq!("This", db.get_range("a", 0, 3).unwrap());
+    assert_eq!("ing", db.get_range("a", -3, -1).unwrap());
+    assert_eq!("This is a string", db.get_range("a", 0, -1).unwrap());
+    assert_eq!("string", db.get_range("a", 10, 100).unwrap());
+    assert_eq!("This", db.get_range("a", -100, 3).unwrap());
+    assert_eq!("", db.get_range("a", 5, 2).unwrap());
+    assert_eq!("", db.get_range("a", 100, 200).unwrap());
+    assert_eq!("", db.get_range("missing", 0, -1).unwrap());
+    assert_eq!("234", db.get_range("n", 1, 3).unwrap());
+}
+
+/// `set_range` перезаписывает часть строки и дополняет ее нулевыми байтами
+#[tokio::test]
+async fn set_range() {
+    let guard = DbDropGuard::new();
+    let db = guard.db();
+
+    db.set(
+        "a".to_string(),
+        "Hello World".into(),
+        Some(Duration::from_secs(60)),
+    );
+    assert_eq!(11, db.set_range("a", 6, b"Redis").unwrap());
+    assert_eq!(Some("Hello Redis".into()), db.get("a"));
+    assert!(db.ttl("a").unwrap().is_some());
+
+    assert_eq!(13, db.set_range("a", 11, b"!!").unwrap());
+    assert_eq!(Some("Hello Redis!!".into()), db.get("a"));
+
+    assert_eq!(5, db.set_range("b", 3, b"ab").unwrap());
+    assert_eq!(Some("\0\0\0ab".into()), db.get("b"));
+
+    // Пустое значение не создает ключ
+    assert_eq!(0, db.set_range("c", 10, b"").unwrap());
+    assert!(db.get("c").is_none());
+    assert_eq!(13, db.set_range("a", 100, b"").unwrap());
+
+    let err = db.set_range("a", 512 * 1024 * 1024, b"x").unwrap_err();
+    assert!(err.to_string().contains("maximum allowed size"), "{}", err);
+}
+
+/// `incr_by` изменяет только целые числа и сохраняет время жизни
+#[tokio::test]
+async fn incr_by() {
+    let guard = DbDropGuard::new();
+    let db = guard.db();
+
+    assert_eq!(1, db.incr_by("counter", 1).unwrap());
+    assert_eq!(-1, db.incr_by("counter", -2).unwrap());
+    assert_eq!(Some("-1".into()), db.get("counter"));
+
+    db.set(
+        "session".to_string(),
+        "10".into(),
+        Some(Duration::from_secs(60)),
+    );
+    assert_eq!(11, db.incr_by("session", 1).unwrap());
+    assert!(db.ttl("session").unwrap().is_some());
+
+    db.set("text".to_string(), "abc".into(), None);
+    db.set("padded".to_string(), "007".into(), None);
+    assert!(db.incr_by("text", 1).is_err());
+    assert!(db.incr_by("padded", 1).is_err());
+    assert_eq!(Some("abc".into()), db.get("text"));
+
+    db.set("max".to_string(), i64::MAX.to_string().into(), None);
+    let err = db.incr_by("max", 1).unwrap_err();
+    assert!(err.to_string().contains("overflow"), "{}", err);
+    assert_eq!(Some(i64::MAX.to_string().into()), db.get("max"));
+}
+
+/// `incr_by_float` хранит результат без экспоненты и незначащих нулей
+#[tokio::test]
+async fn incr_by_float() {
+    let guard = DbDropGuard::new();
+    let db = guard.db();
+
+    db.set("a".to_string(), "10.50".into(), None);
+    assert_eq!(10.6, db.incr_by_float("a", 0.1).unwrap());
+    assert_eq!(Some("10.6".into()), db.get("a"));
+
+    db.set("b".to_string(), "5.0e3".into(), None);
+    assert_eq!(5200.0, db.incr_by_float("b", 200.0).unwrap());
+    assert_eq!(Some("5200".into()), db.get("b"));
+
+    assert_eq!(-0.5, db.incr_by_float("missing", -0.5).unwrap());
+    assert_eq!(Some("-0.5".into()), db.get("missing"));
+
+    assert_eq!(1e21, db.incr_by_float("big", 1e21).unwrap());
+    assert_eq!(Some("1000000000000000000000".into()), db.get("big"));
+
+    db.set("text".to_string(), "abc".into(), None);
+    let err = db.incr_by_float("text", 1.0).unwrap_err();
+    assert_eq!("ERR value is not a valid float", err.to_string());
+
+    let err = db.incr_by_float("a", f64::INFINITY).unwrap_err();
+    assert_eq!(
+        "ERR increment would produce NaN or Infinity",
+        err.to_string()
+    );
+    assert_eq!(Some("10.6".into()), db.get("a"));
+}
+
+/// Pub/sub без сервера
+#[tokio::test]
+async fn publish_subscribe() {
+    let guard = DbDropGuard::new();
+    let db = guard.db();
+
+    assert_eq!(0, db.publish("news", "ignored".into()));
+
+    let mut first = db.subscribe("news".to_string());
+    let mut second = db.subscribe("news".to_string());
+
+    assert_eq!(2, db.publish("news", "hello".into()));
+    assert_eq!(b"hello", &first.recv().await.unwrap()[..]);
+    assert_eq!(b"hello", &second.recv().await.unwrap()[..]);
+}
+
+/// Канал удаляется при публикации, если у него не осталось подписчиков
+#[tokio::test]
+async fn publish_removes_unused_channels() {
+    let guard = DbDropGuard::new();
+    let db = guard.db();
+
+    let first = db.subscribe("news".to_string());
+    let second = db.subscribe("news".to_string());
+    assert_eq!(1, db.num_channels());
+
+    drop(first);
+    assert_eq!(1, db.publish("news", "hello".into()));
+    assert_eq!(1, db.num_channels());
+
+    drop(second);
+    assert_eq!(0, db.publish("news", "hello".into()));
+    assert_eq!(0, db.num_channels());
+}
+
+/// `memory_stats` учитывает размер ключей и значений
+#[tokio::test]
+async fn memory_stats() {
+    let guard = DbDropGuard::new();
+    let db = guard.db();
+
+    let empty = db.memory_stats();
+    assert_eq!(0, empty.keys);
+    assert_eq!(0, empty.dataset_bytes);
+    assert!(empty.doctor().contains("empty"));
+
+    db.set("small".to_string(), "1".into(), None);
+    db.set("large".to_string(), vec![0; 10_000].into(), None);
+
+    let stats = db.memory_stats();
+    assert_eq!(2, stats.keys);
+    assert!(stats.dataset_bytes >= 10_000, "{:?}", stats);
+    assert!(stats.overhead_bytes > 0);
+
+    if cfg!(target_os = "linux") {
+        assert!(stats.rss_bytes.unwrap() > 0);
+        assert!(stats.peak_rss_bytes.unwrap() >= stats.rss_bytes.unwrap());
+        assert!(stats.fragmentation().is_some());
+    }
+
+    assert!(db.del("large"));
+    assert!(db.memory_stats().dataset_bytes < 10_000);
+}
+
+/// `throttle` разрешает всплеск из `max_burst + 1` запросов, а затем
+/// отклоняет запросы до освобождения места
+#[tokio::test]
+async fn throttle() {
+    let guard = DbDropGuard::new();
+    let db = guard.db();
+    let period = Duration::from_secs(60);
+
+    // 10 запросов в минуту со всплеском до 4 дополнительных запросов
+    for remaining in (0..5).rev() {
+        let res = db.throttle("user:1", 4, 10, period, 1).unwrap();
+        assert!(res.allowed);
+        assert_eq!(5, res.limit);
+        assert_eq!(remaining, res.remaining);
+        assert!(res.retry_after.is_none());
+    }
+
+    let res = db.throttle("user:1", 4, 10, period, 1).unwrap();
+    assert!(!res.allowed);
+    assert_eq!(0, res.remaining);
+    let retry_after = res.retry_after.unwrap();
+    assert!(retry_after > Duration::from_secs(5) && retry_after <= Duration::from_secs(6));
+    assert!(res.reset_after <= Duration::from_secs(30));
+
+    // Ограничения разных ключей независимы
+    assert!(db.throttle("user:2", 4, 10, period, 1).unwrap().allowed);
+
+    // Запрос, превышающий лимит, не может быть разрешен
+    let res = db.throttle("user:3", 4, 10, period, 6).unwrap();
+    assert!(!res.allowed);
+    assert!(res.retry_after.is_none());
+
+    // Состояние хранится со временем жизни до сброса ограничения
+    assert!(db.ttl("user:1").unwrap().unwrap() <= Duration::from_secs(30));
+    assert!(db.get("user:3").is_none());
+
+    db.set("string".to_string(), "value".into(), None);
+    assert!(db.throttle("string", 4, 10, period, 1).is_err());
+
+    // Целое число, записанное пользователем, не считается состоянием
+    // ограничения и не изменяется
+    db.set("counter".to_string(), "42".into(), None);
+    assert!(db.throttle("counter", 4, 10, period, 1).is_err());
+    assert_eq!(Some("42".into()), db.get("counter"));
+
+    // Состояние ограничения не является счетчиком
+    assert!(db.incr_by("user:1", 1).is_err());
+
+    // Нулевые `count` и `period` отклоняются без паники
+    assert!(db.throttle("user:4", 4, 0, period, 1).is_err());
+    assert!(db.throttle("user:4", 4, 10, Duration::ZERO, 1).is_err());
+    assert!(db.get("user:4").is_none());
+}
+
+/// `wait_for_key` возвращает значение после его установки, пробуждая
+/// ожидающие задачи в порядке вызова
+#[tokio::test]
+async fn wait_for_key() {
+    let guard = DbDropGuard::new();
+    let db = guard.db();
+
+    db.set("ready".to_string(), "now".into(), None);
+    assert_eq!(Some("now".into()), db.wait_for_key("ready", None).await);
+
+    let timeout = Some(Duration::from_millis(20));
+    assert!(db.wait_for_key("missing", timeout).await.is_none());
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut tasks = vec![];
+    for i in 0..3 {
+        let db = db.clone();
+        let tx = tx.clone();
+        tasks.push(tokio::spawn(async move {
+            let value = db.wait_for_key("result", None).await;
+            tx.send(i).unwrap();
+            value
+        }));
+
+        // Задача должна зарегистрироваться до запуска следующей
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    // Отмененная задача не забирает пробуждение у остальных
+    let cancelled = {
+        let db = db.clone();
+        tokio::spawn(async move { db.wait_for_key("result", None).await })
+    };
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    cancelled.abort();
+
+    db.set("result".to_string(), "done".into(), None);
+
+    for task in tasks {
+        assert_eq!(Some("done".into()), task.await.unwrap());
+    }
+    let order: Vec<i32> = (0..3).map(|_| rx.try_recv().unwrap()).collect();
+    assert_eq!(vec![0, 1, 2], order);
+}
+
+/// Задача с истекшим временем ожидания покидает очередь и не забирает
+/// значение у следующей задачи
+#[tokio::test]
+async fn wait_for_key_timeout() {
+    let guard = DbDropGuard::new();
+    let db = guard.db();
+
+    let first = {
+        let db = db.clone();
+        tokio::spawn(async move {
+            db.wait_for_key("key", Some(Duration::from_millis(30)))
+                .await
+        })
+    };
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    let second = {
+        let db = db.clone();
+        tokio::spawn(async move { db.wait_for_key("key", Some(Duration::from_secs(5))).await })
+    };
+
+    assert!(first.await.unwrap().is_none());
+
+    db.set("key".to_string(), "value".into(), None);
+    assert_eq!(Some("value".into()), second.await.unwrap());
+}
+
+/// `dump` и `restore` переносят значения с сохранением представления и
+/// отклоняют поврежденные данные
+#[tokio::test]
+async fn dump_and_restore() {
+    let guard = DbDropGuard::new();
+    let db = guard.db();
+
+    assert!(db.dump("missing").is_none());
+
+    db.set("string".to_string(), "value".into(), None);
+    db.set("number".to_string(), "-42".into(), None);
+
+    let string = db.dump("string").unwrap();
+    let number = db.dump("number").unwrap();
+
+    // Целое число сериализуется как `i64`
+    assert_eq!(1 + 8 + 2 + 8, number.len());
+
+    db.restore("copy".to_string(), &number, None, false)
+        .unwrap();
+    assert_eq!(Some("-42".into()), db.get("copy"));
+    assert_eq!(Some(None), db.ttl("copy"));
+
+    // Существующий ключ заменяется только при `replace`
+    let err = db
+        .restore("copy".to_string(), &string, None, false)
+        .unwrap_err();
+    assert_eq!("BUSYKEY Target key name already exists.", err.to_string());
+
+    let expires_at = SystemTime::now() + Duration::from_secs(60);
+    db.restore("copy".to_string(), &string, Some(expires_at), true)
+        .unwrap();
+    assert_eq!(Some("value".into()), db.get("copy"));
+    assert_eq!(Some(Some(expires_at)), db.expire_time("copy"));
+
+    // Момент истечения в прошлом удаляет заменяемый ключ
+    let past = SystemTime::now() - Duration::from_secs(1);
+    db.restore("copy".to_string(), &string, Some(past), true)
+        .unwrap();
+    assert!(db.get("copy").is_none());
+
+    // Поврежденные данные не восстанавливаются
+    let mut corrupted = string.to_vec();
+    corrupted[1] ^= 1;
+    let err = db
+        .restore("copy".to_string(), &corrupted, None, false)
+        .unwrap_err();
+    assert_eq!(
+        "ERR DUMP payload version or checksum are wrong",
+        err.to_string()
+    );
+    assert!(db.restore("copy".to_string(), b"", None, false).is_err());
+    assert!(db.get("copy").is_none());
+}
+
+/// Хеш: `hset`, `hget`, `hdel` и `hgetall`, ошибки типа и сериализация
+#[tokio::test]
+async fn hash() {
+    let guard = DbDropGuard::new();
+    let db = guard.db();
+
+    let fields = vec![
+        ("name".to_string(), "Alice".into()),
+        ("age".to_string(), "30".into()),
+    ];
+    assert_eq!(2, db.hset("user", fields).unwrap());
+    assert_eq!(
+        0,
+        db.hset("user", vec![("age".to_string(), "31".into())])
+            .unwrap()
+    );
+
+    assert_eq!(Some("31".into()), db.hget("user", "age").unwrap());
+    assert!(db.hget("user", "missing").unwrap().is_none());
+    assert!(db.hget("missing", "age").unwrap().is_none());
+    assert_eq!(
+        vec![
+            ("age".to_string(), "31".into()),
+            ("name".to_string(), "Alice".into())
+        ],
+        db.hgetall("user").unwrap()
+    );
+
+    // Хеш не является строкой, но входит в снимок
+    assert!(db.get("user").is_none());
+    assert!(db.get_range("user", 0, -1).is_err());
+    assert!(db.incr_by("user", 1).is_err());
+    let entry = db.snapshot().next().unwrap();
+    assert_eq!(
+        SnapshotValue::Hash(vec![
+            ("age".to_string(), "31".into()),
+            ("name".to_string(), "Alice".into()),
+        ]),
+        entry.value
+    );
+
+    // Хеш переносится с помощью `dump` и `restore`
+    let payload = db.dump("user").unwrap();
+    db.restore("copy".to_string(), &payload, None, false)
+        .unwrap();
+    assert_eq!(db.hgetall("user").unwrap(), db.hgetall("copy").unwrap());
+
+    // Удаление последнего поля удаляет хеш
+    assert_eq!(1, db.hdel("user", &["age", "missing"]).unwrap());
+    assert_eq!(1, db.hdel("user", &["name"]).unwrap());
+    assert_eq!(0, db.exists(&["user"]));
+    assert!(db.hgetall("user").unwrap().is_empty());
+
+    // Строка не является хешем
+    db.set("string".to_string(), "value".into(), None);
+    let err = db.hget("string", "field").unwrap_err();
+    assert_eq!(
+        "WRONGTYPE Operation against a key holding the wrong kind of value",
+        err.to_string()
+    );
+    assert!(db
+        .hset("string", vec![("f".to_string(), "v".into())])
+        .is_err());
+    assert!(db.hdel("string", &["f"]).is_err());
+    assert_eq!(Some("value".into()), db.get("string"));
+}
+
+/// `hincr_by`, `hexists`, `hlen` и `hmget`
+#[tokio::test]
+async fn hash_extras() {
+    let guard = DbDropGuard::new();
+    let db = guard.db();
+
+    assert_eq!(5, db.hincr_by("stats", "visits", 5).unwrap());
+    assert_eq!(-1, db.hincr_by("stats", "visits", -6).unwrap());
+    assert_eq!(Some("-1".into()), db.hget("stats", "visits").unwrap());
+
+    db.hset("stats", vec![("name".to_string(), "home".into())])
+        .unwrap();
+    let err = db.hincr_by("stats", "name", 1).unwrap_err();
+    assert_eq!("ERR hash value is not an integer", err.to_string());
+
+    db.hset(
+        "stats",
+        vec![("max".to_string(), i64::MAX.to_string().into())],
+    )
+    .unwrap();
+    let err = db.hincr_by("stats", "max", 1).unwrap_err();
+    assert_eq!("ERR increment or decrement would overflow", err.to_string());
+
+    assert!(db.hexists("stats", "visits").unwrap());
+    assert!(!db.hexists("stats", "missing").unwrap());
+    assert!(!db.hexists("missing", "visits").unwrap());
+
+    assert_eq!(3, db.hlen("stats").unwrap());
+    assert_eq!(0, db.hlen("missing").unwrap());
+
+    assert_eq!(
+        vec![Some("home".into()), None, Some("-1".into())],
+        db.hmget("stats", &["name", "missing", "visits"]).unwrap()
+    );
+    assert_eq!(vec![None, None], db.hmget("missing", &["a", "b"]).unwrap());
+
+    db.set("string".to_string(), "1".into(), None);
+    assert!(db.hincr_by("string", "field", 1).is_err());
+    assert!(db.hexists("string", "field").is_err());
+    assert!(db.hlen("string").is_err());
+    assert!(db.hmget("string", &["field"]).is_err());
+}
+
+/// `hset_nx` не заменяет поле, `hrandfield` выбирает различные или
+/// повторяющиеся поля
+#[tokio::test]
+async fn hset_nx_and_hrandfield() {
+    let guard = DbDropGuard::new();
+    let db = guard.db();
+
+    assert!(db
+        .hset_nx("user", "name".to_string(), "Alice".into())
+        .unwrap());
+    assert!(!db
+        .hset_nx("user", "name".to_string(), "Bob".into())
+        .unwrap());
+    assert!(db.hset_nx("user", "age".to_string(), "30".into()).unwrap());
+    assert_eq!(Some("Alice".into()), db.hget("user", "name").unwrap());
+
+    let fields = db.hgetall("user").unwrap();
+
+    // Положительное количество: различные поля, не больше размера хеша
+    let mut sample = db.hrandfield("user", 5).unwrap();
+    sample.sort();
+    assert_eq!(fields, sample);
+    assert_eq!(1, db.hrandfield("user", 1).unwrap().len());
+    assert!(db.hrandfield("user", 0).unwrap().is_empty());
+
+    // Отрицательное количество: ровно столько полей, с повторениями
+    let sample = db.hrandfield("user", -10).unwrap();
+    assert_eq!(10, sample.len());
+    assert!(sample.iter().all(|field| fields.contains(field)));
+
+    assert!(db.hrandfield("missing", -3).unwrap().is_empty());
+
+    db.set("string".to_string(), "value".into(), None);
+    assert!(db.hset_nx("string", "f".to_string(), "v".into()).is_err());
+    assert!(db.hrandfield("string", 1).is_err());
+
+    // Количество с повторениями ограничено
+    assert!(db.hrandfield("user", i64::MIN).is_err());
+    assert!(db.hrandfield("user", -(1 << 20) - 1).is_err());
+    assert_eq!(1 << 20, db.hrandfield("user", -(1 << 20)).unwrap().len());
+}
+
+/// Сортированное множество: `zadd`, `zscore`, `zrem` и `zrange`
+#[tokio::test]
+async fn sorted_set() {
+    let guard = DbDropGuard::new();
+    let db = guard.db();
+
+    let members = |pairs: &[(f64, &str)]| -> Vec<(f64, String)> {
+        pairs
+            .iter()
+            .map(|(score, member)| (*score, member.to_string()))
+            .collect()
+    };
+    let options = ZaddOptions::default();
+
+    assert_eq!(
+        3,
+        db.zadd(
+            "board",
+            members(&[(20.0, "bob"), (10.0, "alice"), (20.0, "amy")]),
+            options
+        )
+        .unwrap()
+    );
+    assert_eq!(Some(10.0), db.zscore("board", "alice").unwrap());
+    assert!(db.zscore("board", "missing").unwrap().is_none());
+
+    // Элементы с одинаковой оценкой упорядочены по имени
+    let range = db.zrange("board", 0, -1, false).unwrap();
+    assert_eq!(
+        vec![
+            ("alice".to_string(), 10.0),
+            ("amy".to_string(), 20.0),
+            ("bob".to_string(), 20.0)
+        ],
+        range
+    );
+    let rev = db.zrange("board", 0, 1, true).unwrap();
+    assert_eq!(vec!["bob", "amy"], names(rev));
+    assert_eq!(
+        vec!["amy"],
+        names(db.zrange("board", -2, -2, false).unwrap())
+    );
+    assert!(db.zrange("board", 2, 1, false).unwrap().is_empty());
+    assert!(db.zrange("board", 5, 10, false).unwrap().is_empty());
+
+    // Обновление оценки учитывается только с `changed`
+    assert_eq!(
+        0,
+        db.zadd("board", members(&[(5.0, "bob")]), options).unwrap()
+    );
+    let ch = ZaddOptions {
+        changed: true,
+        ..options
+    };
+    assert_eq!(
+        2,
+        db.zadd("board", members(&[(1.0, "bob"), (3.0, "carl")]), ch)
+            .unwrap()
+    );
+    assert_eq!(
+        vec!["bob", "carl", "alice", "amy"],
+        names(db.zrange("board", 0, -1, false).unwrap())
+    );
+
+    // `NX`, `XX`, `GT` и `LT`
+    let nx = ZaddOptions {
+        condition: Some(SetCondition::Nx),
+        ..ch
+    };
+    assert_eq!(
+        1,
+        db.zadd("board", members(&[(100.0, "bob"), (7.0, "dan")]), nx)
+            .unwrap()
+    );
+    assert_eq!(Some(1.0), db.zscore("board", "bob").unwrap());
+
+    let xx = ZaddOptions {
+        condition: Some(SetCondition::Xx),
+        ..ch
+    };
+    assert_eq!(
+        1,
+        db.zadd("board", members(&[(2.0, "bob"), (7.0, "eve")]), xx)
+            .unwrap()
+    );
+    assert!(db.zscore("board", "eve").unwrap().is_none());
+    assert_eq!(0, db.zadd("missing", members(&[(1.0, "a")]), xx).unwrap());
+    assert_eq!(0, db.exists(&["missing"]));
+
+    let gt = ZaddOptions {
+        comparison: Some(ZaddComparison::Gt),
+        ..ch
+    };
+    assert_eq!(
+        1,
+        db.zadd("board", members(&[(1.0, "bob"), (50.0, "amy")]), gt)
+            .unwrap()
+    );
+    assert_eq!(Some(2.0), db.zscore("board", "bob").unwrap());
+    assert_eq!(Some(50.0), db.zscore("board", "amy").unwrap());
+
+    let lt = ZaddOptions {
+        comparison: Some(ZaddComparison::Lt),
+        ..ch
+    };
+    assert_eq!(
+        1,
+        db.zadd("board", members(&[(f64::NEG_INFINITY, "amy")]), lt)
+            .unwrap()
+    );
+    assert_eq!(
+        Some("amy".to_string()),
+        names(db.zrange("board", 0, 0, false).unwrap()).pop()
+    );
+
+    // Сортированное множество не является строкой и переносится с помощью
+    // `dump` и `restore`
+    assert!(db.get("board").is_none());
+    assert!(db.incr_by("board", 1).is_err());
+    assert!(db.hget("board", "bob").is_err());
+
+    let payload = db.dump("board").unwrap();
+    db.restore("copy".to_string(), &payload, None, false)
+        .unwrap();
+    assert_eq!(
+        db.zrange("board", 0, -1, false).unwrap(),
+        db.zrange("copy", 0, -1, false).unwrap()
+    );
+
+    // Удаление последнего элемента удаляет множество
+    assert_eq!(2, db.zrem("copy", &["amy", "bob", "missing"]).unwrap());
+    assert_eq!(3, db.zrem("copy", &["alice", "carl", "dan"]).unwrap());
+    assert_eq!(0, db.exists(&["copy"]));
+
+    db.set("string".to_string(), "value".into(), None);
+    let err = db.zscore("string", "member").unwrap_err();
+    assert!(err.to_string().starts_with("WRONGTYPE"));
+    assert!(db.zadd("string", members(&[(1.0, "a")]), options).is_err());
+    assert!(db.zrange("string", 0, -1, false).is_err());
+}
+
+/// `zrange_by_score`, `zcount`, `zincr_by` и `zrank`
+#[tokio::test]
+async fn sorted_set_ranges() {
+    let guard = DbDropGuard::new();
+    let db = guard.db();
+
+    let members = (1..=5).map(|i| (i as f64, format!("m{}", i))).collect();
+    db.zadd("board", members, ZaddOptions::default()).unwrap();
+
+    let range = |min, max, offset, count| {
+        names(
+            db.zrange_by_score("board", min, max, offset, count)
+                .unwrap(),
+        )
+    };
+
+    assert_eq!(
+        vec!["m2", "m3", "m4"],
+        range(Bound::Included(2.0), Bound::Included(4.0), 0, None)
+    );
+    assert_eq!(
+        vec!["m3"],
+        range(Bound::Excluded(2.0), Bound::Excluded(4.0), 0, None)
+    );
+    assert_eq!(
+        vec!["m2", "m3"],
+        range(Bound::Unbounded, Bound::Unbounded, 1, Some(2))
+    );
+    assert!(range(Bound::Included(4.0), Bound::Included(2.0), 0, None).is_empty());
+    assert!(range(Bound::Included(1.0), Bound::Unbounded, 10, None).is_empty());
+
+    assert_eq!(
+        5,
+        db.zcount("board", Bound::Unbounded, Bound::Unbounded)
+            .unwrap()
+    );
+    assert_eq!(
+        2,
+        db.zcount("board", Bound::Excluded(3.0), Bound::Included(5.0))
+            .unwrap()
+    );
+    assert_eq!(
+        0,
+        db.zcount("missing", Bound::Unbounded, Bound::Unbounded)
+            .unwrap()
+    );
+
+    assert_eq!(Some(0), db.zrank("board", "m1").unwrap());
+    assert_eq!(Some(4), db.zrank("board", "m5").unwrap());
+    assert!(db.zrank("board", "missing").unwrap().is_none());
+
+    // Увеличение оценки перемещает элемент
+    assert_eq!(10.5, db.zincr_by("board", "m1", 9.5).unwrap());
+    assert_eq!(Some(4), db.zrank("board", "m1").unwrap());
+    assert_eq!(Some(0), db.zrank("board", "m2").unwrap());
+
+    // Отсутствующие множество и элемент создаются
+    assert_eq!(-2.0, db.zincr_by("new", "a", -2.0).unwrap());
+    assert_eq!(Some(-2.0), db.zscore("new", "a").unwrap());
+
+    db.zincr_by("new", "a", f64::INFINITY).unwrap();
+    let err = db.zincr_by("new", "a", f64::NEG_INFINITY).unwrap_err();
+    assert_eq!("ERR resulting score is not a number (NaN)", err.to_string());
+    assert_eq!(Some(f64::INFINITY), db.zscore("new", "a").unwrap());
+}
+
+/// `zrange_by_lex`: лексикографические диапазоны
+#[tokio::test]
+async fn sorted_set_lex_ranges() {
+    let guard = DbDropGuard::new();
+    let db = guard.db();
+
+    let members = ["a", "b", "ca", "cab", "cb", "d"]
+        .iter()
+        .map(|member| (0.0, member.to_string()))
+        .collect();
+    db.zadd("words", members, ZaddOptions::default()).unwrap();
+
+    let range = |min, max, offset, count| {
+        names(db.zrange_by_lex("words", min, max, offset, count).unwrap())
+    };
+
+    assert_eq!(
+        vec!["ca", "cab"],
+        range(Bound::Included("ca"), Bound::Excluded("cb"), 0, None)
+    );
+    assert_eq!(
+        vec!["cab", "cb"],
+        range(Bound::Excluded("ca"), Bound::Included("cb"), 0, None)
+    );
+    assert_eq!(
+        vec!["b", "ca"],
+        range(Bound::Unbounded, Bound::Unbounded, 1, Some(2))
+    );
+    assert!(range(Bound::Included("x"), Bound::Unbounded, 0, None).is_empty());
+    assert!(db
+        .zrange_by_lex("missing", Bound::Unbounded, Bound::Unbounded, 0, None)
+        .unwrap()
+        .is_empty());
+}
+
+/// Возвращает имена элементов сортированного множества
+fn names(members: Vec<(String, f64)>) -> Vec<String> {
+    members.into_iter().map(|(member, _)| member).collect()
+}
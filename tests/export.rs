@@ -0,0 +1,183 @@
+use mini_redis::cmd::ZaddOptions;
+use mini_redis::export;
+use mini_redis::snapshot::{SnapshotEntry, SnapshotValue};
+use mini_redis::test_util::TestServer;
+use mini_redis::DbDropGuard;
+
+use bytes::Bytes;
+use std::time::Duration;
+
+/// Экспорт встроенной БД и импорт в другую БД сохраняют значения и время
+/// жизни
+#[tokio::test]
+async fn db_round_trip() {
+    let source = DbDropGuard::new();
+    let db = source.db();
+
+    db.set("string".to_string(), "value".into(), None);
+    db.set("number".to_string(), "42".into(), None);
+    db.set(
+        "binary".to_string(),
+        Bytes::from_static(&[0, 159, 146, 150]),
+        None,
+    );
+    db.set(
+        "session".to_string(),
+        "data".into(),
+        Some(Duration::from_secs(60)),
+    );
+    db.hset(
+        "user",
+        vec![
+            ("name".to_string(), "Alice".into()),
+            ("age".to_string(), "30".into()),
+        ],
+    )
+    .unwrap();
+    db.zadd(
+        "board",
+        vec![
+            (1.5, "alice".to_string()),
+            (f64::NEG_INFINITY, "bob".to_string()),
+        ],
+        ZaddOptions::default(),
+    )
+    .unwrap();
+
+    let mut file = vec![];
+    assert_eq!(6, export::export_db(&db, &mut file).unwrap());
+    assert_eq!(6, file.iter().filter(|&&b| b == b'\n').count());
+
+    let target = DbDropGuard::new();
+    let copy = target.db();
+    assert_eq!(6, export::import_db(&copy, &file[..]).unwrap());
+
+    // Хеши и сортированные множества не пропускаются
+    assert_eq!(db.dump("user"), copy.dump("user"));
+    assert_eq!(db.dump("board"), copy.dump("board"));
+
+    assert_eq!(Some("value".into()), copy.get("string"));
+    assert_eq!(Some("42".into()), copy.get("number"));
+    assert_eq!(
+        Some(Bytes::from_static(&[0, 159, 146, 150])),
+        copy.get("binary")
+    );
+    assert_eq!(Some(None), copy.ttl("string"));
+
+    let ttl = copy.ttl("session").unwrap().unwrap();
+    assert!(ttl > Duration::from_secs(50) && ttl <= Duration::from_secs(60));
+}
+
+/// Формат записи и ошибки разбора
+#[tokio::test]
+async fn entry_format() {
+    let entry = SnapshotEntry::new(
+        "key".to_string(),
+        SnapshotValue::String("value".into()),
+        Some(Duration::from_millis(1500)),
+    );
+
+    let mut line = vec![];
+    export::write_entry(&mut line, &entry).unwrap();
+    assert_eq!(
+        r#"{"key":"key","ttl_ms":1500,"value":"value"}"#.to_string() + "\n",
+        String::from_utf8(line).unwrap()
+    );
+
+    assert_eq!(
+        entry,
+        export::read_entry(r#"{"key":"key","value":"value","ttl_ms":1500}"#).unwrap()
+    );
+
+    assert!(export::read_entry("not json").is_err());
+    assert!(export::read_entry(r#"{"key":"key"}"#).is_err());
+    assert!(export::read_entry(r#"{"key":"key","value":[256]}"#).is_err());
+    assert!(export::read_entry(r#"{"key":"key","type":"list","value":[]}"#).is_err());
+    assert!(export::read_entry(r#"{"key":"key","type":"hash","value":{}}"#).is_err());
+    assert!(export::read_entry(r#"{"key":"key","type":"zset","value":{"a":"x"}}"#).is_err());
+
+    // Хеш и сортированное множество
+    let entry = SnapshotEntry::new(
+        "board".to_string(),
+        SnapshotValue::SortedSet(vec![
+            ("alice".to_string(), 1.5),
+            ("bob".to_string(), f64::INFINITY),
+        ]),
+        None,
+    );
+    let mut line = vec![];
+    export::write_entry(&mut line, &entry).unwrap();
+    let line = String::from_utf8(line).unwrap();
+    assert_eq!(
+        r#"{"key":"board","type":"zset","value":{"alice":1.5,"bob":"inf"}}"#.to_string() + "\n",
+        line
+    );
+    assert_eq!(entry, export::read_entry(&line).unwrap());
+
+    let entry = SnapshotEntry::new(
+        "user".to_string(),
+        SnapshotValue::Hash(vec![("name".to_string(), "Alice".into())]),
+        None,
+    );
+    let mut line = vec![];
+    export::write_entry(&mut line, &entry).unwrap();
+    let line = String::from_utf8(line).unwrap();
+    assert_eq!(
+        r#"{"key":"user","type":"hash","value":{"name":"Alice"}}"#.to_string() + "\n",
+        line
+    );
+    assert_eq!(entry, export::read_entry(&line).unwrap());
+
+    // Ошибка импорта содержит номер строки
+    let guard = DbDropGuard::new();
+    let input = "{\"key\":\"a\",\"value\":\"1\"}\n\n{\"key\":1}\n";
+    let err = export::import_db(&guard.db(), input.as_bytes()).unwrap_err();
+    assert!(err.to_string().starts_with("Строка 3"), "{}", err);
+}
+
+/// Экспорт с сервера и импорт на другой сервер через клиентов
+#[tokio::test]
+async fn client_round_trip() {
+    let source = TestServer::start().await;
+    let mut client = source.client().await;
+
+    for i in 0..30 {
+        client
+            .set(&format!("key:{}", i), i.to_string().into())
+            .await
+            .unwrap();
+    }
+    client
+        .set_expires("session", "data".into(), Duration::from_secs(60))
+        .await
+        .unwrap();
+    client.hset("user", "name", "Alice".into()).await.unwrap();
+    client.zadd("board", &[(1.5, "alice")]).await.unwrap();
+
+    let mut file = vec![];
+    assert_eq!(
+        33,
+        export::export_client(&mut client, &mut file).await.unwrap()
+    );
+
+    let target = TestServer::start().await;
+    let mut client = target.client().await;
+    assert_eq!(
+        33,
+        export::import_client(&mut client, &file[..]).await.unwrap()
+    );
+
+    // Время жизни сохраняется
+    assert_eq!(Some(None), target.db().ttl("key:0"));
+    let ttl = target.db().ttl("session").unwrap().unwrap();
+    assert!(ttl > Duration::from_secs(50) && ttl <= Duration::from_secs(60));
+
+    for i in 0..30 {
+        assert_eq!(
+            Some(Bytes::from(i.to_string())),
+            target.db().get(&format!("key:{}", i))
+        );
+    }
+    assert_eq!(source.db().dump("user"), target.db().dump("user"));
+    assert_eq!(source.db().dump("board"), target.db().dump("board"));
+}
@@ -0,0 +1,137 @@
+use bytes::BytesMut;
+use mini_redis::frame::{Error, Frame};
+use std::io::Cursor;
+
+fn check(src: &[u8]) -> Result<(), Error> {
+    Frame::check(&mut Cursor::new(src))
+}
+
+fn parse(src: &[u8]) -> Result<Frame, Error> {
+    Frame::parse(&mut Cursor::new(src))
+}
+
+/// Проверяет и разбирает `src` в строгом режиме. Результаты `check_strict`
+/// и `parse_strict` должны совпадать
+fn parse_strict(src: &[u8]) -> Result<Frame, Error> {
+    let checked = Frame::check_strict(&mut Cursor::new(src));
+    let parsed = Frame::parse_strict(&mut Cursor::new(src));
+    assert_eq!(checked.is_ok(), parsed.is_ok());
+    parsed
+}
+
+/// Возвращает сообщение ошибки строгого разбора `src`
+fn strict_error(src: &[u8]) -> String {
+    match parse_strict(src) {
+        Err(Error::Other(err)) => err.to_string(),
+        res => panic!("Ожидалась ошибка, получено {:?}", res),
+    }
+}
+
+/// Закодированный кадр проверяется и разбирается в тот же кадр
+#[test]
+fn encode_round_trip() {
+    let frame = Frame::Array(vec![
+        Frame::Simple("OK".into()),
+        Frame::Error("ERR".into()),
+        Frame::Integer(42),
+        Frame::Integer(-2),
+        Frame::Bulk("hello".into()),
+        Frame::Null,
+        Frame::Array(vec![Frame::Bulk("".into())]),
+    ]);
+
+    let mut buf = BytesMut::new();
+    frame.encode(&mut buf);
+
+    assert_eq!(
+        &buf[..],
+        b"*7\r\n+OK\r\n-ERR\r\n:42\r\n:-2\r\n$5\r\nhello\r\n$-1\r\n*1\r\n$0\r\n\r\n"
+    );
+
+    let mut src = Cursor::new(&buf[..]);
+    Frame::check(&mut src).unwrap();
+    assert_eq!(src.position() as usize, buf.len());
+
+    let mut reencoded = BytesMut::new();
+    parse(&buf).unwrap().encode(&mut reencoded);
+    assert_eq!(buf, reencoded);
+}
+
+/// Любой обрезанный префикс кадра считается неполным
+#[test]
+fn truncated_input_is_incomplete() {
+    let src = b"*2\r\n$3\r\nget\r\n:1\r\n";
+
+    for len in 0..src.len() {
+        assert!(matches!(check(&src[..len]), Err(Error::Incomplete)));
+        assert!(matches!(parse(&src[..len]), Err(Error::Incomplete)));
+    }
+}
+
+/// Невалидные данные приводят к ошибке, а не к панике
+#[test]
+fn malformed_input_is_error() {
+    // Неизвестный тип кадра
+    assert!(matches!(parse(b"!foo\r\n"), Err(Error::Other(_))));
+    assert!(matches!(check(b"!foo\r\n"), Err(Error::Other(_))));
+
+    // Длина объемной строки на границе `usize`
+    let src = format!("${}\r\n", usize::MAX);
+    assert!(check(src.as_bytes()).is_err());
+    assert!(parse(src.as_bytes()).is_err());
+
+    // Огромная длина массива не приводит к выделению памяти под нее
+    let src = format!("*{}\r\n:1\r\n", u64::MAX);
+    assert!(matches!(parse(src.as_bytes()), Err(Error::Incomplete)));
+
+    // Невалидное целое число
+    assert!(matches!(parse(b":abc\r\n"), Err(Error::Other(_))));
+}
+
+/// Глубоко вложенные массивы отклоняются
+#[test]
+fn deep_nesting_is_error() {
+    let src = b"*1\r\n".repeat(100_000);
+
+    assert!(matches!(check(&src), Err(Error::Other(_))));
+    assert!(matches!(parse(&src), Err(Error::Other(_))));
+}
+
+/// Строгий режим отклоняет данные, которые принимаются обычным разбором
+#[test]
+fn strict_mode_rejects_lenient_input() {
+    // `\n` без `\r`
+    assert_eq!(
+        Frame::Simple("OK\nfoo".into()),
+        parse(b"+OK\nfoo\r\n").unwrap()
+    );
+    assert!(strict_error(b"+OK\nfoo\r\n").contains("`\\n` без `\\r` (позиция 3)"));
+    assert!(strict_error(b"+OK\n").contains("`\\n` без `\\r`"));
+
+    // `\r` без `\n`
+    assert!(strict_error(b"+OK\rfoo\r\n").contains("`\\r` без `\\n` (позиция 3)"));
+
+    // Лишние символы в заголовке длины
+    assert_eq!(
+        Frame::Bulk("foo".into()),
+        parse(b"$3abc\r\nfoo\r\n").unwrap()
+    );
+    assert!(strict_error(b"$3abc\r\nfoo\r\n").contains("невалидное число `3abc`"));
+    assert!(strict_error(b"*\r\n").contains("невалидное число ``"));
+
+    // Объемная строка без завершающих `\r\n`
+    assert_eq!(Frame::Bulk("foo".into()), parse(b"$3\r\nfooXY").unwrap());
+    assert!(strict_error(b"$3\r\nfooXY").contains("не завершается `\\r\\n` (позиция 7)"));
+    assert!(strict_error(b"$-1XY\r\n").contains("невалидный формат"));
+}
+
+/// Валидные кадры одинаково разбираются в обоих режимах
+#[test]
+fn strict_mode_accepts_valid_input() {
+    let src = b"*6\r\n+OK\r\n-ERR\r\n:42\r\n$6\r\nhel\rlo\r\n$-1\r\n*1\r\n$0\r\n\r\n";
+    assert_eq!(parse(src).unwrap(), parse_strict(src).unwrap());
+
+    for len in 0..src.len() {
+        assert!(matches!(parse_strict(&src[..len]), Err(Error::Incomplete)));
+    }
+}
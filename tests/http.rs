@@ -0,0 +1,113 @@
+use mini_redis::test_util::TestServer;
+
+use std::net::SocketAddr;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+
+/// Запускает HTTP-шлюз к БД сервера и возвращает его адрес
+async fn start_gateway(server: &TestServer) -> (SocketAddr, oneshot::Sender<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+
+    tokio::spawn(mini_redis::http::run(
+        listener,
+        server.db().clone(),
+        shutdown_rx,
+    ));
+
+    (addr, shutdown_tx)
+}
+
+/// Отправляет запрос и возвращает код ответа и тело
+async fn request(addr: SocketAddr, method: &str, path: &str, body: &str) -> (u16, String) {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let request = format!(
+        "{} {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{}",
+        method,
+        path,
+        body.len(),
+        body
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+
+    let status = response[9..12].parse().unwrap();
+    let body = response.split_once("\r\n\r\n").unwrap().1.to_string();
+
+    (status, body)
+}
+
+/// Значения, записанные через HTTP, доступны клиентам `Redis`, и наоборот
+#[tokio::test]
+async fn keys_shared_with_server() {
+    let server = TestServer::start().await;
+    let (addr, _shutdown) = start_gateway(&server).await;
+
+    assert_eq!(
+        (404, String::new()),
+        request(addr, "GET", "/keys/hello", "").await
+    );
+
+    assert_eq!(204, request(addr, "PUT", "/keys/hello", "world").await.0);
+    let mut client = server.client().await;
+    assert_eq!(client.get("hello").await.unwrap().unwrap(), "world");
+
+    client.set("a b", "value".into()).await.unwrap();
+    assert_eq!(
+        (200, "value".to_string()),
+        request(addr, "GET", "/keys/a%20b", "").await
+    );
+
+    assert_eq!(204, request(addr, "DELETE", "/keys/hello", "").await.0);
+    assert_eq!(404, request(addr, "DELETE", "/keys/hello", "").await.0);
+    assert!(client.get("hello").await.unwrap().is_none());
+}
+
+/// Время жизни задается параметром `ttl`
+#[tokio::test]
+async fn put_with_ttl() {
+    let server = TestServer::start().await;
+    let (addr, _shutdown) = start_gateway(&server).await;
+
+    assert_eq!(
+        204,
+        request(addr, "PUT", "/keys/hello?ttl=60000", "world")
+            .await
+            .0
+    );
+    assert!(server.db().ttl("hello").unwrap().is_some());
+
+    assert_eq!(
+        400,
+        request(addr, "PUT", "/keys/hello?ttl=abc", "world").await.0
+    );
+}
+
+/// Сообщения публикуются в канал, ответ содержит количество подписчиков
+#[tokio::test]
+async fn publish_to_channel() {
+    let server = TestServer::start().await;
+    let (addr, _shutdown) = start_gateway(&server).await;
+
+    let mut subscriber = server
+        .client()
+        .await
+        .subscribe(vec!["news".into()])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        (200, "1".to_string()),
+        request(addr, "POST", "/publish/news", "hello").await
+    );
+
+    let message = subscriber.next_message().await.unwrap().unwrap();
+    assert_eq!(&message.content[..], b"hello");
+
+    assert_eq!(405, request(addr, "GET", "/publish/news", "").await.0);
+    assert_eq!(404, request(addr, "GET", "/unknown", "").await.0);
+}
@@ -0,0 +1,62 @@
+use mini_redis::{server, DbDropGuard};
+
+/// Клиент, подключенный к серверу в текущем процессе, выполняет команды
+/// без TCP
+#[tokio::test]
+async fn key_value_get_set() {
+    let db = DbDropGuard::new();
+    let mut client = server::connect_in_memory(db.db());
+
+    client.set("hello", "world".into()).await.unwrap();
+
+    let value = client.get("hello").await.unwrap().unwrap();
+    assert_eq!(b"world", &value[..]);
+}
+
+/// Клиенты, подключенные к одной БД, работают с общим состоянием
+#[tokio::test]
+async fn clients_share_db() {
+    let db = DbDropGuard::new();
+    let mut first = server::connect_in_memory(db.db());
+    let mut second = server::connect_in_memory(db.db());
+
+    first.set("hello", "world".into()).await.unwrap();
+
+    let value = second.get("hello").await.unwrap().unwrap();
+    assert_eq!(b"world", &value[..]);
+
+    // Другая БД не видит этих значений
+    let other = DbDropGuard::new();
+    let mut third = server::connect_in_memory(other.db());
+    assert!(third.get("hello").await.unwrap().is_none());
+}
+
+/// Pub/sub поверх `tokio::io::duplex`
+#[tokio::test]
+async fn pub_sub() {
+    let db = DbDropGuard::new();
+    let subscriber = server::connect_in_memory(db.db());
+    let mut subscriber = subscriber.subscribe(vec!["hello".into()]).await.unwrap();
+
+    let mut publisher = server::connect_in_memory(db.db());
+    assert_eq!(1, publisher.publish("hello", "world".into()).await.unwrap());
+
+    let message = subscriber.next_message().await.unwrap().unwrap();
+    assert_eq!("hello", &message.channel);
+    assert_eq!(b"world", &message.content[..]);
+}
+
+/// `serve_connection` завершается, когда клиент закрывает соединение
+#[tokio::test]
+async fn serve_connection_ends_on_client_drop() {
+    let db = DbDropGuard::new();
+    let (client_io, server_io) = tokio::io::duplex(1024);
+
+    let handle = tokio::spawn(server::serve_connection(server_io, db.db()));
+
+    let mut client = mini_redis::Client::new(client_io);
+    client.ping(None).await.unwrap();
+    drop(client);
+
+    handle.await.unwrap().unwrap();
+}
@@ -0,0 +1,99 @@
+use mini_redis::clients::{Client, ErrorKind, ServerError};
+use mini_redis::test_util::TestServer;
+use mini_redis::Frame;
+
+/// Отправляет команду `JSON.*` с аргументами `args`
+async fn json(client: &mut Client, cmd: &str, args: &[&str]) -> mini_redis::Result<Frame> {
+    let mut parts = vec![Frame::Bulk(format!("JSON.{}", cmd).into())];
+    parts.extend(args.iter().map(|arg| Frame::Bulk(arg.to_string().into())));
+    client.send_frame(Frame::Array(parts)).await
+}
+
+/// Значения документа читаются и изменяются по путям
+#[tokio::test]
+async fn set_get_del_paths() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    let doc = r#"{"name":"Alice","tags":["a","b"],"address":{"city":"Paris"}}"#;
+    let res = json(&mut client, "SET", &["user", "$", doc]).await.unwrap();
+    assert_eq!(Frame::Simple("OK".into()), res);
+
+    let res = json(&mut client, "GET", &["user", "/name"]).await.unwrap();
+    assert_eq!(Frame::Bulk(r#""Alice""#.into()), res);
+
+    // Изменение поля, добавление поля и элемента массива
+    json(&mut client, "SET", &["user", "/address/city", r#""Rome""#])
+        .await
+        .unwrap();
+    json(&mut client, "SET", &["user", "/age", "30"])
+        .await
+        .unwrap();
+    json(&mut client, "SET", &["user", "/tags/-", r#""c""#])
+        .await
+        .unwrap();
+
+    // Путь с отсутствующим родителем не существует
+    let res = json(&mut client, "SET", &["user", "/job/title", r#""dev""#])
+        .await
+        .unwrap();
+    assert_eq!(Frame::Null, res);
+
+    let res = json(&mut client, "DEL", &["user", "/tags/0"])
+        .await
+        .unwrap();
+    assert_eq!(Frame::Integer(1), res);
+    let res = json(&mut client, "DEL", &["user", "/missing"])
+        .await
+        .unwrap();
+    assert_eq!(Frame::Integer(0), res);
+
+    let res = json(&mut client, "GET", &["user"]).await.unwrap();
+    let expected = r#"{"address":{"city":"Rome"},"age":30,"name":"Alice","tags":["b","c"]}"#;
+    assert_eq!(Frame::Bulk(expected.into()), res);
+
+    let res = json(&mut client, "GET", &["user", "/missing"])
+        .await
+        .unwrap();
+    assert_eq!(Frame::Null, res);
+
+    // Удаление корня удаляет ключ
+    let res = json(&mut client, "DEL", &["user"]).await.unwrap();
+    assert_eq!(Frame::Integer(1), res);
+    assert!(server.db().get("user").is_none());
+    let res = json(&mut client, "GET", &["user"]).await.unwrap();
+    assert_eq!(Frame::Null, res);
+}
+
+/// Команды `JSON.*` возвращают ошибки для строк, невалидного JSON и
+/// создания документа не в корне
+#[tokio::test]
+async fn errors() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    client.set("string", "value".into()).await.unwrap();
+    let err = json(&mut client, "GET", &["string"]).await.unwrap_err();
+    let err = err.downcast::<ServerError>().unwrap();
+    assert_eq!(&ErrorKind::WrongType, err.kind());
+
+    let err = json(&mut client, "SET", &["doc", "$", "{"])
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("invalid JSON"), "{}", err);
+
+    let err = json(&mut client, "SET", &["doc", "/a", "1"])
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("root"), "{}", err);
+
+    // Ошибки не закрывают соединение
+    json(&mut client, "SET", &["doc", "", "[1,2]"])
+        .await
+        .unwrap();
+    assert_eq!(Some("[1,2]".into()), client.get("doc").await.unwrap());
+    assert_eq!(
+        Some("json".to_string()),
+        client.object_encoding("doc").await.unwrap()
+    );
+}
@@ -0,0 +1,83 @@
+use mini_redis::test_util::TestServer;
+
+use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+use metrics_util::{CompositeKey, MetricKind};
+
+/// Метрики, записанные к моменту снимка
+type Snapshot = Vec<(
+    CompositeKey,
+    Option<metrics::Unit>,
+    Option<metrics::SharedString>,
+    DebugValue,
+)>;
+
+/// Возвращает значение метрики `name` с атрибутами `labels`
+fn value<'a>(
+    snapshot: &'a Snapshot,
+    kind: MetricKind,
+    name: &str,
+    labels: &[(&str, &str)],
+) -> Option<&'a DebugValue> {
+    snapshot
+        .iter()
+        .find(|(key, _, _, _)| {
+            let (key_kind, key) = (key.kind(), key.key());
+            key_kind == kind
+                && key.name() == name
+                && labels.iter().all(|(label, value)| {
+                    key.labels()
+                        .any(|l| l.key() == *label && l.value() == *value)
+                })
+        })
+        .map(|(_, _, _, value)| value)
+}
+
+/// Выполнение команд и соединения записываются через фасад `metrics`
+#[tokio::test]
+async fn commands_recorded_by_facade() {
+    // Экспортер устанавливается глобально, поэтому в этом файле один тест
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    recorder.install().unwrap();
+
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    client.set("hello", "world".into()).await.unwrap();
+    client.set("hello", "again".into()).await.unwrap();
+    client.get("hello").await.unwrap();
+
+    // Снимок очищает записанные значения гистограмм, поэтому он делается
+    // один раз
+    let snapshot = snapshotter.snapshot().into_vec();
+
+    assert_eq!(
+        Some(&DebugValue::Counter(2)),
+        value(
+            &snapshot,
+            MetricKind::Counter,
+            "mini_redis.commands",
+            &[("command", "set"), ("status", "ok")]
+        )
+    );
+
+    match value(
+        &snapshot,
+        MetricKind::Histogram,
+        "mini_redis.command.duration",
+        &[("command", "get")],
+    ) {
+        Some(DebugValue::Histogram(samples)) => assert_eq!(1, samples.len()),
+        value => panic!("неожиданное значение {:?}", value),
+    }
+
+    assert_eq!(
+        Some(&DebugValue::Counter(1)),
+        value(
+            &snapshot,
+            MetricKind::Counter,
+            "mini_redis.connections.accepted",
+            &[]
+        )
+    );
+}
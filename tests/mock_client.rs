@@ -0,0 +1,177 @@
+use mini_redis::clients::{
+    BlockingClient, BlockingRedisClient, BufferedClient, Client, ErrorKind, RedisClient,
+    ServerError,
+};
+use mini_redis::test_util::{MockClient, TestServer};
+use mini_redis::Frame;
+
+use std::time::Duration;
+
+/// Код приложения, зависящий только от трейта
+async fn incr_visits<C: RedisClient>(client: &mut C, page: &str) -> mini_redis::Result<u64> {
+    let visits = match client.get(page).await? {
+        Some(value) => std::str::from_utf8(&value)?.parse::<u64>()? + 1,
+        None => 1,
+    };
+
+    client.set(page, visits.to_string().into()).await?;
+    client.publish("visits", page.to_string().into()).await?;
+
+    Ok(visits)
+}
+
+fn blocking_incr_visits<C: BlockingRedisClient>(
+    client: &mut C,
+    page: &str,
+) -> mini_redis::Result<u64> {
+    let visits = match client.get(page)? {
+        Some(value) => std::str::from_utf8(&value)?.parse::<u64>()? + 1,
+        None => 1,
+    };
+
+    client.set(page, visits.to_string().into())?;
+    client.publish("visits", page.to_string().into())?;
+
+    Ok(visits)
+}
+
+/// `MockClient` проверяет запросы и возвращает заданные ответы
+#[tokio::test]
+async fn mock_scripted_responses() {
+    let mut client = MockClient::new();
+    client
+        .expect(["get", "/"], Frame::Bulk("41".into()))
+        .expect(["set", "/", "42"], Frame::Simple("OK".into()))
+        .expect(["publish", "visits", "/"], Frame::Integer(0));
+
+    assert_eq!(42, incr_visits(&mut client, "/").await.unwrap());
+    assert_eq!(0, client.remaining());
+}
+
+/// Блокирующий интерфейс `MockClient`
+#[test]
+fn mock_blocking_scripted_responses() {
+    let mut client = MockClient::new();
+    client
+        .expect(["get", "/"], Frame::Null)
+        .expect(["set", "/", "1"], Frame::Simple("OK".into()))
+        .expect(["publish", "visits", "/"], Frame::Integer(3))
+        .expect(["ping"], Frame::Simple("PONG".into()))
+        .expect(["set", "k", "v", "px", "1500"], Frame::Simple("OK".into()));
+
+    assert_eq!(1, blocking_incr_visits(&mut client, "/").unwrap());
+    assert_eq!(
+        &b"PONG"[..],
+        &BlockingRedisClient::ping(&mut client, None).unwrap()
+    );
+    BlockingRedisClient::set_expires(&mut client, "k", "v".into(), Duration::from_millis(1500))
+        .unwrap();
+}
+
+/// Ошибки сервера, неожиданные и лишние команды возвращают ошибку
+#[tokio::test]
+async fn mock_errors() {
+    let mut client = MockClient::new();
+    client
+        .expect(["get", "foo"], Frame::Error("ERR boom".into()))
+        .expect(["get", "foo"], Frame::Null);
+
+    let err = RedisClient::get(&mut client, "foo").await.unwrap_err();
+    assert_eq!("ERR boom", err.to_string());
+
+    // Запрос не совпадает с ожидаемым
+    assert!(RedisClient::get(&mut client, "bar").await.is_err());
+
+    // Ожидаемые команды закончились
+    assert!(RedisClient::ping(&mut client, None).await.is_err());
+}
+
+/// Невыполненные ожидаемые команды приводят к панике при уничтожении
+#[test]
+#[should_panic(expected = "Не выполнены ожидаемые команды")]
+fn mock_unmet_expectations() {
+    let mut client = MockClient::new();
+    client.expect(["get", "foo"], Frame::Null);
+}
+
+/// Трейты реализуются всеми клиентами
+#[tokio::test]
+async fn real_clients_implement_traits() {
+    let server = TestServer::start().await;
+
+    let mut client = server.client().await;
+    assert_eq!(1, incr_visits(&mut client, "/").await.unwrap());
+
+    let mut buffered = BufferedClient::buffer(Client::connect(server.addr()).await.unwrap());
+    assert_eq!(2, incr_visits(&mut buffered, "/").await.unwrap());
+    RedisClient::set_expires(&mut buffered, "tmp", "1".into(), Duration::from_secs(60))
+        .await
+        .unwrap();
+    assert_eq!(
+        &b"PONG"[..],
+        &RedisClient::ping(&mut buffered, None).await.unwrap()
+    );
+
+    let addr = server.addr();
+    let visits = tokio::task::spawn_blocking(move || {
+        let mut client = BlockingClient::connect(addr).unwrap();
+        blocking_incr_visits(&mut client, "/").unwrap()
+    })
+    .await
+    .unwrap();
+    assert_eq!(3, visits);
+
+    assert_eq!(server.db().get("/").unwrap(), "3");
+    assert!(server.db().ttl("tmp").is_some());
+}
+
+/// Ошибки сервера классифицируются по префиксу
+#[tokio::test]
+async fn mock_error_kinds() {
+    let mut client = MockClient::new();
+    client
+        .expect(
+            ["get", "list"],
+            Frame::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".into(),
+            ),
+        )
+        .expect(
+            ["get", "foo"],
+            Frame::Error("MOVED 3999 127.0.0.1:6381".into()),
+        )
+        .expect(
+            ["get", "bar"],
+            Frame::Error("NOAUTH Authentication required.".into()),
+        )
+        .expect(
+            ["get", "baz"],
+            Frame::Error("ERR unknown command 'get'".into()),
+        );
+
+    let kind = |res: mini_redis::Result<Option<bytes::Bytes>>| {
+        let err = res.unwrap_err();
+        err.downcast_ref::<ServerError>().unwrap().clone()
+    };
+
+    assert_eq!(
+        &ErrorKind::WrongType,
+        kind(RedisClient::get(&mut client, "list").await).kind()
+    );
+    assert_eq!(
+        &ErrorKind::Moved {
+            slot: 3999,
+            addr: "127.0.0.1:6381".into()
+        },
+        kind(RedisClient::get(&mut client, "foo").await).kind()
+    );
+    assert_eq!(
+        &ErrorKind::NoAuth,
+        kind(RedisClient::get(&mut client, "bar").await).kind()
+    );
+
+    let err = kind(RedisClient::get(&mut client, "baz").await);
+    assert_eq!(&ErrorKind::Unknown, err.kind());
+    assert_eq!("ERR", err.prefix());
+    assert_eq!("ERR unknown command 'get'", err.to_string());
+}
@@ -0,0 +1,239 @@
+use mini_redis::proxy::{self, Direction, Record};
+use mini_redis::test_util::TestServer;
+use mini_redis::{Client, Frame};
+
+use bytes::Bytes;
+use std::path::PathBuf;
+use tokio::fs::File;
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+
+/// Записывает сессию, выполняемую функцией `session`, в файл и возвращает
+/// записанные кадры.
+async fn record_session<F, Fut>(server: &TestServer, name: &str, session: F) -> Vec<Record>
+where
+    F: FnOnce(Client) -> Fut,
+    Fut: std::future::Future<Output = ()>,
+{
+    let path: PathBuf =
+        std::env::temp_dir().join(format!("mini-redis-{}-{}.resp", name, std::process::id()));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let out = File::create(&path).await.unwrap();
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+
+    let recorder = tokio::spawn(proxy::record(listener, server.addr(), out, shutdown_rx));
+
+    session(Client::connect(addr).await.unwrap()).await;
+
+    shutdown_tx.send(()).unwrap();
+    recorder.await.unwrap().unwrap();
+
+    let records = proxy::read_records(File::open(&path).await.unwrap())
+        .await
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    records
+}
+
+/// Прокси передает команды серверу и записывает запросы и ответы
+#[tokio::test]
+async fn record_session_frames() {
+    let server = TestServer::start().await;
+
+    let records = record_session(&server, "record", |mut client| async move {
+        client.set("hello", "world".into()).await.unwrap();
+        let value = client.get("hello").await.unwrap().unwrap();
+        assert_eq!(b"world", &value[..]);
+    })
+    .await;
+
+    assert_eq!(server.db().get("hello").unwrap(), "world");
+
+    let frames: Vec<_> = records
+        .iter()
+        .map(|record| {
+            assert_eq!(0, record.connection);
+            (record.direction, record.frame.clone())
+        })
+        .collect();
+
+    assert_eq!(
+        vec![
+            (
+                Direction::ToServer,
+                Frame::Array(vec![
+                    Frame::Bulk("set".into()),
+                    Frame::Bulk("hello".into()),
+                    Frame::Bulk("world".into()),
+                ])
+            ),
+            (Direction::ToClient, Frame::Simple("OK".into())),
+            (
+                Direction::ToServer,
+                Frame::Array(vec![Frame::Bulk("get".into()), Frame::Bulk("hello".into())])
+            ),
+            (Direction::ToClient, Frame::Bulk("world".into())),
+        ],
+        frames
+    );
+
+    assert!(records
+        .windows(2)
+        .all(|pair| pair[0].elapsed <= pair[1].elapsed));
+}
+
+/// Воспроизведение записанной сессии на другом сервере сравнивает ответы
+#[tokio::test]
+async fn replay_session() {
+    let server = TestServer::start().await;
+    server.db().set("existing".into(), "value".into(), None);
+
+    let records = record_session(&server, "replay", |mut client| async move {
+        client.set("hello", "world".into()).await.unwrap();
+        client.get("hello").await.unwrap();
+        client.get("existing").await.unwrap();
+    })
+    .await;
+
+    // На пустом сервере значение `existing` отсутствует
+    let target = TestServer::start().await;
+    let report = proxy::replay(target.addr(), records.clone(), false)
+        .await
+        .unwrap();
+
+    assert_eq!(1, report.connections);
+    assert_eq!(3, report.requests);
+    assert_eq!(2, report.matched);
+    assert_eq!(1, report.mismatched);
+    assert_eq!(target.db().get("hello").unwrap(), "world");
+
+    // После восстановления состояния сессия воспроизводится без отличий
+    let target = TestServer::start().await;
+    target.db().set("existing".into(), "value".into(), None);
+
+    let report = proxy::replay(target.addr(), records, false).await.unwrap();
+    assert_eq!(3, report.matched);
+    assert_eq!(0, report.mismatched);
+}
+
+/// Запускает прокси, распределяющий ключи между `servers`, и возвращает
+/// клиента, подключенного к прокси.
+async fn shard_client(servers: &[&TestServer]) -> (Client, oneshot::Sender<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let backends = servers.iter().map(|server| server.addr()).collect();
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+
+    tokio::spawn(proxy::shard(listener, backends, shutdown_rx));
+
+    (Client::connect(addr).await.unwrap(), shutdown_tx)
+}
+
+/// Ключи распределяются между серверами, каждый ключ хранится на одном
+/// сервере и читается через прокси
+#[tokio::test]
+async fn shard_distributes_keys() {
+    let first = TestServer::start().await;
+    let second = TestServer::start().await;
+    let (mut client, _shutdown) = shard_client(&[&first, &second]).await;
+
+    for i in 0..100 {
+        let key = format!("key:{}", i);
+        client.set(&key, i.to_string().into()).await.unwrap();
+    }
+
+    let mut on_first = 0;
+    for i in 0..100 {
+        let key = format!("key:{}", i);
+        let value = client.get(&key).await.unwrap().unwrap();
+        assert_eq!(i.to_string().as_bytes(), &value[..]);
+
+        match (first.db().get(&key), second.db().get(&key)) {
+            (Some(_), None) => on_first += 1,
+            (None, Some(_)) => {}
+            res => panic!("ключ `{}` хранится неверно: {:?}", key, res),
+        }
+    }
+
+    assert!(
+        on_first > 0 && on_first < 100,
+        "на первом сервере {}",
+        on_first
+    );
+}
+
+/// Команды без ключа обрабатываются прокси или отклоняются
+#[tokio::test]
+async fn shard_unsharded_commands() {
+    let server = TestServer::start().await;
+    let (mut client, _shutdown) = shard_client(&[&server]).await;
+
+    assert_eq!(b"PONG", &client.ping(None).await.unwrap()[..]);
+
+    let err = client.keys("*").await.unwrap_err();
+    assert!(err.to_string().contains("not supported"), "{}", err);
+
+    // Соединение остается открытым после ошибки
+    client.set("hello", "world".into()).await.unwrap();
+    assert_eq!(server.db().get("hello").unwrap(), "world");
+}
+
+/// Команды с несколькими ключами выполняются, только если все ключи
+/// находятся на одном сервере
+#[tokio::test]
+async fn shard_multi_key_commands() {
+    let first = TestServer::start().await;
+    let second = TestServer::start().await;
+    let (mut client, _shutdown) = shard_client(&[&first, &second]).await;
+
+    for i in 0..20 {
+        client
+            .set(&format!("key:{}", i), "value".into())
+            .await
+            .unwrap();
+    }
+
+    // Ключи первого сервера и ключ второго сервера
+    let keys: Vec<String> = (0..20).map(|i| format!("key:{}", i)).collect();
+    let local: Vec<&str> = keys
+        .iter()
+        .filter(|key| first.db().get(key).is_some())
+        .map(String::as_str)
+        .collect();
+    let remote = keys
+        .iter()
+        .find(|key| second.db().get(key).is_some())
+        .unwrap();
+
+    assert_eq!(local.len() as u64, client.exists(&local).await.unwrap());
+
+    let err = client.exists(&[local[0], remote]).await.unwrap_err();
+    assert!(err.to_string().contains("CROSSSLOT"), "{}", err);
+
+    let values = client.mget(&local).await.unwrap();
+    assert!(values
+        .iter()
+        .all(|value| value.as_deref() == Some(&b"value"[..])));
+
+    let err = client.mget(&[local[0], remote]).await.unwrap_err();
+    assert!(err.to_string().contains("CROSSSLOT"), "{}", err);
+
+    // Значения `MSET` не являются ключами и не проверяются
+    client
+        .mset(&[
+            (local[0], Bytes::from(remote.clone())),
+            (local[1], "new".into()),
+        ])
+        .await
+        .unwrap();
+    assert_eq!(Some("new".into()), first.db().get(local[1]));
+
+    let err = client
+        .mset(&[(local[0], "new".into()), (remote, "new".into())])
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("CROSSSLOT"), "{}", err);
+}
@@ -1,16 +1,30 @@
-use mini_redis::server;
-
+use mini_redis::clients::{ErrorKind, ServerError};
+use mini_redis::log_filter::LogFilter;
+use mini_redis::server::{Accept, AcceptRetry, Builder, ConnectionService, Server};
+use mini_redis::test_util::TestServer;
+use mini_redis::{Client, Frame};
+
+use std::future::poll_fn;
+use std::io;
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::{TcpListener, TcpStream};
 use tokio::time::{self, Duration};
+use tower_service::Service;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{reload, EnvFilter};
 
 /// Базовый тест. Экземпляр сервера запускается в фоновой задаче.
 /// Затем устанавливается клиентское соединение TCP, и серверу отправляются
 /// сырые (raw) команды Redis. Ответ оценивается на уровне байтов
 #[tokio::test]
 async fn key_value_get_set() {
-    let addr = start_server().await;
+    let server = TestServer::start().await;
+    let addr = server.addr();
 
     // Устанавливаем соединение с сервером
     let mut stream = TcpStream::connect(addr).await.unwrap();
@@ -65,7 +79,8 @@ async fn key_value_get_set() {
 async fn key_value_timeout() {
     tokio::time::pause();
 
-    let addr = start_server().await;
+    let server = TestServer::start().await;
+    let addr = server.addr();
 
     // Устанавливаем соединение с сервером
     let mut stream = TcpStream::connect(addr).await.unwrap();
@@ -118,7 +133,8 @@ async fn key_value_timeout() {
 
 #[tokio::test]
 async fn pub_sub() {
-    let addr = start_server().await;
+    let server = TestServer::start().await;
+    let addr = server.addr();
 
     let mut publisher = TcpStream::connect(addr).await.unwrap();
 
@@ -240,7 +256,8 @@ async fn pub_sub() {
 
 #[tokio::test]
 async fn manage_subscription() {
-    let addr = start_server().await;
+    let server = TestServer::start().await;
+    let addr = server.addr();
 
     let mut publisher = TcpStream::connect(addr).await.unwrap();
 
@@ -330,7 +347,8 @@ async fn manage_subscription() {
 // при отправке клиентом неизвестной команды
 #[tokio::test]
 async fn send_error_unknown_command() {
-    let addr = start_server().await;
+    let server = TestServer::start().await;
+    let addr = server.addr();
 
     // Устанавливаем соединение с сервером
     let mut stream = TcpStream::connect(addr).await.unwrap();
@@ -352,7 +370,8 @@ async fn send_error_unknown_command() {
 // при отправке клиентом команды `GET` или `SET` после `SUBSCRIBE`
 #[tokio::test]
 async fn send_error_get_set_after_subscribe() {
-    let addr = start_server().await;
+    let server = TestServer::start().await;
+    let addr = server.addr();
 
     let mut stream = TcpStream::connect(addr).await.unwrap();
 
@@ -392,11 +411,1489 @@ async fn send_error_get_set_after_subscribe() {
     assert_eq!(b"-ERR unknown command \'get\'\r\n", &response);
 }
 
-async fn start_server() -> SocketAddr {
+// В данном случае мы тестируем, что после `QUIT` сервер отвечает `OK`
+// и закрывает соединение
+#[tokio::test]
+async fn quit_closes_connection() {
+    let server = TestServer::start().await;
+    let addr = server.addr();
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    stream.write_all(b"*1\r\n$4\r\nQUIT\r\n").await.unwrap();
+
+    // После ответа сервер закрывает соединение
+    let mut response = vec![];
+    stream.read_to_end(&mut response).await.unwrap();
+    assert_eq!(b"+OK\r\n", &response[..]);
+}
+
+// В данном случае мы тестируем, что `QUIT` закрывает соединение
+// в режиме подписки
+#[tokio::test]
+async fn quit_after_subscribe() {
+    let server = TestServer::start().await;
+    let addr = server.addr();
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    stream
+        .write_all(b"*2\r\n$9\r\nsubscribe\r\n$5\r\nhello\r\n")
+        .await
+        .unwrap();
+
+    let mut response = [0; 34];
+    stream.read_exact(&mut response).await.unwrap();
+
+    stream.write_all(b"*1\r\n$4\r\nQUIT\r\n").await.unwrap();
+
+    let mut response = vec![];
+    stream.read_to_end(&mut response).await.unwrap();
+    assert_eq!(b"+OK\r\n", &response[..]);
+}
+
+/// Обработчик сервера предоставляет адрес и позволяет закрыть сервер
+#[tokio::test]
+async fn server_handle_shutdown() {
+    let server = Server::builder().bind("127.0.0.1:0").await.unwrap();
+    let addr = server.local_addr();
+
+    let mut client = Client::connect(addr).await.unwrap();
+    client.ping(None).await.unwrap();
+
+    server.shutdown();
+    server.join().await.unwrap();
+
+    // Активное соединение закрыто сервером
+    assert!(client.ping(None).await.is_err());
+
+    // Новые соединения не принимаются
+    assert!(TcpStream::connect(addr).await.is_err());
+}
+
+/// Сервер не обрабатывает соединения сверх лимита, пока одно из активных
+/// соединений не будет закрыто
+#[tokio::test]
+async fn server_max_connections() {
+    let server = Server::builder()
+        .max_connections(1)
+        .bind("127.0.0.1:0")
+        .await
+        .unwrap();
+    let addr = server.local_addr();
+
+    let mut first = Client::connect(addr).await.unwrap();
+    first.ping(None).await.unwrap();
+
+    // Соединение TCP устанавливается операционной системой, но сервер
+    // не обрабатывает команды
+    let mut second = Client::connect(addr).await.unwrap();
+    let pong = time::timeout(Duration::from_millis(100), second.ping(None)).await;
+    assert!(pong.is_err());
+
+    // После закрытия первого соединения второе обслуживается
+    drop(first);
+    second.ping(None).await.unwrap();
+}
+
+/// Команда подписки, превышающая лимит подписок, отклоняется целиком.
+/// Существующие подписки продолжают работать
+#[tokio::test]
+async fn server_max_subscriptions() {
+    let server = Server::builder()
+        .max_subscriptions(2)
+        .bind("127.0.0.1:0")
+        .await
+        .unwrap();
+    let addr = server.local_addr();
+
+    // Начальная подписка сверх лимита
+    let client = Client::connect(addr).await.unwrap();
+    let channels = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+    assert!(client.subscribe(channels).await.is_err());
+
+    let client = Client::connect(addr).await.unwrap();
+    let mut subscriber = client
+        .subscribe(vec!["a".to_string(), "b".to_string()])
+        .await
+        .unwrap();
+
+    let err = subscriber.subscribe(&["c".to_string()]).await.unwrap_err();
+    let err = err.downcast_ref::<ServerError>().unwrap();
+    assert_eq!(&ErrorKind::Unknown, err.kind());
+    assert_eq!("ERR", err.prefix());
+    assert!(err.message().contains("max number of subscriptions"));
+    assert_eq!(&["a", "b"], subscriber.get_subscribed());
+
+    let mut publisher = Client::connect(addr).await.unwrap();
+    assert_eq!(0, publisher.publish("c", "ignored".into()).await.unwrap());
+    assert_eq!(1, publisher.publish("b", "hello".into()).await.unwrap());
+
+    let message = subscriber.next_message().await.unwrap().unwrap();
+    assert_eq!("b", message.channel);
+    assert_eq!(&b"hello"[..], &message.content[..]);
+}
+
+/// Команда, не завершившаяся за отведенное время, прерывается, а соединение
+/// закрывается. Подписка не ограничивается временем выполнения команды
+#[tokio::test]
+async fn server_command_timeout() {
+    let server = Server::builder()
+        .command_timeout(Duration::from_millis(100))
+        .bind("127.0.0.1:0")
+        .await
+        .unwrap();
+    let addr = server.local_addr();
+
+    // Значение больше буферов сокета, поэтому запись ответа клиенту,
+    // не читающему ответы, не может завершиться
+    let value = vec![b'x'; 32 * 1024 * 1024];
+    let mut client = Client::connect(addr).await.unwrap();
+    client.set("big", value.clone().into()).await.unwrap();
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream
+        .write_all(b"*2\r\n$3\r\nGET\r\n$3\r\nbig\r\n")
+        .await
+        .unwrap();
+    time::sleep(Duration::from_millis(500)).await;
+
+    // Соединение закрыто, ответ не отправлен полностью
+    let mut response = vec![];
+    stream.read_to_end(&mut response).await.unwrap();
+    assert!(response.len() < value.len());
+
+    // Соединения, уложившиеся во время, продолжают работать
+    assert_eq!(value.len(), client.get("big").await.unwrap().unwrap().len());
+
+    let mut subscriber = Client::connect(addr)
+        .await
+        .unwrap()
+        .subscribe(vec!["hello".into()])
+        .await
+        .unwrap();
+    time::sleep(Duration::from_millis(200)).await;
+
+    client.publish("hello", "world".into()).await.unwrap();
+    let message = subscriber.next_message().await.unwrap().unwrap();
+    assert_eq!(&message.content[..], b"world");
+}
+
+/// Перебор ключей под мьютексом БД не может быть прерван извне, поэтому
+/// `KEYS` и `SCAN` сами прекращают его по истечении времени и отвечают
+/// ошибкой, не закрывая соединение
+#[tokio::test]
+async fn server_command_timeout_cuts_keys_short() {
+    let server = Server::builder()
+        .command_timeout(Duration::from_millis(1))
+        .bind("127.0.0.1:0")
+        .await
+        .unwrap();
+
+    // Перебор такого количества ключей занимает намного больше миллисекунды
+    for i in 0..200_000 {
+        server.db().set(format!("key:{}", i), "value".into(), None);
+    }
+
+    let mut client = Client::connect(server.local_addr()).await.unwrap();
+
+    let err = client.keys("*:1*").await.unwrap_err();
+    let err = err.downcast_ref::<ServerError>().unwrap();
+    assert_eq!("ERR command timed out", err.message());
+
+    let err = client
+        .scan_page(0, None, Some(1_000_000))
+        .await
+        .unwrap_err();
+    let err = err.downcast_ref::<ServerError>().unwrap();
+    assert_eq!("ERR command timed out", err.message());
+
+    // Соединение остается открытым
+    assert_eq!(Some("value".into()), client.get("key:1").await.unwrap());
+}
+
+/// Соединения сверх лимита ожидают в очереди ограниченного размера не
+/// дольше заданного времени
+#[tokio::test]
+async fn server_admission_queue() {
+    let server = Server::builder()
+        .max_connections(1)
+        .max_queued_connections(1)
+        .admission_timeout(Duration::from_millis(200))
+        .bind("127.0.0.1:0")
+        .await
+        .unwrap();
+    let addr = server.local_addr();
+
+    let mut first = Client::connect(addr).await.unwrap();
+    first.ping(None).await.unwrap();
+
+    // Второе соединение ожидает в очереди, для третьего места нет
+    let mut second = Client::connect(addr).await.unwrap();
+    let mut third = Client::connect(addr).await.unwrap();
+
+    let err = third.ping(None).await.unwrap_err();
+    let err = err.downcast_ref::<ServerError>().unwrap();
+    assert_eq!("ERR max number of clients reached", err.message());
+
+    // Время ожидания второго соединения истекает
+    let err = second.ping(None).await.unwrap_err();
+    let err = err.downcast_ref::<ServerError>().unwrap();
+    assert_eq!("ERR max number of clients reached", err.message());
+
+    // Соединение, дождавшееся закрытия активного соединения, обслуживается
+    let mut fourth = Client::connect(addr).await.unwrap();
+    time::sleep(Duration::from_millis(50)).await;
+    drop(first);
+    fourth.ping(None).await.unwrap();
+}
+
+/// В строгом режиме соединение с невалидным кадром закрывается
+#[tokio::test]
+async fn server_strict_framing() {
+    let server = Server::builder()
+        .strict_framing(true)
+        .bind("127.0.0.1:0")
+        .await
+        .unwrap();
+
+    let mut stream = TcpStream::connect(server.local_addr()).await.unwrap();
+
+    stream.write_all(b"*1\r\n$4\r\nPING\r\n").await.unwrap();
+    let mut response = [0; 7];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"+PONG\r\n", &response);
+
+    // Объемная строка не завершается `\r\n`
+    stream.write_all(b"*1\r\n$4\r\nPINGXY").await.unwrap();
+    let mut response = vec![];
+    stream.read_to_end(&mut response).await.unwrap();
+    assert!(response.is_empty());
+}
+
+/// `DEBUG SET-ACTIVE-EXPIRE` управляет фоновой очисткой истекших ключей
+#[tokio::test]
+async fn debug_set_active_expire() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    let debug = |enabled: &str| {
+        Frame::Array(vec![
+            Frame::Bulk("DEBUG".into()),
+            Frame::Bulk("SET-ACTIVE-EXPIRE".into()),
+            Frame::Bulk(enabled.to_string().into()),
+        ])
+    };
+
+    assert_eq!(
+        Frame::Simple("OK".into()),
+        client.send_frame(debug("0")).await.unwrap()
+    );
+
+    client
+        .set_expires("hello", "world".into(), Duration::from_millis(10))
+        .await
+        .unwrap();
+    time::sleep(Duration::from_millis(50)).await;
+    assert!(client.get("hello").await.unwrap().is_none());
+
+    assert_eq!(
+        Frame::Simple("OK".into()),
+        client.send_frame(debug("1")).await.unwrap()
+    );
+}
+
+/// Создает кадр команды `DEBUG CHAOS`
+fn debug_chaos(args: &[&str]) -> Frame {
+    let mut parts = vec![Frame::Bulk("DEBUG".into()), Frame::Bulk("CHAOS".into())];
+    parts.extend(args.iter().map(|arg| Frame::Bulk(arg.to_string().into())));
+    Frame::Array(parts)
+}
+
+/// `DEBUG CHAOS DROP` разрывает соединения вместо выполнения команд, но не
+/// затрагивает команды `DEBUG`
+#[tokio::test]
+async fn chaos_drop_connections() {
+    let server = TestServer::start().await;
+    let mut control = server.client().await;
+
+    assert_eq!(
+        Frame::Simple("OK".into()),
+        control
+            .send_frame(debug_chaos(&["DROP", "100"]))
+            .await
+            .unwrap()
+    );
+
+    let mut client = server.client().await;
+    assert!(client.get("hello").await.is_err());
+
+    assert_eq!(
+        Frame::Simple("OK".into()),
+        control.send_frame(debug_chaos(&["OFF"])).await.unwrap()
+    );
+
+    let mut client = server.client().await;
+    assert!(client.get("hello").await.unwrap().is_none());
+}
+
+/// `DEBUG CHAOS LAG` теряет сообщения подписчиков
+#[tokio::test]
+async fn chaos_lagged_subscriber() {
+    let server = TestServer::start().await;
+    let mut control = server.client().await;
+    let mut subscriber = server
+        .client()
+        .await
+        .subscribe(vec!["hello".into()])
+        .await
+        .unwrap();
+
+    control
+        .send_frame(debug_chaos(&["LAG", "100"]))
+        .await
+        .unwrap();
+    assert_eq!(1, control.publish("hello", "lost".into()).await.unwrap());
+
+    control.send_frame(debug_chaos(&["OFF"])).await.unwrap();
+    control.publish("hello", "world".into()).await.unwrap();
+
+    let message = subscriber.next_message().await.unwrap().unwrap();
+    assert_eq!(&message.content[..], b"world");
+}
+
+/// Команды выполняются с задержкой `DEBUG CHAOS LATENCY`, невалидные
+/// настройки сбоев отклоняются
+#[tokio::test]
+async fn chaos_latency() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    assert_eq!(
+        Frame::Simple("OK".into()),
+        client
+            .send_frame(debug_chaos(&["LATENCY", "20"]))
+            .await
+            .unwrap()
+    );
+
+    let start = time::Instant::now();
+    client.set("hello", "world".into()).await.unwrap();
+    assert!(start.elapsed() < Duration::from_secs(1));
+
+    // Вероятность не может превышать 100%
+    assert!(client
+        .send_frame(debug_chaos(&["DROP", "101"]))
+        .await
+        .is_err());
+}
+
+/// Обработчик, установка соединения в котором всегда проваливается
+struct FailingListener {
+    attempts: Arc<AtomicUsize>,
+}
+
+impl Accept for FailingListener {
+    type Io = tokio::io::DuplexStream;
+
+    async fn accept(&mut self) -> io::Result<(Self::Io, SocketAddr)> {
+        self.attempts.fetch_add(1, Ordering::SeqCst);
+        Err(io::Error::other("слишком много открытых файлов"))
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        Ok("127.0.0.1:0".parse().unwrap())
+    }
+}
+
+/// `MEMORY STATS` возвращает пары "название - значение", а `MEMORY DOCTOR` -
+/// текстовый отчет
+#[tokio::test]
+async fn memory_stats_and_doctor() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    client.set("hello", "world".into()).await.unwrap();
+
+    let stats = client
+        .send_frame(Frame::Array(vec![
+            Frame::Bulk("MEMORY".into()),
+            Frame::Bulk("STATS".into()),
+        ]))
+        .await
+        .unwrap();
+
+    let fields = match stats {
+        Frame::Array(fields) => fields,
+        frame => panic!("ожидается массив, получено {:?}", frame),
+    };
+    assert_eq!(0, fields.len() % 2);
+    assert_eq!(Frame::Bulk("keys.count".into()), fields[0]);
+    assert_eq!(Frame::Integer(1), fields[1]);
+    assert!(fields.contains(&Frame::Bulk("dataset.bytes".into())));
+
+    let report = client
+        .send_frame(Frame::Array(vec![
+            Frame::Bulk("MEMORY".into()),
+            Frame::Bulk("DOCTOR".into()),
+        ]))
+        .await
+        .unwrap();
+    assert!(matches!(report, Frame::Bulk(report) if !report.is_empty()));
+}
+
+/// `THROTTLE` возвращает результат проверки ограничения
+#[tokio::test]
+async fn throttle() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    let throttle = |args: &[&str]| {
+        let mut parts = vec![Frame::Bulk("THROTTLE".into())];
+        parts.extend(args.iter().map(|arg| Frame::Bulk(arg.to_string().into())));
+        Frame::Array(parts)
+    };
+
+    let res = client
+        .send_frame(throttle(&["limit", "1", "1", "60"]))
+        .await
+        .unwrap();
+    assert_eq!(
+        Frame::Array(vec![
+            Frame::Integer(0),
+            Frame::Integer(2),
+            Frame::Integer(1),
+            Frame::Integer(0),
+            Frame::Integer(60),
+        ]),
+        res
+    );
+
+    client
+        .send_frame(throttle(&["limit", "1", "1", "60"]))
+        .await
+        .unwrap();
+
+    let res = client
+        .send_frame(throttle(&["limit", "1", "1", "60"]))
+        .await
+        .unwrap();
+    assert_eq!(
+        Frame::Array(vec![
+            Frame::Integer(1),
+            Frame::Integer(2),
+            Frame::Integer(0),
+            Frame::Integer(60),
+            Frame::Integer(120),
+        ]),
+        res
+    );
+
+    let err = client
+        .send_frame(throttle(&["limit", "1", "1", "60", "3"]))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("maximum burst"), "{}", err);
+
+    client.set("hello", "world".into()).await.unwrap();
+    let err = client
+        .send_frame(throttle(&["hello", "1", "1", "60"]))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().starts_with("WRONGTYPE"), "{}", err);
+
+    // Целое число тоже не является состоянием ограничения
+    client.set("counter", "42".into()).await.unwrap();
+    let err = client
+        .send_frame(throttle(&["counter", "1", "1", "60"]))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().starts_with("WRONGTYPE"), "{}", err);
+    assert_eq!(Some("42".into()), client.get("counter").await.unwrap());
+}
+
+/// Создает кадр команды из ее названия и аргументов
+fn command(args: &[&str]) -> Frame {
+    Frame::Array(
+        args.iter()
+            .map(|arg| Frame::Bulk(arg.to_string().into()))
+            .collect(),
+    )
+}
+
+/// `EXPIRE` и `PEXPIRE` устанавливают время жизни, отрицательное время жизни
+/// удаляет ключ
+#[tokio::test]
+async fn expire_and_pexpire() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    client.set("hello", "world".into()).await.unwrap();
+
+    let res = client
+        .send_frame(command(&["EXPIRE", "hello", "100"]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Integer(1), res);
+    let ttl = server.db().ttl("hello").unwrap().unwrap();
+    assert!(ttl > Duration::from_secs(90) && ttl <= Duration::from_secs(100));
+
+    let res = client
+        .send_frame(command(&["PEXPIRE", "hello", "5000"]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Integer(1), res);
+    assert!(server.db().ttl("hello").unwrap().unwrap() <= Duration::from_secs(5));
+
+    let res = client
+        .send_frame(command(&["EXPIRE", "missing", "100"]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Integer(0), res);
+
+    let err = client
+        .send_frame(command(&["EXPIRE", "hello", &i64::MAX.to_string()]))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("invalid expire time"), "{}", err);
+
+    let res = client
+        .send_frame(command(&["EXPIRE", "hello", "-1"]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Integer(1), res);
+    assert!(server.db().get("hello").is_none());
+}
+
+/// `EXPIREAT` и `PEXPIREAT` принимают Unix-время
+#[tokio::test]
+async fn expireat_and_pexpireat() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    client.set("hello", "world".into()).await.unwrap();
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+
+    let at = (now + Duration::from_secs(100)).as_secs().to_string();
+    let res = client
+        .send_frame(command(&["EXPIREAT", "hello", &at]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Integer(1), res);
+    let ttl = server.db().ttl("hello").unwrap().unwrap();
+    assert!(ttl > Duration::from_secs(90) && ttl <= Duration::from_secs(100));
+
+    let at = (now + Duration::from_secs(5)).as_millis().to_string();
+    let res = client
+        .send_frame(command(&["PEXPIREAT", "hello", &at]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Integer(1), res);
+    assert!(server.db().ttl("hello").unwrap().unwrap() <= Duration::from_secs(5));
+
+    let res = client
+        .send_frame(command(&["EXPIREAT", "missing", &at]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Integer(0), res);
+
+    let err = client
+        .send_frame(command(&["EXPIREAT", "hello", &i64::MAX.to_string()]))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("invalid expire time"), "{}", err);
+
+    // Момент в прошлом удаляет ключ
+    let res = client
+        .send_frame(command(&["PEXPIREAT", "hello", "1"]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Integer(1), res);
+    assert!(server.db().get("hello").is_none());
+}
+
+/// `EXPIRETIME` и `PEXPIRETIME` возвращают Unix-время истечения ключа
+#[tokio::test]
+async fn expiretime_and_pexpiretime() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    client.set("hello", "world".into()).await.unwrap();
+
+    let res = client
+        .send_frame(command(&["EXPIRETIME", "hello"]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Integer(-1), res);
+
+    let res = client
+        .send_frame(command(&["PEXPIRETIME", "missing"]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Integer(-2), res);
+
+    let at = SystemTime::now().duration_since(UNIX_EPOCH).unwrap() + Duration::from_secs(100);
+    let at = at.as_millis() as i64;
+    client
+        .send_frame(command(&["PEXPIREAT", "hello", &at.to_string()]))
+        .await
+        .unwrap();
+
+    let res = client
+        .send_frame(command(&["PEXPIRETIME", "hello"]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Integer(at), res);
+
+    let res = client
+        .send_frame(command(&["EXPIRETIME", "hello"]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Integer((at + 500) / 1000), res);
+}
+
+/// Настройки `SET` можно комбинировать, а противоречащие друг другу
+/// настройки отклоняются
+#[tokio::test]
+async fn set_options() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    let res = client
+        .send_frame(command(&["SET", "hello", "world", "NX", "EX", "100"]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Simple("OK".into()), res);
+
+    let res = client
+        .send_frame(command(&["SET", "hello", "other", "NX"]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Null, res);
+
+    let res = client
+        .send_frame(command(&["SET", "hello", "new", "XX", "KEEPTTL", "GET"]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Bulk("world".into()), res);
+    assert_eq!(Some("new".into()), server.db().get("hello"));
+    assert!(server.db().ttl("hello").unwrap().is_some());
+
+    // `GET` не заменяет значение, не являющееся строкой
+    server
+        .db()
+        .hset("user", vec![("name".to_string(), "Alice".into())])
+        .unwrap();
+    let err = client
+        .send_frame(command(&["SET", "user", "v", "GET"]))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().starts_with("WRONGTYPE"), "{}", err);
+    assert_eq!(
+        Some("Alice".into()),
+        server.db().hget("user", "name").unwrap()
+    );
+
+    // Ошибка разбора закрывает соединение
+    for args in [
+        &["SET", "hello", "world", "NX", "XX"][..],
+        &["SET", "hello", "world", "EX", "1", "KEEPTTL"],
+        &["SET", "hello", "world", "FOO"],
+    ] {
+        let mut client = server.client().await;
+        assert!(client.send_frame(command(args)).await.is_err());
+    }
+    assert_eq!(Some("new".into()), server.db().get("hello"));
+}
+
+/// `GETRANGE` и `SUBSTR` возвращают часть строки
+#[tokio::test]
+async fn getrange_and_substr() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    client.set("hello", "Hello, world".into()).await.unwrap();
+
+    let res = client
+        .send_frame(command(&["GETRANGE", "hello", "0", "4"]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Bulk("Hello".into()), res);
+
+    let res = client
+        .send_frame(command(&["SUBSTR", "hello", "-5", "-1"]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Bulk("world".into()), res);
+
+    let res = client
+        .send_frame(command(&["GETRANGE", "missing", "0", "-1"]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Bulk("".into()), res);
+}
+
+/// `SETRANGE` возвращает новую длину строки и отклоняет отрицательное
+/// смещение
+#[tokio::test]
+async fn setrange() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    client.set("hello", "Hello World".into()).await.unwrap();
+
+    let res = client
+        .send_frame(command(&["SETRANGE", "hello", "6", "Redis"]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Integer(11), res);
+    assert_eq!(Some("Hello Redis".into()), server.db().get("hello"));
+
+    let err = client
+        .send_frame(command(&["SETRANGE", "hello", "-1", "x"]))
+        .await
+        .unwrap_err();
+    assert_eq!("ERR offset is out of range", err.to_string());
+}
+
+/// `INCRBYFLOAT` возвращает новое значение строкой и отклоняет невалидные
+/// приращения
+#[tokio::test]
+async fn incrbyfloat() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    client.set("price", "10.50".into()).await.unwrap();
+
+    let res = client
+        .send_frame(command(&["INCRBYFLOAT", "price", "0.1"]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Bulk("10.6".into()), res);
+
+    let res = client
+        .send_frame(command(&["INCRBYFLOAT", "price", "-5.6"]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Bulk("5".into()), res);
+
+    assert_eq!(5.5, client.incr_by_float("price", 0.5).await.unwrap());
+
+    let err = client
+        .send_frame(command(&["INCRBYFLOAT", "price", "inf"]))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("NaN or Infinity"), "{}", err);
+
+    // Невалидное приращение является ошибкой протокола
+    assert!(client
+        .send_frame(command(&["INCRBYFLOAT", "price", "nan"]))
+        .await
+        .is_err());
+
+    assert_eq!(Some("5.5".into()), server.db().get("price"));
+}
+
+/// `TTL` и `PTTL` возвращают `-1` для ключей без времени жизни и `-2` для
+/// отсутствующих ключей
+#[tokio::test]
+async fn ttl_and_pttl() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    client.set("hello", "world".into()).await.unwrap();
+    client
+        .set_expires("session", "data".into(), Duration::from_millis(100_400))
+        .await
+        .unwrap();
+
+    for cmd in ["TTL", "PTTL"] {
+        let res = client.send_frame(command(&[cmd, "hello"])).await.unwrap();
+        assert_eq!(Frame::Integer(-1), res);
+
+        let res = client.send_frame(command(&[cmd, "missing"])).await.unwrap();
+        assert_eq!(Frame::Integer(-2), res);
+    }
+
+    let res = client
+        .send_frame(command(&["TTL", "session"]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Integer(100), res);
+
+    match client
+        .send_frame(command(&["PTTL", "session"]))
+        .await
+        .unwrap()
+    {
+        Frame::Integer(ms) => assert!(ms > 99_000 && ms <= 100_400, "{}", ms),
+        frame => panic!("{:?}", frame),
+    }
+}
+
+fn config(args: &[&str]) -> Frame {
+    let mut parts = vec![Frame::Bulk("CONFIG".into())];
+    parts.extend(args.iter().map(|arg| Frame::Bulk(arg.to_string().into())));
+    Frame::Array(parts)
+}
+
+/// `CONFIG SET loglevel` заменяет фильтр логов, переданный серверу
+#[tokio::test]
+async fn config_set_loglevel() {
+    // Подписчик не устанавливается глобально, но должен существовать, пока
+    // фильтр изменяется
+    let (layer, handle) = reload::Layer::new(EnvFilter::new("info"));
+    let _subscriber = tracing_subscriber::registry().with(layer);
+
+    let server = Server::builder()
+        .log_filter(LogFilter::new(handle))
+        .bind("127.0.0.1:0")
+        .await
+        .unwrap();
+    let mut client = Client::connect(server.local_addr()).await.unwrap();
+
+    assert_eq!(
+        Frame::Array(vec![
+            Frame::Bulk("loglevel".into()),
+            Frame::Bulk("info".into())
+        ]),
+        client
+            .send_frame(config(&["GET", "loglevel"]))
+            .await
+            .unwrap()
+    );
+
+    assert_eq!(
+        Frame::Simple("OK".into()),
+        client
+            .send_frame(config(&["SET", "loglevel", "mini_redis=debug"]))
+            .await
+            .unwrap()
+    );
+    assert_eq!(
+        Frame::Array(vec![
+            Frame::Bulk("loglevel".into()),
+            Frame::Bulk("mini_redis=debug".into())
+        ]),
+        client
+            .send_frame(config(&["GET", "loglevel"]))
+            .await
+            .unwrap()
+    );
+
+    // Невалидные директивы не изменяют фильтр
+    assert!(client
+        .send_frame(config(&["SET", "loglevel", "mini_redis=loud"]))
+        .await
+        .is_err());
+    assert!(client
+        .send_frame(config(&["SET", "maxmemory", "100"]))
+        .await
+        .is_err());
+    assert_eq!(
+        Frame::Array(vec![
+            Frame::Bulk("loglevel".into()),
+            Frame::Bulk("mini_redis=debug".into())
+        ]),
+        client
+            .send_frame(config(&["GET", "loglevel"]))
+            .await
+            .unwrap()
+    );
+
+    // Сервер без фильтра не поддерживает изменение уровня логирования
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+    assert!(client
+        .send_frame(config(&["SET", "loglevel", "debug"]))
+        .await
+        .is_err());
+    assert_eq!(
+        Frame::Array(vec![]),
+        client
+            .send_frame(config(&["GET", "loglevel"]))
+            .await
+            .unwrap()
+    );
+}
+
+/// Сервер закрывается после исчерпания повторных попыток установить
+/// соединение
+#[tokio::test(start_paused = true)]
+async fn accept_retry_gives_up() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let listener = FailingListener {
+        attempts: attempts.clone(),
+    };
+
+    let retry = AcceptRetry::new()
+        .initial_delay(Duration::from_secs(1))
+        .multiplier(3)
+        .max_delay(Duration::from_secs(5))
+        .max_retries(3);
+
+    let start = time::Instant::now();
+    Builder::new()
+        .accept_retry(retry)
+        .run(listener, std::future::pending::<()>())
+        .await;
+
+    // Задержки: 1, 3 и 5 (вместо 9) секунд
+    assert_eq!(4, attempts.load(Ordering::SeqCst));
+    assert_eq!(Duration::from_secs(9), start.elapsed());
+}
+
+/// Сервер может повторять попытки бесконечно со случайной задержкой
+#[tokio::test(start_paused = true)]
+async fn accept_retry_forever_with_jitter() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let listener = FailingListener {
+        attempts: attempts.clone(),
+    };
+
+    let retry = AcceptRetry::new()
+        .max_delay(Duration::from_secs(10))
+        .retry_forever()
+        .jitter(true);
+
+    let run = Builder::new()
+        .accept_retry(retry)
+        .run(listener, std::future::pending::<()>());
+    assert!(time::timeout(Duration::from_secs(1000), run).await.is_err());
+
+    // Задержка не превышает 10 секунд и не меньше 5 секунд
+    let attempts = attempts.load(Ordering::SeqCst);
+    assert!((100..=210).contains(&attempts), "{}", attempts);
+}
+
+/// При закрытии сервера клиенты получают ошибку `SHUTDOWN`, а не сброс
+/// соединения
+#[tokio::test]
+async fn shutdown_notifies_clients() {
+    let server = Server::builder().bind("127.0.0.1:0").await.unwrap();
+    let addr = server.local_addr();
+
+    let mut client = Client::connect(addr).await.unwrap();
+    client.ping(None).await.unwrap();
+
+    let mut subscriber = Client::connect(addr)
+        .await
+        .unwrap()
+        .subscribe(vec!["hello".into()])
+        .await
+        .unwrap();
+
+    server.shutdown();
+    server.join().await.unwrap();
+
+    let err = subscriber.next_message().await.unwrap_err();
+    let err = err.downcast_ref::<ServerError>().unwrap();
+    assert_eq!(&ErrorKind::Shutdown, err.kind());
+    assert_eq!("SHUTDOWN server is going away", err.message());
+
+    // Уведомление прочитано вместо ответа на команду
+    let err = client.ping(None).await.unwrap_err();
+    let err = err.downcast_ref::<ServerError>().unwrap();
+    assert_eq!(&ErrorKind::Shutdown, err.kind());
+}
+
+/// Промежуточный слой, считающий соединения и отклоняющий соединения с
+/// портов из списка
+#[derive(Clone)]
+struct CountConnections {
+    inner: ConnectionService,
+    accepted: Arc<AtomicUsize>,
+    blocked_port: u16,
+}
+
+impl Service<(TcpStream, SocketAddr)> for CountConnections {
+    type Response = ();
+    type Error = mini_redis::Error;
+    type Future = <ConnectionService as Service<(TcpStream, SocketAddr)>>::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<mini_redis::Result<()>> {
+        <ConnectionService as Service<(TcpStream, SocketAddr)>>::poll_ready(&mut self.inner, cx)
+    }
+
+    fn call(&mut self, (socket, peer): (TcpStream, SocketAddr)) -> Self::Future {
+        if peer.port() == self.blocked_port {
+            return Box::pin(async { Err("соединение отклонено".into()) });
+        }
+
+        self.accepted.fetch_add(1, Ordering::SeqCst);
+        self.inner.call((socket, peer))
+    }
+}
+
+/// Сервис соединений работает в собственном цикле приема соединений и
+/// оборачивается промежуточным слоем
+#[tokio::test]
+async fn connection_service_with_middleware() {
     let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
     let addr = listener.local_addr().unwrap();
 
-    tokio::spawn(async move { server::run(listener, tokio::signal::ctrl_c()).await });
+    let inner = Builder::new().into_service();
+    let db = inner.db().clone();
+    let accepted = Arc::new(AtomicUsize::new(0));
+
+    // Порт первого клиента становится известен после подключения, поэтому
+    // сначала подключаем клиента, а затем запускаем цикл
+    let blocked = TcpStream::connect(addr).await.unwrap();
+    let mut service = CountConnections {
+        inner: inner.clone(),
+        accepted: accepted.clone(),
+        blocked_port: blocked.local_addr().unwrap().port(),
+    };
+
+    tokio::spawn(async move {
+        loop {
+            let (socket, peer) = listener.accept().await.unwrap();
+            poll_fn(|cx| service.poll_ready(cx)).await.unwrap();
+            tokio::spawn(service.call((socket, peer)));
+        }
+    });
+
+    // Отклоненное соединение закрывается без ответа
+    let mut blocked = Client::new(blocked);
+    assert!(blocked.ping(None).await.is_err());
+
+    let mut client = Client::connect(addr).await.unwrap();
+    client.set("hello", "world".into()).await.unwrap();
+    assert_eq!(db.get("hello").unwrap(), "world");
+    assert_eq!(1, accepted.load(Ordering::SeqCst));
+
+    // Закрытие сервиса уведомляет активные соединения
+    inner.shutdown();
+    let err = client.get("hello").await.unwrap_err();
+    let err = err.downcast_ref::<ServerError>().unwrap();
+    assert_eq!(&ErrorKind::Shutdown, err.kind());
+}
+
+/// `RESTORE` с настройками `ABSTTL` и `REPLACE` и ошибки времени жизни
+#[tokio::test]
+async fn restore_options() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
 
-    addr
+    server.db().set("hello".to_string(), "world".into(), None);
+    let payload = match client
+        .send_frame(command(&["DUMP", "hello"]))
+        .await
+        .unwrap()
+    {
+        Frame::Bulk(payload) => payload,
+        frame => panic!("{:?}", frame),
+    };
+
+    // Сериализованное значение передается как есть, а не строкой
+    let restore = |args: &[&str], options: &[&str]| {
+        let mut frames: Vec<Frame> = args
+            .iter()
+            .map(|arg| Frame::Bulk(arg.to_string().into()))
+            .collect();
+        frames.push(Frame::Bulk(payload.clone()));
+        frames.extend(
+            options
+                .iter()
+                .map(|arg| Frame::Bulk(arg.to_string().into())),
+        );
+        Frame::Array(frames)
+    };
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis()
+        + 100_000;
+    let res = client
+        .send_frame(restore(
+            &["RESTORE", "copy", &timestamp.to_string()],
+            &["ABSTTL"],
+        ))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Simple("OK".into()), res);
+
+    let res = client
+        .send_frame(command(&["PEXPIRETIME", "copy"]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Integer(timestamp as i64), res);
+
+    // Без `REPLACE` существующий ключ не заменяется
+    let err = client
+        .send_frame(restore(&["RESTORE", "copy", "0"], &[]))
+        .await
+        .unwrap_err();
+    assert_eq!("BUSYKEY Target key name already exists.", err.to_string());
+
+    let res = client
+        .send_frame(restore(&["RESTORE", "copy", "0"], &["REPLACE"]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Simple("OK".into()), res);
+    assert_eq!(Some(None), server.db().ttl("copy"));
+
+    let err = client
+        .send_frame(restore(&["RESTORE", "other", "-1"], &[]))
+        .await
+        .unwrap_err();
+    assert_eq!("ERR Invalid TTL value, must be >= 0", err.to_string());
+
+    let err = client
+        .send_frame(command(&["RESTORE", "other", "0", "garbage"]))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        "ERR DUMP payload version or checksum are wrong",
+        err.to_string()
+    );
+    assert!(server.db().get("other").is_none());
+}
+
+/// `HSET` с несколькими полями, `HDEL` и `HGETALL`
+#[tokio::test]
+async fn hset_hdel_hgetall() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    let res = client
+        .send_frame(command(&["HSET", "user", "name", "Alice", "age", "30"]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Integer(2), res);
+
+    let res = client
+        .send_frame(command(&["HGETALL", "user"]))
+        .await
+        .unwrap();
+    assert_eq!(
+        Frame::Array(vec![
+            Frame::Bulk("age".into()),
+            Frame::Bulk("30".into()),
+            Frame::Bulk("name".into()),
+            Frame::Bulk("Alice".into()),
+        ]),
+        res
+    );
+
+    let res = client
+        .send_frame(command(&["HDEL", "user", "age", "missing"]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Integer(1), res);
+
+    let res = client
+        .send_frame(command(&["HDEL", "user", "name"]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Integer(1), res);
+    assert_eq!(0, server.db().exists(&["user"]));
+
+    let res = client
+        .send_frame(command(&["HGETALL", "user"]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Array(vec![]), res);
+
+    // Нечетное количество полей и значений закрывает соединение
+    assert!(client
+        .send_frame(command(&["HSET", "user", "name"]))
+        .await
+        .is_err());
+    assert!(server.db().hgetall("user").unwrap().is_empty());
+}
+
+/// `HRANDFIELD` без количества возвращает одно поле или `nil`
+#[tokio::test]
+async fn hrandfield_single() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    server
+        .db()
+        .hset("user", vec![("name".to_string(), "Alice".into())])
+        .unwrap();
+
+    let res = client
+        .send_frame(command(&["HRANDFIELD", "user"]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Bulk("name".into()), res);
+
+    let res = client
+        .send_frame(command(&["HRANDFIELD", "missing"]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Null, res);
+
+    let res = client
+        .send_frame(command(&["HRANDFIELD", "user", "2", "WITHVALUES"]))
+        .await
+        .unwrap();
+    assert_eq!(
+        Frame::Array(vec![
+            Frame::Bulk("name".into()),
+            Frame::Bulk("Alice".into())
+        ]),
+        res
+    );
+
+    // Огромное отрицательное количество отклоняется без выделения памяти
+    for count in ["-4611686018427387903", "-1048577"] {
+        let err = client
+            .send_frame(command(&["HRANDFIELD", "user", count]))
+            .await
+            .unwrap_err();
+        assert_eq!("ERR value is out of range", err.to_string());
+    }
+    let res = client
+        .send_frame(command(&["HRANDFIELD", "user", "-3"]))
+        .await
+        .unwrap();
+    assert!(matches!(res, Frame::Array(fields) if fields.len() == 3));
+
+    // `WITHVALUES` без количества закрывает соединение
+    assert!(client
+        .send_frame(command(&["HRANDFIELD", "user", "WITHVALUES"]))
+        .await
+        .is_err());
+}
+
+/// Ответ `HSCAN` на отсутствующий ключ и на строку
+#[tokio::test]
+async fn hscan_reply() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    let res = client
+        .send_frame(command(&["HSCAN", "missing", "0"]))
+        .await
+        .unwrap();
+    assert_eq!(
+        Frame::Array(vec![Frame::Bulk("0".into()), Frame::Array(vec![])]),
+        res
+    );
+
+    client.set("string", "value".into()).await.unwrap();
+    let err = client
+        .send_frame(command(&["HSCAN", "string", "0", "COUNT", "5"]))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().starts_with("WRONGTYPE"), "{}", err);
+
+    // Неизвестная настройка закрывает соединение
+    assert!(client
+        .send_frame(command(&["HSCAN", "missing", "0", "LIMIT", "5"]))
+        .await
+        .is_err());
+}
+
+/// Настройки `ZADD` и формат оценок в ответах
+#[tokio::test]
+async fn zadd_options_and_scores() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    let res = client
+        .send_frame(command(&[
+            "ZADD", "board", "1.5", "a", "+inf", "b", "-0", "c",
+        ]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Integer(3), res);
+
+    let res = client
+        .send_frame(command(&["ZRANGE", "board", "0", "-1", "WITHSCORES"]))
+        .await
+        .unwrap();
+    assert_eq!(
+        Frame::Array(vec![
+            Frame::Bulk("c".into()),
+            Frame::Bulk("0".into()),
+            Frame::Bulk("a".into()),
+            Frame::Bulk("1.5".into()),
+            Frame::Bulk("b".into()),
+            Frame::Bulk("inf".into()),
+        ]),
+        res
+    );
+
+    let res = client
+        .send_frame(command(&[
+            "ZADD", "board", "xx", "ch", "gt", "3", "a", "1", "b", "1", "d",
+        ]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Integer(1), res);
+
+    let res = client
+        .send_frame(command(&["ZRANGE", "board", "0", "0", "rev"]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Array(vec![Frame::Bulk("b".into())]), res);
+
+    let res = client
+        .send_frame(command(&["ZSCORE", "board", "d"]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Null, res);
+
+    client.set("string", "value".into()).await.unwrap();
+    let err = client
+        .send_frame(command(&["ZADD", "string", "1", "a"]))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().starts_with("WRONGTYPE"), "{}", err);
+
+    // Несовместимые настройки закрывают соединение
+    assert!(client
+        .send_frame(command(&["ZADD", "board", "NX", "GT", "1", "a"]))
+        .await
+        .is_err());
+
+    let mut client = server.client().await;
+    assert!(client
+        .send_frame(command(&["ZADD", "board", "nan", "a"]))
+        .await
+        .is_err());
+}
+
+/// Синтаксис границ и `LIMIT` в `ZRANGEBYSCORE`
+#[tokio::test]
+async fn zrangebyscore_syntax() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    client
+        .send_frame(command(&[
+            "ZADD", "z", "1", "a", "2", "b", "3", "c", "4", "d",
+        ]))
+        .await
+        .unwrap();
+
+    let res = client
+        .send_frame(command(&[
+            "ZRANGEBYSCORE",
+            "z",
+            "(1",
+            "+inf",
+            "WITHSCORES",
+            "LIMIT",
+            "1",
+            "2",
+        ]))
+        .await
+        .unwrap();
+    assert_eq!(
+        Frame::Array(vec![
+            Frame::Bulk("c".into()),
+            Frame::Bulk("3".into()),
+            Frame::Bulk("d".into()),
+            Frame::Bulk("4".into()),
+        ]),
+        res
+    );
+
+    // Отрицательное количество снимает ограничение, отрицательное смещение
+    // дает пустой ответ
+    let res = client
+        .send_frame(command(&[
+            "ZRANGEBYSCORE",
+            "z",
+            "-inf",
+            "(3",
+            "LIMIT",
+            "0",
+            "-1",
+        ]))
+        .await
+        .unwrap();
+    assert_eq!(
+        Frame::Array(vec![Frame::Bulk("a".into()), Frame::Bulk("b".into())]),
+        res
+    );
+    let res = client
+        .send_frame(command(&[
+            "ZRANGEBYSCORE",
+            "z",
+            "-inf",
+            "+inf",
+            "LIMIT",
+            "-1",
+            "1",
+        ]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Array(vec![]), res);
+
+    let res = client
+        .send_frame(command(&["ZINCRBY", "z", "0.5", "a"]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Bulk("1.5".into()), res);
+
+    let res = client
+        .send_frame(command(&["ZRANK", "z", "missing"]))
+        .await
+        .unwrap();
+    assert_eq!(Frame::Null, res);
+
+    // Невалидная граница закрывает соединение
+    assert!(client
+        .send_frame(command(&["ZCOUNT", "z", "(x", "+inf"]))
+        .await
+        .is_err());
+}
+
+/// Синтаксис границ `ZRANGEBYLEX`
+#[tokio::test]
+async fn zrangebylex_syntax() {
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    client
+        .send_frame(command(&[
+            "ZADD", "z", "0", "a", "0", "b", "0", "c", "0", "d",
+        ]))
+        .await
+        .unwrap();
+
+    let members = |names: &[&str]| {
+        Frame::Array(
+            names
+                .iter()
+                .map(|name| Frame::Bulk(name.to_string().into()))
+                .collect(),
+        )
+    };
+
+    let res = client
+        .send_frame(command(&["ZRANGEBYLEX", "z", "(a", "[c"]))
+        .await
+        .unwrap();
+    assert_eq!(members(&["b", "c"]), res);
+
+    let res = client
+        .send_frame(command(&["ZRANGEBYLEX", "z", "-", "+", "LIMIT", "1", "2"]))
+        .await
+        .unwrap();
+    assert_eq!(members(&["b", "c"]), res);
+
+    // `+` в качестве нижней и `-` в качестве верхней границы дают пустой
+    // диапазон
+    for (min, max) in [("+", "+"), ("-", "-"), ("+", "-")] {
+        let res = client
+            .send_frame(command(&["ZRANGEBYLEX", "z", min, max]))
+            .await
+            .unwrap();
+        assert_eq!(members(&[]), res);
+    }
+
+    // Граница без префикса закрывает соединение
+    assert!(client
+        .send_frame(command(&["ZRANGEBYLEX", "z", "a", "+"]))
+        .await
+        .is_err());
 }
@@ -0,0 +1,137 @@
+use mini_redis::server::Server;
+use mini_redis::test_util::TestServer;
+use mini_redis::ShardedClient;
+
+use bytes::Bytes;
+
+/// Ключи распределяются между серверами и читаются с того же сервера
+#[tokio::test]
+async fn keys_are_distributed() {
+    let servers = [TestServer::start().await, TestServer::start().await];
+    let client = ShardedClient::new(servers.iter().map(TestServer::addr).collect());
+
+    for i in 0..100 {
+        let key = format!("key:{}", i);
+        client.set(&key, i.to_string().into()).await.unwrap();
+    }
+
+    let mut on_first = 0;
+    for i in 0..100 {
+        let key = format!("key:{}", i);
+        let value = client.get(&key).await.unwrap().unwrap();
+        assert_eq!(i.to_string().as_bytes(), &value[..]);
+
+        // Ключ хранится только на сервере, которому он принадлежит
+        for server in &servers {
+            assert_eq!(
+                server.addr() == client.addr(&key),
+                server.db().get(&key).is_some()
+            );
+        }
+
+        if client.addr(&key) == servers[0].addr() {
+            on_first += 1;
+        }
+    }
+
+    assert!(
+        on_first > 0 && on_first < 100,
+        "на первом сервере {}",
+        on_first
+    );
+}
+
+/// `mget` собирает значения со всех серверов в порядке ключей
+#[tokio::test]
+async fn mget_across_shards() {
+    let servers = [
+        TestServer::start().await,
+        TestServer::start().await,
+        TestServer::start().await,
+    ];
+    let client = ShardedClient::new(servers.iter().map(TestServer::addr).collect());
+
+    let keys: Vec<String> = (0..50).map(|i| format!("key:{}", i)).collect();
+    for key in keys.iter().step_by(2) {
+        client.set(key, Bytes::from(key.clone())).await.unwrap();
+    }
+
+    let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+    let values = client.mget(&keys).await.unwrap();
+
+    assert_eq!(keys.len(), values.len());
+    for (i, (key, value)) in keys.iter().zip(values).enumerate() {
+        if i % 2 == 0 {
+            assert_eq!(key.as_bytes(), &value.unwrap()[..]);
+        } else {
+            assert!(value.is_none());
+        }
+    }
+
+    assert!(client.mget(&[]).await.unwrap().is_empty());
+}
+
+/// Клоны клиента работают конкурентно
+#[tokio::test]
+async fn concurrent_clones() {
+    let servers = [TestServer::start().await, TestServer::start().await];
+    let client = ShardedClient::new(servers.iter().map(TestServer::addr).collect());
+
+    let tasks: Vec<_> = (0..10)
+        .map(|i| {
+            let client = client.clone();
+            tokio::spawn(async move {
+                let key = format!("task:{}", i);
+                client.set(&key, "value".into()).await.unwrap();
+                client.get(&key).await.unwrap()
+            })
+        })
+        .collect();
+
+    for task in tasks {
+        assert_eq!(Some(Bytes::from("value")), task.await.unwrap());
+    }
+}
+
+/// Недоступность сервера приводит к ошибке только для его ключей
+#[tokio::test]
+async fn unavailable_shard() {
+    let server = TestServer::start().await;
+
+    // Адрес, на котором никто не принимает соединения
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let unavailable = listener.local_addr().unwrap();
+    drop(listener);
+
+    let client = ShardedClient::new(vec![server.addr(), unavailable]);
+
+    let keys: Vec<String> = (0..20).map(|i| format!("key:{}", i)).collect();
+    for key in &keys {
+        let res = client.set(key, "value".into()).await;
+        assert_eq!(client.addr(key) == server.addr(), res.is_ok());
+    }
+
+    let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+    assert!(client.mget(&keys).await.is_err());
+}
+
+/// Соединения, закрытые сервером за время простоя в пуле, не используются
+/// для команд
+#[tokio::test]
+async fn pool_discards_closed_connections() {
+    let server = Server::builder().bind("127.0.0.1:0").await.unwrap();
+    let addr = server.local_addr();
+    let client = ShardedClient::new(vec![addr]);
+
+    client.set("hello", "world".into()).await.unwrap();
+
+    // Перезапускаем сервер на том же адресе. Соединение в пуле получает
+    // ошибку `SHUTDOWN` и закрывается
+    server.shutdown();
+    server.join().await.unwrap();
+    let server = Server::builder().bind(addr).await.unwrap();
+
+    assert!(client.get("hello").await.unwrap().is_none());
+    client.set("hello", "again".into()).await.unwrap();
+    assert_eq!(Some(Bytes::from("again")), server.db().get("hello"));
+}
@@ -0,0 +1,56 @@
+use mini_redis::test_util::TestServer;
+use tokio::net::TcpStream;
+
+/// Данные, записанные клиентом сервера в текущем процессе, доступны через БД
+/// сервера, и наоборот
+#[tokio::test]
+async fn in_memory_server_db_inspection() {
+    let server = TestServer::in_memory();
+    let mut client = server.client().await;
+
+    client.set("hello", "world".into()).await.unwrap();
+    assert_eq!(server.db().get("hello").unwrap(), "world");
+
+    server.db().set("foo".into(), "bar".into(), None);
+    let value = client.get("foo").await.unwrap().unwrap();
+    assert_eq!(b"bar", &value[..]);
+}
+
+/// Каждый `TestServer` имеет собственную БД
+#[tokio::test]
+async fn servers_are_isolated() {
+    let first = TestServer::start().await;
+    let second = TestServer::start().await;
+
+    first
+        .client()
+        .await
+        .set("hello", "world".into())
+        .await
+        .unwrap();
+
+    assert!(second.client().await.get("hello").await.unwrap().is_none());
+    assert!(second.db().get("hello").is_none());
+}
+
+/// Уничтожение `TestServer` закрывает сервер
+#[tokio::test]
+async fn drop_shuts_down_server() {
+    let server = TestServer::start().await;
+    let addr = server.addr();
+    let mut client = server.client().await;
+    client.ping(None).await.unwrap();
+
+    drop(server);
+
+    // Дожидаемся завершения цикла приема соединений
+    let mut closed = false;
+    for _ in 0..100 {
+        if TcpStream::connect(addr).await.is_err() {
+            closed = true;
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert!(closed);
+}
@@ -0,0 +1,128 @@
+//! Проверка структуры span'ов сервера. Подписчик `tracing` устанавливается
+//! для текущего потока, поэтому тесты используют однопоточную среду выполнения.
+
+use mini_redis::test_util::TestServer;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::Subscriber;
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Записанные span'ы.
+#[derive(Default)]
+struct Spans {
+    /// Поля span'ов соединений.
+    connections: HashMap<Id, HashMap<String, String>>,
+
+    /// Span соединения, которому принадлежит каждый span `apply`.
+    commands: Vec<Option<Id>>,
+}
+
+/// Слой, записывающий span'ы соединений и команд.
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Spans>>);
+
+struct FieldVisitor<'a>(&'a mut HashMap<String, String>);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{:?}", value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+}
+
+impl<S> Layer<S> for Recorder
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut spans = self.0.lock().unwrap();
+
+        match attrs.metadata().name() {
+            "connection" => {
+                let mut fields = HashMap::new();
+                attrs.record(&mut FieldVisitor(&mut fields));
+                spans.connections.insert(id.clone(), fields);
+            }
+            "apply" => {
+                // Ищем ближайший span соединения среди предков
+                let span = ctx.span(id).unwrap();
+                let connection = span
+                    .scope()
+                    .skip(1)
+                    .find(|parent| parent.name() == "connection")
+                    .map(|parent| parent.id());
+                spans.commands.push(connection);
+            }
+            _ => {}
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, _ctx: Context<'_, S>) {
+        let mut spans = self.0.lock().unwrap();
+
+        if let Some(fields) = spans.connections.get_mut(id) {
+            values.record(&mut FieldVisitor(fields));
+        }
+    }
+}
+
+/// Span'ы команд вложены в span соединения, в котором указан адрес клиента.
+/// Команды разных соединений относятся к разным span'ам
+#[tokio::test(flavor = "current_thread")]
+async fn command_spans_are_children_of_connection_span() {
+    let recorder = Recorder::default();
+    let subscriber = tracing_subscriber::registry().with(recorder.clone());
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let server = TestServer::start().await;
+
+    let mut first = server.client().await;
+    let mut second = server.client().await;
+
+    first.set("hello", "world".into()).await.unwrap();
+    second.get("hello").await.unwrap();
+    first.get("hello").await.unwrap();
+
+    let spans = recorder.0.lock().unwrap();
+
+    assert_eq!(2, spans.connections.len());
+    for fields in spans.connections.values() {
+        assert!(fields.contains_key("id"));
+        assert!(fields["peer"].starts_with("127.0.0.1:"));
+    }
+
+    assert_eq!(3, spans.commands.len());
+    assert!(spans.commands.iter().all(Option::is_some));
+    assert_eq!(spans.commands[0], spans.commands[2]);
+    assert_ne!(spans.commands[0], spans.commands[1]);
+}
+
+/// Имя клиента, установленное командой `HELLO`, записывается в span соединения
+#[tokio::test(flavor = "current_thread")]
+async fn client_name_is_recorded() {
+    let recorder = Recorder::default();
+    let subscriber = tracing_subscriber::registry().with(recorder.clone());
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let server = TestServer::start().await;
+    let mut client = server.client().await;
+
+    client.hello(None, Some("worker-1")).await.unwrap();
+
+    let spans = recorder.0.lock().unwrap();
+    let fields = spans.connections.values().next().unwrap();
+    assert_eq!("worker-1", fields["client_name"]);
+}
@@ -0,0 +1,119 @@
+//! Тесты в симулированной сети `turmoil`. Время и доставка сообщений
+//! контролируются симуляцией, поэтому сценарии с задержками и разделением
+//! сети воспроизводятся детерминированно.
+
+use mini_redis::{server, Client};
+
+use std::net::{IpAddr, Ipv4Addr};
+use tokio::time::{self, Duration, Instant};
+use turmoil::net::{TcpListener, TcpStream};
+
+const PORT: u16 = 6379;
+
+/// Добавляет в симуляцию сервер `mini-redis` на хосте `server`.
+fn start_server(sim: &mut turmoil::Sim<'_>) {
+    sim.host("server", || async {
+        let listener = TcpListener::bind((IpAddr::from(Ipv4Addr::UNSPECIFIED), PORT)).await?;
+        server::run(listener, std::future::pending::<()>()).await;
+        Ok(())
+    });
+}
+
+async fn connect() -> Client {
+    Client::new(TcpStream::connect(("server", PORT)).await.unwrap())
+}
+
+/// Каждый запрос занимает как минимум две задержки доставки сообщения
+#[test]
+fn get_set_with_latency() {
+    let mut sim = turmoil::Builder::new()
+        .min_message_latency(Duration::from_millis(100))
+        .max_message_latency(Duration::from_millis(100))
+        .build();
+    start_server(&mut sim);
+
+    sim.client("client", async {
+        let mut client = connect().await;
+
+        let start = Instant::now();
+        client.set("hello", "world".into()).await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(200));
+
+        let value = client.get("hello").await.unwrap().unwrap();
+        assert_eq!(b"world", &value[..]);
+
+        Ok(())
+    });
+
+    sim.run().unwrap();
+}
+
+/// Во время разделения сети запросы не выполняются. После восстановления
+/// сети клиент подключается заново, а данные сервера сохраняются
+#[test]
+fn partition_and_repair() {
+    let mut sim = turmoil::Builder::new().build();
+    start_server(&mut sim);
+
+    sim.client("client", async {
+        let mut client = connect().await;
+        client.set("hello", "world".into()).await.unwrap();
+
+        turmoil::partition("client", "server");
+
+        let res = time::timeout(Duration::from_secs(1), client.get("hello")).await;
+        assert!(res.is_err());
+
+        turmoil::repair("client", "server");
+
+        let mut client = connect().await;
+        let value = client.get("hello").await.unwrap().unwrap();
+        assert_eq!(b"world", &value[..]);
+
+        Ok(())
+    });
+
+    sim.run().unwrap();
+}
+
+/// Сообщение, опубликованное во время задержки доставки подписчику,
+/// доставляется после восстановления связи
+#[test]
+fn pubsub_delivery_is_held() {
+    let mut sim = turmoil::Builder::new().build();
+    start_server(&mut sim);
+
+    sim.client("subscriber", async {
+        let mut subscriber = connect()
+            .await
+            .subscribe(vec!["news".into()])
+            .await
+            .unwrap();
+        let start = Instant::now();
+
+        let message = subscriber.next_message().await.unwrap().unwrap();
+        assert_eq!(b"hello", &message.content[..]);
+
+        // Сообщение удерживалось до восстановления связи
+        assert!(start.elapsed() >= Duration::from_secs(5));
+
+        Ok(())
+    });
+
+    sim.client("publisher", async {
+        // Ждем подписки
+        time::sleep(Duration::from_secs(1)).await;
+
+        turmoil::hold("server", "subscriber");
+
+        let mut client = connect().await;
+        assert_eq!(1, client.publish("news", "hello".into()).await.unwrap());
+
+        time::sleep(Duration::from_secs(5)).await;
+        turmoil::release("server", "subscriber");
+
+        Ok(())
+    });
+
+    sim.run().unwrap();
+}
@@ -0,0 +1,30 @@
+#![cfg(target_os = "linux")]
+
+use mini_redis::server::Server;
+use mini_redis::uring::UringListener;
+use mini_redis::Client;
+
+/// Сервер, принимающий соединения через `io_uring`, обрабатывает команды
+/// нескольких клиентов
+#[tokio::test]
+async fn serve_over_io_uring() {
+    let listener = UringListener::bind("127.0.0.1:0").await.unwrap();
+    let server = Server::builder().serve(listener).unwrap();
+    let addr = server.local_addr();
+
+    let mut first = Client::connect(addr).await.unwrap();
+    let mut second = Client::connect(addr).await.unwrap();
+
+    first.set("hello", "world".into()).await.unwrap();
+    assert_eq!(Some("world".into()), second.get("hello").await.unwrap());
+
+    // Значение больше буфера копирования
+    let large = vec![b'x'; 100 * 1024];
+    second.set("large", large.clone().into()).await.unwrap();
+    assert_eq!(Some(large.into()), first.get("large").await.unwrap());
+
+    drop(first);
+    drop(second);
+    server.shutdown();
+    server.join().await.unwrap();
+}
@@ -0,0 +1,72 @@
+use mini_redis::server::Server;
+use mini_redis::websocket::{self, WsListener};
+
+use futures_util::{SinkExt, StreamExt};
+use tokio_tungstenite::tungstenite::Message;
+
+/// Запускает сервер, принимающий соединения WebSocket, и возвращает его URL
+async fn start_server() -> (Server, String) {
+    let listener = WsListener::bind("127.0.0.1:0").await.unwrap();
+    let server = Server::builder().serve(listener).unwrap();
+    let url = format!("ws://{}", server.local_addr());
+
+    (server, url)
+}
+
+/// Клиент выполняет команды через соединение WebSocket
+#[tokio::test]
+async fn client_over_websocket() {
+    let (server, url) = start_server().await;
+    let mut client = websocket::connect(&url).await.unwrap();
+
+    client.set("hello", "world".into()).await.unwrap();
+    let value = client.get("hello").await.unwrap().unwrap();
+    assert_eq!(b"world", &value[..]);
+
+    assert_eq!(server.db().get("hello").unwrap(), "world");
+}
+
+/// Одно сообщение может содержать несколько кадров, а кадр может быть
+/// разделен между сообщениями
+#[tokio::test]
+async fn frames_across_messages() {
+    let (_server, url) = start_server().await;
+    let (mut ws, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+
+    let request =
+        "*3\r\n$3\r\nSET\r\n$5\r\nhello\r\n$5\r\nworld\r\n*2\r\n$3\r\nGET\r\n$5\r\nhello\r\n";
+    let (first, second) = request.split_at(20);
+    ws.send(Message::binary(first.as_bytes().to_vec()))
+        .await
+        .unwrap();
+    ws.send(Message::binary(second.as_bytes().to_vec()))
+        .await
+        .unwrap();
+
+    let mut response = vec![];
+    while response.len() < b"+OK\r\n$5\r\nworld\r\n".len() {
+        match ws.next().await.unwrap().unwrap() {
+            Message::Binary(data) => response.extend_from_slice(&data),
+            message => panic!("неожиданное сообщение {:?}", message),
+        }
+    }
+
+    assert_eq!(b"+OK\r\n$5\r\nworld\r\n", &response[..]);
+}
+
+/// Соединение, не выполнившее рукопожатие, не мешает приему других
+#[tokio::test]
+async fn failed_handshake() {
+    let (_server, url) = start_server().await;
+
+    let mut stream = tokio::net::TcpStream::connect(url.trim_start_matches("ws://"))
+        .await
+        .unwrap();
+    tokio::io::AsyncWriteExt::write_all(&mut stream, b"*1\r\n$4\r\nPING\r\n")
+        .await
+        .unwrap();
+    drop(stream);
+
+    let mut client = websocket::connect(&url).await.unwrap();
+    client.ping(None).await.unwrap();
+}